@@ -0,0 +1,226 @@
+// Authentication - who is calling, from the API key they present
+//
+// Keys are configured as `name=key` pairs, comma separated, in two secret settings (read
+// through optimus_common::secrets, so they can come from a file, Vault or AWS):
+// - API_KEYS: submitters. A request presenting one of these keys is that principal; a
+//   request presenting any other key is refused with 401. Requests without a key are
+//   anonymous. The name is also the tenant the key submits as.
+// - ADMIN_API_KEYS: operators. Required for /admin/* and for changing results (reviewer
+//   notes, score overrides). Admin keys are accepted wherever API keys are.
+//
+// A key is presented as X-Api-Key, `Authorization: Bearer <key>`, or as the password of
+// `Authorization: Basic` (so a browser can open /admin/ui). The principal recorded in audit
+// trails is the key's name, never the key. Only SHA-256 digests of keys are kept in memory.
+
+use axum::extract::{FromRequestParts, Request, State};
+use axum::http::request::Parts;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Json, Response};
+use base64::{engine::general_purpose, Engine as _};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::AppState;
+
+/// Who made a request
+#[derive(Debug, Clone, PartialEq)]
+pub struct Principal {
+    /// Name the key was configured under
+    pub name: String,
+    pub admin: bool,
+}
+
+/// Configured keys, by SHA-256 digest
+#[derive(Debug, Default)]
+pub struct KeyStore {
+    keys: HashMap<[u8; 32], Principal>,
+}
+
+impl KeyStore {
+    /// Load from API_KEYS and ADMIN_API_KEYS
+    pub fn from_env() -> Result<Self, String> {
+        let users = optimus_common::secrets::var("API_KEYS")?;
+        let admins = optimus_common::secrets::var("ADMIN_API_KEYS")?;
+        Self::parse(users.as_deref(), admins.as_deref())
+    }
+
+    pub fn parse(users: Option<&str>, admins: Option<&str>) -> Result<Self, String> {
+        let mut store = Self::default();
+        for (setting, list, admin) in [("API_KEYS", users, false), ("ADMIN_API_KEYS", admins, true)] {
+            for entry in list.unwrap_or("").split(',').map(str::trim).filter(|e| !e.is_empty()) {
+                let Some((name, key)) = entry.split_once('=').map(|(n, k)| (n.trim(), k.trim())) else {
+                    return Err(format!("{} entries must be name=key", setting));
+                };
+                if name.is_empty() || key.is_empty() {
+                    return Err(format!("{} entries must be name=key", setting));
+                }
+                // The name is also the tenant the key submits as
+                if let Err(message) = crate::retention::validate_tenant(name) {
+                    return Err(format!("{}: key name '{}': {}", setting, name, message));
+                }
+                let principal = Principal { name: name.to_string(), admin };
+                if store.keys.insert(digest(key), principal).is_some() {
+                    return Err(format!("{}: the key of '{}' is configured twice", setting, name));
+                }
+            }
+        }
+        Ok(store)
+    }
+
+    pub fn user_keys(&self) -> usize {
+        self.keys.values().filter(|p| !p.admin).count()
+    }
+
+    pub fn admin_keys(&self) -> usize {
+        self.keys.values().filter(|p| p.admin).count()
+    }
+
+    /// The request's principal: Ok(None) without a key, Err for a key that isn't configured
+    pub fn authenticate(&self, headers: &HeaderMap) -> Result<Option<Principal>, Refusal> {
+        match presented_key(headers) {
+            None => Ok(None),
+            Some(key) => self.keys.get(&digest(&key)).cloned().map(Some).ok_or(Refusal::UnknownKey),
+        }
+    }
+
+    /// The request's principal, which must be an operator
+    pub fn authenticate_admin(&self, headers: &HeaderMap) -> Result<Principal, Refusal> {
+        match self.authenticate(headers)? {
+            Some(principal) if principal.admin => Ok(principal),
+            Some(_) => Err(Refusal::NotAdmin),
+            None => Err(Refusal::MissingKey),
+        }
+    }
+
+    /// The request's principal, which must be an operator or the job's owner (see
+    /// JobMetadata::owner); anonymous jobs are only an operator's to change
+    pub fn authenticate_owner(&self, headers: &HeaderMap, owner: Option<&str>) -> Result<Principal, Refusal> {
+        match self.authenticate(headers)? {
+            Some(principal) if principal.admin || owner == Some(principal.name.as_str()) => Ok(principal),
+            Some(_) => Err(Refusal::NotOwner),
+            None => Err(Refusal::MissingKey),
+        }
+    }
+}
+
+fn digest(key: &str) -> [u8; 32] {
+    Sha256::digest(key.as_bytes()).into()
+}
+
+/// The key a request presents, if any
+fn presented_key(headers: &HeaderMap) -> Option<String> {
+    let non_empty = |key: &str| Some(key.trim()).filter(|k| !k.is_empty()).map(str::to_string);
+    if let Some(key) = headers.get("x-api-key").and_then(|v| v.to_str().ok()).and_then(non_empty) {
+        return Some(key);
+    }
+    let authorization = headers.get(header::AUTHORIZATION)?.to_str().ok()?.trim();
+    let (scheme, credentials) = authorization.split_once(' ')?;
+    if scheme.eq_ignore_ascii_case("bearer") {
+        non_empty(credentials)
+    } else if scheme.eq_ignore_ascii_case("basic") {
+        let decoded = general_purpose::STANDARD.decode(credentials.trim()).ok()?;
+        let decoded = String::from_utf8(decoded).ok()?;
+        non_empty(decoded.split_once(':').map_or(decoded.as_str(), |(_, password)| password))
+    } else {
+        None
+    }
+}
+
+/// Why a request was not let through
+#[derive(Debug, PartialEq)]
+pub enum Refusal {
+    MissingKey,
+    UnknownKey,
+    NotAdmin,
+    /// Neither an admin key nor the key that submitted the job
+    NotOwner,
+}
+
+impl IntoResponse for Refusal {
+    fn into_response(self) -> Response {
+        let (status, code, message) = match self {
+            Refusal::MissingKey => (StatusCode::UNAUTHORIZED, "UNAUTHORIZED", "An API key is required"),
+            Refusal::UnknownKey => (StatusCode::UNAUTHORIZED, "UNAUTHORIZED", "Unknown API key"),
+            Refusal::NotAdmin => (StatusCode::FORBIDDEN, "ADMIN_REQUIRED", "This API key is not an admin key"),
+            Refusal::NotOwner => (StatusCode::FORBIDDEN, "NOT_JOB_OWNER", "Only the API key that submitted the job, or an admin key, may do this"),
+        };
+        let body = serde_json::json!({ "error": { "code": code, "message": message } });
+        if status == StatusCode::UNAUTHORIZED {
+            (status, [(header::WWW_AUTHENTICATE, "Basic realm=\"optimus\"")], Json(body)).into_response()
+        } else {
+            (status, Json(body)).into_response()
+        }
+    }
+}
+
+/// Middleware: only operators get through (the /admin routes)
+pub async fn require_admin(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+    match state.keys.authenticate_admin(request.headers()) {
+        Ok(_) => next.run(request).await,
+        Err(refusal) => refusal.into_response(),
+    }
+}
+
+/// Extractor for handlers outside /admin that only operators may call
+pub struct Admin(pub Principal);
+
+#[axum::async_trait]
+impl FromRequestParts<Arc<AppState>> for Admin {
+    type Rejection = Refusal;
+
+    async fn from_request_parts(parts: &mut Parts, state: &Arc<AppState>) -> Result<Self, Self::Rejection> {
+        state.keys.authenticate_admin(&parts.headers).map(Admin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(name: &str, value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(axum::http::HeaderName::from_bytes(name.as_bytes()).unwrap(), value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_keys_identify_principals() {
+        let store = KeyStore::parse(Some("course=sk-1, lab=sk-2"), Some("ops=ak-1")).unwrap();
+        assert_eq!((store.user_keys(), store.admin_keys()), (2, 1));
+
+        let course = store.authenticate(&headers("x-api-key", "sk-1")).unwrap().unwrap();
+        assert_eq!(course, Principal { name: "course".to_string(), admin: false });
+        assert_eq!(store.authenticate(&HeaderMap::new()), Ok(None));
+        assert_eq!(store.authenticate(&headers("x-api-key", "sk-3")), Err(Refusal::UnknownKey));
+
+        // Bearer and Basic (password) carry the key too
+        assert_eq!(store.authenticate_admin(&headers("authorization", "Bearer ak-1")).unwrap().name, "ops");
+        let basic = format!("Basic {}", general_purpose::STANDARD.encode("anyone:ak-1"));
+        assert_eq!(store.authenticate_admin(&headers("authorization", &basic)).unwrap().name, "ops");
+
+        assert_eq!(store.authenticate_admin(&headers("x-api-key", "sk-2")), Err(Refusal::NotAdmin));
+        assert_eq!(store.authenticate_admin(&HeaderMap::new()), Err(Refusal::MissingKey));
+    }
+
+    #[test]
+    fn test_owner_or_admin() {
+        let store = KeyStore::parse(Some("course=sk-1, lab=sk-2"), Some("ops=ak-1")).unwrap();
+        assert_eq!(store.authenticate_owner(&headers("x-api-key", "sk-1"), Some("course")).unwrap().name, "course");
+        assert_eq!(store.authenticate_owner(&headers("x-api-key", "sk-2"), Some("course")), Err(Refusal::NotOwner));
+        assert_eq!(store.authenticate_owner(&headers("x-api-key", "ak-1"), Some("course")).unwrap().name, "ops");
+        // Anonymous jobs belong to nobody
+        assert_eq!(store.authenticate_owner(&headers("x-api-key", "sk-1"), None), Err(Refusal::NotOwner));
+        assert_eq!(store.authenticate_owner(&HeaderMap::new(), None), Err(Refusal::MissingKey));
+    }
+
+    #[test]
+    fn test_malformed_keys_are_rejected() {
+        assert!(KeyStore::parse(Some("sk-1"), None).is_err());
+        assert!(KeyStore::parse(Some("a=sk-1"), Some("b=sk-1")).is_err());
+        // Names double as tenant ids
+        assert!(KeyStore::parse(Some("my course=sk-1"), None).is_err());
+        assert_eq!(KeyStore::parse(None, None).unwrap().admin_keys(), 0);
+    }
+}
@@ -0,0 +1,141 @@
+// Canary report - how a candidate judge image compares with the primary image
+//
+// Workers shadow-run a sample of live jobs on the candidate image configured in
+// languages.json and record a comparison per job (see the worker's canary module).
+// This module turns those comparisons into a promotion report per candidate image.
+
+use optimus_common::types::{JobStatus, Language, ShadowComparison};
+use serde::Serialize;
+use uuid::Uuid;
+
+/// Disagreements listed in full per candidate
+const MAX_DISAGREEMENTS: usize = 20;
+
+#[derive(Debug, Serialize)]
+pub struct CanaryReport {
+    pub language: Language,
+    pub candidates: Vec<CandidateReport>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CandidateReport {
+    pub candidate_image: String,
+    pub sampled_jobs: usize,
+    pub agreed: usize,
+    pub disagreed: usize,
+    pub agreement_rate: f64,
+    pub avg_primary_time_ms: u64,
+    pub avg_candidate_time_ms: u64,
+    /// Most recent disagreements, newest first
+    pub disagreements: Vec<Disagreement>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Disagreement {
+    pub job_id: Uuid,
+    pub primary_status: JobStatus,
+    pub candidate_status: JobStatus,
+    pub primary_score: u32,
+    pub candidate_score: u32,
+    pub mismatched_tests: Vec<u32>,
+}
+
+/// Build the report from comparisons ordered newest first
+pub fn build_report(language: Language, comparisons: &[ShadowComparison]) -> CanaryReport {
+    let mut candidates: Vec<CandidateReport> = Vec::new();
+    let mut time_totals: Vec<(u64, u64)> = Vec::new();
+
+    for comparison in comparisons {
+        let idx = match candidates.iter().position(|c| c.candidate_image == comparison.candidate_image) {
+            Some(idx) => idx,
+            None => {
+                candidates.push(CandidateReport {
+                    candidate_image: comparison.candidate_image.clone(),
+                    sampled_jobs: 0,
+                    agreed: 0,
+                    disagreed: 0,
+                    agreement_rate: 0.0,
+                    avg_primary_time_ms: 0,
+                    avg_candidate_time_ms: 0,
+                    disagreements: Vec::new(),
+                });
+                time_totals.push((0, 0));
+                candidates.len() - 1
+            }
+        };
+
+        let report = &mut candidates[idx];
+        report.sampled_jobs += 1;
+        time_totals[idx].0 += comparison.primary_time_ms;
+        time_totals[idx].1 += comparison.candidate_time_ms;
+        if comparison.agrees() {
+            report.agreed += 1;
+        } else {
+            report.disagreed += 1;
+            if report.disagreements.len() < MAX_DISAGREEMENTS {
+                report.disagreements.push(Disagreement {
+                    job_id: comparison.job_id,
+                    primary_status: comparison.primary_status,
+                    candidate_status: comparison.candidate_status,
+                    primary_score: comparison.primary_score,
+                    candidate_score: comparison.candidate_score,
+                    mismatched_tests: comparison.mismatched_tests.clone(),
+                });
+            }
+        }
+    }
+
+    for (report, (primary_ms, candidate_ms)) in candidates.iter_mut().zip(time_totals) {
+        let n = report.sampled_jobs as u64;
+        report.agreement_rate = report.agreed as f64 / report.sampled_jobs as f64;
+        report.avg_primary_time_ms = primary_ms / n;
+        report.avg_candidate_time_ms = candidate_ms / n;
+    }
+
+    CanaryReport { language, candidates }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comparison(candidate: &str, mismatched_tests: Vec<u32>, candidate_time_ms: u64) -> ShadowComparison {
+        ShadowComparison {
+            job_id: Uuid::new_v4(),
+            language: Language::Python,
+            primary_image: "optimus-python:latest".to_string(),
+            candidate_image: candidate.to_string(),
+            primary_status: JobStatus::Completed,
+            candidate_status: if mismatched_tests.is_empty() { JobStatus::Completed } else { JobStatus::Failed },
+            primary_score: 10,
+            candidate_score: if mismatched_tests.is_empty() { 10 } else { 5 },
+            max_score: 10,
+            mismatched_tests,
+            primary_time_ms: 100,
+            candidate_time_ms,
+            compared_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_report_groups_by_candidate() {
+        let comparisons = vec![
+            comparison("py:rc2", vec![], 80),
+            comparison("py:rc2", vec![3], 120),
+            comparison("py:rc1", vec![], 200),
+            comparison("py:rc2", vec![], 100),
+        ];
+        let report = build_report(Language::Python, &comparisons);
+
+        assert_eq!(report.candidates.len(), 2);
+        let rc2 = &report.candidates[0];
+        assert_eq!(rc2.candidate_image, "py:rc2");
+        assert_eq!((rc2.sampled_jobs, rc2.agreed, rc2.disagreed), (3, 2, 1));
+        assert!((rc2.agreement_rate - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(rc2.avg_primary_time_ms, 100);
+        assert_eq!(rc2.avg_candidate_time_ms, 100);
+        assert_eq!(rc2.disagreements[0].mismatched_tests, vec![3]);
+
+        assert_eq!(report.candidates[1].agreement_rate, 1.0);
+    }
+}
@@ -0,0 +1,197 @@
+// Result diff between two submissions of the same problem (GET /jobs/compare)
+//
+// Aligns per-test results by test id and reports which tests changed verdict and how
+// much each test's time moved - the answer to "why did my resubmission get worse?".
+
+use optimus_common::types::{ExecutionResult, JobRequest, JobStatus, TestResult, TestStatus};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+#[derive(Debug, Serialize)]
+pub struct JobComparison {
+    pub a: JobSummary,
+    pub b: JobSummary,
+    /// Whether both jobs ran the same test cases (None if either job copy has expired)
+    pub same_test_cases: Option<bool>,
+    /// b.score - a.score
+    pub score_delta: i64,
+    pub improved: Vec<u32>,
+    pub regressed: Vec<u32>,
+    pub tests: Vec<TestDelta>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JobSummary {
+    pub job_id: Uuid,
+    pub overall_status: JobStatus,
+    pub score: u32,
+    pub max_score: u32,
+    pub total_time_ms: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TestDelta {
+    pub test_id: u32,
+    pub a_status: Option<TestStatus>,
+    pub b_status: Option<TestStatus>,
+    pub change: VerdictChange,
+    pub a_time_ms: Option<u64>,
+    pub b_time_ms: Option<u64>,
+    /// b - a, when the test ran in both jobs
+    pub time_delta_ms: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerdictChange {
+    Unchanged,
+    /// Passed in b only
+    Improved,
+    /// Passed in a only
+    Regressed,
+    /// Failed in both, for different reasons (e.g. wrong answer -> TLE)
+    Changed,
+    /// Test exists in only one of the jobs
+    Missing,
+}
+
+/// Whether two jobs ran the same problem: same language and identical test cases
+pub fn same_test_cases(a: &JobRequest, b: &JobRequest) -> bool {
+    a.language == b.language
+        && a.test_cases.len() == b.test_cases.len()
+        && a.test_cases.iter().zip(&b.test_cases).all(|(x, y)| {
+            x.id == y.id && x.input == y.input && x.expected_output == y.expected_output && x.weight == y.weight
+        })
+}
+
+pub fn compare(a: &ExecutionResult, b: &ExecutionResult, same_test_cases: Option<bool>) -> JobComparison {
+    let mut aligned: BTreeMap<u32, (Option<&TestResult>, Option<&TestResult>)> = BTreeMap::new();
+    for test in &a.results {
+        aligned.entry(test.test_id).or_default().0 = Some(test);
+    }
+    for test in &b.results {
+        aligned.entry(test.test_id).or_default().1 = Some(test);
+    }
+
+    let mut improved = Vec::new();
+    let mut regressed = Vec::new();
+    let tests = aligned
+        .into_iter()
+        .map(|(test_id, (x, y))| {
+            let change = match (x.map(|t| t.status), y.map(|t| t.status)) {
+                (Some(s), Some(t)) if s == t => VerdictChange::Unchanged,
+                (Some(_), Some(TestStatus::Passed)) => VerdictChange::Improved,
+                (Some(TestStatus::Passed), Some(_)) => VerdictChange::Regressed,
+                (Some(_), Some(_)) => VerdictChange::Changed,
+                _ => VerdictChange::Missing,
+            };
+            match change {
+                VerdictChange::Improved => improved.push(test_id),
+                VerdictChange::Regressed => regressed.push(test_id),
+                _ => {}
+            }
+            let a_time_ms = x.map(|t| t.execution_time_ms);
+            let b_time_ms = y.map(|t| t.execution_time_ms);
+            TestDelta {
+                test_id,
+                a_status: x.map(|t| t.status),
+                b_status: y.map(|t| t.status),
+                change,
+                a_time_ms,
+                b_time_ms,
+                time_delta_ms: a_time_ms.zip(b_time_ms).map(|(s, t)| t as i64 - s as i64),
+            }
+        })
+        .collect();
+
+    JobComparison {
+        a: summary(a),
+        b: summary(b),
+        same_test_cases,
+        score_delta: b.score as i64 - a.score as i64,
+        improved,
+        regressed,
+        tests,
+    }
+}
+
+fn summary(result: &ExecutionResult) -> JobSummary {
+    JobSummary {
+        job_id: result.job_id,
+        overall_status: result.overall_status,
+        score: result.score,
+        max_score: result.max_score,
+        total_time_ms: result.results.iter().map(|r| r.execution_time_ms).sum(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(tests: &[(u32, TestStatus, u64)]) -> ExecutionResult {
+        let results: Vec<TestResult> = tests
+            .iter()
+            .map(|&(test_id, status, execution_time_ms)| TestResult {
+                test_id,
+                status,
+                stdout: String::new(),
+                stderr: String::new(),
+                execution_time_ms,
+                stdout_base64: None,
+                stderr_base64: None,
+                overhead_ms: None,
+                cpu_time_ms: None,
+                wall_time_ms: None,
+                memory_kb: None,
+                failure: None,
+                checker_output: None,
+                sanitizer_report: None,
+                diff: None,
+            })
+            .collect();
+        ExecutionResult {
+            job_id: Uuid::new_v4(),
+            overall_status: JobStatus::Completed,
+            score: results.iter().filter(|r| r.status == TestStatus::Passed).count() as u32 * 10,
+            max_score: results.len() as u32 * 10,
+            results,
+            judge_env_version: None,
+            worker_id: None,
+            signature: None,
+            points: None,
+            timings: None,
+            misuse: Vec::new(),
+            effective_timeout_ms: None,
+            subtasks: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_compare_aligns_tests_by_id() {
+        let a = result(&[(1, TestStatus::Passed, 10), (2, TestStatus::Passed, 20), (3, TestStatus::Failed, 30), (4, TestStatus::Failed, 5)]);
+        let b = result(&[(1, TestStatus::Passed, 15), (2, TestStatus::TimeLimitExceeded, 1000), (3, TestStatus::Passed, 25), (4, TestStatus::RuntimeError, 5)]);
+        let diff = compare(&a, &b, Some(true));
+
+        assert_eq!(diff.score_delta, 0);
+        assert_eq!(diff.improved, vec![3]);
+        assert_eq!(diff.regressed, vec![2]);
+        let changes: Vec<VerdictChange> = diff.tests.iter().map(|t| t.change).collect();
+        assert_eq!(
+            changes,
+            vec![VerdictChange::Unchanged, VerdictChange::Regressed, VerdictChange::Improved, VerdictChange::Changed]
+        );
+        assert_eq!(diff.tests[0].time_delta_ms, Some(5));
+        assert_eq!(diff.tests[2].time_delta_ms, Some(-5));
+        assert_eq!(diff.b.total_time_ms, 1045);
+    }
+
+    #[test]
+    fn test_compare_reports_missing_tests() {
+        let diff = compare(&result(&[(1, TestStatus::Passed, 10)]), &result(&[(2, TestStatus::Passed, 10)]), None);
+        assert_eq!(diff.tests.len(), 2);
+        assert!(diff.tests.iter().all(|t| t.change == VerdictChange::Missing && t.time_delta_ms.is_none()));
+        assert!(diff.improved.is_empty() && diff.regressed.is_empty());
+    }
+}
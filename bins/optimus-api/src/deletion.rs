@@ -0,0 +1,115 @@
+// Soft deletion - DELETE /job/:id tombstones a submission instead of erasing it at once
+//
+// Only the key that submitted the job, or an admin key, may delete it. A tombstone hides
+// the job from every read endpoint immediately (410 JOB_DELETED), takes it out of its
+// tenant's retention index, the leaderboards and its problem's statistics. The data stays
+// until the restore window (DELETION_RESTORE_WINDOW_SECS, default 24 hours) closes, so an
+// admin can undo a mistaken deletion; then the purge loop deletes everything derived from
+// the job, as the retention janitor's delete mode does, DLQ entries included.
+//
+// Purge and restore both claim the job by removing it from the tombstone index, so a job
+// is never restored halfway through its purge.
+
+use crate::retention::{self, RetentionMode};
+use chrono::{Duration as ChronoDuration, Utc};
+use optimus_common::redis;
+use optimus_common::types::Tombstone;
+use ::redis::aio::ConnectionManager;
+use ::redis::AsyncCommands;
+use std::collections::HashSet;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// How often the purge loop looks for tombstones whose window has closed
+const PURGE_INTERVAL: Duration = Duration::from_secs(60);
+const PURGE_BATCH: usize = 100;
+
+/// How long a deleted job can be restored (DELETION_RESTORE_WINDOW_SECS, default 24 hours)
+pub fn restore_window_secs() -> u64 {
+    static WINDOW: OnceLock<u64> = OnceLock::new();
+    *WINDOW.get_or_init(|| {
+        std::env::var("DELETION_RESTORE_WINDOW_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(86400)
+    })
+}
+
+/// Tombstone for a job deleted now
+pub fn tombstone(job_id: uuid::Uuid, tenant: &str, submitted_at: Option<i64>, window_secs: u64) -> Tombstone {
+    let deleted_at = Utc::now();
+    Tombstone {
+        job_id,
+        deleted_at,
+        purge_at: deleted_at + ChronoDuration::seconds(window_secs as i64),
+        tenant: tenant.to_string(),
+        submitted_at,
+    }
+}
+
+/// Purge every job whose restore window has closed; returns how many were purged
+pub async fn purge_due(conn: &mut ConnectionManager) -> ::redis::RedisResult<usize> {
+    let mut purged = HashSet::new();
+    loop {
+        let due = redis::due_tombstones(conn, Utc::now().timestamp(), PURGE_BATCH).await?;
+        if due.is_empty() {
+            break;
+        }
+        for job_id in due {
+            // Lost claims were restored (or purged) by someone else
+            if !redis::claim_tombstone(conn, &job_id).await? {
+                continue;
+            }
+            if let Err(e) = retention::delete_job_data(conn, &job_id).await {
+                // Back in the index, so the next pass retries it
+                warn!(job_id = %job_id, error = %e, "Failed to purge deleted job");
+                let _: Result<(), _> = conn.zadd(redis::tombstone_index_key(), job_id.to_string(), 0).await;
+                return Err(e);
+            }
+            if let Err(e) = redis::rescore_leaderboard(conn, &job_id, redis::LeaderboardChange::Purge).await {
+                warn!(job_id = %job_id, error = %e, "Failed to purge deleted job from the leaderboards");
+            }
+            purged.insert(job_id);
+        }
+    }
+
+    if !purged.is_empty() {
+        retention::purge_dlq_entries(conn, RetentionMode::Delete, &purged, |message| {
+            warn!("Deletion purge: {}", message)
+        })
+        .await;
+        info!(jobs = purged.len(), "Purged deleted jobs");
+    }
+    Ok(purged.len())
+}
+
+/// Background task purging deleted jobs once their restore window closes
+pub async fn purge_loop(mut conn: ConnectionManager) {
+    let mut interval = tokio::time::interval(PURGE_INTERVAL);
+    loop {
+        interval.tick().await;
+        if let Err(e) = purge_due(&mut conn).await {
+            error!(error = %e, "Deletion purge failed");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tombstone_window() {
+        let job_id = uuid::Uuid::new_v4();
+        let tombstone = tombstone(job_id, "acme", Some(1_700_000_000), 3600);
+        assert_eq!(tombstone.job_id, job_id);
+        assert_eq!((tombstone.purge_at - tombstone.deleted_at).num_seconds(), 3600);
+        assert_eq!(tombstone.tenant, "acme");
+
+        let json = serde_json::to_value(&tombstone).unwrap();
+        assert_eq!(json["submitted_at"], 1_700_000_000);
+        let parsed: Tombstone = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, tombstone);
+    }
+}
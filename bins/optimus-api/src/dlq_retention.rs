@@ -0,0 +1,321 @@
+// DLQ retention - keeps the dead letter queues bounded
+//
+// Dead-lettered jobs stay in their language's DLQ until an admin requeues or drops them,
+// so the lists only ever grow. With DLQ_MAX_AGE_SECS and/or DLQ_MAX_LEN set, each run of
+// the retention janitor trims every DLQ (and the dispatcher's intake DLQ) from its oldest
+// end: entries older than the max age, and the oldest entries past the max length. An
+// entry's age is counted from when it was dead-lettered (`dead_lettered_at`, else when it
+// was last queued); entries with neither, and unreadable ones, only count toward the length,
+// and age trimming steps over them to the entries behind. Every API replica runs the
+// janitor, so a run only trims while it holds a Redis lock; the others skip it.
+//
+// With DLQ_ARCHIVE_URL set, each entry is archived before it is removed, exactly as stored
+// (still sealed when encryption at rest is on), as `{url}/{queue}/{name}.json`, where the
+// name is the job id and when it was dead-lettered (a digest for unreadable entries):
+// - http(s)://...: an HTTP PUT, e.g. to an object storage bucket or an upload gateway
+//   (DLQ_ARCHIVE_TOKEN, when set, is sent as a bearer token)
+// - file:///path: a file under that directory (local disk or a mounted bucket)
+// An entry that can't be archived stays in the DLQ, so forensic data is never dropped
+// while the store is unreachable; the next run tries again.
+
+use chrono::{DateTime, Utc};
+use optimus_common::redis;
+use optimus_common::types::Language;
+use ::redis::AsyncCommands;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::info;
+
+use crate::metrics;
+use crate::retention::parse_secs;
+
+/// Time allowed for one archive upload
+const ARCHIVE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Where entries go before they are trimmed
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArchiveTarget {
+    /// PUT to `{url}/{queue}/{name}.json`
+    Http { url: String },
+    /// Written to `{dir}/{queue}/{name}.json`
+    Directory { dir: PathBuf },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DlqPolicy {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_len: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_age_secs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive: Option<ArchiveTarget>,
+    pub interval_secs: u64,
+    /// Bearer token for HTTP archives (never reported)
+    #[serde(skip)]
+    archive_token: Option<String>,
+}
+
+/// Why an entry was trimmed (the reason label of optimus_dlq_evictions_total)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Eviction {
+    MaxAge,
+    MaxLen,
+}
+
+impl Eviction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Eviction::MaxAge => "max_age",
+            Eviction::MaxLen => "max_len",
+        }
+    }
+}
+
+impl DlqPolicy {
+    /// Load from DLQ_* env vars (the janitor's RETENTION_INTERVAL_SECS sets how often)
+    /// Returns Ok(None) when neither DLQ_MAX_LEN nor DLQ_MAX_AGE_SECS is set
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+        let max_len = match var("DLQ_MAX_LEN") {
+            Some(v) => Some(v.trim().parse::<usize>().map_err(|_| format!("DLQ_MAX_LEN must be a number, got '{}'", v))?),
+            None => None,
+        };
+        let max_age_secs = var("DLQ_MAX_AGE_SECS").map(|v| parse_secs("DLQ_MAX_AGE_SECS", &v)).transpose()?;
+        if max_len.is_none() && max_age_secs.is_none() {
+            return Ok(None);
+        }
+        let archive = var("DLQ_ARCHIVE_URL").map(|url| parse_archive(&url)).transpose()?;
+        let interval_secs = match var("RETENTION_INTERVAL_SECS") {
+            Some(v) => parse_secs("RETENTION_INTERVAL_SECS", &v)?,
+            None => 3600,
+        };
+        Ok(Some(Self { max_len, max_age_secs, archive, interval_secs, archive_token: var("DLQ_ARCHIVE_TOKEN") }))
+    }
+
+    /// Whether the oldest entry of a DLQ holding `len` entries goes
+    pub fn eviction(&self, len: usize, dead_lettered_at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> Option<Eviction> {
+        if self.max_len.is_some_and(|max_len| len > max_len) {
+            return Some(Eviction::MaxLen);
+        }
+        let max_age = self.max_age_secs? as i64;
+        dead_lettered_at.filter(|at| (now - *at).num_seconds() > max_age).map(|_| Eviction::MaxAge)
+    }
+}
+
+fn parse_archive(url: &str) -> Result<ArchiveTarget, String> {
+    let url = url.trim().trim_end_matches('/');
+    if let Some(dir) = url.strip_prefix("file://") {
+        if dir.is_empty() {
+            return Err("DLQ_ARCHIVE_URL file:// needs a directory".to_string());
+        }
+        return Ok(ArchiveTarget::Directory { dir: PathBuf::from(dir) });
+    }
+    if url.starts_with("http://") || url.starts_with("https://") {
+        return Ok(ArchiveTarget::Http { url: url.to_string() });
+    }
+    Err(format!("DLQ_ARCHIVE_URL must start with http://, https:// or file://, got '{}'", url))
+}
+
+/// What one trimming pass did
+#[derive(Debug, Default)]
+pub struct TrimOutcome {
+    pub evicted: u64,
+    pub archived: u64,
+}
+
+/// Trim every language's DLQ and the intake DLQ; failures go to `on_error` and leave the
+/// rest of that queue for the next run. Does nothing while another replica is trimming.
+pub async fn trim_all(conn: &mut impl redis::Connection, policy: &DlqPolicy, on_error: impl Fn(String)) -> TrimOutcome {
+    let holder = uuid::Uuid::new_v4().to_string();
+    match redis::claim_dlq_trim(conn, &holder, policy.interval_secs).await {
+        Ok(true) => {}
+        Ok(false) => {
+            info!("DLQ retention: another replica is trimming, skipping this run");
+            return TrimOutcome::default();
+        }
+        Err(e) => {
+            on_error(format!("failed to take the DLQ trimming lock: {}", e));
+            return TrimOutcome::default();
+        }
+    }
+    let outcome = trim_queues(conn, policy, &on_error).await;
+    if let Err(e) = redis::release_dlq_trim(conn, &holder).await {
+        on_error(format!("failed to release the DLQ trimming lock: {}", e));
+    }
+    outcome
+}
+
+async fn trim_queues(conn: &mut impl redis::Connection, policy: &DlqPolicy, on_error: &impl Fn(String)) -> TrimOutcome {
+    let client = match policy.archive {
+        Some(ArchiveTarget::Http { .. }) => match reqwest::Client::builder().timeout(ARCHIVE_TIMEOUT).build() {
+            Ok(client) => Some(client),
+            Err(e) => {
+                on_error(format!("failed to build archive client: {}", e));
+                return TrimOutcome::default();
+            }
+        },
+        _ => None,
+    };
+    let queues = Language::all_variants()
+        .iter()
+        .map(|language| (redis::dlq_name(language), language.to_string()))
+        .chain([(redis::intake_dlq_name(), "intake".to_string())]);
+
+    let mut outcome = TrimOutcome::default();
+    for (queue, label) in queues {
+        if let Err(e) = trim_queue(conn, policy, client.as_ref(), &queue, &label, &mut outcome).await {
+            on_error(format!("failed to trim {}: {}", queue, e));
+        }
+    }
+    outcome
+}
+
+/// Remove entries from the front of one DLQ until the policy keeps the oldest entry of
+/// known age; entries of unknown age the policy keeps are stepped over
+async fn trim_queue(
+    conn: &mut impl redis::Connection,
+    policy: &DlqPolicy,
+    client: Option<&reqwest::Client>,
+    queue: &str,
+    label: &str,
+    outcome: &mut TrimOutcome,
+) -> Result<(), String> {
+    let now = Utc::now();
+    // Entries before this index were kept for their unknown age
+    let mut index = 0;
+    loop {
+        let len: usize = conn.llen(queue).await.map_err(|e| e.to_string())?;
+        let Some(entry): Option<String> = conn.lindex(queue, index).await.map_err(|e| e.to_string())? else {
+            return Ok(());
+        };
+        let job = redis::decode_job(&entry).ok();
+        let dead_lettered_at = job.as_ref().and_then(|job| job.metadata.dead_lettered_at.or(job.metadata.enqueued_at));
+        let Some(eviction) = policy.eviction(len, dead_lettered_at, now) else {
+            if dead_lettered_at.is_some() {
+                return Ok(());
+            }
+            index += 1;
+            continue;
+        };
+
+        if let Some(target) = &policy.archive {
+            let name = archive_name(job.as_ref(), dead_lettered_at, &entry);
+            let archived = archive(target, client, policy.archive_token.as_deref(), label, &name, &entry).await;
+            metrics::record_dlq_archive(if archived.is_ok() { "ok" } else { "error" });
+            archived.map_err(|e| format!("archiving {} failed, entry kept: {}", name, e))?;
+            outcome.archived += 1;
+        }
+        // Removed by value: an admin may have requeued it meanwhile
+        let removed: usize = conn.lrem(queue, 1, &entry).await.map_err(|e| e.to_string())?;
+        if removed > 0 {
+            outcome.evicted += 1;
+            metrics::record_dlq_eviction(label, eviction.as_str());
+            if let Some(job) = &job {
+                redis::clear_job_location(conn, &job.id).await.map_err(|e| e.to_string())?;
+            }
+        }
+    }
+}
+
+/// Archive name of an entry: a job dead-lettered twice gets two archives
+fn archive_name(job: Option<&optimus_common::types::JobRequest>, dead_lettered_at: Option<DateTime<Utc>>, entry: &str) -> String {
+    match (job, dead_lettered_at) {
+        (Some(job), Some(at)) => format!("{}-{}", job.id, at.format("%Y%m%dT%H%M%S%.3fZ")),
+        (Some(job), None) => format!("{}-{}", job.id, optimus_common::crypto::digest(entry)),
+        (None, _) => optimus_common::crypto::digest(entry),
+    }
+}
+
+/// Store one entry in the archive
+async fn archive(
+    target: &ArchiveTarget,
+    client: Option<&reqwest::Client>,
+    token: Option<&str>,
+    queue: &str,
+    name: &str,
+    entry: &str,
+) -> Result<(), String> {
+    match target {
+        ArchiveTarget::Directory { dir } => {
+            let dir = dir.join(queue);
+            tokio::fs::create_dir_all(&dir).await.map_err(|e| e.to_string())?;
+            tokio::fs::write(dir.join(format!("{}.json", name)), entry).await.map_err(|e| e.to_string())
+        }
+        ArchiveTarget::Http { url } => {
+            let client = client.ok_or("no archive client")?;
+            let mut request = client
+                .put(format!("{}/{}/{}.json", url, queue, name))
+                .header("content-type", "application/json")
+                .body(entry.to_string());
+            if let Some(token) = token {
+                request = request.bearer_auth(token);
+            }
+            let response = request.send().await.map_err(|e| e.to_string())?;
+            if !response.status().is_success() {
+                return Err(format!("archive returned {}", response.status()));
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(max_len: Option<usize>, max_age_secs: Option<u64>) -> DlqPolicy {
+        DlqPolicy { max_len, max_age_secs, archive: None, interval_secs: 60, archive_token: None }
+    }
+
+    #[test]
+    fn test_eviction_by_length_and_age() {
+        let now = Utc::now();
+        let day_old = Some(now - chrono::Duration::days(1));
+
+        let both = policy(Some(100), Some(3600));
+        assert_eq!(both.eviction(101, None, now), Some(Eviction::MaxLen));
+        assert_eq!(both.eviction(100, day_old, now), Some(Eviction::MaxAge));
+        assert_eq!(both.eviction(100, Some(now), now), None);
+        // Entries of unknown age only count toward the length
+        assert_eq!(both.eviction(100, None, now), None);
+
+        assert_eq!(policy(None, Some(3600)).eviction(1_000_000, Some(now), now), None);
+        assert_eq!(policy(Some(0), None).eviction(1, day_old, now), Some(Eviction::MaxLen));
+    }
+
+    #[test]
+    fn test_archive_targets() {
+        assert!(matches!(
+            parse_archive("https://bucket.example.com/optimus-dlq/"),
+            Ok(ArchiveTarget::Http { url }) if url == "https://bucket.example.com/optimus-dlq"
+        ));
+        assert!(matches!(
+            parse_archive("file:///var/lib/optimus/dlq"),
+            Ok(ArchiveTarget::Directory { dir }) if dir == std::path::Path::new("/var/lib/optimus/dlq")
+        ));
+        assert!(parse_archive("s3://bucket").is_err());
+        assert!(parse_archive("file://").is_err());
+    }
+
+    #[test]
+    fn test_archive_names_differ_per_dead_lettering() {
+        let job = optimus_common::types::JobRequest::default();
+        let first = "2026-01-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let second = first + chrono::Duration::minutes(5);
+        assert_eq!(archive_name(Some(&job), Some(first), "{}"), format!("{}-20260101T120000.000Z", job.id));
+        assert_ne!(archive_name(Some(&job), Some(first), "{}"), archive_name(Some(&job), Some(second), "{}"));
+        assert_eq!(archive_name(None, None, "garbage"), optimus_common::crypto::digest("garbage"));
+    }
+
+    #[tokio::test]
+    async fn test_directory_archive_keeps_the_entry_as_stored() {
+        let dir = std::env::temp_dir().join(format!("optimus-dlq-{}", uuid::Uuid::new_v4()));
+        let target = ArchiveTarget::Directory { dir: dir.clone() };
+        archive(&target, None, None, "python", "job-1", "{\"sealed\":true}").await.unwrap();
+        let stored = std::fs::read_to_string(dir.join("python").join("job-1.json")).unwrap();
+        assert_eq!(stored, "{\"sealed\":true}");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
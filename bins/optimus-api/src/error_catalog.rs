@@ -0,0 +1,505 @@
+// Error message catalog - ErrorDetail messages in the caller's language
+//
+// Handlers write English messages; this layer swaps in the translation for the error's
+// code when Accept-Language asks for another supported locale (see optimus_common::i18n).
+// The code never changes, so clients keep branching on it. Translations are per code, so
+// they can't repeat the specifics of the English message (sizes, ids); that message is
+// kept as `error.detail`. Errors whose code has no translation are left in English.
+//
+// Adding a code: list it in the tests' `CODES`, and give it an entry in `translations` with every
+// locale's text.
+
+use axum::body::{Body, HttpBody};
+use axum::extract::Request;
+use axum::http::header;
+use axum::http::response::Parts;
+use axum::middleware::Next;
+use axum::response::Response;
+use optimus_common::i18n::Locale;
+
+/// Error bodies are small; anything larger is passed through untouched
+const MAX_ERROR_BODY: usize = 64 * 1024;
+
+/// Text of an error code in each locale
+struct Translations {
+    en: &'static str,
+    es: &'static str,
+    hi: &'static str,
+}
+
+const fn t(en: &'static str, es: &'static str, hi: &'static str) -> Translations {
+    Translations { en, es, hi }
+}
+
+fn translations(code: &str) -> Option<Translations> {
+    Some(match code {
+        // Request bodies
+        "MALFORMED_JSON" => t(
+            "The request body is not valid JSON.",
+            "El cuerpo de la solicitud no es JSON válido.",
+            "अनुरोध का बॉडी मान्य JSON नहीं है।",
+        ),
+        "INVALID_FIELD" => t(
+            "A field of the request has the wrong type or an out-of-range value.",
+            "Un campo de la solicitud tiene un tipo incorrecto o un valor fuera de rango.",
+            "अनुरोध के किसी फ़ील्ड का प्रकार गलत है या उसका मान सीमा से बाहर है।",
+        ),
+        "INVALID_BODY" => t(
+            "The request body could not be read.",
+            "No se pudo leer el cuerpo de la solicitud.",
+            "अनुरोध का बॉडी पढ़ा नहीं जा सका।",
+        ),
+        "UNSUPPORTED_MEDIA_TYPE" => t(
+            "The request must be sent as application/json.",
+            "La solicitud debe enviarse como application/json.",
+            "अनुरोध application/json के रूप में भेजा जाना चाहिए।",
+        ),
+        "PAYLOAD_TOO_LARGE" => t(
+            "The request body is too large.",
+            "El cuerpo de la solicitud es demasiado grande.",
+            "अनुरोध का बॉडी बहुत बड़ा है।",
+        ),
+        // Submissions
+        "LANGUAGE_NOT_SUPPORTED" => t(
+            "This language is not enabled or supported.",
+            "Este lenguaje no está habilitado o no es compatible.",
+            "यह भाषा सक्षम या समर्थित नहीं है।",
+        ),
+        "GPU_NOT_SUPPORTED" => t(
+            "This language cannot run submissions on a GPU.",
+            "Este lenguaje no puede ejecutar envíos en una GPU.",
+            "यह भाषा सबमिशन को GPU पर नहीं चला सकती।",
+        ),
+        "REQUIREMENT_NOT_SUPPORTED" => t(
+            "No worker for this language offers a required capability.",
+            "Ningún worker de este lenguaje ofrece una capacidad requerida.",
+            "इस भाषा का कोई वर्कर आवश्यक क्षमता प्रदान नहीं करता।",
+        ),
+        "INVALID_LANGUAGE" => t("Unknown language.", "Lenguaje desconocido.", "अज्ञात भाषा।"),
+        "NO_TEST_CASES" => t(
+            "At least one test case is required.",
+            "Se requiere al menos un caso de prueba.",
+            "कम से कम एक टेस्ट केस आवश्यक है।",
+        ),
+        "TOO_MANY_TEST_CASES" => t(
+            "Too many test cases.",
+            "Demasiados casos de prueba.",
+            "बहुत अधिक टेस्ट केस हैं।",
+        ),
+        "SOURCE_CODE_TOO_LARGE" => t(
+            "The source code is too large.",
+            "El código fuente es demasiado grande.",
+            "सोर्स कोड बहुत बड़ा है।",
+        ),
+        "EMPTY_SOURCE_CODE" => t(
+            "The source code cannot be empty.",
+            "El código fuente no puede estar vacío.",
+            "सोर्स कोड खाली नहीं हो सकता।",
+        ),
+        "TEST_CASE_INPUT_TOO_LARGE" => t(
+            "A test case input is too large.",
+            "La entrada de un caso de prueba es demasiado grande.",
+            "किसी टेस्ट केस का इनपुट बहुत बड़ा है।",
+        ),
+        "TEST_CASE_OUTPUT_TOO_LARGE" => t(
+            "A test case expected output is too large.",
+            "La salida esperada de un caso de prueba es demasiado grande.",
+            "किसी टेस्ट केस का अपेक्षित आउटपुट बहुत बड़ा है।",
+        ),
+        "INVALID_WEIGHT" => t(
+            "A test case weight is too large.",
+            "El peso de un caso de prueba es demasiado grande.",
+            "किसी टेस्ट केस का भार बहुत अधिक है।",
+        ),
+        "INVALID_TEST_CASES" => t(
+            "Two test cases share an id.",
+            "Dos casos de prueba comparten un identificador.",
+            "दो टेस्ट केस की आईडी एक ही है।",
+        ),
+        "INVALID_EXPECTED_OUTPUT" => t(
+            "In binary output mode, expected outputs must be base64.",
+            "En el modo de salida binaria, las salidas esperadas deben estar en base64.",
+            "बाइनरी आउटपुट मोड में अपेक्षित आउटपुट base64 में होने चाहिए।",
+        ),
+        "INVALID_TIMEOUT" => t(
+            "The timeout is out of range.",
+            "El tiempo límite está fuera de rango.",
+            "टाइमआउट सीमा से बाहर है।",
+        ),
+        "INVALID_EVALUATION" => t(
+            "The evaluation pipeline is invalid.",
+            "La configuración de evaluación no es válida.",
+            "मूल्यांकन पाइपलाइन अमान्य है।",
+        ),
+        "INVALID_STATUS_POLICY" => t(
+            "The status policy is invalid.",
+            "La política de estado no es válida.",
+            "स्टेटस नीति अमान्य है।",
+        ),
+        "INVALID_SCORING" => t(
+            "The scoring settings are invalid.",
+            "La configuración de puntuación no es válida.",
+            "स्कोरिंग सेटिंग्स अमान्य हैं।",
+        ),
+        "INVALID_LEADERBOARD" => t(
+            "The leaderboard ids are invalid.",
+            "Los identificadores de la clasificación no son válidos.",
+            "लीडरबोर्ड आईडी अमान्य हैं।",
+        ),
+        "INVALID_PRIORITY" => t(
+            "The priority must be between 0 and 9.",
+            "La prioridad debe estar entre 0 y 9.",
+            "प्राथमिकता 0 और 9 के बीच होनी चाहिए।",
+        ),
+        "INVALID_INTERACTOR" => t(
+            "The interactor must be non-empty source code within the source size limit, and cannot be combined with a checker.",
+            "El interactor debe ser código fuente no vacío dentro del límite de tamaño y no puede combinarse con un checker.",
+            "इंटरैक्टर आकार सीमा के भीतर गैर-रिक्त स्रोत कोड होना चाहिए और इसे checker के साथ नहीं जोड़ा जा सकता।",
+        ),
+        "INVALID_CHECKER" => t(
+            "The checker must be non-empty source code within the source size limit.",
+            "El checker debe ser código fuente no vacío dentro del límite de tamaño.",
+            "चेकर खाली नहीं होना चाहिए और स्रोत आकार सीमा के भीतर होना चाहिए।",
+        ),
+        "SANITIZERS_UNSUPPORTED" => t(
+            "Sanitizers can only be used with C submissions.",
+            "Los sanitizers solo se pueden usar con envíos en C.",
+            "सैनिटाइज़र केवल C सबमिशन के साथ उपयोग किए जा सकते हैं।",
+        ),
+        "NOTEBOOK_UNSUPPORTED" => t(
+            "Notebook submissions can only be used with Python.",
+            "Los envíos como notebook solo se pueden usar con Python.",
+            "नोटबुक सबमिशन केवल Python के साथ उपयोग किए जा सकते हैं।",
+        ),
+        "INVALID_NOTEBOOK" => t(
+            "The submission is not a Jupyter notebook with code cells.",
+            "El envío no es un notebook de Jupyter con celdas de código.",
+            "सबमिशन कोड सेल वाली Jupyter नोटबुक नहीं है।",
+        ),
+        "INVALID_ENCODING" => t(
+            "Source code must be UTF-8 text, not a binary or UTF-16 file.",
+            "El código fuente debe ser texto UTF-8, no un archivo binario o UTF-16.",
+            "स्रोत कोड UTF-8 टेक्स्ट होना चाहिए, बाइनरी या UTF-16 फ़ाइल नहीं।",
+        ),
+        "INVALID_TENANT" => t(
+            "The tenant id is invalid.",
+            "El identificador de inquilino no es válido.",
+            "टेनेंट आईडी अमान्य है।",
+        ),
+        "PRIORITY_REQUIRES_ADMIN" => t(
+            "Only an admin API key may submit with a priority.",
+            "Solo una clave de API de administrador puede enviar con prioridad.",
+            "केवल एडमिन API कुंजी ही प्राथमिकता के साथ सबमिट कर सकती है।",
+        ),
+        "IDEMPOTENCY_CONFLICT" => t(
+            "This idempotency key was already used with a different request.",
+            "Esta clave de idempotencia ya se usó con otra solicitud.",
+            "यह आइडेम्पोटेंसी कुंजी पहले ही किसी अलग अनुरोध के साथ उपयोग की जा चुकी है।",
+        ),
+        "SUBMISSION_COOLDOWN" => t(
+            "You are submitting to this problem too often; please wait before retrying.",
+            "Estás enviando a este problema con demasiada frecuencia; espera antes de reintentar.",
+            "आप इस समस्या पर बहुत बार सबमिट कर रहे हैं; दोबारा प्रयास करने से पहले कृपया प्रतीक्षा करें।",
+        ),
+        "RATE_LIMITED" => t(
+            "Too many submissions with this API key; please wait before retrying.",
+            "Demasiados envíos con esta clave de API; espera antes de reintentar.",
+            "इस API कुंजी से बहुत अधिक सबमिशन हुए हैं; दोबारा प्रयास करने से पहले कृपया प्रतीक्षा करें।",
+        ),
+        "CPU_QUOTA_EXCEEDED" => t(
+            "This API key has used up its CPU time for today; please retry tomorrow.",
+            "Esta clave de API ha agotado su tiempo de CPU de hoy; vuelve a intentarlo mañana.",
+            "इस API कुंजी का आज का CPU समय समाप्त हो गया है; कृपया कल फिर प्रयास करें।",
+        ),
+        "QUEUE_FAILURE" => t(
+            "The job could not be queued; please retry.",
+            "No se pudo encolar el trabajo; inténtalo de nuevo.",
+            "जॉब को कतार में नहीं डाला जा सका; कृपया फिर से प्रयास करें।",
+        ),
+        // Jobs and results
+        "INVALID_JOB_ID" => t(
+            "The job id is not valid.",
+            "El identificador del trabajo no es válido.",
+            "जॉब आईडी मान्य नहीं है।",
+        ),
+        "INVALID_ID" => t("The id is not valid.", "El identificador no es válido.", "आईडी मान्य नहीं है।"),
+        "RESULT_NOT_FOUND" => t(
+            "No result was found for this job.",
+            "No se encontró un resultado para este trabajo.",
+            "इस जॉब का कोई परिणाम नहीं मिला।",
+        ),
+        "RESULT_INTEGRITY_ERROR" => t(
+            "The stored result failed its integrity check.",
+            "El resultado almacenado no superó la verificación de integridad.",
+            "संग्रहीत परिणाम अखंडता जाँच में विफल रहा।",
+        ),
+        "JOB_NOT_AVAILABLE" => t(
+            "The job was not found or its stored copy has expired.",
+            "No se encontró el trabajo o su copia almacenada ha caducado.",
+            "जॉब नहीं मिला या उसकी संग्रहीत प्रति समाप्त हो गई है।",
+        ),
+        "UPGRADE_REQUIRED" => t(
+            "Connect with a WebSocket client.",
+            "Conéctate con un cliente WebSocket.",
+            "WebSocket क्लाइंट से कनेक्ट करें।",
+        ),
+        "JOB_NOT_FOUND" => t(
+            "The job is unknown or has expired.",
+            "El trabajo es desconocido o ha caducado.",
+            "जॉब अज्ञात है या समाप्त हो चुका है।",
+        ),
+        "WORKER_NOT_FOUND" => t(
+            "No live worker has this id.",
+            "Ningún worker activo tiene este identificador.",
+            "इस आईडी वाला कोई सक्रिय वर्कर नहीं है।",
+        ),
+        "JOB_DELETED" => t(
+            "This job was deleted.",
+            "Este trabajo fue eliminado.",
+            "यह जॉब हटा दिया गया था।",
+        ),
+        "JOB_NOT_FINISHED" => t(
+            "The job has not finished yet.",
+            "El trabajo aún no ha terminado.",
+            "जॉब अभी पूरा नहीं हुआ है।",
+        ),
+        "DIFFERENT_PROBLEMS" => t(
+            "The two jobs are for different problems.",
+            "Los dos trabajos corresponden a problemas distintos.",
+            "दोनों जॉब अलग-अलग समस्याओं के हैं।",
+        ),
+        "UNKNOWN_TEST_ID" => t(
+            "The job has no test with this id.",
+            "El trabajo no tiene una prueba con este identificador.",
+            "इस जॉब में इस आईडी का कोई टेस्ट नहीं है।",
+        ),
+        "INVALID_ANNOTATION" => t(
+            "The annotation is invalid.",
+            "La anotación no es válida.",
+            "एनोटेशन अमान्य है।",
+        ),
+        "INVALID_OVERRIDE" => t(
+            "The score override is invalid.",
+            "La corrección de puntuación no es válida.",
+            "स्कोर ओवरराइड अमान्य है।",
+        ),
+        "INVALID_TEST_SET" => t(
+            "The test set is invalid.",
+            "El conjunto de pruebas no es válido.",
+            "टेस्ट सेट अमान्य है।",
+        ),
+        "TEST_SET_NOT_FOUND" => t(
+            "The test set is not registered for this problem.",
+            "El conjunto de pruebas no está registrado para este problema.",
+            "यह टेस्ट सेट इस समस्या के लिए पंजीकृत नहीं है।",
+        ),
+        "VERIFICATION_NOT_FOUND" => t(
+            "No verification has been requested for this job.",
+            "No se ha solicitado ninguna verificación para este trabajo.",
+            "इस जॉब के लिए कोई सत्यापन अनुरोधित नहीं किया गया है।",
+        ),
+        "DEBUG_ARTIFACTS_NOT_FOUND" => t(
+            "No debug artifacts were recorded for this job.",
+            "No se registraron artefactos de depuración para este trabajo.",
+            "इस जॉब के लिए कोई डीबग आर्टिफ़ैक्ट दर्ज नहीं किए गए।",
+        ),
+        "RUNTIME_INFO_UNAVAILABLE" => t(
+            "No worker has reported runtime information for this language yet.",
+            "Ningún worker ha informado aún del entorno de ejecución de este lenguaje.",
+            "किसी भी वर्कर ने अभी तक इस भाषा की रनटाइम जानकारी नहीं दी है।",
+        ),
+        // Administration
+        "UNAUTHORIZED" => t(
+            "A valid API key is required.",
+            "Se requiere una clave de API válida.",
+            "एक मान्य API कुंजी आवश्यक है।",
+        ),
+        "ADMIN_REQUIRED" => t(
+            "This action needs an admin API key.",
+            "Esta acción requiere una clave de API de administrador.",
+            "इस कार्रवाई के लिए एडमिन API कुंजी आवश्यक है।",
+        ),
+        "NOT_JOB_OWNER" => t(
+            "Only the API key that submitted the job, or an admin key, may do this.",
+            "Solo la clave de API que envió el trabajo, o una clave de administrador, puede hacer esto.",
+            "यह केवल वही API कुंजी कर सकती है जिसने जॉब सबमिट किया था, या कोई एडमिन कुंजी।",
+        ),
+        "IDEMPOTENCY_KEY_NOT_FOUND" => t(
+            "The idempotency key was not found.",
+            "No se encontró la clave de idempotencia.",
+            "आइडेम्पोटेंसी कुंजी नहीं मिली।",
+        ),
+        "TOMBSTONE_NOT_FOUND" => t(
+            "This job has not been deleted.",
+            "Este trabajo no ha sido eliminado.",
+            "यह जॉब हटाया नहीं गया है।",
+        ),
+        "RESTORE_WINDOW_CLOSED" => t(
+            "The job can no longer be restored; it has been purged.",
+            "El trabajo ya no se puede restaurar; ha sido purgado.",
+            "जॉब को अब पुनर्स्थापित नहीं किया जा सकता; उसे स्थायी रूप से हटा दिया गया है।",
+        ),
+        "RETENTION_DISABLED" => t(
+            "Retention is not configured.",
+            "La retención no está configurada.",
+            "रिटेंशन कॉन्फ़िगर नहीं है।",
+        ),
+        "RETENTION_RUNNING" => t(
+            "A retention run is already in progress.",
+            "Ya hay una ejecución de retención en curso.",
+            "एक रिटेंशन रन पहले से चल रहा है।",
+        ),
+        "INTERNAL_ERROR" => t(
+            "Something went wrong on our side; please retry.",
+            "Algo salió mal de nuestro lado; inténtalo de nuevo.",
+            "हमारी ओर से कुछ गलत हो गया; कृपया फिर से प्रयास करें।",
+        ),
+        _ => return None,
+    })
+}
+
+/// Catalog text of an error code, if it has been translated
+pub fn message(code: &str, locale: Locale) -> Option<&'static str> {
+    let translations = translations(code)?;
+    Some(match locale {
+        Locale::En => translations.en,
+        Locale::Es => translations.es,
+        Locale::Hi => translations.hi,
+    })
+}
+
+/// Translate `error.message` of a JSON error body, keeping the original as `error.detail`;
+/// None when there is nothing to translate
+fn localize_body(body: &[u8], locale: Locale) -> Option<Vec<u8>> {
+    let mut value: serde_json::Value = serde_json::from_slice(body).ok()?;
+    let error = value.get_mut("error")?.as_object_mut()?;
+    let translated = message(error.get("code")?.as_str()?, locale)?;
+    if let Some(original) = error.insert("message".to_string(), translated.into()) {
+        error.insert("detail".to_string(), original);
+    }
+    serde_json::to_vec(&value).ok()
+}
+
+/// Rewrite the body of a small JSON error response; the response is handed back untouched
+/// when it is not one, or when `rewrite` returns None for its body
+pub(crate) async fn rewrite_error_body(
+    response: Response,
+    rewrite: impl FnOnce(&[u8]) -> Option<Vec<u8>>,
+) -> Result<(Parts, Vec<u8>), Response> {
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+    let is_error = response.status().is_client_error() || response.status().is_server_error();
+    let small = response.body().size_hint().upper().is_some_and(|len| len <= MAX_ERROR_BODY as u64);
+    if !is_error || !is_json || !small {
+        return Err(response);
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let Ok(bytes) = axum::body::to_bytes(body, MAX_ERROR_BODY).await else {
+        return Err(Response::from_parts(parts, Body::empty()));
+    };
+    let Some(rewritten) = rewrite(&bytes) else {
+        return Err(Response::from_parts(parts, Body::from(bytes)));
+    };
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Ok((parts, rewritten))
+}
+
+/// Add Accept-Language to a response's Vary header, unless it is already there
+fn vary_on_language(headers: &mut header::HeaderMap) {
+    let listed = headers
+        .get_all(header::VARY)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .any(|name| name.trim().eq_ignore_ascii_case("accept-language") || name.trim() == "*");
+    if !listed {
+        headers.append(header::VARY, header::HeaderValue::from_static("Accept-Language"));
+    }
+}
+
+/// Middleware: localize error responses for callers that asked for another language
+///
+/// Every error response varies on Accept-Language, English ones included: a cache must
+/// not hand the English error to a caller who asked for Spanish, or the other way round.
+pub async fn localize_errors(request: Request, next: Next) -> Response {
+    let locale = Locale::negotiate(request.headers().get(header::ACCEPT_LANGUAGE).and_then(|v| v.to_str().ok()));
+    let mut response = next.run(request).await;
+    if response.status().is_client_error() || response.status().is_server_error() {
+        vary_on_language(response.headers_mut());
+    }
+    if locale == Locale::En {
+        return response;
+    }
+
+    match rewrite_error_body(response, |body| localize_body(body, locale)).await {
+        Ok((mut parts, localized)) => {
+            parts.headers.insert(header::CONTENT_LANGUAGE, header::HeaderValue::from_static(locale.tag()));
+            Response::from_parts(parts, Body::from(localized))
+        }
+        Err(response) => response,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every error code the API returns besides the submission checks' (`validate::CODES`);
+    /// each has an entry in `translations`
+    const CODES: &[&str] = &[
+        // Request bodies
+        "MALFORMED_JSON", "INVALID_FIELD", "INVALID_BODY", "UNSUPPORTED_MEDIA_TYPE",
+        "PAYLOAD_TOO_LARGE",
+        // Submissions
+        "LANGUAGE_NOT_SUPPORTED", "GPU_NOT_SUPPORTED", "REQUIREMENT_NOT_SUPPORTED",
+        "INVALID_LANGUAGE", "INVALID_TENANT", "PRIORITY_REQUIRES_ADMIN",
+        "IDEMPOTENCY_CONFLICT", "SUBMISSION_COOLDOWN", "RATE_LIMITED", "CPU_QUOTA_EXCEEDED", "QUEUE_FAILURE",
+        // Jobs and results
+        "INVALID_JOB_ID", "INVALID_ID", "RESULT_NOT_FOUND", "RESULT_INTEGRITY_ERROR",
+        "JOB_NOT_AVAILABLE", "UPGRADE_REQUIRED", "JOB_NOT_FOUND", "WORKER_NOT_FOUND", "JOB_DELETED",
+        "JOB_NOT_FINISHED", "DIFFERENT_PROBLEMS", "UNKNOWN_TEST_ID", "INVALID_ANNOTATION",
+        "INVALID_OVERRIDE", "INVALID_TEST_SET", "TEST_SET_NOT_FOUND", "VERIFICATION_NOT_FOUND",
+        "DEBUG_ARTIFACTS_NOT_FOUND", "RUNTIME_INFO_UNAVAILABLE",
+        // Administration
+        "UNAUTHORIZED", "ADMIN_REQUIRED", "NOT_JOB_OWNER", "IDEMPOTENCY_KEY_NOT_FOUND",
+        "TOMBSTONE_NOT_FOUND", "RESTORE_WINDOW_CLOSED", "RETENTION_DISABLED", "RETENTION_RUNNING",
+        "INTERNAL_ERROR",
+    ];
+
+    #[test]
+    fn test_every_code_translated_into_every_locale() {
+        for code in CODES.iter().chain(optimus_common::validate::CODES) {
+            for locale in Locale::ALL {
+                assert!(message(code, locale).is_some_and(|m| !m.is_empty()), "{} has no {} text", code, locale.tag());
+            }
+        }
+    }
+
+    #[test]
+    fn test_vary_on_language_once() {
+        let mut headers = header::HeaderMap::new();
+        vary_on_language(&mut headers);
+        headers.append(header::VARY, header::HeaderValue::from_static("Accept"));
+        vary_on_language(&mut headers);
+        let vary: Vec<_> = headers.get_all(header::VARY).iter().collect();
+        assert_eq!(vary, ["Accept-Language", "Accept"]);
+    }
+
+    #[test]
+    fn test_localize_body() {
+        let body = br#"{"error":{"code":"INVALID_JOB_ID","message":"Invalid job ID format"}}"#;
+        let localized: serde_json::Value = serde_json::from_slice(&localize_body(body, Locale::Es).unwrap()).unwrap();
+        assert_eq!(localized["error"]["code"], "INVALID_JOB_ID");
+        assert_eq!(localized["error"]["message"], "El identificador del trabajo no es válido.");
+        assert_eq!(localized["error"]["detail"], "Invalid job ID format");
+
+        // Unknown codes and non-error bodies are left alone
+        assert!(localize_body(br#"{"error":{"code":"SOMETHING_NEW","message":"x"}}"#, Locale::Hi).is_none());
+        assert!(localize_body(br#"{"status":"ok"}"#, Locale::Hi).is_none());
+        assert!(localize_body(b"not json", Locale::Hi).is_none());
+    }
+}
@@ -0,0 +1,51 @@
+// ETags for job results - pollers revalidate with If-None-Match and get a 304
+//
+// A finished result never changes except through annotations or score overrides, so a
+// client polling GET /job/{id} mostly re-downloads the same body. The tag is a hash of the
+// serialized response, so it covers everything in it (overrides, locale) and changes
+// whenever any of it does.
+
+use axum::http::HeaderMap;
+use axum::http::header;
+use sha2::{Digest, Sha256};
+
+/// Strong entity tag of a response body (quoted, 128 bits of SHA-256)
+pub fn etag_for(body: &[u8]) -> String {
+    let digest = Sha256::digest(body);
+    let hex: String = digest[..16].iter().map(|b| format!("{:02x}", b)).collect();
+    format!("\"{}\"", hex)
+}
+
+/// Whether the request's If-None-Match already names `etag` (weak comparison, as RFC 9110
+/// asks for If-None-Match; `*` matches any current representation)
+pub fn not_modified(headers: &HeaderMap, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .any(|tag| tag.trim() == "*" || opaque(tag) == opaque(etag))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn test_etag_and_if_none_match() {
+        let etag = etag_for(br#"{"overall_status":"completed"}"#);
+        assert_eq!(etag.len(), 34);
+        assert_ne!(etag, etag_for(br#"{"overall_status":"failed"}"#));
+
+        let mut headers = HeaderMap::new();
+        assert!(!not_modified(&headers, &etag));
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_str(&format!("\"stale\", W/{}", etag)).unwrap());
+        assert!(not_modified(&headers, &etag));
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_static("\"stale\""));
+        assert!(!not_modified(&headers, &etag));
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_static("*"));
+        assert!(not_modified(&headers, &etag));
+    }
+}
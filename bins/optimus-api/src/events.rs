@@ -0,0 +1,211 @@
+// Event bus - outbound webhooks for operational events
+//
+// Workers and the API publish SystemEvents (job_submitted, job_started, job_retried,
+// job_dlq, worker_up, worker_down, worker_draining, queue_paused, queue_resumed) to the `optimus:events`
+// Redis stream, which automation can read directly with XREAD. When EVENT_WEBHOOKS is
+// set, the API also POSTs every matching event as JSON to the configured URLs:
+//
+//   EVENT_WEBHOOKS='[{"url": "https://hooks.example.com/optimus",
+//                     "events": ["job_dlq", "worker_down"], "languages": ["python"]}]'
+//
+// Empty `events`/`languages` match everything. Delivery runs through a consumer group,
+// so each event is posted once even with several API replicas. Up to WEBHOOK_CONCURRENCY
+// (default 8) events are delivered at once, so one slow target doesn't hold up the rest;
+// events may reach a target out of order. Events a replica read but never acknowledged
+// (it died mid-delivery) are claimed by another replica after a minute. A failed delivery
+// is retried with backoff and then dropped (logged and counted in
+// optimus_webhook_deliveries_total).
+
+use crate::metrics;
+use optimus_common::redis;
+use optimus_common::types::{EventKind, Language, SystemEvent};
+use ::redis::aio::ConnectionManager;
+use futures_util::StreamExt;
+use serde::Deserialize;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// Consumer group shared by all API replicas
+const WEBHOOK_GROUP: &str = "optimus-webhooks";
+
+/// Delivery attempts per event and target
+const MAX_ATTEMPTS: u32 = 3;
+
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Events read per batch
+const BATCH: usize = 50;
+
+/// How long an event may stay unacknowledged before another replica claims it; well
+/// above the longest delivery (every attempt timing out, plus backoff)
+const CLAIM_IDLE_MS: u64 = 60_000;
+
+/// Events delivered at once (WEBHOOK_CONCURRENCY, default 8)
+fn concurrency() -> usize {
+    std::env::var("WEBHOOK_CONCURRENCY")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|n| *n > 0)
+        .unwrap_or(8)
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct WebhookTarget {
+    pub url: String,
+    #[serde(default)]
+    pub events: Vec<EventKind>,
+    #[serde(default)]
+    pub languages: Vec<Language>,
+}
+
+impl WebhookTarget {
+    /// Whether the target subscribed to this event
+    pub fn matches(&self, event: &SystemEvent) -> bool {
+        let kind_ok = self.events.is_empty() || self.events.contains(&event.kind);
+        // Events without a language (none today) pass a language filter
+        let language_ok = self.languages.is_empty()
+            || event.language.is_none_or(|language| self.languages.contains(&language));
+        kind_ok && language_ok
+    }
+}
+
+/// Parse the EVENT_WEBHOOKS JSON list
+pub fn parse_targets(spec: &str) -> Result<Vec<WebhookTarget>, String> {
+    let targets: Vec<WebhookTarget> =
+        serde_json::from_str(spec).map_err(|e| format!("EVENT_WEBHOOKS is not a valid target list: {}", e))?;
+    if let Some(target) = targets
+        .iter()
+        .find(|t| !(t.url.starts_with("http://") || t.url.starts_with("https://")))
+    {
+        return Err(format!("Webhook URL '{}' must start with http:// or https://", target.url));
+    }
+    Ok(targets)
+}
+
+/// Load webhook targets from EVENT_WEBHOOKS
+/// Returns Ok(None) when no webhooks are configured
+pub fn from_env() -> Result<Option<Vec<WebhookTarget>>, String> {
+    match optimus_common::secrets::var("EVENT_WEBHOOKS")? {
+        Some(spec) if !spec.trim().is_empty() => parse_targets(&spec).map(Some),
+        _ => Ok(None),
+    }
+}
+
+/// Background task delivering stream events to webhooks
+pub async fn dispatcher_loop(targets: Vec<WebhookTarget>, mut conn: ConnectionManager) {
+    let client = match reqwest::Client::builder().timeout(DELIVERY_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(e) => {
+            error!(error = %e, "Failed to create webhook HTTP client; webhooks disabled");
+            return;
+        }
+    };
+    let consumer = std::env::var("HOSTNAME")
+        .unwrap_or_else(|_| format!("api-{}", &uuid::Uuid::new_v4().to_string()[..8]));
+
+    while let Err(e) = redis::ensure_event_group(&mut conn, WEBHOOK_GROUP).await {
+        warn!(error = %e, "Failed to create webhook consumer group; retrying");
+        tokio::time::sleep(Duration::from_secs(5)).await;
+    }
+    info!(targets = targets.len(), consumer = %consumer, "Webhook dispatcher started");
+
+    let concurrency = concurrency();
+    loop {
+        // Entries abandoned by a replica that died mid-delivery come first
+        let stale = match redis::claim_stale_events(&mut conn, WEBHOOK_GROUP, &consumer, CLAIM_IDLE_MS, BATCH).await {
+            Ok(entries) => entries,
+            Err(e) => {
+                warn!(error = %e, "Failed to claim stale events");
+                Vec::new()
+            }
+        };
+        if !stale.is_empty() {
+            info!(count = stale.len(), "Claimed events left unacknowledged by another replica");
+        }
+        let entries = match redis::read_events(&mut conn, WEBHOOK_GROUP, &consumer, BATCH, 5000).await {
+            Ok(entries) => entries,
+            Err(e) => {
+                warn!(error = %e, "Failed to read event stream");
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                continue;
+            }
+        };
+
+        futures_util::stream::iter(stale.into_iter().chain(entries))
+            .for_each_concurrent(concurrency, |(entry_id, event)| {
+                let (client, targets, mut conn) = (&client, &targets, conn.clone());
+                async move {
+                    match event {
+                        Some(event) => {
+                            let deliveries = targets.iter().filter(|t| t.matches(&event)).map(|t| deliver(client, t, &event));
+                            futures_util::future::join_all(deliveries).await;
+                        }
+                        None => warn!(entry_id = %entry_id, "Skipping malformed event"),
+                    }
+                    if let Err(e) = redis::ack_event(&mut conn, WEBHOOK_GROUP, &entry_id).await {
+                        warn!(entry_id = %entry_id, error = %e, "Failed to acknowledge event");
+                    }
+                }
+            })
+            .await;
+    }
+}
+
+async fn deliver(client: &reqwest::Client, target: &WebhookTarget, event: &SystemEvent) {
+    for attempt in 1..=MAX_ATTEMPTS {
+        let outcome = client.post(&target.url).json(event).send().await;
+        match outcome {
+            Ok(response) if response.status().is_success() => {
+                metrics::record_webhook_delivery("delivered");
+                return;
+            }
+            Ok(response) => {
+                warn!(url = %target.url, kind = ?event.kind, status = %response.status(), attempt, "Webhook rejected event");
+            }
+            Err(e) => {
+                warn!(url = %target.url, kind = ?event.kind, error = %e, attempt, "Webhook delivery failed");
+            }
+        }
+        if attempt < MAX_ATTEMPTS {
+            tokio::time::sleep(Duration::from_millis(500 * 2u64.pow(attempt - 1))).await;
+        }
+    }
+    error!(url = %target.url, kind = ?event.kind, event_id = %event.id, "Dropping event after failed deliveries");
+    metrics::record_webhook_delivery("dropped");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_targets() {
+        let targets = parse_targets(
+            r#"[{"url": "https://a.example/hook", "events": ["job_dlq", "worker_down"]}, {"url": "http://b.example"}]"#,
+        )
+        .unwrap();
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets[0].events, vec![EventKind::JobDlq, EventKind::WorkerDown]);
+        assert!(targets[1].events.is_empty());
+
+        assert!(parse_targets(r#"[{"url": "ftp://a.example"}]"#).is_err());
+        assert!(parse_targets(r#"[{"url": "https://a.example", "events": ["job_exploded"]}]"#).is_err());
+    }
+
+    #[test]
+    fn test_target_filters() {
+        let target = WebhookTarget {
+            url: "https://a.example".to_string(),
+            events: vec![EventKind::JobDlq],
+            languages: vec![Language::Python],
+        };
+        let dlq = |language| SystemEvent { language: Some(language), ..SystemEvent::new(EventKind::JobDlq) };
+
+        assert!(target.matches(&dlq(Language::Python)));
+        assert!(!target.matches(&dlq(Language::Java)));
+        assert!(!target.matches(&SystemEvent::worker(EventKind::WorkerDown, "w1", Language::Python)));
+
+        let all = WebhookTarget { url: target.url.clone(), events: vec![], languages: vec![] };
+        assert!(all.matches(&SystemEvent::worker(EventKind::WorkerDown, "w1", Language::Rust)));
+    }
+}
@@ -1,19 +1,39 @@
 // HTTP route handlers for the Optimus API
 
 use axum::{
-    extract::{State, Path},
-    http::{StatusCode, HeaderMap},
-    response::{IntoResponse, Json},
+    extract::{rejection::JsonRejection, ws::{rejection::WebSocketUpgradeRejection, WebSocketUpgrade}, State, Path, Query},
+    http::{header, StatusCode, HeaderMap},
+    response::{sse::{KeepAlive, Sse}, Html, IntoResponse, Json, Response},
 };
-use optimus_common::types::{JobRequest, Language};
+use optimus_common::types::{
+    Annotation, ComparisonMode, EvaluationPipeline, EventKind, ExecutionResult, GroupScoring, IdempotencyEntry, JobPlace, JobRequest,
+    JobStatus, Language, LeaderboardEntry, LeaderboardTag, NormalizationOptions, OutputMode, Sanitizer, ScoreOverride, SourceFormat, ScoreScaling, StatusPolicy,
+    SystemEvent,
+};
+use optimus_common::i18n::{Locale, VerdictText};
+use optimus_common::capabilities;
+use optimus_common::limits;
 use optimus_common::redis;
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::collections::BTreeMap;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
 use uuid::Uuid;
 use tracing::{info, error, warn};
 
 use crate::AppState;
+use crate::auth::{Admin, Principal};
+use crate::canary;
+use crate::compare;
+use crate::etag;
+use crate::longpoll;
 use crate::metrics;
+use crate::quota;
+use crate::redis_pool;
+use crate::result_cache::CachedResult;
+use crate::retention;
+use crate::stream;
+use crate::validation;
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct SubmitRequest {
@@ -22,6 +42,66 @@ pub struct SubmitRequest {
     pub test_cases: Vec<TestCaseInput>,
     #[serde(default = "default_timeout")]
     pub timeout_ms: u64,
+    /// "text" (default) or "binary" - binary expects base64 expected_output
+    #[serde(default)]
+    pub output_mode: OutputMode,
+    /// Default comparison for all test cases: "ordered", "unordered" or "strict"
+    /// (omitted = the language's default, usually "ordered")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comparison: Option<ComparisonMode>,
+    /// {"ignore_trailing_whitespace", "ignore_case", "collapse_whitespace", "tokens", ...}
+    /// - how outputs are normalized before the comparison (all off by default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub normalization: Option<NormalizationOptions>,
+    /// Declarative normalizers + comparator; overrides `comparison` and `normalization` when set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evaluation: Option<EvaluationPipeline>,
+    /// "any_pass", "all_pass" or {"threshold": percent}; enables Passed/PartiallyPassed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_policy: Option<StatusPolicy>,
+    /// {"max_points": 100, "rounding": "half_up", "decimals": 0}
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scoring: Option<ScoreScaling>,
+    /// {"problem_id": "...", "contest_id": "...", "participant": "..."} - rank the result
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leaderboard: Option<LeaderboardTag>,
+    /// Run on a GPU worker (the language needs a `gpu` block in languages.json)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub gpu: bool,
+    /// Capability labels the worker must offer (the language's `capabilities`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub requirements: Vec<String>,
+    /// 0-9, higher first; honoured when the dispatcher schedules jobs
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub priority: u8,
+    /// Checker source in the submission's language (special judge); exit 0 passes a test,
+    /// exit 1 fails it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checker: Option<String>,
+    /// Untimed run before the timed tests (warms the file cache, not the JIT); unset: the
+    /// language's `warmup` flag
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warmup: Option<bool>,
+    /// gcc sanitizers to build a C program with ("address", "undefined")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sanitizers: Vec<Sanitizer>,
+    /// "script" (default) or "notebook" - source_code is a Jupyter .ipynb (Python only)
+    #[serde(default)]
+    pub source_format: SourceFormat,
+    /// Interactor source in the submission's language (interactive problems); it talks to
+    /// the program over stdin/stdout, then exits 0 to pass the test or 1 to fail it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interactor: Option<String>,
+    /// How tests sharing a group_id are scored: "all_or_nothing" (default) or "sum"
+    #[serde(default)]
+    pub group_scoring: GroupScoring,
+    /// How particular groups are scored, by group id, where they differ from group_scoring
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub subtask_scoring: std::collections::BTreeMap<u32, GroupScoring>,
+}
+
+fn is_zero(value: &u8) -> bool {
+    *value == 0
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -30,6 +110,12 @@ pub struct TestCaseInput {
     pub expected_output: String,
     #[serde(default = "default_weight")]
     pub weight: u32,
+    /// Overrides the job-level comparison for this test case
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comparison: Option<ComparisonMode>,
+    /// Subtask the test belongs to; a group is scored per group_scoring
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_id: Option<u32>,
 }
 
 fn default_timeout() -> u64 {
@@ -45,14 +131,6 @@ pub struct SubmitResponse {
     pub job_id: String,
 }
 
-// Safety limits (per specification)
-const MAX_TEST_CASES: usize = 100;
-const MAX_SOURCE_CODE_SIZE: usize = 256_000; // 256 KB
-const MAX_STDIN_SIZE: usize = 64_000; // 64 KB per test case input
-const MAX_EXPECTED_OUTPUT_SIZE: usize = 64_000; // 64 KB per expected output
-const MAX_TIMEOUT_MS: u64 = 60_000; // 60 seconds
-const MIN_TIMEOUT_MS: u64 = 1; // 1 millisecond
-
 #[derive(Debug, Serialize)]
 pub struct ErrorResponse {
     pub error: ErrorDetail,
@@ -64,22 +142,171 @@ pub struct ErrorDetail {
     pub message: String,
 }
 
+/// Value stored under the idempotency key to detect payload changes
+/// With encryption at rest enabled only a digest is kept, never the source code itself
+fn idempotency_fingerprint(payload: &SubmitRequest) -> String {
+    let json = serde_json::to_string(payload).unwrap_or_default();
+    match optimus_common::crypto::global_cipher() {
+        Some(_) => format!("sha256:{}", optimus_common::crypto::digest(&json)),
+        None => json,
+    }
+}
+
+/// How long Idempotency-Keys are kept (IDEMPOTENCY_TTL_SECS, default 24 hours)
+fn idempotency_ttl_secs() -> u64 {
+    static TTL: OnceLock<u64> = OnceLock::new();
+    *TTL.get_or_init(|| {
+        std::env::var("IDEMPOTENCY_TTL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|&secs| secs > 0)
+            .unwrap_or(86400)
+    })
+}
+
+/// Minimum gap between a submitter's submissions to one problem
+/// (SUBMISSION_COOLDOWN_SECS, default 0 = no cool-down)
+fn submission_cooldown_secs() -> u64 {
+    static COOLDOWN: OnceLock<u64> = OnceLock::new();
+    *COOLDOWN.get_or_init(|| {
+        std::env::var("SUBMISSION_COOLDOWN_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0)
+    })
+}
+
+/// Queue submissions on the dispatcher's intake instead of the worker queues
+/// (DISPATCHER_ENABLED=true; needs a running optimus-dispatcher)
+fn dispatcher_enabled() -> bool {
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+    *ENABLED.get_or_init(|| {
+        std::env::var("DISPATCHER_ENABLED")
+            .map(|v| v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false)
+    })
+}
+
+/// Who a submission's cool-down is kept for: the API key, which the client can't change, or
+/// for anonymous submissions the leaderboard participant they name
+fn cooldown_submitter(principal: Option<&Principal>, participant: &str) -> String {
+    match principal {
+        Some(principal) => format!("key:{}", principal.name),
+        None => format!("participant:{}", participant),
+    }
+}
+
+/// 429 for a submission inside its cool-down, with the seconds left as Retry-After
+fn cooldown_refusal(problem_id: &str, cooldown_secs: u64, wait_secs: u64) -> Response {
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(header::RETRY_AFTER, wait_secs.to_string())],
+        Json(serde_json::json!({
+            "error": {
+                "code": "SUBMISSION_COOLDOWN",
+                "message": format!(
+                    "Submissions to '{}' are limited to one every {}s; retry in {}s",
+                    problem_id, cooldown_secs, wait_secs
+                ),
+                "retry_after_secs": wait_secs,
+            }
+        })),
+    ).into_response()
+}
+
+/// Tenant a submission is filed under (retention, fair scheduling): an admin key may name one
+/// with X-Tenant-ID, any other key is its own tenant, and anonymous submissions share the
+/// default tenant. Err carries the rejected tenant id and why.
+fn submission_tenant(principal: Option<&Principal>, headers: &HeaderMap) -> Result<String, (String, String)> {
+    let tenant = match principal {
+        Some(principal) if principal.admin => headers
+            .get("x-tenant-id")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or(&principal.name)
+            .to_string(),
+        Some(principal) => principal.name.clone(),
+        None => retention::DEFAULT_TENANT.to_string(),
+    };
+    match retention::validate_tenant(&tenant) {
+        Ok(()) => Ok(tenant),
+        Err(message) => Err((tenant, message)),
+    }
+}
+
 /// POST /execute - Submit a job for execution
 /// 
 /// Supports idempotency via Idempotency-Key header
 /// - Same key + same payload → returns same job_id
 /// - Same key + different payload → returns 409 Conflict
+///
+/// With SUBMISSION_COOLDOWN_SECS set, a leaderboard-tagged submission to the same problem
+/// from the same API key (or, without a key, the same participant) within the cool-down
+/// gets 429 with the seconds left (Retry-After)
+///
+/// With RATE_LIMIT_PER_MINUTE or CPU_QUOTA_SECONDS_PER_DAY set, an API key (X-Api-Key)
+/// over its limit gets 429 with Retry-After as well
+///
+/// An unknown API key gets 401; a priority above 0 needs an admin key (403)
 pub async fn submit_job(
     State(state): State<Arc<AppState>>,
     headers: HeaderMap,
-    Json(payload): Json<SubmitRequest>,
+    payload: Result<Json<SubmitRequest>, JsonRejection>,
 ) -> impl IntoResponse {
+    let Json(payload) = match payload {
+        Ok(payload) => payload,
+        Err(rejection) => {
+            let rejection = validation::json_rejection(rejection);
+            metrics::record_job_rejected(&rejection.reason());
+            warn!(code = rejection.code, reason = %rejection.message, "Rejected: Invalid request body");
+            return rejection.into_response();
+        }
+    };
+
     // Extract idempotency key if provided
     let idempotency_key = headers
         .get("idempotency-key")
         .and_then(|v| v.to_str().ok())
         .map(|s| s.to_string());
     
+    // The key decides the tenant and who may raise priority; request headers can't claim either
+    let principal = match state.keys.authenticate(&headers) {
+        Ok(principal) => principal,
+        Err(refusal) => {
+            metrics::record_job_rejected("unauthorized");
+            warn!("Rejected: Unknown API key");
+            return refusal.into_response();
+        }
+    };
+    let tenant = match submission_tenant(principal.as_ref(), &headers) {
+        Ok(tenant) => tenant,
+        Err((tenant, message)) => {
+            metrics::record_job_rejected("invalid_tenant");
+            warn!(tenant = %tenant, "Rejected: Invalid tenant id");
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INVALID_TENANT".to_string(),
+                        message,
+                    },
+                }),
+            ).into_response();
+        }
+    };
+    if payload.priority > 0 && !principal.as_ref().is_some_and(|p| p.admin) {
+        metrics::record_job_rejected("priority_requires_admin");
+        warn!(priority = payload.priority, "Rejected: Priority without an admin key");
+        return (
+            StatusCode::FORBIDDEN,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "PRIORITY_REQUIRES_ADMIN".to_string(),
+                    message: "Only an admin API key may submit with a priority above 0".to_string(),
+                },
+            }),
+        ).into_response();
+    }
+    
     // 0. Validate language is enabled
     if !state.language_registry.is_enabled(payload.language) {
         metrics::record_job_rejected("language_not_supported");
@@ -103,8 +330,8 @@ pub async fn submit_job(
     
     // Handle idempotency if key is provided
     if let Some(ref key) = idempotency_key {
-        let mut conn = state.redis.clone();
-        let idempotency_redis_key = format!("optimus:idempotency:{}", key);
+        let mut conn = state.redis.conn();
+        let idempotency_redis_key = redis::idempotency_key(key);
         
         // Check if this key was used before using redis commands
         match ::redis::cmd("GET")
@@ -114,7 +341,7 @@ pub async fn submit_job(
         {
             Ok(Some(stored_data)) => {
                 // Key exists - check if payload matches
-                let payload_json = serde_json::to_string(&payload).unwrap_or_default();
+                let payload_json = idempotency_fingerprint(&payload);
                 
                 if let Ok(stored) = serde_json::from_str::<serde_json::Value>(&stored_data) {
                     if let Some(stored_payload) = stored.get("payload").and_then(|p| p.as_str()) {
@@ -167,160 +394,84 @@ pub async fn submit_job(
     let job_id = Uuid::new_v4();
     
     // Serialize payload early for idempotency check (before moving fields)
-    let payload_json_for_idempotency = serde_json::to_string(&payload).unwrap_or_default();
+    let payload_json_for_idempotency = idempotency_fingerprint(&payload);
     
     // Safety checks - validate request before queueing
-    
-    // 1. Check test case count
-    if payload.test_cases.is_empty() {
-        metrics::record_job_rejected("no_test_cases");
-        error!(job_id = %job_id, "Rejected: No test cases provided");
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: ErrorDetail {
-                    code: "NO_TEST_CASES".to_string(),
-                    message: "At least one test case is required".to_string(),
-                },
-            }),
-        ).into_response();
+    if let Err(rejection) = validation::validate_submission(&payload) {
+        metrics::record_job_rejected(&rejection.reason());
+        error!(job_id = %job_id, code = rejection.code, reason = %rejection.message, "Rejected: Invalid submission");
+        return rejection.into_response();
     }
-    
-    if payload.test_cases.len() > MAX_TEST_CASES {
-        metrics::record_job_rejected("too_many_test_cases");
-        error!(
-            job_id = %job_id,
-            test_cases = payload.test_cases.len(),
-            limit = MAX_TEST_CASES,
-            "Rejected: Too many test cases"
-        );
+
+    // GPU jobs need a language with GPU workers; some languages run every job on one
+    let gpu_config = state.language_registry.gpu(payload.language);
+    if payload.gpu && gpu_config.is_none() {
+        metrics::record_job_rejected("gpu_not_supported");
+        warn!(language = %payload.language, "Rejected: GPU requested for a language without GPU workers");
         return (
-            StatusCode::BAD_REQUEST,
+            StatusCode::UNPROCESSABLE_ENTITY,
             Json(ErrorResponse {
                 error: ErrorDetail {
-                    code: "TOO_MANY_TEST_CASES".to_string(),
-                    message: format!(
-                        "Maximum {} test cases allowed, got {}",
-                        MAX_TEST_CASES,
-                        payload.test_cases.len()
-                    ),
+                    code: "GPU_NOT_SUPPORTED".to_string(),
+                    message: format!("Language '{}' has no GPU workers", payload.language),
                 },
             }),
         ).into_response();
     }
-    
-    // 2. Check source code size
-    if payload.source_code.len() > MAX_SOURCE_CODE_SIZE {
-        metrics::record_job_rejected("source_code_too_large");
-        error!(
-            job_id = %job_id,
-            size = payload.source_code.len(),
-            limit = MAX_SOURCE_CODE_SIZE,
-            "Rejected: Source code too large"
-        );
-        return (
-            StatusCode::PAYLOAD_TOO_LARGE,
-            Json(ErrorResponse {
-                error: ErrorDetail {
-                    code: "SOURCE_CODE_TOO_LARGE".to_string(),
-                    message: format!(
-                        "Maximum {} bytes allowed, got {} bytes",
-                        MAX_SOURCE_CODE_SIZE,
-                        payload.source_code.len()
-                    ),
-                },
-            }),
-        ).into_response();
+    let mut requirements = payload.requirements.clone();
+    if payload.gpu || gpu_config.is_some_and(|config| config.required) {
+        requirements.push(capabilities::GPU.to_string());
     }
-    
-    // 3. Validate source code is not empty
-    if payload.source_code.trim().is_empty() {
-        metrics::record_job_rejected("empty_source_code");
-        error!(job_id = %job_id, "Rejected: Empty source code");
+    let requirements = capabilities::normalize(&requirements);
+
+    // Only labels some worker of the language can offer; others would wait forever
+    let requirable = state.language_registry.requirable_labels(payload.language);
+    if let Some(label) = requirements.iter().find(|label| !requirable.contains(label)) {
+        metrics::record_job_rejected("requirement_not_supported");
+        warn!(language = %payload.language, capability = %label, "Rejected: requirement no worker offers");
         return (
-            StatusCode::BAD_REQUEST,
+            StatusCode::UNPROCESSABLE_ENTITY,
             Json(ErrorResponse {
                 error: ErrorDetail {
-                    code: "EMPTY_SOURCE_CODE".to_string(),
-                    message: "Source code cannot be empty".to_string(),
+                    code: "REQUIREMENT_NOT_SUPPORTED".to_string(),
+                    message: format!("Language '{}' has no workers with capability '{}'", payload.language, label),
                 },
             }),
         ).into_response();
     }
-    
-    // 4. Check test case input/output sizes
-    for (idx, tc) in payload.test_cases.iter().enumerate() {
-        if tc.input.len() > MAX_STDIN_SIZE {
-            metrics::record_job_rejected("test_case_input_too_large");
-            error!(
-                job_id = %job_id,
-                test_case = idx + 1,
-                size = tc.input.len(),
-                limit = MAX_STDIN_SIZE,
-                "Rejected: Test case input too large"
-            );
-            return (
-                StatusCode::PAYLOAD_TOO_LARGE,
-                Json(ErrorResponse {
-                    error: ErrorDetail {
-                        code: "TEST_CASE_INPUT_TOO_LARGE".to_string(),
-                        message: format!(
-                            "Test case {} input exceeds {} bytes",
-                            idx + 1,
-                            MAX_STDIN_SIZE
-                        ),
-                    },
-                }),
-            ).into_response();
-        }
-        
-        if tc.expected_output.len() > MAX_EXPECTED_OUTPUT_SIZE {
-            metrics::record_job_rejected("test_case_output_too_large");
-            error!(
-                job_id = %job_id,
-                test_case = idx + 1,
-                size = tc.expected_output.len(),
-                limit = MAX_EXPECTED_OUTPUT_SIZE,
-                "Rejected: Test case expected output too large"
-            );
-            return (
-                StatusCode::PAYLOAD_TOO_LARGE,
-                Json(ErrorResponse {
-                    error: ErrorDetail {
-                        code: "TEST_CASE_OUTPUT_TOO_LARGE".to_string(),
-                        message: format!(
-                            "Test case {} expected output exceeds {} bytes",
-                            idx + 1,
-                            MAX_EXPECTED_OUTPUT_SIZE
-                        ),
-                    },
-                }),
-            ).into_response();
+
+    // Cool-down per submitter and problem, which only leaderboard-tagged submissions name
+    let cooldown_secs = submission_cooldown_secs();
+    let cooldown = payload
+        .leaderboard
+        .as_ref()
+        .filter(|_| cooldown_secs > 0)
+        .map(|tag| (tag.problem_id.clone(), cooldown_submitter(principal.as_ref(), &tag.participant)));
+    if let Some((problem_id, submitter)) = &cooldown {
+        match redis::claim_submission_cooldown(&mut state.redis.conn(), problem_id, submitter, cooldown_secs).await {
+            Ok(None) => {}
+            Ok(Some(wait_secs)) => {
+                metrics::record_job_rejected("submission_cooldown");
+                warn!(problem_id = %problem_id, submitter = %submitter, wait_secs, "Rejected: Submission cool-down");
+                return cooldown_refusal(problem_id, cooldown_secs, wait_secs);
+            }
+            // Like idempotency, the cool-down is skipped rather than failing the submission
+            Err(e) => error!(error = %e, "Failed to check submission cool-down"),
         }
     }
-    
-    // 5. Validate timeout
-    if payload.timeout_ms < MIN_TIMEOUT_MS || payload.timeout_ms > MAX_TIMEOUT_MS {
-        metrics::record_job_rejected("invalid_timeout");
-        error!(
-            job_id = %job_id,
-            timeout_ms = payload.timeout_ms,
-            "Rejected: Invalid timeout"
-        );
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: ErrorDetail {
-                    code: "INVALID_TIMEOUT".to_string(),
-                    message: format!(
-                        "Timeout must be between {}ms and {}ms",
-                        MIN_TIMEOUT_MS,
-                        MAX_TIMEOUT_MS
-                    ),
-                },
-            }),
-        ).into_response();
-    }
+
+    // Per-API-key rate limit and daily CPU quota, after the cool-down so a submission the
+    // cool-down refuses doesn't use up a rate token
+    let api_key_id = quota::api_key_id(principal.as_ref());
+    let rate_token = match quota::check(&mut state.redis.conn(), &state.quota, &api_key_id).await {
+        Ok(rate_token) => rate_token,
+        Err(refusal) => {
+            metrics::record_job_rejected(refusal.reason());
+            warn!(api_key_id = %api_key_id, reason = refusal.reason(), "Rejected: Per-key limit");
+            release_cooldown(&mut state.redis.conn(), cooldown.as_ref()).await;
+            return refusal.into_response();
+        }
+    };
 
     // Convert test case inputs to internal format
     let test_cases: Vec<optimus_common::types::TestCase> = payload
@@ -332,6 +483,8 @@ pub async fn submit_job(
             input: tc.input,
             expected_output: tc.expected_output,
             weight: tc.weight,
+            comparison: tc.comparison,
+            group_id: tc.group_id,
         })
         .collect();
 
@@ -342,39 +495,92 @@ pub async fn submit_job(
         source_code: payload.source_code,
         test_cases,
         timeout_ms: payload.timeout_ms,
-        metadata: optimus_common::types::JobMetadata::default(),
+        metadata: optimus_common::types::JobMetadata {
+            enqueued_at: Some(chrono::Utc::now()),
+            requirements,
+            priority: payload.priority,
+            tenant: Some(tenant.clone()),
+            owner: principal.map(|principal| principal.name),
+            ..Default::default()
+        },
+        normalization: payload.normalization.unwrap_or_default(),
+        output_mode: payload.output_mode,
+        comparison: payload.comparison,
+        evaluation: payload.evaluation,
+        status_policy: payload.status_policy,
+        scoring: payload.scoring,
+        leaderboard: payload.leaderboard,
+        checker: payload.checker,
+        warmup: payload.warmup,
+        sanitizers: payload.sanitizers,
+        source_format: payload.source_format,
+        interactor: payload.interactor,
+        group_scoring: payload.group_scoring,
+        subtask_scoring: payload.subtask_scoring,
     };
 
-    // Push to Redis queue
-    let mut conn = state.redis.clone();
-    match redis::push_job(&mut conn, &job).await {
-        Ok(_) => {
-            // Store idempotency key if provided
-            if let Some(ref key) = idempotency_key {
-                let idempotency_redis_key = format!("optimus:idempotency:{}", key);
-                let idempotency_data = serde_json::json!({
-                    "job_id": job_id.to_string(),
-                    "payload": payload_json_for_idempotency,
-                    "created_at": chrono::Utc::now().to_rfc3339(),
-                });
-                
-                // Store with 24 hour TTL using SETEX
-                let mut conn_for_idempotency = state.redis.clone();
-                if let Err(e) = ::redis::cmd("SETEX")
-                    .arg(&idempotency_redis_key)
-                    .arg(86400) // 24 hours
-                    .arg(idempotency_data.to_string())
-                    .query_async::<_, ()>(&mut conn_for_idempotency)
-                    .await
-                {
-                    error!(
-                        error = %e,
-                        idempotency_key = %key,
-                        "Failed to store idempotency key (job already queued)"
-                    );
-                    // Don't fail the request - job is already queued
+    // Queue the job together with its status, copy, idempotency key and indexes
+    let recent = optimus_common::types::RecentJob {
+        job_id,
+        language: job.language,
+        test_cases: job.test_cases.len(),
+        submitted_at: chrono::Utc::now(),
+    };
+    let submission = redis::Submission {
+        job: &job,
+        idempotency: idempotency_key.as_deref().map(|key| {
+            let data = serde_json::json!({
+                "job_id": job_id.to_string(),
+                "payload": payload_json_for_idempotency,
+                "created_at": chrono::Utc::now().to_rfc3339(),
+            });
+            (key, data.to_string())
+        }),
+        idempotency_ttl_secs: idempotency_ttl_secs(),
+        // Index for the retention janitor (only when retention is enabled)
+        retention_tenant: state.retention.policy.is_some().then_some(tenant.as_str()),
+        recent: &recent,
+        via_intake: dispatcher_enabled(),
+    };
+    let mut conn = state.redis.conn();
+    match redis::enqueue_submission(&mut conn, &submission).await {
+        Ok(redis::Enqueued::IdempotencyKeyTaken(stored)) => {
+            release_cooldown(&mut conn, cooldown.as_ref()).await;
+            quota::refund(&mut conn, rate_token.as_ref()).await;
+            // A concurrent request with the same key won the race; answer as the check above would
+            let stored: serde_json::Value = serde_json::from_str(&stored).unwrap_or_default();
+            let same_payload = stored.get("payload").and_then(|p| p.as_str()) == Some(payload_json_for_idempotency.as_str());
+            match stored.get("job_id").and_then(|j| j.as_str()).filter(|_| same_payload) {
+                Some(existing_job_id) => {
+                    info!(job_id = %existing_job_id, "Idempotent request - returning existing job_id");
+                    (
+                        StatusCode::ACCEPTED,
+                        Json(SubmitResponse {
+                            job_id: existing_job_id.to_string(),
+                        }),
+                    ).into_response()
+                }
+                None => {
+                    metrics::record_job_rejected("idempotency_conflict");
+                    (
+                        StatusCode::CONFLICT,
+                        Json(ErrorResponse {
+                            error: ErrorDetail {
+                                code: "IDEMPOTENCY_CONFLICT".to_string(),
+                                message: "Same idempotency key used with different payload".to_string(),
+                            },
+                        }),
+                    ).into_response()
                 }
             }
+        }
+        Ok(redis::Enqueued::Queued) => {
+            quota::record_owner(&mut conn, &state.quota, job_id, &api_key_id).await;
+
+            // Announce on the event stream (best-effort)
+            if let Err(e) = redis::publish_event(&mut state.redis.conn(), &SystemEvent::job(EventKind::JobSubmitted, &job)).await {
+                warn!(error = %e, job_id = %job_id, "Failed to publish job_submitted event");
+            }
             
             // Record metrics
             metrics::record_job_submitted(&job.language.to_string());
@@ -397,6 +603,8 @@ pub async fn submit_job(
         }
         Err(e) => {
             error!(job_id = %job_id, error = %e, "Failed to queue job");
+            release_cooldown(&mut conn, cooldown.as_ref()).await;
+            quota::refund(&mut conn, rate_token.as_ref()).await;
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(ErrorResponse {
@@ -410,6 +618,14 @@ pub async fn submit_job(
     }
 }
 
+/// Give back a cool-down claimed by a submission that was not queued (best-effort)
+async fn release_cooldown(conn: &mut impl redis::Connection, cooldown: Option<&(String, String)>) {
+    let Some((problem_id, submitter)) = cooldown else { return };
+    if let Err(e) = redis::release_submission_cooldown(conn, problem_id, submitter).await {
+        warn!(problem_id = %problem_id, submitter = %submitter, error = %e, "Failed to release submission cool-down");
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct HealthResponse {
     pub status: String,
@@ -421,8 +637,7 @@ pub struct HealthResponse {
 /// GET /metrics - Prometheus metrics endpoint
 pub async fn metrics_handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
     // Update queue depth metrics before rendering
-    let mut conn = state.redis.clone();
-    metrics::update_queue_depths(&mut conn).await;
+    metrics::update_queue_depths(&state.redis).await;
     
     let metrics_text = metrics::render_metrics();
     (
@@ -454,7 +669,7 @@ pub async fn readiness_check(State(state): State<Arc<AppState>>) -> impl IntoRes
     
     // Test Redis connectivity with PING
     let redis_ok = match ::redis::cmd("PING")
-        .query_async::<_, String>(&mut state.redis.clone())
+        .query_async::<_, String>(&mut state.redis.conn())
         .await
     {
         Ok(_) => true,
@@ -478,10 +693,66 @@ pub async fn readiness_check(State(state): State<Arc<AppState>>) -> impl IntoRes
     }
 }
 
+#[derive(Debug, Serialize)]
+pub struct JobResultResponse {
+    #[serde(flatten)]
+    pub result: ExecutionResult,
+    /// Reviewer notes attached via POST /job/{id}/annotations
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<Annotation>,
+    /// Manual correction in effect; score/overall_status above remain the machine verdict
+    #[serde(rename = "override", skip_serializing_if = "Option::is_none")]
+    pub score_override: Option<ScoreOverride>,
+    /// User-facing verdict in the Accept-Language locale (absent for cancelled jobs)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verdict: Option<VerdictText>,
+    /// Verdict of each test, in the order of `results`
+    pub test_verdicts: Vec<TestVerdict>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TestVerdict {
+    pub test_id: u32,
+    #[serde(flatten)]
+    pub verdict: VerdictText,
+}
+
+impl JobResultResponse {
+    /// The stored result with its verdicts explained in `locale`
+    pub fn new(result: ExecutionResult, annotations: Vec<Annotation>, score_override: Option<ScoreOverride>, locale: Locale) -> Self {
+        let verdict = result.verdict().map(|v| VerdictText::new(v, locale));
+        let test_verdicts = result
+            .results
+            .iter()
+            .map(|t| TestVerdict { test_id: t.test_id, verdict: VerdictText::new(t.verdict(), locale) })
+            .collect();
+        Self { result, annotations, score_override, verdict, test_verdicts }
+    }
+}
+
+/// Locale picked from a request's Accept-Language header
+pub fn request_locale(headers: &HeaderMap) -> Locale {
+    Locale::negotiate(headers.get(header::ACCEPT_LANGUAGE).and_then(|v| v.to_str().ok()))
+}
+
+/// Content-Language and Vary headers of a localized response
+pub fn locale_headers(locale: Locale) -> [(header::HeaderName, &'static str); 2] {
+    [(header::CONTENT_LANGUAGE, locale.tag()), (header::VARY, "Accept-Language")]
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResultQuery {
+    /// Hold the request up to this long for the result (capped at LONG_POLL_MAX_MS)
+    #[serde(default)]
+    pub wait_ms: u64,
+}
+
 /// GET /job/{job_id} - Query execution result
 pub async fn get_job_result(
     State(state): State<Arc<AppState>>,
     Path(job_id): Path<String>,
+    Query(query): Query<ResultQuery>,
+    headers: HeaderMap,
 ) -> impl IntoResponse {
     // Parse job ID
     let job_uuid = match Uuid::parse_str(&job_id) {
@@ -499,18 +770,95 @@ pub async fn get_job_result(
         }
     };
 
-    // Fetch result from Redis
-    let mut conn = state.redis.clone();
-    match redis::get_result(&mut conn, &job_uuid).await {
+    if let Some(response) = deleted_job_response(&state, &job_uuid).await {
+        return response;
+    }
+
+    // A completed result served moments ago is served again without reading Redis
+    if let Some(cached) = state.result_cache.get(&job_uuid) {
+        return job_result_response(&job_id, &cached, &headers);
+    }
+
+    // Fetch result from Redis, waiting for it if the client asked to long-poll. Subscribe
+    // first so a result stored between the read and the wait still wakes us.
+    let wait = Duration::from_millis(query.wait_ms.min(longpoll::max_wait_ms()));
+    let deadline = tokio::time::Instant::now() + wait;
+    let mut completions = (!wait.is_zero()).then(|| state.completions.subscribe());
+    let mut conn = state.redis.conn();
+    let mut fetched = redis::get_result(&mut conn, &job_uuid).await;
+    // No result and no status: never submitted or expired, so there is nothing to wait for
+    if matches!(fetched, Ok(None)) {
+        match redis::get_job_status(&mut conn, &job_uuid).await {
+            Ok(Some(_)) => {}
+            Ok(None) => {
+                return (
+                    StatusCode::NOT_FOUND,
+                    Json(ErrorResponse {
+                        error: ErrorDetail {
+                            code: "JOB_NOT_FOUND".to_string(),
+                            message: format!("Job {} is unknown or has expired", job_id),
+                        },
+                    }),
+                ).into_response();
+            }
+            Err(e) => fetched = Err(e),
+        }
+    }
+    if let Some(rx) = completions.as_mut() {
+        while matches!(fetched, Ok(None)) && tokio::time::Instant::now() < deadline {
+            longpoll::wait_for(rx, job_uuid, deadline).await;
+            fetched = redis::get_result(&mut conn, &job_uuid).await;
+        }
+    }
+    match fetched {
         Ok(Some(result)) => {
+            // Refuse to serve verdicts that fail integrity checks
+            if let Some(signer) = state.result_signer {
+                if let Err(e) = signer.verify(&result) {
+                    error!(job_id = %job_id, error = %e, "Result signature verification failed");
+                    return (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(ErrorResponse {
+                            error: ErrorDetail {
+                                code: "RESULT_INTEGRITY_ERROR".to_string(),
+                                message: format!("Stored result failed verification: {}", e),
+                            },
+                        }),
+                    ).into_response();
+                }
+            }
             info!(job_id = %job_id, status = ?result.overall_status, "Job result retrieved");
-            // Result exists - return it
-            (StatusCode::OK, Json(result)).into_response()
+            // Annotations are supplementary; the verdict is served even if they can't be read
+            // (but not cached without them)
+            let mut cacheable = true;
+            let annotations = redis::get_annotations(&mut conn, &job_uuid).await.unwrap_or_else(|e| {
+                warn!(job_id = %job_id, error = %e, "Failed to read annotations");
+                cacheable = false;
+                Vec::new()
+            });
+            // An override changes the final verdict, so failing to read it must not look like "none"
+            let score_override = match redis::get_score_overrides(&mut conn, &job_uuid).await {
+                Ok(mut overrides) => overrides.pop(),
+                Err(e) => {
+                    error!(job_id = %job_id, error = %e, "Failed to read score overrides");
+                    return (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(ErrorResponse {
+                            error: ErrorDetail {
+                                code: "INTERNAL_ERROR".to_string(),
+                                message: format!("Failed to read score overrides: {}", e),
+                            },
+                        }),
+                    ).into_response();
+                }
+            };
+            let entry = CachedResult { result, annotations, score_override };
+            let entry = if cacheable { state.result_cache.insert(job_uuid, entry) } else { Arc::new(entry) };
+            job_result_response(&job_id, &entry, &headers)
         }
         Ok(None) => {
-            info!(job_id = %job_id, "Job still pending or not found");
-            // Result not found - job may still be queued/running (or doesn't exist)
-            // We return 202 optimistically to avoid expensive queue scans
+            info!(job_id = %job_id, "Job still pending");
+            // The job exists (its status key was checked) but is still queued or running
             (
                 StatusCode::ACCEPTED,
                 Json(serde_json::json!({
@@ -535,6 +883,29 @@ pub async fn get_job_result(
     }
 }
 
+/// A completed job's result, or 304 if the poller already has this version
+fn job_result_response(job_id: &str, entry: &CachedResult, headers: &HeaderMap) -> Response {
+    let locale = request_locale(headers);
+    let response = JobResultResponse::new(entry.result.clone(), entry.annotations.clone(), entry.score_override.clone(), locale);
+    let body = match serde_json::to_vec(&response) {
+        Ok(body) => body,
+        Err(e) => {
+            error!(job_id = %job_id, error = %e, "Failed to serialize job result");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let tag = etag::etag_for(&body);
+    if etag::not_modified(headers, &tag) {
+        return (StatusCode::NOT_MODIFIED, locale_headers(locale), [(header::ETAG, tag)]).into_response();
+    }
+    (
+        StatusCode::OK,
+        locale_headers(locale),
+        [(header::ETAG, tag), (header::CONTENT_TYPE, "application/json".to_string())],
+        body,
+    ).into_response()
+}
+
 #[derive(Debug, Serialize)]
 pub struct JobDebugInfo {
     pub job_id: String,
@@ -545,16 +916,32 @@ pub struct JobDebugInfo {
     pub in_main_queue: bool,
     pub in_retry_queue: bool,
     pub in_dlq: bool,
+    /// The list holding the job (absent while it runs or once it left the queues)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub queue: Option<String>,
     pub result: Option<optimus_common::types::ExecutionResult>,
+    /// "valid" or the verification error (only when signing is enabled)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature_status: Option<String>,
 }
 
-/// GET /job/{job_id}/debug - Detailed debugging information for job
-/// Shows retry attempts, queue status, and failure reasons
-pub async fn get_job_debug(
+#[derive(Debug, Serialize)]
+pub struct JobProgressResponse {
+    pub job_id: String,
+    /// "pending", "running", or the final job status once a result exists
+    pub status: String,
+    pub completed_tests: usize,
+    /// Unknown until the first test finishes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_tests: Option<usize>,
+    pub tests: Vec<optimus_common::types::TestProgress>,
+}
+
+/// GET /job/{job_id}/progress - Per-test progress while a job executes
+pub async fn get_job_progress(
     State(state): State<Arc<AppState>>,
     Path(job_id): Path<String>,
 ) -> impl IntoResponse {
-    // Parse job ID
     let job_uuid = match Uuid::parse_str(&job_id) {
         Ok(id) => id,
         Err(_) => {
@@ -570,148 +957,111 @@ pub async fn get_job_debug(
         }
     };
 
-    let mut conn = state.redis.clone();
-    
-    // Fetch result from Redis
-    let result = match redis::get_result(&mut conn, &job_uuid).await {
-        Ok(result) => result,
+    if let Some(response) = deleted_job_response(&state, &job_uuid).await {
+        return response;
+    }
+
+    let mut conn = state.redis.conn();
+    let tests = match redis::get_progress(&mut conn, &job_uuid).await {
+        Ok(tests) => tests,
         Err(e) => {
-            error!(job_id = %job_id, error = %e, "Failed to fetch job result");
+            error!(job_id = %job_id, error = %e, "Failed to fetch job progress");
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(ErrorResponse {
                     error: ErrorDetail {
                         code: "INTERNAL_ERROR".to_string(),
-                        message: format!("Failed to query job: {}", e),
+                        message: format!("Failed to query job progress: {}", e),
                     },
                 }),
             ).into_response();
         }
     };
-    
-    // Check all queues for this job (search all languages)
-    let mut in_main_queue = false;
-    let mut in_retry_queue = false;
-    let mut in_dlq = false;
-    let mut job_metadata = None;
-    
-    for language in Language::all_variants() {
-        let lang = language.to_string();
-        // Check main queue
-        let main_queue = format!("optimus:queue:{}", lang);
-        if let Ok(items) = ::redis::cmd("LRANGE")
-            .arg(&main_queue)
-            .arg(0)
-            .arg(-1)
-            .query_async::<_, Vec<String>>(&mut conn)
-            .await
-        {
-            for item in items {
-                if let Ok(job) = serde_json::from_str::<optimus_common::types::JobRequest>(&item) {
-                    if job.id == job_uuid {
-                        in_main_queue = true;
-                        job_metadata = Some(job.metadata);
-                        break;
-                    }
-                }
-            }
-        }
-        
-        // Check retry queue
-        let retry_queue = format!("optimus:queue:{}:retry", lang);
-        if let Ok(items) = ::redis::cmd("LRANGE")
-            .arg(&retry_queue)
-            .arg(0)
-            .arg(-1)
-            .query_async::<_, Vec<String>>(&mut conn)
-            .await
-        {
-            for item in items {
-                if let Ok(job) = serde_json::from_str::<optimus_common::types::JobRequest>(&item) {
-                    if job.id == job_uuid {
-                        in_retry_queue = true;
-                        job_metadata = Some(job.metadata);
-                        break;
-                    }
-                }
-            }
-        }
-        
-        // Check DLQ
-        let dlq = format!("optimus:queue:{}:dlq", lang);
-        if let Ok(items) = ::redis::cmd("LRANGE")
-            .arg(&dlq)
-            .arg(0)
-            .arg(-1)
-            .query_async::<_, Vec<String>>(&mut conn)
-            .await
-        {
-            for item in items {
-                if let Ok(job) = serde_json::from_str::<optimus_common::types::JobRequest>(&item) {
-                    if job.id == job_uuid {
-                        in_dlq = true;
-                        job_metadata = Some(job.metadata);
-                        break;
-                    }
-                }
-            }
-        }
-        
-        if in_main_queue || in_retry_queue || in_dlq {
-            break;
-        }
-    }
-    
-    let debug_info = JobDebugInfo {
-        job_id: job_id.clone(),
-        status: if result.is_some() {
-            "completed".to_string()
-        } else if in_dlq {
-            "dead_letter_queue".to_string()
-        } else if in_retry_queue {
-            "retrying".to_string()
-        } else if in_main_queue {
-            "queued".to_string()
-        } else {
-            "unknown".to_string()
-        },
-        attempts: job_metadata.as_ref().map(|m| m.attempts).unwrap_or(0),
-        max_attempts: job_metadata.as_ref().map(|m| m.max_attempts).unwrap_or(3),
-        last_failure_reason: job_metadata.and_then(|m| m.last_failure_reason),
-        in_main_queue,
-        in_retry_queue,
-        in_dlq,
-        result,
+
+    // Final status comes from the status key so we don't decode the full result
+    // (it holds "queued" from submission until the result is stored)
+    let final_status = ::redis::cmd("GET")
+        .arg(redis::status_key(&job_uuid))
+        .query_async::<_, Option<String>>(&mut conn)
+        .await
+        .ok()
+        .flatten()
+        .map(|s| s.trim_matches('"').to_string())
+        .filter(|s| s != "queued");
+
+    let status = match final_status {
+        Some(status) => status,
+        None if tests.is_empty() => "pending".to_string(),
+        None => "running".to_string(),
     };
-    
-    info!(job_id = %job_id, status = %debug_info.status, "Debug info retrieved");
-    (StatusCode::OK, Json(debug_info)).into_response()
+
+    (
+        StatusCode::OK,
+        Json(JobProgressResponse {
+            job_id,
+            status,
+            completed_tests: tests.len(),
+            total_tests: tests.last().map(|t| t.total_tests),
+            tests,
+        }),
+    ).into_response()
 }
 
-#[derive(Debug, Serialize)]
-pub struct CancelResponse {
-    pub job_id: String,
-    pub status: String,
-    pub message: String,
+/// GET /job/{job_id}/stream - Upgrade to a WebSocket pushing the job's progress events
+/// (see stream.rs)
+pub async fn stream_job_progress(
+    State(state): State<Arc<AppState>>,
+    Path(job_id): Path<String>,
+    upgrade: Result<WebSocketUpgrade, WebSocketUpgradeRejection>,
+) -> Response {
+    let (job_uuid, conn) = match followed_job(&state, &job_id).await {
+        Ok(job) => job,
+        Err(response) => return response,
+    };
+
+    let upgrade_required = || {
+        (
+            StatusCode::UPGRADE_REQUIRED,
+            [(header::UPGRADE, "websocket")],
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "UPGRADE_REQUIRED".to_string(),
+                    message: "This endpoint streams over WebSocket; use /job/{id}/events otherwise".to_string(),
+                },
+            }),
+        ).into_response()
+    };
+    // Not a WebSocket upgrade, or one that can't be served (e.g. over HTTP/2)
+    let Ok(upgrade) = upgrade else {
+        return upgrade_required();
+    };
+
+    let events = state.progress.subscribe();
+    stream::limit_client_frames(upgrade)
+        .on_failed_upgrade(move |e| warn!(job_id = %job_uuid, error = %e, "WebSocket upgrade failed"))
+        .on_upgrade(move |socket| stream::run(socket, conn, job_uuid, events))
 }
 
-/// POST /job/{job_id}/cancel - Cancel a running or queued job
-/// 
-/// Behavior:
-/// - Sets cancellation flag in Redis
-/// - Idempotent (multiple calls are safe)
-/// - Returns 200 OK if cancelled
-/// - Returns 409 Conflict if already completed/failed
-/// - Returns 404 Not Found if job doesn't exist
-pub async fn cancel_job(
+/// GET /job/{job_id}/events - The job's progress events as Server-Sent Events
+/// (see stream.rs); the response ends once the job is done
+pub async fn job_events(
     State(state): State<Arc<AppState>>,
     Path(job_id): Path<String>,
-) -> impl IntoResponse {
-    // Parse job ID
-    let job_uuid = match Uuid::parse_str(&job_id) {
+) -> Response {
+    let (job_uuid, conn) = match followed_job(&state, &job_id).await {
+        Ok(job) => job,
+        Err(response) => return response,
+    };
+    let events = stream::sse_events(conn, job_uuid, state.progress.subscribe());
+    Sse::new(events).keep_alive(KeepAlive::default()).into_response()
+}
+
+/// A job whose progress can be streamed: a valid id of a job that exists and wasn't deleted
+async fn followed_job(state: &AppState, job_id: &str) -> Result<(Uuid, redis_pool::PooledConnection), Response> {
+    let job_uuid = match Uuid::parse_str(job_id) {
         Ok(id) => id,
         Err(_) => {
-            return (
+            return Err((
                 StatusCode::BAD_REQUEST,
                 Json(ErrorResponse {
                     error: ErrorDetail {
@@ -719,11 +1069,166 @@ pub async fn cancel_job(
                         message: "Invalid job ID format".to_string(),
                     },
                 }),
-            ).into_response();
+            ).into_response());
         }
     };
 
-    let mut conn = state.redis.clone();
+    if let Some(response) = deleted_job_response(state, &job_uuid).await {
+        return Err(response);
+    }
+
+    let mut conn = state.redis.conn();
+    match redis::get_job_status(&mut conn, &job_uuid).await {
+        Ok(Some(_)) => Ok((job_uuid, conn)),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "JOB_NOT_FOUND".to_string(),
+                    message: format!("Job {} is unknown or has expired", job_id),
+                },
+            }),
+        ).into_response()),
+        Err(e) => {
+            error!(job_id = %job_id, error = %e, "Failed to fetch job status");
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INTERNAL_ERROR".to_string(),
+                        message: format!("Failed to query job status: {}", e),
+                    },
+                }),
+            ).into_response())
+        }
+    }
+}
+
+/// GET /job/{job_id}/debug - Detailed debugging information for job
+/// Shows retry attempts, queue status, and failure reasons
+pub async fn get_job_debug(
+    State(state): State<Arc<AppState>>,
+    Path(job_id): Path<String>,
+) -> impl IntoResponse {
+    // Parse job ID
+    let job_uuid = match Uuid::parse_str(&job_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INVALID_JOB_ID".to_string(),
+                        message: "Invalid job ID format".to_string(),
+                    },
+                }),
+            ).into_response();
+        }
+    };
+
+    if let Some(response) = deleted_job_response(&state, &job_uuid).await {
+        return response;
+    }
+
+    let mut conn = state.redis.conn();
+    
+    // Fetch result from Redis
+    let result = match redis::get_result(&mut conn, &job_uuid).await {
+        Ok(result) => result,
+        Err(e) => {
+            error!(job_id = %job_id, error = %e, "Failed to fetch job result");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INTERNAL_ERROR".to_string(),
+                        message: format!("Failed to query job: {}", e),
+                    },
+                }),
+            ).into_response();
+        }
+    };
+    
+    // Where the job is: one read of its location, never a scan of the queues
+    let location = match redis::get_job_location(&mut conn, &job_uuid).await {
+        Ok(location) => location,
+        Err(e) => {
+            warn!(job_id = %job_id, error = %e, "Failed to read job location");
+            None
+        }
+    };
+    let place = location.as_ref().map(|l| l.place);
+
+    let signature_status = match (state.result_signer, &result) {
+        (Some(signer), Some(result)) => Some(match signer.verify(result) {
+            Ok(()) => "valid".to_string(),
+            Err(e) => e.to_string(),
+        }),
+        _ => None,
+    };
+    
+    let debug_info = JobDebugInfo {
+        job_id: job_id.clone(),
+        status: match (&result, place) {
+            (Some(_), _) => "completed",
+            (None, Some(JobPlace::DeadLettered)) => "dead_letter_queue",
+            (None, Some(JobPlace::Retrying)) => "retrying",
+            (None, Some(JobPlace::Queued | JobPlace::Intake)) => "queued",
+            (None, Some(JobPlace::Running)) => "running",
+            (None, None) => "unknown",
+        }
+        .to_string(),
+        attempts: location.as_ref().map(|l| l.metadata.attempts).unwrap_or(0),
+        max_attempts: location.as_ref().map(|l| l.metadata.max_attempts).unwrap_or(3),
+        last_failure_reason: location.as_ref().and_then(|l| l.metadata.last_failure_reason.clone()),
+        in_main_queue: place == Some(JobPlace::Queued),
+        in_retry_queue: place == Some(JobPlace::Retrying),
+        in_dlq: place == Some(JobPlace::DeadLettered),
+        queue: location.and_then(|l| l.queue),
+        result,
+        signature_status,
+    };
+    
+    info!(job_id = %job_id, status = %debug_info.status, "Debug info retrieved");
+    (StatusCode::OK, Json(debug_info)).into_response()
+}
+
+#[derive(Debug, Serialize)]
+pub struct CancelResponse {
+    pub job_id: String,
+    pub status: String,
+    pub message: String,
+}
+
+/// POST /job/{job_id}/cancel - Cancel a running or queued job
+/// 
+/// Behavior:
+/// - Sets cancellation flag in Redis
+/// - Idempotent (multiple calls are safe)
+/// - Returns 200 OK if cancelled
+/// - Returns 409 Conflict if already completed/failed
+/// - Returns 404 Not Found if job doesn't exist
+pub async fn cancel_job(
+    State(state): State<Arc<AppState>>,
+    Path(job_id): Path<String>,
+) -> impl IntoResponse {
+    // Parse job ID
+    let job_uuid = match Uuid::parse_str(&job_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INVALID_JOB_ID".to_string(),
+                        message: "Invalid job ID format".to_string(),
+                    },
+                }),
+            ).into_response();
+        }
+    };
+
+    let mut conn = state.redis.conn();
     
     // Check if job already has a result (completed/failed)
     match redis::get_result(&mut conn, &job_uuid).await {
@@ -732,6 +1237,8 @@ pub async fn cancel_job(
             let status = match result.overall_status {
                 optimus_common::types::JobStatus::Completed => "completed",
                 optimus_common::types::JobStatus::Failed => "failed",
+                optimus_common::types::JobStatus::Passed => "passed",
+                optimus_common::types::JobStatus::PartiallyPassed => "partially_passed",
                 optimus_common::types::JobStatus::TimedOut => "timed_out",
                 optimus_common::types::JobStatus::Cancelled => "cancelled",
                 _ => "finished",
@@ -798,3 +1305,1670 @@ pub async fn cancel_job(
         }
     }
 }
+
+/// 410 for a job deleted via DELETE /job/{job_id}, whose data must no longer be served
+///
+/// None when the job was not deleted, or the tombstone can't be read (logged; the
+/// endpoint then answers as before rather than failing every read).
+async fn deleted_job_response(state: &AppState, job_id: &Uuid) -> Option<Response> {
+    let tombstone = match redis::get_tombstone(&mut state.redis.conn(), job_id).await {
+        Ok(tombstone) => tombstone?,
+        Err(e) => {
+            warn!(job_id = %job_id, error = %e, "Failed to read tombstone");
+            return None;
+        }
+    };
+    Some((
+        StatusCode::GONE,
+        Json(ErrorResponse {
+            error: ErrorDetail {
+                code: "JOB_DELETED".to_string(),
+                message: format!("Job {} was deleted at {}", job_id, tombstone.deleted_at.to_rfc3339()),
+            },
+        }),
+    ).into_response())
+}
+
+/// DELETE /job/{job_id} - Delete a submission (e.g. for a GDPR erasure request)
+///
+/// Behavior:
+/// - Hides the result at once and removes the job from its tenant's retention index
+///   (the tenant recorded on submission)
+/// - Takes it off the leaderboards (a restore ranks it again) and out of its problem's
+///   statistics and timeline
+/// - Needs the API key that submitted the job, or an admin key
+/// - Cancels the job if it has not finished
+/// - Purges everything stored for it once the restore window closes (see deletion.rs)
+/// - Idempotent: deleting again returns the existing tombstone
+/// - Returns 202 Accepted with the tombstone, 404 if the job is unknown or expired
+pub async fn delete_job(
+    State(state): State<Arc<AppState>>,
+    Path(job_id): Path<String>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let Ok(job_uuid) = Uuid::parse_str(&job_id) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "INVALID_JOB_ID".to_string(),
+                    message: "Invalid job ID format".to_string(),
+                },
+            }),
+        ).into_response();
+    };
+    let internal_error = |e: ::redis::RedisError| {
+        error!(job_id = %job_id, error = %e, "Failed to delete job");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "INTERNAL_ERROR".to_string(),
+                    message: format!("Failed to delete job: {}", e),
+                },
+            }),
+        ).into_response()
+    };
+
+    let mut conn = state.redis.conn();
+    let (owner, tenant) = match redis::get_job_copy(&mut conn, &job_uuid).await {
+        Ok(job) => job.map(|job| (job.metadata.owner, job.metadata.tenant)).unwrap_or_default(),
+        Err(e) => return internal_error(e),
+    };
+    let tenant = tenant.unwrap_or_else(|| retention::DEFAULT_TENANT.to_string());
+    if let Err(refusal) = state.keys.authenticate_owner(&headers, owner.as_deref()) {
+        return refusal.into_response();
+    }
+
+    match redis::get_tombstone(&mut conn, &job_uuid).await {
+        Ok(Some(tombstone)) => return (StatusCode::ACCEPTED, Json(tombstone)).into_response(),
+        Ok(None) => {}
+        Err(e) => return internal_error(e),
+    }
+
+    // The status key lives from submission until the result expires
+    use ::redis::AsyncCommands;
+    let status: Option<String> = match conn.get(redis::status_key(&job_uuid)).await {
+        Ok(status) => status,
+        Err(e) => return internal_error(e),
+    };
+    let Some(status) = status else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "JOB_NOT_FOUND".to_string(),
+                    message: format!("Job {} is unknown or has expired", job_id),
+                },
+            }),
+        ).into_response();
+    };
+    // Not finished yet (queued or running): stop it, so no fresh result appears for a deleted job
+    let finished = serde_json::from_str::<JobStatus>(&status).is_ok_and(|s| !matches!(s, JobStatus::Queued | JobStatus::Running));
+    if !finished {
+        if let Err(e) = redis::set_job_cancelled(&mut conn, &job_uuid).await {
+            return internal_error(e);
+        }
+        metrics::record_job_cancelled("deleted");
+    }
+
+    let submitted_at = match conn.zscore::<_, _, Option<f64>>(redis::submission_index_key(&tenant), job_uuid.to_string()).await {
+        Ok(score) => score.map(|s| s as i64),
+        Err(e) => return internal_error(e),
+    };
+    let tombstone = crate::deletion::tombstone(job_uuid, &tenant, submitted_at, crate::deletion::restore_window_secs());
+    match redis::tombstone_job(&mut conn, &tombstone).await {
+        Ok(tombstone) => {
+            state.result_cache.invalidate(&job_uuid);
+            if let Err(e) = redis::rescore_leaderboard(&mut conn, &job_uuid, redis::LeaderboardChange::Hide).await {
+                warn!(job_id = %job_id, error = %e, "Failed to take deleted job off the leaderboards");
+            }
+            if let Err(e) = redis::forget_problem_stats(&mut conn, &job_uuid).await {
+                warn!(job_id = %job_id, error = %e, "Failed to take deleted job out of problem statistics");
+            }
+            info!(job_id = %job_id, tenant = %tenant, purge_at = %tombstone.purge_at, "Job deleted");
+            (StatusCode::ACCEPTED, Json(tombstone)).into_response()
+        }
+        Err(e) => internal_error(e),
+    }
+}
+
+/// POST /admin/jobs/{job_id}/restore - Undo a deletion before the job is purged
+///
+/// - Returns 200 OK with the removed tombstone
+/// - Returns 404 if the job was not deleted, 409 if its restore window has closed
+pub async fn restore_job(
+    State(state): State<Arc<AppState>>,
+    Path(job_id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(job_uuid) = Uuid::parse_str(&job_id) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "INVALID_JOB_ID".to_string(),
+                    message: "Invalid job ID format".to_string(),
+                },
+            }),
+        ).into_response();
+    };
+    let internal_error = |e: ::redis::RedisError| {
+        error!(job_id = %job_id, error = %e, "Failed to restore job");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "INTERNAL_ERROR".to_string(),
+                    message: format!("Failed to restore job: {}", e),
+                },
+            }),
+        ).into_response()
+    };
+
+    let mut conn = state.redis.conn();
+    let tombstone = match redis::get_tombstone(&mut conn, &job_uuid).await {
+        Ok(Some(tombstone)) => tombstone,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "TOMBSTONE_NOT_FOUND".to_string(),
+                        message: format!("Job {} has not been deleted", job_id),
+                    },
+                }),
+            ).into_response();
+        }
+        Err(e) => return internal_error(e),
+    };
+
+    // Past the window, or the purge loop got there first
+    let claimed = tombstone.purge_at > chrono::Utc::now() && match redis::claim_tombstone(&mut conn, &job_uuid).await {
+        Ok(claimed) => claimed,
+        Err(e) => return internal_error(e),
+    };
+    if !claimed {
+        return (
+            StatusCode::CONFLICT,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "RESTORE_WINDOW_CLOSED".to_string(),
+                    message: format!("Job {} was purged (or is being purged) at {}", job_id, tombstone.purge_at.to_rfc3339()),
+                },
+            }),
+        ).into_response();
+    }
+    if let Err(e) = redis::restore_tombstone(&mut conn, &tombstone).await {
+        return internal_error(e);
+    }
+    if let Err(e) = redis::rescore_leaderboard(&mut conn, &job_uuid, redis::LeaderboardChange::Show).await {
+        warn!(job_id = %job_id, error = %e, "Failed to rank restored job again");
+    }
+    info!(job_id = %job_id, "Deleted job restored");
+    (StatusCode::OK, Json(tombstone)).into_response()
+}
+
+#[derive(Debug, Serialize)]
+pub struct RetentionResponse {
+    pub enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub policy: Option<retention::RetentionPolicy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dlq: Option<crate::dlq_retention::DlqPolicy>,
+    pub status: retention::RetentionStatus,
+}
+
+fn retention_response(state: &AppState) -> RetentionResponse {
+    RetentionResponse {
+        enabled: state.retention.policy.is_some() || state.retention.dlq.is_some(),
+        policy: state.retention.policy.clone(),
+        dlq: state.retention.dlq.clone(),
+        status: state.retention.status(),
+    }
+}
+
+/// GET /admin/retention - Retention policy and progress of the current/last run
+pub async fn get_retention_status(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    (StatusCode::OK, Json(retention_response(&state))).into_response()
+}
+
+/// POST /admin/retention/run - Trigger a retention run in the background
+///
+/// - Returns 202 Accepted with the run's initial progress
+/// - Returns 409 Conflict if retention is disabled or a run is in progress
+pub async fn run_retention(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    if state.retention.policy.is_none() && state.retention.dlq.is_none() {
+        return (
+            StatusCode::CONFLICT,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "RETENTION_DISABLED".to_string(),
+                    message: "Retention is not configured (set RETENTION_MAX_AGE_SECS, DLQ_MAX_LEN or DLQ_MAX_AGE_SECS)".to_string(),
+                },
+            }),
+        ).into_response();
+    }
+
+    if !state.retention.try_begin("manual") {
+        return (
+            StatusCode::CONFLICT,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "RETENTION_RUNNING".to_string(),
+                    message: "A retention run is already in progress".to_string(),
+                },
+            }),
+        ).into_response();
+    }
+
+    info!("Manual retention run triggered");
+    let janitor = state.retention.clone();
+    let conn = state.redis.conn();
+    tokio::spawn(async move { janitor.run(conn).await });
+
+    (StatusCode::ACCEPTED, Json(retention_response(&state))).into_response()
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueueDepth {
+    pub language: Language,
+    pub queued: i64,
+    pub retrying: i64,
+    pub dead_lettered: i64,
+    /// Jobs with requirements waiting (pending and retrying), per requirement set
+    /// ("gpu", "arm64+gpu"); absent for languages without capabilities
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub requirement_queued: BTreeMap<String, i64>,
+    /// Workers are holding off new jobs (POST /admin/queues/:language/pause)
+    pub paused: bool,
+}
+
+/// GET /admin/queues - Main, retry and DLQ depth per language
+pub async fn get_queue_depths(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    // Every length in one pipeline: main, retry and DLQ, then each requirement set's pair
+    let mut conn = state.redis.conn();
+    let mut queues = Vec::new();
+    let mut layout = Vec::new();
+    for language in Language::all_variants() {
+        queues.extend([redis::queue_name(language), redis::retry_queue_name(language), redis::dlq_name(language)]);
+        let requirable = state.language_registry.requirable_labels(*language);
+        let mut sets = Vec::new();
+        for requirements in capabilities::satisfiable_sets(requirable) {
+            if requirements.is_empty() {
+                continue;
+            }
+            let (queue, retry_queue) = redis::requirement_queues(language, &requirements);
+            queues.extend([queue, retry_queue]);
+            sets.push(requirements.join("+"));
+        }
+        layout.push((language, sets));
+    }
+    let lengths = state.redis.queue_lengths(&queues).await.unwrap_or_else(|_| vec![0; queues.len()]);
+
+    let mut lengths = lengths.into_iter();
+    let mut next = || lengths.next().unwrap_or(0);
+    let mut depths = Vec::new();
+    for (language, sets) in layout {
+        let (queued, retrying, dead_lettered) = (next(), next(), next());
+        let requirement_queued = sets.into_iter().map(|set| (set, next() + next())).collect();
+        let paused = redis::is_queue_paused(&mut conn, language).await.unwrap_or(false);
+        depths.push(QueueDepth {
+            language: *language,
+            queued,
+            retrying,
+            dead_lettered,
+            requirement_queued,
+            paused,
+        });
+    }
+
+    (StatusCode::OK, Json(depths)).into_response()
+}
+
+/// POST /admin/queues/:language/pause - Stop workers taking new jobs for a language
+pub async fn pause_queue(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> impl IntoResponse {
+    set_queue_paused(state, name, true).await
+}
+
+/// POST /admin/queues/:language/resume - Let workers take jobs for a language again
+pub async fn resume_queue(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> impl IntoResponse {
+    set_queue_paused(state, name, false).await
+}
+
+async fn set_queue_paused(state: Arc<AppState>, name: String, paused: bool) -> axum::response::Response {
+    let Some(language) = Language::from_str(&name) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "INVALID_LANGUAGE".to_string(),
+                    message: format!("Unknown language '{}'", name),
+                },
+            }),
+        ).into_response();
+    };
+
+    let mut conn = state.redis.conn();
+    if let Err(e) = redis::set_queue_paused(&mut conn, &language, paused).await {
+        error!(language = %language, error = %e, "Failed to update queue pause flag");
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "INTERNAL_ERROR".to_string(),
+                    message: format!("Failed to update queue: {}", e),
+                },
+            }),
+        ).into_response();
+    }
+
+    info!(language = %language, paused, "Queue pause flag updated");
+    let kind = if paused { EventKind::QueuePaused } else { EventKind::QueueResumed };
+    let event = SystemEvent { language: Some(language), ..SystemEvent::new(kind) };
+    if let Err(e) = redis::publish_event(&mut conn, &event).await {
+        warn!(language = %language, error = %e, "Failed to publish queue event");
+    }
+
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({ "language": language, "paused": paused })),
+    ).into_response()
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecentJobEntry {
+    #[serde(flatten)]
+    pub job: optimus_common::types::RecentJob,
+    /// Terminal status if a result exists, otherwise "pending"
+    pub status: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LimitQuery {
+    #[serde(default = "default_admin_limit")]
+    pub limit: isize,
+}
+
+fn default_admin_limit() -> isize {
+    50
+}
+
+/// GET /admin/jobs/recent - Most recent submissions with their current status
+pub async fn get_recent_jobs(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<LimitQuery>,
+) -> impl IntoResponse {
+    let mut conn = state.redis.conn();
+    let limit = query.limit.clamp(1, redis::RECENT_JOBS_LIMIT);
+
+    let jobs = match redis::get_recent_jobs(&mut conn, limit).await {
+        Ok(jobs) => jobs,
+        Err(e) => {
+            error!(error = %e, "Failed to read recent jobs");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INTERNAL_ERROR".to_string(),
+                        message: format!("Failed to read recent jobs: {}", e),
+                    },
+                }),
+            ).into_response();
+        }
+    };
+
+    // Deleted jobs are hidden here as everywhere else
+    let ids: Vec<Uuid> = jobs.iter().map(|job| job.job_id).collect();
+    let deleted = match redis::deleted_jobs(&mut conn, &ids).await {
+        Ok(deleted) => deleted,
+        Err(e) => {
+            error!(error = %e, "Failed to read tombstones of recent jobs");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INTERNAL_ERROR".to_string(),
+                        message: format!("Failed to read recent jobs: {}", e),
+                    },
+                }),
+            ).into_response();
+        }
+    };
+
+    let mut entries = Vec::with_capacity(jobs.len());
+    for job in jobs.into_iter().filter(|job| !deleted.contains(&job.job_id)) {
+        let status = ::redis::cmd("GET")
+            .arg(redis::status_key(&job.job_id))
+            .query_async::<_, Option<String>>(&mut conn)
+            .await
+            .ok()
+            .flatten()
+            .map(|s| s.trim_matches('"').to_string())
+            .filter(|s| s != "queued")
+            .unwrap_or_else(|| "pending".to_string());
+        entries.push(RecentJobEntry { job, status });
+    }
+
+    (StatusCode::OK, Json(entries)).into_response()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LeaderboardQuery {
+    /// Ranks to skip
+    #[serde(default)]
+    pub offset: usize,
+    #[serde(default = "default_admin_limit")]
+    pub limit: isize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LeaderboardPage {
+    pub entries: Vec<LeaderboardEntry>,
+    /// Ranked participants
+    pub total: u64,
+}
+
+/// GET /problems/:id/leaderboard - Participants' best results on a problem
+pub async fn get_problem_leaderboard(
+    State(state): State<Arc<AppState>>,
+    Path(problem_id): Path<String>,
+    Query(query): Query<LeaderboardQuery>,
+) -> impl IntoResponse {
+    leaderboard_page(&state, &problem_id, redis::problem_leaderboard_key, query).await
+}
+
+/// GET /contests/:id/leaderboard - Participants' best results summed over a contest's problems
+pub async fn get_contest_leaderboard(
+    State(state): State<Arc<AppState>>,
+    Path(contest_id): Path<String>,
+    Query(query): Query<LeaderboardQuery>,
+) -> impl IntoResponse {
+    leaderboard_page(&state, &contest_id, redis::contest_leaderboard_key, query).await
+}
+
+/// GET /problems/:id/stats - Submission counts, first solve, fastest solution and timeline
+pub async fn get_problem_stats(
+    State(state): State<Arc<AppState>>,
+    Path(problem_id): Path<String>,
+) -> impl IntoResponse {
+    if !optimus_common::types::valid_leaderboard_id(&problem_id) {
+        return invalid_id_response();
+    }
+    match redis::read_problem_stats(&mut state.redis.conn(), &problem_id).await {
+        Ok(stats) => (StatusCode::OK, Json(stats)).into_response(),
+        Err(e) => {
+            error!(problem_id = %problem_id, error = %e, "Failed to read problem stats");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INTERNAL_ERROR".to_string(),
+                        message: format!("Failed to read problem stats: {}", e),
+                    },
+                }),
+            ).into_response()
+        }
+    }
+}
+
+fn invalid_id_response() -> Response {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(ErrorResponse {
+            error: ErrorDetail {
+                code: "INVALID_ID".to_string(),
+                message: format!(
+                    "Ids are 1-{} letters, digits, '-', '_' or '.'",
+                    optimus_common::types::MAX_LEADERBOARD_ID_LEN
+                ),
+            },
+        }),
+    ).into_response()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RegisterTestSetRequest {
+    /// A job judged against the problem's official tests (e.g. the reference solution)
+    pub job_id: Option<Uuid>,
+    /// Or the test set's fingerprint itself
+    pub fingerprint: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TestSetsResponse {
+    pub problem_id: String,
+    /// Fingerprints of the test sets whose results the problem's leaderboard ranks
+    pub test_sets: Vec<String>,
+}
+
+/// Whether a string can be a test-set fingerprint (hex SHA-256)
+fn valid_fingerprint(fingerprint: &str) -> bool {
+    fingerprint.len() == 64 && fingerprint.bytes().all(|b| b.is_ascii_hexdigit() && !b.is_ascii_uppercase())
+}
+
+fn test_sets_error(problem_id: &str, e: ::redis::RedisError) -> Response {
+    error!(problem_id = %problem_id, error = %e, "Failed to update test sets");
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(ErrorResponse {
+            error: ErrorDetail {
+                code: "INTERNAL_ERROR".to_string(),
+                message: format!("Failed to update test sets: {}", e),
+            },
+        }),
+    ).into_response()
+}
+
+/// GET /admin/problems/:problem_id/test-sets - Test sets the problem's leaderboard ranks
+pub async fn get_test_sets(
+    State(state): State<Arc<AppState>>,
+    Path(problem_id): Path<String>,
+) -> impl IntoResponse {
+    if !optimus_common::types::valid_leaderboard_id(&problem_id) {
+        return invalid_id_response();
+    }
+    match redis::registered_test_sets(&mut state.redis.conn(), &problem_id).await {
+        Ok(test_sets) => (StatusCode::OK, Json(TestSetsResponse { problem_id, test_sets })).into_response(),
+        Err(e) => test_sets_error(&problem_id, e),
+    }
+}
+
+/// POST /admin/problems/:problem_id/test-sets - Rank results judged against a test set
+///
+/// The test set is named by its fingerprint, or by a job (stored for 24h) that was
+/// judged against it. Results are only ranked from then on; earlier ones stay unranked.
+pub async fn register_test_set(
+    State(state): State<Arc<AppState>>,
+    Path(problem_id): Path<String>,
+    Json(payload): Json<RegisterTestSetRequest>,
+) -> impl IntoResponse {
+    if !optimus_common::types::valid_leaderboard_id(&problem_id) {
+        return invalid_id_response();
+    }
+    let invalid = |message: &str| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "INVALID_TEST_SET".to_string(),
+                    message: message.to_string(),
+                },
+            }),
+        ).into_response()
+    };
+
+    let mut conn = state.redis.conn();
+    let fingerprint = match (payload.job_id, payload.fingerprint) {
+        (Some(job_id), None) => match redis::get_job_copy(&mut conn, &job_id).await {
+            Ok(Some(job)) => job.test_set_fingerprint(),
+            Ok(None) => {
+                return (
+                    StatusCode::NOT_FOUND,
+                    Json(ErrorResponse {
+                        error: ErrorDetail {
+                            code: "JOB_NOT_FOUND".to_string(),
+                            message: format!("Job {} is unknown or has expired", job_id),
+                        },
+                    }),
+                ).into_response();
+            }
+            Err(e) => return test_sets_error(&problem_id, e),
+        },
+        (None, Some(fingerprint)) if valid_fingerprint(&fingerprint) => fingerprint,
+        (None, Some(_)) => return invalid("fingerprint must be 64 lowercase hex digits"),
+        _ => return invalid("set exactly one of job_id and fingerprint"),
+    };
+    match redis::register_test_set(&mut conn, &problem_id, &fingerprint).await {
+        Ok(added) => {
+            info!(problem_id = %problem_id, fingerprint = %fingerprint, "Test set registered");
+            let status = if added { StatusCode::CREATED } else { StatusCode::OK };
+            (status, Json(serde_json::json!({ "problem_id": problem_id, "fingerprint": fingerprint }))).into_response()
+        }
+        Err(e) => test_sets_error(&problem_id, e),
+    }
+}
+
+/// DELETE /admin/problems/:problem_id/test-sets/:fingerprint - Stop ranking new results
+/// judged against a test set (results already ranked stay on the board)
+pub async fn unregister_test_set(
+    State(state): State<Arc<AppState>>,
+    Path((problem_id, fingerprint)): Path<(String, String)>,
+) -> impl IntoResponse {
+    if !optimus_common::types::valid_leaderboard_id(&problem_id) {
+        return invalid_id_response();
+    }
+    match redis::unregister_test_set(&mut state.redis.conn(), &problem_id, &fingerprint).await {
+        Ok(true) => {
+            info!(problem_id = %problem_id, fingerprint = %fingerprint, "Test set unregistered");
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Ok(false) => (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "TEST_SET_NOT_FOUND".to_string(),
+                    message: format!("Test set {} is not registered for {}", fingerprint, problem_id),
+                },
+            }),
+        ).into_response(),
+        Err(e) => test_sets_error(&problem_id, e),
+    }
+}
+
+/// Read a page of a leaderboard kept up to date by the workers (see redis::record_leaderboard)
+async fn leaderboard_page(state: &AppState, id: &str, board_key: fn(&str) -> String, query: LeaderboardQuery) -> Response {
+    if !optimus_common::types::valid_leaderboard_id(id) {
+        return invalid_id_response();
+    }
+    let limit = query.limit.clamp(1, 1000) as usize;
+    match redis::read_leaderboard(&mut state.redis.conn(), &board_key(id), query.offset, limit).await {
+        Ok((entries, total)) => (StatusCode::OK, Json(LeaderboardPage { entries, total })).into_response(),
+        Err(e) => {
+            error!(id = %id, error = %e, "Failed to read leaderboard");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INTERNAL_ERROR".to_string(),
+                        message: format!("Failed to read leaderboard: {}", e),
+                    },
+                }),
+            ).into_response()
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IdempotencyQuery {
+    /// Only keys starting with this prefix
+    #[serde(default)]
+    pub prefix: String,
+    /// next_cursor of the previous page (0 = first page)
+    #[serde(default)]
+    pub cursor: u64,
+    #[serde(default = "default_admin_limit")]
+    pub limit: isize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IdempotencyPage {
+    pub entries: Vec<IdempotencyEntry>,
+    /// Cursor for the next page (absent on the last page)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<u64>,
+    /// TTL given to new keys
+    pub ttl_secs: u64,
+}
+
+/// GET /admin/idempotency - Stored Idempotency-Keys with their job ids (never payloads)
+pub async fn get_idempotency_keys(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<IdempotencyQuery>,
+) -> impl IntoResponse {
+    let limit = query.limit.clamp(1, 1000) as usize;
+    match redis::list_idempotency_keys(&mut state.redis.conn(), &query.prefix, query.cursor, limit).await {
+        Ok((entries, next_cursor)) => (
+            StatusCode::OK,
+            Json(IdempotencyPage { entries, next_cursor, ttl_secs: idempotency_ttl_secs() }),
+        ).into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to list idempotency keys");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INTERNAL_ERROR".to_string(),
+                        message: format!("Failed to list idempotency keys: {}", e),
+                    },
+                }),
+            ).into_response()
+        }
+    }
+}
+
+/// DELETE /admin/idempotency/:key - Forget a key so the client can reuse it
+///
+/// The job it pointed at is untouched.
+pub async fn delete_idempotency_key(
+    State(state): State<Arc<AppState>>,
+    Path(key): Path<String>,
+) -> impl IntoResponse {
+    match redis::delete_idempotency_key(&mut state.redis.conn(), &key).await {
+        Ok(Some(entry)) => {
+            info!(idempotency_key = %key, job_id = ?entry.job_id, "Idempotency key deleted");
+            (StatusCode::OK, Json(entry)).into_response()
+        }
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "IDEMPOTENCY_KEY_NOT_FOUND".to_string(),
+                    message: format!("No stored idempotency key '{}'", key),
+                },
+            }),
+        ).into_response(),
+        Err(e) => {
+            error!(idempotency_key = %key, error = %e, "Failed to delete idempotency key");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INTERNAL_ERROR".to_string(),
+                        message: format!("Failed to delete idempotency key: {}", e),
+                    },
+                }),
+            ).into_response()
+        }
+    }
+}
+
+/// GET /admin/workers - Workers with a live heartbeat
+pub async fn get_workers(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    match redis::list_heartbeats(&mut state.redis.conn()).await {
+        Ok(workers) => (StatusCode::OK, Json(workers)).into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to list worker heartbeats");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INTERNAL_ERROR".to_string(),
+                        message: format!("Failed to list workers: {}", e),
+                    },
+                }),
+            ).into_response()
+        }
+    }
+}
+
+/// POST /admin/workers/:worker_id/drain - The worker stops taking jobs and exits once its
+/// running jobs finish (it notices within a heartbeat, about 10s)
+pub async fn drain_worker(
+    State(state): State<Arc<AppState>>,
+    Path(worker_id): Path<String>,
+) -> impl IntoResponse {
+    match redis::request_worker_drain(&mut state.redis.conn(), &worker_id).await {
+        Ok(true) => {
+            info!(worker_id = %worker_id, "Worker drain requested");
+            (
+                StatusCode::ACCEPTED,
+                Json(serde_json::json!({ "worker_id": worker_id, "drain_requested": true })),
+            ).into_response()
+        }
+        Ok(false) => (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "WORKER_NOT_FOUND".to_string(),
+                    message: format!("No live worker '{}'", worker_id),
+                },
+            }),
+        ).into_response(),
+        Err(e) => {
+            error!(worker_id = %worker_id, error = %e, "Failed to request worker drain");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INTERNAL_ERROR".to_string(),
+                        message: format!("Failed to request drain: {}", e),
+                    },
+                }),
+            ).into_response()
+        }
+    }
+}
+
+/// DELETE /admin/workers/:worker_id/drain - Withdraw a drain request the worker hasn't
+/// acted on yet (or let a restarted worker with the same id take jobs again)
+pub async fn cancel_worker_drain(
+    State(state): State<Arc<AppState>>,
+    Path(worker_id): Path<String>,
+) -> impl IntoResponse {
+    match redis::cancel_worker_drain(&mut state.redis.conn(), &worker_id).await {
+        Ok(cancelled) => {
+            if cancelled {
+                info!(worker_id = %worker_id, "Worker drain request withdrawn");
+            }
+            (
+                StatusCode::OK,
+                Json(serde_json::json!({ "worker_id": worker_id, "drain_requested": false, "cancelled": cancelled })),
+            ).into_response()
+        }
+        Err(e) => {
+            error!(worker_id = %worker_id, error = %e, "Failed to cancel worker drain");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INTERNAL_ERROR".to_string(),
+                        message: format!("Failed to cancel drain: {}", e),
+                    },
+                }),
+            ).into_response()
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct DlqEntry {
+    pub job_id: String,
+    pub language: Language,
+    pub attempts: u8,
+    pub max_attempts: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_failure_reason: Option<String>,
+}
+
+/// GET /admin/dlq - Dead-lettered jobs (metadata only, never source code)
+pub async fn get_dlq_entries(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<LimitQuery>,
+) -> impl IntoResponse {
+    let limit = query.limit.max(1);
+    let mut entries = Vec::new();
+
+    let queues: Vec<String> = Language::all_variants().iter().map(redis::dlq_name).collect();
+    let contents = state.redis.scan_queues(&queues, limit - 1).await.unwrap_or_default();
+    for item in contents.iter().flatten() {
+        if let Ok(job) = redis::decode_job(item) {
+            entries.push(DlqEntry {
+                job_id: job.id.to_string(),
+                language: job.language,
+                attempts: job.metadata.attempts,
+                max_attempts: job.metadata.max_attempts,
+                last_failure_reason: job.metadata.last_failure_reason,
+            });
+        }
+    }
+    // Each queue was read up to the limit; the listing as a whole is held to it too
+    entries.truncate(limit as usize);
+
+    (StatusCode::OK, Json(entries)).into_response()
+}
+
+/// GET /languages/:name/runtime-info - Exact toolchain versions in the judge image
+///
+/// Versions are probed by workers from the image they actually run, so this is the
+/// environment that judges submissions (not what languages.json claims).
+pub async fn get_runtime_info(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> impl IntoResponse {
+    let language = match Language::from_str(&name) {
+        Some(language) if state.language_registry.is_enabled(language) => language,
+        _ => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "LANGUAGE_NOT_SUPPORTED".to_string(),
+                        message: format!("Language '{}' is not enabled or supported", name),
+                    },
+                }),
+            ).into_response();
+        }
+    };
+
+    match redis::get_runtime_info(&mut state.redis.conn(), &language).await {
+        Ok(Some(info)) => (StatusCode::OK, Json(info)).into_response(),
+        Ok(None) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "RUNTIME_INFO_UNAVAILABLE".to_string(),
+                    message: format!(
+                        "No {} worker has probed its judge image yet; retry once a worker is running",
+                        language
+                    ),
+                },
+            }),
+        ).into_response(),
+        Err(e) => {
+            error!(language = %language, error = %e, "Failed to read runtime info");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INTERNAL_ERROR".to_string(),
+                        message: format!("Failed to read runtime info: {}", e),
+                    },
+                }),
+            ).into_response()
+        }
+    }
+}
+
+/// GET /languages/:name/environments - Judge environment versions with build dates
+///
+/// Results carry `judge_env_version`; this changelog maps it to the image build,
+/// which explains verdicts that change over time for the same submission.
+pub async fn get_judge_environments(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> impl IntoResponse {
+    let environment = Language::from_str(&name)
+        .filter(|language| state.language_registry.is_enabled(*language))
+        .and_then(|language| state.language_registry.environment(language));
+
+    match environment {
+        Some(environment) => (StatusCode::OK, Json(environment.clone())).into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "LANGUAGE_NOT_SUPPORTED".to_string(),
+                    message: format!("Language '{}' is not enabled or supported", name),
+                },
+            }),
+        ).into_response(),
+    }
+}
+
+/// GET /limits - Effective submission limits, so clients can validate before submitting
+///
+/// Reflects the limits as last (re)loaded; they can change at runtime (see limits.rs).
+pub async fn get_limits() -> impl IntoResponse {
+    (StatusCode::OK, Json(limits::report()))
+}
+
+/// GET /admin/canary/:language - Shadow-run comparison of canary images against the primary image
+pub async fn get_canary_report(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> impl IntoResponse {
+    let Some(language) = Language::from_str(&name).filter(|l| state.language_registry.is_enabled(*l)) else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "LANGUAGE_NOT_SUPPORTED".to_string(),
+                    message: format!("Language '{}' is not enabled or supported", name),
+                },
+            }),
+        ).into_response();
+    };
+
+    match redis::list_shadow_comparisons(&mut state.redis.conn(), &language).await {
+        Ok(comparisons) => (StatusCode::OK, Json(canary::build_report(language, &comparisons))).into_response(),
+        Err(e) => {
+            error!(language = %language, error = %e, "Failed to read canary comparisons");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INTERNAL_ERROR".to_string(),
+                        message: format!("Failed to read canary comparisons: {}", e),
+                    },
+                }),
+            ).into_response()
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct VerifyRequest {
+    /// Re-run on this image instead of the language's configured image
+    #[serde(default)]
+    pub image: Option<String>,
+    /// Keep the re-run off the worker that produced the original result (default: true)
+    #[serde(default = "default_different_worker")]
+    pub different_worker: bool,
+}
+
+fn default_different_worker() -> bool {
+    true
+}
+
+/// POST /admin/jobs/:job_id/verify - Re-run a finished job and compare the two results
+///
+/// Queues a copy of the job under a new id (the user's result is never touched).
+/// The worker that runs it stores a field-by-field report, read via GET on this path.
+pub async fn verify_job(
+    State(state): State<Arc<AppState>>,
+    Path(job_id): Path<String>,
+    body: Option<Json<VerifyRequest>>,
+) -> impl IntoResponse {
+    let request = body.map(|Json(r)| r).unwrap_or(VerifyRequest {
+        image: None,
+        different_worker: true,
+    });
+    let Ok(job_uuid) = Uuid::parse_str(&job_id) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "INVALID_JOB_ID".to_string(),
+                    message: "Invalid job ID format".to_string(),
+                },
+            }),
+        ).into_response();
+    };
+    if let Some(response) = deleted_job_response(&state, &job_uuid).await {
+        return response;
+    }
+
+    let mut conn = state.redis.conn();
+    let (original, job) = match (
+        redis::get_result(&mut conn, &job_uuid).await,
+        redis::get_job_copy(&mut conn, &job_uuid).await,
+    ) {
+        (Ok(Some(result)), Ok(Some(job))) => (result, job),
+        (Ok(None), Ok(Some(_))) => {
+            return (
+                StatusCode::CONFLICT,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "JOB_NOT_FINISHED".to_string(),
+                        message: "Job has no result yet; verify it once it has finished".to_string(),
+                    },
+                }),
+            ).into_response();
+        }
+        (Ok(_), Ok(None)) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "JOB_NOT_AVAILABLE".to_string(),
+                        message: "Job not found or its stored copy has expired".to_string(),
+                    },
+                }),
+            ).into_response();
+        }
+        (Err(e), _) | (_, Err(e)) => {
+            error!(job_id = %job_id, error = %e, "Failed to load job for verification");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INTERNAL_ERROR".to_string(),
+                        message: format!("Failed to load job: {}", e),
+                    },
+                }),
+            ).into_response();
+        }
+    };
+
+    let rerun = JobRequest {
+        id: Uuid::new_v4(),
+        metadata: optimus_common::types::JobMetadata {
+            verification: Some(optimus_common::types::VerificationTarget {
+                original_job_id: job.id,
+                image: request.image,
+                avoid_worker: original.worker_id.filter(|_| request.different_worker),
+            }),
+            enqueued_at: Some(chrono::Utc::now()),
+            requirements: job.metadata.requirements,
+            ..Default::default()
+        },
+        // Re-runs never change the leaderboard
+        leaderboard: None,
+        ..job
+    };
+    let verification = optimus_common::types::Verification {
+        original_job_id: job_uuid,
+        verification_job_id: rerun.id,
+        requested_at: chrono::Utc::now(),
+        report: None,
+    };
+
+    if let Err(e) = redis::store_verification(&mut conn, &verification).await {
+        error!(job_id = %job_id, error = %e, "Failed to record verification request");
+    }
+    if let Err(e) = redis::push_job(&mut conn, &rerun).await {
+        error!(job_id = %job_id, error = %e, "Failed to queue verification re-run");
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "QUEUE_FAILURE".to_string(),
+                    message: format!("Failed to queue verification re-run: {}", e),
+                },
+            }),
+        ).into_response();
+    }
+
+    info!(job_id = %job_id, verification_job_id = %rerun.id, "Verification re-run queued");
+    (StatusCode::ACCEPTED, Json(verification)).into_response()
+}
+
+/// GET /admin/jobs/:job_id/verify - Latest verification request and its report (once finished)
+pub async fn get_verification(
+    State(state): State<Arc<AppState>>,
+    Path(job_id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(job_uuid) = Uuid::parse_str(&job_id) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "INVALID_JOB_ID".to_string(),
+                    message: "Invalid job ID format".to_string(),
+                },
+            }),
+        ).into_response();
+    };
+    if let Some(response) = deleted_job_response(&state, &job_uuid).await {
+        return response;
+    }
+
+    match redis::get_verification(&mut state.redis.conn(), &job_uuid).await {
+        Ok(Some(verification)) => (StatusCode::OK, Json(verification)).into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "VERIFICATION_NOT_FOUND".to_string(),
+                    message: "No verification has been requested for this job".to_string(),
+                },
+            }),
+        ).into_response(),
+        Err(e) => {
+            error!(job_id = %job_id, error = %e, "Failed to read verification");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INTERNAL_ERROR".to_string(),
+                        message: format!("Failed to read verification: {}", e),
+                    },
+                }),
+            ).into_response()
+        }
+    }
+}
+
+/// GET /admin/jobs/:job_id/debug - Per-test resource usage sampled while the job ran
+pub async fn get_debug_artifacts(
+    State(state): State<Arc<AppState>>,
+    Path(job_id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(job_uuid) = Uuid::parse_str(&job_id) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "INVALID_JOB_ID".to_string(),
+                    message: "Invalid job ID format".to_string(),
+                },
+            }),
+        ).into_response();
+    };
+    if let Some(response) = deleted_job_response(&state, &job_uuid).await {
+        return response;
+    }
+
+    match redis::get_debug_artifacts(&mut state.redis.conn(), &job_uuid).await {
+        Ok(Some(artifacts)) => (StatusCode::OK, Json(artifacts)).into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "DEBUG_ARTIFACTS_NOT_FOUND".to_string(),
+                    message: "No debug artifacts recorded for this job (not run yet, expired, or monitoring disabled)".to_string(),
+                },
+            }),
+        ).into_response(),
+        Err(e) => {
+            error!(job_id = %job_id, error = %e, "Failed to read debug artifacts");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INTERNAL_ERROR".to_string(),
+                        message: format!("Failed to read debug artifacts: {}", e),
+                    },
+                }),
+            ).into_response()
+        }
+    }
+}
+
+/// Longest annotation text accepted
+const MAX_ANNOTATION_LEN: usize = 4000;
+
+#[derive(Debug, Deserialize)]
+pub struct AnnotationRequest {
+    pub text: String,
+    /// Tests the note refers to; must exist in the result
+    #[serde(default)]
+    pub test_ids: Vec<u32>,
+}
+
+/// POST /job/:job_id/annotations - Attach a reviewer note to a stored result
+///
+/// Admin only; the note's author is the admin key's name.
+pub async fn add_annotation(
+    State(state): State<Arc<AppState>>,
+    Admin(reviewer): Admin,
+    Path(job_id): Path<String>,
+    Json(payload): Json<AnnotationRequest>,
+) -> impl IntoResponse {
+    let Ok(job_uuid) = Uuid::parse_str(&job_id) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "INVALID_JOB_ID".to_string(),
+                    message: "Invalid job ID format".to_string(),
+                },
+            }),
+        ).into_response();
+    };
+    if let Some(response) = deleted_job_response(&state, &job_uuid).await {
+        return response;
+    }
+
+    let text = payload.text.trim();
+    if text.is_empty() || text.len() > MAX_ANNOTATION_LEN {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "INVALID_ANNOTATION".to_string(),
+                    message: format!(
+                        "text is required and limited to {} bytes",
+                        MAX_ANNOTATION_LEN
+                    ),
+                },
+            }),
+        ).into_response();
+    }
+
+    let mut conn = state.redis.conn();
+    let result = match redis::get_result(&mut conn, &job_uuid).await {
+        Ok(Some(result)) => result,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "RESULT_NOT_FOUND".to_string(),
+                        message: "Only finished jobs with a stored result can be annotated".to_string(),
+                    },
+                }),
+            ).into_response();
+        }
+        Err(e) => {
+            error!(job_id = %job_id, error = %e, "Failed to read result for annotation");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INTERNAL_ERROR".to_string(),
+                        message: format!("Failed to read result: {}", e),
+                    },
+                }),
+            ).into_response();
+        }
+    };
+
+    if let Some(unknown) = payload.test_ids.iter().find(|id| !result.results.iter().any(|r| r.test_id == **id)) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "UNKNOWN_TEST_ID".to_string(),
+                    message: format!("Job has no test {}", unknown),
+                },
+            }),
+        ).into_response();
+    }
+
+    let annotation = Annotation {
+        id: Uuid::new_v4(),
+        author: reviewer.name,
+        text: text.to_string(),
+        test_ids: payload.test_ids,
+        created_at: chrono::Utc::now(),
+    };
+    if let Err(e) = redis::add_annotation(&mut conn, &job_uuid, &annotation).await {
+        error!(job_id = %job_id, error = %e, "Failed to store annotation");
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "INTERNAL_ERROR".to_string(),
+                    message: format!("Failed to store annotation: {}", e),
+                },
+            }),
+        ).into_response();
+    }
+    state.result_cache.invalidate(&job_uuid);
+
+    info!(job_id = %job_id, author = %annotation.author, "Annotation added");
+    (StatusCode::CREATED, Json(annotation)).into_response()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OverrideRequest {
+    pub reason: String,
+    /// Final score (defaults to the machine score)
+    pub score: Option<u32>,
+    /// Final status (defaults to the machine status)
+    pub status: Option<JobStatus>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OverrideHistory {
+    pub job_id: String,
+    /// Oldest first; the last entry is in effect
+    pub overrides: Vec<ScoreOverride>,
+}
+
+/// POST /job/:job_id/override - Set a final score/status, keeping the machine result
+///
+/// Admin only; the override's actor is the admin key's name. A ranked result is ranked by
+/// the overridden score from then on.
+pub async fn override_score(
+    State(state): State<Arc<AppState>>,
+    Admin(actor): Admin,
+    Path(job_id): Path<String>,
+    Json(payload): Json<OverrideRequest>,
+) -> impl IntoResponse {
+    let Ok(job_uuid) = Uuid::parse_str(&job_id) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "INVALID_JOB_ID".to_string(),
+                    message: "Invalid job ID format".to_string(),
+                },
+            }),
+        ).into_response();
+    };
+    if let Some(response) = deleted_job_response(&state, &job_uuid).await {
+        return response;
+    }
+
+    let reason = payload.reason.trim();
+    let invalid = if reason.is_empty() {
+        Some("reason is required".to_string())
+    } else if payload.score.is_none() && payload.status.is_none() {
+        Some("set score, status or both".to_string())
+    } else if matches!(payload.status, Some(JobStatus::Queued | JobStatus::Running)) {
+        Some("status must be a final status".to_string())
+    } else {
+        None
+    };
+    if let Some(message) = invalid {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "INVALID_OVERRIDE".to_string(),
+                    message,
+                },
+            }),
+        ).into_response();
+    }
+
+    let mut conn = state.redis.conn();
+    let result = match redis::get_result(&mut conn, &job_uuid).await {
+        Ok(Some(result)) => result,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "RESULT_NOT_FOUND".to_string(),
+                        message: "Only finished jobs with a stored result can be overridden".to_string(),
+                    },
+                }),
+            ).into_response();
+        }
+        Err(e) => {
+            error!(job_id = %job_id, error = %e, "Failed to read result for override");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INTERNAL_ERROR".to_string(),
+                        message: format!("Failed to read result: {}", e),
+                    },
+                }),
+            ).into_response();
+        }
+    };
+
+    let score = payload.score.unwrap_or(result.score);
+    if score > result.max_score {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "INVALID_OVERRIDE".to_string(),
+                    message: format!("score cannot exceed max_score ({})", result.max_score),
+                },
+            }),
+        ).into_response();
+    }
+
+    let score_override = ScoreOverride {
+        job_id: job_uuid,
+        actor: actor.name,
+        reason: reason.to_string(),
+        score,
+        status: payload.status.unwrap_or(result.overall_status),
+        machine_score: result.score,
+        machine_status: result.overall_status,
+        created_at: chrono::Utc::now(),
+    };
+    if let Err(e) = redis::add_score_override(&mut conn, &score_override).await {
+        error!(job_id = %job_id, error = %e, "Failed to store score override");
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "INTERNAL_ERROR".to_string(),
+                    message: format!("Failed to store score override: {}", e),
+                },
+            }),
+        ).into_response();
+    }
+    state.result_cache.invalidate(&job_uuid);
+    if let Err(e) = redis::rescore_leaderboard(&mut conn, &job_uuid, redis::LeaderboardChange::Score(score)).await {
+        warn!(job_id = %job_id, error = %e, "Failed to rank overridden score");
+    }
+
+    warn!(
+        job_id = %job_id,
+        actor = %score_override.actor,
+        machine_score = score_override.machine_score,
+        score = score_override.score,
+        status = ?score_override.status,
+        reason = %score_override.reason,
+        "Score overridden"
+    );
+    (StatusCode::CREATED, Json(score_override)).into_response()
+}
+
+/// GET /job/:job_id/override - Audit trail of manual score overrides
+pub async fn get_score_overrides(
+    State(state): State<Arc<AppState>>,
+    Path(job_id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(job_uuid) = Uuid::parse_str(&job_id) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "INVALID_JOB_ID".to_string(),
+                    message: "Invalid job ID format".to_string(),
+                },
+            }),
+        ).into_response();
+    };
+    if let Some(response) = deleted_job_response(&state, &job_uuid).await {
+        return response;
+    }
+
+    match redis::get_score_overrides(&mut state.redis.conn(), &job_uuid).await {
+        Ok(overrides) => (StatusCode::OK, Json(OverrideHistory { job_id, overrides })).into_response(),
+        Err(e) => {
+            error!(job_id = %job_id, error = %e, "Failed to read score overrides");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INTERNAL_ERROR".to_string(),
+                        message: format!("Failed to read score overrides: {}", e),
+                    },
+                }),
+            ).into_response()
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CompareQuery {
+    pub a: String,
+    pub b: String,
+}
+
+/// GET /jobs/compare?a={id1}&b={id2} - Per-test verdict and time differences between two jobs
+pub async fn compare_jobs(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<CompareQuery>,
+) -> impl IntoResponse {
+    let (Ok(a_id), Ok(b_id)) = (Uuid::parse_str(&query.a), Uuid::parse_str(&query.b)) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "INVALID_JOB_ID".to_string(),
+                    message: "Both a and b must be valid job IDs".to_string(),
+                },
+            }),
+        ).into_response();
+    };
+
+    let mut conn = state.redis.conn();
+    let mut results = Vec::with_capacity(2);
+    for job_id in [a_id, b_id] {
+        if let Some(response) = deleted_job_response(&state, &job_id).await {
+            return response;
+        }
+        let result = match redis::get_result(&mut conn, &job_id).await {
+            Ok(Some(result)) => result,
+            Ok(None) => {
+                return (
+                    StatusCode::NOT_FOUND,
+                    Json(ErrorResponse {
+                        error: ErrorDetail {
+                            code: "RESULT_NOT_FOUND".to_string(),
+                            message: format!("Job {} has no result (still running, expired or unknown)", job_id),
+                        },
+                    }),
+                ).into_response();
+            }
+            Err(e) => {
+                error!(job_id = %job_id, error = %e, "Failed to read result for comparison");
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ErrorResponse {
+                        error: ErrorDetail {
+                            code: "INTERNAL_ERROR".to_string(),
+                            message: format!("Failed to read result: {}", e),
+                        },
+                    }),
+                ).into_response();
+            }
+        };
+        // Same integrity rule as GET /job/{id}
+        if let Some(signer) = state.result_signer {
+            if let Err(e) = signer.verify(&result) {
+                error!(job_id = %job_id, error = %e, "Result signature verification failed");
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ErrorResponse {
+                        error: ErrorDetail {
+                            code: "RESULT_INTEGRITY_ERROR".to_string(),
+                            message: format!("Stored result of job {} failed verification: {}", job_id, e),
+                        },
+                    }),
+                ).into_response();
+            }
+        }
+        results.push(result);
+    }
+
+    // Job copies are best-effort: without them the problem cannot be checked
+    let same_test_cases = match (
+        redis::get_job_copy(&mut conn, &a_id).await,
+        redis::get_job_copy(&mut conn, &b_id).await,
+    ) {
+        (Ok(Some(a)), Ok(Some(b))) => Some(compare::same_test_cases(&a, &b)),
+        _ => None,
+    };
+    if same_test_cases == Some(false) {
+        return (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "DIFFERENT_PROBLEMS".to_string(),
+                    message: "Jobs ran different languages or test cases and cannot be compared".to_string(),
+                },
+            }),
+        ).into_response();
+    }
+
+    (StatusCode::OK, Json(compare::compare(&results[0], &results[1], same_test_cases))).into_response()
+}
+
+/// GET /admin/ui - Static operator dashboard backed by the /admin endpoints
+pub async fn admin_ui() -> impl IntoResponse {
+    Html(include_str!("../assets/admin.html"))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cooldown_follows_the_api_key() {
+        let course = Principal { name: "course".to_string(), admin: false };
+        // A keyed client can't dodge the cool-down by renaming the participant
+        assert_eq!(cooldown_submitter(Some(&course), "alice"), cooldown_submitter(Some(&course), "mallory"));
+        assert_eq!(cooldown_submitter(Some(&course), "alice"), "key:course");
+        // Anonymous submissions fall back to the participant, apart from any key's
+        assert_eq!(cooldown_submitter(None, "alice"), "participant:alice");
+        assert_ne!(cooldown_submitter(None, "course"), cooldown_submitter(Some(&course), "course"));
+    }
+
+    #[tokio::test]
+    async fn test_cooldown_refusal_is_429_with_retry_after() {
+        let response = cooldown_refusal("two-sum", 30, 12);
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "12");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"]["code"], "SUBMISSION_COOLDOWN");
+        assert_eq!(body["error"]["retry_after_secs"], 12);
+        assert!(body["error"]["message"].as_str().unwrap().contains("two-sum"));
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires Redis
+    async fn test_cooldown_claim_and_release() {
+        let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+        let mut conn = ::redis::Client::open(url).unwrap().get_connection_manager().await.unwrap();
+        let cooldown = (format!("test-{}", Uuid::new_v4()), cooldown_submitter(None, "alice"));
+        let (problem_id, submitter) = &cooldown;
+
+        assert_eq!(redis::claim_submission_cooldown(&mut conn, problem_id, submitter, 60).await.unwrap(), None);
+        assert!(redis::claim_submission_cooldown(&mut conn, problem_id, submitter, 60).await.unwrap().is_some());
+        // A submission that isn't queued gives its claim back
+        release_cooldown(&mut conn, Some(&cooldown)).await;
+        assert_eq!(redis::claim_submission_cooldown(&mut conn, problem_id, submitter, 60).await.unwrap(), None);
+        release_cooldown(&mut conn, Some(&cooldown)).await;
+    }
+}
@@ -1,34 +1,19 @@
 // Language configuration management
 // Loads and validates languages from languages.json
 
-use optimus_common::types::Language;
-use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
-use std::fs;
+use optimus_common::language_config::{GpuConfig, LanguagesFile};
+use optimus_common::types::{JudgeEnvRelease, Language};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct LanguageConfig {
-    pub name: String,
-    pub version: String,
+/// A language's judge environment: current version and changelog
+#[derive(Debug, Clone, Serialize)]
+pub struct JudgeEnvironment {
+    pub language: Language,
+    pub current_version: u32,
     pub image: String,
-    pub dockerfile_path: String,
-    pub execution: ExecutionConfig,
-    pub queue_name: String,
-    pub memory_limit_mb: u32,
-    pub cpu_limit: f64,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ExecutionConfig {
-    pub command: String,
-    pub args: Vec<String>,
-    pub file_extension: String,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct LanguagesFile {
-    languages: Vec<LanguageConfig>,
+    pub history: Vec<JudgeEnvRelease>,
 }
 
 /// Registry of configured languages
@@ -36,23 +21,38 @@ struct LanguagesFile {
 #[derive(Debug, Clone)]
 pub struct LanguageRegistry {
     enabled_languages: HashSet<Language>,
+    environments: HashMap<Language, JudgeEnvironment>,
+    /// Languages that can run jobs on GPU workers
+    gpu: HashMap<Language, GpuConfig>,
+    /// Capability labels jobs of each language may require
+    requirable: HashMap<Language, Vec<String>>,
 }
 
 impl LanguageRegistry {
-    /// Load language configuration from languages.json
-    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
-        let content = fs::read_to_string(path.as_ref())
-            .map_err(|e| format!("Failed to read languages.json: {}", e))?;
-        
-        let config: LanguagesFile = serde_json::from_str(&content)
-            .map_err(|e| format!("Failed to parse languages.json: {}", e))?;
-        
+    /// Load language configuration from languages.json, with a profile's overlay merged in
+    pub fn load_from_file<P: AsRef<Path>>(path: P, profile: Option<&str>) -> Result<Self, String> {
+        let config = LanguagesFile::load_profile(path, profile)?;
+        config.check_queue_names()?;
+
         let mut enabled_languages = HashSet::new();
+        let mut environments = HashMap::new();
+        let mut gpu = HashMap::new();
+        let mut requirable = HashMap::new();
         
         for lang_config in &config.languages {
             match Language::from_str(&lang_config.name) {
                 Some(lang) => {
                     enabled_languages.insert(lang);
+                    environments.insert(lang, JudgeEnvironment {
+                        language: lang,
+                        current_version: lang_config.env_version,
+                        image: lang_config.image.clone(),
+                        history: lang_config.env_history.clone(),
+                    });
+                    if let Some(config) = lang_config.gpu {
+                        gpu.insert(lang, config);
+                    }
+                    requirable.insert(lang, lang_config.requirable_labels());
                 }
                 None => {
                     return Err(format!(
@@ -67,7 +67,7 @@ impl LanguageRegistry {
             return Err("No languages configured in languages.json".to_string());
         }
         
-        Ok(Self { enabled_languages })
+        Ok(Self { enabled_languages, environments, gpu, requirable })
     }
     
     /// Check if a language is enabled
@@ -79,6 +79,21 @@ impl LanguageRegistry {
     pub fn enabled_languages(&self) -> Vec<Language> {
         self.enabled_languages.iter().copied().collect()
     }
+
+    /// GPU settings of a language (None: it has no GPU workers)
+    pub fn gpu(&self, language: Language) -> Option<GpuConfig> {
+        self.gpu.get(&language).copied()
+    }
+
+    /// Capability labels jobs of a language may require (sorted)
+    pub fn requirable_labels(&self, language: Language) -> &[String] {
+        self.requirable.get(&language).map_or(&[], Vec::as_slice)
+    }
+
+    /// Judge environment version and changelog for a language
+    pub fn environment(&self, language: Language) -> Option<&JudgeEnvironment> {
+        self.environments.get(&language)
+    }
 }
 
 #[cfg(test)]
@@ -88,12 +103,13 @@ mod tests {
     #[test]
     fn test_language_registry() {
         // This test assumes languages.json exists in config/
-        let registry = LanguageRegistry::load_from_file("../../config/languages.json");
+        let registry = LanguageRegistry::load_from_file("../../config/languages.json", None);
         assert!(registry.is_ok());
         
         if let Ok(reg) = registry {
             // Should have at least python
             assert!(reg.is_enabled(Language::Python));
+            assert!(reg.environment(Language::Python).is_some());
         }
     }
 }
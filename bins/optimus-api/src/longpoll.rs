@@ -0,0 +1,90 @@
+// Long polling - GET /job/{id}?wait_ms=N holds the request until the result is stored
+//
+// Workers publish every stored result on the completions channel; the API's subscriber
+// forwards the job ids to `Completions`, and waiting requests wake when theirs arrives.
+// Waits are capped at LONG_POLL_MAX_MS (default 30000). The result is also re-read every
+// few seconds while waiting, so a missed notification (subscriber reconnecting) costs
+// latency, never the result.
+
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::time::Instant;
+use uuid::Uuid;
+
+/// Buffered job ids; a waiter that falls further behind just re-reads the result
+const CHANNEL_CAPACITY: usize = 1024;
+
+/// How often a waiting request re-reads the result without a notification
+pub const RECHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Longest wait a request may ask for (LONG_POLL_MAX_MS, default 30000)
+pub fn max_wait_ms() -> u64 {
+    static MAX_WAIT: OnceLock<u64> = OnceLock::new();
+    *MAX_WAIT.get_or_init(|| {
+        std::env::var("LONG_POLL_MAX_MS")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(30_000)
+    })
+}
+
+/// Fan-out of completed job ids to waiting requests
+pub struct Completions {
+    tx: broadcast::Sender<Uuid>,
+}
+
+impl Default for Completions {
+    fn default() -> Self {
+        Self { tx: broadcast::channel(CHANNEL_CAPACITY).0 }
+    }
+}
+
+impl Completions {
+    /// A job's result was stored
+    pub fn notify(&self, job_id: Uuid) {
+        // No receivers just means nobody is waiting
+        let _ = self.tx.send(job_id);
+    }
+
+    /// Subscribe before reading the result, so a completion in between isn't missed
+    pub fn subscribe(&self) -> broadcast::Receiver<Uuid> {
+        self.tx.subscribe()
+    }
+}
+
+/// Wait until `job_id` completes, `deadline` passes or it is time to re-read the result;
+/// true when the job's completion was seen
+pub async fn wait_for(rx: &mut broadcast::Receiver<Uuid>, job_id: Uuid, deadline: Instant) -> bool {
+    let until = deadline.min(Instant::now() + RECHECK_INTERVAL);
+    loop {
+        match tokio::time::timeout_at(until, rx.recv()).await {
+            Ok(Ok(id)) if id == job_id => return true,
+            Ok(Ok(_)) => continue,
+            // Missed messages might include ours
+            Ok(Err(broadcast::error::RecvError::Lagged(_))) => return true,
+            Ok(Err(broadcast::error::RecvError::Closed)) | Err(_) => return false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_wait_for_wakes_on_its_job() {
+        let completions = Completions::default();
+        let job_id = Uuid::new_v4();
+        let mut rx = completions.subscribe();
+        completions.notify(Uuid::new_v4());
+        completions.notify(job_id);
+        assert!(wait_for(&mut rx, job_id, Instant::now() + Duration::from_secs(1)).await);
+
+        // Nothing for this job: returns at the deadline
+        let start = Instant::now();
+        completions.notify(Uuid::new_v4());
+        assert!(!wait_for(&mut rx, job_id, start + Duration::from_millis(50)).await);
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+}
@@ -2,25 +2,70 @@ mod handlers;
 mod routes;
 mod metrics;
 mod language_config;
+mod retention;
+mod dlq_retention;
+mod canary;
+mod compare;
+mod events;
+mod notifier;
+mod validation;
+mod error_catalog;
+mod deletion;
+mod watchdog;
+mod problem;
+mod etag;
+mod longpoll;
+mod stream;
+mod quota;
+mod result_cache;
+mod redis_pool;
+mod auth;
 
 use axum::Router;
+use clap::Parser;
 use futures_util::StreamExt;
 use redis::aio::ConnectionManager;
 use std::sync::Arc;
 use tokio::net::TcpListener;
-use tracing::info;
+use tower_http::compression::predicate::{NotForContentType, Predicate, SizeAbove};
+use tower_http::compression::CompressionLayer;
+use tracing::{info, warn};
 
 #[derive(Clone)]
 pub struct AppState {
-    pub redis: ConnectionManager,
+    /// Connections for request handlers
+    pub redis: Arc<redis_pool::RedisPool>,
     pub start_time: Arc<std::time::Instant>,
     pub language_registry: Arc<language_config::LanguageRegistry>,
+    /// Verifies worker signatures on stored results (None = signing disabled)
+    pub result_signer: Option<&'static optimus_common::signing::ResultSigner>,
+    /// Retention policy and janitor progress
+    pub retention: Arc<retention::Janitor>,
+    /// Completed job ids, for requests long-polling a result
+    pub completions: Arc<longpoll::Completions>,
+    /// Live progress events, for WebSocket streams
+    pub progress: Arc<stream::ProgressHub>,
+    /// Per-API-key submission rate and CPU quotas
+    pub quota: Arc<quota::QuotaConfig>,
+    /// Completed results recently served by GET /job/{id}
+    pub result_cache: Arc<result_cache::ResultCache>,
+    /// Configured API and admin keys
+    pub keys: Arc<auth::KeyStore>,
+}
+
+#[derive(Parser)]
+#[command(name = "optimus-api", about = "Optimus API server")]
+struct Args {
+    /// Config profile: overlays config/languages.{env}.json on config/languages.json
+    #[arg(long, env = optimus_common::language_config::PROFILE_ENV_VAR, value_parser = optimus_common::language_config::parse_profile)]
+    env: Option<String>,
 }
 
 #[tokio::main]
 async fn main() {
     // Load environment variables from .env file
     dotenvy::dotenv().ok();
+    let args = Args::parse();
     
     // Initialize tracing subscriber
     tracing_subscriber::fmt()
@@ -37,23 +82,39 @@ async fn main() {
     metrics::init_metrics();
     info!("Metrics registry initialized");
 
+    // Resolve secrets kept in Vault / AWS Secrets Manager before any config reads them
+    optimus_common::secrets::init().await
+        .unwrap_or_else(|e| panic!("Failed to resolve secrets: {}", e));
+
     // Connect to Redis
-    let redis_url = std::env::var("REDIS_URL")
-        .unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+    let redis_url = redis_url();
     
     let client = redis::Client::open(redis_url.as_str())
         .expect("Failed to create Redis client");
     
-    let redis_conn = ConnectionManager::new(client).await
+    let redis_conn = optimus_common::retry::connect(&client).await
         .expect("Failed to connect to Redis");
     
-    info!("Connected to Redis: {}", redis_url);
+    let pool_size = redis_pool::RedisPool::size_from_env()
+        .unwrap_or_else(|e| panic!("Invalid Redis pool configuration: {}", e));
+    let redis_pool = redis_pool::RedisPool::connect(&client, pool_size).await
+        .expect("Failed to connect to Redis");
+
+    info!(
+        "Connected to Redis: {} ({} request connections)",
+        optimus_common::secrets::redact_url(&redis_url),
+        redis_pool.size()
+    );
 
     // Load language configuration
     let config_path = std::env::var("LANGUAGE_CONFIG_PATH")
         .unwrap_or_else(|_| "config/languages.json".to_string());
     
-    let language_registry = language_config::LanguageRegistry::load_from_file(&config_path)
+    let profile = args.env;
+    if let Some(profile) = &profile {
+        info!("Config profile: {}", profile);
+    }
+    let language_registry = language_config::LanguageRegistry::load_from_file(&config_path, profile.as_deref())
         .unwrap_or_else(|e| {
             panic!("Failed to load language configuration from {}: {}", config_path, e);
         });
@@ -64,18 +125,154 @@ async fn main() {
         .collect();
     info!("Loaded language configuration: enabled languages = {:?}", enabled_langs);
 
+    // Jobs under legacy queue names (jobs:{language}) are never picked up
+    warn_legacy_queues(&mut redis_conn.clone(), &language_registry.enabled_languages()).await;
+
+    // Load safety limits (file and env overrides; reloaded at runtime)
+    let limits = optimus_common::limits::init()
+        .unwrap_or_else(|e| panic!("Invalid safety limits: {}", e));
+    info!(
+        "Safety limits: max_test_cases={}, max_source_code_bytes={}, timeout_ms={}..={}",
+        limits.max_test_cases, limits.max_source_code_bytes, limits.min_timeout_ms, limits.max_timeout_ms
+    );
+
+    // Load result signing keys (verification only - workers sign)
+    let result_signer = optimus_common::signing::init_from_env()
+        .unwrap_or_else(|e| panic!("Invalid result signing configuration: {}", e));
+    match result_signer {
+        Some(signer) => info!("Result signature verification enabled (active key: {})", signer.active_key_id()),
+        None => info!("Result signature verification disabled"),
+    }
+
+    // Load encryption-at-rest keys (API seals queued jobs and opens stored results)
+    match optimus_common::crypto::init_from_env()
+        .unwrap_or_else(|e| panic!("Invalid encryption configuration: {}", e))
+    {
+        Some(cipher) => info!("Encryption at rest enabled (active key: {})", cipher.active_key_id()),
+        None => info!("Encryption at rest disabled"),
+    }
+
+    // Load retention policy
+    let retention_policy = retention::RetentionPolicy::from_env()
+        .unwrap_or_else(|e| panic!("Invalid retention configuration: {}", e));
+    match &retention_policy {
+        Some(policy) => info!(
+            "Retention enabled: mode={:?}, max_age={}s, tenant overrides={}, interval={}s",
+            policy.mode,
+            policy.default_max_age_secs,
+            policy.tenant_max_age_secs.len(),
+            policy.interval_secs
+        ),
+        None => info!("Retention disabled (RETENTION_MAX_AGE_SECS not set)"),
+    }
+    let dlq_policy = dlq_retention::DlqPolicy::from_env()
+        .unwrap_or_else(|e| panic!("Invalid DLQ retention configuration: {}", e));
+    match &dlq_policy {
+        Some(policy) => info!(
+            "DLQ retention enabled: max_len={:?}, max_age={:?}s, archive={}",
+            policy.max_len,
+            policy.max_age_secs,
+            policy.archive.is_some()
+        ),
+        None => info!("DLQ retention disabled (DLQ_MAX_LEN and DLQ_MAX_AGE_SECS not set)"),
+    }
+    let janitor = Arc::new(retention::Janitor::new(retention_policy, dlq_policy));
+
+    // Load event webhooks
+    let webhook_targets = events::from_env()
+        .unwrap_or_else(|e| panic!("Invalid webhook configuration: {}", e));
+    match &webhook_targets {
+        Some(targets) => info!("Event webhooks enabled: {} target(s)", targets.len()),
+        None => info!("Event webhooks disabled (EVENT_WEBHOOKS not set)"),
+    }
+
+    // Load alert notifier
+    let notifier_config = notifier::NotifierConfig::from_env()
+        .unwrap_or_else(|e| panic!("Invalid alert configuration: {}", e));
+    match &notifier_config {
+        Some(config) => info!(
+            "Alerts enabled: channels={}, queue_depth>={}, dlq_growth>={}, interval={}s, cooldown={}s",
+            config.channels.len(),
+            config.queue_depth_threshold,
+            config.dlq_growth_threshold,
+            config.interval_secs,
+            config.cooldown_secs
+        ),
+        None => info!("Alerts disabled (ALERT_CHANNELS not set)"),
+    }
+
+    // Load per-API-key limits
+    let quota_config = quota::QuotaConfig::from_env()
+        .unwrap_or_else(|e| panic!("Invalid quota configuration: {}", e));
+    info!(
+        "Per-key limits: submissions/minute={}, cpu_seconds/day={}",
+        quota_config.submissions_per_minute.map_or("unlimited".to_string(), |n| n.to_string()),
+        quota_config.cpu_seconds_per_day.map_or("unlimited".to_string(), |n| n.to_string())
+    );
+    let quota_config = Arc::new(quota_config);
+
+    let result_cache = result_cache::ResultCache::from_env()
+        .unwrap_or_else(|e| panic!("Invalid result cache configuration: {}", e));
+    info!("Result cache TTL: {}ms", result_cache.ttl().as_millis());
+    let result_cache = Arc::new(result_cache);
+
+    let keys = auth::KeyStore::from_env()
+        .unwrap_or_else(|e| panic!("Invalid API key configuration: {}", e));
+    info!("API keys: {} submitter, {} admin", keys.user_keys(), keys.admin_keys());
+    if keys.admin_keys() == 0 {
+        warn!("ADMIN_API_KEYS not set: /admin endpoints will refuse every request");
+    }
+
+    let completions = Arc::new(longpoll::Completions::default());
+    let progress = Arc::new(stream::ProgressHub::default());
     let state = Arc::new(AppState {
-        redis: redis_conn.clone(),
+        redis: Arc::new(redis_pool),
         start_time: Arc::new(std::time::Instant::now()),
         language_registry: Arc::new(language_registry),
+        result_signer,
+        retention: janitor.clone(),
+        completions: completions.clone(),
+        progress: progress.clone(),
+        quota: quota_config,
+        result_cache: result_cache.clone(),
+        keys: Arc::new(keys),
     });
 
-    // Start background metrics subscriber
-    tokio::spawn(metrics_subscriber());
+    // Start background metrics subscriber (it also wakes long-polling requests, feeds
+    // progress streams and drops rejudged results from the cache)
+    tokio::spawn(metrics_subscriber(completions, progress, result_cache));
+
+    // Start retention janitor (no-op when retention is disabled)
+    tokio::spawn(retention::janitor_loop(janitor, redis_conn.clone()));
+
+    // Flag languages whose queue has jobs but no live worker
+    tokio::spawn(watchdog::watchdog_loop(state.language_registry.clone(), redis_conn.clone()));
+
+    // Pick up edited safety limits (SIGHUP or LIMITS_RELOAD_SECS)
+    tokio::spawn(optimus_common::limits::watch());
+
+    // Purge deleted jobs once their restore window closes
+    tokio::spawn(deletion::purge_loop(redis_conn.clone()));
+
+    // Start webhook dispatcher for the event stream. Its reads block, so it gets its own
+    // connection rather than stalling requests on the shared multiplexed one.
+    if let Some(targets) = webhook_targets {
+        let events_conn = ConnectionManager::new(client.clone()).await
+            .expect("Failed to connect to Redis");
+        tokio::spawn(events::dispatcher_loop(targets, events_conn));
+    }
+
+    // Start alert notifier
+    if let Some(config) = notifier_config {
+        tokio::spawn(notifier::notifier_loop(config, state.language_registry.clone(), redis_conn.clone()));
+    }
 
     // Build router
     let app = Router::new()
-        .merge(routes::routes())
+        .merge(routes::routes(state.clone()))
+        .layer(axum::middleware::from_fn(error_catalog::localize_errors))
+        .layer(axum::middleware::from_fn(problem::problem_details))
+        .layer(CompressionLayer::new().compress_when(compression_predicate()))
         .with_state(state);
 
     // Start server
@@ -92,11 +289,46 @@ async fn main() {
         .expect("Server error");
 }
 
-/// Background task to subscribe to job completion events and update metrics
-async fn metrics_subscriber() {
-    let client = match redis::Client::open(
-        std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string()).as_str()
-    ) {
+/// Compress responses larger than COMPRESSION_MIN_BYTES (default 1024) - big job results,
+/// mostly - for clients that send Accept-Encoding
+fn compression_predicate() -> impl Predicate {
+    let min_bytes = std::env::var("COMPRESSION_MIN_BYTES")
+        .ok()
+        .and_then(|v| v.trim().parse::<u16>().ok())
+        .unwrap_or(1024);
+    SizeAbove::new(min_bytes)
+        .and(NotForContentType::GRPC)
+        .and(NotForContentType::IMAGES)
+        .and(NotForContentType::SSE)
+}
+
+/// Log queues that still hold jobs under legacy names (best-effort)
+async fn warn_legacy_queues(conn: &mut ConnectionManager, languages: &[optimus_common::types::Language]) {
+    match optimus_common::redis::legacy_queue_depths(conn, languages).await {
+        Ok(stuck) => {
+            for (queue, depth) in stuck {
+                warn!(queue = %queue, depth, "Jobs stuck under a legacy queue name - run `optimus-cli migrate-queues`");
+            }
+        }
+        Err(e) => warn!(error = %e, "Failed to check legacy queues"),
+    }
+}
+
+/// REDIS_URL (possibly a secret reference), defaulting to a local Redis
+fn redis_url() -> String {
+    optimus_common::secrets::var("REDIS_URL")
+        .unwrap_or_else(|e| panic!("Invalid REDIS_URL: {}", e))
+        .unwrap_or_else(|| "redis://127.0.0.1:6379".to_string())
+}
+
+/// Background task to subscribe to job completion events, update metrics and wake
+/// requests waiting for those jobs; it also forwards progress events to open streams
+async fn metrics_subscriber(
+    completions: Arc<longpoll::Completions>,
+    progress: Arc<stream::ProgressHub>,
+    result_cache: Arc<result_cache::ResultCache>,
+) {
+    let client = match redis::Client::open(redis_url().as_str()) {
         Ok(c) => c,
         Err(e) => {
             tracing::error!("Failed to create Redis client for metrics subscriber: {}", e);
@@ -112,37 +344,58 @@ async fn metrics_subscriber() {
         }
     };
     
-    if let Err(e) = pubsub.subscribe("optimus:metrics:completions").await {
+    let progress_channel = optimus_common::redis::progress_channel();
+    let variance_channel = optimus_common::redis::variance_channel();
+    let channels = [optimus_common::redis::completions_channel(), variance_channel.clone(), progress_channel.clone()];
+    if let Err(e) = pubsub.subscribe(&channels).await {
         tracing::error!("Failed to subscribe to metrics channel: {}", e);
         return;
     }
     
     info!("Metrics subscriber started - listening for job completions");
     
-    loop {
-        match pubsub.on_message().next().await {
-            Some(msg) => {
-                let payload: String = match msg.get_payload() {
-                    Ok(p) => p,
-                    Err(_) => continue,
-                };
-                
-                if let Ok(event) = serde_json::from_str::<serde_json::Value>(&payload) {
-                    let language = event["language"].as_str().unwrap_or("unknown");
-                    let status = event["status"].as_str().unwrap_or("unknown");
-                    let exec_time = event["execution_time_ms"].as_f64().unwrap_or(0.0);
-                    
-                    metrics::record_job_completed(language, status, exec_time);
-                    
-                    tracing::debug!(
-                        job_id = event["job_id"].as_str().unwrap_or("unknown"),
-                        language = language,
-                        status = status,
-                        "Recorded job completion metrics"
-                    );
-                }
+    while let Some(msg) = pubsub.on_message().next().await {
+        let payload: String = match msg.get_payload() {
+            Ok(p) => p,
+            Err(_) => continue,
+        };
+        
+        let channel = msg.get_channel_name().to_string();
+        if channel == progress_channel {
+            if let Ok(event) = serde_json::from_str(&payload) {
+                progress.publish(event);
+            }
+            continue;
+        }
+        if channel == variance_channel {
+            if let Ok(event) = serde_json::from_str::<serde_json::Value>(&payload) {
+                metrics::record_timing_variance(
+                    event["language"].as_str().unwrap_or("unknown"),
+                    event["pinned"].as_bool().unwrap_or(false),
+                    event["cv"].as_f64().unwrap_or(0.0),
+                );
+            }
+            continue;
+        }
+
+        if let Ok(event) = serde_json::from_str::<serde_json::Value>(&payload) {
+            if let Some(job_id) = event["job_id"].as_str().and_then(|id| uuid::Uuid::parse_str(id).ok()) {
+                // A stored result replaces any cached one (a rejudged job completes again)
+                result_cache.invalidate(&job_id);
+                completions.notify(job_id);
             }
-            None => break,
+            let language = event["language"].as_str().unwrap_or("unknown");
+            let status = event["status"].as_str().unwrap_or("unknown");
+            let exec_time = event["execution_time_ms"].as_f64().unwrap_or(0.0);
+            
+            metrics::record_job_completed(language, status, exec_time);
+            
+            tracing::debug!(
+                job_id = event["job_id"].as_str().unwrap_or("unknown"),
+                language = language,
+                status = status,
+                "Recorded job completion metrics"
+            );
         }
     }
 }
@@ -49,6 +49,38 @@ lazy_static! {
     )
     .expect("metric can be created");
 
+    // Redis calls made through the API's pool, by command class and result (ok / error)
+    pub static ref REDIS_COMMAND_SECONDS: HistogramVec = HistogramVec::new(
+        HistogramOpts::new(
+            "optimus_api_redis_command_seconds",
+            "Latency of Redis calls made by API handlers"
+        )
+        .buckets(vec![0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 1.0]),
+        &["class", "result"]
+    )
+    .expect("metric can be created");
+
+    // GET /job/{id} result cache lookups, by result (hit / miss)
+    pub static ref RESULT_CACHE_LOOKUPS: CounterVec = CounterVec::new(
+        Opts::new("optimus_result_cache_lookups_total", "Result cache lookups by GET /job/{id}"),
+        &["result"]
+    )
+    .expect("metric can be created");
+
+    // DLQ entries trimmed by the retention janitor (see dlq_retention.rs)
+    pub static ref DLQ_EVICTIONS: CounterVec = CounterVec::new(
+        Opts::new("optimus_dlq_evictions_total", "DLQ entries removed by DLQ retention"),
+        &["queue", "reason"]
+    )
+    .expect("metric can be created");
+
+    // DLQ entries archived before trimming, by result (ok / error)
+    pub static ref DLQ_ARCHIVED: CounterVec = CounterVec::new(
+        Opts::new("optimus_dlq_archived_total", "DLQ entries archived before trimming"),
+        &["result"]
+    )
+    .expect("metric can be created");
+
     // Jobs rejected counter (backpressure)
     pub static ref JOBS_REJECTED: CounterVec = CounterVec::new(
         Opts::new("optimus_jobs_rejected_total", "Total jobs rejected due to validation"),
@@ -56,12 +88,44 @@ lazy_static! {
     )
     .expect("metric can be created");
 
+    // Run-to-run timing variance of sampled tests (see the worker's determinism module)
+    pub static ref EXEC_TIME_CV: HistogramVec = HistogramVec::new(
+        HistogramOpts::new(
+            "optimus_exec_time_cv",
+            "Coefficient of variation of repeated test execution times"
+        )
+        .buckets(vec![0.01, 0.02, 0.05, 0.1, 0.2, 0.5, 1.0]),
+        &["language", "pinned"]
+    )
+    .expect("metric can be created");
+
     // Jobs cancelled counter
     pub static ref JOBS_CANCELLED: CounterVec = CounterVec::new(
         Opts::new("optimus_jobs_cancelled_total", "Total jobs cancelled"),
         &["source"]
     )
     .expect("metric can be created");
+
+    // Webhook deliveries of system events (delivered / dropped)
+    pub static ref WEBHOOK_DELIVERIES: CounterVec = CounterVec::new(
+        Opts::new("optimus_webhook_deliveries_total", "Total system event webhook deliveries"),
+        &["result"]
+    )
+    .expect("metric can be created");
+
+    // 1 while a language has queued jobs but no live worker (see watchdog.rs)
+    pub static ref LANGUAGE_STARVED: IntGaugeVec = IntGaugeVec::new(
+        Opts::new("optimus_language_starved", "Whether a language's queue is starved of workers"),
+        &["language"]
+    )
+    .expect("metric can be created");
+
+    // Operational alerts sent by the notifier, per channel delivery (sent / failed)
+    pub static ref ALERTS_SENT: CounterVec = CounterVec::new(
+        Opts::new("optimus_alerts_sent_total", "Total operational alert deliveries"),
+        &["result"]
+    )
+    .expect("metric can be created");
 }
 
 /// Initialize metrics registry
@@ -93,6 +157,38 @@ pub fn init_metrics() {
     REGISTRY
         .register(Box::new(JOBS_CANCELLED.clone()))
         .expect("collector can be registered");
+
+    REGISTRY
+        .register(Box::new(EXEC_TIME_CV.clone()))
+        .expect("collector can be registered");
+
+    REGISTRY
+        .register(Box::new(WEBHOOK_DELIVERIES.clone()))
+        .expect("collector can be registered");
+
+    REGISTRY
+        .register(Box::new(ALERTS_SENT.clone()))
+        .expect("collector can be registered");
+
+    REGISTRY
+        .register(Box::new(DLQ_EVICTIONS.clone()))
+        .expect("collector can be registered");
+
+    REGISTRY
+        .register(Box::new(DLQ_ARCHIVED.clone()))
+        .expect("collector can be registered");
+
+    REGISTRY
+        .register(Box::new(RESULT_CACHE_LOOKUPS.clone()))
+        .expect("collector can be registered");
+
+    REGISTRY
+        .register(Box::new(LANGUAGE_STARVED.clone()))
+        .expect("collector can be registered");
+
+    REGISTRY
+        .register(Box::new(REDIS_COMMAND_SECONDS.clone()))
+        .expect("collector can be registered");
 }
 
 /// Render metrics in Prometheus text format
@@ -121,13 +217,13 @@ pub fn record_job_completed(language: &str, status: &str, execution_time_ms: f64
 }
 
 /// Update queue depth for a language
-pub async fn update_queue_depths(redis_conn: &mut redis::aio::ConnectionManager) {
-    use redis::AsyncCommands;
+pub async fn update_queue_depths(pool: &crate::redis_pool::RedisPool) {
     use optimus_common::types::Language;
-    
-    for language in Language::all_variants() {
-        let queue_name = optimus_common::redis::queue_name(language);
-        if let Ok(depth) = redis_conn.llen::<_, i64>(&queue_name).await {
+
+    let languages = Language::all_variants();
+    let queues: Vec<String> = languages.iter().map(optimus_common::redis::queue_name).collect();
+    if let Ok(depths) = pool.queue_lengths(&queues).await {
+        for (language, depth) in languages.iter().zip(depths) {
             QUEUE_DEPTH
                 .with_label_values(&[&language.to_string()])
                 .set(depth);
@@ -139,3 +235,47 @@ pub async fn update_queue_depths(redis_conn: &mut redis::aio::ConnectionManager)
 pub fn record_job_cancelled(source: &str) {
     JOBS_CANCELLED.with_label_values(&[source]).inc();
 }
+
+/// Record a timing-variance sample
+pub fn record_timing_variance(language: &str, pinned: bool, cv: f64) {
+    EXEC_TIME_CV
+        .with_label_values(&[language, if pinned { "true" } else { "false" }])
+        .observe(cv);
+}
+
+/// Record a webhook delivery outcome
+pub fn record_webhook_delivery(result: &str) {
+    WEBHOOK_DELIVERIES.with_label_values(&[result]).inc();
+}
+
+/// Set whether a language is starved of workers
+pub fn set_language_starved(language: &str, starved: bool) {
+    LANGUAGE_STARVED.with_label_values(&[language]).set(starved as i64);
+}
+
+/// Record an alert delivery outcome
+pub fn record_alert_sent(result: &str) {
+    ALERTS_SENT.with_label_values(&[result]).inc();
+}
+
+/// Record a DLQ entry trimmed by retention
+pub fn record_dlq_eviction(queue: &str, reason: &str) {
+    DLQ_EVICTIONS.with_label_values(&[queue, reason]).inc();
+}
+
+/// Record a DLQ archive attempt
+pub fn record_dlq_archive(result: &str) {
+    DLQ_ARCHIVED.with_label_values(&[result]).inc();
+}
+
+/// Record a result cache lookup
+pub fn record_result_cache_lookup(result: &str) {
+    RESULT_CACHE_LOOKUPS.with_label_values(&[result]).inc();
+}
+
+/// Record one Redis call made through the API's pool
+pub fn record_redis_command(class: &str, ok: bool, seconds: f64) {
+    REDIS_COMMAND_SECONDS
+        .with_label_values(&[class, if ok { "ok" } else { "error" }])
+        .observe(seconds);
+}
@@ -0,0 +1,545 @@
+// Notifier - built-in operational alerts (Slack, webhook, email)
+//
+// For deployments without Alertmanager. When ALERT_CHANNELS is set, the API checks
+// every ALERT_INTERVAL_SECS (default 60) for:
+// - dlq_growth: a language's DLQ grew by ALERT_DLQ_GROWTH entries (default 10) since the last check
+// - queue_depth: a queue (a language's, or one of its requirement queues) holds
+//   ALERT_QUEUE_DEPTH or more waiting jobs (default 1000)
+// - worker_death: a worker's heartbeat expired without a graceful shutdown (crash, OOM kill)
+// - sandbox_self_test_failed: a worker's sandbox self-test failed
+// - language_starved: a queue's jobs have waited STARVATION_THRESHOLD_SECS (default 300) with
+//   no live worker reading that queue (see watchdog.rs)
+// A threshold of 0 disables that check.
+//
+//   ALERT_CHANNELS='[{"type": "slack", "webhook_url": "https://hooks.slack.com/services/..."},
+//                    {"type": "webhook", "url": "https://ops.example.com/alerts"},
+//                    {"type": "email", "smtp_host": "smtp.example.com", "username": "optimus",
+//                     "password": "...", "from": "optimus@example.com", "to": ["ops@example.com"]}]'
+//
+// Each alert is sent at most once per ALERT_COOLDOWN_SECS (default 900). The cooldown is
+// claimed in Redis, so several API replicas send one alert between them.
+
+use crate::language_config::LanguageRegistry;
+use crate::metrics;
+use crate::watchdog::{self, StarvationWatch};
+use chrono::{DateTime, Utc};
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use optimus_common::{capabilities, redis};
+use optimus_common::types::{EventKind, Language, SystemEvent};
+use ::redis::aio::ConnectionManager;
+use ::redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// Events read from the stream per request; each check reads until it is caught up
+const EVENT_BATCH: usize = 500;
+
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Channel {
+    Slack { webhook_url: String },
+    Webhook { url: String },
+    Email(EmailChannel),
+}
+
+/// SMTP relay reached over STARTTLS
+#[derive(Debug, Clone, Deserialize)]
+pub struct EmailChannel {
+    pub smtp_host: String,
+    #[serde(default = "default_smtp_port")]
+    pub smtp_port: u16,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+fn default_smtp_port() -> u16 {
+    587
+}
+
+#[derive(Debug, Clone)]
+pub struct NotifierConfig {
+    pub channels: Vec<Channel>,
+    pub queue_depth_threshold: i64,
+    pub dlq_growth_threshold: i64,
+    pub interval_secs: u64,
+    pub cooldown_secs: u64,
+    pub starvation_secs: u64,
+}
+
+impl NotifierConfig {
+    /// Load from ALERT_* env vars
+    /// Returns Ok(None) when ALERT_CHANNELS is unset (notifier disabled)
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let channels = match optimus_common::secrets::var("ALERT_CHANNELS")? {
+            Some(v) if !v.trim().is_empty() => parse_channels(&v)?,
+            _ => return Ok(None),
+        };
+
+        Ok(Some(Self {
+            channels,
+            queue_depth_threshold: env_number("ALERT_QUEUE_DEPTH", 1000)?,
+            dlq_growth_threshold: env_number("ALERT_DLQ_GROWTH", 10)?,
+            interval_secs: env_number("ALERT_INTERVAL_SECS", 60)?.max(1),
+            cooldown_secs: env_number("ALERT_COOLDOWN_SECS", 900)?,
+            starvation_secs: watchdog::threshold_secs(),
+        }))
+    }
+}
+
+fn env_number<T: std::str::FromStr>(name: &str, default: T) -> Result<T, String> {
+    match std::env::var(name) {
+        Ok(v) => v
+            .trim()
+            .parse()
+            .map_err(|_| format!("{} must be a non-negative integer, got '{}'", name, v)),
+        Err(_) => Ok(default),
+    }
+}
+
+/// Parse and validate the ALERT_CHANNELS JSON list
+pub fn parse_channels(spec: &str) -> Result<Vec<Channel>, String> {
+    let channels: Vec<Channel> =
+        serde_json::from_str(spec).map_err(|e| format!("ALERT_CHANNELS is not a valid channel list: {}", e))?;
+    if channels.is_empty() {
+        return Err("ALERT_CHANNELS must list at least one channel".to_string());
+    }
+    for channel in &channels {
+        match channel {
+            Channel::Slack { webhook_url: url } | Channel::Webhook { url } => {
+                if !(url.starts_with("http://") || url.starts_with("https://")) {
+                    return Err(format!("Alert URL '{}' must start with http:// or https://", url));
+                }
+            }
+            Channel::Email(email) => {
+                if email.to.is_empty() {
+                    return Err("Email channel needs at least one 'to' address".to_string());
+                }
+                for address in std::iter::once(&email.from).chain(&email.to) {
+                    address
+                        .parse::<Mailbox>()
+                        .map_err(|e| format!("Invalid email address '{}': {}", address, e))?;
+                }
+            }
+        }
+    }
+    Ok(channels)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    DlqGrowth,
+    QueueDepth,
+    WorkerDeath,
+    SandboxSelfTestFailed,
+    LanguageStarved,
+}
+
+impl AlertKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertKind::DlqGrowth => "dlq_growth",
+            AlertKind::QueueDepth => "queue_depth",
+            AlertKind::WorkerDeath => "worker_death",
+            AlertKind::SandboxSelfTestFailed => "sandbox_self_test_failed",
+            AlertKind::LanguageStarved => "language_starved",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Alert {
+    pub kind: AlertKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<Language>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub worker_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub queue: Option<String>,
+    pub message: String,
+    pub at: DateTime<Utc>,
+}
+
+impl Alert {
+    fn new(kind: AlertKind, language: Option<Language>, worker_id: Option<String>, message: String) -> Self {
+        Self { kind, language, worker_id, queue: None, message, at: Utc::now() }
+    }
+
+    fn on_queue(mut self, queue: String) -> Self {
+        self.queue = Some(queue);
+        self
+    }
+
+    /// Identity for cooldowns: repeats of the same condition share a key
+    pub fn key(&self) -> String {
+        let kind = self.kind.as_str();
+        match (&self.worker_id, &self.queue, self.language) {
+            (Some(worker_id), _, _) => format!("{}:{}", kind, worker_id),
+            (None, Some(queue), _) => format!("{}:{}", kind, queue),
+            (None, None, Some(language)) => format!("{}:{}", kind, language),
+            (None, None, None) => kind.to_string(),
+        }
+    }
+
+    pub fn title(&self) -> String {
+        format!("[optimus] {}", self.key())
+    }
+}
+
+/// Queue and worker state at one check
+#[derive(Debug, Default)]
+pub struct Snapshot {
+    /// (language, dead-lettered); requirement queues share their language's DLQ
+    pub dead_lettered: Vec<(Language, i64)>,
+    /// (queue, language, waiting jobs) of every pending queue, requirement queues included;
+    /// a queue's retrying jobs count as waiting in it
+    pub pending: Vec<(String, Language, i64)>,
+    /// (worker id, language) of every live heartbeat
+    pub workers: Vec<(String, Language)>,
+    /// Pending queues some live worker reads, from the heartbeats
+    pub consumed: HashSet<String>,
+    /// Workers gone since the last check that shut down gracefully
+    pub stopped: HashSet<String>,
+}
+
+/// What the notifier remembers between checks
+#[derive(Debug, Default)]
+pub struct Watch {
+    dlq_depths: HashMap<Language, i64>,
+    workers: HashMap<String, Language>,
+    starvation: StarvationWatch,
+}
+
+impl Watch {
+    /// Note an event from the stream; some events are alerts in themselves
+    pub fn observe(&mut self, event: &SystemEvent) -> Option<Alert> {
+        match event.kind {
+            EventKind::SandboxSelfTestFailed => {
+                let reason = event.details["reason"].as_str().unwrap_or("unknown reason");
+                Some(Alert::new(
+                    AlertKind::SandboxSelfTestFailed,
+                    event.language,
+                    event.worker_id.clone(),
+                    format!(
+                        "Sandbox self-test failed on worker {}: {}",
+                        event.worker_id.as_deref().unwrap_or("unknown"),
+                        reason
+                    ),
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    /// Workers seen at the last check whose heartbeat is gone from the snapshot
+    pub fn missing(&self, snapshot: &Snapshot) -> Vec<String> {
+        let live: HashSet<&str> = snapshot.workers.iter().map(|(worker_id, _)| worker_id.as_str()).collect();
+        self.workers.keys().filter(|worker_id| !live.contains(worker_id.as_str())).cloned().collect()
+    }
+
+    /// Compare a snapshot with the previous one
+    /// The first check only records a baseline (no growth or deaths can be seen yet)
+    pub fn check(&mut self, snapshot: &Snapshot, config: &NotifierConfig) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+
+        for (queue, language, queued) in &snapshot.pending {
+            if config.queue_depth_threshold > 0 && *queued >= config.queue_depth_threshold {
+                alerts.push(
+                    Alert::new(
+                        AlertKind::QueueDepth,
+                        Some(*language),
+                        None,
+                        format!("{} holds {} jobs (threshold {})", queue, queued, config.queue_depth_threshold),
+                    )
+                    .on_queue(queue.clone()),
+                );
+            }
+        }
+        for &(language, dead_lettered) in &snapshot.dead_lettered {
+            if let Some(previous) = self.dlq_depths.insert(language, dead_lettered) {
+                let growth = dead_lettered - previous;
+                if config.dlq_growth_threshold > 0 && growth >= config.dlq_growth_threshold {
+                    alerts.push(Alert::new(
+                        AlertKind::DlqGrowth,
+                        Some(language),
+                        None,
+                        format!(
+                            "{} DLQ grew by {} to {} in the last {}s",
+                            language, growth, dead_lettered, config.interval_secs
+                        ),
+                    ));
+                }
+            }
+        }
+
+        for starved in self.starvation.check(&snapshot.pending, &snapshot.consumed, Utc::now(), config.starvation_secs) {
+            alerts.push(
+                Alert::new(
+                    AlertKind::LanguageStarved,
+                    Some(starved.language),
+                    None,
+                    format!(
+                        "{} has held jobs ({} now) since {} but no live worker reads it - check worker queue names and capabilities",
+                        starved.queue, starved.queued, starved.since.to_rfc3339()
+                    ),
+                )
+                .on_queue(starved.queue),
+            );
+        }
+
+        let live: HashMap<String, Language> = snapshot.workers.iter().cloned().collect();
+        for (worker_id, language) in &self.workers {
+            if live.contains_key(worker_id) || snapshot.stopped.contains(worker_id) {
+                continue;
+            }
+            alerts.push(Alert::new(
+                AlertKind::WorkerDeath,
+                Some(*language),
+                Some(worker_id.clone()),
+                format!("{} worker {} stopped sending heartbeats without shutting down", language, worker_id),
+            ));
+        }
+        self.workers = live;
+
+        alerts
+    }
+}
+
+/// Background task checking for alert conditions and notifying every channel
+pub async fn notifier_loop(config: NotifierConfig, registry: Arc<LanguageRegistry>, mut conn: ConnectionManager) {
+    let client = match reqwest::Client::builder().timeout(DELIVERY_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(e) => {
+            error!(error = %e, "Failed to create alert HTTP client; notifier disabled");
+            return;
+        }
+    };
+    info!(channels = config.channels.len(), "Notifier started");
+
+    // Only events published from now on
+    let mut last_event_id = format!("{}-0", Utc::now().timestamp_millis());
+    let mut watch = Watch::default();
+    let mut ticker = tokio::time::interval(Duration::from_secs(config.interval_secs));
+
+    loop {
+        ticker.tick().await;
+        let mut alerts = Vec::new();
+
+        loop {
+            match redis::read_events_after(&mut conn, &last_event_id, EVENT_BATCH).await {
+                Ok(entries) => {
+                    let caught_up = entries.len() < EVENT_BATCH;
+                    for (entry_id, event) in entries {
+                        alerts.extend(event.and_then(|event| watch.observe(&event)));
+                        last_event_id = entry_id;
+                    }
+                    if caught_up {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    warn!(error = %e, "Failed to read event stream for alerts");
+                    break;
+                }
+            }
+        }
+
+        match snapshot(&mut conn, &registry).await {
+            Ok(mut snapshot) => match redis::stopped_workers(&mut conn, &watch.missing(&snapshot)).await {
+                Ok(stopped) => {
+                    snapshot.stopped = stopped;
+                    alerts.extend(watch.check(&snapshot, &config));
+                }
+                Err(e) => warn!(error = %e, "Failed to read worker shutdowns for alerts"),
+            },
+            Err(e) => warn!(error = %e, "Failed to read queue state for alerts"),
+        }
+
+        for alert in alerts {
+            match redis::claim_alert(&mut conn, &alert.key(), config.cooldown_secs).await {
+                Ok(true) => send(&client, &config.channels, &alert).await,
+                Ok(false) => {}
+                Err(e) => warn!(alert = %alert.key(), error = %e, "Failed to claim alert cooldown"),
+            }
+        }
+    }
+}
+
+/// Queue depths and live workers, as the notifier and the starvation watchdog see them
+pub async fn snapshot(conn: &mut ConnectionManager, registry: &LanguageRegistry) -> ::redis::RedisResult<Snapshot> {
+    let mut dead_lettered = Vec::new();
+    let mut pending = Vec::new();
+    for language in Language::all_variants() {
+        dead_lettered.push((*language, conn.llen(redis::dlq_name(language)).await?));
+        for requirements in capabilities::satisfiable_sets(registry.requirable_labels(*language)) {
+            let (queue, retry_queue) = redis::requirement_queues(language, &requirements);
+            let (waiting, retrying): (i64, i64) =
+                ::redis::pipe().llen(&queue).llen(&retry_queue).query_async(conn).await?;
+            pending.push((queue, *language, waiting + retrying));
+        }
+    }
+    let mut workers = Vec::new();
+    let mut consumed = HashSet::new();
+    for heartbeat in redis::list_heartbeats(conn).await? {
+        consumed.extend(heartbeat.queues);
+        workers.push((heartbeat.worker_id, heartbeat.language));
+    }
+    Ok(Snapshot { pending, dead_lettered, workers, consumed, stopped: HashSet::new() })
+}
+
+async fn send(client: &reqwest::Client, channels: &[Channel], alert: &Alert) {
+    warn!(alert = %alert.key(), message = %alert.message, "Sending operational alert");
+    for channel in channels {
+        let outcome = match channel {
+            Channel::Slack { webhook_url } => {
+                let text = format!("*{}*\n{}", alert.title(), alert.message);
+                post(client, webhook_url, &serde_json::json!({ "text": text })).await
+            }
+            Channel::Webhook { url } => post(client, url, alert).await,
+            Channel::Email(email) => send_email(email, alert).await,
+        };
+        match outcome {
+            Ok(()) => metrics::record_alert_sent("sent"),
+            Err(e) => {
+                error!(alert = %alert.key(), error = %e, "Failed to send alert");
+                metrics::record_alert_sent("failed");
+            }
+        }
+    }
+}
+
+async fn post<T: Serialize + ?Sized>(client: &reqwest::Client, url: &str, body: &T) -> Result<(), String> {
+    let response = client.post(url).json(body).send().await.map_err(|e| e.to_string())?;
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("{} returned {}", url, response.status()))
+    }
+}
+
+async fn send_email(email: &EmailChannel, alert: &Alert) -> Result<(), String> {
+    let mut builder = Message::builder()
+        .from(email.from.parse::<Mailbox>().map_err(|e| e.to_string())?)
+        .subject(alert.title());
+    for to in &email.to {
+        builder = builder.to(to.parse::<Mailbox>().map_err(|e| e.to_string())?);
+    }
+    let message = builder.body(alert.message.clone()).map_err(|e| e.to_string())?;
+
+    let mut transport = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&email.smtp_host)
+        .map_err(|e| e.to_string())?
+        .port(email.smtp_port)
+        .timeout(Some(DELIVERY_TIMEOUT));
+    if let (Some(username), Some(password)) = (&email.username, &email.password) {
+        transport = transport.credentials(Credentials::new(username.clone(), password.clone()));
+    }
+    transport.build().send(message).await.map(|_| ()).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> NotifierConfig {
+        NotifierConfig {
+            channels: vec![],
+            queue_depth_threshold: 100,
+            dlq_growth_threshold: 5,
+            interval_secs: 60,
+            cooldown_secs: 900,
+            starvation_secs: 300,
+        }
+    }
+
+    fn snapshot(queued: i64, dead_lettered: i64, workers: &[&str]) -> Snapshot {
+        let queue = redis::queue_name(&Language::Python);
+        Snapshot {
+            pending: vec![(queue.clone(), Language::Python, queued)],
+            dead_lettered: vec![(Language::Python, dead_lettered)],
+            workers: workers.iter().map(|w| (w.to_string(), Language::Python)).collect(),
+            consumed: workers.iter().map(|_| queue.clone()).collect(),
+            stopped: HashSet::new(),
+        }
+    }
+
+    fn kinds(alerts: &[Alert]) -> Vec<AlertKind> {
+        alerts.iter().map(|a| a.kind).collect()
+    }
+
+    #[test]
+    fn test_parse_channels() {
+        let channels = parse_channels(
+            r#"[{"type": "slack", "webhook_url": "https://hooks.slack.com/x"},
+                {"type": "email", "smtp_host": "smtp.example.com", "from": "optimus@example.com", "to": ["ops@example.com"]}]"#,
+        )
+        .unwrap();
+        assert!(matches!(&channels[1], Channel::Email(email) if email.smtp_port == 587));
+
+        assert!(parse_channels("[]").is_err());
+        assert!(parse_channels(r#"[{"type": "webhook", "url": "ops.example.com"}]"#).is_err());
+        assert!(parse_channels(r#"[{"type": "email", "smtp_host": "h", "from": "not an address", "to": ["a@b.c"]}]"#).is_err());
+        assert!(parse_channels(r#"[{"type": "pager", "url": "https://x"}]"#).is_err());
+    }
+
+    #[test]
+    fn test_queue_and_dlq_thresholds() {
+        let mut watch = Watch::default();
+        // Baseline: deep queue alerts at once, DLQ growth needs a previous check
+        assert_eq!(kinds(&watch.check(&snapshot(150, 40, &[]), &config())), vec![AlertKind::QueueDepth]);
+        assert!(watch.check(&snapshot(10, 44, &[]), &config()).is_empty());
+
+        let alerts = watch.check(&snapshot(10, 50, &[]), &config());
+        assert_eq!(kinds(&alerts), vec![AlertKind::DlqGrowth]);
+        assert_eq!(alerts[0].key(), "dlq_growth:python");
+
+        // Requirement queues are checked on their own
+        let (gpu_queue, _) = redis::requirement_queues(&Language::Python, &["gpu".to_string()]);
+        let mut deep_gpu = snapshot(10, 50, &[]);
+        deep_gpu.pending.push((gpu_queue.clone(), Language::Python, 150));
+        let alerts = watch.check(&deep_gpu, &config());
+        assert_eq!(kinds(&alerts), vec![AlertKind::QueueDepth]);
+        assert_eq!(alerts[0].key(), format!("queue_depth:{}", gpu_queue));
+
+        let disabled = NotifierConfig { queue_depth_threshold: 0, dlq_growth_threshold: 0, ..config() };
+        assert!(watch.check(&snapshot(10_000, 10_000, &[]), &disabled).is_empty());
+    }
+
+    #[test]
+    fn test_worker_death_ignores_graceful_shutdown() {
+        let mut watch = Watch::default();
+        watch.check(&snapshot(0, 0, &["w1", "w2"]), &config());
+
+        let mut gone = snapshot(0, 0, &[]);
+        let mut missing = watch.missing(&gone);
+        missing.sort();
+        assert_eq!(missing, vec!["w1", "w2"]);
+
+        gone.stopped.insert("w1".to_string());
+        let alerts = watch.check(&gone, &config());
+        assert_eq!(kinds(&alerts), vec![AlertKind::WorkerDeath]);
+        assert_eq!(alerts[0].worker_id.as_deref(), Some("w2"));
+        assert!(watch.missing(&snapshot(0, 0, &[])).is_empty());
+    }
+
+    #[test]
+    fn test_self_test_failure_event_alerts() {
+        let mut watch = Watch::default();
+        let event = SystemEvent::worker(EventKind::SandboxSelfTestFailed, "w1", Language::Java)
+            .with_details(serde_json::json!({ "reason": "network isolation broken" }));
+        let alert = watch.observe(&event).unwrap();
+        assert_eq!(alert.key(), "sandbox_self_test_failed:w1");
+        assert!(alert.message.contains("network isolation broken"));
+        assert!(watch.observe(&SystemEvent::worker(EventKind::WorkerUp, "w1", Language::Java)).is_none());
+    }
+}
@@ -0,0 +1,163 @@
+// RFC 7807 problem details - error bodies as application/problem+json on request
+//
+// Errors are `{"error": {"code", "message"}}` by default. A client whose Accept header
+// prefers `application/problem+json` (over `application/json`) gets the same error as a
+// problem document instead, which API gateways and standard HTTP clients read uniformly:
+//
+//   type     PROBLEM_TYPE_BASE_URI + the code in kebab case (urn:optimus:error:job-not-found)
+//   title    catalog text of the code, in the Accept-Language locale
+//   status   the HTTP status
+//   detail   the handler's message for this occurrence
+//   instance the request path
+//   code     the error code, plus any other field of the error (e.g. retry_after_secs)
+//
+// This layer runs after error localization, so it reads the translated message from there.
+
+use crate::error_catalog;
+use axum::body::Body;
+use axum::extract::Request;
+use axum::http::{header, StatusCode};
+use axum::middleware::Next;
+use axum::response::Response;
+use optimus_common::i18n::Locale;
+use serde_json::{Map, Value};
+use std::sync::OnceLock;
+
+pub const CONTENT_TYPE: &str = "application/problem+json";
+
+/// Prefix of problem type URIs (PROBLEM_TYPE_BASE_URI, default `urn:optimus:error:`)
+fn type_base_uri() -> &'static str {
+    static BASE: OnceLock<String> = OnceLock::new();
+    BASE.get_or_init(|| {
+        std::env::var("PROBLEM_TYPE_BASE_URI")
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(|| "urn:optimus:error:".to_string())
+    })
+}
+
+/// Problem type URI of an error code ("JOB_NOT_FOUND" -> "{base}job-not-found")
+pub fn type_uri(base: &str, code: &str) -> String {
+    format!("{}{}", base, code.to_ascii_lowercase().replace('_', "-"))
+}
+
+/// Whether an Accept header prefers problem documents to plain JSON
+///
+/// True when `application/problem+json` is acceptable with a q-value at least that of
+/// `application/json`. Wildcards don't count: they accept either format.
+pub fn wants_problem(accept: Option<&str>) -> bool {
+    let Some(accept) = accept else { return false };
+    let q_of = |media_type: &str| {
+        accept
+            .split(',')
+            .filter_map(|range| {
+                let mut parts = range.split(';');
+                if !parts.next()?.trim().eq_ignore_ascii_case(media_type) {
+                    return None;
+                }
+                Some(
+                    parts
+                        .find_map(|p| p.trim().strip_prefix("q="))
+                        .map_or(1.0, |q| q.trim().parse::<f32>().unwrap_or(0.0)),
+                )
+            })
+            .fold(0.0f32, f32::max)
+    };
+    let problem = q_of(CONTENT_TYPE);
+    problem > 0.0 && problem >= q_of("application/json")
+}
+
+/// Problem document for a JSON error body; None when the body has no `error.code`
+///
+/// A localized body carries the translation in `message` and the original in `detail`;
+/// otherwise the catalog's English text (or the message itself) is the title.
+pub fn problem_body(body: &[u8], status: StatusCode, instance: &str, type_base: &str) -> Option<Vec<u8>> {
+    let value: Value = serde_json::from_slice(body).ok()?;
+    let mut error = value.get("error")?.as_object()?.clone();
+    let code = error.get("code")?.as_str()?.to_string();
+    let message = error.remove("message").and_then(|m| m.as_str().map(str::to_string)).unwrap_or_default();
+    let (title, detail) = match error.remove("detail").and_then(|d| d.as_str().map(str::to_string)) {
+        Some(original) => (message, original),
+        None => (
+            error_catalog::message(&code, Locale::En).map_or_else(|| message.clone(), str::to_string),
+            message,
+        ),
+    };
+
+    let mut problem = Map::new();
+    problem.insert("type".to_string(), type_uri(type_base, &code).into());
+    problem.insert("title".to_string(), title.into());
+    problem.insert("status".to_string(), status.as_u16().into());
+    problem.insert("detail".to_string(), detail.into());
+    problem.insert("instance".to_string(), instance.into());
+    // Extension members: the code and whatever else the error carried
+    problem.extend(error);
+    serde_json::to_vec(&Value::Object(problem)).ok()
+}
+
+/// Middleware: error responses as problem documents for clients that ask for them
+pub async fn problem_details(request: Request, next: Next) -> Response {
+    let wanted = wants_problem(request.headers().get(header::ACCEPT).and_then(|v| v.to_str().ok()));
+    let instance = request.uri().path().to_string();
+    let response = next.run(request).await;
+    if !wanted {
+        return response;
+    }
+
+    let status = response.status();
+    let rewritten = error_catalog::rewrite_error_body(response, |body| {
+        problem_body(body, status, &instance, type_base_uri())
+    })
+    .await;
+    match rewritten {
+        Ok((mut parts, problem)) => {
+            parts.headers.insert(header::CONTENT_TYPE, header::HeaderValue::from_static(CONTENT_TYPE));
+            parts.headers.append(header::VARY, header::HeaderValue::from_static("Accept"));
+            Response::from_parts(parts, Body::from(problem))
+        }
+        Err(response) => response,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wants_problem() {
+        assert!(wants_problem(Some("application/problem+json")));
+        assert!(wants_problem(Some("application/problem+json, application/json")));
+        assert!(wants_problem(Some("application/json;q=0.5, application/problem+json")));
+        assert!(!wants_problem(Some("application/json, application/problem+json;q=0.9")));
+        assert!(!wants_problem(Some("application/problem+json;q=0")));
+        assert!(!wants_problem(Some("*/*")));
+        assert!(!wants_problem(Some("application/json")));
+        assert!(!wants_problem(None));
+    }
+
+    #[test]
+    fn test_problem_body() {
+        let body = br#"{"error":{"code":"SUBMISSION_COOLDOWN","message":"Wait 30s","retry_after_secs":30}}"#;
+        let problem: Value =
+            serde_json::from_slice(&problem_body(body, StatusCode::TOO_MANY_REQUESTS, "/execute", "urn:optimus:error:").unwrap())
+                .unwrap();
+        assert_eq!(problem["type"], "urn:optimus:error:submission-cooldown");
+        assert_eq!(problem["title"], error_catalog::message("SUBMISSION_COOLDOWN", Locale::En).unwrap());
+        assert_eq!(problem["status"], 429);
+        assert_eq!(problem["detail"], "Wait 30s");
+        assert_eq!(problem["instance"], "/execute");
+        assert_eq!(problem["code"], "SUBMISSION_COOLDOWN");
+        assert_eq!(problem["retry_after_secs"], 30);
+
+        // Localized bodies keep the translation as the title
+        let body = br#"{"error":{"code":"INVALID_JOB_ID","message":"El identificador","detail":"Invalid job ID format"}}"#;
+        let problem: Value =
+            serde_json::from_slice(&problem_body(body, StatusCode::BAD_REQUEST, "/job/x", "/errors/").unwrap()).unwrap();
+        assert_eq!(problem["type"], "/errors/invalid-job-id");
+        assert_eq!(problem["title"], "El identificador");
+        assert_eq!(problem["detail"], "Invalid job ID format");
+
+        assert!(problem_body(br#"{"status":"ok"}"#, StatusCode::BAD_REQUEST, "/", "").is_none());
+    }
+}
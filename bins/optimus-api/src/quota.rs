@@ -0,0 +1,224 @@
+// Per-API-key rate limiting and CPU quotas (POST /execute)
+//
+// Submissions are counted per authenticated key, by the key's name (see auth.rs; unknown
+// keys are refused before this, and requests without a key share the "anonymous" bucket).
+// Two limits, each off unless configured:
+// - RATE_LIMIT_PER_MINUTE: a token bucket in Redis holding that many submissions and
+//   refilled continuously, so a short burst passes and a steady flood is held to the rate
+// - CPU_QUOTA_SECONDS_PER_DAY: CPU-seconds the key's jobs may use per UTC day
+//
+// Over either limit a submission gets 429 with Retry-After. A submission that ends up not
+// queued (an idempotent replay or conflict, a queue failure) gets its rate token back.
+// A job's CPU time is only known once it completes: a queued submission records the key it
+// is charged to, and the worker
+// charges the job when it stores the result (redis::charged_cpu_seconds: never less than
+// the wall time it measured). A key just under its quota can overshoot it by the jobs it
+// still has running.
+//
+// Redis keys hold the key's name, never the key itself. Redis errors let the submission
+// through, like the cool-down and idempotency checks.
+
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Json, Response};
+use chrono::{DateTime, Duration, Utc};
+use optimus_common::redis;
+
+use crate::auth::Principal;
+use tracing::error;
+use uuid::Uuid;
+
+/// Key id of submissions without an API key
+const ANONYMOUS: &str = "anonymous";
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QuotaConfig {
+    /// Submissions a key may make per minute (None = unlimited)
+    pub submissions_per_minute: Option<u32>,
+    /// CPU-seconds a key's jobs may use per UTC day (None = unlimited)
+    pub cpu_seconds_per_day: Option<u64>,
+}
+
+impl QuotaConfig {
+    /// Load from RATE_LIMIT_PER_MINUTE and CPU_QUOTA_SECONDS_PER_DAY (unset or 0 = off)
+    pub fn from_env() -> Result<Self, String> {
+        Ok(Self {
+            submissions_per_minute: parse_limit("RATE_LIMIT_PER_MINUTE", std::env::var("RATE_LIMIT_PER_MINUTE").ok())?,
+            cpu_seconds_per_day: parse_limit("CPU_QUOTA_SECONDS_PER_DAY", std::env::var("CPU_QUOTA_SECONDS_PER_DAY").ok())?,
+        })
+    }
+}
+
+fn parse_limit<T: std::str::FromStr + Default + PartialEq>(name: &str, value: Option<String>) -> Result<Option<T>, String> {
+    let Some(value) = value.filter(|v| !v.trim().is_empty()) else {
+        return Ok(None);
+    };
+    match value.trim().parse::<T>() {
+        Ok(limit) if limit == T::default() => Ok(None),
+        Ok(limit) => Ok(Some(limit)),
+        Err(_) => Err(format!("{} must be a whole number, got '{}'", name, value)),
+    }
+}
+
+/// Id the request's limits are kept under: the name of its (authenticated) key
+pub fn api_key_id(principal: Option<&Principal>) -> String {
+    principal.map_or_else(|| ANONYMOUS.to_string(), |principal| principal.name.clone())
+}
+
+/// Seconds until the next UTC day, when daily quotas start over
+pub fn secs_until_next_day(now: DateTime<Utc>) -> u64 {
+    let midnight = (now.date_naive() + Duration::days(1)).and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+    (midnight - now).num_seconds().max(1) as u64
+}
+
+/// Why a submission was refused
+#[derive(Debug, PartialEq)]
+pub enum Refusal {
+    RateLimited { per_minute: u32, wait_secs: u64 },
+    CpuQuotaExceeded { quota_secs: u64, wait_secs: u64 },
+}
+
+impl Refusal {
+    /// Label of the jobs_rejected metric
+    pub fn reason(&self) -> &'static str {
+        match self {
+            Refusal::RateLimited { .. } => "rate_limited",
+            Refusal::CpuQuotaExceeded { .. } => "cpu_quota_exceeded",
+        }
+    }
+}
+
+impl IntoResponse for Refusal {
+    fn into_response(self) -> Response {
+        let (wait_secs, body) = match self {
+            Refusal::RateLimited { per_minute, wait_secs } => (
+                wait_secs,
+                serde_json::json!({
+                    "error": {
+                        "code": "RATE_LIMITED",
+                        "message": format!("Submissions are limited to {} per minute; retry in {}s", per_minute, wait_secs),
+                        "retry_after_secs": wait_secs,
+                    }
+                }),
+            ),
+            Refusal::CpuQuotaExceeded { quota_secs, wait_secs } => (
+                wait_secs,
+                serde_json::json!({
+                    "error": {
+                        "code": "CPU_QUOTA_EXCEEDED",
+                        "message": format!("Daily quota of {} CPU-seconds used up; it resets in {}s", quota_secs, wait_secs),
+                        "retry_after_secs": wait_secs,
+                    }
+                }),
+            ),
+        };
+        (StatusCode::TOO_MANY_REQUESTS, [(header::RETRY_AFTER, wait_secs.to_string())], Json(body)).into_response()
+    }
+}
+
+/// A rate token taken by `check`, to `refund` if the job isn't queued after all
+#[derive(Debug, PartialEq)]
+pub struct RateToken {
+    api_key_id: String,
+    per_minute: u32,
+}
+
+/// Check a key's limits before queueing one more job; takes a token when the rate is limited
+///
+/// The CPU quota is checked first, so a key out of quota doesn't also drain its bucket.
+pub async fn check(conn: &mut impl redis::Connection, config: &QuotaConfig, api_key_id: &str) -> Result<Option<RateToken>, Refusal> {
+    let now = Utc::now();
+    if let Some(quota_secs) = config.cpu_seconds_per_day {
+        match redis::cpu_seconds_used(conn, api_key_id, &redis::quota_day(now)).await {
+            Ok(used) if used >= quota_secs as f64 => {
+                return Err(Refusal::CpuQuotaExceeded { quota_secs, wait_secs: secs_until_next_day(now) });
+            }
+            Ok(_) => {}
+            Err(e) => error!(error = %e, "Failed to read CPU quota usage"),
+        }
+    }
+    if let Some(per_minute) = config.submissions_per_minute {
+        match redis::take_rate_token(conn, api_key_id, per_minute).await {
+            Ok(None) => return Ok(Some(RateToken { api_key_id: api_key_id.to_string(), per_minute })),
+            Ok(Some(wait_secs)) => return Err(Refusal::RateLimited { per_minute, wait_secs }),
+            Err(e) => error!(error = %e, "Failed to check submission rate limit"),
+        }
+    }
+    Ok(None)
+}
+
+/// Give back the rate token of a submission that was not queued (best-effort)
+pub async fn refund(conn: &mut impl redis::Connection, token: Option<&RateToken>) {
+    let Some(token) = token else { return };
+    if let Err(e) = redis::refund_rate_token(conn, &token.api_key_id, token.per_minute).await {
+        error!(api_key_id = %token.api_key_id, error = %e, "Failed to refund submission rate token");
+    }
+}
+
+/// Record the key a job's CPU time is charged to, once it is queued (a worker can't finish
+/// the job within the one round trip this takes)
+pub async fn record_owner(conn: &mut impl redis::Connection, config: &QuotaConfig, job_id: Uuid, api_key_id: &str) {
+    if config.cpu_seconds_per_day.is_none() {
+        return;
+    }
+    if let Err(e) = redis::set_quota_owner(conn, &job_id, api_key_id).await {
+        error!(job_id = %job_id, error = %e, "Failed to record quota owner; the job won't count against the quota");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_limits_from_env_values() {
+        assert_eq!(parse_limit::<u32>("X", None), Ok(None));
+        assert_eq!(parse_limit::<u32>("X", Some("0".into())), Ok(None));
+        assert_eq!(parse_limit::<u32>("X", Some(" 30 ".into())), Ok(Some(30)));
+        assert!(parse_limit::<u64>("X", Some("-1".into())).is_err());
+    }
+
+    #[test]
+    fn test_key_ids_are_principal_names() {
+        assert_eq!(api_key_id(None), "anonymous");
+        let course = Principal { name: "course".to_string(), admin: false };
+        assert_eq!(api_key_id(Some(&course)), "course");
+    }
+
+    #[test]
+    fn test_daily_quota_resets_at_utc_midnight() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 16, 23, 59, 30).unwrap();
+        assert_eq!(secs_until_next_day(now), 30);
+        assert_eq!(secs_until_next_day(Utc.with_ymd_and_hms(2026, 10, 16, 0, 0, 0).unwrap()), 86400);
+    }
+
+    #[tokio::test]
+    async fn test_refusals_are_429_with_retry_after() {
+        let response = Refusal::RateLimited { per_minute: 10, wait_secs: 6 }.into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "6");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"]["code"], "RATE_LIMITED");
+        assert_eq!(body["error"]["retry_after_secs"], 6);
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires Redis
+    async fn test_unqueued_submission_gets_its_token_back() {
+        let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+        let mut conn = ::redis::Client::open(url).unwrap().get_connection_manager().await.unwrap();
+        let config = QuotaConfig { submissions_per_minute: Some(1), cpu_seconds_per_day: None };
+        let api_key_id = format!("test-{}", Uuid::new_v4());
+
+        let token = check(&mut conn, &config, &api_key_id).await.unwrap();
+        assert!(token.is_some());
+        assert!(matches!(check(&mut conn, &config, &api_key_id).await, Err(Refusal::RateLimited { .. })));
+        refund(&mut conn, token.as_ref()).await;
+        assert!(check(&mut conn, &config, &api_key_id).await.unwrap().is_some());
+        // Without a rate limit no token is taken, so there's nothing to refund
+        assert_eq!(check(&mut conn, &QuotaConfig::default(), &api_key_id).await.unwrap(), None);
+
+        let _: () = ::redis::AsyncCommands::del(&mut conn, redis::rate_limit_key(&api_key_id)).await.unwrap();
+    }
+}
@@ -0,0 +1,142 @@
+// Redis pool - the API's request connections and batched queue reads
+//
+// A ConnectionManager multiplexes every command over one TCP connection, so a handler
+// reading a long queue holds up every other request behind it. Handlers take a connection
+// from this pool instead: REDIS_POOL_SIZE connections (default 4), handed out round-robin.
+// Background loops (janitor, watchdog, subscribers) keep their own connection.
+//
+// Reads that touch every queue (the debug lookup, queue depths, the DLQ listing) go out as
+// one pipeline here, so they cost one round trip instead of one per queue.
+//
+// Every command sent on a pooled connection is timed in
+// optimus_api_redis_command_seconds{class}: the class is the command's name (`get`,
+// `evalsha`, ...), or for a pipeline the name its commands share, else `pipeline`.
+
+use ::redis::aio::{ConnectionLike, ConnectionManager};
+use ::redis::{Cmd, Pipeline, RedisFuture, RedisResult, Value};
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use crate::metrics;
+
+pub struct RedisPool {
+    conns: Vec<ConnectionManager>,
+    next: AtomicUsize,
+}
+
+impl RedisPool {
+    /// Pool size from REDIS_POOL_SIZE
+    pub fn size_from_env() -> Result<usize, String> {
+        match std::env::var("REDIS_POOL_SIZE") {
+            Ok(v) if !v.trim().is_empty() => match v.trim().parse::<usize>() {
+                Ok(size) if size > 0 => Ok(size),
+                _ => Err(format!("REDIS_POOL_SIZE must be a positive number, got '{}'", v)),
+            },
+            _ => Ok(4),
+        }
+    }
+
+    /// Open `size` connections (retrying while Redis is unreachable)
+    pub async fn connect(client: &::redis::Client, size: usize) -> RedisResult<Self> {
+        let mut conns = Vec::with_capacity(size);
+        for _ in 0..size.max(1) {
+            conns.push(optimus_common::retry::connect(client).await?);
+        }
+        Ok(Self { conns, next: AtomicUsize::new(0) })
+    }
+
+    pub fn size(&self) -> usize {
+        self.conns.len()
+    }
+
+    /// The next connection in turn (a cheap handle to a shared connection)
+    pub fn conn(&self) -> PooledConnection {
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % self.conns.len();
+        PooledConnection(self.conns[index].clone())
+    }
+
+    /// LRANGE `0..=stop` of every queue in one pipeline, in the order given
+    /// (`stop` -1 reads whole queues)
+    pub async fn scan_queues(&self, queues: &[String], stop: isize) -> RedisResult<Vec<Vec<String>>> {
+        if queues.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut pipe = ::redis::pipe();
+        for queue in queues {
+            pipe.cmd("LRANGE").arg(queue).arg(0).arg(stop);
+        }
+        pipe.query_async(&mut self.conn()).await
+    }
+
+    /// LLEN of every queue in one pipeline, in the order given
+    pub async fn queue_lengths(&self, queues: &[String]) -> RedisResult<Vec<i64>> {
+        if queues.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut pipe = ::redis::pipe();
+        for queue in queues {
+            pipe.cmd("LLEN").arg(queue);
+        }
+        pipe.query_async(&mut self.conn()).await
+    }
+}
+
+/// A connection from the pool; times every command sent on it
+#[derive(Clone)]
+pub struct PooledConnection(ConnectionManager);
+
+impl ConnectionLike for PooledConnection {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+        Box::pin(timed(command_class(cmd), self.0.req_packed_command(cmd)))
+    }
+
+    fn req_packed_commands<'a>(&'a mut self, pipe: &'a Pipeline, offset: usize, count: usize) -> RedisFuture<'a, Vec<Value>> {
+        Box::pin(timed(pipeline_class(pipe), self.0.req_packed_commands(pipe, offset, count)))
+    }
+
+    fn get_db(&self) -> i64 {
+        self.0.get_db()
+    }
+}
+
+/// A command's name, lowercased
+fn command_class(cmd: &Cmd) -> String {
+    match cmd.args_iter().next() {
+        Some(::redis::Arg::Simple(name)) => String::from_utf8_lossy(name).to_ascii_lowercase(),
+        _ => "unknown".to_string(),
+    }
+}
+
+/// The name every command of the pipeline shares, else `pipeline`
+fn pipeline_class(pipe: &Pipeline) -> String {
+    let mut classes = pipe.cmd_iter().map(command_class);
+    match classes.next() {
+        Some(first) if classes.all(|class| class == first) => first,
+        _ => "pipeline".to_string(),
+    }
+}
+
+/// Run a Redis call, recording how long it took under `class`
+async fn timed<T>(class: String, call: impl Future<Output = RedisResult<T>>) -> RedisResult<T> {
+    let started = Instant::now();
+    let result = call.await;
+    metrics::record_redis_command(&class, result.is_ok(), started.elapsed().as_secs_f64());
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_classes() {
+        assert_eq!(command_class(::redis::cmd("GET").arg("optimus:result:1")), "get");
+        let mut pipe = ::redis::pipe();
+        pipe.cmd("LLEN").arg("a").cmd("LLEN").arg("b");
+        assert_eq!(pipeline_class(&pipe), "llen");
+        pipe.cmd("LRANGE").arg("a").arg(0).arg(-1);
+        assert_eq!(pipeline_class(&pipe), "pipeline");
+        assert_eq!(pipeline_class(&::redis::pipe()), "pipeline");
+    }
+}
@@ -0,0 +1,130 @@
+// Result cache - short-lived in-process cache for GET /job/{id}
+//
+// Serving a completed job takes three Redis reads: the result, its annotations and its
+// score overrides. When many clients poll one popular job (a scoreboard recalculation, a
+// class refreshing the same submission), those reads repeat for every request. Completed
+// results are read through this cache instead, for RESULT_CACHE_TTL_MS (default 2000; 0
+// disables it), up to RESULT_CACHE_MAX_ENTRIES jobs (default 10000).
+//
+// An entry is dropped when this replica changes the job (override, annotation, delete)
+// and when a worker stores a new result for it (a rejudge), which every replica
+// hears on the completions channel. Overrides and annotations made through another
+// replica show up here once the TTL runs out. Pending jobs and tombstones are never
+// cached, so a deleted job is hidden at once everywhere.
+
+use moka::sync::Cache;
+use optimus_common::types::{Annotation, ExecutionResult, ScoreOverride};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::metrics;
+
+/// What GET /job/{id} reads from Redis for a completed job
+#[derive(Debug, Clone)]
+pub struct CachedResult {
+    pub result: ExecutionResult,
+    pub annotations: Vec<Annotation>,
+    pub score_override: Option<ScoreOverride>,
+}
+
+pub struct ResultCache {
+    /// None when caching is disabled
+    cache: Option<Cache<Uuid, Arc<CachedResult>>>,
+}
+
+impl ResultCache {
+    /// Load from RESULT_CACHE_* env vars
+    pub fn from_env() -> Result<Self, String> {
+        let ttl_ms = parse_env("RESULT_CACHE_TTL_MS", 2000)?;
+        let max_entries = parse_env("RESULT_CACHE_MAX_ENTRIES", 10_000)?;
+        Ok(Self::new(Duration::from_millis(ttl_ms), max_entries))
+    }
+
+    pub fn new(ttl: Duration, max_entries: u64) -> Self {
+        let enabled = !ttl.is_zero() && max_entries > 0;
+        Self {
+            cache: enabled.then(|| Cache::builder().time_to_live(ttl).max_capacity(max_entries).build()),
+        }
+    }
+
+    /// Time entries live for (zero when disabled)
+    pub fn ttl(&self) -> Duration {
+        self.cache.as_ref().and_then(|c| c.policy().time_to_live()).unwrap_or_default()
+    }
+
+    pub fn get(&self, job_id: &Uuid) -> Option<Arc<CachedResult>> {
+        let cache = self.cache.as_ref()?;
+        let cached = cache.get(job_id);
+        metrics::record_result_cache_lookup(if cached.is_some() { "hit" } else { "miss" });
+        cached
+    }
+
+    pub fn insert(&self, job_id: Uuid, entry: CachedResult) -> Arc<CachedResult> {
+        let entry = Arc::new(entry);
+        if let Some(cache) = &self.cache {
+            cache.insert(job_id, entry.clone());
+        }
+        entry
+    }
+
+    /// Drop a job's entry after its result, annotations or overrides changed
+    pub fn invalidate(&self, job_id: &Uuid) {
+        if let Some(cache) = &self.cache {
+            cache.invalidate(job_id);
+        }
+    }
+}
+
+fn parse_env(name: &str, default: u64) -> Result<u64, String> {
+    match std::env::var(name) {
+        Ok(v) if !v.trim().is_empty() => {
+            v.trim().parse().map_err(|_| format!("{} must be a number, got '{}'", name, v))
+        }
+        _ => Ok(default),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use optimus_common::types::JobStatus;
+
+    fn entry(job_id: Uuid) -> CachedResult {
+        CachedResult {
+            result: serde_json::from_value(serde_json::json!({
+                "job_id": job_id,
+                "overall_status": JobStatus::Completed,
+                "score": 10,
+                "max_score": 10,
+                "results": []
+            }))
+            .unwrap(),
+            annotations: Vec::new(),
+            score_override: None,
+        }
+    }
+
+    #[test]
+    fn test_cached_until_invalidated() {
+        let cache = ResultCache::new(Duration::from_secs(60), 100);
+        let job_id = Uuid::new_v4();
+        assert!(cache.get(&job_id).is_none());
+
+        cache.insert(job_id, entry(job_id));
+        assert_eq!(cache.get(&job_id).unwrap().result.score, 10);
+
+        cache.invalidate(&job_id);
+        assert!(cache.get(&job_id).is_none());
+    }
+
+    #[test]
+    fn test_zero_ttl_disables_caching() {
+        let cache = ResultCache::new(Duration::ZERO, 100);
+        let job_id = Uuid::new_v4();
+        // The entry is still handed back to the caller, just not kept
+        assert_eq!(cache.insert(job_id, entry(job_id)).result.score, 10);
+        assert!(cache.get(&job_id).is_none());
+        assert_eq!(cache.ttl(), Duration::ZERO);
+    }
+}
@@ -0,0 +1,567 @@
+// Retention janitor - deletes or anonymizes submissions older than a configured age
+//
+// Every accepted job is recorded in a per-tenant index (sorted set scored by submission
+// time). The janitor walks each tenant's index, and for entries past that tenant's
+// max age it removes or scrubs everything derived from the submission:
+// - result, status, progress and score override keys (anonymize keeps verdicts/scores,
+//   including overrides, and drops program output)
+// - cancellation flags and idempotency records (which hold the request payload)
+// - stored job copies, verification reports, canary shadow results, debug artifacts
+//   and reviewer annotations
+// - dead letter queue entries (which hold source code and test data)
+// - the index entries themselves
+//
+// Jobs deleted through the API (DELETE /job/:id) are purged the same way by the deletion
+// module once their restore window closes.
+//
+// Queued and retrying jobs are never touched. Redis is the only place artifacts live;
+// the one exception is DLQ retention (dlq_retention.rs), which the janitor also runs and
+// which can archive the entries it trims to an object store first.
+
+use chrono::{DateTime, Utc};
+use optimus_common::redis;
+use optimus_common::types::{ExecutionResult, JobRequest, Language};
+use ::redis::aio::ConnectionManager;
+use ::redis::AsyncCommands;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::dlq_retention::{self, DlqPolicy};
+
+/// Tenant of submissions made without an API key
+pub const DEFAULT_TENANT: &str = "default";
+
+const MAX_TENANT_LEN: usize = 64;
+const BATCH_SIZE: isize = 100;
+
+/// What happens to expired submissions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RetentionMode {
+    /// Remove every key derived from the submission
+    Delete,
+    /// Keep verdicts and scores, drop source code, test data and program output
+    Anonymize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RetentionPolicy {
+    pub mode: RetentionMode,
+    pub default_max_age_secs: u64,
+    pub tenant_max_age_secs: HashMap<String, u64>,
+    pub interval_secs: u64,
+}
+
+impl RetentionPolicy {
+    /// Load from RETENTION_* env vars
+    /// Returns Ok(None) when RETENTION_MAX_AGE_SECS is unset (retention disabled)
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let default_max_age_secs = match std::env::var("RETENTION_MAX_AGE_SECS") {
+            Ok(v) if !v.trim().is_empty() => parse_secs("RETENTION_MAX_AGE_SECS", &v)?,
+            _ => return Ok(None),
+        };
+
+        let tenant_max_age_secs = match std::env::var("RETENTION_TENANT_MAX_AGE_SECS") {
+            Ok(v) => parse_tenant_ages(&v)?,
+            Err(_) => HashMap::new(),
+        };
+
+        let mode = match std::env::var("RETENTION_MODE").as_deref() {
+            Ok("delete") | Err(_) => RetentionMode::Delete,
+            Ok("anonymize") => RetentionMode::Anonymize,
+            Ok(other) => {
+                return Err(format!(
+                    "RETENTION_MODE must be 'delete' or 'anonymize', got '{}'",
+                    other
+                ))
+            }
+        };
+
+        let interval_secs = match std::env::var("RETENTION_INTERVAL_SECS") {
+            Ok(v) => parse_secs("RETENTION_INTERVAL_SECS", &v)?,
+            Err(_) => 3600,
+        };
+
+        Ok(Some(Self {
+            mode,
+            default_max_age_secs,
+            tenant_max_age_secs,
+            interval_secs,
+        }))
+    }
+
+    /// Max age for a tenant, falling back to the default
+    pub fn max_age_secs(&self, tenant: &str) -> u64 {
+        self.tenant_max_age_secs
+            .get(tenant)
+            .copied()
+            .unwrap_or(self.default_max_age_secs)
+    }
+}
+
+pub fn parse_secs(name: &str, value: &str) -> Result<u64, String> {
+    match value.trim().parse::<u64>() {
+        Ok(secs) if secs > 0 => Ok(secs),
+        _ => Err(format!("{} must be a positive number of seconds, got '{}'", name, value)),
+    }
+}
+
+/// Parse `tenant:secs,tenant:secs`
+fn parse_tenant_ages(spec: &str) -> Result<HashMap<String, u64>, String> {
+    let mut ages = HashMap::new();
+    for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (tenant, secs) = entry
+            .split_once(':')
+            .ok_or_else(|| format!("Tenant retention entry '{}' must be 'tenant:seconds'", entry))?;
+        validate_tenant(tenant.trim())?;
+        ages.insert(tenant.trim().to_string(), parse_secs("RETENTION_TENANT_MAX_AGE_SECS", secs)?);
+    }
+    Ok(ages)
+}
+
+/// Tenant ids become part of Redis keys, so keep them to a safe alphabet
+pub fn validate_tenant(tenant: &str) -> Result<(), String> {
+    if tenant.is_empty() || tenant.len() > MAX_TENANT_LEN {
+        return Err(format!("Tenant id must be 1-{} characters", MAX_TENANT_LEN));
+    }
+    if !tenant.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err("Tenant id may only contain letters, digits, '-' and '_'".to_string());
+    }
+    Ok(())
+}
+
+/// Progress of the current (or last) janitor run
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RetentionStatus {
+    pub running: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trigger: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+    pub tenants_scanned: u64,
+    pub jobs_expired: u64,
+    pub results_deleted: u64,
+    pub results_anonymized: u64,
+    pub dlq_entries_purged: u64,
+    /// Entries trimmed by DLQ retention, and how many of those were archived first
+    pub dlq_entries_evicted: u64,
+    pub dlq_entries_archived: u64,
+    pub errors: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+/// Shared janitor state: policies plus progress of the current/last run
+#[derive(Debug)]
+pub struct Janitor {
+    pub policy: Option<RetentionPolicy>,
+    pub dlq: Option<DlqPolicy>,
+    status: Mutex<RetentionStatus>,
+}
+
+impl Janitor {
+    pub fn new(policy: Option<RetentionPolicy>, dlq: Option<DlqPolicy>) -> Self {
+        Self {
+            policy,
+            dlq,
+            status: Mutex::new(RetentionStatus::default()),
+        }
+    }
+
+    pub fn status(&self) -> RetentionStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    /// Mark a run as started; returns false if one is already in progress
+    pub fn try_begin(&self, trigger: &str) -> bool {
+        let mut status = self.status.lock().unwrap();
+        if status.running {
+            return false;
+        }
+        *status = RetentionStatus {
+            running: true,
+            trigger: Some(trigger.to_string()),
+            started_at: Some(Utc::now()),
+            ..Default::default()
+        };
+        true
+    }
+
+    fn update(&self, f: impl FnOnce(&mut RetentionStatus)) {
+        f(&mut self.status.lock().unwrap());
+    }
+
+    fn record_error(&self, message: String) {
+        warn!("Retention: {}", message);
+        self.update(|s| {
+            s.errors += 1;
+            s.last_error = Some(message);
+        });
+    }
+
+    /// Run one janitor pass; caller must have called `try_begin`
+    pub async fn run(&self, mut conn: impl redis::Connection) {
+        if let Some(policy) = self.policy.clone() {
+            let expired = self.expire_indexed_jobs(&mut conn, &policy).await;
+            if !expired.is_empty() {
+                self.purge_dlq_entries(&mut conn, &policy, &expired).await;
+            }
+        }
+        if let Some(dlq) = &self.dlq {
+            let trimmed = dlq_retention::trim_all(&mut conn, dlq, |message| self.record_error(message)).await;
+            self.update(|s| {
+                s.dlq_entries_evicted += trimmed.evicted;
+                s.dlq_entries_archived += trimmed.archived;
+            });
+        }
+
+        let status = {
+            let mut status = self.status.lock().unwrap();
+            status.running = false;
+            status.finished_at = Some(Utc::now());
+            status.clone()
+        };
+        info!(
+            jobs_expired = status.jobs_expired,
+            results_deleted = status.results_deleted,
+            results_anonymized = status.results_anonymized,
+            dlq_entries_purged = status.dlq_entries_purged,
+            dlq_entries_evicted = status.dlq_entries_evicted,
+            errors = status.errors,
+            "Retention run finished"
+        );
+    }
+
+    /// Walk every tenant index and purge jobs past their max age
+    async fn expire_indexed_jobs(
+        &self,
+        conn: &mut impl redis::Connection,
+        policy: &RetentionPolicy,
+    ) -> HashSet<uuid::Uuid> {
+        let mut expired = HashSet::new();
+
+        let tenants: Vec<String> = match conn.smembers(redis::tenant_index_key()).await {
+            Ok(t) => t,
+            Err(e) => {
+                self.record_error(format!("failed to list tenants: {}", e));
+                return expired;
+            }
+        };
+
+        let now = Utc::now().timestamp();
+        for tenant in tenants {
+            let cutoff = now - policy.max_age_secs(&tenant) as i64;
+            let index = redis::submission_index_key(&tenant);
+
+            loop {
+                // Always read from the front: processed entries are removed from the index
+                let batch: Vec<String> = match ::redis::cmd("ZRANGEBYSCORE")
+                    .arg(&index)
+                    .arg("-inf")
+                    .arg(cutoff)
+                    .arg("LIMIT")
+                    .arg(0)
+                    .arg(BATCH_SIZE)
+                    .query_async(conn)
+                    .await
+                {
+                    Ok(b) => b,
+                    Err(e) => {
+                        self.record_error(format!("failed to read index for tenant '{}': {}", tenant, e));
+                        break;
+                    }
+                };
+                if batch.is_empty() {
+                    break;
+                }
+
+                for member in batch {
+                    if let Ok(job_id) = uuid::Uuid::parse_str(&member) {
+                        self.expire_job(conn, policy.mode, &job_id).await;
+                        expired.insert(job_id);
+                    }
+                    // Always drop the index entry so a failing job cannot stall the loop
+                    let _: Result<(), _> = conn.zrem(&index, &member).await;
+                    let _: Result<(), _> = conn.hdel(redis::idempotency_index_key(), &member).await;
+                }
+            }
+
+            self.update(|s| s.tenants_scanned += 1);
+        }
+
+        expired
+    }
+
+    async fn expire_job(&self, conn: &mut impl redis::Connection, mode: RetentionMode, job_id: &uuid::Uuid) {
+        match mode {
+            RetentionMode::Delete => {
+                match delete_job_data(conn, job_id).await {
+                    Ok(result_deleted) => self.update(|s| {
+                        s.jobs_expired += 1;
+                        if result_deleted {
+                            s.results_deleted += 1;
+                        }
+                    }),
+                    Err(e) => self.record_error(format!("failed to delete job {}: {}", job_id, e)),
+                }
+            }
+            RetentionMode::Anonymize => {
+                let keys = match payload_keys(conn, job_id).await {
+                    Ok(keys) => keys,
+                    Err(e) => {
+                        self.record_error(format!("failed to read idempotency index for job {}: {}", job_id, e));
+                        return;
+                    }
+                };
+                if let Err(e) = conn.del::<_, ()>(&keys).await {
+                    self.record_error(format!("failed to delete keys for job {}: {}", job_id, e));
+                    return;
+                }
+                match redis::get_result(conn, job_id).await {
+                    Ok(Some(mut result)) => {
+                        scrub_result(&mut result);
+                        if let Err(e) = redis::replace_result(conn, &result).await {
+                            self.record_error(format!("failed to anonymize result {}: {}", job_id, e));
+                            return;
+                        }
+                        self.update(|s| s.results_anonymized += 1);
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        self.record_error(format!("failed to read result {}: {}", job_id, e));
+                        return;
+                    }
+                }
+                self.update(|s| s.jobs_expired += 1);
+            }
+        }
+    }
+
+    /// DLQ entries never expire on their own, so remove/scrub those of expired jobs
+    async fn purge_dlq_entries(
+        &self,
+        conn: &mut impl redis::Connection,
+        policy: &RetentionPolicy,
+        expired: &HashSet<uuid::Uuid>,
+    ) {
+        let purged = purge_dlq_entries(conn, policy.mode, expired, |message| self.record_error(message)).await;
+        self.update(|s| s.dlq_entries_purged += purged);
+    }
+}
+
+/// Keys of a job that hold its payload or anything derived from it, except the result
+async fn payload_keys(conn: &mut impl redis::Connection, job_id: &uuid::Uuid) -> ::redis::RedisResult<Vec<String>> {
+    // Idempotency records hold the request payload
+    let idempotency: Option<String> = conn.hget(redis::idempotency_index_key(), job_id.to_string()).await?;
+    // So do the stored job copy, verification reports and canary shadow results;
+    // debug artifacts and reviewer annotations go with the job too
+    let mut keys = vec![
+        redis::control_key(job_id),
+        redis::job_key(job_id),
+        redis::verification_key(job_id),
+        redis::shadow_result_key(job_id),
+        redis::debug_key(job_id),
+        redis::annotations_key(job_id),
+    ];
+    keys.extend(idempotency);
+    Ok(keys)
+}
+
+/// Delete every key derived from a job (DLQ entries aside); true if it had a result
+pub async fn delete_job_data(conn: &mut impl redis::Connection, job_id: &uuid::Uuid) -> ::redis::RedisResult<bool> {
+    let mut keys = payload_keys(conn, job_id).await?;
+    keys.push(redis::result_key(job_id));
+    keys.push(redis::status_key(job_id));
+    keys.push(redis::progress_key(job_id));
+    keys.push(redis::overrides_key(job_id));
+    keys.push(redis::job_location_key(job_id));
+    let result_existed: bool = conn.exists(redis::result_key(job_id)).await?;
+    conn.del::<_, ()>(&keys).await?;
+    conn.hdel::<_, _, ()>(redis::idempotency_index_key(), job_id.to_string()).await?;
+    Ok(result_existed)
+}
+
+/// Remove (or scrub, when anonymizing) the DLQ entries of `jobs` in every language
+///
+/// Returns how many entries were purged; failures go to `on_error` and don't stop the sweep.
+pub async fn purge_dlq_entries(
+    conn: &mut impl redis::Connection,
+    mode: RetentionMode,
+    jobs: &HashSet<uuid::Uuid>,
+    on_error: impl Fn(String),
+) -> u64 {
+    let mut purged = 0;
+    for language in Language::all_variants() {
+        let dlq = redis::dlq_name(language);
+        let entries: Vec<String> = match conn.lrange(&dlq, 0, -1).await {
+            Ok(e) => e,
+            Err(e) => {
+                on_error(format!("failed to read {}: {}", dlq, e));
+                continue;
+            }
+        };
+
+        for (index, entry) in entries.iter().enumerate() {
+            let mut job = match redis::decode_job(entry) {
+                Ok(job) if jobs.contains(&job.id) => job,
+                Ok(_) => continue,
+                Err(e) => {
+                    on_error(format!("unreadable entry in {}: {}", dlq, e));
+                    continue;
+                }
+            };
+
+            let outcome = match mode {
+                RetentionMode::Delete => conn.lrem::<_, _, ()>(&dlq, 1, entry).await,
+                RetentionMode::Anonymize => {
+                    scrub_job(&mut job);
+                    match redis::encode_job(&job) {
+                        // Anonymizing never removes entries, so indexes stay valid
+                        Ok(payload) => conn.lset::<_, _, ()>(&dlq, index as isize, payload).await,
+                        Err(e) => {
+                            on_error(format!("failed to encode scrubbed job {}: {}", job.id, e));
+                            continue;
+                        }
+                    }
+                }
+            };
+
+            match outcome {
+                Ok(()) => purged += 1,
+                Err(e) => on_error(format!("failed to purge job {} from {}: {}", job.id, dlq, e)),
+            }
+        }
+    }
+    purged
+}
+
+/// Drop program output, keeping verdicts, timings and scores
+pub fn scrub_result(result: &mut ExecutionResult) {
+    for test in &mut result.results {
+        test.stdout.clear();
+        test.stderr.clear();
+        test.stdout_base64 = None;
+        test.stderr_base64 = None;
+        test.diff = None;
+    }
+}
+
+/// Drop source code and test data, keeping ids, weights and metadata
+pub fn scrub_job(job: &mut JobRequest) {
+    job.source_code.clear();
+    for test in &mut job.test_cases {
+        test.input.clear();
+        test.expected_output.clear();
+    }
+}
+
+/// Background task running the janitor on the policy interval
+pub async fn janitor_loop(janitor: std::sync::Arc<Janitor>, conn: ConnectionManager) {
+    let interval_secs = janitor.policy.as_ref().map(|p| p.interval_secs);
+    let Some(interval_secs) = interval_secs.or(janitor.dlq.as_ref().map(|d| d.interval_secs)) else {
+        return;
+    };
+
+    let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+    loop {
+        interval.tick().await;
+        if janitor.try_begin("schedule") {
+            janitor.run(conn.clone()).await;
+        } else {
+            error!("Retention run skipped: previous run still in progress");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use optimus_common::types::{JobStatus, TestResult, TestStatus};
+
+    #[test]
+    fn test_tenant_age_parsing() {
+        let ages = parse_tenant_ages("acme:3600, uni_2:86400").unwrap();
+        assert_eq!(ages.get("acme"), Some(&3600));
+        assert_eq!(ages.get("uni_2"), Some(&86400));
+
+        assert!(parse_tenant_ages("acme").is_err());
+        assert!(parse_tenant_ages("acme:0").is_err());
+        assert!(parse_tenant_ages("ac:me:10").is_err());
+        assert!(parse_tenant_ages("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_max_age_falls_back_to_default() {
+        let policy = RetentionPolicy {
+            mode: RetentionMode::Delete,
+            default_max_age_secs: 100,
+            tenant_max_age_secs: HashMap::from([("acme".to_string(), 10)]),
+            interval_secs: 60,
+        };
+        assert_eq!(policy.max_age_secs("acme"), 10);
+        assert_eq!(policy.max_age_secs("other"), 100);
+    }
+
+    #[test]
+    fn test_validate_tenant() {
+        assert!(validate_tenant("acme-01_x").is_ok());
+        assert!(validate_tenant("").is_err());
+        assert!(validate_tenant("a:b").is_err());
+        assert!(validate_tenant(&"x".repeat(MAX_TENANT_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn test_scrub_result_keeps_verdicts() {
+        let mut result = ExecutionResult {
+            job_id: uuid::Uuid::new_v4(),
+            overall_status: JobStatus::Completed,
+            score: 10,
+            max_score: 10,
+            results: vec![TestResult {
+                test_id: 1,
+                status: TestStatus::Passed,
+                stdout: "student output".to_string(),
+                stderr: "warning".to_string(),
+                execution_time_ms: 5,
+                stdout_base64: Some("AAA=".to_string()),
+                stderr_base64: None,
+                overhead_ms: None,
+                cpu_time_ms: None,
+                wall_time_ms: None,
+                memory_kb: None,
+                failure: None,
+                checker_output: None,
+                sanitizer_report: None,
+                diff: None,
+            }],
+            judge_env_version: None,
+            worker_id: None,
+            signature: None,
+            points: None,
+            timings: None,
+            misuse: Vec::new(),
+            effective_timeout_ms: None,
+            subtasks: Vec::new(),
+        };
+        scrub_result(&mut result);
+
+        assert_eq!(result.score, 10);
+        assert_eq!(result.results[0].status, TestStatus::Passed);
+        assert!(result.results[0].stdout.is_empty());
+        assert!(result.results[0].stderr.is_empty());
+        assert!(result.results[0].stdout_base64.is_none());
+    }
+
+    #[test]
+    fn test_try_begin_rejects_concurrent_runs() {
+        let janitor = Janitor::new(None, None);
+        assert!(janitor.try_begin("manual"));
+        assert!(!janitor.try_begin("schedule"));
+        assert!(janitor.status().running);
+    }
+}
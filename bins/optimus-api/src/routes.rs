@@ -1,20 +1,57 @@
 // Route definitions for the Optimus API
 
 use axum::{
-    routing::{get, post},
+    middleware,
+    routing::{delete, get, post},
     Router,
 };
 use std::sync::Arc;
 
-use crate::{handlers, AppState};
+use crate::{auth, handlers, AppState};
 
-pub fn routes() -> Router<Arc<AppState>> {
+pub fn routes(state: Arc<AppState>) -> Router<Arc<AppState>> {
     Router::new()
         .route("/execute", post(handlers::submit_job))
         .route("/health", get(handlers::health_check))
         .route("/ready", get(handlers::readiness_check))
         .route("/metrics", get(handlers::metrics_handler))
-        .route("/job/:job_id", get(handlers::get_job_result))
+        .route("/limits", get(handlers::get_limits))
+        .route("/job/:job_id", get(handlers::get_job_result).delete(handlers::delete_job))
         .route("/job/:job_id/debug", get(handlers::get_job_debug))
+        .route("/job/:job_id/progress", get(handlers::get_job_progress))
+        .route("/job/:job_id/stream", get(handlers::stream_job_progress))
+        .route("/job/:job_id/events", get(handlers::job_events))
         .route("/job/:job_id/cancel", post(handlers::cancel_job))
+        .route("/job/:job_id/annotations", post(handlers::add_annotation))
+        .route("/job/:job_id/override", post(handlers::override_score).get(handlers::get_score_overrides))
+        .route("/jobs/compare", get(handlers::compare_jobs))
+        .route("/problems/:problem_id/leaderboard", get(handlers::get_problem_leaderboard))
+        .route("/problems/:problem_id/stats", get(handlers::get_problem_stats))
+        .route("/contests/:contest_id/leaderboard", get(handlers::get_contest_leaderboard))
+        .route("/languages/:name/runtime-info", get(handlers::get_runtime_info))
+        .route("/languages/:name/environments", get(handlers::get_judge_environments))
+        .merge(admin_routes().route_layer(middleware::from_fn_with_state(state, auth::require_admin)))
+}
+
+/// Operator endpoints: every one needs an admin API key (see auth.rs)
+fn admin_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/admin/retention", get(handlers::get_retention_status))
+        .route("/admin/retention/run", post(handlers::run_retention))
+        .route("/admin/queues", get(handlers::get_queue_depths))
+        .route("/admin/queues/:language/pause", post(handlers::pause_queue))
+        .route("/admin/queues/:language/resume", post(handlers::resume_queue))
+        .route("/admin/jobs/recent", get(handlers::get_recent_jobs))
+        .route("/admin/jobs/:job_id/verify", post(handlers::verify_job).get(handlers::get_verification))
+        .route("/admin/jobs/:job_id/restore", post(handlers::restore_job))
+        .route("/admin/jobs/:job_id/debug", get(handlers::get_debug_artifacts))
+        .route("/admin/idempotency", get(handlers::get_idempotency_keys))
+        .route("/admin/idempotency/:key", delete(handlers::delete_idempotency_key))
+        .route("/admin/workers", get(handlers::get_workers))
+        .route("/admin/workers/:worker_id/drain", post(handlers::drain_worker).delete(handlers::cancel_worker_drain))
+        .route("/admin/dlq", get(handlers::get_dlq_entries))
+        .route("/admin/problems/:problem_id/test-sets", get(handlers::get_test_sets).post(handlers::register_test_set))
+        .route("/admin/problems/:problem_id/test-sets/:fingerprint", delete(handlers::unregister_test_set))
+        .route("/admin/canary/:language", get(handlers::get_canary_report))
+        .route("/admin/ui", get(handlers::admin_ui))
 }
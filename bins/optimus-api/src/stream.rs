@@ -0,0 +1,297 @@
+// Live job progress - pushed to clients as the job's progress events, then closed once
+// the job is done:
+//
+//   {"event": "queued"}
+//   {"event": "running"}
+//   {"event": "compiling"}
+//   {"event": "output", "test_id": 3, "stream": "stdout", "data": "42\n"}
+//   {"event": "test", "test_id": 3, "status": "passed", "execution_time_ms": 41, "total_tests": 20}
+//   {"event": "done", "status": "passed"}
+//
+// GET /job/{id}/stream sends them as WebSocket text frames, GET /job/{id}/events as
+// Server-Sent Events named after the event (for browsers without WebSocket infra). The
+// WebSocket answers pings and close frames from the client and ignores anything else it
+// sends; client messages are capped at MAX_CLIENT_MESSAGE_BYTES, since none carry data.
+//
+// Workers publish events on the progress channel; the API's subscriber hands them to
+// `ProgressHub`, which fans them out to open streams. A stream starts by replaying what
+// already happened (status key and progress list), so a client connecting late misses
+// nothing; tests both replayed and published are sent once. The status is re-read every
+// RECHECK_INTERVAL, so a missed `done` only delays the close. `output` events (workers with
+// LIVE_OUTPUT_KB set) are never stored and can't be replayed: a late client misses them.
+
+use crate::longpoll::RECHECK_INTERVAL;
+use crate::redis_pool::PooledConnection;
+use axum::extract::ws::{close_code, CloseFrame, Message, WebSocket, WebSocketUpgrade};
+use axum::response::sse;
+use futures_util::{SinkExt, Stream, StreamExt};
+use optimus_common::redis;
+use optimus_common::types::{JobProgressEvent, JobStatus, ProgressEvent, TestProgress};
+use std::collections::HashSet;
+use std::convert::Infallible;
+use std::mem::{discriminant, Discriminant};
+use tokio::sync::{broadcast, mpsc};
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+/// Buffered events; a stream that falls further behind replays the job's state instead
+const CHANNEL_CAPACITY: usize = 4096;
+
+/// Events queued between the follower and a slow client
+const EVENT_BUFFER: usize = 64;
+
+/// Largest message accepted from a client
+const MAX_CLIENT_MESSAGE_BYTES: usize = 64 * 1024;
+
+/// Fan-out of progress events to open streams
+pub struct ProgressHub {
+    tx: broadcast::Sender<JobProgressEvent>,
+}
+
+impl Default for ProgressHub {
+    fn default() -> Self {
+        Self { tx: broadcast::channel(CHANNEL_CAPACITY).0 }
+    }
+}
+
+impl ProgressHub {
+    pub fn publish(&self, event: JobProgressEvent) {
+        // No receivers just means nobody is streaming
+        let _ = self.tx.send(event);
+    }
+
+    /// Subscribe before replaying the job's state, so an event in between isn't missed
+    pub fn subscribe(&self) -> broadcast::Receiver<JobProgressEvent> {
+        self.tx.subscribe()
+    }
+}
+
+/// What a stream has sent so far
+#[derive(Default)]
+pub struct StreamState {
+    tests: HashSet<u32>,
+    phases: HashSet<Discriminant<ProgressEvent>>,
+    done: bool,
+}
+
+impl StreamState {
+    /// Whether to send an event: each test and each phase once, every output chunk, nothing
+    /// after `done`
+    pub fn admit(&mut self, event: &ProgressEvent) -> bool {
+        if self.done {
+            return false;
+        }
+        match event {
+            ProgressEvent::Test(test) => self.tests.insert(test.test_id),
+            ProgressEvent::Output(_) => true,
+            ProgressEvent::Done { .. } => {
+                self.done = true;
+                true
+            }
+            phase => self.phases.insert(discriminant(phase)),
+        }
+    }
+}
+
+/// Events describing a job's state so far, from its status and the tests it finished
+pub fn replay(status: JobStatus, tests: Vec<TestProgress>) -> Vec<ProgressEvent> {
+    let finished = !matches!(status, JobStatus::Queued | JobStatus::Running);
+    if !finished && tests.is_empty() {
+        return vec![ProgressEvent::Queued];
+    }
+    let mut events = vec![ProgressEvent::Running];
+    events.extend(tests.into_iter().map(ProgressEvent::Test));
+    if finished {
+        events.push(ProgressEvent::Done { status });
+    }
+    events
+}
+
+/// Send the job's events, each once, until it is done or the receiver is dropped
+pub async fn follow(
+    mut conn: PooledConnection,
+    job_id: Uuid,
+    mut events: broadcast::Receiver<JobProgressEvent>,
+    tx: mpsc::Sender<ProgressEvent>,
+) {
+    let mut state = StreamState::default();
+    let mut recheck = tokio::time::interval(RECHECK_INTERVAL);
+    while !state.done {
+        let pending = tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) if event.job_id == job_id => vec![event.event],
+                Ok(_) => continue,
+                Err(broadcast::error::RecvError::Lagged(_)) => sync(&mut conn, job_id, &mut state).await,
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            // The first tick is immediate: replay what already happened
+            _ = recheck.tick() => sync(&mut conn, job_id, &mut state).await,
+            _ = tx.closed() => break,
+        };
+        for event in pending {
+            if state.admit(&event) && tx.send(event).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// The job's state so far (sent events are filtered out by `admit`)
+async fn sync(conn: &mut PooledConnection, job_id: Uuid, state: &mut StreamState) -> Vec<ProgressEvent> {
+    let status = match redis::get_job_status(conn, &job_id).await {
+        Ok(Some(status)) => status,
+        // Expired meanwhile: nothing more will happen
+        Ok(None) => {
+            state.done = true;
+            return Vec::new();
+        }
+        Err(e) => {
+            warn!(job_id = %job_id, error = %e, "Failed to read job status for progress stream");
+            return Vec::new();
+        }
+    };
+    let tests = match redis::get_progress(conn, &job_id).await {
+        Ok(tests) => tests,
+        Err(e) => {
+            warn!(job_id = %job_id, error = %e, "Failed to read job progress for progress stream");
+            Vec::new()
+        }
+    };
+    replay(status, tests)
+}
+
+/// Apply the cap on client messages to an upgrade
+pub fn limit_client_frames(upgrade: WebSocketUpgrade) -> WebSocketUpgrade {
+    upgrade.max_message_size(MAX_CLIENT_MESSAGE_BYTES).max_frame_size(MAX_CLIENT_MESSAGE_BYTES)
+}
+
+/// Serve one upgraded WebSocket connection until the job is done or the client goes away
+pub async fn run(socket: WebSocket, conn: PooledConnection, job_id: Uuid, events: broadcast::Receiver<JobProgressEvent>) {
+    let (mut sender, mut receiver) = socket.split();
+
+    // Client messages are read on their own task: a read cut off by select! would lose data.
+    // Pings are answered by the WebSocket itself.
+    let (closed_tx, mut closed) = mpsc::channel::<()>(1);
+    let read_task = tokio::spawn(async move {
+        while let Some(Ok(message)) = receiver.next().await {
+            if matches!(message, Message::Close(_)) {
+                break;
+            }
+        }
+        drop(closed_tx);
+    });
+    let (tx, mut progress) = mpsc::channel(EVENT_BUFFER);
+    let follower = tokio::spawn(follow(conn, job_id, events, tx));
+
+    let served = async {
+        loop {
+            tokio::select! {
+                event = progress.recv() => match event {
+                    Some(event) => sender.send(message(&event)).await?,
+                    None => break,
+                },
+                _ = closed.recv() => break,
+            }
+        }
+        let close = CloseFrame { code: close_code::NORMAL, reason: "".into() };
+        sender.send(Message::Close(Some(close))).await?;
+        sender.close().await
+    };
+    if let Err(e) = served.await {
+        debug!(job_id = %job_id, error = %e, "Progress stream closed");
+    }
+    follower.abort();
+    read_task.abort();
+}
+
+/// A progress event as a text message
+fn message(event: &ProgressEvent) -> Message {
+    Message::Text(serde_json::to_string(event).unwrap_or_default())
+}
+
+/// The job's events as Server-Sent Events; the stream ends once the job is done
+pub fn sse_events(
+    conn: PooledConnection,
+    job_id: Uuid,
+    events: broadcast::Receiver<JobProgressEvent>,
+) -> impl Stream<Item = Result<sse::Event, Infallible>> {
+    let (tx, progress) = mpsc::channel(EVENT_BUFFER);
+    // Ends by itself once the response (and with it `progress`) is dropped
+    tokio::spawn(follow(conn, job_id, events, tx));
+    futures_util::stream::unfold(progress, |mut progress| async move {
+        let event = progress.recv().await?;
+        Some((Ok(sse_event(&event)), progress))
+    })
+}
+
+/// An SSE event named after the progress event, with its JSON as data
+fn sse_event(event: &ProgressEvent) -> sse::Event {
+    let data = serde_json::to_string(event).unwrap_or_default();
+    let name = match event {
+        ProgressEvent::Queued => "queued",
+        ProgressEvent::Running => "running",
+        ProgressEvent::Compiling => "compiling",
+        ProgressEvent::Output(_) => "output",
+        ProgressEvent::Test(_) => "test",
+        ProgressEvent::Done { .. } => "done",
+    };
+    sse::Event::default().event(name).data(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use optimus_common::types::{OutputChunk, OutputStream, TestStatus};
+
+    fn test(test_id: u32) -> TestProgress {
+        TestProgress { test_id, status: TestStatus::Passed, execution_time_ms: 5, total_tests: 2 }
+    }
+
+    #[test]
+    fn test_replay_then_live_events_sent_once() {
+        assert!(matches!(replay(JobStatus::Queued, vec![])[..], [ProgressEvent::Queued]));
+
+        let mut state = StreamState::default();
+        let replayed = replay(JobStatus::Queued, vec![test(1)]);
+        assert!(replayed.iter().all(|event| state.admit(event)));
+
+        // Published while the replay was read
+        assert!(!state.admit(&ProgressEvent::Test(test(1))));
+        assert!(!state.admit(&ProgressEvent::Running));
+        let chunk = || ProgressEvent::Output(OutputChunk { test_id: 2, stream: OutputStream::Stdout, data: "2\n".to_string() });
+        assert!(state.admit(&chunk()) && state.admit(&chunk()));
+        assert!(state.admit(&ProgressEvent::Test(test(2))));
+
+        let finished = replay(JobStatus::Passed, vec![test(1), test(2)]);
+        let sent: Vec<_> = finished.into_iter().filter(|event| state.admit(event)).collect();
+        assert!(matches!(sent[..], [ProgressEvent::Done { status: JobStatus::Passed }]));
+        assert!(!state.admit(&ProgressEvent::Compiling));
+    }
+
+    #[test]
+    fn test_events_sent_as_text_messages() {
+        let frames: Vec<serde_json::Value> = replay(JobStatus::Failed, vec![test(1)])
+            .iter()
+            .map(|event| match message(event) {
+                Message::Text(text) => serde_json::from_str(&text).unwrap(),
+                other => panic!("expected a text message, got {:?}", other),
+            })
+            .collect();
+        let kinds: Vec<_> = frames.iter().map(|f| f["event"].as_str().unwrap().to_string()).collect();
+        assert_eq!(kinds, ["running", "test", "done"]);
+        assert_eq!(frames[2]["status"], "failed");
+    }
+
+    #[tokio::test]
+    async fn test_sse_events_named_after_event() {
+        use axum::response::IntoResponse;
+
+        let events = replay(JobStatus::Passed, vec![test(1)]).into_iter().map(|event| Ok::<_, Infallible>(sse_event(&event)));
+        let response = sse::Sse::new(futures_util::stream::iter(events)).into_response();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.starts_with("event: running\ndata: {\"event\":\"running\"}\n\n"));
+        assert!(body.contains("event: test\ndata: {\"event\":\"test\",\"test_id\":1,"));
+        assert!(body.ends_with("event: done\ndata: {\"event\":\"done\",\"status\":\"passed\"}\n\n"));
+    }
+}
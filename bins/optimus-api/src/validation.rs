@@ -0,0 +1,343 @@
+// Submission validation (POST /execute)
+//
+// Checks a submission against the safety limits before it is queued (the checks shared
+// with workers and the client SDK, optimus_common::validate), and gives request
+// bodies that don't even parse (malformed JSON, wrong types, negative or oversized
+// numbers, bodies over the size limit) the same structured 4xx error as every other
+// rejection instead of axum's plain-text one.
+
+use axum::extract::rejection::JsonRejection;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Json, Response};
+use optimus_common::limits;
+use optimus_common::validate::{self, Payload, TestCasePayload, Violation, Violations};
+
+use crate::handlers::{ErrorDetail, ErrorResponse, SubmitRequest};
+
+/// Why a submission was refused
+#[derive(Debug)]
+pub struct Rejection {
+    pub status: StatusCode,
+    pub code: &'static str,
+    pub message: String,
+    /// Everything wrong with the submission, when it failed the limit checks
+    pub violations: Vec<Violation>,
+}
+
+impl Rejection {
+    fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        Self { status, code, message: message.into(), violations: Vec::new() }
+    }
+
+    /// Label of the jobs_rejected metric
+    pub fn reason(&self) -> String {
+        self.code.to_ascii_lowercase()
+    }
+}
+
+impl From<Violations> for Rejection {
+    fn from(violations: Violations) -> Self {
+        let status = if violations.too_large() { StatusCode::PAYLOAD_TOO_LARGE } else { StatusCode::BAD_REQUEST };
+        let first = violations.first();
+        Self { violations: violations.as_slice().to_vec(), ..Self::new(status, first.code, first.message.clone()) }
+    }
+}
+
+impl IntoResponse for Rejection {
+    fn into_response(self) -> Response {
+        if !self.violations.is_empty() {
+            let body = serde_json::json!({
+                "error": { "code": self.code, "message": self.message, "violations": self.violations }
+            });
+            return (self.status, Json(body)).into_response();
+        }
+        let body = ErrorResponse {
+            error: ErrorDetail { code: self.code.to_string(), message: self.message },
+        };
+        (self.status, Json(body)).into_response()
+    }
+}
+
+/// Structured error for a body the JSON extractor refused
+pub fn json_rejection(rejection: JsonRejection) -> Rejection {
+    let code = match &rejection {
+        JsonRejection::JsonDataError(_) => "INVALID_FIELD",
+        JsonRejection::JsonSyntaxError(_) => "MALFORMED_JSON",
+        JsonRejection::MissingJsonContentType(_) => "UNSUPPORTED_MEDIA_TYPE",
+        _ if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE => "PAYLOAD_TOO_LARGE",
+        _ => "INVALID_BODY",
+    };
+    Rejection::new(rejection.status(), code, rejection.body_text())
+}
+
+/// Check a parsed submission against the safety limits in effect
+pub fn validate_submission(payload: &SubmitRequest) -> Result<(), Rejection> {
+    let test_cases = payload
+        .test_cases
+        .iter()
+        .map(|tc| TestCasePayload {
+            id: None,
+            input: &tc.input,
+            expected_output: &tc.expected_output,
+            weight: tc.weight,
+        })
+        .collect();
+    let submission = Payload {
+        priority: payload.priority,
+        output_mode: payload.output_mode,
+        evaluation: payload.evaluation.as_ref(),
+        status_policy: payload.status_policy.as_ref(),
+        scoring: payload.scoring.as_ref(),
+        leaderboard: payload.leaderboard.as_ref(),
+        checker: payload.checker.as_deref(),
+        interactor: payload.interactor.as_deref(),
+        sanitizers: &payload.sanitizers,
+        source_format: payload.source_format,
+        ..Payload::new(payload.language, &payload.source_code, test_cases, payload.timeout_ms)
+    };
+    validate::validate_submission(&submission, &limits::report()).map_err(Rejection::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::extract::FromRequest;
+    use axum::http::Request;
+    use optimus_common::limits::Limits;
+    use optimus_common::types::MAX_TEST_WEIGHT;
+
+    /// Deterministic xorshift generator, so a failing case reproduces from its seed
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: usize) -> usize {
+            (self.next() % n as u64) as usize
+        }
+
+        fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+            items[self.below(items.len())]
+        }
+
+        fn chance(&mut self, percent: usize) -> bool {
+            self.below(100) < percent
+        }
+    }
+
+    /// Hostile numbers: negative, past u32/u64, non-finite spellings, fractions, wrong types
+    const NUMBERS: &[&str] = &[
+        "0", "1", "-1", "5000", "60001", "4294967295", "4294967296", "18446744073709551615",
+        "18446744073709551616", "-9223372036854775809", "1e400", "-1e400", "NaN", "Infinity", "1.5",
+        "-0", "1e2", "null", "\"5000\"", "true", "[]", "{}",
+    ];
+
+    /// JSON string literal, sometimes right at `limit` bytes
+    fn string(rng: &mut Rng, limit: usize) -> String {
+        let unit = rng.pick(&[
+            "a", "print(1)", "é", "\u{1F600}", "\u{202E}", "a\u{0301}\u{0302}\u{0303}", "\u{FEFF}", "\\u0000",
+            "\\ud800", "\\\"", "\\n", " ", "\u{10FFFF}",
+        ]);
+        let repeat = match rng.below(4) {
+            0 => 0,
+            1 => rng.below(16),
+            2 => rng.below(1_000),
+            _ => (limit / unit.len()).saturating_sub(1) + rng.below(3),
+        };
+        format!("\"{}\"", unit.repeat(repeat))
+    }
+
+    fn nested(rng: &mut Rng) -> String {
+        let depth = 1 + rng.below(300);
+        format!("{}1{}", "[".repeat(depth), "]".repeat(depth))
+    }
+
+    fn test_case(rng: &mut Rng) -> String {
+        let mut fields = vec![
+            format!("\"input\":{}", string(rng, Limits::default().max_stdin_bytes)),
+            format!("\"expected_output\":{}", string(rng, Limits::default().max_expected_output_bytes)),
+        ];
+        if rng.chance(70) {
+            fields.push(format!("\"weight\":{}", rng.pick(NUMBERS)));
+        }
+        if rng.chance(20) {
+            fields.push(format!("\"comparison\":{}", rng.pick(&["\"ordered\"", "\"strict\"", "\"fuzzy\"", "7"])));
+        }
+        format!("{{{}}}", fields.join(","))
+    }
+
+    fn submission(rng: &mut Rng) -> Vec<u8> {
+        let mut fields = Vec::new();
+        if rng.chance(95) {
+            fields.push(format!("\"language\":{}", rng.pick(&["\"python\"", "\"java\"", "\"rust\"", "\"PYTHON\"", "\"cobol\"", "1"])));
+        }
+        if rng.chance(95) {
+            fields.push(format!("\"source_code\":{}", string(rng, Limits::default().max_source_code_bytes)));
+        }
+        if rng.chance(95) {
+            let count = match rng.below(3) {
+                0 => rng.below(3),
+                1 => rng.below(20),
+                _ => Limits::default().max_test_cases - 2 + rng.below(5),
+            };
+            let case = test_case(rng);
+            // Mostly copies of one case, so large counts stay cheap to generate
+            let cases: Vec<String> = (0..count).map(|i| if i == 0 || rng.chance(90) { case.clone() } else { test_case(rng) }).collect();
+            fields.push(format!("\"test_cases\":[{}]", cases.join(",")));
+        }
+        if rng.chance(70) {
+            fields.push(format!("\"timeout_ms\":{}", rng.pick(NUMBERS)));
+        }
+        if rng.chance(20) {
+            fields.push(format!("\"output_mode\":{}", rng.pick(&["\"text\"", "\"binary\"", "\"hex\""])));
+        }
+        if rng.chance(20) {
+            let pipeline = match rng.below(4) {
+                0 => format!("{{\"normalizers\":[{{\"type\":\"float_round\",\"decimals\":{}}}]}}", rng.pick(NUMBERS)),
+                1 => format!(
+                    "{{\"comparator\":{{\"type\":\"numeric\",\"abs_tolerance\":{},\"rel_tolerance\":{}}}}}",
+                    rng.pick(NUMBERS),
+                    rng.pick(NUMBERS)
+                ),
+                2 => nested(rng),
+                _ => "{\"comparator\":{\"type\":\"unknown\"}}".to_string(),
+            };
+            fields.push(format!("\"evaluation\":{}", pipeline));
+        }
+        if rng.chance(20) {
+            let policy = match rng.below(3) {
+                0 => format!("{{\"threshold\":{}}}", rng.pick(NUMBERS)),
+                1 => "\"all_pass\"".to_string(),
+                _ => nested(rng),
+            };
+            fields.push(format!("\"status_policy\":{}", policy));
+        }
+        if rng.chance(20) {
+            fields.push(format!(
+                "\"scoring\":{{\"max_points\":{},\"decimals\":{}}}",
+                rng.pick(NUMBERS),
+                rng.pick(NUMBERS)
+            ));
+        }
+        let mut body = format!("{{{}}}", fields.join(",")).into_bytes();
+        // Corrupt some bodies outright: truncated (also mid-character), trailing garbage, invalid UTF-8
+        match rng.below(20) {
+            0 => body.truncate(rng.below(body.len() + 1)),
+            1 => body.extend_from_slice(b"}}"),
+            2 => body = nested(rng).into_bytes(),
+            3 => {
+                let at = rng.below(body.len());
+                body[at] = 0xFF;
+            }
+            _ => {}
+        }
+        body
+    }
+
+    fn request(body: impl Into<Body>) -> Request<Body> {
+        Request::builder().method("POST").uri("/execute").header("content-type", "application/json").body(body.into()).unwrap()
+    }
+
+    /// What the /execute handler answers before touching Redis
+    async fn respond(request: Request<Body>) -> Result<SubmitRequest, Rejection> {
+        let Json(payload) = Json::<SubmitRequest>::from_request(request, &()).await.map_err(json_rejection)?;
+        validate_submission(&payload)?;
+        Ok(payload)
+    }
+
+    async fn assert_structured_4xx(rejection: Rejection, body: &[u8]) {
+        let body = String::from_utf8_lossy(&body[..body.len().min(200)]);
+        assert!(rejection.status.is_client_error(), "{} for {}", rejection.status, body);
+        let response = rejection.into_response();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let error: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert!(error["error"]["code"].as_str().is_some_and(|code| !code.is_empty()), "{}", error);
+        assert!(error["error"]["message"].is_string(), "{}", error);
+    }
+
+    /// Random hostile submissions never panic: each is either accepted within the limits
+    /// or refused with a structured 4xx error
+    #[tokio::test]
+    async fn test_fuzz_submissions_are_accepted_or_refused_cleanly() {
+        let limits = Limits::default();
+        let mut accepted = 0;
+        for seed in 1..=1_000u64 {
+            let mut rng = Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+            let body = submission(&mut rng);
+            match respond(request(body.clone())).await {
+                Ok(payload) => {
+                    accepted += 1;
+                    assert!((1..=limits.max_test_cases).contains(&payload.test_cases.len()), "seed {}", seed);
+                    assert!((limits.min_timeout_ms..=limits.max_timeout_ms).contains(&payload.timeout_ms), "seed {}", seed);
+                    assert!(payload.source_code.len() <= limits.max_source_code_bytes, "seed {}", seed);
+                    assert!(payload.test_cases.iter().all(|tc| tc.weight <= MAX_TEST_WEIGHT), "seed {}", seed);
+                }
+                Err(rejection) => assert_structured_4xx(rejection, &body).await,
+            }
+        }
+        // The generator must still reach the accepting paths
+        assert!(accepted > 0);
+    }
+
+    #[tokio::test]
+    async fn test_fuzz_random_bytes_are_refused_cleanly() {
+        for seed in 1..=500u64 {
+            let mut rng = Rng(seed.wrapping_mul(0xD1B5_4A32_D192_ED03));
+            let bytes: Vec<u8> = (0..rng.below(512)).map(|_| rng.next() as u8).collect();
+            let rejection = respond(request(bytes)).await.expect_err("random bytes are never a submission");
+            assert_structured_4xx(rejection, &[]).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unparseable_bodies_get_error_codes() {
+        let valid_case = r#"{"input":"","expected_output":"1"}"#;
+        let cases = [
+            (format!(r#"{{"language":"python","source_code":"x","test_cases":[{}]"#, valid_case), 400, "MALFORMED_JSON"),
+            (r#"{"language":"python","source_code":"x","test_cases":[{"input":"","expected_output":"","weight":-1}]}"#.to_string(), 422, "INVALID_FIELD"),
+            (format!(r#"{{"language":"python","source_code":"x","test_cases":[{}],"timeout_ms":1e400}}"#, valid_case), 400, "MALFORMED_JSON"),
+            (format!(r#"{{"language":"python","source_code":"x","test_cases":[{}],"timeout_ms":NaN}}"#, valid_case), 400, "MALFORMED_JSON"),
+            (format!(r#"{{"language":"python","source_code":"x","test_cases":[{}],"timeout_ms":0}}"#, valid_case), 400, "INVALID_TIMEOUT"),
+            (r#"{"language":"python","source_code":"x","test_cases":[{"input":"","expected_output":"","weight":4294967295}]}"#.to_string(), 400, "INVALID_WEIGHT"),
+            (format!(r#"{{"language":"python","source_code":"x","test_cases":[{}],"leaderboard":{{"problem_id":"a:b","participant":"u"}}}}"#, valid_case), 400, "INVALID_LEADERBOARD"),
+            (format!(r#"{{"language":"python","source_code":"x","test_cases":[{}],"sanitizers":["address"]}}"#, valid_case), 400, "SANITIZERS_UNSUPPORTED"),
+            (format!(r#"{{"language":"c","source_code":"x","test_cases":[{}],"sanitizers":["thread"]}}"#, valid_case), 422, "INVALID_FIELD"),
+            (format!(r#"{{"language":"java","source_code":"x","test_cases":[{}],"source_format":"notebook"}}"#, valid_case), 400, "NOTEBOOK_UNSUPPORTED"),
+            (format!(r#"{{"language":"python","source_code":"print(1)","test_cases":[{}],"source_format":"notebook"}}"#, valid_case), 400, "INVALID_NOTEBOOK"),
+            (format!(r#"{{"language":"python","source_code":"{{\"cells\":[{{\"cell_type\":\"markdown\",\"source\":\"hi\"}}]}}","test_cases":[{}],"source_format":"notebook"}}"#, valid_case), 400, "INVALID_NOTEBOOK"),
+            (format!(r#"{{"language":"python","source_code":"x","test_cases":[{}],"interactor":" "}}"#, valid_case), 400, "INVALID_INTERACTOR"),
+            (format!(r#"{{"language":"python","source_code":"x","test_cases":[{}],"interactor":"y","checker":"z"}}"#, valid_case), 400, "INVALID_INTERACTOR"),
+            (format!(r#"{{"language":"python","source_code":"\u0000x","test_cases":[{}]}}"#, valid_case), 400, "INVALID_ENCODING"),
+        ];
+        for (body, status, code) in cases {
+            let rejection = respond(request(body.clone())).await.unwrap_err();
+            assert_eq!((rejection.status.as_u16(), rejection.code), (status, code), "{}", body);
+        }
+
+        let without_content_type = Request::builder().method("POST").uri("/execute").body(Body::from("{}")).unwrap();
+        let rejection = respond(without_content_type).await.unwrap_err();
+        assert_eq!((rejection.status.as_u16(), rejection.code), (415, "UNSUPPORTED_MEDIA_TYPE"));
+
+        let too_large = format!(r#"{{"source_code":"{}"}}"#, "a".repeat(3 * 1024 * 1024));
+        let rejection = respond(request(too_large)).await.unwrap_err();
+        assert_eq!((rejection.status.as_u16(), rejection.code), (413, "PAYLOAD_TOO_LARGE"));
+    }
+
+    #[tokio::test]
+    async fn test_every_violation_in_the_response() {
+        let body = r#"{"language":"python","source_code":"x","test_cases":[{"input":"","expected_output":"1","weight":4294967295}],"timeout_ms":0}"#;
+        let rejection = respond(request(body)).await.unwrap_err();
+        let response = rejection.into_response();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let error: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(error["error"]["code"], "INVALID_WEIGHT");
+        let codes: Vec<_> = error["error"]["violations"].as_array().unwrap().iter().map(|v| v["code"].clone()).collect();
+        assert_eq!(codes, ["INVALID_WEIGHT", "INVALID_TIMEOUT"]);
+    }
+}
@@ -0,0 +1,164 @@
+// Starvation watchdog - queues with waiting jobs but no worker reading them
+//
+// A queue is starved once it has held jobs for STARVATION_THRESHOLD_SECS (default 300,
+// 0 disables) without any live worker heartbeat listing it among the queues the worker
+// reads. Requirement queues (`optimus:queue:python:gpu`) are checked like the language's
+// own queue, so GPU jobs with no GPU worker are caught too. The usual cause is a
+// misconfiguration such as workers consuming a different queue name, which otherwise
+// goes unnoticed until users complain. The watchdog exports the state as the
+// `optimus_language_starved` gauge (a language is starved while any of its queues is);
+// the notifier alerts on the same condition.
+
+use crate::language_config::LanguageRegistry;
+use crate::{metrics, notifier};
+use chrono::{DateTime, Utc};
+use optimus_common::types::Language;
+use ::redis::aio::ConnectionManager;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tracing::{info, warn};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How long a queue may wait without workers before it counts as starved
+pub fn threshold_secs() -> u64 {
+    static THRESHOLD: OnceLock<u64> = OnceLock::new();
+    *THRESHOLD.get_or_init(|| {
+        std::env::var("STARVATION_THRESHOLD_SECS")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(300)
+    })
+}
+
+/// A starved queue at one check
+#[derive(Debug, Clone, PartialEq)]
+pub struct Starved {
+    pub queue: String,
+    pub language: Language,
+    pub queued: i64,
+    /// When jobs were first seen waiting with no worker alive
+    pub since: DateTime<Utc>,
+}
+
+/// When each queue started waiting without workers
+#[derive(Debug, Default)]
+pub struct StarvationWatch {
+    since: HashMap<String, DateTime<Utc>>,
+}
+
+impl StarvationWatch {
+    /// Queues starved for at least `threshold_secs` (none when it is 0)
+    ///
+    /// `queues` holds (queue, language, waiting) and `consumed` the queues some live
+    /// worker's heartbeat says it reads.
+    pub fn check(
+        &mut self,
+        queues: &[(String, Language, i64)],
+        consumed: &HashSet<String>,
+        now: DateTime<Utc>,
+        threshold_secs: u64,
+    ) -> Vec<Starved> {
+        let mut starved = Vec::new();
+        for (queue, language, queued) in queues {
+            if *queued == 0 || consumed.contains(queue) {
+                self.since.remove(queue);
+                continue;
+            }
+            let since = *self.since.entry(queue.clone()).or_insert(now);
+            if threshold_secs > 0 && (now - since).num_seconds() >= threshold_secs as i64 {
+                starved.push(Starved { queue: queue.clone(), language: *language, queued: *queued, since });
+            }
+        }
+        starved
+    }
+}
+
+/// Background task keeping the starvation gauge up to date
+pub async fn watchdog_loop(registry: Arc<LanguageRegistry>, mut conn: ConnectionManager) {
+    let threshold = threshold_secs();
+    if threshold == 0 {
+        info!("Starvation watchdog disabled (STARVATION_THRESHOLD_SECS=0)");
+        return;
+    }
+
+    let mut watch = StarvationWatch::default();
+    let mut reported = HashSet::new();
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+    loop {
+        interval.tick().await;
+        let snapshot = match notifier::snapshot(&mut conn, &registry).await {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                warn!(error = %e, "Failed to read queue state for starvation check");
+                continue;
+            }
+        };
+        let starved = watch.check(&snapshot.pending, &snapshot.consumed, Utc::now(), threshold);
+        let starved_languages: HashSet<Language> = starved.iter().map(|s| s.language).collect();
+        for language in Language::all_variants() {
+            metrics::set_language_starved(&language.to_string(), starved_languages.contains(language));
+        }
+
+        let starved_queues: HashSet<String> = starved.iter().map(|s| s.queue.clone()).collect();
+        for s in &starved {
+            if reported.insert(s.queue.clone()) {
+                warn!(
+                    queue = %s.queue,
+                    language = %s.language,
+                    queued = s.queued,
+                    since = %s.since,
+                    "Queue starved: jobs waiting but no live worker reads this queue (check queue names and capabilities)"
+                );
+            }
+        }
+        reported.retain(|queue| {
+            let still = starved_queues.contains(queue);
+            if !still {
+                info!(queue = %queue, "Queue no longer starved");
+            }
+            still
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use optimus_common::redis;
+
+    #[test]
+    fn test_starvation_needs_jobs_no_workers_and_time() {
+        let mut watch = StarvationWatch::default();
+        let start = Utc::now();
+        let at = |secs| start + chrono::Duration::seconds(secs);
+        let none = HashSet::new();
+        let python = redis::queue_name(&Language::Python);
+        let python_live = HashSet::from([python.clone()]);
+        let queues = [(python.clone(), Language::Python, 3), (redis::queue_name(&Language::Java), Language::Java, 0)];
+
+        assert!(watch.check(&queues, &none, at(0), 300).is_empty());
+        let starved = watch.check(&queues, &none, at(300), 300);
+        assert_eq!(
+            starved,
+            vec![Starved { queue: python.clone(), language: Language::Python, queued: 3, since: at(0) }]
+        );
+
+        // A heartbeat reading the queue (or an empty queue) resets the clock
+        assert!(watch.check(&queues, &python_live, at(400), 300).is_empty());
+        assert!(watch.check(&queues, &none, at(500), 300).is_empty());
+        assert_eq!(watch.check(&queues, &none, at(800), 300).len(), 1);
+        assert!(watch.check(&[(python.clone(), Language::Python, 0)], &none, at(900), 300).is_empty());
+
+        // A Python worker that doesn't read the GPU queue leaves GPU jobs starved
+        let (gpu, _) = redis::requirement_queues(&Language::Python, &["gpu".to_string()]);
+        let gpu_queues = [(gpu.clone(), Language::Python, 2)];
+        assert!(watch.check(&gpu_queues, &python_live, at(1_000), 300).is_empty());
+        assert_eq!(watch.check(&gpu_queues, &python_live, at(1_300), 300)[0].queue, gpu);
+        assert!(watch.check(&gpu_queues, &HashSet::from([gpu.clone()]), at(1_400), 300).is_empty());
+
+        // Disabled
+        assert!(watch.check(&queues, &none, at(10_000), 0).is_empty());
+    }
+}
@@ -1,104 +1,64 @@
 // CLI commands for managing Optimus
 use anyhow::{Context, Result, bail};
-use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::collections::HashMap;
 use std::fs;
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
+use futures_util::StreamExt;
 use handlebars::Handlebars;
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct LanguageExecution {
-    pub command: String,
-    pub args: Vec<String>,
-    pub file_extension: String,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ResourceRequests {
-    pub memory: String,
-    pub cpu: String,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ResourceLimits {
-    pub memory: String,
-    pub cpu: String,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Resources {
-    pub requests: ResourceRequests,
-    pub limits: ResourceLimits,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Concurrency {
-    pub max_parallel_jobs: u32,
-    pub max_parallel_tests: u32,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct LanguageConfig {
-    pub name: String,
-    pub version: String,
-    pub image: String,
-    pub dockerfile_path: String,
-    pub execution: LanguageExecution,
-    pub queue_name: String,
-    pub memory_limit_mb: u32,
-    pub cpu_limit: f32,
-    pub resources: Resources,
-    pub concurrency: Concurrency,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct LanguagesJson {
-    pub languages: Vec<LanguageConfig>,
+use optimus_common::capabilities;
+use optimus_common::language_config::{CompileSpec, ExecutionConfig, LanguageConfig, LanguagesFile, DEFAULT_CONFIG_PATH};
+use optimus_common::redis;
+use optimus_common::types::{JudgeEnvRelease, Language};
+use crate::export;
+
+/// Load languages configuration (empty if the file doesn't exist yet)
+fn load_languages_config() -> Result<LanguagesFile> {
+    if !Path::new(DEFAULT_CONFIG_PATH).exists() {
+        return Ok(LanguagesFile::default());
+    }
+    LanguagesFile::load(DEFAULT_CONFIG_PATH).map_err(|e| anyhow::anyhow!(e))
 }
 
-/// Load languages configuration
-fn load_languages_config() -> Result<LanguagesJson> {
-    let config_path = Path::new("config/languages.json");
-    if !config_path.exists() {
-        return Ok(LanguagesJson { languages: vec![] });
+/// Languages configuration with a profile's overlay (config/languages.{profile}.json) merged in
+///
+/// Only for reading: commands that change the config write the base file.
+fn load_profile_config(profile: Option<&str>) -> Result<LanguagesFile> {
+    match profile {
+        Some(profile) => LanguagesFile::load_profile(DEFAULT_CONFIG_PATH, Some(profile)).map_err(|e| anyhow::anyhow!(e)),
+        None => load_languages_config(),
     }
+}
 
-    let content = fs::read_to_string(config_path)
-        .context("Failed to read languages.json")?;
-    serde_json::from_str(&content)
-        .context("Failed to parse languages.json")
+/// Capability labels jobs of each configured language may require (see capabilities.rs)
+pub fn requirable_labels(profile: Option<&str>) -> Result<HashMap<Language, Vec<String>>> {
+    Ok(load_profile_config(profile)?
+        .languages
+        .iter()
+        .filter_map(|lang| Some((Language::from_str(&lang.name)?, lang.requirable_labels())))
+        .collect())
 }
 
-/// Save languages configuration
-fn save_languages_config(config: &LanguagesJson) -> Result<()> {
-    let config_path = Path::new("config/languages.json");
-    
-    // Ensure config directory exists
-    if let Some(parent) = config_path.parent() {
-        fs::create_dir_all(parent)?;
-    }
-    
-    let json_content = serde_json::to_string_pretty(&config)
-        .context("Failed to serialize languages.json")?;
-    
-    fs::write(config_path, json_content)
-        .context("Failed to write languages.json")?;
-    
-    Ok(())
+/// Save languages configuration (refuses to write a file the API and workers would reject)
+fn save_languages_config(config: &LanguagesFile) -> Result<()> {
+    config.save(DEFAULT_CONFIG_PATH).map_err(|e| anyhow::anyhow!(e))
 }
 
 /// Add a new language to Optimus
+#[allow(clippy::too_many_arguments)]
 pub async fn add_language(
     name: &str,
     ext: &str,
     version: &str,
     base_image: Option<&str>,
     command: Option<&str>,
+    compile: &[String],
     queue: Option<&str>,
     memory: u32,
-    cpu: f32,
+    cpu: f64,
+    time_multiplier: f64,
     build_docker: bool,
 ) -> Result<()> {
     println!("🚀 Adding language: {}", name);
@@ -119,34 +79,45 @@ pub async fn add_language(
     // Determine defaults
     let exec_command = command.unwrap_or(name).to_string();
     let queue_name = queue.map(|q| q.to_string())
-        .unwrap_or_else(|| format!("optimus:queue:{}", name));
+        .unwrap_or_else(|| optimus_common::redis::canonical_queue_name(name));
     let file_extension = if ext.starts_with('.') {
         ext.to_string()
     } else {
         format!(".{}", ext)
     };
 
-    // Calculate resource allocations
-    let (resources, concurrency) = calculate_resources(memory, cpu);
-
     // Create new language config
-    let new_lang = LanguageConfig {
+    let mut new_lang = LanguageConfig {
         name: name.to_string(),
         version: version.to_string(),
         image: format!("optimus-{}:{}", name, version),
         dockerfile_path: format!("dockerfiles/{}/Dockerfile", name),
-        execution: LanguageExecution {
+        platforms: vec![],
+        execution: ExecutionConfig {
             command: exec_command,
             args: vec![],
             file_extension,
         },
+        compile: compile.split_first().map(|(command, then)| CompileSpec { command: command.clone(), then: then.to_vec() }),
         queue_name,
         memory_limit_mb: memory,
         cpu_limit: cpu,
-        resources,
-        concurrency,
+        time_multiplier,
+        resources: None,
+        concurrency: None,
+        env_version: 0,
+        env_history: vec![],
+        canary: None,
+        determinism: None,
+        flags: Default::default(),
+        gpu: None,
+        capabilities: Vec::new(),
     };
 
+    // Pod resources and concurrency are derived from the container limits
+    new_lang.resources = Some(new_lang.resources_or_default());
+    new_lang.concurrency = Some(new_lang.concurrency_or_default());
+
     // Add to languages
     languages_json.languages.push(new_lang);
 
@@ -168,7 +139,7 @@ pub async fn add_language(
     // Build Docker image if requested
     if build_docker {
         println!("\n🔨 Building Docker image...");
-        build_docker_image(name, false).await?;
+        build_docker_image(name, false, None, false, None).await?;
         
         println!("\n📋 Next steps:");
         println!("  1. Render K8s manifests: optimus-cli render-k8s");
@@ -184,41 +155,6 @@ pub async fn add_language(
     Ok(())
 }
 
-/// Calculate resource allocations based on memory and CPU
-fn calculate_resources(memory_mb: u32, cpu: f32) -> (Resources, Concurrency) {
-    // Resource requests are 50% of limits
-    let memory_request = format!("{}Mi", memory_mb * 2);
-    let memory_limit = format!("{}Gi", (memory_mb as f32 * 4.0 / 1024.0).ceil() as u32);
-    let cpu_request = format!("{}m", (cpu * 1000.0) as u32);
-    let cpu_limit = format!("{}m", (cpu * 4000.0) as u32);
-
-    let resources = Resources {
-        requests: ResourceRequests {
-            memory: memory_request,
-            cpu: cpu_request,
-        },
-        limits: ResourceLimits {
-            memory: memory_limit,
-            cpu: cpu_limit,
-        },
-    };
-
-    // Concurrency based on memory
-    let concurrency = if memory_mb >= 512 {
-        Concurrency {
-            max_parallel_jobs: 2,
-            max_parallel_tests: 3,
-        }
-    } else {
-        Concurrency {
-            max_parallel_jobs: 3,
-            max_parallel_tests: 5,
-        }
-    };
-
-    (resources, concurrency)
-}
-
 /// Remove a language from Optimus
 pub async fn remove_language(name: &str, yes: bool) -> Result<()> {
     println!("🗑️  Removing language: {}", name);
@@ -236,10 +172,10 @@ pub async fn remove_language(name: &str, yes: bool) -> Result<()> {
 
     // Confirm deletion
     if !yes {
-        print!("⚠️  This will remove:\n");
-        print!("  - Config entry in languages.json\n");
-        print!("  - Dockerfile at {}\n", lang_dockerfile_path);
-        print!("  - K8s manifests (worker-deployment-{}.yaml, KEDA ScaledObjects)\n", name);
+        println!("⚠️  This will remove:");
+        println!("  - Config entry in languages.json");
+        println!("  - Dockerfile at {}", lang_dockerfile_path);
+        println!("  - K8s manifests (worker-deployment-{}.yaml, KEDA ScaledObjects)", name);
         print!("\nContinue? (y/N): ");
         io::stdout().flush()?;
 
@@ -320,8 +256,8 @@ pub async fn remove_language(name: &str, yes: bool) -> Result<()> {
 }
 
 /// List all configured languages
-pub async fn list_languages() -> Result<()> {
-    let languages_json = load_languages_config()?;
+pub async fn list_languages(profile: Option<&str>) -> Result<()> {
+    let languages_json = load_profile_config(profile)?;
 
     if languages_json.languages.is_empty() {
         println!("No languages configured.");
@@ -364,7 +300,13 @@ fn generate_dockerfile(
     let dockerfile_content = match name {
         "python" => generate_python_dockerfile(version),
         "java" => generate_java_dockerfile(version),
+        "kotlin" => generate_kotlin_dockerfile(version),
+        "scala" => generate_scala_dockerfile(version),
         "rust" => generate_rust_dockerfile(version),
+        "c" => generate_c_dockerfile(version),
+        "php" => generate_php_dockerfile(version),
+        "ruby" => generate_ruby_dockerfile(version),
+        "asm" => generate_asm_dockerfile(version),
         "cpp" => generate_cpp_dockerfile(version),
         "go" => generate_go_dockerfile(version),
         "javascript" | "node" => generate_node_dockerfile(version),
@@ -411,6 +353,10 @@ ENV PYTHONUNBUFFERED=1 \
 
 WORKDIR /code
 
+# GNU time lets the runner report peak memory per test
+RUN apt-get update && apt-get install -y --no-install-recommends time \
+    && rm -rf /var/lib/apt/lists/*
+
 # Install common packages (pre-installed at build time, not runtime)
 RUN pip install --no-cache-dir \
     pytest==7.4.3 \
@@ -447,8 +393,8 @@ ENV JAVA_TOOL_OPTIONS="-XX:+UseContainerSupport -XX:MaxRAMPercentage=75.0 -XX:+T
 
 WORKDIR /code
 
-# Install JUnit and bash at build time (bash needed for runner.sh)
-RUN apk add --no-cache wget bash && \
+# Install JUnit, bash and setpriv at build time (bash needed for runner.sh, setpriv for the test runner)
+RUN apk add --no-cache wget bash setpriv && \
     wget -q https://repo1.maven.org/maven2/junit/junit/4.13.2/junit-4.13.2.jar -P /opt/ && \
     wget -q https://repo1.maven.org/maven2/org/hamcrest/hamcrest-core/1.3/hamcrest-core-1.3.jar -P /opt/ && \
     apk del wget && \
@@ -473,7 +419,222 @@ ENTRYPOINT ["/runner.sh"]
     )
 }
 
-/// Generate C++ Dockerfile
+/// Generate Kotlin Dockerfile (`version` is the JDK's, as for Java)
+fn generate_kotlin_dockerfile(version: &str) -> String {
+    format!(
+        r#"# GENERATED BY optimus-cli — DO NOT EDIT
+# Kotlin Execution Environment - compiled with kotlinc, run on the JDK
+FROM eclipse-temurin:{}-jdk-alpine
+
+ARG KOTLIN_VERSION=1.9.24
+
+# Set environment variables for performance
+ENV JAVA_TOOL_OPTIONS="-XX:+UseContainerSupport -XX:MaxRAMPercentage=75.0 -XX:+TieredCompilation -XX:TieredStopAtLevel=1" \
+    PATH=/opt/kotlinc/bin:$PATH \
+    LANGUAGE=kotlin
+
+WORKDIR /code
+
+# Install the Kotlin compiler, bash and setpriv at build time (bash needed for kotlinc and runner.sh, setpriv for the test runner)
+RUN apk add --no-cache wget unzip bash setpriv && \
+    wget -q https://github.com/JetBrains/kotlin/releases/download/v${{KOTLIN_VERSION}}/kotlin-compiler-${{KOTLIN_VERSION}}.zip -O /tmp/kotlinc.zip && \
+    unzip -q /tmp/kotlinc.zip -d /opt && \
+    rm /tmp/kotlinc.zip && \
+    apk del wget unzip && \
+    rm -rf /var/cache/apk/*
+
+# Copy universal runner script (build context is repo root)
+COPY dockerfiles/runner.sh /runner.sh
+RUN chmod +x /runner.sh
+
+# Create non-root user for security
+RUN adduser -D -u 1000 optimus && \
+    chown -R optimus:optimus /code
+
+USER optimus
+
+# Use universal runner
+ENTRYPOINT ["/runner.sh"]
+"#,
+        version
+    )
+}
+
+/// Generate Scala Dockerfile (`version` is the JDK's, as for Java)
+fn generate_scala_dockerfile(version: &str) -> String {
+    format!(
+        r#"# GENERATED BY optimus-cli — DO NOT EDIT
+# Scala Execution Environment - compiled with scalac, run on the JDK
+FROM eclipse-temurin:{}-jdk-alpine
+
+ARG SCALA_VERSION=2.13.14
+
+# Set environment variables for performance
+ENV JAVA_TOOL_OPTIONS="-XX:+UseContainerSupport -XX:MaxRAMPercentage=75.0 -XX:+TieredCompilation -XX:TieredStopAtLevel=1" \
+    PATH=/opt/scala/bin:$PATH \
+    LANGUAGE=scala
+
+WORKDIR /code
+
+# Install Scala, bash and setpriv at build time (bash needed for scalac and runner.sh, setpriv for the test runner)
+# Programs run with /opt/scala/lib/scala-library.jar on the class path
+RUN apk add --no-cache wget bash setpriv && \
+    wget -q https://downloads.lightbend.com/scala/${{SCALA_VERSION}}/scala-${{SCALA_VERSION}}.tgz -O /tmp/scala.tgz && \
+    mkdir -p /opt/scala && \
+    tar -xzf /tmp/scala.tgz -C /opt/scala --strip-components=1 && \
+    rm /tmp/scala.tgz && \
+    apk del wget && \
+    rm -rf /var/cache/apk/*
+
+# Copy universal runner script (build context is repo root)
+COPY dockerfiles/runner.sh /runner.sh
+RUN chmod +x /runner.sh
+
+# Create non-root user for security
+RUN adduser -D -u 1000 optimus && \
+    chown -R optimus:optimus /code
+
+USER optimus
+
+# Use universal runner
+ENTRYPOINT ["/runner.sh"]
+"#,
+        version
+    )
+}
+
+/// Generate C Dockerfile (gcc ships the sanitizer runtimes jobs may ask for)
+fn generate_c_dockerfile(version: &str) -> String {
+    format!(
+        r#"# GENERATED BY optimus-cli — DO NOT EDIT
+# C Execution Environment
+FROM gcc:{}
+
+# Set environment variables
+ENV LANGUAGE=c
+
+WORKDIR /code
+
+# GNU time lets the runner report peak memory per test
+RUN apt-get update && apt-get install -y --no-install-recommends time \
+    && rm -rf /var/lib/apt/lists/*
+
+# Copy universal runner script (build context is repo root)
+COPY dockerfiles/runner.sh /runner.sh
+RUN chmod +x /runner.sh
+
+# Create non-root user for security
+RUN useradd -m -u 1000 optimus && \
+    chown -R optimus:optimus /code
+
+USER optimus
+
+# Use universal runner
+ENTRYPOINT ["/runner.sh"]
+"#,
+        version
+    )
+}
+
+/// Generate PHP Dockerfile (CLI interpreter only)
+fn generate_php_dockerfile(version: &str) -> String {
+    format!(
+        r#"# GENERATED BY optimus-cli — DO NOT EDIT
+# PHP Execution Environment
+FROM php:{}-cli
+
+# Set environment variables
+ENV LANGUAGE=php
+
+WORKDIR /code
+
+# GNU time lets the runner report peak memory per test
+RUN apt-get update && apt-get install -y --no-install-recommends time \
+    && rm -rf /var/lib/apt/lists/*
+
+# Copy universal runner script (build context is repo root)
+COPY dockerfiles/runner.sh /runner.sh
+RUN chmod +x /runner.sh
+
+# Create non-root user for security
+RUN useradd -m -u 1000 optimus && \
+    chown -R optimus:optimus /code
+
+USER optimus
+
+# Use universal runner
+ENTRYPOINT ["/runner.sh"]
+"#,
+        version
+    )
+}
+
+/// Generate Ruby Dockerfile
+fn generate_ruby_dockerfile(version: &str) -> String {
+    format!(
+        r#"# GENERATED BY optimus-cli — DO NOT EDIT
+# Ruby Execution Environment
+FROM ruby:{}-slim
+
+# Set environment variables
+ENV LANGUAGE=ruby
+
+WORKDIR /code
+
+# GNU time lets the runner report peak memory per test
+RUN apt-get update && apt-get install -y --no-install-recommends time \
+    && rm -rf /var/lib/apt/lists/*
+
+# Copy universal runner script (build context is repo root)
+COPY dockerfiles/runner.sh /runner.sh
+RUN chmod +x /runner.sh
+
+# Create non-root user for security
+RUN useradd -m -u 1000 optimus && \
+    chown -R optimus:optimus /code
+
+USER optimus
+
+# Use universal runner
+ENTRYPOINT ["/runner.sh"]
+"#,
+        version
+    )
+}
+
+/// Generate x86-64 assembly Dockerfile (nasm and binutils' ld on Debian)
+fn generate_asm_dockerfile(version: &str) -> String {
+    format!(
+        r#"# GENERATED BY optimus-cli — DO NOT EDIT
+# x86-64 Assembly Execution Environment - assembled with nasm, linked with ld
+FROM debian:{}-slim
+
+# Set environment variables
+ENV LANGUAGE=asm
+
+WORKDIR /code
+
+# nasm and ld build the program; GNU time lets the runner report peak memory per test
+RUN apt-get update && apt-get install -y --no-install-recommends nasm binutils time \
+    && rm -rf /var/lib/apt/lists/*
+
+# Copy universal runner script (build context is repo root)
+COPY dockerfiles/runner.sh /runner.sh
+RUN chmod +x /runner.sh
+
+# Create non-root user for security
+RUN useradd -m -u 1000 optimus && \
+    chown -R optimus:optimus /code
+
+USER optimus
+
+# Use universal runner
+ENTRYPOINT ["/runner.sh"]
+"#,
+        version
+    )
+}
+
 fn generate_cpp_dockerfile(version: &str) -> String {
     format!(
         r#"# GENERATED BY optimus-cli — DO NOT EDIT
@@ -589,6 +750,7 @@ WORKDIR /code
 # Install required packages
 RUN apt-get update && apt-get install -y --no-install-recommends \
     ca-certificates \
+    time \
     && rm -rf /var/lib/apt/lists/*
 
 # Copy universal runner script (build context is repo root)
@@ -609,11 +771,15 @@ ENTRYPOINT ["/runner.sh"]
 }
 
 /// Build Docker image for a language
-pub async fn build_docker_image(name: &str, no_cache: bool) -> Result<()> {
+///
+/// With target platforms (from `--platform` or the language's `platforms`) the image is
+/// built with buildx. A multi-arch manifest can't be loaded into the local image store,
+/// so several platforms require `--push`, which publishes the configured `image`.
+pub async fn build_docker_image(name: &str, no_cache: bool, platform: Option<&str>, push: bool, profile: Option<&str>) -> Result<()> {
     println!("🐳 Building Docker image for: {}", name);
     
     // Read languages.json to get version info
-    let languages_json = load_languages_config()?;
+    let languages_json = load_profile_config(profile)?;
     
     let lang_config = languages_json.languages.iter()
         .find(|l| l.name == name)
@@ -625,11 +791,32 @@ pub async fn build_docker_image(name: &str, no_cache: bool) -> Result<()> {
     if !dockerfile_path.exists() {
         bail!("Dockerfile not found at {}. Generate it first with add-lang command.", dockerfile_path.display());
     }
+
+    let platforms: Vec<String> = match platform {
+        Some(list) => list.split(',').map(|p| p.trim().to_string()).filter(|p| !p.is_empty()).collect(),
+        None => lang_config.platforms.clone(),
+    };
+    let buildx = !platforms.is_empty() || push;
+    if platforms.len() > 1 && !push {
+        bail!(
+            "Building for {} platforms produces a multi-arch manifest, which the local image store can't hold. \
+             Re-run with --push to publish {}, or pick one with --platform.",
+            platforms.len(),
+            lang_config.image
+        );
+    }
     
-    // Build image tag
-    let image_tag = format!("optimus-{}:{}", name, lang_config.version);
+    // Build image tag (pushed builds publish the configured image reference)
+    let image_tag = if push {
+        lang_config.image.clone()
+    } else {
+        format!("optimus-{}:{}", name, lang_config.version)
+    };
     
     println!("📦 Building tag: {}", image_tag);
+    if !platforms.is_empty() {
+        println!("🖥️  Platforms: {}", platforms.join(", "));
+    }
     
     // Use current directory (.) as build context to support both:
     // - COPY dockerfiles/{lang}/file.ext (for manually created Dockerfiles)
@@ -639,17 +826,33 @@ pub async fn build_docker_image(name: &str, no_cache: bool) -> Result<()> {
     println!("📄 Dockerfile: {}", dockerfile_path.display());
     
     // Build docker command
-    let mut docker_args = vec![
+    let mut docker_args = Vec::new();
+    if buildx {
+        docker_args.push("buildx".to_string());
+    }
+    docker_args.extend([
         "build".to_string(),
         "-t".to_string(),
         image_tag.clone(),
         "-f".to_string(),
         dockerfile_path.to_string_lossy().to_string(),
-    ];
+    ]);
     
     if no_cache {
         docker_args.push("--no-cache".to_string());
     }
+
+    // buildx reports the pushed manifest's digest here; it identifies the environment
+    let metadata_file = std::env::temp_dir().join(format!("optimus-build-{}-{}.json", name, std::process::id()));
+    if buildx {
+        if !platforms.is_empty() {
+            docker_args.push("--platform".to_string());
+            docker_args.push(platforms.join(","));
+        }
+        docker_args.push(if push { "--push" } else { "--load" }.to_string());
+        docker_args.push("--metadata-file".to_string());
+        docker_args.push(metadata_file.to_string_lossy().to_string());
+    }
     
     // Add build context as the final argument
     docker_args.push(build_context.to_string());
@@ -663,7 +866,7 @@ pub async fn build_docker_image(name: &str, no_cache: bool) -> Result<()> {
         .stdout(Stdio::inherit())
         .stderr(Stdio::inherit())
         .status()
-        .context("Failed to execute docker build. Is Docker installed and running?")?;
+        .context("Failed to execute docker build. Is Docker installed and running? (multi-arch builds need buildx)")?;
     
     if !status.success() {
         bail!("Docker build failed with exit code: {:?}", status.code());
@@ -671,29 +874,193 @@ pub async fn build_docker_image(name: &str, no_cache: bool) -> Result<()> {
     
     println!("\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
     println!("✅ Docker image built successfully!");
-    println!("\n📦 Available image: {}", image_tag);
-    
-    // Verify image exists
-    println!("\n🔍 Verifying image...");
-    let verify_status = Command::new("docker")
-        .args(&["images", &image_tag, "--format", "{{.Repository}}:{{.Tag}}"])
-        .stdout(Stdio::inherit())
-        .stderr(Stdio::inherit())
-        .status();
+
+    let image_id = if push {
+        println!("\n📤 Pushed: {}", image_tag);
+        let digest = manifest_digest(&metadata_file);
+        let _ = fs::remove_file(&metadata_file);
+        digest
+    } else {
+        let _ = fs::remove_file(&metadata_file);
+        println!("\n📦 Available image: {}", image_tag);
+        
+        // Verify image exists
+        println!("\n🔍 Verifying image...");
+        let verify_status = Command::new("docker")
+            .args(["images", &image_tag, "--format", "{{.Repository}}:{{.Tag}}"])
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit())
+            .status();
+        
+        if verify_status.is_ok() {
+            println!("✅ Image verification complete!");
+        }
+        local_image_id(&image_tag)?
+    };
+
+    record_env_release(name, &image_tag, image_id)?;
     
-    if verify_status.is_ok() {
-        println!("✅ Image verification complete!");
+    Ok(())
+}
+
+/// Id of a locally stored image
+fn local_image_id(image_tag: &str) -> Result<Option<String>> {
+    let output = Command::new("docker")
+        .args(["image", "inspect", "--format", "{{.Id}}", image_tag])
+        .output()
+        .context("Failed to inspect built image")?;
+    Ok(Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|id| output.status.success() && !id.is_empty()))
+}
+
+/// Digest of the manifest a buildx build pushed, from its --metadata-file
+fn manifest_digest(metadata_file: &Path) -> Option<String> {
+    let metadata: serde_json::Value = serde_json::from_str(&fs::read_to_string(metadata_file).ok()?).ok()?;
+    metadata["containerimage.digest"].as_str().map(str::to_string)
+}
+
+/// Bump the language's judge environment version if the build produced a new image
+///
+/// Rebuilds that hit the cache yield the same image id (or manifest digest, for pushed
+/// builds) and keep the current version, so the version only moves when the environment
+/// that judges code actually changed.
+fn record_env_release(name: &str, image_tag: &str, image_id: Option<String>) -> Result<()> {
+    let mut languages_json = load_languages_config()?;
+    let lang_config = languages_json.languages.iter_mut()
+        .find(|l| l.name == name)
+        .ok_or_else(|| anyhow::anyhow!("Language '{}' not found in config", name))?;
+
+    let last = lang_config.env_history.last();
+    if image_id.is_some() && last.map(|r| &r.image_id) == Some(&image_id) {
+        println!("\n🏷️  Image unchanged - judge environment stays at v{}", lang_config.env_version);
+        return Ok(());
     }
-    
+
+    lang_config.env_version += 1;
+    lang_config.env_history.push(JudgeEnvRelease {
+        version: lang_config.env_version,
+        image: image_tag.to_string(),
+        image_id,
+        built_at: chrono::Utc::now(),
+    });
+    println!("\n🏷️  Judge environment for {} is now v{}", name, lang_config.env_version);
+
+    save_languages_config(&languages_json)
+}
+
+/// Judge images of all configured languages as (init container name, image)
+/// Active canary images are included, so promoting one doesn't cold-pull either
+fn judge_images(languages: &LanguagesFile) -> Vec<(String, String)> {
+    let mut images = Vec::new();
+    for lang in &languages.languages {
+        images.push((lang.name.clone(), lang.image.clone()));
+        if let Some(canary) = lang.canary.as_ref().filter(|c| c.percent > 0.0) {
+            images.push((format!("{}-canary", lang.name), canary.image.clone()));
+        }
+    }
+    images
+}
+
+/// Pull every configured judge image onto this node, or write a DaemonSet that does it on every node
+pub async fn pull_images(parallel: usize, build_missing: bool, daemonset: bool, profile: Option<&str>) -> Result<()> {
+    let languages_json = load_profile_config(profile)?;
+    if languages_json.languages.is_empty() {
+        bail!("No languages configured. Add a language first with: optimus-cli add-lang");
+    }
+    let images = judge_images(&languages_json);
+
+    if daemonset {
+        return render_prepuller_daemonset(&images);
+    }
+
+    println!("📥 Pulling {} judge image(s), {} at a time...\n", images.len(), parallel);
+    let results: Vec<(String, String, Result<()>)> = futures_util::stream::iter(images)
+        .map(|(name, image)| async move {
+            let result = docker_pull(&image).await;
+            (name, image, result)
+        })
+        .buffer_unordered(parallel.max(1))
+        .collect()
+        .await;
+
+    let mut failed = Vec::new();
+    for (name, image, result) in results {
+        match result {
+            Ok(()) => println!("  ✅ {}", image),
+            Err(e) => {
+                println!("  ❌ {} ({})", image, e);
+                failed.push((name, image));
+            }
+        }
+    }
+
+    if build_missing {
+        // Images that exist in no registry are built locally (canary images are never built)
+        for (name, image) in failed.iter().filter(|(name, _)| !name.ends_with("-canary")) {
+            println!("\n🔨 {} could not be pulled - building it", image);
+            build_docker_image(name, false, None, false, profile).await?;
+        }
+        failed.retain(|(name, _)| name.ends_with("-canary"));
+    }
+
+    println!();
+    if !failed.is_empty() {
+        bail!(
+            "{} image(s) could not be pulled: {}",
+            failed.len(),
+            failed.iter().map(|(_, image)| image.as_str()).collect::<Vec<_>>().join(", ")
+        );
+    }
+    println!("✅ All judge images are cached on this node");
+    Ok(())
+}
+
+async fn docker_pull(image: &str) -> Result<()> {
+    let output = tokio::process::Command::new("docker")
+        .args(["pull", "--quiet", image])
+        .output()
+        .await
+        .context("Failed to execute docker pull. Is Docker installed and running?")?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("{}", stderr.lines().last().unwrap_or("docker pull failed").trim())
+    }
+}
+
+fn render_prepuller_daemonset(images: &[(String, String)]) -> Result<()> {
+    let template = fs::read_to_string("config/templates/image-prepuller.yaml.tmpl")
+        .context("Failed to read image-prepuller.yaml.tmpl")?;
+    let mut handlebars = Handlebars::new();
+    handlebars.set_strict_mode(true);
+    handlebars.register_template_string("prepuller", &template)?;
+
+    let data = json!({
+        "images": images
+            .iter()
+            .map(|(name, image)| json!({ "name": name, "image": image }))
+            .collect::<Vec<_>>(),
+    });
+    let yaml = handlebars.render("prepuller", &data).context("Failed to render image pre-puller DaemonSet")?;
+    let path = "k8s/image-prepuller.yaml";
+    fs::write(path, yaml).context(format!("Failed to write {}", path))?;
+
+    println!("✅ {} ({} image(s))", path, images.len());
+    println!("\n📋 Deploy it with:");
+    println!("     kubectl apply -f {}", path);
     Ok(())
 }
 
 /// Render Kubernetes manifests from templates for all configured languages
-pub async fn render_k8s_manifests() -> Result<()> {
+pub async fn render_k8s_manifests(profile: Option<&str>, node_pools: bool) -> Result<()> {
     println!("📊 Rendering Kubernetes manifests from templates...\n");
+    if let Some(profile) = profile {
+        println!("🏷️  Profile: {} (workers load it via OPTIMUS_ENV)\n", profile);
+    }
     
     // Load languages config
-    let languages_json = load_languages_config()?;
+    let languages_json = load_profile_config(profile)?;
     
     if languages_json.languages.is_empty() {
         bail!("No languages configured. Add a language first with: optimus-cli add-lang");
@@ -725,17 +1092,25 @@ pub async fn render_k8s_manifests() -> Result<()> {
     for lang in &languages_json.languages {
         println!("🔧 Rendering manifests for: {}", lang.name);
         
-        // Prepare template data
+        // Prepare template data (older files may lack resources/concurrency)
+        let resources = lang.resources_or_default();
+        let concurrency = lang.concurrency_or_default();
         let data = json!({
             "language": lang.name,
             "queue_name": lang.queue_name,
             "image": lang.image,
-            "memory_request": lang.resources.requests.memory,
-            "memory_limit": lang.resources.limits.memory,
-            "cpu_request": lang.resources.requests.cpu,
-            "cpu_limit": lang.resources.limits.cpu,
-            "max_parallel_jobs": lang.concurrency.max_parallel_jobs,
-            "max_parallel_tests": lang.concurrency.max_parallel_tests,
+            "memory_request": resources.requests.memory,
+            "memory_limit": resources.limits.memory,
+            "cpu_request": resources.requests.cpu,
+            "cpu_limit": resources.limits.cpu,
+            "max_parallel_jobs": concurrency.max_parallel_jobs,
+            "max_parallel_tests": concurrency.max_parallel_tests,
+            "profile": profile,
+            "suffix": "",
+            "gpu": false,
+            "arch": null,
+            "capabilities": "",
+            "node_pools": node_pools,
         });
         
         // Render worker deployment
@@ -757,6 +1132,43 @@ pub async fn render_k8s_manifests() -> Result<()> {
         
         generated_files.push(worker_path);
         generated_files.push(scaled_object_path);
+
+        // Workers for jobs with requirements: a deployment and scaler per requirement set,
+        // scaled on the set's own queue (they read the plainer queues too)
+        for requirements in capabilities::satisfiable_sets(&lang.requirable_labels()) {
+            let archs: Vec<&String> = requirements.iter().filter(|label| ARCH_LABELS.contains(&label.as_str())).collect();
+            // No node has two architectures
+            if requirements.is_empty() || archs.len() > 1 {
+                continue;
+            }
+            let gpu = requirements.iter().any(|label| label == capabilities::GPU);
+            let listed: Vec<&str> = requirements
+                .iter()
+                .map(String::as_str)
+                .filter(|label| *label != capabilities::GPU && !ARCH_LABELS.contains(label))
+                .collect();
+            let name = format!("{}-{}", lang.name, requirements.join("-"));
+            let mut set_data = data.clone();
+            set_data["suffix"] = json!(format!("-{}", requirements.join("-")));
+            set_data["queue_name"] = json!(format!("{}{}", lang.queue_name, capabilities::queue_suffix(&requirements)));
+            set_data["gpu"] = json!(gpu);
+            set_data["gpu_devices"] = json!(lang.gpu.map_or(1, |gpu| gpu.devices));
+            set_data["arch"] = json!(archs.first());
+            set_data["capabilities"] = json!(listed.join(","));
+            // gen-infra's node groups offer none of these labels
+            set_data["node_pools"] = json!(false);
+            for (template, path) in [
+                ("worker", format!("k8s/workers/worker-deployment-{}.yaml", name)),
+                ("scaled_object", format!("k8s/keda/scaled-object-{}.yaml", name)),
+            ] {
+                let yaml = handlebars.render(template, &set_data)
+                    .context(format!("Failed to render {} for {}", template, name))?;
+                fs::write(&path, yaml)
+                    .context(format!("Failed to write {}", path))?;
+                println!("  ✅ {}", path);
+                generated_files.push(path);
+            }
+        }
     }
     
     println!("\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
@@ -789,3 +1201,332 @@ pub async fn render_k8s_manifests() -> Result<()> {
     Ok(())
 }
 
+
+/// Capability labels naming a CPU architecture (see capabilities::arch_label)
+const ARCH_LABELS: &[&str] = &["amd64", "arm64"];
+
+/// Worker replicas KEDA keeps and allows per language (see scaled-object.yaml.tmpl)
+const MIN_WORKERS: u64 = 10;
+const MAX_WORKERS: u64 = 50;
+
+/// Worker pods a node should hold at least, so one pod's churn doesn't leave a node idle
+const MIN_PODS_PER_NODE: u64 = 2;
+
+/// General-purpose instance sizes (vCPUs, GiB) tried smallest first
+const INSTANCE_SIZES: &[(&str, u64, u64)] = &[
+    ("large", 2, 8),
+    ("xlarge", 4, 16),
+    ("2xlarge", 8, 32),
+    ("4xlarge", 16, 64),
+    ("8xlarge", 32, 128),
+];
+
+/// CPU quantity ("500m", "2", "1.5") in millicores
+fn cpu_millis(quantity: &str) -> Option<u64> {
+    match quantity.strip_suffix('m') {
+        Some(millis) => millis.parse().ok(),
+        None => quantity.parse::<f64>().ok().map(|cores| (cores * 1000.0).ceil() as u64),
+    }
+}
+
+/// Memory quantity ("512Mi", "1Gi", "1G") in MiB
+fn memory_mib(quantity: &str) -> Option<u64> {
+    const UNITS: &[(&str, f64)] = &[
+        ("Ki", 1.0 / 1024.0), ("Mi", 1.0), ("Gi", 1024.0), ("Ti", 1024.0 * 1024.0),
+        ("k", 1e3 / 1048576.0), ("M", 1e6 / 1048576.0), ("G", 1e9 / 1048576.0), ("T", 1e12 / 1048576.0),
+    ];
+    let (number, factor) = UNITS
+        .iter()
+        .find_map(|(suffix, factor)| quantity.strip_suffix(suffix).map(|n| (n, *factor)))
+        .unwrap_or((quantity, 1.0 / 1048576.0));
+    number.parse::<f64>().ok().map(|n| (n * factor).ceil() as u64)
+}
+
+/// Node group for one language: the smallest instance that fits MIN_PODS_PER_NODE worker
+/// pods at their limits (judge containers run inside those limits), with enough nodes for
+/// KEDA's replica range
+fn node_group(lang: &LanguageConfig) -> Result<serde_json::Value> {
+    let limits = lang.resources_or_default().limits;
+    let pod_cpu = cpu_millis(&limits.cpu)
+        .ok_or_else(|| anyhow::anyhow!("{}: can't read CPU limit {:?}", lang.name, limits.cpu))?
+        .max(1);
+    let pod_memory = memory_mib(&limits.memory)
+        .ok_or_else(|| anyhow::anyhow!("{}: can't read memory limit {:?}", lang.name, limits.memory))?
+        .max(1);
+
+    // Graviton only when every image is built for arm64 alone
+    let arm = !lang.platforms.is_empty() && lang.platforms.iter().all(|p| p.starts_with("linux/arm64"));
+    let (family, ami_type) = if arm { ("m7g", "AL2_ARM_64") } else { ("m6i", "AL2_x86_64") };
+
+    // Leave room for the kubelet, system pods and the Docker daemon
+    let pods_on = |vcpus: u64, gib: u64| ((vcpus * 900) / pod_cpu).min((gib * 1024 * 85 / 100) / pod_memory);
+    let (size, pods_per_node) = INSTANCE_SIZES
+        .iter()
+        .map(|(size, vcpus, gib)| (*size, pods_on(*vcpus, *gib)))
+        .find(|(_, pods)| *pods >= MIN_PODS_PER_NODE)
+        .unwrap_or_else(|| {
+            let (size, vcpus, gib) = INSTANCE_SIZES[INSTANCE_SIZES.len() - 1];
+            (size, pods_on(vcpus, gib))
+        });
+    if pods_per_node == 0 {
+        bail!("{}: a worker pod ({} CPU, {}) doesn't fit on any {} instance", lang.name, limits.cpu, limits.memory, family);
+    }
+
+    Ok(json!({
+        "language": lang.name,
+        "resource_name": lang.name.replace('-', "_"),
+        "instance_type": format!("{}.{}", family, size),
+        "ami_type": ami_type,
+        "pod_cpu": limits.cpu,
+        "pod_memory": limits.memory,
+        "pods_per_node": pods_per_node,
+        "min_size": MIN_WORKERS.div_ceil(pods_per_node),
+        "max_size": MAX_WORKERS.div_ceil(pods_per_node),
+    }))
+}
+
+/// ECR repository name for an image reference (registry host and tag dropped)
+fn repository_name(image: &str) -> String {
+    let without_digest = image.split('@').next().unwrap_or(image);
+    let mut parts: Vec<&str> = without_digest.split('/').collect();
+    if parts.len() > 1 && (parts[0].contains('.') || parts[0].contains(':') || parts[0] == "localhost") {
+        parts.remove(0);
+    }
+    let path = parts.join("/");
+    match path.rsplit_once(':') {
+        Some((name, _tag)) => name.to_string(),
+        None => path,
+    }
+}
+
+/// Generate infrastructure-as-code for a cloud provider from config/languages.json
+pub async fn gen_infra(provider: &str, profile: Option<&str>) -> Result<()> {
+    let languages_json = load_profile_config(profile)?;
+    if languages_json.languages.is_empty() {
+        bail!("No languages configured. Add a language first with: optimus-cli add-lang");
+    }
+
+    let template_path = match provider {
+        "aws" => "config/templates/infra-aws.tf.tmpl",
+        other => bail!("Unsupported provider '{}' (supported: aws)", other),
+    };
+    println!("☁️  Generating {} infrastructure...\n", provider);
+
+    let node_groups = languages_json.languages.iter().map(node_group).collect::<Result<Vec<_>>>()?;
+
+    let mut repositories: Vec<String> = ["optimus-api".to_string(), "optimus-worker".to_string()]
+        .into_iter()
+        .chain(judge_images(&languages_json).into_iter().map(|(_, image)| repository_name(&image)))
+        .collect();
+    repositories.sort();
+    repositories.dedup();
+
+    let template = fs::read_to_string(template_path).context(format!("Failed to read {}", template_path))?;
+    let mut handlebars = Handlebars::new();
+    handlebars.set_strict_mode(true);
+    handlebars.register_escape_fn(handlebars::no_escape);
+    handlebars.register_template_string("infra", &template)?;
+
+    let data = json!({
+        "node_groups": node_groups,
+        "repositories": repositories
+            .iter()
+            .map(|name| json!({ "name": name, "resource_name": name.replace(['-', '/', '.'], "_") }))
+            .collect::<Vec<_>>(),
+    });
+    let output = handlebars.render("infra", &data).context("Failed to render infrastructure template")?;
+
+    let mut dir = PathBuf::from("infra").join(provider);
+    if let Some(profile) = profile {
+        dir.push(profile);
+    }
+    fs::create_dir_all(&dir).context(format!("Failed to create {}", dir.display()))?;
+    let path = dir.join("main.tf");
+    fs::write(&path, output).context(format!("Failed to write {}", path.display()))?;
+
+    for group in &node_groups {
+        println!(
+            "  🖥️  {}: {} × {}-{} nodes ({} worker pods per node)",
+            group["language"].as_str().unwrap_or_default(),
+            group["instance_type"].as_str().unwrap_or_default(),
+            group["min_size"],
+            group["max_size"],
+            group["pods_per_node"],
+        );
+    }
+    println!("  📦 {} ECR repositories", repositories.len());
+    println!("\n✅ {}", path.display());
+    println!("\n📋 Next steps:");
+    println!("     cd {} && terraform init", dir.display());
+    println!("     terraform plan -var cluster_name=... -var node_role_arn=... -var vpc_id=... -var 'subnet_ids=[...]'");
+    println!("  Then render workers onto the node groups: optimus-cli render-k8s --node-pools");
+    Ok(())
+}
+
+/// Point queue_name fields using the `from` scheme at `to`; returns the updated config
+/// (None if nothing changed)
+///
+/// Works on the raw file: legacy queue names don't pass validation, so the typed loader
+/// would refuse the very config this fixes. The result is validated before it's returned.
+fn migrate_queue_names(content: &str, from: &str, to: &str) -> Result<Option<LanguagesFile>> {
+    let mut raw: serde_json::Value = serde_json::from_str(content).context("Failed to parse config/languages.json")?;
+    let mut changed = false;
+    for language in raw["languages"].as_array_mut().into_iter().flatten() {
+        let name = language["name"].as_str().unwrap_or_default().to_string();
+        if let Some(queue) = language["queue_name"].as_str().filter(|queue| queue.starts_with(from)) {
+            println!("  📝 {}: queue_name {} → {}{}", name, queue, to, name);
+            language["queue_name"] = json!(format!("{}{}", to, name));
+            changed = true;
+        }
+    }
+    if !changed {
+        return Ok(None);
+    }
+    LanguagesFile::parse(&raw.to_string()).map(Some).map_err(|e| anyhow::anyhow!(e))
+}
+
+/// Move jobs queued under one key-naming scheme to another and update the config
+pub async fn migrate_queues(redis_url: &str, from: &str, to: &str, dry_run: bool) -> Result<()> {
+    if from == to {
+        bail!("--from and --to are the same ({})", from);
+    }
+    println!("🔀 Migrating queues {}* → {}*{}\n", from, to, if dry_run { " (dry run)" } else { "" });
+
+    // Validate the new config before touching Redis
+    let config = match fs::read_to_string(DEFAULT_CONFIG_PATH) {
+        Ok(content) => migrate_queue_names(&content, from, to)?,
+        Err(_) => None,
+    };
+
+    let client = ::redis::Client::open(redis_url)
+        .with_context(|| format!("Invalid Redis URL: {}", optimus_common::secrets::redact_url(redis_url)))?;
+    let mut conn = ::redis::aio::ConnectionManager::new(client)
+        .await
+        .with_context(|| format!("Failed to connect to Redis at {}", optimus_common::secrets::redact_url(redis_url)))?;
+
+    // Every built-in language, plus any the config knows about
+    let mut names: Vec<String> = Language::all_variants().iter().map(|l| l.to_string()).collect();
+    names.extend(load_languages_config().map(|c| c.languages.into_iter().map(|l| l.name).collect::<Vec<_>>()).unwrap_or_default());
+    if let Some(config) = &config {
+        names.extend(config.languages.iter().map(|l| l.name.clone()));
+    }
+    names.sort();
+    names.dedup();
+
+    let mut moved_total = 0;
+    for name in &names {
+        for suffix in redis::QUEUE_SUFFIXES {
+            let (source, target) = (format!("{}{}{}", from, name, suffix), format!("{}{}{}", to, name, suffix));
+            let pending: i64 = ::redis::AsyncCommands::llen(&mut conn, &source).await?;
+            if pending == 0 {
+                continue;
+            }
+            let moved = if dry_run { pending } else { redis::move_queue(&mut conn, &source, &target).await? };
+            println!("  📦 {} → {}: {} job(s)", source, target, moved);
+            moved_total += moved;
+        }
+    }
+
+    match config {
+        Some(config) if !dry_run => {
+            save_languages_config(&config)?;
+            println!("  ✅ Updated {}", DEFAULT_CONFIG_PATH);
+        }
+        Some(_) => println!("  ℹ️  Would update {}", DEFAULT_CONFIG_PATH),
+        None => println!("  ℹ️  No queue_name in {} uses {}", DEFAULT_CONFIG_PATH, from),
+    }
+
+    if dry_run {
+        println!("\n✅ {} job(s) would move", moved_total);
+    } else {
+        println!("\n✅ Moved {} job(s)", moved_total);
+    }
+    Ok(())
+}
+
+pub async fn export_results(redis_url: &str, since: &str, format: &str, out: &Path) -> Result<()> {
+    let window_secs = export::parse_window(since)?;
+    if window_secs > redis::RESULT_TTL_SECS {
+        println!("⚠️  Results expire after {}h; only those are exported", redis::RESULT_TTL_SECS / 3600);
+    }
+    // Encrypted results need the same keys the workers use
+    optimus_common::crypto::init_from_env().map_err(|e| anyhow::anyhow!(e))?;
+
+    let client = ::redis::Client::open(redis_url)
+        .with_context(|| format!("Invalid Redis URL: {}", optimus_common::secrets::redact_url(redis_url)))?;
+    let mut conn = ::redis::aio::ConnectionManager::new(client)
+        .await
+        .with_context(|| format!("Failed to connect to Redis at {}", optimus_common::secrets::redact_url(redis_url)))?;
+
+    if let Some(parent) = out.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    let mut writer = export::ExportWriter::create(format, out)?;
+
+    println!("📤 Exporting results from the last {} to {}\n", since, out.display());
+    let now = chrono::Utc::now();
+    let mut verdicts: std::collections::BTreeMap<String, u64> = std::collections::BTreeMap::new();
+    let mut cursor = 0;
+    loop {
+        let (next, page) = redis::scan_results(&mut conn, cursor).await?;
+        // Deleted jobs are left out, as the API hides them
+        let ids: Vec<_> = page.iter().map(|(result, _)| result.job_id).collect();
+        let deleted = redis::deleted_jobs(&mut conn, &ids).await?;
+        let page: Vec<_> = page
+            .into_iter()
+            .filter(|(result, _)| !deleted.contains(&result.job_id))
+            .filter_map(|(result, ttl)| export::completed_within(ttl, window_secs, now).map(|completed_at| (result, completed_at)))
+            .collect();
+
+        // Languages come from the job copies that haven't expired yet
+        let languages: Vec<Option<String>> = if page.is_empty() {
+            Vec::new()
+        } else {
+            let keys: Vec<String> = page.iter().map(|(result, _)| redis::job_key(&result.job_id)).collect();
+            let jobs: Vec<Option<String>> = ::redis::cmd("MGET").arg(&keys).query_async(&mut conn).await?;
+            jobs.iter().map(|job| job.as_deref().and_then(|j| redis::decode_job(j).ok()).map(|j| j.language.to_string())).collect()
+        };
+
+        for ((result, completed_at), language) in page.iter().zip(languages) {
+            let row = export::export_row(result, language, completed_at.map(|t| t.to_rfc3339()));
+            *verdicts.entry(row["overall_status"].as_str().unwrap_or_default().to_string()).or_default() += 1;
+            writer.write(row)?;
+        }
+
+        cursor = next;
+        if cursor == 0 {
+            break;
+        }
+    }
+    writer.finish()?;
+
+    for (verdict, count) in &verdicts {
+        println!("  {:<20} {}", verdict, count);
+    }
+    println!("\n✅ Exported {} result(s)", verdicts.values().sum::<u64>());
+    Ok(())
+}
+
+pub async fn drain_worker(redis_url: &str, worker_id: &str, cancel: bool) -> Result<()> {
+    let client = ::redis::Client::open(redis_url)
+        .with_context(|| format!("Invalid Redis URL: {}", optimus_common::secrets::redact_url(redis_url)))?;
+    let mut conn = ::redis::aio::ConnectionManager::new(client)
+        .await
+        .with_context(|| format!("Failed to connect to Redis at {}", optimus_common::secrets::redact_url(redis_url)))?;
+
+    if cancel {
+        if redis::cancel_worker_drain(&mut conn, worker_id).await? {
+            println!("✅ Drain request for '{}' withdrawn", worker_id);
+        } else {
+            println!("ℹ️  No drain request for '{}'", worker_id);
+        }
+        return Ok(());
+    }
+
+    if !redis::request_worker_drain(&mut conn, worker_id).await? {
+        bail!("No live worker '{}' (see `optimus-cli top` or GET /admin/workers)", worker_id);
+    }
+    println!("🚰 Draining '{}': it stops taking jobs within ~10s and exits once its running jobs finish", worker_id);
+    println!("   It clears the request on exit (or it expires in {}h); `--cancel` withdraws it", redis::DRAIN_REQUEST_TTL_SECS / 3600);
+    Ok(())
+}
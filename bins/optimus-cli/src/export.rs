@@ -0,0 +1,290 @@
+// `optimus-cli export-results` - result rows and the files they are written to
+// One flat row per result (verdicts, scores, timings; never program output), written as
+// JSON lines or as a Parquet file with a fixed schema, so every export reads the same
+// in pandas, DuckDB or Spark whatever the results in it.
+
+use anyhow::{bail, Context, Result};
+use arrow_array::RecordBatch;
+use arrow_schema::{DataType, Field, Fields, Schema, SchemaRef, TimeUnit};
+use optimus_common::redis;
+use optimus_common::types::ExecutionResult;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use serde_json::{json, Value};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Rows buffered per Parquet record batch
+const PARQUET_BATCH_ROWS: usize = 1024;
+
+/// Parse a window like `90s`, `30m`, `12h` or `7d` into seconds
+pub fn parse_window(window: &str) -> Result<u64> {
+    let window = window.trim();
+    let split = window.find(|c: char| !c.is_ascii_digit()).unwrap_or(window.len());
+    let (amount, unit) = window.split_at(split);
+    let amount: u64 = amount.parse().with_context(|| format!("Invalid window '{}' (expected e.g. 30m, 12h, 7d)", window))?;
+    let unit_secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "" | "d" => 86400,
+        _ => bail!("Invalid window unit '{}' (expected s, m, h or d)", unit),
+    };
+    Ok(amount.saturating_mul(unit_secs))
+}
+
+/// When a result with `ttl` seconds left completed, if that is within the last
+/// `window_secs`: Some(None) for results without an expiry (their age is unknown, so they
+/// are always exported), None for results outside the window
+///
+/// Results carry no timestamp; their age follows from the remaining TTL.
+pub fn completed_within(ttl: i64, window_secs: u64, now: chrono::DateTime<chrono::Utc>) -> Option<Option<chrono::DateTime<chrono::Utc>>> {
+    if ttl < 0 {
+        return Some(None);
+    }
+    let age = redis::RESULT_TTL_SECS.saturating_sub(ttl as u64);
+    (age <= window_secs).then(|| Some(now - chrono::Duration::seconds(age as i64)))
+}
+
+/// One flat export row: verdicts, scores and timings, without program output
+pub fn export_row(result: &ExecutionResult, language: Option<String>, completed_at: Option<String>) -> Value {
+    let mut status_counts = serde_json::Map::new();
+    for test in &result.results {
+        let status = serde_json::to_value(test.status).ok().and_then(|s| s.as_str().map(str::to_string)).unwrap_or_default();
+        let count = status_counts.get(&status).and_then(|c| c.as_u64()).unwrap_or(0);
+        status_counts.insert(status, json!(count + 1));
+    }
+    let timings = result.timings.as_ref();
+    let tests: Vec<_> = result
+        .results
+        .iter()
+        .map(|t| json!({
+            "test_id": t.test_id,
+            "status": t.status,
+            "execution_time_ms": t.execution_time_ms,
+            "cpu_time_ms": t.cpu_time_ms,
+            "memory_kb": t.memory_kb,
+        }))
+        .collect();
+    json!({
+        "job_id": result.job_id,
+        "language": language,
+        "overall_status": result.overall_status,
+        "score": result.score,
+        "max_score": result.max_score,
+        "points": result.points.as_ref().map(|p| p.points),
+        "test_count": result.results.len(),
+        "test_statuses": status_counts,
+        "max_test_time_ms": result.results.iter().map(|t| t.execution_time_ms).max(),
+        "total_test_time_ms": result.results.iter().map(|t| t.execution_time_ms).sum::<u64>(),
+        "queue_wait_ms": timings.and_then(|t| t.queue_wait_ms),
+        "compile_ms": timings.and_then(|t| t.compile_ms),
+        "execution_ms": timings.map(|t| t.total_execution_ms),
+        "evaluation_ms": timings.map(|t| t.evaluation_ms),
+        "persist_ms": timings.and_then(|t| t.persist_ms),
+        "worker_id": result.worker_id,
+        "judge_env_version": result.judge_env_version,
+        "completed_at": completed_at,
+        "tests": tests,
+    })
+}
+
+/// Parquet schema of `export_row`
+pub fn parquet_schema() -> SchemaRef {
+    let nullable = |name: &str, data_type: DataType| Field::new(name, data_type, true);
+    let required = |name: &str, data_type: DataType| Field::new(name, data_type, false);
+    let test = Fields::from(vec![
+        required("test_id", DataType::UInt32),
+        required("status", DataType::Utf8),
+        required("execution_time_ms", DataType::UInt64),
+        nullable("cpu_time_ms", DataType::UInt64),
+        nullable("memory_kb", DataType::UInt64),
+    ]);
+    let status_count = Fields::from(vec![required("keys", DataType::Utf8), required("values", DataType::UInt64)]);
+    Arc::new(Schema::new(vec![
+        required("job_id", DataType::Utf8),
+        nullable("language", DataType::Utf8),
+        required("overall_status", DataType::Utf8),
+        required("score", DataType::UInt32),
+        required("max_score", DataType::UInt32),
+        nullable("points", DataType::Float64),
+        required("test_count", DataType::UInt64),
+        required(
+            "test_statuses",
+            DataType::Map(Arc::new(required("entries", DataType::Struct(status_count))), false),
+        ),
+        nullable("max_test_time_ms", DataType::UInt64),
+        required("total_test_time_ms", DataType::UInt64),
+        nullable("queue_wait_ms", DataType::UInt64),
+        nullable("compile_ms", DataType::UInt64),
+        nullable("execution_ms", DataType::UInt64),
+        nullable("evaluation_ms", DataType::UInt64),
+        nullable("persist_ms", DataType::UInt64),
+        nullable("worker_id", DataType::Utf8),
+        nullable("judge_env_version", DataType::UInt32),
+        nullable("completed_at", DataType::Timestamp(TimeUnit::Microsecond, Some("+00:00".into()))),
+        required(
+            "tests",
+            DataType::List(Arc::new(required("item", DataType::Struct(test)))),
+        ),
+    ]))
+}
+
+/// Where export rows go, in the format `--format` chose
+pub enum ExportWriter {
+    Jsonl(BufWriter<File>),
+    Parquet {
+        writer: Box<ArrowWriter<File>>,
+        schema: SchemaRef,
+        rows: Vec<Value>,
+    },
+}
+
+impl ExportWriter {
+    pub fn create(format: &str, out: &Path) -> Result<Self> {
+        let file = File::create(out).with_context(|| format!("Failed to create {}", out.display()))?;
+        match format {
+            "jsonl" => Ok(Self::Jsonl(BufWriter::new(file))),
+            "parquet" => {
+                let schema = parquet_schema();
+                let properties = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+                let writer = Box::new(ArrowWriter::try_new(file, schema.clone(), Some(properties))?);
+                Ok(Self::Parquet { writer, schema, rows: Vec::with_capacity(PARQUET_BATCH_ROWS) })
+            }
+            _ => bail!("Unsupported format '{}' (supported: jsonl, parquet)", format),
+        }
+    }
+
+    pub fn write(&mut self, row: Value) -> Result<()> {
+        match self {
+            Self::Jsonl(writer) => {
+                serde_json::to_writer(&mut *writer, &row)?;
+                writer.write_all(b"\n")?;
+            }
+            Self::Parquet { writer, schema, rows } => {
+                rows.push(row);
+                if rows.len() >= PARQUET_BATCH_ROWS {
+                    writer.write(&record_batch(schema, rows)?)?;
+                    rows.clear();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn finish(self) -> Result<()> {
+        match self {
+            Self::Jsonl(mut writer) => writer.flush()?,
+            Self::Parquet { mut writer, schema, rows } => {
+                if !rows.is_empty() {
+                    writer.write(&record_batch(&schema, &rows)?)?;
+                }
+                writer.close()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Rows as one record batch of `schema`
+fn record_batch(schema: &SchemaRef, rows: &[Value]) -> Result<RecordBatch> {
+    let mut decoder = arrow_json::ReaderBuilder::new(schema.clone()).with_batch_size(rows.len().max(1)).build_decoder()?;
+    decoder.serialize(rows)?;
+    decoder.flush()?.context("no rows to write")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::UInt64Type;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    fn result() -> ExecutionResult {
+        serde_json::from_value(json!({
+            "job_id": "6f1c1b7e-7d5e-4d43-9a57-2d7f3c0f4a11",
+            "overall_status": "partially_passed",
+            "score": 10,
+            "max_score": 20,
+            "worker_id": "worker-1",
+            "results": [
+                {"test_id": 1, "status": "passed", "stdout": "secret output", "stderr": "", "execution_time_ms": 12, "memory_kb": 9420},
+                {"test_id": 2, "status": "failed", "stdout": "", "stderr": "", "execution_time_ms": 30}
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_since_window() {
+        assert_eq!(parse_window("90s").unwrap(), 90);
+        assert_eq!(parse_window("30m").unwrap(), 1800);
+        assert_eq!(parse_window("12h").unwrap(), 43_200);
+        assert_eq!(parse_window("7d").unwrap(), 604_800);
+        assert_eq!(parse_window("2").unwrap(), 172_800);
+        assert!(parse_window("7w").is_err());
+        assert!(parse_window("h").is_err());
+
+        let now = chrono::Utc::now();
+        let full = redis::RESULT_TTL_SECS as i64;
+        // Stored 10 minutes ago: inside a 1h window, outside a 5m one
+        let completed = completed_within(full - 600, 3600, now).unwrap().unwrap();
+        assert_eq!(completed, now - chrono::Duration::seconds(600));
+        assert_eq!(completed_within(full - 600, 300, now), None);
+        assert!(completed_within(full - 300, 300, now).is_some(), "the window's edge is inside");
+        // No expiry: age unknown, always exported
+        assert_eq!(completed_within(-1, 60, now), Some(None));
+    }
+
+    #[test]
+    fn test_export_row_shape() {
+        let row = export_row(&result(), Some("python".to_string()), Some("2026-01-01T12:00:00+00:00".to_string()));
+        let mut keys: Vec<&str> = row.as_object().unwrap().keys().map(String::as_str).collect();
+        keys.sort_unstable();
+        let mut columns: Vec<String> = parquet_schema().fields().iter().map(|f| f.name().clone()).collect();
+        columns.sort_unstable();
+        assert_eq!(keys, columns, "the row and the Parquet schema have the same columns");
+
+        assert_eq!(row["overall_status"], "partially_passed");
+        assert_eq!(row["test_count"], 2);
+        assert_eq!(row["test_statuses"], json!({"passed": 1, "failed": 1}));
+        assert_eq!((row["max_test_time_ms"].clone(), row["total_test_time_ms"].clone()), (json!(30), json!(42)));
+        assert_eq!(row["execution_ms"], Value::Null);
+        assert_eq!(
+            row["tests"][0],
+            json!({"test_id": 1, "status": "passed", "execution_time_ms": 12, "cpu_time_ms": null, "memory_kb": 9420})
+        );
+        assert!(!row.to_string().contains("secret output"), "program output is never exported");
+    }
+
+    #[test]
+    fn test_parquet_round_trip() {
+        let path = std::env::temp_dir().join(format!("optimus-export-{}.parquet", unique_suffix()));
+        let mut writer = ExportWriter::create("parquet", &path).unwrap();
+        for _ in 0..PARQUET_BATCH_ROWS + 1 {
+            writer.write(export_row(&result(), None, Some("2026-01-01T12:00:00+00:00".to_string()))).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap()).unwrap().build().unwrap();
+        let batches: Vec<RecordBatch> = reader.map(Result::unwrap).collect();
+        assert_eq!(batches.iter().map(RecordBatch::num_rows).sum::<usize>(), PARQUET_BATCH_ROWS + 1);
+        let batch = &batches[0];
+        assert_eq!(batch.schema().fields(), parquet_schema().fields());
+        let total = batch.column_by_name("total_test_time_ms").unwrap().as_primitive::<UInt64Type>();
+        assert_eq!(total.value(0), 42);
+        assert!(batch.column_by_name("language").unwrap().is_null(0));
+        let tests = batch.column_by_name("tests").unwrap().as_list::<i32>();
+        assert_eq!(tests.value(0).len(), 2);
+        std::fs::remove_file(path).unwrap();
+
+        assert!(ExportWriter::create("csv", &std::env::temp_dir().join("optimus-export.csv")).is_err());
+    }
+
+    fn unique_suffix() -> String {
+        format!("{}-{}", std::process::id(), chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default())
+    }
+}
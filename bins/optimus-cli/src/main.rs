@@ -1,12 +1,20 @@
 mod commands;
+mod export;
+mod top;
 
 use clap::{Parser, Subcommand};
 use anyhow::Result;
+use optimus_common::redis;
 
 #[derive(Parser)]
 #[command(name = "optimus-cli")]
 #[command(about = "Optimus CLI - Manage languages, deployments, and configurations", long_about = None)]
 struct Cli {
+    /// Config profile: overlays config/languages.{env}.json on config/languages.json
+    /// (commands that change the config always write the base file)
+    #[arg(long, global = true, env = optimus_common::language_config::PROFILE_ENV_VAR, value_parser = optimus_common::language_config::parse_profile)]
+    env: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -35,7 +43,13 @@ enum Commands {
         #[arg(short, long)]
         command: Option<String>,
 
-        /// Queue name (defaults to optimus:queue:{language})
+        /// Compile command for compiled languages, run once per job with the source in /code
+        /// (e.g., "g++ -O2 /code/main.cpp -o /code/main"); omit for interpreted languages.
+        /// Repeat for a multi-stage build, run in order (e.g., assemble, then link)
+        #[arg(long)]
+        compile: Vec<String>,
+
+        /// Queue name (defaults to, and must be, optimus:queue:{language})
         #[arg(short, long)]
         queue: Option<String>,
 
@@ -45,7 +59,11 @@ enum Commands {
 
         /// CPU limit
         #[arg(long, default_value = "0.5")]
-        cpu: f32,
+        cpu: f64,
+
+        /// Factor applied to every job's time limit (e.g., 3 for slow interpreters)
+        #[arg(long, default_value = "1.0")]
+        time_multiplier: f64,
 
         /// Skip Docker image build
         #[arg(long)]
@@ -75,15 +93,140 @@ enum Commands {
         /// Skip build cache
         #[arg(long, default_value = "false")]
         no_cache: bool,
+
+        /// Target platforms, comma-separated (e.g. linux/amd64,linux/arm64); overrides the
+        /// language's `platforms` in config/languages.json
+        #[arg(long)]
+        platform: Option<String>,
+
+        /// Push the image (required for multi-arch builds) as the configured `image`
+        #[arg(long)]
+        push: bool,
     },
 
     /// Render Kubernetes manifests from templates
-    RenderK8s,
+    RenderK8s {
+        /// Pin workers to the per-language node groups that gen-infra creates
+        #[arg(long)]
+        node_pools: bool,
+    },
+
+    /// Pre-pull all configured judge images so new nodes don't cold-pull on the first job
+    PullImages {
+        /// Images pulled at the same time
+        #[arg(short, long, default_value = "4")]
+        parallel: usize,
+
+        /// Build images that can't be pulled from a registry
+        #[arg(long)]
+        build_missing: bool,
+
+        /// Write k8s/image-prepuller.yaml (a DaemonSet pulling the images on every node) instead
+        #[arg(long)]
+        daemonset: bool,
+    },
+
+    /// Generate infrastructure-as-code (Redis, node groups, registries) from the language config
+    GenInfra {
+        /// Cloud provider
+        #[arg(long, value_parser = ["aws"])]
+        provider: String,
+    },
+
+    /// Move queued jobs from one queue key-naming scheme to another (e.g. the legacy
+    /// jobs:{language} to optimus:queue:{language}) and update queue_name in the config
+    MigrateQueues {
+        /// Prefix the jobs are queued under now
+        #[arg(long, default_value = redis::LEGACY_QUEUE_PREFIX)]
+        from: String,
+
+        /// Prefix to move them to (the queue prefix under OPTIMUS_KEY_PREFIX)
+        #[arg(long, default_value_t = format!("{}:", redis::namespaced(redis::QUEUE_PREFIX)))]
+        to: String,
+
+        /// Redis connection URL [default: REDIS_URL or REDIS_URL_FILE, else redis://127.0.0.1:6379]
+        #[arg(long)]
+        redis_url: Option<String>,
+
+        /// Only report what would move
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Dump stored results for offline analysis of verdicts and timings
+    ExportResults {
+        /// Only results completed within this window (e.g. 30m, 12h, 7d)
+        #[arg(long, default_value = "1d")]
+        since: String,
+
+        /// Output format
+        #[arg(long, default_value = "jsonl", value_parser = ["jsonl", "parquet"])]
+        format: String,
+
+        /// File to write
+        #[arg(long)]
+        out: std::path::PathBuf,
+
+        /// Redis connection URL [default: REDIS_URL or REDIS_URL_FILE, else redis://127.0.0.1:6379]
+        #[arg(long)]
+        redis_url: Option<String>,
+    },
+
+    /// Manage individual workers
+    Worker {
+        #[command(subcommand)]
+        action: WorkerAction,
+    },
+
+    /// Live dashboard of queues, workers and verdicts
+    Top {
+        /// Redis connection URL [default: REDIS_URL or REDIS_URL_FILE, else redis://127.0.0.1:6379]
+        #[arg(long)]
+        redis_url: Option<String>,
+
+        /// Refresh interval in seconds
+        #[arg(short, long, default_value = "2")]
+        interval: u64,
+    },
+}
+
+#[derive(Subcommand)]
+enum WorkerAction {
+    /// Stop a worker taking jobs; it exits once its running jobs finish
+    Drain {
+        /// Worker id, as listed by GET /admin/workers (the pod name in Kubernetes)
+        worker_id: String,
+
+        /// Withdraw an earlier drain request instead
+        #[arg(long)]
+        cancel: bool,
+
+        /// Redis connection URL [default: REDIS_URL or REDIS_URL_FILE, else redis://127.0.0.1:6379]
+        #[arg(long)]
+        redis_url: Option<String>,
+    },
+}
+
+const DEFAULT_REDIS_URL: &str = "redis://127.0.0.1:6379";
+
+/// The Redis URL from --redis-url, else REDIS_URL as the API and workers read it (a plain
+/// value, REDIS_URL_FILE or a secret:// reference)
+///
+/// Also resolves the other secret settings (encryption keys for export-results).
+async fn redis_url_or_env(flag: Option<String>) -> Result<String> {
+    optimus_common::secrets::init().await.map_err(|e| anyhow::anyhow!(e))?;
+    match flag {
+        Some(url) => optimus_common::secrets::resolve(&url).await.map_err(|e| anyhow::anyhow!(e)),
+        None => Ok(optimus_common::secrets::var("REDIS_URL")
+            .map_err(|e| anyhow::anyhow!(e))?
+            .unwrap_or_else(|| DEFAULT_REDIS_URL.to_string())),
+    }
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
+    let profile = cli.env.as_deref().filter(|p| !p.is_empty());
 
     match cli.command {
         Commands::AddLang {
@@ -92,9 +235,11 @@ async fn main() -> Result<()> {
             version,
             base_image,
             command,
+            compile,
             queue,
             memory,
             cpu,
+            time_multiplier,
             skip_docker,
         } => {
             commands::add_language(
@@ -103,9 +248,11 @@ async fn main() -> Result<()> {
                 &version,
                 base_image.as_deref(),
                 command.as_deref(),
+                &compile,
                 queue.as_deref(),
                 memory,
                 cpu,
+                time_multiplier,
                 !skip_docker,
             ).await?;
         }
@@ -113,13 +260,39 @@ async fn main() -> Result<()> {
             commands::remove_language(&name, yes).await?;
         }
         Commands::ListLangs => {
-            commands::list_languages().await?;
+            commands::list_languages(profile).await?;
+        }
+        Commands::BuildImage { name, no_cache, platform, push } => {
+            commands::build_docker_image(&name, no_cache, platform.as_deref(), push, profile).await?;
+        }
+        Commands::RenderK8s { node_pools } => {
+            commands::render_k8s_manifests(profile, node_pools).await?;
+        }
+        Commands::PullImages { parallel, build_missing, daemonset } => {
+            commands::pull_images(parallel, build_missing, daemonset, profile).await?;
+        }
+        Commands::GenInfra { provider } => {
+            commands::gen_infra(&provider, profile).await?;
+        }
+        Commands::MigrateQueues { from, to, redis_url, dry_run } => {
+            let redis_url = redis_url_or_env(redis_url).await?;
+            commands::migrate_queues(&redis_url, &from, &to, dry_run).await?;
+        }
+        Commands::ExportResults { since, format, out, redis_url } => {
+            let redis_url = redis_url_or_env(redis_url).await?;
+            commands::export_results(&redis_url, &since, &format, &out).await?;
         }
-        Commands::BuildImage { name, no_cache } => {
-            commands::build_docker_image(&name, no_cache).await?;
+        Commands::Worker { action: WorkerAction::Drain { worker_id, cancel, redis_url } } => {
+            let redis_url = redis_url_or_env(redis_url).await?;
+            commands::drain_worker(&redis_url, &worker_id, cancel).await?;
         }
-        Commands::RenderK8s => {
-            commands::render_k8s_manifests().await?;
+        Commands::Top { redis_url, interval } => {
+            let redis_url = redis_url_or_env(redis_url).await?;
+            let requirable = commands::requirable_labels(profile).unwrap_or_else(|e| {
+                eprintln!("⚠️  Could not read languages config, requirement queues not shown: {}", e);
+                Default::default()
+            });
+            top::run_top(&redis_url, std::time::Duration::from_secs(interval.max(1)), requirable).await?;
         }
     }
 
@@ -0,0 +1,324 @@
+// `optimus-cli top` - live terminal dashboard
+// Reads queue depths, worker heartbeats and recent submissions straight from Redis,
+// and follows the completion events workers publish to compute throughput and show
+// the latest verdicts. Requirement queues (`python:gpu`) get a row of their own, for
+// the requirement sets languages.json allows.
+
+use anyhow::{Context, Result};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use futures_util::StreamExt;
+use optimus_common::{capabilities, redis};
+use optimus_common::types::{Language, RecentJob, WorkerHeartbeat};
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table},
+    DefaultTerminal, Frame,
+};
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// Completion events kept for the verdict panel
+const MAX_VERDICTS: usize = 50;
+/// Window used for the jobs/sec figure
+const THROUGHPUT_WINDOW: Duration = Duration::from_secs(60);
+
+struct QueueRow {
+    /// Language, or language and requirement set (`python:gpu`)
+    name: String,
+    queued: i64,
+    retrying: i64,
+    /// None on requirement queues, which share their language's DLQ
+    dead_lettered: Option<i64>,
+}
+
+/// A completion event from optimus:metrics:completions
+struct Verdict {
+    job_id: String,
+    language: String,
+    status: String,
+    execution_time_ms: u64,
+    received_at: Instant,
+}
+
+#[derive(Default)]
+struct Dashboard {
+    /// Capability labels jobs of each language may require (languages.json)
+    requirable: HashMap<Language, Vec<String>>,
+    queues: Vec<QueueRow>,
+    workers: Vec<WorkerHeartbeat>,
+    recent: Vec<RecentJob>,
+    verdicts: VecDeque<Verdict>,
+    last_error: Option<String>,
+    refreshed_at: Option<chrono::DateTime<chrono::Local>>,
+}
+
+impl Dashboard {
+    fn jobs_per_sec(&self) -> f64 {
+        let now = Instant::now();
+        let completed = self
+            .verdicts
+            .iter()
+            .filter(|v| now.duration_since(v.received_at) <= THROUGHPUT_WINDOW)
+            .count();
+        completed as f64 / THROUGHPUT_WINDOW.as_secs_f64()
+    }
+
+    fn push_verdict(&mut self, verdict: Verdict) {
+        self.verdicts.push_front(verdict);
+        self.verdicts.truncate(MAX_VERDICTS);
+    }
+
+    async fn refresh(&mut self, conn: &mut ::redis::aio::ConnectionManager) -> Result<()> {
+        use ::redis::AsyncCommands;
+
+        let mut queues = Vec::new();
+        for language in Language::all_variants() {
+            queues.push(QueueRow {
+                name: language.to_string(),
+                queued: conn.llen(redis::queue_name(language)).await?,
+                retrying: conn.llen(redis::retry_queue_name(language)).await?,
+                dead_lettered: Some(conn.llen(redis::dlq_name(language)).await?),
+            });
+            let requirable = self.requirable.get(language).map_or(&[][..], Vec::as_slice);
+            for requirements in capabilities::satisfiable_sets(requirable) {
+                if requirements.is_empty() {
+                    continue;
+                }
+                let (queue, retry_queue) = redis::requirement_queues(language, &requirements);
+                queues.push(QueueRow {
+                    name: format!("{}:{}", language, requirements.join("+")),
+                    queued: conn.llen(queue).await?,
+                    retrying: conn.llen(retry_queue).await?,
+                    dead_lettered: None,
+                });
+            }
+        }
+        self.queues = queues;
+        self.workers = redis::list_heartbeats(conn).await?;
+        self.recent = redis::get_recent_jobs(conn, 20).await?;
+        self.refreshed_at = Some(chrono::Local::now());
+        Ok(())
+    }
+}
+
+/// Run the dashboard until the user presses q / Esc / Ctrl-C
+///
+/// `requirable` holds each language's requirable capability labels, whose queues get rows.
+pub async fn run_top(redis_url: &str, interval: Duration, requirable: HashMap<Language, Vec<String>>) -> Result<()> {
+    let client = ::redis::Client::open(redis_url)
+        .with_context(|| format!("Invalid Redis URL: {}", optimus_common::secrets::redact_url(redis_url)))?;
+    let mut conn = ::redis::aio::ConnectionManager::new(client.clone())
+        .await
+        .with_context(|| format!("Failed to connect to Redis at {}", optimus_common::secrets::redact_url(redis_url)))?;
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    tokio::spawn(follow_completions(client, tx));
+
+    let mut dashboard = Dashboard { requirable, ..Default::default() };
+    let mut terminal = ratatui::init();
+    let outcome = event_loop(&mut terminal, &mut dashboard, &mut conn, &mut rx, interval).await;
+    ratatui::restore();
+    outcome
+}
+
+async fn event_loop(
+    terminal: &mut DefaultTerminal,
+    dashboard: &mut Dashboard,
+    conn: &mut ::redis::aio::ConnectionManager,
+    rx: &mut mpsc::UnboundedReceiver<Verdict>,
+    interval: Duration,
+) -> Result<()> {
+    let mut next_refresh = Instant::now();
+    loop {
+        if Instant::now() >= next_refresh {
+            dashboard.last_error = dashboard
+                .refresh(conn)
+                .await
+                .err()
+                .map(|e| e.to_string());
+            next_refresh = Instant::now() + interval;
+        }
+        while let Ok(verdict) = rx.try_recv() {
+            dashboard.push_verdict(verdict);
+        }
+
+        terminal.draw(|frame| draw(frame, dashboard))?;
+
+        // Short poll keeps verdicts flowing between data refreshes
+        if event::poll(Duration::from_millis(200))? {
+            if let Event::Key(key) = event::read()? {
+                let ctrl_c = key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+                if key.kind == KeyEventKind::Press
+                    && (matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) || ctrl_c)
+                {
+                    return Ok(());
+                }
+                if key.code == KeyCode::Char('r') {
+                    next_refresh = Instant::now();
+                }
+            }
+        }
+    }
+}
+
+/// Forward completion events to the dashboard; ends when the dashboard exits
+async fn follow_completions(client: ::redis::Client, tx: mpsc::UnboundedSender<Verdict>) {
+    let Ok(conn) = client.get_async_connection().await else {
+        return;
+    };
+    let mut pubsub = conn.into_pubsub();
+    if pubsub
+        .subscribe(redis::completions_channel())
+        .await
+        .is_err()
+    {
+        return;
+    }
+
+    let mut messages = pubsub.on_message();
+    while let Some(msg) = messages.next().await {
+        let Ok(payload) = msg.get_payload::<String>() else {
+            continue;
+        };
+        let Ok(event) = serde_json::from_str::<serde_json::Value>(&payload) else {
+            continue;
+        };
+        let verdict = Verdict {
+            job_id: event["job_id"].as_str().unwrap_or("unknown").to_string(),
+            language: event["language"].as_str().unwrap_or("unknown").to_string(),
+            status: event["status"].as_str().unwrap_or("unknown").to_string(),
+            execution_time_ms: event["execution_time_ms"].as_u64().unwrap_or(0),
+            received_at: Instant::now(),
+        };
+        if tx.send(verdict).is_err() {
+            return;
+        }
+    }
+}
+
+fn status_color(status: &str) -> Color {
+    match status.to_ascii_lowercase().as_str() {
+        "completed" | "passed" => Color::Green,
+        "partiallypassed" | "queued" | "running" | "pending" => Color::Yellow,
+        _ => Color::Red,
+    }
+}
+
+fn header_row(cells: &[&'static str]) -> Row<'static> {
+    Row::new(cells.to_vec()).style(Style::default().add_modifier(Modifier::BOLD))
+}
+
+fn draw(frame: &mut Frame, dashboard: &Dashboard) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(dashboard.queues.len().max(Language::all_variants().len()) as u16 + 3),
+            Constraint::Min(6),
+        ])
+        .split(frame.area());
+    let middle = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+        .split(rows[1]);
+    let bottom = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+        .split(rows[2]);
+
+    // Summary line
+    let busy = dashboard.workers.iter().filter(|w| w.executing).count();
+    let mut summary = format!(
+        " jobs/sec: {:.2}   workers: {} ({} busy)   queued: {}   dlq: {}",
+        dashboard.jobs_per_sec(),
+        dashboard.workers.len(),
+        busy,
+        dashboard.queues.iter().map(|q| q.queued + q.retrying).sum::<i64>(),
+        dashboard.queues.iter().filter_map(|q| q.dead_lettered).sum::<i64>(),
+    );
+    if let Some(at) = dashboard.refreshed_at {
+        summary.push_str(&format!("   updated {}", at.format("%H:%M:%S")));
+    }
+    let mut lines = vec![Line::from(summary)];
+    if let Some(err) = &dashboard.last_error {
+        lines = vec![Line::styled(format!(" Redis error: {}", err), Style::default().fg(Color::Red))];
+    }
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(" optimus top  (q to quit, r to refresh) ")),
+        rows[0],
+    );
+
+    // Queues
+    let queue_rows = dashboard.queues.iter().map(|q| {
+        let dead_lettered = q.dead_lettered.unwrap_or(0);
+        Row::new(vec![
+            Cell::from(q.name.clone()),
+            Cell::from(q.queued.to_string()),
+            Cell::from(q.retrying.to_string()),
+            Cell::from(q.dead_lettered.map_or_else(|| "-".to_string(), |n| n.to_string()))
+                .style(Style::default().fg(if dead_lettered > 0 { Color::Red } else { Color::Reset })),
+        ])
+    });
+    frame.render_widget(
+        Table::new(queue_rows, [Constraint::Min(10), Constraint::Length(8), Constraint::Length(9), Constraint::Length(6)])
+            .header(header_row(&["LANGUAGE", "QUEUED", "RETRYING", "DLQ"]))
+            .block(Block::default().borders(Borders::ALL).title(" Queues ")),
+        middle[0],
+    );
+
+    // Workers
+    let now = chrono::Utc::now();
+    let worker_rows = dashboard.workers.iter().map(|w| {
+        let (state, color) = match (w.draining, w.executing) {
+            (true, _) => ("draining", Color::Magenta),
+            (false, true) => ("executing", Color::Yellow),
+            (false, false) => ("idle", Color::Green),
+        };
+        Row::new(vec![
+            Cell::from(w.worker_id.clone()),
+            Cell::from(w.language.to_string()),
+            Cell::from(state).style(Style::default().fg(color)),
+            Cell::from(format!("{}s ago", (now - w.last_seen).num_seconds().max(0))),
+        ])
+    });
+    frame.render_widget(
+        Table::new(worker_rows, [Constraint::Min(16), Constraint::Length(10), Constraint::Length(10), Constraint::Length(10)])
+            .header(header_row(&["WORKER", "LANGUAGE", "STATE", "SEEN"]))
+            .block(Block::default().borders(Borders::ALL).title(" Workers ")),
+        middle[1],
+    );
+
+    // Recent submissions
+    let recent_rows = dashboard.recent.iter().map(|j| {
+        Row::new(vec![
+            Cell::from(j.job_id.to_string()),
+            Cell::from(j.language.to_string()),
+            Cell::from(j.test_cases.to_string()),
+        ])
+    });
+    frame.render_widget(
+        Table::new(recent_rows, [Constraint::Length(36), Constraint::Length(10), Constraint::Length(6)])
+            .header(header_row(&["JOB", "LANGUAGE", "TESTS"]))
+            .block(Block::default().borders(Borders::ALL).title(" Recent submissions ")),
+        bottom[0],
+    );
+
+    // Verdicts streamed from workers
+    let verdict_rows = dashboard.verdicts.iter().map(|v| {
+        Row::new(vec![
+            Cell::from(v.job_id.clone()),
+            Cell::from(v.language.clone()),
+            Cell::from(v.status.clone()).style(Style::default().fg(status_color(&v.status))),
+            Cell::from(format!("{}ms", v.execution_time_ms)),
+        ])
+    });
+    frame.render_widget(
+        Table::new(verdict_rows, [Constraint::Length(36), Constraint::Length(10), Constraint::Length(10), Constraint::Length(9)])
+            .header(header_row(&["JOB", "LANGUAGE", "STATUS", "TIME"]))
+            .block(Block::default().borders(Borders::ALL).title(" Recent verdicts ")),
+        bottom[1],
+    );
+}
@@ -0,0 +1,185 @@
+// Optimus dispatcher - schedules jobs from one intake queue onto the worker queues
+//
+// Optional: with DISPATCHER_ENABLED=true the API queues every submission on
+// optimus:intake instead of its worker queue, and this process moves them on in the
+// order the policy picks (priority, tenant fairness, per-queue capacity; see policy.rs).
+// Workers are unchanged: they read their queues as before. Retries go straight back to
+// the worker queues.
+//
+// Each move is one atomic step (LREM from the intake + RPUSH onto the queue), so jobs
+// stay on the intake until moved and a crash never loses one; several dispatchers can
+// run side by side.
+
+mod policy;
+
+use optimus_common::redis;
+use policy::Candidate;
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+
+/// Tuning, from the environment
+struct Settings {
+    /// Intake entries considered per round (DISPATCH_WINDOW, default 500)
+    window: usize,
+    /// Pause after a round that moved nothing (DISPATCH_INTERVAL_MS, default 200)
+    interval: Duration,
+    /// Queued jobs per live worker slot (DISPATCH_JOBS_PER_SLOT, default 2)
+    jobs_per_slot: usize,
+    /// Jobs a queue may always hold, even without live workers (DISPATCH_QUEUE_FLOOR, default 10)
+    queue_floor: usize,
+}
+
+impl Settings {
+    fn from_env() -> Self {
+        let var = |name: &str, default: u64| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.trim().parse().ok())
+                .filter(|v: &u64| *v > 0)
+                .unwrap_or(default)
+        };
+        Self {
+            window: var("DISPATCH_WINDOW", 500) as usize,
+            interval: Duration::from_millis(var("DISPATCH_INTERVAL_MS", 200)),
+            jobs_per_slot: var("DISPATCH_JOBS_PER_SLOT", 2) as usize,
+            queue_floor: var("DISPATCH_QUEUE_FLOOR", 10) as usize,
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    dotenvy::dotenv().ok();
+
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info"))
+        )
+        .with_target(false)
+        .init();
+
+    info!("Optimus Dispatcher booting...");
+
+    if let Err(e) = optimus_common::secrets::init().await {
+        error!("❌ FATAL: Failed to resolve secrets: {}", e);
+        std::process::exit(1);
+    }
+
+    // Sealed queue entries are opened to read their scheduling fields, and moved as stored
+    if let Err(e) = optimus_common::crypto::init_from_env() {
+        error!("❌ FATAL: Invalid encryption configuration: {}", e);
+        std::process::exit(1);
+    }
+
+    let redis_url = optimus_common::secrets::var("REDIS_URL")
+        .map_err(|e| anyhow::anyhow!(e))?
+        .unwrap_or_else(|| "redis://127.0.0.1:6379".to_string());
+    let client = ::redis::Client::open(redis_url.as_str())?;
+    let mut conn = optimus_common::retry::connect(&client).await?;
+    info!("Connected to Redis: {}", optimus_common::secrets::redact_url(&redis_url));
+
+    let settings = Settings::from_env();
+    info!(
+        "Dispatching from {} (window={}, jobs_per_slot={}, queue_floor={})",
+        redis::intake_queue_name(), settings.window, settings.jobs_per_slot, settings.queue_floor
+    );
+
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+    loop {
+        let moved = match dispatch_round(&mut conn, &settings).await {
+            Ok(moved) => moved,
+            Err(e) => {
+                warn!(error = %e, "Dispatch round failed");
+                0
+            }
+        };
+        if moved > 0 {
+            debug!(moved, "Dispatched jobs");
+            continue;
+        }
+        tokio::select! {
+            _ = &mut shutdown => break,
+            _ = tokio::time::sleep(settings.interval) => {}
+        }
+    }
+
+    info!("✓ Dispatcher stopped");
+    Ok(())
+}
+
+/// Move what fits from the intake window to the worker queues; returns how many moved
+async fn dispatch_round(
+    conn: &mut ::redis::aio::ConnectionManager,
+    settings: &Settings,
+) -> ::redis::RedisResult<usize> {
+    let entries = redis::peek_intake(conn, settings.window).await?;
+    if entries.is_empty() {
+        return Ok(0);
+    }
+
+    // Unreadable entries would sit at the head forever
+    let mut jobs = Vec::new();
+    for payload in entries {
+        match redis::decode_job(&payload) {
+            Ok(job) if policy::valid_requirements(&job) => jobs.push((payload, job)),
+            Ok(job) => {
+                error!(job_id = %job.id, requirements = ?job.metadata.requirements, "Intake job has invalid requirements");
+                redis::dispatch_entry(conn, &payload, Some(&job), &redis::intake_dlq_name()).await?;
+            }
+            Err(e) => {
+                error!(error = %e, "Unreadable intake entry");
+                redis::dispatch_entry(conn, &payload, None, &redis::intake_dlq_name()).await?;
+            }
+        }
+    }
+
+    // Free room per target queue: capacity from live workers, minus what already waits
+    let heartbeats = redis::list_heartbeats(conn).await?;
+    let mut room: HashMap<String, usize> = HashMap::new();
+    for (_, job) in &jobs {
+        let queue = redis::job_queues(job).0;
+        if room.contains_key(&queue) {
+            continue;
+        }
+        let capacity = policy::queue_capacity(
+            policy::live_slots(&heartbeats, job),
+            settings.jobs_per_slot,
+            settings.queue_floor,
+        );
+        let queued: usize = ::redis::AsyncCommands::llen(conn, &queue).await?;
+        room.insert(queue, capacity.saturating_sub(queued));
+    }
+
+    let candidates: Vec<Candidate> = jobs.iter().map(|(_, job)| Candidate::of(job)).collect();
+    let mut moved = 0;
+    for i in policy::plan(&candidates, room) {
+        let (payload, job) = &jobs[i];
+        if redis::dispatch_entry(conn, payload, Some(job), &candidates[i].queue).await? {
+            debug!(job_id = %job.id, queue = %candidates[i].queue, priority = job.metadata.priority, "Dispatched");
+            moved += 1;
+        }
+    }
+    Ok(moved)
+}
+
+/// Wait for SIGTERM (Kubernetes) or SIGINT (CTRL+C)
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut sigterm = signal(SignalKind::terminate())
+            .expect("failed to install SIGTERM signal handler");
+        tokio::select! {
+            _ = sigterm.recv() => warn!("⚠️  Received SIGTERM - stopping"),
+            _ = tokio::signal::ctrl_c() => warn!("⚠️  Received SIGINT (CTRL+C) - stopping"),
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        tokio::signal::ctrl_c().await.expect("failed to install CTRL+C signal handler");
+        warn!("⚠️  Received CTRL+C - stopping");
+    }
+}
@@ -0,0 +1,158 @@
+// Scheduling policy - which intake entries go to the worker queues, and in what order
+//
+// - Priority: higher `metadata.priority` first.
+// - Fairness: within a priority, tenants take turns (one job each per round), so a
+//   tenant submitting a burst doesn't push everyone else's jobs back.
+// - Capacity: a worker queue is only filled up to a few jobs per live worker slot able
+//   to run it (language and capabilities), with a floor so queues whose workers are
+//   scaled to zero still get the jobs KEDA scales them up for. Jobs past that stay on
+//   the intake, where later, higher-priority jobs can still overtake them.
+
+use optimus_common::capabilities;
+use optimus_common::types::{JobRequest, WorkerHeartbeat};
+use std::collections::HashMap;
+
+/// An intake entry awaiting dispatch
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub priority: u8,
+    pub tenant: String,
+    /// Worker queue the job belongs on
+    pub queue: String,
+}
+
+/// Tenant of jobs submitted without one
+const NO_TENANT: &str = "";
+
+impl Candidate {
+    pub fn of(job: &JobRequest) -> Self {
+        Self {
+            priority: job.metadata.priority,
+            tenant: job.metadata.tenant.clone().unwrap_or_else(|| NO_TENANT.to_string()),
+            queue: optimus_common::redis::job_queues(job).0,
+        }
+    }
+}
+
+/// Indices of `candidates` (in intake order) in dispatch order: priority, then each
+/// tenant's n-th job in round n, then intake order
+pub fn order(candidates: &[Candidate]) -> Vec<usize> {
+    let mut seen: HashMap<(u8, &str), usize> = HashMap::new();
+    let mut keyed: Vec<(u8, usize, usize)> = candidates
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            let round = seen.entry((c.priority, c.tenant.as_str())).or_insert(0);
+            *round += 1;
+            (c.priority, *round, i)
+        })
+        .collect();
+    keyed.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)).then(a.2.cmp(&b.2)));
+    keyed.into_iter().map(|(_, _, i)| i).collect()
+}
+
+/// Indices of the candidates to dispatch now, in order, given the free room per queue
+pub fn plan(candidates: &[Candidate], mut room: HashMap<String, usize>) -> Vec<usize> {
+    order(candidates)
+        .into_iter()
+        .filter(|&i| match room.get_mut(&candidates[i].queue) {
+            Some(free) if *free > 0 => {
+                *free -= 1;
+                true
+            }
+            _ => false,
+        })
+        .collect()
+}
+
+/// Job slots of live workers that read a language's queue for these requirements
+/// (draining workers take no more jobs)
+pub fn live_slots(heartbeats: &[WorkerHeartbeat], job: &JobRequest) -> usize {
+    heartbeats
+        .iter()
+        .filter(|hb| hb.language == job.language && !hb.draining)
+        .filter(|hb| job.metadata.requirements.iter().all(|r| hb.capabilities.contains(r)))
+        .map(|hb| hb.max_parallel_jobs)
+        .sum()
+}
+
+/// Most jobs a worker queue should hold: `per_slot` per live slot, at least `floor`
+pub fn queue_capacity(slots: usize, per_slot: usize, floor: usize) -> usize {
+    slots.saturating_mul(per_slot).max(floor)
+}
+
+/// Whether a label set names a real requirement queue (the dispatcher routes by it)
+pub fn valid_requirements(job: &JobRequest) -> bool {
+    job.metadata.requirements.iter().all(|label| capabilities::check_label(label).is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use optimus_common::types::Language;
+
+    fn candidate(priority: u8, tenant: &str, queue: &str) -> Candidate {
+        Candidate { priority, tenant: tenant.to_string(), queue: queue.to_string() }
+    }
+
+    #[test]
+    fn test_order_priority_then_tenant_turns() {
+        let candidates = [
+            candidate(0, "a", "q"),
+            candidate(0, "a", "q"),
+            candidate(0, "a", "q"),
+            candidate(0, "b", "q"),
+            candidate(5, "c", "q"),
+            candidate(0, "b", "q"),
+        ];
+        // c's priority job first, then a and b alternate, a's surplus last
+        assert_eq!(order(&candidates), [4, 0, 3, 1, 5, 2]);
+    }
+
+    #[test]
+    fn test_plan_respects_room_per_queue() {
+        let candidates = [
+            candidate(0, "a", "python"),
+            candidate(0, "a", "python"),
+            candidate(0, "a", "java"),
+            candidate(0, "b", "python"),
+        ];
+        let room = HashMap::from([("python".to_string(), 2), ("java".to_string(), 0)]);
+        // A full java queue doesn't hold back python jobs behind it
+        assert_eq!(plan(&candidates, room), [0, 3]);
+    }
+
+    #[test]
+    fn test_live_slots_match_language_and_capabilities() {
+        let heartbeat = |language, capabilities: &[&str], slots| WorkerHeartbeat {
+            worker_id: "w".to_string(),
+            language,
+            started_at: chrono::Utc::now(),
+            last_seen: chrono::Utc::now(),
+            executing: false,
+            max_parallel_jobs: slots,
+            draining: false,
+            capabilities: capabilities.iter().map(|c| c.to_string()).collect(),
+            queues: vec![],
+        };
+        let heartbeats = [
+            heartbeat(Language::Python, &["amd64"], 2),
+            heartbeat(Language::Python, &["amd64", "gpu"], 1),
+            heartbeat(Language::Java, &["gpu"], 4),
+        ];
+        let mut job: JobRequest = serde_json::from_value(serde_json::json!({
+            "id": "00000000-0000-0000-0000-000000000001",
+            "language": "python",
+            "source_code": "print(1)",
+            "test_cases": [],
+            "timeout_ms": 1000
+        }))
+        .unwrap();
+        assert_eq!(live_slots(&heartbeats, &job), 3);
+        job.metadata.requirements = vec!["gpu".to_string()];
+        assert_eq!(live_slots(&heartbeats, &job), 1);
+
+        assert_eq!(queue_capacity(3, 2, 10), 10);
+        assert_eq!(queue_capacity(8, 2, 10), 16);
+    }
+}
@@ -0,0 +1,194 @@
+//! Canary Judge Images - Shadow Execution
+//!
+//! **Core Responsibility:**
+//! Validate a new judge image against live traffic before promoting it.
+//! A configured share of jobs is executed a second time on the candidate image
+//! after the primary result has been stored; the candidate's result is kept under
+//! `optimus:shadow:*` and only the comparison is reported (GET /admin/canary/{language}).
+//!
+//! **Configuration** (`config/languages.json`, per language):
+//! `"canary": { "image": "optimus-python:candidate", "percent": 10 }`
+//!
+//! Sampling is derived from the job id, so a retried job makes the same choice.
+//! Shadow runs are best-effort: failures are logged and never affect the user's result.
+
+use crate::config::{CanaryConfig, LanguageConfigManager};
+use crate::engine::DockerEngine;
+use crate::executor;
+use optimus_common::redis;
+use optimus_common::types::{ExecutionResult, JobRequest, ShadowComparison};
+use std::collections::HashMap;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+/// Whether this job falls into the canary sample
+pub fn should_shadow(job: &JobRequest, canary: &CanaryConfig) -> bool {
+    // UUID v4 bits are random, so the bucket is uniform over 0..10000
+    let bucket = (job.id.as_u128() % 10_000) as f64;
+    bucket < canary.percent * 100.0
+}
+
+/// Compare the primary and candidate results test by test
+pub fn compare(
+    job: &JobRequest,
+    primary: &ExecutionResult,
+    candidate: &ExecutionResult,
+    primary_image: &str,
+    candidate_image: &str,
+) -> ShadowComparison {
+    let candidate_status: HashMap<u32, _> = candidate
+        .results
+        .iter()
+        .map(|r| (r.test_id, r.status))
+        .collect();
+    let mismatched_tests = primary
+        .results
+        .iter()
+        .filter(|r| candidate_status.get(&r.test_id) != Some(&r.status))
+        .map(|r| r.test_id)
+        .collect();
+    let total_time = |result: &ExecutionResult| result.results.iter().map(|r| r.execution_time_ms).sum();
+
+    ShadowComparison {
+        job_id: job.id,
+        language: job.language,
+        primary_image: primary_image.to_string(),
+        candidate_image: candidate_image.to_string(),
+        primary_status: primary.overall_status,
+        candidate_status: candidate.overall_status,
+        primary_score: primary.score,
+        candidate_score: candidate.score,
+        max_score: primary.max_score,
+        mismatched_tests,
+        primary_time_ms: total_time(primary),
+        candidate_time_ms: total_time(candidate),
+        compared_at: chrono::Utc::now(),
+    }
+}
+
+/// Re-run the job on the candidate image and record the comparison, if sampled
+pub async fn maybe_shadow(
+    job: &JobRequest,
+    primary: &ExecutionResult,
+    engine: &DockerEngine,
+    config_manager: &LanguageConfigManager,
+    redis_conn: &mut ::redis::aio::ConnectionManager,
+) {
+    let Some(canary) = config_manager.get_canary(&job.language) else { return };
+    if !should_shadow(job, canary) {
+        return;
+    }
+
+    info!(job_id = %job.id, candidate_image = %canary.image, "Shadow-running job on canary image");
+    let candidate = match executor::execute_on_image(job, engine, config_manager, redis_conn, &canary.image, &CancellationToken::new()).await {
+        Ok(result) => result,
+        Err(e) => {
+            warn!(job_id = %job.id, error = %e, "Canary shadow run failed");
+            return;
+        }
+    };
+
+    let primary_image = config_manager.get_image(&job.language).unwrap_or_default();
+    let comparison = compare(job, primary, &candidate, &primary_image, &canary.image);
+    if !comparison.agrees() {
+        warn!(
+            job_id = %job.id,
+            mismatched_tests = ?comparison.mismatched_tests,
+            "Canary image disagrees with primary image"
+        );
+    }
+    if let Err(e) = redis::store_shadow_run(redis_conn, &candidate, &comparison).await {
+        warn!(job_id = %job.id, error = %e, "Failed to store canary comparison");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use optimus_common::types::{JobStatus, Language, TestCase, TestResult, TestStatus};
+    use uuid::Uuid;
+
+    fn job(id: Uuid) -> JobRequest {
+        JobRequest {
+            id,
+            language: Language::Python,
+            source_code: "print(input())".to_string(),
+            test_cases: vec![
+                TestCase { id: 1, input: "a".to_string(), expected_output: "a".to_string(), weight: 5, ..Default::default() },
+                TestCase { id: 2, input: "b".to_string(), expected_output: "b".to_string(), weight: 5, ..Default::default() },
+            ],
+            timeout_ms: 1000,
+            ..Default::default()
+        }
+    }
+
+    fn result(job_id: Uuid, statuses: &[TestStatus], time_ms: u64) -> ExecutionResult {
+        let results: Vec<TestResult> = statuses
+            .iter()
+            .enumerate()
+            .map(|(i, status)| TestResult {
+                test_id: i as u32 + 1,
+                status: *status,
+                stdout: String::new(),
+                stderr: String::new(),
+                execution_time_ms: time_ms,
+                stdout_base64: None,
+                stderr_base64: None,
+                overhead_ms: None,
+                cpu_time_ms: None,
+                wall_time_ms: None,
+                memory_kb: None,
+                failure: None,
+                checker_output: None,
+                sanitizer_report: None,
+                diff: None,
+            })
+            .collect();
+        let score = results.iter().filter(|r| r.status == TestStatus::Passed).count() as u32 * 5;
+        ExecutionResult {
+            job_id,
+            overall_status: if score == 10 { JobStatus::Completed } else { JobStatus::Failed },
+            score,
+            max_score: 10,
+            results,
+            judge_env_version: None,
+            worker_id: None,
+            signature: None,
+            points: None,
+            timings: None,
+            misuse: Vec::new(),
+            effective_timeout_ms: None,
+            subtasks: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_sampling_bounds() {
+        let canary = |percent| CanaryConfig { image: "candidate".to_string(), percent };
+        let one = job(Uuid::new_v4());
+        assert!(!should_shadow(&one, &canary(0.0)));
+        assert!(should_shadow(&one, &canary(100.0)));
+
+        let sampled = (0..2000)
+            .filter(|_| should_shadow(&job(Uuid::new_v4()), &canary(25.0)))
+            .count();
+        assert!((300..700).contains(&sampled), "sampled {} of 2000 at 25%", sampled);
+    }
+
+    #[test]
+    fn test_compare_detects_verdict_changes() {
+        let id = Uuid::new_v4();
+        let job = job(id);
+        let primary = result(id, &[TestStatus::Passed, TestStatus::Passed], 10);
+
+        let same = compare(&job, &primary, &result(id, &[TestStatus::Passed, TestStatus::Passed], 12), "old", "new");
+        assert!(same.agrees());
+        assert_eq!(same.primary_time_ms, 20);
+        assert_eq!(same.candidate_time_ms, 24);
+
+        let changed = compare(&job, &primary, &result(id, &[TestStatus::Passed, TestStatus::TimeLimitExceeded], 10), "old", "new");
+        assert!(!changed.agrees());
+        assert_eq!(changed.mismatched_tests, vec![2]);
+        assert_eq!(changed.candidate_score, 5);
+    }
+}
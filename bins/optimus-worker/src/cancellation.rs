@@ -0,0 +1,162 @@
+//! Job Cancellation
+//!
+//! **Core Responsibility:**
+//! Turn the API's cancellation requests into a `CancellationToken` per running job.
+//!
+//! POST /job/{id}/cancel sets the job's control flag and publishes its id on the cancel
+//! channel. One watcher per worker subscribes to that channel and cancels the matching
+//! token at once. Pub/sub drops messages while disconnected, so the watcher also re-reads
+//! the flags of running jobs every CANCEL_POLL_INTERVAL. Engine code only checks or awaits
+//! the token - compile and exec futures are abandoned as soon as it fires.
+
+use futures_util::StreamExt;
+use optimus_common::redis;
+use optimus_common::retry::Backoff;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Fallback poll of running jobs' cancellation flags
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Cancellation tokens of the jobs running on this worker
+#[derive(Debug, Clone, Default)]
+pub struct Cancellations(Arc<Mutex<HashMap<Uuid, CancellationToken>>>);
+
+impl Cancellations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Token for a job about to run (already cancelled if its flag is set); the job
+    /// stays watched until the returned registration is dropped
+    pub async fn register(
+        &self,
+        redis_conn: &mut ::redis::aio::ConnectionManager,
+        job_id: Uuid,
+    ) -> Registration {
+        let token = CancellationToken::new();
+        self.0.lock().unwrap().insert(job_id, token.clone());
+        match redis::is_job_cancelled(redis_conn, &job_id).await {
+            Ok(true) => token.cancel(),
+            Ok(false) => {}
+            // Proceed on error to avoid false cancellations; the watcher checks again
+            Err(e) => warn!(job_id = %job_id, error = %e, "Failed to check cancellation status"),
+        }
+        Registration { cancellations: self.clone(), job_id, token }
+    }
+
+    fn cancel(&self, job_id: &Uuid) {
+        if let Some(token) = self.0.lock().unwrap().get(job_id) {
+            if !token.is_cancelled() {
+                info!(job_id = %job_id, "Cancellation requested - stopping job");
+                token.cancel();
+            }
+        }
+    }
+
+    fn running(&self) -> Vec<Uuid> {
+        self.0.lock().unwrap().keys().copied().collect()
+    }
+}
+
+/// A running job's token; unregisters the job when dropped
+pub struct Registration {
+    cancellations: Cancellations,
+    job_id: Uuid,
+    token: CancellationToken,
+}
+
+impl Registration {
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        let mut tokens = self.cancellations.0.lock().unwrap_or_else(|e| e.into_inner());
+        tokens.remove(&self.job_id);
+    }
+}
+
+/// Cancel tokens from the cancel channel, polling the flags as a fallback
+pub async fn watch_loop(
+    client: ::redis::Client,
+    mut redis_conn: ::redis::aio::ConnectionManager,
+    cancellations: Cancellations,
+) {
+    let mut backoff = Backoff::new();
+    loop {
+        let subscribed = async {
+            let mut pubsub = client.get_async_connection().await?.into_pubsub();
+            pubsub.subscribe(redis::cancel_channel()).await?;
+            Ok::<_, ::redis::RedisError>(pubsub)
+        };
+        let mut pubsub = match subscribed.await {
+            Ok(pubsub) => {
+                if backoff.success() > 0 {
+                    info!("Cancellation channel resubscribed");
+                }
+                pubsub
+            }
+            Err(e) => {
+                let (delay, log) = backoff.failure();
+                if log {
+                    warn!(error = %e, retry_in_ms = delay.as_millis() as u64, "Failed to subscribe to cancellations");
+                }
+                poll_flags(&mut redis_conn, &cancellations).await;
+                tokio::time::sleep(delay).await;
+                continue;
+            }
+        };
+
+        let mut messages = pubsub.on_message();
+        let mut poll = tokio::time::interval(CANCEL_POLL_INTERVAL);
+        loop {
+            tokio::select! {
+                message = messages.next() => {
+                    let Some(message) = message else { break };
+                    if let Some(job_id) = message.get_payload::<String>().ok().and_then(|id| Uuid::parse_str(&id).ok()) {
+                        cancellations.cancel(&job_id);
+                    }
+                }
+                _ = poll.tick() => poll_flags(&mut redis_conn, &cancellations).await,
+            }
+        }
+        warn!("Cancellation channel disconnected - resubscribing");
+    }
+}
+
+async fn poll_flags(redis_conn: &mut ::redis::aio::ConnectionManager, cancellations: &Cancellations) {
+    let running = cancellations.running();
+    match redis::cancelled_jobs(redis_conn, &running).await {
+        Ok(cancelled) => cancelled.iter().for_each(|job_id| cancellations.cancel(job_id)),
+        Err(e) => warn!(error = %e, "Failed to poll cancellation flags"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel_reaches_registered_token_only() {
+        let cancellations = Cancellations::new();
+        let (job_id, other) = (Uuid::new_v4(), Uuid::new_v4());
+        let token = CancellationToken::new();
+        cancellations.0.lock().unwrap().insert(job_id, token.clone());
+        let registration = Registration { cancellations: cancellations.clone(), job_id, token };
+
+        cancellations.cancel(&other);
+        assert!(!registration.token().is_cancelled());
+        cancellations.cancel(&job_id);
+        assert!(registration.token().is_cancelled());
+
+        drop(registration);
+        assert!(cancellations.running().is_empty());
+    }
+}
@@ -0,0 +1,119 @@
+//! Output Capture - Bounded Buffers for Container Output
+//!
+//! **Core Responsibility:**
+//! Keep what a container prints without letting a runaway program exhaust the worker's
+//! own memory.
+//!
+//! A program printing in a loop until it times out can write gigabytes, and the worker
+//! used to hold all of it. Each stream now keeps at most `engine_max_output_bytes`
+//! (safety limits) and counts the rest:
+//!
+//! - stdout keeps its start: that is what is compared, and an output already longer than
+//!   any expected output is wrong however it ends
+//! - stderr keeps its end: the runner's report is printed last, and so is the useful part
+//!   of a traceback
+//!
+//! The totals come back with the test's output; a test whose stdout was cut short fails.
+
+/// Which part of a stream is kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Keep {
+    Head,
+    Tail,
+}
+
+/// A stream's bytes, capped, with a count of everything written
+#[derive(Debug)]
+pub struct CappedBuffer {
+    keep: Keep,
+    cap: usize,
+    buf: Vec<u8>,
+    total: u64,
+}
+
+impl CappedBuffer {
+    /// Keeps the first `cap` bytes
+    pub fn head(cap: usize) -> Self {
+        Self { keep: Keep::Head, cap, buf: Vec::new(), total: 0 }
+    }
+
+    /// Keeps the last `cap` bytes
+    pub fn tail(cap: usize) -> Self {
+        Self { keep: Keep::Tail, cap, buf: Vec::new(), total: 0 }
+    }
+
+    /// Buffers for a test's stdout and stderr under the current safety limits
+    pub fn for_test() -> (Self, Self) {
+        let cap = optimus_common::limits::current().engine_max_output_bytes;
+        (Self::head(cap), Self::tail(cap))
+    }
+
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.total += bytes.len() as u64;
+        match self.keep {
+            Keep::Head => {
+                let room = self.cap.saturating_sub(self.buf.len());
+                self.buf.extend_from_slice(&bytes[..bytes.len().min(room)]);
+            }
+            Keep::Tail => {
+                self.buf.extend_from_slice(&bytes[bytes.len().saturating_sub(self.cap)..]);
+                // Dropped in batches, so each byte moves at most once
+                if self.buf.len() > self.cap.saturating_mul(2) {
+                    self.buf.drain(..self.buf.len() - self.cap);
+                }
+            }
+        }
+    }
+
+    /// The kept bytes and the total written
+    ///
+    /// A cut tail starts at the next character, not in the middle of one.
+    pub fn finish(mut self) -> (Vec<u8>, u64) {
+        if self.keep == Keep::Tail && self.buf.len() > self.cap {
+            self.buf.drain(..self.buf.len() - self.cap);
+        }
+        if self.keep == Keep::Tail && (self.buf.len() as u64) < self.total {
+            let partial = self.buf.iter().take(3).take_while(|b| (**b & 0xC0) == 0x80).count();
+            self.buf.drain(..partial);
+        }
+        (self.buf, self.total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_head_keeps_the_start() {
+        let mut stdout = CappedBuffer::head(5);
+        stdout.push(b"abc");
+        stdout.push(b"defgh");
+        stdout.push(b"ijk");
+        assert_eq!(stdout.finish(), (b"abcde".to_vec(), 11));
+    }
+
+    #[test]
+    fn test_tail_keeps_the_end() {
+        let mut stderr = CappedBuffer::tail(4);
+        for chunk in [&b"ab"[..], b"cdef", b"g", b"hijklmnop", b"q"] {
+            stderr.push(chunk);
+            assert!(stderr.buf.len() <= 8);
+        }
+        assert_eq!(stderr.finish(), (b"nopq".to_vec(), 17));
+
+        // Under the cap nothing is lost
+        let mut short = CappedBuffer::tail(4);
+        short.push(b"ok");
+        assert_eq!(short.finish(), (b"ok".to_vec(), 2));
+    }
+
+    #[test]
+    fn test_cut_tail_starts_at_a_character() {
+        let mut stderr = CappedBuffer::tail(4);
+        stderr.push("xé€".as_bytes());
+        let (kept, total) = stderr.finish();
+        assert_eq!(total, 6);
+        assert_eq!(kept, "€".as_bytes());
+    }
+}
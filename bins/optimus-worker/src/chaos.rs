@@ -0,0 +1,276 @@
+//! Chaos / Fault Injection - Resilience Testing Hooks
+//!
+//! **Core Responsibility:**
+//! Deliberately inject failures into the worker so the retry, DLQ and
+//! terminal-state guarantees can be exercised end-to-end.
+//!
+//! **Safety:**
+//! - Compiled in only with the `chaos` cargo feature
+//! - Without the feature every hook is a no-op that the optimizer removes
+//! - With the feature, nothing is injected unless OPTIMUS_CHAOS_* vars are set
+//!
+//! **Environment Variables:**
+//! - `OPTIMUS_CHAOS_DOCKER_FAILURE_RATE` - probability (0.0-1.0) that a Docker call fails
+//! - `OPTIMUS_CHAOS_REDIS_DELAY_RATE` - probability (0.0-1.0) that a Redis op is delayed
+//! - `OPTIMUS_CHAOS_REDIS_DELAY_MS` - delay applied to Redis ops (default: 500)
+//! - `OPTIMUS_CHAOS_CRASH_RATE` - probability (0.0-1.0) of crashing mid-job (per test case)
+//! - `OPTIMUS_CHAOS_SEED` - RNG seed for reproducible fault sequences
+
+/// Fault injection settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChaosConfig {
+    pub docker_failure_rate: f64,
+    pub redis_delay_rate: f64,
+    pub redis_delay_ms: u64,
+    pub crash_rate: f64,
+    pub seed: u64,
+}
+
+impl Default for ChaosConfig {
+    fn default() -> Self {
+        Self {
+            docker_failure_rate: 0.0,
+            redis_delay_rate: 0.0,
+            redis_delay_ms: 500,
+            crash_rate: 0.0,
+            seed: 0x9E37_79B9_7F4A_7C15,
+        }
+    }
+}
+
+impl ChaosConfig {
+    /// Load fault injection settings from OPTIMUS_CHAOS_* environment variables
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            docker_failure_rate: rate_from_env("OPTIMUS_CHAOS_DOCKER_FAILURE_RATE"),
+            redis_delay_rate: rate_from_env("OPTIMUS_CHAOS_REDIS_DELAY_RATE"),
+            redis_delay_ms: std::env::var("OPTIMUS_CHAOS_REDIS_DELAY_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.redis_delay_ms),
+            crash_rate: rate_from_env("OPTIMUS_CHAOS_CRASH_RATE"),
+            seed: std::env::var("OPTIMUS_CHAOS_SEED")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.seed),
+        }
+    }
+
+    /// Whether any fault is configured
+    pub fn is_active(&self) -> bool {
+        self.docker_failure_rate > 0.0 || self.redis_delay_rate > 0.0 || self.crash_rate > 0.0
+    }
+}
+
+/// Parse a probability, clamping to [0.0, 1.0] and treating garbage as 0.0
+fn rate_from_env(name: &str) -> f64 {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.parse::<f64>().ok())
+        .filter(|v| v.is_finite())
+        .map(|v| v.clamp(0.0, 1.0))
+        .unwrap_or(0.0)
+}
+
+/// Stateful fault injector with a deterministic xorshift RNG
+/// Same seed + same call sequence = same faults (reproducible chaos runs)
+#[cfg(any(feature = "chaos", test))]
+#[derive(Debug, Clone)]
+pub struct FaultInjector {
+    config: ChaosConfig,
+    state: u64,
+}
+
+#[cfg(any(feature = "chaos", test))]
+impl FaultInjector {
+    pub fn new(config: ChaosConfig) -> Self {
+        // xorshift must never be seeded with zero
+        let state = if config.seed == 0 { 1 } else { config.seed };
+        Self { config, state }
+    }
+
+    #[cfg(feature = "chaos")]
+    pub fn config(&self) -> &ChaosConfig {
+        &self.config
+    }
+
+    /// Next pseudo-random value in [0.0, 1.0)
+    fn next_unit(&mut self) -> f64 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.state = x;
+        (x >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn roll(&mut self, rate: f64) -> bool {
+        rate > 0.0 && self.next_unit() < rate
+    }
+
+    /// Decide whether the next Docker call should fail
+    pub fn should_fail_docker(&mut self) -> bool {
+        let rate = self.config.docker_failure_rate;
+        self.roll(rate)
+    }
+
+    /// Decide whether the next Redis op should be delayed (returns the delay)
+    pub fn redis_delay(&mut self) -> Option<std::time::Duration> {
+        let rate = self.config.redis_delay_rate;
+        if self.roll(rate) {
+            Some(std::time::Duration::from_millis(self.config.redis_delay_ms))
+        } else {
+            None
+        }
+    }
+
+    /// Decide whether the worker should crash now
+    pub fn should_crash(&mut self) -> bool {
+        let rate = self.config.crash_rate;
+        self.roll(rate)
+    }
+}
+
+#[cfg(feature = "chaos")]
+mod hooks {
+    use super::{ChaosConfig, FaultInjector};
+    use std::sync::{Mutex, OnceLock};
+    use tracing::warn;
+
+    static INJECTOR: OnceLock<Mutex<FaultInjector>> = OnceLock::new();
+
+    fn injector() -> &'static Mutex<FaultInjector> {
+        INJECTOR.get_or_init(|| Mutex::new(FaultInjector::new(ChaosConfig::from_env())))
+    }
+
+    /// Replace the active fault configuration (used by e2e tests)
+    #[allow(dead_code)]
+    pub fn configure(config: ChaosConfig) {
+        *injector().lock().unwrap_or_else(|e| e.into_inner()) = FaultInjector::new(config);
+    }
+
+    /// Log the active chaos configuration at boot
+    pub fn announce() {
+        let guard = injector().lock().unwrap_or_else(|e| e.into_inner());
+        if guard.config().is_active() {
+            warn!(config = ?guard.config(), "⚠️  CHAOS MODE ACTIVE - faults will be injected");
+        }
+    }
+
+    /// Possibly fail a Docker operation
+    pub fn docker_fault(operation: &str) -> anyhow::Result<()> {
+        let fail = injector().lock().unwrap_or_else(|e| e.into_inner()).should_fail_docker();
+        if fail {
+            warn!(operation = operation, "chaos: injecting Docker failure");
+            anyhow::bail!("chaos: injected Docker failure during {}", operation);
+        }
+        Ok(())
+    }
+
+    /// Possibly delay a Redis operation
+    pub async fn redis_delay(operation: &str) {
+        let delay = injector().lock().unwrap_or_else(|e| e.into_inner()).redis_delay();
+        if let Some(delay) = delay {
+            warn!(operation = operation, delay_ms = delay.as_millis() as u64, "chaos: delaying Redis op");
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Possibly crash the worker process mid-job
+    pub fn crash_point(job_id: &uuid::Uuid) {
+        let crash = injector().lock().unwrap_or_else(|e| e.into_inner()).should_crash();
+        if crash {
+            warn!(job_id = %job_id, "chaos: crashing worker mid-job");
+            std::process::abort();
+        }
+    }
+}
+
+#[cfg(not(feature = "chaos"))]
+mod hooks {
+    use super::ChaosConfig;
+    use tracing::warn;
+
+    /// Warn if chaos variables are set on a build that cannot honour them
+    pub fn announce() {
+        if ChaosConfig::from_env().is_active() {
+            warn!("OPTIMUS_CHAOS_* variables are set but this worker was built without the `chaos` feature - ignoring");
+        }
+    }
+
+    #[inline(always)]
+    pub fn docker_fault(_operation: &str) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub async fn redis_delay(_operation: &str) {}
+
+    #[inline(always)]
+    pub fn crash_point(_job_id: &uuid::Uuid) {}
+}
+
+pub use hooks::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config_is_inactive() {
+        let config = ChaosConfig::default();
+        assert!(!config.is_active());
+
+        let mut injector = FaultInjector::new(config);
+        for _ in 0..1000 {
+            assert!(!injector.should_fail_docker());
+            assert!(injector.redis_delay().is_none());
+            assert!(!injector.should_crash());
+        }
+    }
+
+    #[test]
+    fn test_full_rate_always_fires() {
+        let mut injector = FaultInjector::new(ChaosConfig {
+            docker_failure_rate: 1.0,
+            redis_delay_rate: 1.0,
+            redis_delay_ms: 10,
+            crash_rate: 1.0,
+            ..Default::default()
+        });
+        for _ in 0..100 {
+            assert!(injector.should_fail_docker());
+            assert_eq!(injector.redis_delay(), Some(std::time::Duration::from_millis(10)));
+            assert!(injector.should_crash());
+        }
+    }
+
+    #[test]
+    fn test_same_seed_same_faults() {
+        let config = ChaosConfig {
+            docker_failure_rate: 0.5,
+            seed: 42,
+            ..Default::default()
+        };
+        let mut a = FaultInjector::new(config.clone());
+        let mut b = FaultInjector::new(config);
+        let seq_a: Vec<bool> = (0..64).map(|_| a.should_fail_docker()).collect();
+        let seq_b: Vec<bool> = (0..64).map(|_| b.should_fail_docker()).collect();
+        assert_eq!(seq_a, seq_b);
+        // A 50% rate over 64 rolls should produce both outcomes
+        assert!(seq_a.contains(&true));
+        assert!(seq_a.contains(&false));
+    }
+
+    #[test]
+    fn test_zero_seed_is_usable() {
+        let mut injector = FaultInjector::new(ChaosConfig {
+            docker_failure_rate: 0.5,
+            seed: 0,
+            ..Default::default()
+        });
+        let fired = (0..64).filter(|_| injector.should_fail_docker()).count();
+        assert!(fired > 0 && fired < 64);
+    }
+}
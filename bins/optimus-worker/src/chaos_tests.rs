@@ -0,0 +1,157 @@
+//! End-to-end resilience tests driven by the chaos fault injector
+//!
+//! These tests verify that injected faults never strand a job:
+//! 1. Persistent Docker failures walk the job through retry → DLQ → Failed result
+//! 2. Intermittent Docker failures still end in a terminal result
+//! 3. Slow Redis operations do not lose results
+//!
+//! Run with: cargo test -p optimus-worker --features chaos -- --ignored --test-threads=1
+
+#[cfg(test)]
+mod resilience_tests {
+    use crate::chaos::{self, ChaosConfig};
+    use crate::config::LanguageConfigManager;
+    use crate::engine::DockerEngine;
+    use crate::executor;
+    use optimus_common::redis;
+    use optimus_common::types::{JobRequest, JobStatus, Language, TestCase};
+    use ::redis::AsyncCommands;
+    use tokio_util::sync::CancellationToken;
+    use uuid::Uuid;
+
+    async fn create_redis_conn() -> ::redis::aio::ConnectionManager {
+        let client = ::redis::Client::open("redis://127.0.0.1:6379")
+            .expect("Failed to create Redis client");
+        client.get_connection_manager().await
+            .expect("Failed to connect to Redis")
+    }
+
+    fn make_job() -> JobRequest {
+        JobRequest {
+            id: Uuid::new_v4(),
+            language: Language::Python,
+            source_code: "print(int(input()) * 2)".to_string(),
+            test_cases: vec![TestCase {
+                id: 1,
+                input: "21".to_string(),
+                expected_output: "42".to_string(),
+                weight: 10,
+                ..Default::default()
+            }],
+            timeout_ms: 5000,
+            ..Default::default()
+        }
+    }
+
+    /// Run a job the way worker_loop does until it reaches a terminal state
+    /// Returns the number of attempts that were made
+    async fn run_until_terminal(
+        job: &mut JobRequest,
+        config_manager: &LanguageConfigManager,
+        conn: &mut ::redis::aio::ConnectionManager,
+    ) -> u8 {
+        let engine = DockerEngine::new_with_config(config_manager).expect("Failed to create Docker engine");
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            match executor::execute_docker(job, &engine, config_manager, conn, &CancellationToken::new()).await {
+                Ok(result) => {
+                    chaos::redis_delay("store_result").await;
+                    redis::store_result_with_metrics(conn, &result, &job.language).await
+                        .expect("Failed to store result");
+                    return attempts;
+                }
+                Err(e) => {
+                    crate::handle_execution_failure(conn, job, &e).await;
+                    if job.metadata.attempts >= job.metadata.max_attempts {
+                        return attempts;
+                    }
+                    // Consume our own retry entry so the queue is left clean
+                    let _: () = conn.lrem(redis::retry_queue_name(&job.language), 1, serde_json::to_string(&*job).unwrap())
+                        .await
+                        .expect("Failed to consume retry entry");
+                }
+            }
+        }
+    }
+
+    /// Test: Docker failing every time ends in DLQ with a terminal Failed result
+    #[tokio::test]
+    #[ignore] // Requires Redis
+    async fn test_persistent_docker_failure_reaches_dlq() {
+        chaos::configure(ChaosConfig {
+            docker_failure_rate: 1.0,
+            ..Default::default()
+        });
+
+        let config_manager = LanguageConfigManager::load_default(None)
+            .expect("Failed to load language config");
+        let mut conn = create_redis_conn().await;
+        let mut job = make_job();
+
+        let attempts = run_until_terminal(&mut job, &config_manager, &mut conn).await;
+        assert_eq!(attempts, job.metadata.max_attempts, "Every attempt should have been used");
+
+        let dlq: Vec<String> = conn.lrange(redis::dlq_name(&job.language), 0, -1).await.unwrap();
+        let in_dlq = dlq.iter()
+            .filter_map(|item| serde_json::from_str::<JobRequest>(item).ok())
+            .any(|j| j.id == job.id);
+        assert!(in_dlq, "Job should be in the DLQ");
+
+        let result = redis::get_result(&mut conn, &job.id).await.unwrap()
+            .expect("Terminal result should be stored");
+        assert_eq!(result.overall_status, JobStatus::Failed);
+
+        chaos::configure(ChaosConfig::default());
+    }
+
+    /// Test: Intermittent Docker failures still produce a terminal result
+    #[tokio::test]
+    #[ignore] // Requires Docker and Redis
+    async fn test_intermittent_docker_failure_reaches_terminal_state() {
+        chaos::configure(ChaosConfig {
+            docker_failure_rate: 0.5,
+            seed: 7,
+            ..Default::default()
+        });
+
+        let config_manager = LanguageConfigManager::load_default(None)
+            .expect("Failed to load language config");
+        let mut conn = create_redis_conn().await;
+        let mut job = make_job();
+
+        run_until_terminal(&mut job, &config_manager, &mut conn).await;
+
+        let result = redis::get_result(&mut conn, &job.id).await.unwrap()
+            .expect("Job must reach a terminal state");
+        assert!(matches!(result.overall_status, JobStatus::Completed | JobStatus::Failed));
+
+        chaos::configure(ChaosConfig::default());
+    }
+
+    /// Test: Delayed Redis operations do not drop the result
+    #[tokio::test]
+    #[ignore] // Requires Docker and Redis
+    async fn test_redis_delay_does_not_lose_result() {
+        chaos::configure(ChaosConfig {
+            redis_delay_rate: 1.0,
+            redis_delay_ms: 200,
+            ..Default::default()
+        });
+
+        let config_manager = LanguageConfigManager::load_default(None)
+            .expect("Failed to load language config");
+        let mut conn = create_redis_conn().await;
+        let mut job = make_job();
+
+        let attempts = run_until_terminal(&mut job, &config_manager, &mut conn).await;
+        assert_eq!(attempts, 1);
+
+        let result = redis::get_result(&mut conn, &job.id).await.unwrap()
+            .expect("Result should be stored despite Redis delays");
+        assert_eq!(result.overall_status, JobStatus::Completed);
+        assert_eq!(result.score, 10);
+
+        chaos::configure(ChaosConfig::default());
+    }
+}
@@ -0,0 +1,130 @@
+//! Custom Checkers - Special Judge Support
+//!
+//! **Core Responsibility:**
+//! Define how a job's checker program is run and what its exit code means.
+//!
+//! Problems with many valid answers (any shortest path, any valid colouring) can't be
+//! judged by comparing strings. A job may carry `checker` source code in the job's
+//! language. The worker compiles it once in a container of its own, from the same image
+//! (the submission never shares a container with it), then runs it after every test
+//! that finished cleanly:
+//!
+//! ```text
+//! <checker> /tmp/input /tmp/expected /tmp/actual
+//! ```
+//!
+//! - Exit 0: the output is accepted
+//! - Exit 1: the output is wrong
+//! - Anything else (a crash, a timeout, a checker that doesn't compile): checker error,
+//!   an internal error rather than the submission's fault
+//!
+//! Whatever the checker prints is returned with the test as `checker_output`.
+
+use base64::{Engine as _, engine::general_purpose};
+use optimus_common::types::{JobRequest, OutputMode};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Where the test's input, expected output and actual output are written
+pub const INPUT_PATH: &str = "/tmp/input";
+pub const EXPECTED_PATH: &str = "/tmp/expected";
+pub const ACTUAL_PATH: &str = "/tmp/actual";
+
+/// Longest a checker may run on one test
+pub const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Checker output kept per test
+const MAX_MESSAGE_BYTES: usize = 1024;
+
+/// What one checker run produced
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckerOutcome {
+    /// None when the checker never ran to completion
+    pub exit_code: Option<i64>,
+    /// stdout and stderr, truncated
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckerVerdict {
+    Accepted,
+    Rejected,
+    Error,
+}
+
+impl CheckerOutcome {
+    /// A checker that could not be run (didn't compile, timed out, sandbox failure)
+    pub fn error(message: impl Into<String>) -> Self {
+        Self { exit_code: None, message: truncate(message.into()) }
+    }
+
+    pub fn finished(exit_code: Option<i64>, printed: &str) -> Self {
+        Self { exit_code, message: truncate(printed.trim().to_string()) }
+    }
+
+    pub fn verdict(&self) -> CheckerVerdict {
+        match self.exit_code {
+            Some(0) => CheckerVerdict::Accepted,
+            Some(1) => CheckerVerdict::Rejected,
+            _ => CheckerVerdict::Error,
+        }
+    }
+}
+
+fn truncate(mut message: String) -> String {
+    if message.len() > MAX_MESSAGE_BYTES {
+        let mut end = MAX_MESSAGE_BYTES;
+        while !message.is_char_boundary(end) {
+            end -= 1;
+        }
+        message.truncate(end);
+        message.push('…');
+    }
+    message
+}
+
+/// Extra time a job needs for its checker: compiling it, then one run per test
+pub fn allowance(job: &JobRequest) -> Duration {
+    if job.checker.is_none() {
+        return Duration::ZERO;
+    }
+    crate::engine::COMPILE_BUDGET.saturating_add(TIMEOUT.saturating_mul(job.test_cases.len() as u32))
+}
+
+/// Shell command running the compiled checker on the three files
+pub fn command(program: &str) -> String {
+    format!("cd /code && {} {} {} {} 2>&1", program, INPUT_PATH, EXPECTED_PATH, ACTUAL_PATH)
+}
+
+/// Bytes the checker reads as the expected output (binary mode stores them base64)
+pub fn expected_bytes(expected_output: &str, mode: OutputMode) -> Vec<u8> {
+    match mode {
+        OutputMode::Text => expected_output.as_bytes().to_vec(),
+        OutputMode::Binary => general_purpose::STANDARD
+            .decode(expected_output.trim())
+            .unwrap_or_else(|_| expected_output.as_bytes().to_vec()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_codes() {
+        assert_eq!(CheckerOutcome::finished(Some(0), "ok").verdict(), CheckerVerdict::Accepted);
+        assert_eq!(CheckerOutcome::finished(Some(1), "wrong path").verdict(), CheckerVerdict::Rejected);
+        assert_eq!(CheckerOutcome::finished(Some(2), "").verdict(), CheckerVerdict::Error);
+        assert_eq!(CheckerOutcome::error("did not compile").verdict(), CheckerVerdict::Error);
+    }
+
+    #[test]
+    fn test_message_truncated_on_char_boundary() {
+        let outcome = CheckerOutcome::finished(Some(1), &"é".repeat(1000));
+        assert!(outcome.message.len() <= MAX_MESSAGE_BYTES + "…".len());
+        assert!(outcome.message.ends_with('…'));
+
+        assert_eq!(expected_bytes("aGk=", OutputMode::Binary), b"hi");
+        assert_eq!(expected_bytes("aGk=", OutputMode::Text), b"aGk=");
+    }
+}
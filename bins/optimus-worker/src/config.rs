@@ -1,34 +1,11 @@
 // Language configuration management for Optimus Worker
-use anyhow::{Context, Result, bail};
-use serde::{Deserialize, Serialize};
+use anyhow::{anyhow, Result, bail};
 use std::collections::HashMap;
-use std::fs;
 use std::path::Path;
+use optimus_common::language_config::{legacy_compile, LanguagesFile, DEFAULT_CONFIG_PATH};
 use optimus_common::types::Language;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct LanguageExecution {
-    pub command: String,
-    pub args: Vec<String>,
-    pub file_extension: String,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct LanguageConfig {
-    pub name: String,
-    pub version: String,
-    pub image: String,
-    pub dockerfile_path: String,
-    pub execution: LanguageExecution,
-    pub queue_name: String,
-    pub memory_limit_mb: u32,
-    pub cpu_limit: f32,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct LanguagesJson {
-    languages: Vec<LanguageConfig>,
-}
+pub use optimus_common::language_config::{CanaryConfig, CompileSpec, DeterminismConfig, LanguageConfig, LanguageFlags};
 
 /// Language configuration manager
 #[derive(Clone)]
@@ -37,30 +14,27 @@ pub struct LanguageConfigManager {
 }
 
 impl LanguageConfigManager {
-    /// Load language configurations from languages.json
-    pub fn load(config_path: &Path) -> Result<Self> {
+    /// Load language configurations from languages.json, with a profile's overlay merged in
+    pub fn load(config_path: &Path, profile: Option<&str>) -> Result<Self> {
         if !config_path.exists() {
             bail!("Language config file not found: {}", config_path.display());
         }
 
-        let content = fs::read_to_string(config_path)
-            .context("Failed to read languages.json")?;
-        
-        let languages_json: LanguagesJson = serde_json::from_str(&content)
-            .context("Failed to parse languages.json")?;
+        let languages_file = LanguagesFile::load_profile(config_path, profile).map_err(|e| anyhow!(e))?;
+        languages_file.check_queue_names().map_err(|e| anyhow!("Queue name mismatch in {}:\n{}", config_path.display(), e))?;
 
         let mut configs = HashMap::new();
-        for lang in languages_json.languages {
+        for lang in languages_file.languages {
             configs.insert(lang.name.clone(), lang);
         }
 
         Ok(Self { configs })
     }
 
-    /// Load with default path (config/languages.json)
-    pub fn load_default() -> Result<Self> {
-        let default_path = Path::new("config/languages.json");
-        Self::load(default_path)
+    /// Load with default path (config/languages.json) and a profile's overlay
+    pub fn load_default(profile: Option<&str>) -> Result<Self> {
+        let default_path = Path::new(DEFAULT_CONFIG_PATH);
+        Self::load(default_path, profile)
     }
 
     /// Get configuration for a specific language
@@ -87,10 +61,52 @@ impl LanguageConfigManager {
     }
 
     /// Get CPU limit for a language
-    pub fn get_cpu_limit(&self, language: &Language) -> Result<f32> {
+    pub fn get_cpu_limit(&self, language: &Language) -> Result<f64> {
         Ok(self.get_config(language)?.cpu_limit)
     }
 
+    /// Get judge environment version for a language (None if untracked)
+    pub fn get_env_version(&self, language: &Language) -> Option<u32> {
+        self.get_config(language)
+            .ok()
+            .map(|config| config.env_version)
+            .filter(|version| *version > 0)
+    }
+
+    /// Get the factor applied to a language's time limits (1 when unconfigured)
+    pub fn get_time_multiplier(&self, language: &Language) -> f64 {
+        self.get_config(language).map(|config| config.time_multiplier).unwrap_or(1.0)
+    }
+
+    /// Get the canary configuration for a language, if one is active
+    pub fn get_canary(&self, language: &Language) -> Option<&CanaryConfig> {
+        self.get_config(language)
+            .ok()
+            .and_then(|config| config.canary.as_ref())
+            .filter(|canary| canary.percent > 0.0 && !canary.image.is_empty())
+    }
+
+    /// Get the determinism settings for a language, if configured
+    pub fn get_determinism(&self, language: &Language) -> Option<&DeterminismConfig> {
+        self.get_config(language)
+            .ok()
+            .and_then(|config| config.determinism.as_ref())
+    }
+
+    /// Get the compile step for a language (None: interpreted)
+    /// Unconfigured languages keep the compile commands workers used to hard-code
+    pub fn get_compile(&self, language: &Language) -> Option<CompileSpec> {
+        match self.get_config(language) {
+            Ok(config) => config.compile.clone(),
+            Err(_) => legacy_compile(&language.to_string()),
+        }
+    }
+
+    /// Get the execution flags for a language (defaults when unconfigured)
+    pub fn get_flags(&self, language: &Language) -> LanguageFlags {
+        self.get_config(language).map(|config| config.flags).unwrap_or_default()
+    }
+
     /// List all supported languages
     pub fn list_languages(&self) -> Vec<String> {
         self.configs.keys().cloned().collect()
@@ -104,7 +120,7 @@ mod tests {
     #[test]
     fn test_load_config() {
         // This test will only work if config/languages.json exists
-        let result = LanguageConfigManager::load_default();
+        let result = LanguageConfigManager::load_default(None);
         match result {
             Ok(manager) => {
                 println!("Loaded languages: {:?}", manager.list_languages());
@@ -0,0 +1,126 @@
+//! Deterministic Execution Aids - Timing Variance Reduction
+//!
+//! **Core Responsibility:**
+//! Make repeated runs of the same program take as close to the same time as possible,
+//! so tight time limits don't produce flaky TLEs.
+//!
+//! **Configuration** (`config/languages.json`, per language):
+//! `"determinism": { "cpuset": "2,3", "disable_aslr": true, "locale": "C.UTF-8",
+//! "env": { "PYTHONHASHSEED": "0" }, "variance_sample_percent": 1 }`
+//!
+//! - `cpuset` pins execution containers to dedicated host CPUs
+//! - `disable_aslr` runs each test under `setarch -R`; if the container's seccomp profile
+//!   forbids it, the test runs normally rather than failing
+//! - `locale`/`env` give every run the same controlled environment (TZ is always UTC)
+//! - `variance_sample_percent` repeats the first test of a share of jobs and publishes the
+//!   coefficient of variation of its times to `optimus:metrics:variance`, so the effect of
+//!   pinning is visible in `optimus_exec_time_cv` on the API's /metrics
+
+use crate::config::DeterminismConfig;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Repeats of the sampled test when measuring variance
+pub const VARIANCE_REPEATS: usize = 5;
+
+/// Tests slower than this are not repeated (the job result would wait too long)
+pub const VARIANCE_MAX_TEST_MS: u64 = 1_000;
+
+/// Locale used when none is configured
+const DEFAULT_LOCALE: &str = "C.UTF-8";
+
+/// Variables the runner relies on; configured env never overrides them
+const RESERVED_ENV: &[&str] = &["SOURCE_CODE", "TEST_INPUT", "LANGUAGE"];
+
+/// Controlled environment variables as `KEY=value` entries for a container
+pub fn env_vars(config: &DeterminismConfig) -> Vec<String> {
+    let locale = config.locale.as_deref().unwrap_or(DEFAULT_LOCALE);
+    let mut env: BTreeMap<&str, &str> = BTreeMap::new();
+    env.insert("LANG", locale);
+    env.insert("LC_ALL", locale);
+    env.insert("TZ", "UTC");
+    for (key, value) in &config.env {
+        if !RESERVED_ENV.contains(&key.as_str()) {
+            env.insert(key, value);
+        }
+    }
+    env.into_iter().map(|(key, value)| format!("{}={}", key, value)).collect()
+}
+
+/// Wrap a shell command so it runs with ASLR disabled when the sandbox allows it
+pub fn wrap_command(config: &DeterminismConfig, cmd: &str) -> String {
+    if !config.disable_aslr {
+        return cmd.to_string();
+    }
+    let quoted = cmd.replace('\'', "'\\''");
+    format!(
+        "if setarch \"$(uname -m)\" -R true 2>/dev/null; then setarch \"$(uname -m)\" -R bash -c '{}'; else {}; fi",
+        quoted, cmd
+    )
+}
+
+/// Whether this job falls into the variance sample
+pub fn should_sample(job_id: &Uuid, config: &DeterminismConfig) -> bool {
+    // UUID v4 bits are random, so the bucket is uniform over 0..10000
+    let bucket = (job_id.as_u128() % 10_000) as f64;
+    bucket < config.variance_sample_percent * 100.0
+}
+
+/// Coefficient of variation (stddev / mean) of repeated execution times
+pub fn coefficient_of_variation(times_ms: &[u64]) -> Option<f64> {
+    if times_ms.len() < 2 {
+        return None;
+    }
+    let n = times_ms.len() as f64;
+    let mean = times_ms.iter().sum::<u64>() as f64 / n;
+    if mean == 0.0 {
+        return None;
+    }
+    let variance = times_ms
+        .iter()
+        .map(|&t| (t as f64 - mean).powi(2))
+        .sum::<f64>()
+        / n;
+    Some(variance.sqrt() / mean)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_vars_defaults_and_overrides() {
+        let mut config = DeterminismConfig::default();
+        assert_eq!(env_vars(&config), vec!["LANG=C.UTF-8", "LC_ALL=C.UTF-8", "TZ=UTC"]);
+
+        config.locale = Some("en_US.UTF-8".to_string());
+        config.env.insert("PYTHONHASHSEED".to_string(), "0".to_string());
+        config.env.insert("TZ".to_string(), "Europe/Paris".to_string());
+        config.env.insert("SOURCE_CODE".to_string(), "x".to_string());
+        assert_eq!(
+            env_vars(&config),
+            vec!["LANG=en_US.UTF-8", "LC_ALL=en_US.UTF-8", "PYTHONHASHSEED=0", "TZ=Europe/Paris"]
+        );
+    }
+
+    #[test]
+    fn test_wrap_command() {
+        let mut config = DeterminismConfig::default();
+        let cmd = "echo 'aGk=' | base64 -d | /code/main";
+        assert_eq!(wrap_command(&config, cmd), cmd);
+
+        config.disable_aslr = true;
+        let wrapped = wrap_command(&config, cmd);
+        assert!(wrapped.contains("-R bash -c 'echo '\\''aGk='\\'' | base64 -d | /code/main'"));
+        assert!(wrapped.ends_with(&format!("else {}; fi", cmd)));
+    }
+
+    #[test]
+    fn test_coefficient_of_variation() {
+        assert_eq!(coefficient_of_variation(&[10]), None);
+        assert_eq!(coefficient_of_variation(&[0, 0]), None);
+        assert_eq!(coefficient_of_variation(&[10, 10, 10]), Some(0.0));
+        let cv = coefficient_of_variation(&[8, 12]).unwrap();
+        assert!((cv - 0.2).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,127 @@
+//! Output Diff - Where a Failed Test Went Wrong
+//!
+//! **Core Responsibility:**
+//! Find the first place a failed test's output differs from the expected output, and
+//! describe it in a few lines rather than the whole output.
+//!
+//! Frontends render "your output differs at line 3" from this instead of diffing stdout
+//! (up to megabytes) themselves. Both outputs go through the job's pipeline first, so the
+//! difference is one the comparison saw: whitespace a trim removes never counts. Pipelines
+//! that stream are read a line at a time up to the difference, as `StreamingComparison`
+//! compares them; the others are normalized whole. Line and
+//! column are positions in the normalized outputs; the snippets are that line of each side,
+//! cut to a window around the column.
+//!
+//! Only the `exact` comparator has a first difference. Unordered lines, tokens and numeric
+//! tolerance get no diff, and neither do binary outputs or checker verdicts.
+
+use crate::pipeline::Pipeline;
+use crate::streaming::StreamingComparison;
+use optimus_common::types::{Comparator, OutputDiff};
+
+/// Characters of context kept before the difference
+const SNIPPET_BEFORE: usize = 20;
+
+/// Characters kept per snippet
+const SNIPPET_CHARS: usize = 80;
+
+/// The first difference between the outputs as the pipeline compares them; None when the
+/// comparator isn't positional or the outputs match
+pub fn first_difference(pipeline: &Pipeline, actual: &str, expected: &str) -> Option<OutputDiff> {
+    if pipeline.comparator() != Comparator::Exact {
+        return None;
+    }
+    if let Some(streaming) = StreamingComparison::new(pipeline) {
+        // Reading from memory can't fail
+        let (line, a, e) = streaming.first_difference(actual.as_bytes(), expected.as_bytes()).ok()??;
+        return Some(describe(line, a.as_deref(), e.as_deref()));
+    }
+    let actual = pipeline.normalize(actual);
+    let expected = pipeline.normalize(expected);
+    if actual == expected {
+        return None;
+    }
+
+    // Split on '\n' alone, so a stray '\r' or a missing final newline is a difference too
+    let mut actual_lines = actual.split('\n');
+    let mut expected_lines = expected.split('\n');
+    let mut line = 0;
+    loop {
+        line += 1;
+        match (actual_lines.next(), expected_lines.next()) {
+            (Some(a), Some(e)) if a == e => continue,
+            (a, e) => return Some(describe(line, a, e)),
+        }
+    }
+}
+
+/// The difference on a line, each side cut to a window around the first differing column
+fn describe(line: usize, actual: Option<&str>, expected: Option<&str>) -> OutputDiff {
+    let column = match (actual, expected) {
+        (Some(a), Some(e)) => a.chars().zip(e.chars()).take_while(|(x, y)| x == y).count() + 1,
+        _ => 1,
+    };
+    let start = column.saturating_sub(SNIPPET_BEFORE).max(1);
+    let snippet = |text: &str| text.chars().skip(start - 1).take(SNIPPET_CHARS).collect();
+    OutputDiff {
+        line,
+        column,
+        expected: expected.map(snippet),
+        actual: actual.map(snippet),
+        snippet_start: start,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use optimus_common::types::{ComparisonMode, NormalizationOptions, Normalizer};
+
+    fn diff(mode: ComparisonMode, actual: &str, expected: &str) -> Option<OutputDiff> {
+        first_difference(&Pipeline::legacy(mode, &NormalizationOptions::default()), actual, expected)
+    }
+
+    #[test]
+    fn test_first_mismatching_line_and_column() {
+        let found = diff(ComparisonMode::Ordered, "1\n2\n3 4 6\n7\n", "1\n2\n3 4 5\n8\n").unwrap();
+        assert_eq!((found.line, found.column, found.snippet_start), (3, 5, 1));
+        assert_eq!(found.expected.as_deref(), Some("3 4 5"));
+        assert_eq!(found.actual.as_deref(), Some("3 4 6"));
+
+        // Only trimmed whitespace differs: the outputs matched as compared
+        assert_eq!(diff(ComparisonMode::Ordered, "\n1\n2  \n", "1\n2"), None);
+        // Line order is no difference to unordered comparison
+        assert_eq!(diff(ComparisonMode::Unordered, "2\n1", "1\n3"), None);
+    }
+
+    #[test]
+    fn test_missing_lines_and_strict_newlines() {
+        let short = diff(ComparisonMode::Ordered, "1\n2", "1\n2\n3").unwrap();
+        assert_eq!((short.line, short.column), (3, 1));
+        assert_eq!((short.expected.as_deref(), short.actual), (Some("3"), None));
+
+        let crlf = diff(ComparisonMode::Strict, "1\r\n", "1\n").unwrap();
+        assert_eq!((crlf.line, crlf.column), (1, 2));
+        let final_newline = diff(ComparisonMode::Strict, "1", "1\n").unwrap();
+        assert_eq!((final_newline.line, final_newline.expected, final_newline.actual), (2, Some(String::new()), None));
+    }
+
+    #[test]
+    fn test_long_lines_are_cut_around_the_difference() {
+        let expected = "x".repeat(1000) + "a" + &"y".repeat(1000);
+        let actual = "x".repeat(1000) + "b" + &"y".repeat(1000);
+        let found = diff(ComparisonMode::Ordered, &actual, &expected).unwrap();
+        assert_eq!((found.column, found.snippet_start), (1001, 981));
+        let snippet = found.actual.unwrap();
+        assert_eq!(snippet.chars().count(), SNIPPET_CHARS);
+        assert_eq!(snippet.chars().nth(SNIPPET_BEFORE), Some('b'));
+    }
+
+    #[test]
+    fn test_unstreamable_pipelines_are_compared_whole() {
+        let bom_after_trim = Pipeline::builder().normalize(Normalizer::Trim).normalize(Normalizer::StripBom).build();
+        assert!(StreamingComparison::new(&bom_after_trim).is_none());
+        let found = first_difference(&bom_after_trim, "\u{FEFF}1\n2\n", "1\n3").unwrap();
+        assert_eq!((found.line, found.column, found.actual.as_deref()), (2, 1, Some("2")));
+    }
+}
@@ -1,125 +1,138 @@
-/// Execution Engine - Abstraction for Code Execution
-///
-/// **Core Responsibility:**
-/// Execute source code with test inputs and capture raw outputs.
-///
-/// **Critical Architectural Boundary:**
-/// - Engine knows HOW to execute (Docker, local, sandbox, etc.)
-/// - Engine does NOT know scoring rules
-/// - Engine does NOT evaluate correctness
-/// - Engine returns raw outputs for Evaluator to judge
-///
-/// **Why This Exists:**
-/// Enables swappable execution backends without touching scoring logic.
-/// Production uses DockerEngine with language-aware configuration.
+//! Execution Engine - Abstraction for Code Execution
+//!
+//! **Core Responsibility:**
+//! Execute source code with test inputs and capture raw outputs.
+//!
+//! **Critical Architectural Boundary:**
+//! - Engine knows HOW to execute (Docker, local, sandbox, etc.)
+//! - Engine does NOT know scoring rules
+//! - Engine does NOT evaluate correctness
+//! - Engine returns raw outputs for Evaluator to judge
+//!
+//! **Why This Exists:**
+//! Enables swappable execution backends without touching scoring logic.
+//! Production uses DockerEngine with language-aware configuration.
 
 use crate::evaluator::TestExecutionOutput;
-use crate::config::LanguageConfigManager;
-use optimus_common::types::{JobRequest, Language};
-use bollard::{Docker, container::Config, image::CreateImageOptions, container::{CreateContainerOptions, StartContainerOptions, WaitContainerOptions, RemoveContainerOptions}};
+use crate::progress::{LiveOutput, ProgressReporter};
+use crate::config::{CompileSpec, DeterminismConfig, LanguageConfigManager};
+use crate::determinism;
+use crate::protocol;
+use crate::monitor::{self, ResourceMonitor};
+use crate::chaos;
+use crate::capture::CappedBuffer;
+use crate::checker::{self, CheckerOutcome};
+use crate::interactor;
+use crate::pool;
+use crate::notebook;
+use crate::sanitizer;
+use optimus_common::types::{JobRequest, Language, OutputStream, TestFailure};
+use optimus_common::language_config::legacy_compile;
+use bollard::{Docker, container::Config, image::CreateImageOptions, container::{CreateContainerOptions, StartContainerOptions, RemoveContainerOptions}};
 use bollard::container::LogOutput;
+use bollard::models::DeviceRequest;
 use futures_util::stream::StreamExt;
+use tokio_util::sync::CancellationToken;
+use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant};
 use anyhow::{Context, Result, bail};
 use base64::{Engine as _, engine::general_purpose};
 use tracing::{debug, info, warn};
 
-/// Safety limits to prevent pathological inputs from reaching Docker
-const MAX_SOURCE_CODE_BYTES: usize = 1024 * 1024; // 1MB
-const MAX_TEST_INPUT_BYTES: usize = 10 * 1024 * 1024; // 10MB
+/// Time allowed for compiling inside a compile-once container
+pub const COMPILE_BUDGET: Duration = Duration::from_secs(120);
 
-/// Execute a complete job using DockerEngine (async version)
-///
-/// This function:
-/// 1. Iterates through all test cases
-/// 2. Checks for cancellation before each test case
-/// 3. Calls engine.execute_in_container() for each
-/// 4. Collects raw outputs
-/// 5. Returns outputs for Evaluator
-///
-/// ## Arguments
-/// * `job` - The job to execute
-/// * `engine` - The Docker execution engine to use
-/// * `redis_conn` - Redis connection for cancellation checks
-///
-/// ## Returns
-/// Vector of raw execution outputs (one per test case)
-pub async fn execute_job_async(
-    job: &JobRequest,
-    engine: &DockerEngine,
-    redis_conn: &mut redis::aio::ConnectionManager,
-) -> Vec<TestExecutionOutput> {
-    let mut outputs = Vec::new();
-
-    println!("→ Executing {} test cases with Docker", job.test_cases.len());
-    println!("  Language: {}", job.language);
-    println!("  Timeout per test: {}ms", job.timeout_ms);
-    println!();
-
-    for test_case in &job.test_cases {
-        // Check for cancellation before each test case
-        match optimus_common::redis::is_job_cancelled(redis_conn, &job.id).await {
-            Ok(true) => {
-                println!("  ⚠ Job cancelled - stopping execution");
-                println!("    Completed {} of {} tests before cancellation", outputs.len(), job.test_cases.len());
-                break;
-            }
-            Ok(false) => {
-                // Not cancelled, continue
-            }
-            Err(e) => {
-                eprintln!("  ⚠ Failed to check cancellation status: {}", e);
-                // Continue execution on error to avoid false cancellations
-            }
-        }
+/// Longest a warmup run may take; it fits in the keep-alive slack and the job budget's overhead
+const WARMUP_MAX_MS: u64 = 5_000;
 
-        println!("  Executing test {} (id: {})", outputs.len() + 1, test_case.id);
+/// Keep-alive margin for source writes, exec setup, a warmup run and cleanup
+const KEEP_ALIVE_SLACK: Duration = Duration::from_secs(60);
 
-        // Execute with Docker engine
-        let result = engine.execute_in_container(
-            &job.language,
-            &job.source_code,
-            &test_case.input,
-            job.timeout_ms,
-        ).await;
+/// Upper bound on any container's keep-alive
+const MAX_KEEP_ALIVE: Duration = Duration::from_secs(24 * 3600);
 
-        let mut output = match result {
-            Ok(output) => output,
-            Err(e) => {
-                eprintln!("    ✗ Docker execution error: {}", e);
-                TestExecutionOutput {
-                    test_id: test_case.id,
-                    stdout: String::new(),
-                    stderr: format!("Docker execution error: {}", e),
-                    execution_time_ms: 0,
-                    timed_out: false,
-                    runtime_error: true,
-                    compilation_failed: false,
-                }
-            }
-        };
+/// How long a compile-once container must stay up for a job
+///
+/// The container's init process exits after this, so a container leaked by a crashed
+/// worker still goes away: compile budget + every test at its wall-clock ceiling
+/// (timing-variance repeats included) + the checker's or interactor's share + slack, capped at 24h.
+pub fn keep_alive_for(job: &JobRequest) -> Duration {
+    let per_test = Duration::from_millis(protocol::wall_limit_ms(job.timeout_ms)).saturating_add(protocol::overhead_grace());
+    let runs = (job.test_cases.len() + determinism::VARIANCE_REPEATS) as u32;
+    COMPILE_BUDGET
+        .saturating_add(per_test.saturating_mul(runs))
+        .saturating_add(checker::allowance(job))
+        .saturating_add(interactor::allowance(job))
+        .saturating_add(KEEP_ALIVE_SLACK)
+        .min(MAX_KEEP_ALIVE)
+}
 
-        // Set correct test_id
-        output.test_id = test_case.id;
+/// Command running the program compiled (or written) in /code
+///
+/// The JVM languages all run on the image's `java`, with the main class named after the
+/// source file (see `Language::source_file`); the Scala library is installed in the image.
+///
+/// CRITICAL: Unset JAVA_TOOL_OPTIONS to prevent JVM noise in stderr
+/// (env -u keeps it a simple command the runner can wrap with GNU time)
+fn program_command(language: &Language) -> &'static str {
+    match language {
+        Language::Java => "env -u JAVA_TOOL_OPTIONS java -cp /code Main",
+        Language::Kotlin => "env -u JAVA_TOOL_OPTIONS java -cp /code/main.jar MainKt",
+        Language::Scala => "env -u JAVA_TOOL_OPTIONS java -cp /code:/opt/scala/lib/scala-library.jar Main",
+        Language::Rust | Language::C | Language::Asm => "/code/main",
+        Language::Python => "python3 -u /code/main.py",
+        Language::Php => "php /code/main.php",
+        Language::Ruby => "ruby /code/main.rb",
+    }
+}
 
-        println!("    Execution time: {}ms", output.execution_time_ms);
-        if output.timed_out {
-            println!("    ⚠ Timed out");
-        }
-        if output.runtime_error {
-            println!("    ✗ Runtime error");
-        }
-        if !output.stderr.is_empty() {
-            println!("    stderr: {}", output.stderr.lines().next().unwrap_or(""));
-        }
+/// Output for a test that could not run because its container is gone
+fn premature_exit_output(test_id: u32, reason: &str) -> TestExecutionOutput {
+    TestExecutionOutput {
+        test_id,
+        stdout: String::new(),
+        stderr: format!("[{}]", reason),
+        execution_time_ms: 0,
+        timed_out: false,
+        runtime_error: true,
+        compilation_failed: false,
+        stdout_bytes: None,
+        stderr_bytes: None,
+        stdout_total_bytes: None,
+        stderr_total_bytes: None,
+        overhead_ms: None,
+        cpu_time_ms: None,
+        memory_kb: None,
+        resource_samples: None,
+        oom_killed: false,
+        failure: None,
+        checker: None,
+    }
+}
 
-        outputs.push(output);
+/// Output for a test the sandbox couldn't run; judged an internal error
+fn sandbox_failure_output(test_id: u32, error: &anyhow::Error) -> TestExecutionOutput {
+    TestExecutionOutput {
+        stderr: format!("{}{}", crate::evaluator::SANDBOX_FAILURE_PREFIX, error),
+        failure: Some(TestFailure::SandboxError),
+        ..premature_exit_output(test_id, "")
     }
+}
 
-    println!();
-    println!("→ All test cases executed");
+/// Extra attempts for a test whose exec failed for reasons outside the program
+/// (TEST_INFRA_RETRIES, default 2); only that test runs again, not the job
+fn test_infra_retries() -> u32 {
+    std::env::var("TEST_INFRA_RETRIES").ok().and_then(|v| v.parse().ok()).unwrap_or(2)
+}
 
-    outputs
+/// Output for an interactive test whose interactor never started; judged an interactor error
+fn judge_unavailable_output(test_id: u32, message: &str) -> TestExecutionOutput {
+    TestExecutionOutput {
+        checker: Some(CheckerOutcome::error(message)),
+        runtime_error: false,
+        stderr: String::new(),
+        ..premature_exit_output(test_id, message)
+    }
 }
 
 /// Container cleanup guard - guarantees container removal on drop
@@ -127,16 +140,26 @@ pub async fn execute_job_async(
 struct ContainerGuard<'a> {
     docker: &'a Docker,
     container_id: String,
+    armed: bool,
 }
 
 impl<'a> ContainerGuard<'a> {
     fn new(docker: &'a Docker, container_id: String) -> Self {
-        Self { docker, container_id }
+        Self { docker, container_id, armed: true }
+    }
+
+    /// Keep the container (it went back to the pool)
+    fn disarm(&mut self) {
+        self.armed = false;
     }
 }
 
 impl<'a> Drop for ContainerGuard<'a> {
     fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+        pool::forget(&self.container_id);
         // Best-effort cleanup - cannot be async in Drop
         // Fire-and-forget cleanup task
         let container_id = self.container_id.clone();
@@ -155,6 +178,37 @@ impl<'a> Drop for ContainerGuard<'a> {
     }
 }
 
+/// The worker's DockerEngine, built once at startup and shared by every job
+///
+/// `healthy()` pings the daemon before handing the engine out and swaps in a fresh
+/// client when it stops answering, so a Docker restart doesn't need a worker restart.
+#[derive(Clone)]
+pub struct SharedEngine(Arc<RwLock<Arc<DockerEngine>>>);
+
+impl SharedEngine {
+    pub fn new(config_manager: &LanguageConfigManager, gpu: bool) -> Result<Self> {
+        let engine = DockerEngine::new_with_config(config_manager)?.with_gpu(gpu);
+        Ok(Self(Arc::new(RwLock::new(Arc::new(engine)))))
+    }
+
+    /// The current engine, without a health check
+    pub fn get(&self) -> Arc<DockerEngine> {
+        self.0.read().unwrap().clone()
+    }
+
+    /// The engine, reconnected first if the daemon stopped answering
+    pub async fn healthy(&self) -> Result<Arc<DockerEngine>> {
+        let engine = self.get();
+        let Err(e) = engine.ping().await else { return Ok(engine) };
+        warn!(error = %e, "Docker connection unhealthy - reconnecting");
+        let fresh = Arc::new(engine.reconnected()?);
+        fresh.ping().await?;
+        *self.0.write().unwrap() = fresh.clone();
+        info!("Reconnected to Docker daemon");
+        Ok(fresh)
+    }
+}
+
 /// Docker-based execution engine for real sandboxed code execution
 ///
 /// **Docker Execution Rules:**
@@ -171,9 +225,14 @@ impl<'a> Drop for ContainerGuard<'a> {
 ///
 /// **Purpose:**
 /// Production-grade sandboxed execution with resource isolation
+#[derive(Clone)]
 pub struct DockerEngine {
     docker: Docker,
     config_manager: Option<LanguageConfigManager>,
+    /// Runs every language on this image instead of the configured one (canary shadow runs)
+    image_override: Option<String>,
+    /// Mount the language's GPUs into every container (GPU workers)
+    gpu: bool,
 }
 
 impl DockerEngine {
@@ -186,11 +245,42 @@ impl DockerEngine {
         Ok(DockerEngine { 
             docker,
             config_manager: Some(config_manager.clone()),
+            image_override: None,
+            gpu: false,
         })
     }
 
+    /// Same engine with a fresh Docker client (the daemon restarted or the socket broke)
+    fn reconnected(&self) -> Result<Self> {
+        let docker = Docker::connect_with_local_defaults()
+            .context("Failed to connect to Docker daemon")?;
+        Ok(DockerEngine { docker, ..self.clone() })
+    }
+
+    /// Check the Docker daemon answers
+    pub async fn ping(&self) -> Result<()> {
+        self.docker.ping().await.context("Docker daemon not responding")?;
+        Ok(())
+    }
+
+    /// Execute on a specific image (e.g. a canary candidate) instead of the configured one
+    pub fn with_image(mut self, image: &str) -> Self {
+        self.image_override = Some(image.to_string());
+        self
+    }
+
+    /// Give every container the language's GPUs (`gpu.devices` in languages.json, default 1)
+    pub fn with_gpu(mut self, gpu: bool) -> Self {
+        self.gpu = gpu;
+        self
+    }
+
     /// Get the Docker image name for a language
     fn get_image_name(&self, language: &Language) -> String {
+        if let Some(ref image) = self.image_override {
+            return image.clone();
+        }
+
         // Try config manager first, fallback to hardcoded values
         if let Some(ref config) = self.config_manager {
             if let Ok(image) = config.get_image(language) {
@@ -203,17 +293,12 @@ impl DockerEngine {
             Language::Python => "optimus-python:latest".to_string(),
             Language::Java => "optimus-java:latest".to_string(),
             Language::Rust => "optimus-rust:latest".to_string(),
-        }
-    }
-
-    /// Get the execution command for a language
-    fn get_execution_command(&self, language: &Language) -> Vec<String> {
-        // Use the runner script from the Docker image
-        // The runner handles decoding SOURCE_CODE and TEST_INPUT env vars
-        match language {
-            Language::Python => vec!["python".to_string(), "/runner.py".to_string()],
-            Language::Java => vec!["java".to_string(), "-cp".to_string(), "/".to_string(), "Runner".to_string()],
-            Language::Rust => vec!["rust".to_string(), "/runner.sh".to_string()],
+            Language::Kotlin => "optimus-kotlin:latest".to_string(),
+            Language::Scala => "optimus-scala:latest".to_string(),
+            Language::C => "optimus-c:latest".to_string(),
+            Language::Php => "optimus-php:latest".to_string(),
+            Language::Ruby => "optimus-ruby:latest".to_string(),
+            Language::Asm => "optimus-asm:latest".to_string(),
         }
     }
 
@@ -237,6 +322,100 @@ impl DockerEngine {
         500_000_000 // Default: 0.5 CPU
     }
 
+    /// CPUs a language's containers get
+    fn get_cpus(&self, language: &Language) -> f64 {
+        self.get_cpu_limit(language) as f64 / 1_000_000_000.0
+    }
+
+    /// Get determinism settings for a language, if configured
+    fn get_determinism(&self, language: &Language) -> Option<&DeterminismConfig> {
+        self.config_manager.as_ref()?.get_determinism(language)
+    }
+
+    /// Get the compile step for a language (None: interpreted, no compile phase)
+    fn get_compile(&self, language: &Language) -> Option<CompileSpec> {
+        match self.config_manager {
+            Some(ref config) => config.get_compile(language),
+            None => legacy_compile(&language.to_string()),
+        }
+    }
+
+    /// Whether compile-once containers for a language are pooled across jobs
+    fn use_pool(&self, language: &Language) -> bool {
+        self.config_manager
+            .as_ref()
+            .is_some_and(|config| config.get_flags(language).container_pooling)
+    }
+
+    /// Whether a job gets a warmup run: its own `warmup`, else the language's flag
+    pub fn use_warmup(&self, job: &JobRequest) -> bool {
+        let flags = self.config_manager.as_ref().map(|config| config.get_flags(&job.language)).unwrap_or_default();
+        flags.warmup_for(job.warmup)
+    }
+
+    /// Run the program once on empty input and discard the run, so the first timed test
+    /// doesn't pay for loading the runtime and program from disk (each test is a new
+    /// process, so the JIT starts cold every time)
+    ///
+    /// A run that doesn't finish in time is killed; a failing run is fine (the input is
+    /// a dummy). Errors are those of reaching the container.
+    async fn warmup(&self, container_id: &str, job: &JobRequest) -> Result<()> {
+        let output = self
+            .execute_test_in_container(container_id, job, "", job.timeout_ms.min(WARMUP_MAX_MS), &mut LiveOutput::off())
+            .await?;
+        debug!(job_id = %job.id, execution_time_ms = output.execution_time_ms, timed_out = output.timed_out, "Warmup run finished");
+        if output.timed_out {
+            pool::kill_strays(&self.docker, container_id).await?;
+        }
+        Ok(())
+    }
+
+    /// Why a container stopped, if it is no longer running
+    async fn exit_reason(&self, container_id: &str) -> Option<String> {
+        let state = match self.docker.inspect_container(container_id, None).await {
+            Ok(info) => info.state?,
+            Err(bollard::errors::Error::DockerResponseServerError { status_code: 404, .. }) => {
+                return Some("container exited prematurely: it no longer exists".to_string());
+            }
+            Err(e) => {
+                debug!(container_id = %container_id, error = %e, "Failed to inspect container");
+                return None;
+            }
+        };
+        if state.running != Some(false) {
+            return None;
+        }
+        let mut reason = format!("container exited prematurely with code {}", state.exit_code.unwrap_or(-1));
+        if state.oom_killed == Some(true) {
+            reason.push_str(" (killed for exceeding its memory limit)");
+        }
+        Some(reason)
+    }
+
+    /// NVIDIA device request for GPU workers (None elsewhere)
+    fn get_device_requests(&self, language: &Language) -> Option<Vec<DeviceRequest>> {
+        if !self.gpu {
+            return None;
+        }
+        let devices = self
+            .config_manager
+            .as_ref()
+            .and_then(|cm| cm.get_config(language).ok())
+            .and_then(|config| config.gpu)
+            .map_or(1, |gpu| gpu.devices);
+        Some(vec![DeviceRequest {
+            driver: Some("nvidia".to_string()),
+            count: Some(devices as i64),
+            capabilities: Some(vec![vec!["gpu".to_string()]]),
+            ..Default::default()
+        }])
+    }
+
+    /// Host CPUs execution containers are pinned to, if any
+    fn get_cpuset(&self, language: &Language) -> Option<String> {
+        self.get_determinism(language)?.cpuset.clone()
+    }
+
     /// Ensure Docker image is available (pull if needed)
     /// 
     /// **Image Cache Health Check:**
@@ -271,212 +450,68 @@ impl DockerEngine {
         Ok(())
     }
 
-    /// Execute code in Docker container with hardened safety guarantees
-    /// 
-    /// **Safety Guarantees:**
-    /// - Input validation: Rejects oversized source code or test inputs
-    /// - Hard timeout: Enforced via tokio::time::timeout, kills container on timeout
-    /// - Guaranteed cleanup: Container removed even on panic/cancellation via Drop guard
-    /// - Error classification: Distinguishes timeout, runtime error, and infrastructure failure
-    /// - Partial output capture: Captures stdout/stderr even on timeout
-    pub async fn execute_in_container(
-        &self,
-        language: &Language,
-        source_code: &str,
-        input: &str,
-        timeout_ms: u64,
-    ) -> Result<TestExecutionOutput> {
-        // GUARDRAIL 1: Validate input sizes
-        if source_code.len() > MAX_SOURCE_CODE_BYTES {
-            bail!("Source code exceeds maximum size of {} bytes", MAX_SOURCE_CODE_BYTES);
-        }
-        if input.len() > MAX_TEST_INPUT_BYTES {
-            bail!("Test input exceeds maximum size of {} bytes", MAX_TEST_INPUT_BYTES);
-        }
-
+    /// Image name and Docker image id for a language, pulling the image if needed
+    pub async fn image_identity(&self, language: &Language) -> Result<(String, Option<String>)> {
         let image = self.get_image_name(language);
-        let container_name = format!("optimus-{}", uuid::Uuid::new_v4());
-
-        // Ensure image is available
         self.ensure_image(&image).await
             .context(format!("Failed to ensure Docker image '{}' is available", image))?;
+        let inspect = self.docker.inspect_image(&image).await
+            .context("Failed to inspect Docker image")?;
+        Ok((image, inspect.id))
+    }
 
-        // Prepare environment and command
-        let cmd = self.get_execution_command(language);
-        
-        // Create container configuration with LANGUAGE env var for universal runner
-        let env = vec![
-            format!("SOURCE_CODE={}", general_purpose::STANDARD.encode(source_code)),
-            format!("TEST_INPUT={}", general_purpose::STANDARD.encode(input)),
-            format!("LANGUAGE={}", format!("{}", language).to_lowercase()),
-        ];
-
-        // Get resource limits from config
-        let memory_limit = self.get_memory_limit(language);
-        let cpu_limit = self.get_cpu_limit(language);
+    /// Run a trusted shell script in a throwaway container of a language's image
+    ///
+    /// Used for toolchain probes, never for user code. Returns stdout and stderr combined.
+    pub async fn run_image_script(&self, language: &Language, script: &str, timeout: Duration) -> Result<String> {
+        let image = self.get_image_name(language);
+        self.ensure_image(&image).await
+            .context(format!("Failed to ensure Docker image '{}' is available", image))?;
 
-        let config = Config {
-            image: Some(image.clone()),
-            cmd: Some(cmd),
-            env: Some(env),
-            attach_stdout: Some(true),
-            attach_stderr: Some(true),
-            network_disabled: Some(true), // SECURITY: No network access
-            host_config: Some(bollard::models::HostConfig {
-                memory: Some(memory_limit),
-                nano_cpus: Some(cpu_limit),
-                readonly_rootfs: Some(false), // Allow writes to /tmp for compilation
-                ..Default::default()
-            }),
-            ..Default::default()
-        };
+        // The judge's own container settings, so the self-test checks the real sandbox
+        let cmd = vec!["/bin/bash".to_string(), "-c".to_string(), script.to_string()];
+        let config = self.sandbox_config(language, &image, cmd, Vec::new());
 
-        // Create container
+        let container_name = format!("optimus-probe-{}", uuid::Uuid::new_v4());
         let create_options = CreateContainerOptions {
             name: container_name.as_str(),
             platform: None,
         };
-
         let container = self.docker
             .create_container(Some(create_options), config)
             .await
             .context("Failed to create Docker container")?;
+        let _guard = ContainerGuard::new(&self.docker, container.id.clone());
 
-        let container_id = container.id.clone();
-        
-        // CRITICAL: Set up cleanup guard immediately after container creation
-        // This guarantees cleanup even if we panic or get cancelled
-        let guard = ContainerGuard::new(&self.docker, container_id.clone());
-
-        // Start execution timer
-        let start_time = Instant::now();
-
-        // Start container
         self.docker
-            .start_container(&container_id, None::<StartContainerOptions<String>>)
+            .start_container(&container.id, None::<StartContainerOptions<String>>)
             .await
             .context("Failed to start Docker container")?;
 
-        let mut timed_out = false;
-        let mut runtime_error = false;
-
-        // HARD TIMEOUT: Wrap execution in tokio::time::timeout
-        let timeout_duration = Duration::from_millis(timeout_ms);
-        
-        let execution_future = async {
-            let mut stdout = String::new();
-            let mut stderr = String::new();
-            let mut exit_code: Option<i64> = None;
-            
-            // Collect logs and wait for completion in parallel
-            let logs_options = Some(bollard::container::LogsOptions::<String> {
+        let collect = async {
+            let mut output: Vec<u8> = Vec::new();
+            let mut logs = self.docker.logs(&container.id, Some(bollard::container::LogsOptions::<String> {
                 stdout: true,
                 stderr: true,
                 follow: true,
                 ..Default::default()
-            });
-            
-            let mut logs_stream = self.docker.logs(&container_id, logs_options);
-            
-            // Collect all output
-            while let Some(output) = logs_stream.next().await {
-                match output {
-                    Ok(LogOutput::StdOut { message }) => {
-                        stdout.push_str(&String::from_utf8_lossy(&message));
-                    }
-                    Ok(LogOutput::StdErr { message }) => {
-                        stderr.push_str(&String::from_utf8_lossy(&message));
-                    }
-                    Err(e) => {
-                        eprintln!("⚠ Error reading container logs: {}", e);
-                        break;
-                    }
-                    _ => {}
-                }
-            }
-            
-            // Get exit code - wait for container to finish
-            let wait_options = WaitContainerOptions {
-                condition: "not-running",
-            };
-            
-            let mut wait_stream = self.docker.wait_container(&container_id, Some(wait_options));
-            if let Some(wait_result) = wait_stream.next().await {
-                if let Ok(response) = wait_result {
-                    exit_code = Some(response.status_code);
-                    println!("    Container exited with code: {}", response.status_code);
-                } else {
-                    eprintln!("    ⚠ Failed to get container exit code");
-                }
-            } else {
-                eprintln!("    ⚠ No wait response from container");
-            }
-            
-            (stdout, stderr, exit_code)
-        };
-
-        // Execute with hard timeout
-        let timeout_result = tokio::time::timeout(timeout_duration, execution_future).await;
-
-        let (stdout, stderr, _exit_code) = match timeout_result {
-            Ok((out, mut err, code)) => {
-                // Execution completed within timeout
-                // Classify error type based on exit code
-                println!("    Received exit code: {:?}", code);
-                if let Some(code) = code {
-                    if code != 0 {
-                        runtime_error = true;
-                        println!("    ✗ Runtime error detected (exit code: {})", code);
-                        
-                        // Special handling for common signals
-                        if code == 137 {
-                            err.push_str("\n[Container killed: likely OOM or exceeded memory limit]");
-                        } else if code == 139 {
-                            err.push_str("\n[Container killed: segmentation fault]");
-                        }
-                    } else {
-                        println!("    ✓ Container exited successfully (code 0)");
+            }));
+            while let Some(chunk) = logs.next().await {
+                match chunk {
+                    Ok(LogOutput::StdOut { message }) | Ok(LogOutput::StdErr { message }) => {
+                        output.extend_from_slice(&message);
                     }
-                } else {
-                    eprintln!("    ⚠ WARNING: No exit code captured from container!");
+                    Ok(_) => {}
+                    Err(e) => bail!("Failed to read container logs: {}", e),
                 }
-                
-                (out, err, code)
-            }
-            Err(_) => {
-                // TIMEOUT: Kill container immediately and capture partial output
-                timed_out = true;
-                
-                println!("    ⚠ Execution timed out after {}ms - killing container", timeout_ms);
-                
-                // Force kill the container
-                if let Err(e) = self.docker
-                    .kill_container(&container_id, None::<bollard::container::KillContainerOptions<String>>)
-                    .await
-                {
-                    eprintln!("    ⚠ Failed to kill timed-out container: {}", e);
-                }
-                
-                // Return empty output with timeout message
-                (String::new(), String::from("\n[Execution timed out]"), None)
             }
+            Ok(output)
         };
 
-        let execution_time_ms = start_time.elapsed().as_millis() as u64;
-
-        // Container cleanup happens automatically via Drop guard
-        // Explicitly keep guard alive until here
-        drop(guard);
-
-        Ok(TestExecutionOutput {
-            test_id: 0, // Will be set by executor
-            stdout,
-            stderr,
-            execution_time_ms,
-            timed_out,
-            runtime_error,
-            compilation_failed: false,
-        })
+        let output = tokio::time::timeout(timeout, collect)
+            .await
+            .map_err(|_| anyhow::anyhow!("Script timed out after {}ms", timeout.as_millis()))??;
+        Ok(String::from_utf8_lossy(&output).into_owned())
     }
 
     /// Compile code in a container (Phase 2: Compile-once execution)
@@ -487,30 +522,27 @@ impl DockerEngine {
     /// ## Arguments
     /// * `container_id` - ID of the running container
     /// * `language` - Programming language
+    /// * `compile` - The language's compile step (from languages.json)
     /// 
     /// ## Returns
     /// CompilationResult with success status and compilation output
-    #[tracing::instrument(skip(self), fields(language = %language))]
+    #[tracing::instrument(skip(self, compile), fields(language = %language))]
     pub async fn compile_in_container(
         &self,
         container_id: &str,
         language: &Language,
+        compile: &CompileSpec,
     ) -> Result<crate::evaluator::CompilationResult> {
         use bollard::exec::{CreateExecOptions, StartExecOptions};
         
         let start_time = Instant::now();
         debug!("Starting compilation for language: {}", language);
         
-        // Determine compilation command based on language
-        let compile_cmd = match language {
-            Language::Java => vec!["bash", "-c", "javac /code/Main.java 2>&1"],
-            Language::Rust => vec!["bash", "-c", "rustc /code/main.rs -o /code/main 2>&1"],
-            Language::Python => vec!["bash", "-c", "python3 -m py_compile /code/main.py 2>&1"],
-        };
+        let compile_cmd = compile.script();
         
         // Create exec instance for compilation
         let exec_config = CreateExecOptions {
-            cmd: Some(compile_cmd),
+            cmd: Some(vec!["bash", "-c", compile_cmd.as_str()]),
             attach_stdout: Some(true),
             attach_stderr: Some(true),
             ..Default::default()
@@ -529,24 +561,19 @@ impl DockerEngine {
         
         let output = self.docker.start_exec(&exec.id, Some(start_config)).await?;
         
-        let mut stdout = String::new();
-        let mut stderr = String::new();
+        // Compiler messages are capped like test output, keeping the first errors (a template
+        // error can fill megabytes); both streams count, since the compile script sends each
+        // stage's stderr to stdout (CompileSpec::script)
+        let mut messages = CappedBuffer::head(optimus_common::limits::current().engine_max_output_bytes);
         
         // Collect compilation output
         if let bollard::exec::StartExecResults::Attached { mut output, .. } = output {
             while let Some(msg) = output.next().await {
                 match msg {
-                    Ok(log_output) => {
-                        match log_output {
-                            LogOutput::StdOut { message } => {
-                                stdout.push_str(&String::from_utf8_lossy(&message));
-                            }
-                            LogOutput::StdErr { message } => {
-                                stderr.push_str(&String::from_utf8_lossy(&message));
-                            }
-                            _ => {}
-                        }
+                    Ok(LogOutput::StdOut { message }) | Ok(LogOutput::StdErr { message }) => {
+                        messages.push(&message);
                     }
+                    Ok(_) => {}
                     Err(e) => {
                         return Ok(crate::evaluator::CompilationResult::failure(
                             format!("Failed to read compilation output: {}", e),
@@ -560,6 +587,8 @@ impl DockerEngine {
             ));
         }
         
+        let stderr = String::from_utf8_lossy(&messages.finish().0).into_owned();
+
         // Check exit code
         let inspect = self.docker.inspect_exec(&exec.id).await?;
         let compilation_time_ms = start_time.elapsed().as_millis() as u64;
@@ -598,27 +627,29 @@ impl DockerEngine {
     /// 
     /// ## Arguments
     /// * `container_id` - ID of the running container with compiled code
-    /// * `language` - Programming language
+    /// * `job` - The job (its language and sanitizers decide the command)
     /// * `input` - Test input
     /// * `timeout_ms` - Timeout for this test execution
     /// 
     /// ## Returns
     /// TestExecutionOutput with execution results
-    #[tracing::instrument(skip(self, input), fields(language = %language, timeout_ms = timeout_ms))]
+    #[tracing::instrument(skip(self, job, input, live), fields(language = %job.language, timeout_ms = timeout_ms))]
     pub async fn execute_test_in_container(
         &self,
         container_id: &str,
-        language: &Language,
+        job: &JobRequest,
         input: &str,
         timeout_ms: u64,
+        live: &mut LiveOutput,
     ) -> Result<TestExecutionOutput> {
         use bollard::exec::{CreateExecOptions, StartExecOptions};
         
         debug!("Executing test in container with timeout {}ms", timeout_ms);
         
         // Validate input size
-        if input.len() > MAX_TEST_INPUT_BYTES {
-            bail!("Test input exceeds maximum size of {} bytes", MAX_TEST_INPUT_BYTES);
+        let max_input = optimus_common::limits::current().engine_max_test_input_bytes;
+        if input.len() > max_input {
+            bail!("Test input exceeds maximum size of {} bytes", max_input);
         }
         
         let start_time = Instant::now();
@@ -626,25 +657,26 @@ impl DockerEngine {
         // Encode input for the runner script
         let encoded_input = general_purpose::STANDARD.encode(input);
         
-        // Determine execution command based on language
-        // CRITICAL: Unset JAVA_TOOL_OPTIONS to prevent JVM noise in stderr
-        // Use parentheses to create a subshell so unset doesn't affect the container
-        let java_cmd = format!("(unset JAVA_TOOL_OPTIONS; echo '{}' | base64 -d | java -cp /code Main)", encoded_input);
-        let rust_cmd = format!("echo '{}' | base64 -d | /code/main", encoded_input);
-        let python_cmd = format!("echo '{}' | base64 -d | python3 -u /code/main.py", encoded_input);
-        
-        let exec_cmd = match language {
-            Language::Java => vec!["bash", "-c", &java_cmd],
-            Language::Rust => vec!["bash", "-c", &rust_cmd],
-            Language::Python => vec!["bash", "-c", &python_cmd],
+        let language = &job.language;
+        let program = sanitizer::program(program_command(language), job);
+        // The runner reports exit code, program time and resource usage (protocol v2)
+        let report_name = uuid::Uuid::new_v4().simple().to_string();
+        let cpu_secs = protocol::cpu_limit_secs(timeout_ms, self.get_cpus(language));
+        let test_cmd = protocol::test_command(&encoded_input, &program, cpu_secs, &report_name);
+        let test_cmd = match self.get_determinism(language) {
+            Some(determinism) => determinism::wrap_command(determinism, &test_cmd),
+            None => test_cmd,
         };
+        let exec_cmd = ["bash", "-c", &test_cmd];
         
-        // Create exec instance for test execution
+        // Create exec instance for test execution (the runner drops to the image's user
+        // for the program itself)
         let exec_config = CreateExecOptions {
             cmd: Some(exec_cmd.iter().map(|s| s.to_string()).collect()),
             attach_stdin: Some(true),
             attach_stdout: Some(true),
             attach_stderr: Some(true),
+            user: Some("root".to_string()),
             ..Default::default()
         };
         
@@ -659,15 +691,20 @@ impl DockerEngine {
             ..Default::default()
         };
         
-        let timeout_duration = Duration::from_millis(timeout_ms);
+        // The limit applies to program CPU time, under a wall-clock ceiling; the grace only
+        // covers exec overhead
+        let wall_limit_ms = protocol::wall_limit_ms(timeout_ms);
+        let timeout_duration = Duration::from_millis(wall_limit_ms) + protocol::overhead_grace();
         let mut timed_out = false;
         let mut runtime_error = false;
+        let mut failure = None;
         
         let execution_future = async {
             let output = self.docker.start_exec(&exec.id, Some(start_config)).await?;
             
-            let mut stdout = String::new();
-            let mut stderr = String::new();
+            // Raw bytes - decoded once at the end so split multi-byte chars survive
+            // (capped: see capture.rs)
+            let (mut stdout, mut stderr) = CappedBuffer::for_test();
             
             // Collect execution output
             if let bollard::exec::StartExecResults::Attached { mut output, .. } = output {
@@ -676,16 +713,18 @@ impl DockerEngine {
                         Ok(log_output) => {
                             match log_output {
                                 LogOutput::StdOut { message } => {
-                                    stdout.push_str(&String::from_utf8_lossy(&message));
+                                    live.push(OutputStream::Stdout, &message);
+                                    stdout.push(&message);
                                 }
                                 LogOutput::StdErr { message } => {
-                                    stderr.push_str(&String::from_utf8_lossy(&message));
+                                    live.push(OutputStream::Stderr, &message);
+                                    stderr.push(&message);
                                 }
                                 _ => {}
                             }
                         }
                         Err(e) => {
-                            stderr.push_str(&format!("\n[Execution error: {}]", e));
+                            stderr.push(format!("\n[Execution error: {}]", e).as_bytes());
                             break;
                         }
                     }
@@ -696,35 +735,63 @@ impl DockerEngine {
             let inspect = self.docker.inspect_exec(&exec.id).await?;
             let exit_code = inspect.exit_code;
             
-            Ok::<(String, String, Option<i64>), anyhow::Error>((stdout, stderr, exit_code))
+            Ok::<_, anyhow::Error>((stdout.finish(), stderr.finish(), exit_code))
         };
         
         // Execute with timeout
+        let monitor = ResourceMonitor::start(&self.docker, container_id);
         let timeout_result = tokio::time::timeout(timeout_duration, execution_future).await;
+        let resource_samples = match monitor {
+            Some(monitor) => Some(monitor.finish().await),
+            None => None,
+        };
         
-        let (stdout, stderr, _exit_code) = match timeout_result {
-            Ok(Ok((out, err, code))) => {
-                // Check exit code for runtime errors
-                if let Some(code) = code {
-                    if code != 0 {
-                        runtime_error = true;
-                    }
-                }
-                (out, err, code)
-            }
+        let ((stdout_bytes, stdout_total), (stderr_bytes, stderr_total), exit_code) = match timeout_result {
+            Ok(Ok((out, err, code))) => (out, err, code),
             Ok(Err(e)) => {
                 // Execution error
                 runtime_error = true;
-                (String::new(), format!("Execution failed: {}", e), None)
+                failure = Some(TestFailure::SandboxError);
+                let message = format!("{}{}", crate::evaluator::SANDBOX_FAILURE_PREFIX, e).into_bytes();
+                ((Vec::new(), 0), (message, 0), None)
             }
             Err(_) => {
                 // Timeout
                 timed_out = true;
-                (String::new(), "[Execution timed out]".to_string(), None)
+                ((Vec::new(), 0), (b"[Execution timed out]".to_vec(), 0), None)
             }
         };
+
+        let wall_time_ms = start_time.elapsed().as_millis() as u64;
+        let report = match exit_code {
+            Some(_) => self.take_report(container_id, &report_name, exit_code, wall_time_ms, language).await,
+            None => None,
+        };
+        let stdout = String::from_utf8_lossy(&stdout_bytes).into_owned();
+        let mut stderr = String::from_utf8_lossy(&stderr_bytes).into_owned();
+
+        // Prefer the runner's report; fall back to the exec's exit code
+        let exit_code = match (report, exit_code) {
+            (Some(report), _) => Some(report.exit_code as i64),
+            (None, code) => code,
+        };
+        if exit_code.is_some_and(|code| code != 0) {
+            runtime_error = true;
+        }
+        let oom_killed = protocol::oom_killed(report, exit_code);
+        if oom_killed {
+            stderr.push('\n');
+            stderr.push_str(crate::evaluator::OOM_NOTE);
+        }
         
-        let execution_time_ms = start_time.elapsed().as_millis() as u64;
+        let (execution_time_ms, overhead_ms) = match report.and_then(|r| r.wall_us) {
+            Some(us) => (us / 1000, Some(wall_time_ms.saturating_sub(us / 1000))),
+            None => (wall_time_ms, None),
+        };
+        let cpu_time_ms = report.and_then(|r| r.cpu_time_ms());
+        if protocol::over_time_limit(timeout_ms, wall_limit_ms, cpu_time_ms, execution_time_ms) {
+            timed_out = true;
+        }
         
         // Log execution metrics
         if timed_out {
@@ -741,6 +808,7 @@ impl DockerEngine {
         } else {
             debug!(
                 execution_time_ms = execution_time_ms,
+                overhead_ms = ?overhead_ms,
                 "Test execution completed successfully"
             );
         }
@@ -753,25 +821,220 @@ impl DockerEngine {
             timed_out,
             runtime_error,
             compilation_failed: false,
+            stdout_bytes: Some(stdout_bytes),
+            stderr_bytes: Some(stderr_bytes),
+            stdout_total_bytes: Some(stdout_total),
+            stderr_total_bytes: Some(stderr_total),
+            overhead_ms,
+            cpu_time_ms,
+            memory_kb: monitor::peak_memory_kb(report.and_then(|r| r.memory_kb()), resource_samples.as_deref()),
+            resource_samples,
+            oom_killed,
+            failure,
+            checker: None,
+        })
+    }
+
+    /// Execute one interactive test: the program in its container, talking to the
+    /// interactor in its own (see interactor.rs)
+    ///
+    /// The interactor's verdict comes back as the output's checker outcome.
+    #[tracing::instrument(skip(self, job, test_case, live), fields(language = %job.language, timeout_ms = timeout_ms))]
+    async fn execute_interactive_test(
+        &self,
+        container_id: &str,
+        interactor_id: &str,
+        job: &JobRequest,
+        test_case: &optimus_common::types::TestCase,
+        timeout_ms: u64,
+        live: &mut LiveOutput,
+    ) -> Result<TestExecutionOutput> {
+        use bollard::exec::{CreateExecOptions, StartExecOptions, StartExecResults};
+
+        let max_input = optimus_common::limits::current().engine_max_test_input_bytes;
+        if test_case.input.len() > max_input {
+            bail!("Test input exceeds maximum size of {} bytes", max_input);
+        }
+        let files = [
+            (checker::INPUT_PATH, test_case.input.as_bytes().to_vec()),
+            (checker::EXPECTED_PATH, checker::expected_bytes(&test_case.expected_output, job.output_mode)),
+        ];
+        for (path, content) in &files {
+            if let Err(e) = self.write_file_to_container(interactor_id, path, content).await {
+                return Ok(judge_unavailable_output(test_case.id, &format!("Interactor setup failed: {}", e)));
+            }
+        }
+
+        let language = &job.language;
+        let program = sanitizer::program(program_command(language), job);
+        let report_name = uuid::Uuid::new_v4().simple().to_string();
+        let cpu_secs = protocol::cpu_limit_secs(timeout_ms, self.get_cpus(language));
+        let test_cmd = protocol::interactive_command(&program, cpu_secs, &report_name);
+        let test_cmd = match self.get_determinism(language) {
+            Some(determinism) => determinism::wrap_command(determinism, &test_cmd),
+            None => test_cmd,
+        };
+        let judge_cmd = interactor::command(program_command(language));
+        let attached = |command: &str, user: Option<&str>| CreateExecOptions {
+            cmd: Some(vec!["bash".to_string(), "-c".to_string(), command.to_string()]),
+            attach_stdin: Some(true),
+            attach_stdout: Some(true),
+            attach_stderr: Some(true),
+            user: user.map(str::to_string),
+            ..Default::default()
+        };
+        let judge_exec = self.docker
+            .create_exec(interactor_id, attached(&judge_cmd, None))
+            .await
+            .context("Failed to create exec for interactor")?;
+        let exec = self.docker
+            .create_exec(container_id, attached(&test_cmd, Some("root")))
+            .await
+            .context("Failed to create exec for test execution")?;
+
+        let start_time = Instant::now();
+        let wall_limit_ms = protocol::wall_limit_ms(timeout_ms);
+        let timeout_duration = Duration::from_millis(wall_limit_ms) + protocol::overhead_grace();
+        let conversation = async {
+            let start = || Some(StartExecOptions { detach: false, ..Default::default() });
+            let judge = self.docker.start_exec(&judge_exec.id, start()).await?;
+            let program = self.docker.start_exec(&exec.id, start()).await?;
+            let (
+                StartExecResults::Attached { output: judge_output, input: judge_input },
+                StartExecResults::Attached { output, input },
+            ) = (judge, program)
+            else {
+                bail!("Exec started detached");
+            };
+            let transcript = interactor::relay(
+                interactor::Side { output, input },
+                interactor::Side { output: judge_output, input: judge_input },
+                live,
+            )
+            .await;
+            let exit_code = self.docker.inspect_exec(&exec.id).await?.exit_code;
+            let judge_exit_code = self.docker.inspect_exec(&judge_exec.id).await?.exit_code;
+            Ok::<_, anyhow::Error>((transcript, exit_code, judge_exit_code))
+        };
+
+        let monitor = ResourceMonitor::start(&self.docker, container_id);
+        let result = tokio::time::timeout(timeout_duration, conversation).await;
+        let resource_samples = match monitor {
+            Some(monitor) => Some(monitor.finish().await),
+            None => None,
+        };
+
+        let mut timed_out = false;
+        let mut runtime_error = false;
+        let mut failure = None;
+        let mut checker_outcome = None;
+        let ((stdout_bytes, stdout_total), (stderr_bytes, stderr_total), exit_code) = match result {
+            Ok(Ok((transcript, exit_code, judge_exit_code))) => {
+                if let Some(error) = &transcript.error {
+                    runtime_error = true;
+                    warn!(error = %error, "Interactive test stream failed");
+                }
+                checker_outcome = Some(interactor::outcome(judge_exit_code, transcript.judge));
+                let mut stderr = transcript.stderr;
+                if let Some(error) = transcript.error {
+                    stderr.push(format!("\n{}{}", crate::evaluator::SANDBOX_FAILURE_PREFIX, error).as_bytes());
+                }
+                (transcript.stdout.finish(), stderr.finish(), exit_code)
+            }
+            Ok(Err(e)) => {
+                runtime_error = true;
+                failure = Some(TestFailure::SandboxError);
+                let message = format!("{}{}", crate::evaluator::SANDBOX_FAILURE_PREFIX, e).into_bytes();
+                ((Vec::new(), 0), (message, 0), None)
+            }
+            Err(_) => {
+                // Either side may still be waiting on the other; neither outlives the test
+                timed_out = true;
+                for id in [container_id, interactor_id] {
+                    if let Err(e) = pool::kill_strays(&self.docker, id).await {
+                        warn!(error = %e, "Failed to stop an interactive test after its timeout");
+                    }
+                }
+                ((Vec::new(), 0), (b"[Execution timed out]".to_vec(), 0), None)
+            }
+        };
+
+        // Time waiting on the interactor counts toward the wall-clock ceiling
+        let wall_time_ms = start_time.elapsed().as_millis() as u64;
+        let report = match exit_code {
+            Some(_) => self.take_report(container_id, &report_name, exit_code, wall_time_ms, language).await,
+            None => None,
+        };
+        let stdout = String::from_utf8_lossy(&stdout_bytes).into_owned();
+        let mut stderr = String::from_utf8_lossy(&stderr_bytes).into_owned();
+        let exit_code = match (report, exit_code) {
+            (Some(report), _) => Some(report.exit_code as i64),
+            (None, code) => code,
+        };
+        if exit_code.is_some_and(|code| code != 0) {
+            runtime_error = true;
+        }
+        let oom_killed = protocol::oom_killed(report, exit_code);
+        if oom_killed {
+            stderr.push('\n');
+            stderr.push_str(crate::evaluator::OOM_NOTE);
+        }
+
+        let (execution_time_ms, overhead_ms) = match report.and_then(|r| r.wall_us) {
+            Some(us) => (us / 1000, Some(wall_time_ms.saturating_sub(us / 1000))),
+            None => (wall_time_ms, None),
+        };
+        let cpu_time_ms = report.and_then(|r| r.cpu_time_ms());
+        if protocol::over_time_limit(timeout_ms, wall_limit_ms, cpu_time_ms, execution_time_ms) {
+            timed_out = true;
+        }
+        debug!(
+            execution_time_ms = execution_time_ms,
+            timed_out = timed_out,
+            runtime_error = runtime_error,
+            judge_exit_code = ?checker_outcome.as_ref().and_then(|o| o.exit_code),
+            "Interactive test finished"
+        );
+
+        Ok(TestExecutionOutput {
+            test_id: test_case.id,
+            stdout,
+            stderr,
+            execution_time_ms,
+            timed_out,
+            runtime_error,
+            compilation_failed: false,
+            stdout_bytes: Some(stdout_bytes),
+            stderr_bytes: Some(stderr_bytes),
+            stdout_total_bytes: Some(stdout_total),
+            stderr_total_bytes: Some(stderr_total),
+            overhead_ms,
+            cpu_time_ms,
+            memory_kb: monitor::peak_memory_kb(report.and_then(|r| r.memory_kb()), resource_samples.as_deref()),
+            resource_samples,
+            oom_killed,
+            failure,
+            checker: checker_outcome,
         })
     }
 
     /// Execute a complete job in a single container (Phase 2: Compile-once execution)
     /// 
     /// This is the new execution path that:
-    /// 1. Creates one container
-    /// 2. Compiles code once
+    /// 1. Creates one container (or reuses a pooled one, see pool.rs)
+    /// 2. Compiles code once (languages with a `compile` step in languages.json)
     /// 3. Executes all test cases against the compiled artifact
-    /// 4. Cleans up the container
+    /// 4. Cleans up the container (or resets it and returns it to the pool)
     /// 
     /// ## Arguments
     /// * `job` - The job request with source code and test cases
-    /// * `redis_conn` - Redis connection for cancellation checks
+    /// * `redis_conn` - Redis connection for progress (None in standalone mode)
+    /// * `cancel` - Fires when the job is cancelled; compilation and tests stop at once
     /// 
     /// ## Returns
     /// Vector of test execution outputs (one per test case)
     #[tracing::instrument(
-        skip(self, job, redis_conn),
+        skip(self, job, redis_conn, cancel, progress),
         fields(
             job_id = %job.id,
             language = %job.language,
@@ -782,7 +1045,9 @@ impl DockerEngine {
     pub async fn execute_job_in_single_container(
         &self,
         job: &JobRequest,
-        redis_conn: &mut redis::aio::ConnectionManager,
+        mut redis_conn: Option<&mut redis::aio::ConnectionManager>,
+        cancel: &CancellationToken,
+        progress: &ProgressReporter<'_>,
     ) -> Vec<TestExecutionOutput> {
         let job_start_time = std::time::Instant::now();
         
@@ -799,98 +1064,145 @@ impl DockerEngine {
         );
 
         // Check for early cancellation
-        match optimus_common::redis::is_job_cancelled(redis_conn, &job.id).await {
-            Ok(true) => {
-                println!("  ⚠ Job cancelled before execution");
-                return Vec::new();
-            }
-            Err(e) => {
-                eprintln!("  ⚠ Failed to check cancellation: {}", e);
-            }
-            _ => {}
+        if cancel.is_cancelled() {
+            println!("  ⚠ Job cancelled before execution");
+            return Vec::new();
         }
 
-        let image = self.get_image_name(&job.language);
-        let container_name = format!("optimus-{}", uuid::Uuid::new_v4());
-
-        // Ensure image is available
-        if let Err(e) = self.ensure_image(&image).await {
-            eprintln!("  ✗ Failed to ensure image: {}", e);
-            return self.create_compilation_error_outputs(&job.test_cases, &format!("Failed to pull image: {}", e));
+        // An earlier attempt checkpointed every test: nothing left to compile or run
+        if let Some(outputs) = progress.restored_all(&job.test_cases) {
+            println!("  ✓ All tests restored from checkpoint");
+            return outputs;
         }
 
-        // Prepare environment - write source code to container
-        let env = vec![
-            format!("SOURCE_CODE={}", general_purpose::STANDARD.encode(&job.source_code)),
-            format!("LANGUAGE={}", format!("{}", job.language).to_lowercase()),
-        ];
-
-        let memory_limit = self.get_memory_limit(&job.language);
-        let cpu_limit = self.get_cpu_limit(&job.language);
-
-        // Create container configuration
-        let config = Config {
-            image: Some(image.clone()),
-            cmd: Some(vec!["/bin/bash".to_string(), "-c".to_string(), "sleep 300".to_string()]), // Keep container alive with bash
-            entrypoint: Some(vec![]),  // Override entrypoint to avoid runner.sh
-            env: Some(env),
-            attach_stdout: Some(true),
-            attach_stderr: Some(true),
-            network_disabled: Some(true),
-            host_config: Some(bollard::models::HostConfig {
-                memory: Some(memory_limit),
-                nano_cpus: Some(cpu_limit),
-                readonly_rootfs: Some(false),
-                ..Default::default()
-            }),
-            working_dir: Some("/code".to_string()),
-            ..Default::default()
-        };
+        let image = self.get_image_name(&job.language);
+        let pooled = self.use_pool(&job.language);
+        let keep_alive = keep_alive_for(job);
 
-        // Create container
-        let create_options = CreateContainerOptions {
-            name: container_name.as_str(),
-            platform: None,
+        let lease = if pooled {
+            let (lease, expired) = pool::checkout(&job.language, &image, keep_alive);
+            pool::remove(&self.docker, expired).await;
+            lease
+        } else {
+            None
         };
-
-        let container = match self.docker.create_container(Some(create_options), config).await {
-            Ok(c) => c,
-            Err(e) => {
-                eprintln!("  ✗ Failed to create container: {}", e);
-                return self.create_compilation_error_outputs(&job.test_cases, &format!("Container creation failed: {}", e));
+        let lease = match lease {
+            Some(lease) => {
+                println!("  ♻ Reusing pooled container");
+                lease
+            }
+            None => {
+                // Pooled containers outlive the job, so they get at least the pool's keep-alive
+                let keep_alive = if pooled { keep_alive.max(pool::KEEP_ALIVE) } else { keep_alive };
+                match self.start_job_container(job, &image, pooled, keep_alive).await {
+                    Ok(container_id) => pool::lease_new(container_id, keep_alive),
+                    Err(message) => return self.create_compilation_error_outputs(&job.test_cases, &message),
+                }
             }
         };
-
-        let container_id = container.id.clone();
-        let _guard = ContainerGuard::new(&self.docker, container_id.clone());
-
-        // Start container
-        if let Err(e) = self.docker.start_container(&container_id, None::<StartContainerOptions<String>>).await {
-            eprintln!("  ✗ Failed to start container: {}", e);
-            return self.create_compilation_error_outputs(&job.test_cases, &format!("Container start failed: {}", e));
-        }
+        let container_id = lease.container_id.clone();
+        let mut guard = ContainerGuard::new(&self.docker, container_id.clone());
 
         // Write source code to container
-        if let Err(e) = self.write_source_to_container(&container_id, &job.language, &job.source_code).await {
+        if let Err(e) = self.write_source_to_container(&container_id, notebook::source_file(job), &job.source_code).await {
             eprintln!("  ✗ Failed to write source code: {}", e);
-            return self.create_compilation_error_outputs(&job.test_cases, &format!("Source write failed: {}", e));
+            let message = match self.exit_reason(&container_id).await {
+                Some(reason) => reason,
+                None => format!("Source write failed: {}", e),
+            };
+            return self.create_compilation_error_outputs(&job.test_cases, &message);
         }
 
-        println!("→ Compiling source code...");
+        // Step 1: Compile code, unless the language is interpreted (no exec round-trip);
+        // a notebook is converted to a script first
+        let compile = self.get_compile(&job.language).map(|compile| sanitizer::compile(compile, job));
+        if let Some(compile) = notebook::compile(compile, job) {
+            println!("→ Compiling source code...");
+            progress.compiling(redis_conn.as_deref_mut()).await;
         
-        // Step 1: Compile code
-        let compilation_result = match self.compile_in_container(&container_id, &job.language).await {
-            Ok(result) => result,
-            Err(e) => {
-                eprintln!("  ✗ Compilation process failed: {}", e);
-                return self.create_compilation_error_outputs(&job.test_cases, &format!("Compilation process error: {}", e));
+            // Dropped (with the container) if the job is cancelled meanwhile
+            let compile_started = Instant::now();
+            let compilation = tokio::select! {
+                compilation = tokio::time::timeout(COMPILE_BUDGET, self.compile_in_container(&container_id, &job.language, &compile)) => compilation,
+                _ = cancel.cancelled() => {
+                    println!("  ⚠ Job cancelled during compilation");
+                    return Vec::new();
+                }
+            };
+            let compilation_result = match compilation {
+                Ok(Ok(result)) => {
+                    progress.record_compile(compile_started.elapsed());
+                    result
+                }
+                Ok(Err(e)) => {
+                    eprintln!("  ✗ Compilation process failed: {}", e);
+                    let message = match self.exit_reason(&container_id).await {
+                        Some(reason) => reason,
+                        None => format!("Compilation process error: {}", e),
+                    };
+                    return self.create_compilation_error_outputs(&job.test_cases, &message);
+                }
+                Err(_) => {
+                    eprintln!("  ✗ Compilation exceeded {}s", COMPILE_BUDGET.as_secs());
+                    return self.create_compilation_error_outputs(
+                        &job.test_cases,
+                        &format!("Compilation exceeded the {}s budget", COMPILE_BUDGET.as_secs()),
+                    );
+                }
+            };
+
+            // If compilation failed, return all tests as failed (unless the container died under it)
+            if !compilation_result.success {
+                if let Some(reason) = self.exit_reason(&container_id).await {
+                    warn!(job_id = %job.id, reason = %reason, "Compile-once container stopped during compilation");
+                    return self.create_compilation_error_outputs(&job.test_cases, &reason);
+                }
+                println!("  ✗ Compilation failed - marking all tests as failed");
+                return self.create_compilation_error_outputs(&job.test_cases, &compilation_result.stderr);
             }
+        } else {
+            println!("→ No compile step for {}", job.language);
+        }
+
+        // Special judge: compile the checker (or interactor) in a container of its own
+        let mut checker_guard = None;
+        let judge = match (&job.checker, &job.interactor) {
+            (Some(source_code), _) => Some(("Checker", source_code)),
+            (None, Some(source_code)) => Some(("Interactor", source_code)),
+            (None, None) => None,
+        };
+        let checker = match judge {
+            Some((role, source_code)) => {
+                println!("→ Preparing {}...", role.to_lowercase());
+                let started = self.start_judge(role, job, &image, source_code, keep_alive).await;
+                match &started {
+                    Ok(checker_id) => checker_guard = Some(ContainerGuard::new(&self.docker, checker_id.clone())),
+                    Err(message) => {
+                        println!("  ✗ {}", message.lines().next().unwrap_or(""));
+                        warn!(job_id = %job.id, role = role, error = %message, "Judge unavailable");
+                    }
+                }
+                Some(started)
+            }
+            None => None,
         };
 
-        // If compilation failed, return all tests as failed
-        if !compilation_result.success {
-            println!("  ✗ Compilation failed - marking all tests as failed");
-            return self.create_compilation_error_outputs(&job.test_cases, &compilation_result.stderr);
+        // Warmup: one untimed run before the timed tests (separate process: warms the file
+        // cache, not the JIT)
+        if self.use_warmup(job) {
+            println!("→ Warmup run...");
+            let warmup = tokio::select! {
+                warmup = self.warmup(&container_id, job) => warmup,
+                _ = cancel.cancelled() => {
+                    println!("  ⚠ Job cancelled during warmup");
+                    return Vec::new();
+                }
+            };
+            // Best effort: the tests report a container that broke during it
+            if let Err(e) = warmup {
+                println!("  ⚠ Warmup failed: {}", e);
+                warn!(job_id = %job.id, error = %e, "Warmup run failed");
+            }
         }
 
         println!();
@@ -899,44 +1211,53 @@ impl DockerEngine {
 
         // Step 2: Execute all test cases
         let mut outputs = Vec::new();
-
-        for (idx, test_case) in job.test_cases.iter().enumerate() {
-            // Check for cancellation between tests
-            match optimus_common::redis::is_job_cancelled(redis_conn, &job.id).await {
-                Ok(true) => {
-                    println!("  ⚠ Job cancelled - stopping at test {}/{}", idx + 1, job.test_cases.len());
-                    break;
-                }
-                Err(e) => {
-                    eprintln!("  ⚠ Failed to check cancellation: {}", e);
-                }
-                _ => {}
+        let mut exited = None;
+
+        'tests: for (idx, test_case) in job.test_cases.iter().enumerate() {
+            // Already completed by an earlier attempt
+            if let Some(output) = progress.restored(test_case.id) {
+                println!("  Skipping test {} (id: {}) - restored from checkpoint", idx + 1, test_case.id);
+                outputs.push(output);
+                continue;
             }
 
             println!("  Executing test {} (id: {})", idx + 1, test_case.id);
 
-            let result = self.execute_test_in_container(
-                &container_id,
-                &job.language,
-                &test_case.input,
-                job.timeout_ms,
-            ).await;
-
-            let mut output = match result {
-                Ok(output) => output,
-                Err(e) => {
-                    eprintln!("    ✗ Test execution error: {}", e);
-                    TestExecutionOutput {
-                        test_id: test_case.id,
-                        stdout: String::new(),
-                        stderr: format!("Test execution error: {}", e),
-                        execution_time_ms: 0,
-                        timed_out: false,
-                        runtime_error: true,
-                        compilation_failed: false,
+            let mut live = progress.live_output(redis_conn.as_deref(), test_case.id);
+            let mut retries_left = test_infra_retries();
+            let mut output = loop {
+                let run = async {
+                    match (&job.interactor, &checker) {
+                        (Some(_), Some(Ok(interactor_id))) => {
+                            self.execute_interactive_test(&container_id, interactor_id, job, test_case, job.timeout_ms, &mut live).await
+                        }
+                        // No interactor to talk to: the test is an interactor error
+                        (Some(_), Some(Err(message))) => Ok(judge_unavailable_output(test_case.id, message)),
+                        _ => self.execute_test_in_container(&container_id, job, &test_case.input, job.timeout_ms, &mut live).await,
                     }
+                };
+                let result = tokio::select! {
+                    result = run => result,
+                    _ = cancel.cancelled() => {
+                        println!("  ⚠ Job cancelled - stopping at test {}/{}", idx + 1, job.test_cases.len());
+                        break 'tests;
+                    }
+                };
+                let output = result.unwrap_or_else(|e| sandbox_failure_output(test_case.id, &e));
+                // Docker failed rather than the program: run just this test again, unless
+                // the container itself is gone (handled below for every remaining test)
+                if retries_left > 0
+                    && crate::evaluator::is_sandbox_failure(&output)
+                    && self.exit_reason(&container_id).await.is_none()
+                {
+                    retries_left -= 1;
+                    println!("    ⚠ Sandbox failure, retrying: {}", output.stderr.lines().next().unwrap_or(""));
+                    warn!(job_id = %job.id, test_id = test_case.id, error = %output.stderr, "Sandbox failure - retrying test");
+                    continue;
                 }
+                break output;
             };
+            live.finish().await;
 
             output.test_id = test_case.id;
 
@@ -951,7 +1272,43 @@ impl DockerEngine {
                 println!("    stderr: {}", output.stderr.lines().next().unwrap_or(""));
             }
 
+            // A dead container fails every remaining test the same way; not checkpointed,
+            // so a retry runs them again
+            if output.runtime_error {
+                if let Some(reason) = self.exit_reason(&container_id).await {
+                    println!("    ✗ {}", reason);
+                    warn!(job_id = %job.id, test_id = test_case.id, reason = %reason, "Compile-once container stopped mid-job");
+                    exited = Some(reason);
+                    break;
+                }
+            }
+
+            // An interactor judged the test as it ran
+            if let Some(checker) = checker.as_ref().filter(|_| job.interactor.is_none()) {
+                if !output.runtime_error && !output.timed_out {
+                    let outcome = match checker {
+                        Ok(checker_id) => self.run_checker(checker_id, job, test_case, &output).await,
+                        Err(message) => CheckerOutcome::error(message.clone()),
+                    };
+                    println!("    Checker exit code: {:?}", outcome.exit_code);
+                    output.checker = Some(outcome);
+                }
+            }
+
+            progress.record(redis_conn.as_deref_mut(), &output).await;
             outputs.push(output);
+            chaos::crash_point(&job.id);
+        }
+        drop(checker_guard);
+
+        if let Some(reason) = &exited {
+            for test_case in &job.test_cases {
+                if !outputs.iter().any(|o| o.test_id == test_case.id) {
+                    outputs.push(progress.restored(test_case.id).unwrap_or_else(|| premature_exit_output(test_case.id, reason)));
+                }
+            }
+        } else if !cancel.is_cancelled() {
+            self.sample_timing_variance(job, &container_id, &outputs, redis_conn).await;
         }
 
         println!();
@@ -969,6 +1326,13 @@ impl DockerEngine {
             "Completed compile-once job execution"
         );
         
+        // Pooled containers are reset and kept for the next job (not after a cancellation,
+        // whose abandoned test may still be running inside)
+        if pooled && !cancel.is_cancelled() && pool::checkin(&self.docker, &job.language, &image, lease).await {
+            guard.disarm();
+            return outputs;
+        }
+
         // Explicitly cleanup container before returning
         let remove_options = RemoveContainerOptions {
             force: true,
@@ -982,56 +1346,302 @@ impl DockerEngine {
         outputs
     }
 
-    /// Helper to write source code to container filesystem
+    /// Container settings of the judge sandbox for a language
+    fn sandbox_config(&self, language: &Language, image: &str, cmd: Vec<String>, env: Vec<String>) -> Config<String> {
+        Config {
+            image: Some(image.to_string()),
+            cmd: Some(cmd),
+            entrypoint: Some(vec![]),  // Override entrypoint to avoid runner.sh
+            env: Some(env),
+            attach_stdout: Some(true),
+            attach_stderr: Some(true),
+            network_disabled: Some(true),
+            host_config: Some(bollard::models::HostConfig {
+                memory: Some(self.get_memory_limit(language)),
+                nano_cpus: Some(self.get_cpu_limit(language)),
+                cpuset_cpus: self.get_cpuset(language),
+                device_requests: self.get_device_requests(language),
+                readonly_rootfs: Some(false),
+                // Docker's init (tini) as PID 1 reaps orphaned test processes and handles signals
+                init: Some(true),
+                ..Default::default()
+            }),
+            working_dir: Some("/code".to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Create and start a compile-once container for a job; the error is reported to every test
+    ///
+    /// Pooled containers are shared by later jobs, so they carry nothing job-specific.
+    async fn start_job_container(
+        &self,
+        job: &JobRequest,
+        image: &str,
+        pooled: bool,
+        keep_alive: Duration,
+    ) -> std::result::Result<String, String> {
+        let container_name = format!("optimus-{}", uuid::Uuid::new_v4());
+
+        // Ensure image is available
+        if let Err(e) = self.ensure_image(image).await {
+            eprintln!("  ✗ Failed to ensure image: {}", e);
+            return Err(format!("Failed to pull image: {}", e));
+        }
+
+        // Prepare environment - write source code to container
+        let mut env = vec![format!("LANGUAGE={}", format!("{}", job.language).to_lowercase())];
+        if !pooled {
+            env.push(format!("SOURCE_CODE={}", general_purpose::STANDARD.encode(&job.source_code)));
+        }
+        if let Some(determinism) = self.get_determinism(&job.language) {
+            env.extend(determinism::env_vars(determinism));
+        }
+
+        // Keep-alive sized for the job; exec'd tests run beside it
+        let cmd = vec!["sleep".to_string(), keep_alive.as_secs().to_string()];
+        let config = self.sandbox_config(&job.language, image, cmd, env);
+
+        // Create container
+        let create_options = CreateContainerOptions {
+            name: container_name.as_str(),
+            platform: None,
+        };
+
+        chaos::docker_fault("create_container").map_err(|e| e.to_string())?;
+        let container = match self.docker.create_container(Some(create_options), config).await {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("  ✗ Failed to create container: {}", e);
+                return Err(format!("Container creation failed: {}", e));
+            }
+        };
+
+        // Start container
+        if let Err(e) = self.docker.start_container(&container.id, None::<StartContainerOptions<String>>).await {
+            eprintln!("  ✗ Failed to start container: {}", e);
+            pool::remove(&self.docker, vec![container.id]).await;
+            return Err(format!("Container start failed: {}", e));
+        }
+        if let Err(e) = pool::record_keep_alive(&self.docker, &container.id).await {
+            pool::remove(&self.docker, vec![container.id]).await;
+            return Err(format!("Container start failed: {}", e));
+        }
+
+        Ok(container.id)
+    }
+
+    /// Repeat the first test of sampled jobs and publish how much its time varies
+    ///
+    /// Runs in the job's own container after all tests, so it measures exactly the
+    /// conditions (pinning, ASLR, environment) the job was judged under.
+    async fn sample_timing_variance(
+        &self,
+        job: &JobRequest,
+        container_id: &str,
+        outputs: &[TestExecutionOutput],
+        redis_conn: Option<&mut redis::aio::ConnectionManager>,
+    ) {
+        let Some(redis_conn) = redis_conn else { return };
+        let Some(determinism) = self.get_determinism(&job.language) else { return };
+        let (Some(test_case), Some(first)) = (job.test_cases.first(), outputs.first()) else { return };
+        if !determinism::should_sample(&job.id, determinism)
+            || first.timed_out
+            || first.runtime_error
+            || first.execution_time_ms > determinism::VARIANCE_MAX_TEST_MS
+        {
+            return;
+        }
+
+        let mut times = vec![first.execution_time_ms];
+        for _ in 1..determinism::VARIANCE_REPEATS {
+            match self.execute_test_in_container(container_id, job, &test_case.input, job.timeout_ms, &mut LiveOutput::off()).await {
+                Ok(output) if !output.timed_out && !output.runtime_error => times.push(output.execution_time_ms),
+                _ => return,
+            }
+        }
+
+        let Some(cv) = determinism::coefficient_of_variation(&times) else { return };
+        let pinned = determinism.cpuset.is_some();
+        debug!(job_id = %job.id, times_ms = ?times, cv = cv, pinned = pinned, "Sampled timing variance");
+        if let Err(e) = optimus_common::redis::publish_timing_variance(redis_conn, &job.language, pinned, cv).await {
+            warn!(job_id = %job.id, error = %e, "Failed to publish timing variance");
+        }
+    }
+
+    /// Helper to write source code to container filesystem, as `file` in /code
     async fn write_source_to_container(
         &self,
         container_id: &str,
-        language: &Language,
+        file: &str,
         source_code: &str,
     ) -> Result<()> {
-        use bollard::exec::{CreateExecOptions, StartExecOptions};
-        
-        let filename = match language {
-            Language::Java => "Main.java",
-            Language::Rust => "main.rs",
-            Language::Python => "main.py",
+        self.write_file_to_container(container_id, &format!("/code/{}", file), source_code.as_bytes())
+            .await
+            .context("Failed to write source code to container")
+    }
+
+    /// Write a file in the container, streaming the content to `cat` on the exec's stdin
+    /// (a command-line argument is capped at 128 KiB, which checker files can exceed)
+    async fn write_file_to_container(&self, container_id: &str, path: &str, content: &[u8]) -> Result<()> {
+        use bollard::exec::{CreateExecOptions, StartExecResults};
+        use tokio::io::AsyncWriteExt;
+
+        let write_command = format!("cat > {}", path);
+        let exec_config = CreateExecOptions {
+            cmd: Some(vec!["bash", "-c", &write_command]),
+            attach_stdin: Some(true),
+            attach_stdout: Some(true),
+            attach_stderr: Some(true),
+            ..Default::default()
         };
-        
-        // Write file using echo command (simple approach for now)
-        let encoded_content = general_purpose::STANDARD.encode(source_code);
-        let write_command = format!("echo '{}' | base64 -d > /code/{}", encoded_content, filename);
-        let write_cmd = vec!["bash", "-c", &write_command];
-        
+        let exec = self.docker.create_exec(container_id, exec_config).await?;
+        let StartExecResults::Attached { mut output, mut input } = self.docker.start_exec(&exec.id, None).await? else {
+            bail!("Exec started detached");
+        };
+        input.write_all(content).await?;
+        input.shutdown().await?;
+
+        let mut printed = Vec::new();
+        while let Some(message) = output.next().await {
+            match message? {
+                LogOutput::StdOut { message } | LogOutput::StdErr { message } => printed.extend_from_slice(&message),
+                _ => {}
+            }
+        }
+        let exit_code = self.docker.inspect_exec(&exec.id).await?.exit_code;
+        if exit_code != Some(0) {
+            bail!("Failed to write {}: {}", path, String::from_utf8_lossy(&printed).trim());
+        }
+        Ok(())
+    }
+
+    /// Run a bash command in the container; returns what it printed and its exit code
+    async fn exec_output(&self, container_id: &str, command: &str) -> Result<(String, Option<i64>)> {
+        self.exec_output_as(container_id, command, None).await
+    }
+
+    /// `exec_output` as `user` (the image's user when None)
+    async fn exec_output_as(&self, container_id: &str, command: &str, user: Option<&str>) -> Result<(String, Option<i64>)> {
+        use bollard::exec::{CreateExecOptions, StartExecOptions};
+
         let exec_config = CreateExecOptions {
-            cmd: Some(write_cmd.iter().map(|s| s.to_string()).collect()),
+            cmd: Some(vec!["bash", "-c", command]),
             attach_stdout: Some(true),
             attach_stderr: Some(true),
+            user,
             ..Default::default()
         };
-        
         let exec = self.docker.create_exec(container_id, exec_config).await?;
-        
+
         let start_config = StartExecOptions {
             detach: false,
             ..Default::default()
         };
-        
-        let output = self.docker.start_exec(&exec.id, Some(start_config)).await?;
-        
-        // Wait for write to complete
-        if let bollard::exec::StartExecResults::Attached { mut output, .. } = output {
-            while let Some(_) = output.next().await {
-                // Drain the stream
+        let mut printed = Vec::new();
+        if let bollard::exec::StartExecResults::Attached { mut output, .. } =
+            self.docker.start_exec(&exec.id, Some(start_config)).await?
+        {
+            while let Some(message) = output.next().await {
+                match message? {
+                    LogOutput::StdOut { message } | LogOutput::StdErr { message } => printed.extend_from_slice(&message),
+                    _ => {}
+                }
             }
         }
-        
-        // Check if write succeeded
+
         let inspect = self.docker.inspect_exec(&exec.id).await?;
-        if inspect.exit_code != Some(0) {
-            bail!("Failed to write source code to container");
+        Ok((String::from_utf8_lossy(&printed).into_owned(), inspect.exit_code))
+    }
+
+    /// Read and remove the runner's report of one test, checked against what the engine saw
+    ///
+    /// The report sits where only root can read it (see protocol.rs), so it takes a root
+    /// exec of its own; a report that can't be read counts as missing.
+    async fn take_report(
+        &self,
+        container_id: &str,
+        report: &str,
+        exit_code: Option<i64>,
+        elapsed_ms: u64,
+        language: &Language,
+    ) -> Option<protocol::RunnerReport> {
+        let printed = match self.exec_output_as(container_id, &protocol::take_report_command(report), Some("root")).await {
+            Ok((printed, _)) => printed,
+            Err(e) => {
+                warn!(error = %e, "Failed to read the runner report");
+                return None;
+            }
+        };
+        let parsed = protocol::parse_report(&printed)?;
+        let checked = parsed.checked(exit_code, elapsed_ms, self.get_cpus(language));
+        if checked.is_none() {
+            warn!(report_exit_code = parsed.exit_code, exit_code = ?exit_code, "Runner report disagrees with the exec; ignoring it");
+        }
+        checked
+    }
+
+    /// Start a judge container (checker or interactor, named by `role`) and compile the judge in it
+    ///
+    /// The judge lives apart from the submission, so the submission can't read the judge,
+    /// its input, or replace it. The error is reported as every test's checker outcome.
+    async fn start_judge(
+        &self,
+        role: &str,
+        job: &JobRequest,
+        image: &str,
+        source_code: &str,
+        keep_alive: Duration,
+    ) -> std::result::Result<String, String> {
+        // Started like a pooled container: nothing of the submission in its environment
+        let container_id = self.start_job_container(job, image, true, keep_alive).await?;
+        if let Err(e) = self.write_source_to_container(&container_id, job.language.source_file(), source_code).await {
+            pool::remove(&self.docker, vec![container_id]).await;
+            return Err(format!("{} setup failed: {}", role, e));
+        }
+        if let Some(compile) = self.get_compile(&job.language) {
+            let command = compile.script();
+            let compiled = tokio::time::timeout(COMPILE_BUDGET, self.exec_output(&container_id, &command)).await;
+            let failure = match compiled {
+                Ok(Ok((_, Some(0)))) => None,
+                Ok(Ok((printed, _))) => Some(format!("{} compilation failed: {}", role, printed.trim())),
+                Ok(Err(e)) => Some(format!("{} compilation process error: {}", role, e)),
+                Err(_) => Some(format!("{} compilation exceeded the {}s budget", role, COMPILE_BUDGET.as_secs())),
+            };
+            if let Some(failure) = failure {
+                pool::remove(&self.docker, vec![container_id]).await;
+                return Err(failure);
+            }
+        }
+        Ok(container_id)
+    }
+
+    /// Run the checker on one test's output
+    async fn run_checker(
+        &self,
+        container_id: &str,
+        job: &JobRequest,
+        test_case: &optimus_common::types::TestCase,
+        output: &TestExecutionOutput,
+    ) -> CheckerOutcome {
+        let actual = output.stdout_bytes.as_deref().unwrap_or(output.stdout.as_bytes());
+        let files = [
+            (checker::INPUT_PATH, test_case.input.as_bytes().to_vec()),
+            (checker::EXPECTED_PATH, checker::expected_bytes(&test_case.expected_output, job.output_mode)),
+            (checker::ACTUAL_PATH, actual.to_vec()),
+        ];
+        for (path, content) in &files {
+            if let Err(e) = self.write_file_to_container(container_id, path, content).await {
+                return CheckerOutcome::error(format!("Checker setup failed: {}", e));
+            }
+        }
+
+        let command = checker::command(program_command(&job.language));
+        match tokio::time::timeout(checker::TIMEOUT, self.exec_output(container_id, &command)).await {
+            Ok(Ok((printed, exit_code))) => CheckerOutcome::finished(exit_code, &printed),
+            Ok(Err(e)) => CheckerOutcome::error(format!("Checker failed to run: {}", e)),
+            Err(_) => CheckerOutcome::error(format!("Checker exceeded {}s", checker::TIMEOUT.as_secs())),
         }
-        
-        Ok(())
     }
 
     /// Helper to create compilation error outputs for all test cases
@@ -1048,6 +1658,17 @@ impl DockerEngine {
             timed_out: false,
             runtime_error: false,
             compilation_failed: true,
+            stdout_bytes: None,
+            stderr_bytes: None,
+            stdout_total_bytes: None,
+            stderr_total_bytes: None,
+            overhead_ms: None,
+            cpu_time_ms: None,
+            memory_kb: None,
+            resource_samples: None,
+            oom_killed: false,
+            failure: None,
+            checker: None,
         }).collect()
     }
 }
@@ -1,18 +1,20 @@
-/// Integration tests for compile-once execution model
-/// 
-/// These tests verify that the new execution path works correctly:
-/// 1. Compilation succeeds and all tests execute
-/// 2. Compilation failures are handled properly
-/// 3. Runtime errors are detected correctly
-/// 4. Timeouts work as expected
-/// 5. Container cleanup happens reliably
+//! Integration tests for compile-once execution model
+//! 
+//! These tests verify that the new execution path works correctly:
+//! 1. Compilation succeeds and all tests execute
+//! 2. Compilation failures are handled properly
+//! 3. Runtime errors are detected correctly
+//! 4. Timeouts work as expected
+//! 5. Container cleanup happens reliably
 
 #[cfg(test)]
 mod compile_once_tests {
-    use crate::engine::DockerEngine;
+    use crate::engine::{keep_alive_for, DockerEngine};
     use crate::config::LanguageConfigManager;
-    use crate::evaluator::{evaluate};
-    use optimus_common::types::{JobRequest, Language, TestCase, JobMetadata, TestStatus};
+    use crate::evaluator::{evaluate, EvaluationOptions};
+    use crate::progress::ProgressReporter;
+    use tokio_util::sync::CancellationToken;
+    use optimus_common::types::{JobRequest, Language, Sanitizer, SourceFormat, TestCase, TestStatus};
     use uuid::Uuid;
 
     /// Helper to create a mock Redis connection manager
@@ -28,7 +30,7 @@ mod compile_once_tests {
     #[tokio::test]
     #[ignore] // Requires Docker and Redis
     async fn test_compile_once_python_success() {
-        let config_manager = LanguageConfigManager::load_default()
+        let config_manager = LanguageConfigManager::load_default(None)
             .expect("Failed to load language config");
         
         let engine = DockerEngine::new_with_config(&config_manager)
@@ -49,26 +51,29 @@ print(n * 2)
                     input: "5".to_string(),
                     expected_output: "10".to_string(),
                     weight: 10,
+                    ..Default::default()
                 },
                 TestCase {
                     id: 2,
                     input: "10".to_string(),
                     expected_output: "20".to_string(),
                     weight: 10,
+                    ..Default::default()
                 },
                 TestCase {
                     id: 3,
                     input: "15".to_string(),
                     expected_output: "30".to_string(),
                     weight: 10,
+                    ..Default::default()
                 },
             ],
             timeout_ms: 5000,
-            metadata: JobMetadata::default(),
+            ..Default::default()
         };
 
         // Execute with compile-once model
-        let outputs = engine.execute_job_in_single_container(&job, &mut redis_conn).await;
+        let outputs = engine.execute_job_in_single_container(&job, Some(&mut redis_conn), &CancellationToken::new(), &ProgressReporter::disabled()).await;
 
         // Verify all tests executed
         assert_eq!(outputs.len(), 3, "Should have 3 test outputs");
@@ -79,7 +84,7 @@ print(n * 2)
         }
         
         // Evaluate results
-        let result = evaluate(&job, outputs);
+        let result = evaluate(&job, outputs, &EvaluationOptions::for_job(&job));
         assert_eq!(result.score, 30, "All tests should pass");
     }
 
@@ -87,7 +92,7 @@ print(n * 2)
     #[tokio::test]
     #[ignore] // Requires Docker and Redis
     async fn test_compile_once_java_compilation_error() {
-        let config_manager = LanguageConfigManager::load_default()
+        let config_manager = LanguageConfigManager::load_default(None)
             .expect("Failed to load language config");
         
         let engine = DockerEngine::new_with_config(&config_manager)
@@ -112,20 +117,22 @@ public class Main {
                     input: "".to_string(),
                     expected_output: "test".to_string(),
                     weight: 10,
+                    ..Default::default()
                 },
                 TestCase {
                     id: 2,
                     input: "".to_string(),
                     expected_output: "test".to_string(),
                     weight: 10,
+                    ..Default::default()
                 },
             ],
             timeout_ms: 5000,
-            metadata: JobMetadata::default(),
+            ..Default::default()
         };
 
         // Execute with compile-once model
-        let outputs = engine.execute_job_in_single_container(&job, &mut redis_conn).await;
+        let outputs = engine.execute_job_in_single_container(&job, Some(&mut redis_conn), &CancellationToken::new(), &ProgressReporter::disabled()).await;
 
         // Verify all tests marked as compilation failed
         assert_eq!(outputs.len(), 2, "Should have 2 test outputs");
@@ -137,7 +144,7 @@ public class Main {
         }
         
         // Evaluate results
-        let result = evaluate(&job, outputs);
+        let result = evaluate(&job, outputs, &EvaluationOptions::for_job(&job));
         assert_eq!(result.score, 0, "No tests should pass with compilation error");
     }
 
@@ -145,7 +152,7 @@ public class Main {
     #[tokio::test]
     #[ignore] // Requires Docker and Redis
     async fn test_compile_once_rust_runtime_error() {
-        let config_manager = LanguageConfigManager::load_default()
+        let config_manager = LanguageConfigManager::load_default(None)
             .expect("Failed to load language config");
         
         let engine = DockerEngine::new_with_config(&config_manager)
@@ -174,26 +181,29 @@ fn main() {
                     input: "10".to_string(),
                     expected_output: "10".to_string(),
                     weight: 10,
+                    ..Default::default()
                 },
                 TestCase {
                     id: 2,
                     input: "0".to_string(), // This will cause division by zero
                     expected_output: "error".to_string(),
                     weight: 10,
+                    ..Default::default()
                 },
                 TestCase {
                     id: 3,
                     input: "5".to_string(),
                     expected_output: "20".to_string(),
                     weight: 10,
+                    ..Default::default()
                 },
             ],
             timeout_ms: 5000,
-            metadata: JobMetadata::default(),
+            ..Default::default()
         };
 
         // Execute with compile-once model
-        let outputs = engine.execute_job_in_single_container(&job, &mut redis_conn).await;
+        let outputs = engine.execute_job_in_single_container(&job, Some(&mut redis_conn), &CancellationToken::new(), &ProgressReporter::disabled()).await;
 
         // Verify compilation succeeded
         assert!(!outputs[0].compilation_failed, "Compilation should succeed");
@@ -208,7 +218,7 @@ fn main() {
         assert!(!outputs[2].runtime_error, "Third test should execute after runtime error");
         
         // Evaluate results
-        let result = evaluate(&job, outputs);
+        let result = evaluate(&job, outputs, &EvaluationOptions::for_job(&job));
         
         // First and third should pass, second should fail
         assert_eq!(result.results[0].status, TestStatus::Passed);
@@ -220,7 +230,7 @@ fn main() {
     #[tokio::test]
     #[ignore] // Requires Docker and Redis
     async fn test_compile_once_timeout() {
-        let config_manager = LanguageConfigManager::load_default()
+        let config_manager = LanguageConfigManager::load_default(None)
             .expect("Failed to load language config");
         
         let engine = DockerEngine::new_with_config(&config_manager)
@@ -244,26 +254,29 @@ print(n)
                     input: "5".to_string(),
                     expected_output: "5".to_string(),
                     weight: 10,
+                    ..Default::default()
                 },
                 TestCase {
                     id: 2,
                     input: "999".to_string(), // This will timeout
                     expected_output: "999".to_string(),
                     weight: 10,
+                    ..Default::default()
                 },
                 TestCase {
                     id: 3,
                     input: "10".to_string(),
                     expected_output: "10".to_string(),
                     weight: 10,
+                    ..Default::default()
                 },
             ],
             timeout_ms: 1000, // 1 second timeout
-            metadata: JobMetadata::default(),
+            ..Default::default()
         };
 
         // Execute with compile-once model
-        let outputs = engine.execute_job_in_single_container(&job, &mut redis_conn).await;
+        let outputs = engine.execute_job_in_single_container(&job, Some(&mut redis_conn), &CancellationToken::new(), &ProgressReporter::disabled()).await;
 
         // Verify compilation succeeded
         assert!(!outputs[0].compilation_failed, "Compilation should succeed");
@@ -278,7 +291,7 @@ print(n)
         assert!(!outputs[2].timed_out, "Third test should execute after timeout");
         
         // Evaluate results
-        let result = evaluate(&job, outputs);
+        let result = evaluate(&job, outputs, &EvaluationOptions::for_job(&job));
         assert_eq!(result.results[0].status, TestStatus::Passed);
         assert_eq!(result.results[1].status, TestStatus::TimeLimitExceeded);
         assert_eq!(result.results[2].status, TestStatus::Passed);
@@ -290,7 +303,7 @@ print(n)
     async fn test_compile_once_performance_comparison() {
         use std::time::Instant;
         
-        let config_manager = LanguageConfigManager::load_default()
+        let config_manager = LanguageConfigManager::load_default(None)
             .expect("Failed to load language config");
         
         let engine = DockerEngine::new_with_config(&config_manager)
@@ -306,6 +319,7 @@ print(n)
                 input: i.to_string(),
                 expected_output: (i * 2).to_string(),
                 weight: 10,
+                ..Default::default()
             });
         }
         
@@ -325,12 +339,12 @@ public class Main {
 "#.to_string(),
             test_cases: test_cases.clone(),
             timeout_ms: 5000,
-            metadata: JobMetadata::default(),
+            ..Default::default()
         };
 
         // Test compile-once execution
         let start = Instant::now();
-        let outputs_new = engine.execute_job_in_single_container(&job, &mut redis_conn).await;
+        let outputs_new = engine.execute_job_in_single_container(&job, Some(&mut redis_conn), &CancellationToken::new(), &ProgressReporter::disabled()).await;
         let compile_once_duration = start.elapsed();
         
         println!("Compile-once execution: {:?}", compile_once_duration);
@@ -343,11 +357,327 @@ public class Main {
         assert!(compile_once_duration.as_secs() < 30, "Should complete within 30 seconds for 10 tests");
     }
 
+    /// Test: Kotlin and Scala compile once and run on the JVM like Java
+    #[tokio::test]
+    #[ignore] // Requires Docker and Redis
+    async fn test_compile_once_kotlin_and_scala() {
+        let config_manager = LanguageConfigManager::load_default(None)
+            .expect("Failed to load language config");
+        
+        let engine = DockerEngine::new_with_config(&config_manager)
+            .expect("Failed to create Docker engine");
+        
+        let mut redis_conn = create_redis_conn().await;
+        
+        let sources = [
+            (Language::Kotlin, r#"
+fun main() {
+    val n = readLine()!!.trim().toInt()
+    println(n * 2)
+}
+"#),
+            (Language::Scala, r#"
+object Main {
+  def main(args: Array[String]): Unit = {
+    val n = scala.io.StdIn.readLine().trim.toInt
+    println(n * 2)
+  }
+}
+"#),
+        ];
+
+        for (language, source_code) in sources {
+            let job = JobRequest {
+                id: Uuid::new_v4(),
+                language,
+                source_code: source_code.to_string(),
+                test_cases: (1..=2)
+                    .map(|i| TestCase {
+                        id: i,
+                        input: i.to_string(),
+                        expected_output: (i * 2).to_string(),
+                        weight: 10,
+                        ..Default::default()
+                    })
+                    .collect(),
+                timeout_ms: 5000,
+                ..Default::default()
+            };
+
+            let outputs = engine.execute_job_in_single_container(&job, Some(&mut redis_conn), &CancellationToken::new(), &ProgressReporter::disabled()).await;
+            assert!(outputs.iter().all(|o| !o.compilation_failed), "{} should compile: {:?}", language, outputs);
+
+            let result = evaluate(&job, outputs, &EvaluationOptions::for_job(&job));
+            assert!(result.results.iter().all(|r| r.status == TestStatus::Passed), "{} tests should pass", language);
+            assert_eq!(result.score, 20);
+        }
+    }
+
+    /// Test: A notebook's code cells are extracted in order and judged as the script
+    #[tokio::test]
+    #[ignore] // Requires Docker and Redis
+    async fn test_compile_once_python_notebook() {
+        let config_manager = LanguageConfigManager::load_default(None)
+            .expect("Failed to load language config");
+        
+        let engine = DockerEngine::new_with_config(&config_manager)
+            .expect("Failed to create Docker engine");
+        
+        let mut redis_conn = create_redis_conn().await;
+        
+        let notebook = serde_json::json!({
+            "cells": [
+                {"cell_type": "markdown", "metadata": {}, "source": ["# Double it"]},
+                {"cell_type": "code", "metadata": {}, "outputs": [], "source": ["%matplotlib inline\n", "def double(n):\n", "    return n * 2"]},
+                {"cell_type": "code", "metadata": {}, "outputs": [], "source": "print(double(int(input())))"},
+            ],
+            "metadata": {},
+            "nbformat": 4,
+            "nbformat_minor": 5,
+        });
+        let job = JobRequest {
+            id: Uuid::new_v4(),
+            language: Language::Python,
+            source_code: notebook.to_string(),
+            test_cases: vec![
+                TestCase {
+                    id: 1,
+                    input: "21".to_string(),
+                    expected_output: "42".to_string(),
+                    weight: 10,
+                    ..Default::default()
+                },
+            ],
+            timeout_ms: 5000,
+            source_format: SourceFormat::Notebook,
+            ..Default::default()
+        };
+
+        let outputs = engine.execute_job_in_single_container(&job, Some(&mut redis_conn), &CancellationToken::new(), &ProgressReporter::disabled()).await;
+        assert!(outputs.iter().all(|o| !o.compilation_failed), "notebook should convert: {:?}", outputs);
+        let result = evaluate(&job, outputs, &EvaluationOptions::for_job(&job));
+        assert_eq!(result.results[0].status, TestStatus::Passed);
+    }
+
+    /// Test: An interactive guessing game, judged by the interactor's exit code
+    #[tokio::test]
+    #[ignore] // Requires Docker and Redis
+    async fn test_compile_once_interactive_guessing() {
+        let config_manager = LanguageConfigManager::load_default(None)
+            .expect("Failed to load language config");
+        
+        let engine = DockerEngine::new_with_config(&config_manager)
+            .expect("Failed to create Docker engine");
+        
+        let mut redis_conn = create_redis_conn().await;
+        
+        // Answers "higher"/"lower"/"correct"; accepts within 7 guesses
+        let interactor = r#"
+import sys
+secret = int(open(sys.argv[1]).read())
+for guesses in range(1, 8):
+    guess = int(input())
+    if guess == secret:
+        print("correct", flush=True)
+        print(f"found in {guesses} guesses", file=sys.stderr)
+        sys.exit(0)
+    print("higher" if guess < secret else "lower", flush=True)
+sys.exit(1)
+"#;
+        let binary_search = r#"
+lo, hi = 1, 100
+while True:
+    mid = (lo + hi) // 2
+    print(mid, flush=True)
+    reply = input()
+    if reply == "correct":
+        break
+    lo, hi = (mid + 1, hi) if reply == "higher" else (lo, mid - 1)
+"#;
+        let mut job = JobRequest {
+            id: Uuid::new_v4(),
+            language: Language::Python,
+            source_code: binary_search.to_string(),
+            test_cases: ["1", "64", "100"]
+                .iter()
+                .zip(1..)
+                .map(|(secret, id)| TestCase {
+                    id,
+                    input: secret.to_string(),
+                    expected_output: String::new(),
+                    weight: 10,
+                    ..Default::default()
+                })
+                .collect(),
+            timeout_ms: 5000,
+            interactor: Some(interactor.to_string()),
+            ..Default::default()
+        };
+
+        let outputs = engine.execute_job_in_single_container(&job, Some(&mut redis_conn), &CancellationToken::new(), &ProgressReporter::disabled()).await;
+        let result = evaluate(&job, outputs.clone(), &EvaluationOptions::for_job(&job));
+        assert!(result.results.iter().all(|r| r.status == TestStatus::Passed), "{:?}", outputs);
+
+        // Always guessing 50 runs out of guesses
+        job.source_code = "while True:\n    print(50, flush=True)\n    input()\n".to_string();
+        let outputs = engine.execute_job_in_single_container(&job, Some(&mut redis_conn), &CancellationToken::new(), &ProgressReporter::disabled()).await;
+        let result = evaluate(&job, outputs, &EvaluationOptions::for_job(&job));
+        assert!(result.results.iter().all(|r| r.status != TestStatus::Passed));
+    }
+
+    /// Test: PHP and Ruby read stdin and run without a compile step
+    #[tokio::test]
+    #[ignore] // Requires Docker and Redis
+    async fn test_compile_once_php_and_ruby() {
+        let config_manager = LanguageConfigManager::load_default(None)
+            .expect("Failed to load language config");
+        
+        let engine = DockerEngine::new_with_config(&config_manager)
+            .expect("Failed to create Docker engine");
+        
+        let mut redis_conn = create_redis_conn().await;
+        
+        let sources = [
+            (Language::Php, r#"<?php
+$n = (int) trim(fgets(STDIN));
+echo $n * 2, "\n";
+"#),
+            (Language::Ruby, r#"
+n = gets.to_i
+puts n * 2
+"#),
+        ];
+
+        for (language, source_code) in sources {
+            let job = JobRequest {
+                id: Uuid::new_v4(),
+                language,
+                source_code: source_code.to_string(),
+                test_cases: (1..=2)
+                    .map(|i| TestCase {
+                        id: i,
+                        input: i.to_string(),
+                        expected_output: (i * 2).to_string(),
+                        weight: 10,
+                        ..Default::default()
+                    })
+                    .collect(),
+                timeout_ms: 5000,
+                ..Default::default()
+            };
+
+            let outputs = engine.execute_job_in_single_container(&job, Some(&mut redis_conn), &CancellationToken::new(), &ProgressReporter::disabled()).await;
+            let result = evaluate(&job, outputs, &EvaluationOptions::for_job(&job));
+            assert!(result.results.iter().all(|r| r.status == TestStatus::Passed), "{} tests should pass", language);
+            assert_eq!(result.score, 20);
+        }
+    }
+
+    /// Test: Assembly is assembled and linked in two stages, then echoes its input
+    #[tokio::test]
+    #[ignore] // Requires Docker and Redis
+    async fn test_compile_once_asm_two_stage_build() {
+        let config_manager = LanguageConfigManager::load_default(None)
+            .expect("Failed to load language config");
+        
+        let engine = DockerEngine::new_with_config(&config_manager)
+            .expect("Failed to create Docker engine");
+        
+        let mut redis_conn = create_redis_conn().await;
+        
+        let job = JobRequest {
+            id: Uuid::new_v4(),
+            language: Language::Asm,
+            source_code: r#"
+section .bss
+buf resb 4096
+section .text
+global _start
+_start:
+    mov rax, 0
+    mov rdi, 0
+    mov rsi, buf
+    mov rdx, 4096
+    syscall
+    mov rdx, rax
+    mov rax, 1
+    mov rdi, 1
+    mov rsi, buf
+    syscall
+    mov rax, 60
+    xor rdi, rdi
+    syscall
+"#.to_string(),
+            test_cases: vec![
+                TestCase {
+                    id: 1,
+                    input: "hello".to_string(),
+                    expected_output: "hello".to_string(),
+                    weight: 10,
+                    ..Default::default()
+                },
+            ],
+            timeout_ms: 2000,
+            ..Default::default()
+        };
+
+        let outputs = engine.execute_job_in_single_container(&job, Some(&mut redis_conn), &CancellationToken::new(), &ProgressReporter::disabled()).await;
+        assert!(outputs.iter().all(|o| !o.compilation_failed), "asm should assemble and link: {:?}", outputs);
+        let result = evaluate(&job, outputs, &EvaluationOptions::for_job(&job));
+        assert_eq!(result.results[0].status, TestStatus::Passed);
+    }
+
+    /// Test: A sanitized C out-of-bounds write is a runtime error with the ASan report
+    #[tokio::test]
+    #[ignore] // Requires Docker and Redis
+    async fn test_c_sanitizer_reports_runtime_error() {
+        let config_manager = LanguageConfigManager::load_default(None)
+            .expect("Failed to load language config");
+        
+        let engine = DockerEngine::new_with_config(&config_manager)
+            .expect("Failed to create Docker engine");
+        
+        let mut redis_conn = create_redis_conn().await;
+        
+        let job = JobRequest {
+            id: Uuid::new_v4(),
+            language: Language::C,
+            source_code: r#"
+#include <stdio.h>
+#include <stdlib.h>
+int main(void) {
+    int *a = malloc(4 * sizeof(int));
+    a[4] = 1;
+    printf("%d\n", a[4]);
+    return 0;
+}
+"#.to_string(),
+            test_cases: vec![
+                TestCase {
+                    id: 1,
+                    input: "".to_string(),
+                    expected_output: "1".to_string(),
+                    weight: 10,
+                    ..Default::default()
+                },
+            ],
+            timeout_ms: 5000,
+            sanitizers: vec![Sanitizer::Address, Sanitizer::Undefined],
+            ..Default::default()
+        };
+
+        let outputs = engine.execute_job_in_single_container(&job, Some(&mut redis_conn), &CancellationToken::new(), &ProgressReporter::disabled()).await;
+        let result = evaluate(&job, outputs, &EvaluationOptions::for_job(&job));
+        assert_eq!(result.results[0].status, TestStatus::RuntimeError);
+        let report = result.results[0].sanitizer_report.as_deref().expect("sanitizer report");
+        assert!(report.contains("AddressSanitizer: heap-buffer-overflow"), "{}", report);
+    }
+
     /// Test: Container cleanup on cancellation
     #[tokio::test]
     #[ignore] // Requires Docker and Redis
     async fn test_compile_once_cleanup_on_error() {
-        let config_manager = LanguageConfigManager::load_default()
+        let config_manager = LanguageConfigManager::load_default(None)
             .expect("Failed to load language config");
         
         let engine = DockerEngine::new_with_config(&config_manager)
@@ -367,17 +697,91 @@ print("test")
                     input: "".to_string(),
                     expected_output: "test".to_string(),
                     weight: 10,
+                    ..Default::default()
                 },
             ],
             timeout_ms: 5000,
-            metadata: JobMetadata::default(),
+            ..Default::default()
         };
 
         // Execute - container should be cleaned up even if test fails
-        let _outputs = engine.execute_job_in_single_container(&job, &mut redis_conn).await;
+        let _outputs = engine.execute_job_in_single_container(&job, Some(&mut redis_conn), &CancellationToken::new(), &ProgressReporter::disabled()).await;
         
         // Container should be automatically cleaned up by Drop guard
         // Manual verification: docker ps should not show lingering containers
         // This test mainly ensures the code doesn't panic during cleanup
     }
+
+    /// Test: A warmup run that hangs on its empty input is killed before the timed tests
+    #[tokio::test]
+    #[ignore] // Requires Docker and Redis
+    async fn test_compile_once_warmup_run_discarded() {
+        let config_manager = LanguageConfigManager::load_default(None)
+            .expect("Failed to load language config");
+
+        let engine = DockerEngine::new_with_config(&config_manager)
+            .expect("Failed to create Docker engine");
+
+        let mut redis_conn = create_redis_conn().await;
+
+        let job = JobRequest {
+            id: Uuid::new_v4(),
+            language: Language::Python,
+            source_code: r#"
+import sys
+data = sys.stdin.read()
+while not data:
+    pass
+print(int(data) * 2)
+"#.to_string(),
+            test_cases: vec![
+                TestCase {
+                    id: 1,
+                    input: "5".to_string(),
+                    expected_output: "10".to_string(),
+                    weight: 10,
+                    ..Default::default()
+                },
+            ],
+            timeout_ms: 1000,
+            warmup: Some(true),
+            ..Default::default()
+        };
+        assert!(engine.use_warmup(&job));
+
+        let outputs = engine.execute_job_in_single_container(&job, Some(&mut redis_conn), &CancellationToken::new(), &ProgressReporter::disabled()).await;
+
+        // The spinning warmup process doesn't slow the timed test down
+        assert_eq!(outputs.len(), 1);
+        assert!(!outputs[0].timed_out, "stderr: {}", outputs[0].stderr);
+        let result = evaluate(&job, outputs, &EvaluationOptions::for_job(&job));
+        assert_eq!(result.score, 10);
+    }
+
+    /// Test: Container keep-alive covers compilation plus every test at its full limit
+    #[test]
+    fn test_keep_alive_sized_from_job() {
+        let test_case = |id| TestCase {
+            id,
+            input: String::new(),
+            expected_output: String::new(),
+            weight: 1,
+            ..Default::default()
+        };
+        let mut job = JobRequest {
+            id: Uuid::new_v4(),
+            language: Language::Java,
+            source_code: String::new(),
+            test_cases: (1..=100).map(test_case).collect(),
+            timeout_ms: 10_000,
+            ..Default::default()
+        };
+
+        // 100 tests x 10s is far beyond the old fixed 300s
+        let keep_alive = keep_alive_for(&job).as_secs();
+        assert!(keep_alive > 100 * 10 + 120, "keep-alive {}s", keep_alive);
+
+        job.timeout_ms = u64::MAX;
+        assert_eq!(keep_alive_for(&job).as_secs(), 24 * 3600);
+    }
 }
@@ -1,34 +1,86 @@
-/// Test Evaluator - Language-Agnostic Scoring Logic
-///
-/// **Core Responsibility:**
-/// Compare raw execution outputs against expected outputs and assign scores.
-///
-/// **Critical Properties:**
-/// - Knows nothing about Docker
-/// - Knows nothing about language runtimes
-/// - Knows nothing about Redis
-/// - Pure function: (execution outputs, expected outputs) → scores
-///
-/// **Scoring Rules:**
-/// - Each test case has a weight
-/// - score = sum of weights for Passed tests
-/// - max_score = sum of all test case weights
-/// - overall_status: Completed if any test passed, Failed if all failed
-///
-/// **Normalization Rules (Applied to All Languages):**
-/// - Trim trailing whitespace: YES
-/// - Trim leading whitespace: YES
-/// - Ignore newline differences (\n vs \r\n): YES (via trim)
-/// - Case sensitivity: YES (exact match required)
-/// - Floating-point tolerance: NO (future enhancement)
-///
-/// **Why This Exists:**
-/// Separates correctness evaluation from execution mechanism.
-/// Guarantees deterministic scoring regardless of execution engine.
+//! Test Evaluator - Language-Agnostic Scoring Logic
+//!
+//! **Core Responsibility:**
+//! Compare raw execution outputs against expected outputs and assign scores.
+//!
+//! **Critical Properties:**
+//! - Knows nothing about Docker
+//! - Knows nothing about language runtimes
+//! - Knows nothing about Redis
+//! - Pure function: (execution outputs, expected outputs) → scores
+//!
+//! **Scoring Rules:**
+//! - Each test case has a weight
+//! - score = sum of weights for Passed tests
+//! - max_score = sum of all test case weights
+//! - Tests sharing a `group_id` are a subtask scored together per its GroupScoring (the
+//!   job's `subtask_scoring` entry, else `group_scoring`): all_or_nothing (default) earns
+//!   the group's weights only if every test in it passed
+//! - points = score scaled to the job's max_points (optional, exact rounding rule)
+//! - overall_status: Completed if any test passed, Failed if all failed
+//! - With a per-job StatusPolicy (any_pass / all_pass / threshold): Passed if every
+//!   test passed, PartiallyPassed if the policy accepts a partial score, else Failed
+//!
+//! **Normalization Rules (Applied to All Languages):**
+//! - Trim trailing whitespace: YES
+//! - Trim leading whitespace: YES
+//! - Ignore newline differences (\n vs \r\n): YES (via trim)
+//! - Case sensitivity: YES (exact match required, unless the job opts out)
+//! - Floating-point tolerance: NO (use an evaluator pipeline)
+//! - Unicode NFC / BOM stripping / lenient UTF-8: OPT-IN per job (NormalizationOptions)
+//! - Trailing whitespace per line / case / internal whitespace / token comparison: OPT-IN
+//!   per job (NormalizationOptions)
+//!
+//! **Evaluator Pipeline (per job, see `pipeline`):**
+//! - A job may declare its own normalizers and comparator instead of the rules above
+//!   (trim, collapse whitespace, lowercase, float rounding; exact, unordered lines,
+//!   tokens or numeric tolerance); per-test comparison modes are then ignored
+//!
+//! A test failed on its output carries a `diff`: where stdout first differs from the
+//! expected output (see `diff`).
+//!
+//! Output the engine cut short at `engine_max_output_bytes` (see `capture`) fails the test.
+//! Outputs of 64KB or more are compared line by line, stopping at the first difference,
+//! rather than normalized into copies of themselves (see `streaming`).
+//!
+//! **Custom Checker (per job, see `checker`):**
+//! - A job with a checker is judged by the checker's exit code instead of comparison
+//!   (0 passes, 1 fails); a checker that can't run fails the test as a CheckerError
+//!
+//! **Binary Output Mode (per job):**
+//! - No normalization at all - raw stdout bytes must equal the expected bytes
+//! - expected_output is base64-encoded; raw stdout/stderr are returned as base64
+//!
+//! **Comparison Modes (per job, overridable per test case):**
+//! - Ordered (default): normalized outputs must be identical
+//! - Unordered: lines may appear in any order, compared as a multiset
+//! - Strict: no trimming at all - outputs must match exactly, trailing newline included
+//!
+//! **Why This Exists:**
+//! Separates correctness evaluation from execution mechanism.
+//! Guarantees deterministic scoring regardless of execution engine.
 
 use optimus_common::types::{
-    ExecutionResult, JobRequest, JobStatus, TestCase, TestResult, TestStatus,
+    ComparisonMode, ExecutionResult, JobRequest, JobStatus, NormalizationOptions, OutputMode,
+    StatusPolicy, SubtaskScore, TestCase, TestFailure, TestResult, TestStatus,
 };
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use base64::{Engine as _, engine::general_purpose};
+use crate::checker::{CheckerOutcome, CheckerVerdict};
+use crate::pipeline::Pipeline;
+use crate::streaming::StreamingComparison;
+use optimus_common::language_config::{LanguageFlags, StderrPolicy};
+use serde::{Deserialize, Serialize};
+
+/// Start of the stderr the engine reports when the sandbox itself failed to run a test
+pub const SANDBOX_FAILURE_PREFIX: &str = "Execution failed: ";
+
+/// Text outputs at least this large are compared line by line instead of normalized whole
+const STREAMING_THRESHOLD_BYTES: usize = 64 * 1024;
+
+/// Note the engine appends to stderr when the memory limit killed a test
+pub const OOM_NOTE: &str = "[Container killed: exceeded memory limit]";
 
 /// Result of code compilation phase
 /// Tracks whether compilation succeeded or failed
@@ -62,7 +114,7 @@ impl CompilationResult {
 /// **Execution Model:**
 /// - For compiled languages: compilation happens once, then all tests execute
 /// - For interpreted languages: compilation phase is optional (syntax check)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TestExecutionOutput {
     pub test_id: u32,
     pub stdout: String,
@@ -73,21 +125,138 @@ pub struct TestExecutionOutput {
     /// Indicates if this test failed due to compilation error
     /// (compilation happens once per job, not per test)
     pub compilation_failed: bool,
+    /// Raw stdout bytes as captured from the container (None = not captured)
+    /// Used for byte-exact comparison in binary output mode
+    pub stdout_bytes: Option<Vec<u8>>,
+    /// Raw stderr bytes as captured from the container (None = not captured)
+    pub stderr_bytes: Option<Vec<u8>>,
+    /// Bytes the program wrote to stdout, kept or not (None = not counted; see capture.rs)
+    #[serde(default)]
+    pub stdout_total_bytes: Option<u64>,
+    /// Bytes the program wrote to stderr, kept or not
+    #[serde(default)]
+    pub stderr_total_bytes: Option<u64>,
+    /// Exec/container setup time excluded from execution_time_ms (None = not measured)
+    #[serde(default)]
+    pub overhead_ms: Option<u64>,
+    /// User + system CPU time reported by the runner
+    #[serde(default)]
+    pub cpu_time_ms: Option<u64>,
+    /// Peak memory of the test (see `monitor::peak_memory_kb`)
+    #[serde(default)]
+    pub memory_kb: Option<u64>,
+    /// Container stats sampled while the test ran (debug artifacts only, never checkpointed)
+    #[serde(skip)]
+    pub resource_samples: Option<Vec<optimus_common::types::ResourceSample>>,
+    /// The program was killed for exceeding the memory limit (see `protocol::oom_killed`)
+    #[serde(default)]
+    pub oom_killed: bool,
+    /// The job's checker run on this test's output (jobs with a checker, clean runs only)
+    #[serde(default)]
+    pub checker: Option<CheckerOutcome>,
+    /// Why the judge, not the program, failed the test (SandboxError: the engine couldn't
+    /// run it); set by the engine alone, never read from anything the program prints
+    #[serde(default)]
+    pub failure: Option<TestFailure>,
+}
+
+impl TestExecutionOutput {
+    /// Whether stdout went over the capture cap and only its start was kept
+    pub fn stdout_truncated(&self) -> bool {
+        let kept = self.stdout_bytes.as_ref().map_or(self.stdout.len(), Vec::len);
+        self.stdout_total_bytes.is_some_and(|total| total > kept as u64)
+    }
+}
+
+/// Job-level evaluation settings
+/// Everything the evaluator needs from the job besides the test cases themselves
+#[derive(Debug, Clone, Default)]
+pub struct EvaluationOptions {
+    pub normalization: NormalizationOptions,
+    pub output_mode: OutputMode,
+    /// Comparison mode for test cases that don't specify their own
+    pub default_comparison: ComparisonMode,
+    /// Whether stderr output of a clean run fails the test
+    pub stderr_policy: StderrPolicy,
+    /// Declared evaluator pipeline; overrides normalization and comparison modes
+    pub pipeline: Option<Pipeline>,
+    /// Scan the source and outputs for misuse (see misuse.rs)
+    pub misuse_detection: bool,
+    /// The job has a checker or interactor: its verdicts replace output comparison
+    pub checker: bool,
+    /// The program was built with sanitizers: runtime errors carry their report
+    pub sanitizers: bool,
+}
+
+impl EvaluationOptions {
+    /// Extract evaluation settings from a job request
+    pub fn for_job(job: &JobRequest) -> Self {
+        Self::for_language(job, &LanguageFlags::default())
+    }
+
+    /// Evaluation settings for a job, with the language's flags filling in what it leaves unset
+    pub fn for_language(job: &JobRequest, flags: &LanguageFlags) -> Self {
+        Self {
+            normalization: job.normalization,
+            output_mode: job.output_mode,
+            default_comparison: job.comparison.or(flags.default_comparison).unwrap_or_default(),
+            stderr_policy: flags.stderr_policy,
+            pipeline: job.evaluation.as_ref().map(Pipeline::from_spec),
+            misuse_detection: flags.misuse_detection,
+            checker: job.checker.is_some() || job.interactor.is_some(),
+            sanitizers: !crate::sanitizer::active(job).is_empty(),
+        }
+    }
 }
 
 /// Normalize output string for comparison
 ///
-/// **Normalization Rules:**
+/// **Normalization Rules (ordered mode, no options):**
 /// - Trim leading whitespace
 /// - Trim trailing whitespace
 /// - Removes differences in line endings (\r\n vs \n)
 ///
-/// **Preserves:**
-/// - Internal whitespace
-/// - Case sensitivity
+/// **Preserves, unless the job's NormalizationOptions say otherwise:**
+/// - Internal whitespace (`collapse_whitespace`)
+/// - Trailing whitespace of inner lines (`ignore_trailing_whitespace`)
+/// - Case sensitivity (`ignore_case`)
 /// - Empty lines within content
-fn normalize_output(output: &str) -> &str {
-    output.trim()
+fn normalize_output<'a>(output: &'a str, mode: ComparisonMode, options: &NormalizationOptions) -> Cow<'a, str> {
+    Pipeline::legacy(mode, options).normalize(output)
+}
+
+/// Compare actual output against expected output using the given mode
+///
+/// Shorthand for `Pipeline::legacy`, the pipeline used by jobs that don't declare one.
+fn outputs_match(
+    actual: &str,
+    expected: &str,
+    mode: ComparisonMode,
+    options: &NormalizationOptions,
+) -> bool {
+    text_matches(&Pipeline::legacy(mode, options), actual, expected)
+}
+
+/// Compare text outputs, line by line once either is large (see streaming.rs)
+fn text_matches(pipeline: &Pipeline, actual: &str, expected: &str) -> bool {
+    if actual.len().max(expected.len()) >= STREAMING_THRESHOLD_BYTES {
+        if let Some(streaming) = StreamingComparison::new(pipeline) {
+            // Reading from memory can't fail
+            return streaming.matches(actual.as_bytes(), expected.as_bytes()).unwrap_or(false);
+        }
+    }
+    pipeline.matches(actual, expected)
+}
+
+/// Byte-exact comparison for binary output mode
+///
+/// `expected_base64` is the test case's expected_output, base64-encoded.
+/// An undecodable expectation never matches (the test is misconfigured).
+fn bytes_match(actual: &[u8], expected_base64: &str) -> bool {
+    match general_purpose::STANDARD.decode(expected_base64.trim()) {
+        Ok(expected) => actual == expected.as_slice(),
+        Err(_) => false,
+    }
 }
 
 /// Filter out JVM informational messages from stderr
@@ -123,36 +292,82 @@ fn filter_jvm_noise(stderr: &str) -> String {
 /// ## Arguments
 /// * `output` - Raw execution output from the engine
 /// * `test_case` - Expected test case definition
+/// * `options` - Job-level settings: encoding normalization, output mode,
+///   default comparison mode (all affect comparison only)
 ///
 /// ## Returns
 /// TestResult with status and execution details
-pub fn evaluate_test(output: &TestExecutionOutput, test_case: &TestCase) -> TestResult {
+pub fn evaluate_test(
+    output: &TestExecutionOutput,
+    test_case: &TestCase,
+    options: &EvaluationOptions,
+) -> TestResult {
     // Filter out JVM informational noise from stderr before evaluation
     let filtered_stderr = filter_jvm_noise(&output.stderr);
+
+    // Only clean runs reach the checker; one that never reported is a checker error
+    let checker_verdict = (options.checker && !output.compilation_failed && !output.runtime_error && !output.timed_out)
+        .then(|| output.checker.as_ref().map_or(CheckerVerdict::Error, CheckerOutcome::verdict));
     
     let status = if output.compilation_failed {
         // Compilation failure is treated as runtime error
         // All tests fail if compilation fails
         TestStatus::RuntimeError
+    } else if output.oom_killed {
+        TestStatus::MemoryLimitExceeded
     } else if output.runtime_error {
         TestStatus::RuntimeError
     } else if output.timed_out {
         TestStatus::TimeLimitExceeded
-    } else if !filtered_stderr.is_empty() {
+    } else if output.stdout_truncated() {
+        // Only the start was kept; the output is already longer than any expected one
+        TestStatus::Failed
+    } else if !filtered_stderr.is_empty() && options.stderr_policy == StderrPolicy::Fail {
         // Any output to stderr indicates an error/warning - mark as failed
         TestStatus::Failed
+    } else if let Some(verdict) = checker_verdict {
+        if verdict == CheckerVerdict::Accepted {
+            TestStatus::Passed
+        } else {
+            TestStatus::Failed
+        }
     } else {
-        // Compare normalized outputs
-        let actual = normalize_output(&output.stdout);
-        let expected = normalize_output(&test_case.expected_output);
+        let matched = match options.output_mode {
+            OutputMode::Text => {
+                if let Some(pipeline) = &options.pipeline {
+                    text_matches(pipeline, &output.stdout, &test_case.expected_output)
+                } else {
+                    // Compare normalized outputs (test case mode overrides the job default)
+                    let mode = test_case.comparison.unwrap_or(options.default_comparison);
+                    outputs_match(&output.stdout, &test_case.expected_output, mode, &options.normalization)
+                }
+            }
+            OutputMode::Binary => {
+                let actual = output.stdout_bytes.as_deref().unwrap_or(output.stdout.as_bytes());
+                bytes_match(actual, &test_case.expected_output)
+            }
+        };
 
-        if actual == expected {
+        if matched {
             TestStatus::Passed
         } else {
             TestStatus::Failed
         }
     };
 
+    // Binary mode returns the exact bytes alongside the lossy text
+    let (stdout_base64, stderr_base64) = match options.output_mode {
+        OutputMode::Text => (None, None),
+        OutputMode::Binary => (
+            Some(general_purpose::STANDARD.encode(
+                output.stdout_bytes.as_deref().unwrap_or(output.stdout.as_bytes()),
+            )),
+            Some(general_purpose::STANDARD.encode(
+                output.stderr_bytes.as_deref().unwrap_or(output.stderr.as_bytes()),
+            )),
+        ),
+    };
+
     // Defensive assertion: Runtime errors and timeouts can NEVER result in Passed status
     debug_assert!(
         !(output.runtime_error && matches!(status, TestStatus::Passed)),
@@ -165,35 +380,81 @@ pub fn evaluate_test(output: &TestExecutionOutput, test_case: &TestCase) -> Test
         output.test_id
     );
 
+    // A test failed on its output says where the output went wrong
+    let diff = if status == TestStatus::Failed && checker_verdict.is_none() && options.output_mode == OutputMode::Text {
+        let legacy;
+        let pipeline = match &options.pipeline {
+            Some(pipeline) => pipeline,
+            None => {
+                let mode = test_case.comparison.unwrap_or(options.default_comparison);
+                legacy = Pipeline::legacy(mode, &options.normalization);
+                &legacy
+            }
+        };
+        crate::diff::first_difference(pipeline, &output.stdout, &test_case.expected_output)
+    } else {
+        None
+    };
+
+    let failure = if output.compilation_failed {
+        Some(TestFailure::CompilationError)
+    } else if output.failure == Some(TestFailure::SandboxError) {
+        Some(TestFailure::SandboxError)
+    } else if checker_verdict == Some(CheckerVerdict::Error) {
+        Some(TestFailure::CheckerError)
+    } else {
+        None
+    };
+
     TestResult {
         test_id: output.test_id,
         status,
         stdout: output.stdout.clone(),
         stderr: output.stderr.clone(),
         execution_time_ms: output.execution_time_ms,
+        stdout_base64,
+        stderr_base64,
+        overhead_ms: output.overhead_ms,
+        cpu_time_ms: output.cpu_time_ms,
+        wall_time_ms: Some(output.execution_time_ms),
+        memory_kb: output.memory_kb,
+        failure,
+        checker_output: output.checker.as_ref().map(|c| c.message.clone()).filter(|m| !m.is_empty()),
+        sanitizer_report: (options.sanitizers && output.runtime_error && !output.compilation_failed)
+            .then(|| crate::sanitizer::report(&output.stderr))
+            .flatten(),
+        diff,
     }
 }
 
+/// Whether the sandbox, not the program, failed to run the test (judged an internal error);
+/// only the engine sets the flag, whatever the program wrote to stderr
+pub fn is_sandbox_failure(output: &TestExecutionOutput) -> bool {
+    output.failure == Some(TestFailure::SandboxError)
+}
+
 /// Aggregate multiple test results into final execution result
 ///
 /// This function:
-/// 1. Calculates total score (sum of passed test weights)
+/// 1. Calculates total score (sum of passed test weights, plus each subtask's score)
 /// 2. Calculates max possible score (sum of all weights)
 /// 3. Determines overall status (Completed if any passed, Failed otherwise)
 ///
 /// ## Arguments
 /// * `outputs` - Raw execution outputs from engine
 /// * `job` - Original job request with test cases
+/// * `options` - Evaluation settings (`EvaluationOptions::for_language` in production)
 ///
 /// ## Returns
 /// Complete ExecutionResult with aggregated scores and status
 pub fn aggregate_results(
     outputs: &[TestExecutionOutput],
     job: &JobRequest,
+    options: &EvaluationOptions,
 ) -> ExecutionResult {
     let mut test_results = Vec::new();
     let mut total_score = 0u32;
-    let max_score: u32 = job.test_cases.iter().map(|tc| tc.weight).sum();
+    let max_score = job.max_score();
 
     println!("→ Evaluating {} test outputs", outputs.len());
     println!("  Max possible score: {}", max_score);
@@ -208,11 +469,11 @@ pub fn aggregate_results(
             .expect("Test case not found for output");
 
         // Evaluate single test
-        let test_result = evaluate_test(output, test_case);
+        let test_result = evaluate_test(output, test_case, options);
 
-        // Update score if passed
-        if test_result.status == TestStatus::Passed {
-            total_score += test_case.weight;
+        // Update score if passed (grouped tests score with their subtask below)
+        if test_result.status == TestStatus::Passed && test_case.group_id.is_none() {
+            total_score = total_score.saturating_add(test_case.weight);
         }
 
         // Log evaluation result
@@ -228,14 +489,16 @@ pub fn aggregate_results(
             TestStatus::Passed => println!("    ✓ Output matched"),
             TestStatus::RuntimeError => println!("    ✗ Runtime error"),
             TestStatus::TimeLimitExceeded => println!("    ✗ Timeout"),
+            TestStatus::MemoryLimitExceeded => println!("    ✗ Memory limit exceeded"),
             TestStatus::Failed => {
-                if !output.stderr.trim().is_empty() {
+                if !output.stderr.trim().is_empty() && options.stderr_policy == StderrPolicy::Fail {
                     println!("    ✗ Error/warning detected in stderr");
                     println!("    stderr: \"{}\"", output.stderr.trim());
                 } else {
+                    let mode = test_case.comparison.unwrap_or(options.default_comparison);
                     println!("    ✗ Output mismatch");
-                    println!("    Expected: \"{}\"", normalize_output(&test_case.expected_output));
-                    println!("    Got:      \"{}\"", normalize_output(&output.stdout));
+                    println!("    Expected: \"{}\"", normalize_output(&test_case.expected_output, mode, &options.normalization));
+                    println!("    Got:      \"{}\"", normalize_output(&output.stdout, mode, &options.normalization));
                 }
             }
         }
@@ -243,16 +506,19 @@ pub fn aggregate_results(
         test_results.push(test_result);
     }
 
-    // Determine overall status
-    let overall_status = if total_score > 0 {
-        JobStatus::Completed
-    } else {
-        JobStatus::Failed
-    };
+    let subtasks = subtask_scores(job, &test_results);
+    let total_score = subtasks.iter().fold(total_score, |sum, subtask| sum.saturating_add(subtask.score));
+    // Outputs repeating a test id must not push the score past the maximum
+    let total_score = total_score.min(max_score);
+    let all_passed = test_results.iter().all(|r| r.status == TestStatus::Passed);
+    let overall_status = overall_status(job.status_policy, total_score, max_score, all_passed);
 
     println!();
     println!("→ Evaluation complete");
     println!("  Score: {} / {}", total_score, max_score);
+    for subtask in &subtasks {
+        println!("  Subtask {}: {} / {}", subtask.group_id, subtask.score, subtask.max_score);
+    }
     println!("  Status: {:?}", overall_status);
 
     ExecutionResult {
@@ -261,6 +527,54 @@ pub fn aggregate_results(
         score: total_score,
         max_score,
         results: test_results,
+        judge_env_version: None,
+        worker_id: None,
+        signature: None,
+        points: job.scoring.map(|scaling| scaling.scale(total_score, max_score)),
+        timings: None,
+        misuse: Vec::new(),
+        effective_timeout_ms: None,
+        subtasks,
+    }
+}
+
+/// Score each test group (subtask) under its group scoring, by group id
+///
+/// A grouped test without a passing result (failed, or never run) counts as failed.
+fn subtask_scores(job: &JobRequest, results: &[TestResult]) -> Vec<SubtaskScore> {
+    let mut groups: BTreeMap<u32, Vec<(u32, bool)>> = BTreeMap::new();
+    for test_case in &job.test_cases {
+        if let Some(group_id) = test_case.group_id {
+            let passed = results.iter().any(|r| r.test_id == test_case.id && r.status == TestStatus::Passed);
+            groups.entry(group_id).or_default().push((test_case.weight, passed));
+        }
+    }
+    groups
+        .into_iter()
+        .map(|(group_id, weights)| {
+            let max_score = weights.iter().fold(0u32, |sum, (weight, _)| sum.saturating_add(*weight));
+            SubtaskScore {
+                group_id,
+                score: job.subtask_scoring.get(&group_id).unwrap_or(&job.group_scoring).score(max_score, &weights),
+                max_score,
+                passed: weights.iter().all(|(_, passed)| *passed),
+            }
+        })
+        .collect()
+}
+
+/// Determine the job's overall status from its score
+///
+/// Without a policy: Completed if any test passed, Failed otherwise.
+/// With one: Passed if every test passed, PartiallyPassed if the policy accepts
+/// the score anyway, Failed otherwise.
+fn overall_status(policy: Option<StatusPolicy>, score: u32, max_score: u32, all_passed: bool) -> JobStatus {
+    match policy {
+        None if score > 0 => JobStatus::Completed,
+        None => JobStatus::Failed,
+        Some(policy) if !policy.accepts(score, max_score, all_passed) => JobStatus::Failed,
+        Some(_) if all_passed => JobStatus::Passed,
+        Some(_) => JobStatus::PartiallyPassed,
     }
 }
 
@@ -273,17 +587,22 @@ pub fn aggregate_results(
 /// ## Arguments
 /// * `job` - The original job request (for test cases and expected outputs)
 /// * `outputs` - Raw execution outputs from the execution engine
+/// * `options` - Evaluation settings (see `EvaluationOptions::for_language`)
 ///
 /// ## Returns
 /// Complete ExecutionResult with scores and aggregated status
-pub fn evaluate(job: &JobRequest, outputs: Vec<TestExecutionOutput>) -> ExecutionResult {
-    aggregate_results(&outputs, job)
+pub fn evaluate(job: &JobRequest, outputs: Vec<TestExecutionOutput>, options: &EvaluationOptions) -> ExecutionResult {
+    let mut result = aggregate_results(&outputs, job, options);
+    if options.misuse_detection {
+        result.misuse = crate::misuse::scan(job, &outputs);
+    }
+    result
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use optimus_common::types::{Language, TestCase};
+    use optimus_common::types::{Comparator, GroupScoring, Language, Normalizer, ScoreScaling, TestCase};
     use uuid::Uuid;
 
     /// Helper to create a test case
@@ -293,6 +612,7 @@ mod tests {
             input: "input".to_string(),
             expected_output: expected_output.to_string(),
             weight,
+            ..Default::default()
         }
     }
 
@@ -306,11 +626,35 @@ mod tests {
             timed_out: false,
             runtime_error: false,
             compilation_failed: false,
+            stdout_bytes: None,
+            stderr_bytes: None,
+            stdout_total_bytes: None,
+            stderr_total_bytes: None,
+            overhead_ms: None,
+            cpu_time_ms: None,
+            memory_kb: None,
+            resource_samples: None,
+            oom_killed: false,
+            failure: None,
+            checker: None,
         }
     }
 
+    /// Helper to create a Python job with default settings
+    fn make_job(test_cases: Vec<TestCase>) -> JobRequest {
+        serde_json::from_value(serde_json::json!({
+            "id": Uuid::new_v4(),
+            "language": "python",
+            "source_code": "",
+            "test_cases": test_cases,
+            "timeout_ms": 5000
+        }))
+        .unwrap()
+    }
+
     #[test]
     fn test_normalize_output() {
+        let normalize_output = |output| normalize_output(output, ComparisonMode::Ordered, &NormalizationOptions::default());
         assert_eq!(normalize_output("hello"), "hello");
         assert_eq!(normalize_output("  hello  "), "hello");
         assert_eq!(normalize_output("hello\n"), "hello");
@@ -320,24 +664,51 @@ mod tests {
         assert_eq!(normalize_output("   "), "");
     }
 
+    #[test]
+    fn test_job_normalization_options() {
+        let options = NormalizationOptions { ignore_case: true, collapse_whitespace: true, ..Default::default() };
+        assert_eq!(normalize_output("  Hello   World \n", ComparisonMode::Ordered, &options), "hello world");
+
+        let job = EvaluationOptions { normalization: options, ..Default::default() };
+        let result = evaluate_test(&make_output(1, "YES  it\tIS", 5), &make_test_case(1, "yes it is", 10), &job);
+        assert_eq!(result.status, TestStatus::Passed);
+        let strict = evaluate_test(&make_output(1, "YES  it\tIS", 5), &make_test_case(1, "yes it is", 10), &EvaluationOptions::default());
+        assert_eq!(strict.status, TestStatus::Failed);
+    }
+
     #[test]
     fn test_evaluate_test_exact_match() {
         let test_case = make_test_case(1, "120", 10);
         let output = make_output(1, "120", 42);
 
-        let result = evaluate_test(&output, &test_case);
+        let result = evaluate_test(&output, &test_case, &EvaluationOptions::default());
 
         assert_eq!(result.status, TestStatus::Passed);
         assert_eq!(result.test_id, 1);
         assert_eq!(result.execution_time_ms, 42);
     }
 
+    #[test]
+    fn test_truncated_stdout_fails() {
+        let test_case = make_test_case(1, "yes", 10);
+        // The program printed "yes" followed by a flood of whitespace, of which the start was kept
+        let output = TestExecutionOutput {
+            stdout_total_bytes: Some(1 << 30),
+            ..make_output(1, "yes   ", 5)
+        };
+        assert!(output.stdout_truncated());
+        assert_eq!(evaluate_test(&output, &test_case, &EvaluationOptions::default()).status, TestStatus::Failed);
+
+        let counted = TestExecutionOutput { stdout_total_bytes: Some(6), ..make_output(1, "yes   ", 5) };
+        assert_eq!(evaluate_test(&counted, &test_case, &EvaluationOptions::default()).status, TestStatus::Passed);
+    }
+
     #[test]
     fn test_evaluate_test_with_whitespace() {
         let test_case = make_test_case(1, "hello", 10);
         let output = make_output(1, "  hello  \n", 5);
 
-        let result = evaluate_test(&output, &test_case);
+        let result = evaluate_test(&output, &test_case, &EvaluationOptions::default());
 
         assert_eq!(result.status, TestStatus::Passed);
     }
@@ -347,9 +718,12 @@ mod tests {
         let test_case = make_test_case(1, "expected", 10);
         let output = make_output(1, "actual", 5);
 
-        let result = evaluate_test(&output, &test_case);
+        let result = evaluate_test(&output, &test_case, &EvaluationOptions::default());
 
         assert_eq!(result.status, TestStatus::Failed);
+        let diff = result.diff.expect("a wrong answer says where it differs");
+        assert_eq!((diff.line, diff.column, diff.actual.as_deref()), (1, 1, Some("actual")));
+        assert!(evaluate_test(&make_output(1, "expected", 5), &test_case, &EvaluationOptions::default()).diff.is_none());
     }
 
     #[test]
@@ -363,9 +737,20 @@ mod tests {
             timed_out: false,
             runtime_error: true,
             compilation_failed: false,
+            stdout_bytes: None,
+            stderr_bytes: None,
+            stdout_total_bytes: None,
+            stderr_total_bytes: None,
+            overhead_ms: None,
+            cpu_time_ms: None,
+            memory_kb: None,
+            resource_samples: None,
+            oom_killed: false,
+            failure: None,
+            checker: None,
         };
 
-        let result = evaluate_test(&output, &test_case);
+        let result = evaluate_test(&output, &test_case, &EvaluationOptions::default());
 
         assert_eq!(result.status, TestStatus::RuntimeError);
     }
@@ -381,9 +766,20 @@ mod tests {
             timed_out: true,
             runtime_error: false,
             compilation_failed: false,
+            stdout_bytes: None,
+            stderr_bytes: None,
+            stdout_total_bytes: None,
+            stderr_total_bytes: None,
+            overhead_ms: None,
+            cpu_time_ms: None,
+            memory_kb: None,
+            resource_samples: None,
+            oom_killed: false,
+            failure: None,
+            checker: None,
         };
 
-        let result = evaluate_test(&output, &test_case);
+        let result = evaluate_test(&output, &test_case, &EvaluationOptions::default());
 
         assert_eq!(result.status, TestStatus::TimeLimitExceeded);
     }
@@ -400,16 +796,19 @@ mod tests {
                     input: "5".to_string(),
                     expected_output: "120".to_string(),
                     weight: 10,
+                    ..Default::default()
                 },
                 TestCase {
                     id: 2,
                     input: "3".to_string(),
                     expected_output: "6".to_string(),
                     weight: 15,
+                    ..Default::default()
                 },
             ],
             timeout_ms: 5000,
             metadata: optimus_common::types::JobMetadata::default(),
+            ..Default::default()
         };
 
         let outputs = vec![
@@ -421,6 +820,17 @@ mod tests {
                 timed_out: false,
                 runtime_error: false,
                 compilation_failed: false,
+                stdout_bytes: None,
+                stderr_bytes: None,
+                stdout_total_bytes: None,
+                stderr_total_bytes: None,
+                overhead_ms: None,
+                cpu_time_ms: None,
+                memory_kb: None,
+                resource_samples: None,
+                oom_killed: false,
+                failure: None,
+                checker: None,
             },
             TestExecutionOutput {
                 test_id: 2,
@@ -430,10 +840,21 @@ mod tests {
                 timed_out: false,
                 runtime_error: false,
                 compilation_failed: false,
+                stdout_bytes: None,
+                stderr_bytes: None,
+                stdout_total_bytes: None,
+                stderr_total_bytes: None,
+                overhead_ms: None,
+                cpu_time_ms: None,
+                memory_kb: None,
+                resource_samples: None,
+                oom_killed: false,
+                failure: None,
+                checker: None,
             },
         ];
 
-        let result = evaluate(&job, outputs);
+        let result = evaluate(&job, outputs, &EvaluationOptions::for_job(&job));
 
         assert_eq!(result.overall_status, JobStatus::Completed);
         assert_eq!(result.score, 25);
@@ -454,16 +875,19 @@ mod tests {
                     input: "input".to_string(),
                     expected_output: "correct".to_string(),
                     weight: 20,
+                    ..Default::default()
                 },
                 TestCase {
                     id: 2,
                     input: "input".to_string(),
                     expected_output: "wrong".to_string(),
                     weight: 30,
+                    ..Default::default()
                 },
             ],
             timeout_ms: 5000,
             metadata: optimus_common::types::JobMetadata::default(),
+            ..Default::default()
         };
 
         let outputs = vec![
@@ -475,6 +899,17 @@ mod tests {
                 timed_out: false,
                 runtime_error: false,
                 compilation_failed: false,
+                stdout_bytes: None,
+                stderr_bytes: None,
+                stdout_total_bytes: None,
+                stderr_total_bytes: None,
+                overhead_ms: None,
+                cpu_time_ms: None,
+                memory_kb: None,
+                resource_samples: None,
+                oom_killed: false,
+                failure: None,
+                checker: None,
             },
             TestExecutionOutput {
                 test_id: 2,
@@ -484,10 +919,21 @@ mod tests {
                 timed_out: false,
                 runtime_error: false,
                 compilation_failed: false,
+                stdout_bytes: None,
+                stderr_bytes: None,
+                stdout_total_bytes: None,
+                stderr_total_bytes: None,
+                overhead_ms: None,
+                cpu_time_ms: None,
+                memory_kb: None,
+                resource_samples: None,
+                oom_killed: false,
+                failure: None,
+                checker: None,
             },
         ];
 
-        let result = evaluate(&job, outputs);
+        let result = evaluate(&job, outputs, &EvaluationOptions::for_job(&job));
 
         assert_eq!(result.overall_status, JobStatus::Completed);
         assert_eq!(result.score, 20);
@@ -508,6 +954,7 @@ mod tests {
             ],
             timeout_ms: 5000,
             metadata: optimus_common::types::JobMetadata::default(),
+            ..Default::default()
         };
 
         let outputs = vec![
@@ -515,7 +962,7 @@ mod tests {
             make_output(2, "wrong2", 10),
         ];
 
-        let result = evaluate(&job, outputs);
+        let result = evaluate(&job, outputs, &EvaluationOptions::for_job(&job));
 
         assert_eq!(result.overall_status, JobStatus::Failed);
         assert_eq!(result.score, 0);
@@ -535,9 +982,11 @@ mod tests {
                 input: "input".to_string(),
                 expected_output: "output".to_string(),
                 weight: 10,
+                ..Default::default()
             }],
             timeout_ms: 5000,
             metadata: optimus_common::types::JobMetadata::default(),
+            ..Default::default()
         };
 
         let outputs = vec![TestExecutionOutput {
@@ -548,9 +997,20 @@ mod tests {
             timed_out: false,
             runtime_error: true,
             compilation_failed: false,
+            stdout_bytes: None,
+            stderr_bytes: None,
+            stdout_total_bytes: None,
+            stderr_total_bytes: None,
+            overhead_ms: None,
+            cpu_time_ms: None,
+            memory_kb: None,
+            resource_samples: None,
+            oom_killed: false,
+            failure: None,
+            checker: None,
         }];
 
-        let result = evaluate(&job, outputs);
+        let result = evaluate(&job, outputs, &EvaluationOptions::for_job(&job));
 
         assert_eq!(result.overall_status, JobStatus::Failed);
         assert_eq!(result.score, 0);
@@ -568,9 +1028,11 @@ mod tests {
                 input: "input".to_string(),
                 expected_output: "output".to_string(),
                 weight: 5,
+                ..Default::default()
             }],
             timeout_ms: 1000,
             metadata: optimus_common::types::JobMetadata::default(),
+            ..Default::default()
         };
 
         let outputs = vec![TestExecutionOutput {
@@ -581,9 +1043,20 @@ mod tests {
             timed_out: true,
             runtime_error: false,
             compilation_failed: false,
+            stdout_bytes: None,
+            stderr_bytes: None,
+            stdout_total_bytes: None,
+            stderr_total_bytes: None,
+            overhead_ms: None,
+            cpu_time_ms: None,
+            memory_kb: None,
+            resource_samples: None,
+            oom_killed: false,
+            failure: None,
+            checker: None,
         }];
 
-        let result = evaluate(&job, outputs);
+        let result = evaluate(&job, outputs, &EvaluationOptions::for_job(&job));
 
         assert_eq!(result.overall_status, JobStatus::Failed);
         assert_eq!(result.score, 0);
@@ -601,9 +1074,11 @@ mod tests {
                 input: "input".to_string(),
                 expected_output: "hello".to_string(),
                 weight: 10,
+                ..Default::default()
             }],
             timeout_ms: 5000,
             metadata: optimus_common::types::JobMetadata::default(),
+            ..Default::default()
         };
 
         let outputs = vec![TestExecutionOutput {
@@ -614,9 +1089,20 @@ mod tests {
             timed_out: false,
             runtime_error: false,
             compilation_failed: false,
+            stdout_bytes: None,
+            stderr_bytes: None,
+            stdout_total_bytes: None,
+            stderr_total_bytes: None,
+            overhead_ms: None,
+            cpu_time_ms: None,
+            memory_kb: None,
+            resource_samples: None,
+            oom_killed: false,
+            failure: None,
+            checker: None,
         }];
 
-        let result = evaluate(&job, outputs);
+        let result = evaluate(&job, outputs, &EvaluationOptions::for_job(&job));
 
         assert_eq!(result.overall_status, JobStatus::Completed);
         assert_eq!(result.score, 10);
@@ -632,12 +1118,13 @@ mod tests {
             test_cases: vec![make_test_case(1, "line1\nline2\nline3", 10)],
             timeout_ms: 5000,
             metadata: optimus_common::types::JobMetadata::default(),
+            ..Default::default()
         };
 
         // Different newline styles should match after normalization
         let outputs = vec![make_output(1, "line1\nline2\nline3\n", 10)];
 
-        let result = evaluate(&job, outputs);
+        let result = evaluate(&job, outputs, &EvaluationOptions::for_job(&job));
 
         assert_eq!(result.results[0].status, TestStatus::Passed);
         assert_eq!(result.score, 10);
@@ -652,11 +1139,12 @@ mod tests {
             test_cases: vec![make_test_case(1, "", 5)],
             timeout_ms: 5000,
             metadata: optimus_common::types::JobMetadata::default(),
+            ..Default::default()
         };
 
         let outputs = vec![make_output(1, "   \n", 5)];
 
-        let result = evaluate(&job, outputs);
+        let result = evaluate(&job, outputs, &EvaluationOptions::for_job(&job));
 
         assert_eq!(result.results[0].status, TestStatus::Passed);
         assert_eq!(result.score, 5);
@@ -671,11 +1159,12 @@ mod tests {
             test_cases: vec![make_test_case(1, "Hello", 10)],
             timeout_ms: 5000,
             metadata: optimus_common::types::JobMetadata::default(),
+            ..Default::default()
         };
 
         let outputs = vec![make_output(1, "hello", 10)];
 
-        let result = evaluate(&job, outputs);
+        let result = evaluate(&job, outputs, &EvaluationOptions::for_job(&job));
 
         // Case should matter - this should fail
         assert_eq!(result.results[0].status, TestStatus::Failed);
@@ -696,6 +1185,7 @@ mod tests {
             ],
             timeout_ms: 1000,
             metadata: optimus_common::types::JobMetadata::default(),
+            ..Default::default()
         };
 
         let outputs = vec![
@@ -709,6 +1199,17 @@ mod tests {
                 timed_out: true,
                 runtime_error: false,
                 compilation_failed: false,
+                stdout_bytes: None,
+                stderr_bytes: None,
+                stdout_total_bytes: None,
+                stderr_total_bytes: None,
+                overhead_ms: None,
+                cpu_time_ms: None,
+                memory_kb: None,
+                resource_samples: None,
+                oom_killed: false,
+                failure: None,
+                checker: None,
             },
             TestExecutionOutput {
                 test_id: 4,
@@ -718,10 +1219,21 @@ mod tests {
                 timed_out: false,
                 runtime_error: true,
                 compilation_failed: false,
+                stdout_bytes: None,
+                stderr_bytes: None,
+                stdout_total_bytes: None,
+                stderr_total_bytes: None,
+                overhead_ms: None,
+                cpu_time_ms: None,
+                memory_kb: None,
+                resource_samples: None,
+                oom_killed: false,
+                failure: None,
+                checker: None,
             },
         ];
 
-        let result = evaluate(&job, outputs);
+        let result = evaluate(&job, outputs, &EvaluationOptions::for_job(&job));
 
         assert_eq!(result.overall_status, JobStatus::Completed); // At least one passed
         assert_eq!(result.score, 10); // Only first test passed
@@ -744,15 +1256,17 @@ mod tests {
                     input: "input".to_string(),
                     expected_output: "output".to_string(),
                     weight: 0,
+                    ..Default::default()
                 },
             ],
             timeout_ms: 5000,
             metadata: optimus_common::types::JobMetadata::default(),
+            ..Default::default()
         };
 
         let outputs = vec![make_output(1, "output", 10)];
 
-        let result = evaluate(&job, outputs);
+        let result = evaluate(&job, outputs, &EvaluationOptions::for_job(&job));
 
         // Even though test passed, score is 0
         assert_eq!(result.score, 0);
@@ -761,6 +1275,45 @@ mod tests {
         assert_eq!(result.overall_status, JobStatus::Failed);
     }
 
+    #[test]
+    fn test_hostile_weights_saturate() {
+        let job = JobRequest {
+            id: Uuid::new_v4(),
+            language: Language::Python,
+            source_code: String::new(),
+            test_cases: (1..=100).map(|id| make_test_case(id, "ok", u32::MAX)).collect(),
+            timeout_ms: 5000,
+            metadata: optimus_common::types::JobMetadata::default(),
+            status_policy: Some(StatusPolicy::Threshold(50.0)),
+            scoring: Some(ScoreScaling { max_points: 100, rounding: Default::default(), decimals: 0 }),
+            ..Default::default()
+        };
+
+        // Every test passes, and test 1 is reported twice
+        let mut outputs: Vec<_> = (1..=100).map(|id| make_output(id, "ok", 10)).collect();
+        outputs.push(make_output(1, "ok", 10));
+        let result = evaluate(&job, outputs, &EvaluationOptions::for_job(&job));
+        assert_eq!(result.max_score, u32::MAX);
+        assert_eq!(result.score, u32::MAX);
+        assert_eq!(result.points.unwrap().points, 100.0);
+    }
+
+    #[test]
+    fn test_status_policies() {
+        // 30 of 40 points: tests 1-3 pass, test 4 fails
+        let status = |policy| overall_status(Some(policy), 30, 40, false);
+        assert_eq!(status(StatusPolicy::AnyPass), JobStatus::PartiallyPassed);
+        assert_eq!(status(StatusPolicy::AllPass), JobStatus::Failed);
+        assert_eq!(status(StatusPolicy::Threshold(75.0)), JobStatus::PartiallyPassed);
+        assert_eq!(status(StatusPolicy::Threshold(80.0)), JobStatus::Failed);
+
+        assert_eq!(overall_status(Some(StatusPolicy::AllPass), 40, 40, true), JobStatus::Passed);
+        assert_eq!(overall_status(Some(StatusPolicy::AnyPass), 0, 40, false), JobStatus::Failed);
+        // No policy keeps the original semantics
+        assert_eq!(overall_status(None, 30, 40, false), JobStatus::Completed);
+        assert_eq!(overall_status(None, 40, 40, true), JobStatus::Completed);
+    }
+
     #[test]
     fn test_aggregate_results_directly() {
         let job = JobRequest {
@@ -773,6 +1326,7 @@ mod tests {
             ],
             timeout_ms: 5000,
             metadata: optimus_common::types::JobMetadata::default(),
+            ..Default::default()
         };
 
         let outputs = vec![
@@ -780,7 +1334,7 @@ mod tests {
             make_output(2, "world", 75),
         ];
 
-        let result = aggregate_results(&outputs, &job);
+        let result = aggregate_results(&outputs, &job, &EvaluationOptions::for_job(&job));
 
         assert_eq!(result.score, 40);
         assert_eq!(result.max_score, 40);
@@ -788,6 +1342,58 @@ mod tests {
         assert_eq!(result.job_id, job.id);
     }
 
+    #[test]
+    fn test_subtasks_score_as_groups() {
+        // Subtask 1: tests 1-2 (10 + 20), subtask 2: tests 3-4 (30 + 40), test 5 on its own (5)
+        let mut job = make_job(vec![
+            make_test_case(1, "a", 10),
+            make_test_case(2, "b", 20),
+            make_test_case(3, "c", 30),
+            make_test_case(4, "d", 40),
+            make_test_case(5, "e", 5),
+        ]);
+        for (test_case, group_id) in job.test_cases.iter_mut().zip([Some(1), Some(1), Some(2), Some(2), None]) {
+            test_case.group_id = group_id;
+        }
+        // Subtask 1 passes, subtask 2 fails test 4, test 5 passes
+        let outputs = vec![
+            make_output(1, "a", 10),
+            make_output(2, "b", 10),
+            make_output(3, "c", 10),
+            make_output(4, "wrong", 10),
+            make_output(5, "e", 10),
+        ];
+
+        let result = aggregate_results(&outputs, &job, &EvaluationOptions::for_job(&job));
+        assert_eq!((result.score, result.max_score), (35, 105));
+        let scores: Vec<_> = result.subtasks.iter().map(|s| (s.group_id, s.score, s.max_score, s.passed)).collect();
+        assert_eq!(scores, [(1, 30, 30, true), (2, 0, 70, false)]);
+
+        job.group_scoring = GroupScoring::Sum;
+        let result = aggregate_results(&outputs, &job, &EvaluationOptions::for_job(&job));
+        assert_eq!(result.score, 65);
+        assert_eq!(result.subtasks[1].score, 30);
+
+        // Each subtask can be scored its own way
+        job.group_scoring = GroupScoring::AllOrNothing;
+        job.subtask_scoring = [(2, GroupScoring::Sum)].into();
+        let result = aggregate_results(&outputs, &job, &EvaluationOptions::for_job(&job));
+        assert_eq!(result.score, 65);
+        job.subtask_scoring = [(1, GroupScoring::Sum)].into();
+        let result = aggregate_results(&outputs, &job, &EvaluationOptions::for_job(&job));
+        assert_eq!(result.score, 35);
+
+        // A grouped test that never ran fails its group
+        job.subtask_scoring.clear();
+        let result = aggregate_results(&outputs[..2], &job, &EvaluationOptions::for_job(&job));
+        assert_eq!(result.score, 30);
+        assert_eq!(result.subtasks[1].score, 0);
+
+        // Jobs without groups report no subtasks
+        let plain = make_job(vec![make_test_case(1, "a", 10)]);
+        assert!(aggregate_results(&outputs[..1], &plain, &EvaluationOptions::for_job(&plain)).subtasks.is_empty());
+    }
+
     // ============================================================================
     // CRITICAL INVARIANT TESTS - These prevent regressions of the core contract
     // ============================================================================
@@ -807,9 +1413,20 @@ mod tests {
             stderr: "Traceback (most recent call last):\n  File \"test.py\", line 1\nZeroDivisionError".to_string(),
             execution_time_ms: 10,
             compilation_failed: false,
+            stdout_bytes: None,
+            stderr_bytes: None,
+            stdout_total_bytes: None,
+            stderr_total_bytes: None,
+            overhead_ms: None,
+            cpu_time_ms: None,
+            memory_kb: None,
+            resource_samples: None,
+            oom_killed: false,
+            failure: None,
+            checker: None,
         };
 
-        let result = evaluate_test(&exec, &test_case);
+        let result = evaluate_test(&exec, &test_case, &EvaluationOptions::default());
 
         // MUST be RuntimeError, NOT Passed
         assert_eq!(result.status, TestStatus::RuntimeError, 
@@ -832,9 +1449,20 @@ mod tests {
             stderr: String::new(),
             execution_time_ms: 5001,
             compilation_failed: false,
+            stdout_bytes: None,
+            stderr_bytes: None,
+            stdout_total_bytes: None,
+            stderr_total_bytes: None,
+            overhead_ms: None,
+            cpu_time_ms: None,
+            memory_kb: None,
+            resource_samples: None,
+            oom_killed: false,
+            failure: None,
+            checker: None,
         };
 
-        let result = evaluate_test(&exec, &test_case);
+        let result = evaluate_test(&exec, &test_case, &EvaluationOptions::default());
 
         // MUST be TimeLimitExceeded, NOT Passed
         assert_eq!(result.status, TestStatus::TimeLimitExceeded,
@@ -854,9 +1482,20 @@ mod tests {
             stderr: String::new(),
             execution_time_ms: 42,
             compilation_failed: false,
+            stdout_bytes: None,
+            stderr_bytes: None,
+            stdout_total_bytes: None,
+            stderr_total_bytes: None,
+            overhead_ms: None,
+            cpu_time_ms: None,
+            memory_kb: None,
+            resource_samples: None,
+            oom_killed: false,
+            failure: None,
+            checker: None,
         };
 
-        let result = evaluate_test(&exec, &test_case);
+        let result = evaluate_test(&exec, &test_case, &EvaluationOptions::default());
 
         assert_eq!(result.status, TestStatus::Passed,
             "Clean execution with correct output MUST pass");
@@ -876,9 +1515,20 @@ mod tests {
             stderr: "Error".to_string(),
             execution_time_ms: 5001,
             compilation_failed: false,
+            stdout_bytes: None,
+            stderr_bytes: None,
+            stdout_total_bytes: None,
+            stderr_total_bytes: None,
+            overhead_ms: None,
+            cpu_time_ms: None,
+            memory_kb: None,
+            resource_samples: None,
+            oom_killed: false,
+            failure: None,
+            checker: None,
         };
 
-        let result = evaluate_test(&exec, &test_case);
+        let result = evaluate_test(&exec, &test_case, &EvaluationOptions::default());
 
         assert_eq!(result.status, TestStatus::RuntimeError,
             "RuntimeError must take precedence over timeout");
@@ -896,6 +1546,7 @@ mod tests {
             ],
             timeout_ms: 5000,
             metadata: optimus_common::types::JobMetadata::default(),
+            ..Default::default()
         };
 
         let outputs = vec![TestExecutionOutput {
@@ -906,9 +1557,20 @@ mod tests {
             stderr: "RuntimeError".to_string(),
             execution_time_ms: 10,
             compilation_failed: false,
+            stdout_bytes: None,
+            stderr_bytes: None,
+            stdout_total_bytes: None,
+            stderr_total_bytes: None,
+            overhead_ms: None,
+            cpu_time_ms: None,
+            memory_kb: None,
+            resource_samples: None,
+            oom_killed: false,
+            failure: None,
+            checker: None,
         }];
 
-        let result = evaluate(&job, outputs);
+        let result = evaluate(&job, outputs, &EvaluationOptions::for_job(&job));
 
         assert_eq!(result.score, 0, "Runtime error test must contribute 0 to score");
         assert_eq!(result.max_score, 50);
@@ -927,6 +1589,7 @@ mod tests {
             ],
             timeout_ms: 1000,
             metadata: optimus_common::types::JobMetadata::default(),
+            ..Default::default()
         };
 
         let outputs = vec![TestExecutionOutput {
@@ -937,9 +1600,20 @@ mod tests {
             stderr: String::new(),
             execution_time_ms: 1001,
             compilation_failed: false,
+            stdout_bytes: None,
+            stderr_bytes: None,
+            stdout_total_bytes: None,
+            stderr_total_bytes: None,
+            overhead_ms: None,
+            cpu_time_ms: None,
+            memory_kb: None,
+            resource_samples: None,
+            oom_killed: false,
+            failure: None,
+            checker: None,
         }];
 
-        let result = evaluate(&job, outputs);
+        let result = evaluate(&job, outputs, &EvaluationOptions::for_job(&job));
 
         assert_eq!(result.score, 0, "Timeout test must contribute 0 to score");
         assert_eq!(result.max_score, 30);
@@ -960,6 +1634,7 @@ mod tests {
             ],
             timeout_ms: 5000,
             metadata: optimus_common::types::JobMetadata::default(),
+            ..Default::default()
         };
 
         let outputs = vec![
@@ -972,6 +1647,17 @@ mod tests {
                 stderr: "Error".to_string(),
                 execution_time_ms: 10,
                 compilation_failed: false,
+                stdout_bytes: None,
+                stderr_bytes: None,
+                stdout_total_bytes: None,
+                stderr_total_bytes: None,
+                overhead_ms: None,
+                cpu_time_ms: None,
+                memory_kb: None,
+                resource_samples: None,
+                oom_killed: false,
+                failure: None,
+                checker: None,
             },
             TestExecutionOutput { // Timeout - even with correct output
                 test_id: 3,
@@ -981,10 +1667,21 @@ mod tests {
                 stderr: String::new(),
                 execution_time_ms: 5001,
                 compilation_failed: false,
+                stdout_bytes: None,
+                stderr_bytes: None,
+                stdout_total_bytes: None,
+                stderr_total_bytes: None,
+                overhead_ms: None,
+                cpu_time_ms: None,
+                memory_kb: None,
+                resource_samples: None,
+                oom_killed: false,
+                failure: None,
+                checker: None,
             },
         ];
 
-        let result = evaluate(&job, outputs);
+        let result = evaluate(&job, outputs, &EvaluationOptions::for_job(&job));
 
         assert_eq!(result.score, 20, "Only passed test should contribute");
         assert_eq!(result.max_score, 60);
@@ -1008,14 +1705,149 @@ mod tests {
             timed_out: false,
             runtime_error: false,
             compilation_failed: true,
+            stdout_bytes: None,
+            stderr_bytes: None,
+            stdout_total_bytes: None,
+            stderr_total_bytes: None,
+            overhead_ms: None,
+            cpu_time_ms: None,
+            memory_kb: None,
+            resource_samples: None,
+            oom_killed: false,
+            failure: None,
+            checker: None,
         };
 
-        let result = evaluate_test(&output, &test_case);
+        let result = evaluate_test(&output, &test_case, &EvaluationOptions::default());
 
         // Compilation failure should be treated as RuntimeError
         assert_eq!(result.status, TestStatus::RuntimeError,
             "Compilation failure should result in RuntimeError status");
         assert!(result.stderr.contains("error:"));
+        assert_eq!(result.failure, Some(TestFailure::CompilationError));
+        assert_eq!(result.verdict(), optimus_common::types::Verdict::CE);
+    }
+
+    /// A sanitizer's abort is a runtime error carrying the report
+    #[test]
+    fn test_sanitizer_report_attached_to_runtime_error() {
+        let test_case = make_test_case(1, "3", 10);
+        let mut output = make_output(1, "3", 5);
+        output.runtime_error = true;
+        output.stderr = "/code/main.c:4:9: runtime error: signed integer overflow".to_string();
+
+        let sanitized = evaluate_test(&output, &test_case, &EvaluationOptions { sanitizers: true, ..Default::default() });
+        assert_eq!((sanitized.status, sanitized.verdict()), (TestStatus::RuntimeError, optimus_common::types::Verdict::RE));
+        assert_eq!(sanitized.sanitizer_report.as_deref(), Some("/code/main.c:4:9: runtime error: signed integer overflow"));
+
+        // Without sanitizers a program printing the same line is just a runtime error
+        assert_eq!(evaluate_test(&output, &test_case, &EvaluationOptions::default()).sanitizer_report, None);
+    }
+
+    /// With a checker, its exit code decides; comparison against expected_output is skipped
+    #[test]
+    fn test_checker_exit_code_decides() {
+        let test_case = make_test_case(1, "1 2 3", 10);
+        let options = EvaluationOptions { checker: true, ..Default::default() };
+        let judged = |exit_code: Option<i64>| {
+            let mut output = make_output(1, "1 3 2", 5);
+            output.checker = exit_code.map(|code| CheckerOutcome::finished(Some(code), "order differs"));
+            evaluate_test(&output, &test_case, &options)
+        };
+
+        let accepted = judged(Some(0));
+        assert_eq!(accepted.status, TestStatus::Passed);
+        assert_eq!(accepted.checker_output.as_deref(), Some("order differs"));
+
+        let rejected = judged(Some(1));
+        assert_eq!((rejected.status, rejected.failure), (TestStatus::Failed, None));
+        // The checker, not the expected output, decided
+        assert!(rejected.diff.is_none());
+
+        // A crashed or missing checker is the judge's fault, not a wrong answer
+        for broken in [judged(Some(3)), judged(None)] {
+            assert_eq!(broken.failure, Some(TestFailure::CheckerError));
+            assert_eq!(broken.verdict(), optimus_common::types::Verdict::IE);
+        }
+    }
+
+    /// Runtime errors caused by the sandbox keep their status but say why
+    #[test]
+    fn test_runtime_error_failure_kinds() {
+        let test_case = make_test_case(1, "1", 10);
+        let output = |stderr: String, failure: Option<TestFailure>| TestExecutionOutput {
+            test_id: 1,
+            stdout: String::new(),
+            stderr,
+            execution_time_ms: 3,
+            timed_out: false,
+            runtime_error: true,
+            compilation_failed: false,
+            stdout_bytes: None,
+            stderr_bytes: None,
+            stdout_total_bytes: None,
+            stderr_total_bytes: None,
+            overhead_ms: None,
+            cpu_time_ms: None,
+            memory_kb: None,
+            resource_samples: None,
+            oom_killed: false,
+            failure,
+            checker: None,
+        };
+        let failure = |stderr: String, failure| evaluate_test(&output(stderr, failure), &test_case, &EvaluationOptions::default()).failure;
+
+        let sandbox = Some(TestFailure::SandboxError);
+        assert_eq!(failure(format!("{}exec create failed", SANDBOX_FAILURE_PREFIX), sandbox), sandbox);
+        assert_eq!(failure("Traceback: ZeroDivisionError".to_string(), None), None);
+        // Only the engine says the sandbox failed; a program printing the same is its own error
+        assert_eq!(failure(format!("{}exec create failed", SANDBOX_FAILURE_PREFIX), None), None);
+
+        // Only sandbox failures are retried by the engine
+        let mut output = make_output(1, "", 10);
+        output.runtime_error = true;
+        output.stderr = format!("{}exec inspect failed", SANDBOX_FAILURE_PREFIX);
+        assert!(!is_sandbox_failure(&output), "a program printing the prefix is not retried");
+        output.failure = Some(TestFailure::SandboxError);
+        assert!(is_sandbox_failure(&output));
+        output.failure = None;
+        output.stderr = "Segmentation fault".to_string();
+        assert!(!is_sandbox_failure(&output));
+    }
+
+    /// Memory kills get their own status, whatever the program printed before
+    #[test]
+    fn test_memory_limit_exceeded() {
+        let test_case = make_test_case(1, "1", 10);
+        let mut output = TestExecutionOutput {
+            test_id: 1,
+            stdout: "1".to_string(),
+            stderr: format!("partial\n{}", OOM_NOTE),
+            execution_time_ms: 40,
+            timed_out: false,
+            runtime_error: true,
+            compilation_failed: false,
+            stdout_bytes: None,
+            stderr_bytes: None,
+            stdout_total_bytes: None,
+            stderr_total_bytes: None,
+            overhead_ms: None,
+            cpu_time_ms: None,
+            memory_kb: Some(262_144),
+            resource_samples: None,
+            oom_killed: true,
+            failure: None,
+            checker: None,
+        };
+        let result = evaluate_test(&output, &test_case, &EvaluationOptions::default());
+        assert_eq!((result.status, result.failure), (TestStatus::MemoryLimitExceeded, None));
+        assert_eq!(result.memory_kb, Some(262_144));
+        assert_eq!(result.verdict(), optimus_common::types::Verdict::MLE);
+
+        // Any other SIGKILL is a plain runtime error
+        output.oom_killed = false;
+        let result = evaluate_test(&output, &test_case, &EvaluationOptions::default());
+        assert_eq!((result.status, result.failure), (TestStatus::RuntimeError, None));
     }
 
     /// Test that compilation failure takes precedence over correct output
@@ -1032,11 +1864,252 @@ mod tests {
             timed_out: false,
             runtime_error: false,
             compilation_failed: true,
+            stdout_bytes: None,
+            stderr_bytes: None,
+            stdout_total_bytes: None,
+            stderr_total_bytes: None,
+            overhead_ms: None,
+            cpu_time_ms: None,
+            memory_kb: None,
+            resource_samples: None,
+            oom_killed: false,
+            failure: None,
+            checker: None,
         };
 
-        let result = evaluate_test(&output, &test_case);
+        let result = evaluate_test(&output, &test_case, &EvaluationOptions::default());
 
         assert_eq!(result.status, TestStatus::RuntimeError,
             "Compilation failure must take precedence even with correct output");
     }
+
+    #[test]
+    fn test_outputs_match_ordered() {
+        assert!(outputs_match("a\nb\nc\n", "a\nb\nc", ComparisonMode::Ordered, &NormalizationOptions::default()));
+        assert!(!outputs_match("c\nb\na", "a\nb\nc", ComparisonMode::Ordered, &NormalizationOptions::default()));
+    }
+
+    #[test]
+    fn test_outputs_match_unordered() {
+        assert!(outputs_match("c\nb\na\n", "a\nb\nc", ComparisonMode::Unordered, &NormalizationOptions::default()));
+        assert!(outputs_match("2 3\r\n1 2\r\n", "1 2\n2 3", ComparisonMode::Unordered, &NormalizationOptions::default()));
+        // Different lines never match
+        assert!(!outputs_match("a\nb\nd", "a\nb\nc", ComparisonMode::Unordered, &NormalizationOptions::default()));
+        // Case sensitivity is preserved
+        assert!(!outputs_match("A\nb", "a\nb", ComparisonMode::Unordered, &NormalizationOptions::default()));
+    }
+
+    #[test]
+    fn test_large_outputs_compared_line_by_line() {
+        let expected: String = (0..20_000).map(|i| format!("{}\n", i)).collect();
+        assert!(expected.len() >= STREAMING_THRESHOLD_BYTES);
+        let actual = format!("\n{}  \n\n", expected.replace('\n', "\r\n"));
+        let options = NormalizationOptions::default();
+        assert!(!outputs_match(&actual, &expected, ComparisonMode::Ordered, &options));
+        assert!(outputs_match(&actual.replace('\r', ""), &expected, ComparisonMode::Ordered, &options));
+        assert!(!outputs_match(&expected.replace("19999", "19998"), &expected, ComparisonMode::Ordered, &options));
+        // Unordered can't stream and is compared in memory
+        let reversed: String = expected.lines().rev().map(|l| format!("{}\n", l)).collect();
+        assert!(outputs_match(&reversed, &expected, ComparisonMode::Unordered, &options));
+    }
+
+    #[test]
+    fn test_outputs_match_unordered_respects_multiplicity() {
+        assert!(outputs_match("x\ny\nx", "x\nx\ny", ComparisonMode::Unordered, &NormalizationOptions::default()));
+        assert!(!outputs_match("x\ny\ny", "x\nx\ny", ComparisonMode::Unordered, &NormalizationOptions::default()));
+        assert!(!outputs_match("x\ny", "x\nx\ny", ComparisonMode::Unordered, &NormalizationOptions::default()));
+    }
+
+    #[test]
+    fn test_evaluate_test_unordered_comparison() {
+        let mut test_case = make_test_case(1, "1 2\n1 3\n2 3", 10);
+        let output = make_output(1, "2 3\n1 2\n1 3\n", 5);
+
+        // Default (ordered) rejects a different ordering
+        assert_eq!(evaluate_test(&output, &test_case, &EvaluationOptions::default()).status, TestStatus::Failed);
+
+        test_case.comparison = Some(ComparisonMode::Unordered);
+        assert_eq!(evaluate_test(&output, &test_case, &EvaluationOptions::default()).status, TestStatus::Passed);
+    }
+
+    #[test]
+    fn test_outputs_match_strict() {
+        let options = NormalizationOptions::default();
+        assert!(outputs_match("1 2\n", "1 2\n", ComparisonMode::Strict, &options));
+        // Missing or extra trailing newline is a mismatch
+        assert!(!outputs_match("1 2", "1 2\n", ComparisonMode::Strict, &options));
+        assert!(!outputs_match("1 2\n\n", "1 2\n", ComparisonMode::Strict, &options));
+        // Trailing spaces and CRLF are significant
+        assert!(!outputs_match("1 2 \n", "1 2\n", ComparisonMode::Strict, &options));
+        assert!(!outputs_match("1 2\r\n", "1 2\n", ComparisonMode::Strict, &options));
+    }
+
+    #[test]
+    fn test_job_default_comparison_and_test_override() {
+        let mut job = JobRequest {
+            id: Uuid::new_v4(),
+            language: Language::Python,
+            source_code: String::new(),
+            test_cases: vec![
+                make_test_case(1, "hello\n", 10),
+                make_test_case(2, "hello\n", 10),
+            ],
+            timeout_ms: 5000,
+            metadata: optimus_common::types::JobMetadata::default(),
+            comparison: Some(ComparisonMode::Strict),
+            ..Default::default()
+        };
+        // Test 2 opts back into the lenient ordered comparison
+        job.test_cases[1].comparison = Some(ComparisonMode::Ordered);
+
+        let outputs = vec![make_output(1, "hello", 5), make_output(2, "hello", 5)];
+        let result = evaluate(&job, outputs, &EvaluationOptions::for_job(&job));
+
+        assert_eq!(result.results[0].status, TestStatus::Failed);
+        assert_eq!(result.results[1].status, TestStatus::Passed);
+        assert_eq!(result.score, 10);
+    }
+
+    #[test]
+    fn test_language_flags_fill_in_job_defaults() {
+        let mut job = JobRequest {
+            id: Uuid::new_v4(),
+            language: Language::Python,
+            source_code: String::new(),
+            test_cases: vec![make_test_case(1, "1\n2", 10)],
+            timeout_ms: 5000,
+            metadata: optimus_common::types::JobMetadata::default(),
+            ..Default::default()
+        };
+        let flags = LanguageFlags {
+            default_comparison: Some(ComparisonMode::Unordered),
+            stderr_policy: StderrPolicy::Ignore,
+            ..Default::default()
+        };
+        let mut output = make_output(1, "2\n1", 5);
+        output.stderr = "DeprecationWarning: ...".to_string();
+
+        let options = EvaluationOptions::for_language(&job, &flags);
+        assert_eq!(evaluate_test(&output, &job.test_cases[0], &options).status, TestStatus::Passed);
+        assert_eq!(evaluate(&job, vec![output.clone()], &options).score, 10);
+
+        // The job's own choice wins over the language default
+        job.comparison = Some(ComparisonMode::Ordered);
+        let options = EvaluationOptions::for_language(&job, &flags);
+        assert_eq!(evaluate_test(&output, &job.test_cases[0], &options).status, TestStatus::Failed);
+        // Default stderr policy fails the same run
+        let options = EvaluationOptions { stderr_policy: StderrPolicy::Fail, ..EvaluationOptions::for_language(&job, &flags) };
+        output.stdout = "1\n2".to_string();
+        assert_eq!(evaluate_test(&output, &job.test_cases[0], &options).status, TestStatus::Failed);
+    }
+
+    #[test]
+    fn test_encoding_normalization_disabled_by_default() {
+        let options = NormalizationOptions::default();
+        let strict = Pipeline::legacy(ComparisonMode::Strict, &options);
+        assert_eq!(strict.normalize("\u{FEFF}caf\u{FFFD}"), "\u{FEFF}caf\u{FFFD}");
+        assert!(!outputs_match("e\u{0301}", "\u{00E9}", ComparisonMode::Ordered, &options));
+    }
+
+    #[test]
+    fn test_encoding_normalization_nfc() {
+        let options = NormalizationOptions { unicode_nfc: true, ..Default::default() };
+        // Decomposed "é" (e + combining acute) matches precomposed "é"
+        assert!(outputs_match("caf\u{0065}\u{0301}", "caf\u{00E9}", ComparisonMode::Ordered, &options));
+        assert!(!outputs_match("cafe", "caf\u{00E9}", ComparisonMode::Ordered, &options));
+    }
+
+    #[test]
+    fn test_encoding_normalization_strip_bom() {
+        let options = NormalizationOptions { strip_bom: true, ..Default::default() };
+        assert!(outputs_match("\u{FEFF}hello", "hello", ComparisonMode::Ordered, &options));
+        // Only a leading BOM is stripped
+        assert!(!outputs_match("he\u{FEFF}llo", "hello", ComparisonMode::Ordered, &options));
+    }
+
+    #[test]
+    fn test_encoding_normalization_lenient_utf8() {
+        let lossy = String::from_utf8_lossy(b"ok\xff\n").into_owned();
+        assert!(!outputs_match(&lossy, "ok", ComparisonMode::Ordered, &NormalizationOptions::default()));
+
+        let options = NormalizationOptions { lenient_utf8: true, ..Default::default() };
+        assert!(outputs_match(&lossy, "ok", ComparisonMode::Ordered, &options));
+    }
+
+    #[test]
+    fn test_declared_pipeline_overrides_comparison_mode() {
+        let mut test_case = make_test_case(1, "0.3333 X", 10);
+        test_case.comparison = Some(ComparisonMode::Strict);
+        let output = make_output(1, "0.33331  x\n", 10);
+        assert_eq!(evaluate_test(&output, &test_case, &EvaluationOptions::default()).status, TestStatus::Failed);
+
+        let options = EvaluationOptions {
+            pipeline: Some(
+                Pipeline::builder()
+                    .normalize(Normalizer::Lowercase)
+                    .compare(Comparator::Numeric { abs_tolerance: 1e-3, rel_tolerance: 0.0 })
+                    .build(),
+            ),
+            ..Default::default()
+        };
+        assert_eq!(evaluate_test(&output, &test_case, &options).status, TestStatus::Passed);
+    }
+
+    #[test]
+    fn test_binary_mode_byte_exact_comparison() {
+        let raw: Vec<u8> = vec![0x00, 0xff, 0x10, b'\n'];
+        let test_case = make_test_case(1, &general_purpose::STANDARD.encode(&raw), 10);
+        let mut output = make_output(1, &String::from_utf8_lossy(&raw), 5);
+        output.stdout_bytes = Some(raw.clone());
+        let binary = EvaluationOptions { output_mode: OutputMode::Binary, ..Default::default() };
+
+        let result = evaluate_test(&output, &test_case, &binary);
+        assert_eq!(result.status, TestStatus::Passed);
+        assert_eq!(result.stdout_base64.as_deref(), Some(general_purpose::STANDARD.encode(&raw).as_str()));
+        assert_eq!(result.stderr_base64.as_deref(), Some(""));
+
+        // Trailing newline matters in binary mode
+        output.stdout_bytes = Some(raw[..3].to_vec());
+        let result = evaluate_test(&output, &test_case, &binary);
+        assert_eq!(result.status, TestStatus::Failed);
+    }
+
+    #[test]
+    fn test_binary_mode_invalid_expected_never_passes() {
+        let test_case = make_test_case(1, "not base64!", 10);
+        let output = make_output(1, "not base64!", 5);
+        let binary = EvaluationOptions { output_mode: OutputMode::Binary, ..Default::default() };
+
+        let result = evaluate_test(&output, &test_case, &binary);
+        assert_eq!(result.status, TestStatus::Failed);
+    }
+
+    #[test]
+    fn test_text_mode_omits_base64_fields() {
+        let test_case = make_test_case(1, "ok", 10);
+        let output = make_output(1, "ok", 5);
+
+        let result = evaluate_test(&output, &test_case, &EvaluationOptions::default());
+        assert!(result.stdout_base64.is_none());
+        assert!(result.stderr_base64.is_none());
+    }
+
+    #[test]
+    fn test_job_normalization_applied_during_aggregation() {
+        let mut job = JobRequest {
+            id: Uuid::new_v4(),
+            language: Language::Python,
+            source_code: String::new(),
+            test_cases: vec![make_test_case(1, "hello", 10)],
+            timeout_ms: 5000,
+            metadata: optimus_common::types::JobMetadata::default(),
+            ..Default::default()
+        };
+        let outputs = vec![make_output(1, "\u{FEFF}hello", 10)];
+
+        assert_eq!(aggregate_results(&outputs, &job, &EvaluationOptions::for_job(&job)).score, 0);
+
+        job.normalization.strip_bom = true;
+        assert_eq!(aggregate_results(&outputs, &job, &EvaluationOptions::for_job(&job)).score, 10);
+    }
 }
@@ -1,76 +1,128 @@
-/// Job Executor - High-Level Orchestration
-///
-/// **Responsibility:**
-/// Coordinate execution engine and evaluator to produce final results.
-///
-/// **Architecture:**
-/// 1. Use DockerEngine to run code in sandboxed containers (engine.rs)
-/// 2. Use Evaluator to score outputs (evaluator.rs)
-/// 3. Return aggregated ExecutionResult
-///
-/// This module is the glue layer - it knows nothing about:
-/// - How code executes (engine's job)
-/// - How scoring works (evaluator's job)
+//! Job Executor - High-Level Orchestration
+//!
+//! **Responsibility:**
+//! Coordinate execution engine and evaluator to produce final results.
+//!
+//! **Architecture:**
+//! 1. Use DockerEngine to run code in sandboxed containers (engine.rs)
+//! 2. Use Evaluator to score outputs (evaluator.rs)
+//! 3. Return aggregated ExecutionResult
+//!
+//! This module is the glue layer - it knows nothing about:
+//! - How code executes (engine's job)
+//! - How scoring works (evaluator's job)
 
-use crate::engine::{execute_job_async, DockerEngine};
-use crate::evaluator;
+use crate::engine::{self, DockerEngine};
+use crate::evaluator::{self, EvaluationOptions, TestExecutionOutput};
+use crate::progress::ProgressReporter;
+use crate::chaos;
+use crate::checker;
+use crate::interactor;
 use crate::config::LanguageConfigManager;
-use optimus_common::types::{ExecutionResult, JobRequest};
+use crate::monitor;
+use crate::protocol;
+use optimus_common::language_config::effective_timeout_ms;
+use optimus_common::types::{ExecutionResult, JobRequest, JobStatus, JobTimings};
 use anyhow::Result;
+use std::borrow::Cow;
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+/// Allowance on top of compilation and the tests for container setup and cleanup
+const JOB_BUDGET_OVERHEAD: Duration = Duration::from_secs(30);
+
+/// Upper bound on any job's time budget (`JOB_BUDGET_MAX_SECS`, default 600)
+///
+/// Keep it below JOB_MAX_WALL_SECS: a job over budget still gets a result (the
+/// remaining tests marked as timed out), while the wall-time limit fails the attempt.
+pub fn max_job_budget() -> Duration {
+    let secs = std::env::var("JOB_BUDGET_MAX_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(600);
+    Duration::from_secs(secs)
+}
+
+/// Total time a job may spend executing
+///
+/// Compilation + every test at its wall-clock ceiling + the checker's or interactor's share + overhead, capped
+/// at `max_job_budget()`: 100 tests at 60s each would otherwise hold a worker for 100 minutes.
+pub fn job_budget(job: &JobRequest, max: Duration) -> Duration {
+    let per_test = Duration::from_millis(protocol::wall_limit_ms(job.timeout_ms)).saturating_add(protocol::overhead_grace());
+    engine::COMPILE_BUDGET
+        .saturating_add(per_test.saturating_mul(job.test_cases.len() as u32))
+        .saturating_add(checker::allowance(job))
+        .saturating_add(interactor::allowance(job))
+        .saturating_add(JOB_BUDGET_OVERHEAD)
+        .min(max)
+}
+
+/// The job as it runs: its time limit scaled by the language's `time_multiplier`
+///
+/// Everything downstream (per-test timeouts, the job budget, container keep-alive, the
+/// monitor's near-limit check) reads `timeout_ms`, so scaling it here covers them all.
+fn with_time_multiplier<'a>(job: &'a JobRequest, multiplier: f64) -> Cow<'a, JobRequest> {
+    let timeout_ms = effective_timeout_ms(job.timeout_ms, multiplier);
+    if timeout_ms == job.timeout_ms {
+        return Cow::Borrowed(job);
+    }
+    let mut scaled = job.clone();
+    scaled.timeout_ms = timeout_ms;
+    Cow::Owned(scaled)
+}
 
 /// Execute a job using Docker engine + evaluator
 ///
 /// This is the production execution path:
-/// - DockerEngine runs code in sandboxed containers with language-specific configs
+/// - DockerEngine compiles once in a sandboxed container and runs every test there
 /// - Evaluator scores outputs
 /// - Results are aggregated
-/// - Cooperative cancellation is checked between test cases
+/// - Cancellation (the job's token, see cancellation.rs) stops compilation or the running test at once
+/// - A job past its time budget (`job_budget`) is stopped and its remaining tests time out
+/// - The result's `timings` hold compile, execution and evaluation time (the worker adds
+///   queue wait and persistence)
+/// - Each test's verdict is published to optimus:progress:{job_id} as it finishes
 /// 
-/// ## Feature Flag: USE_COMPILE_ONCE
-/// Set environment variable `USE_COMPILE_ONCE=true` to enable the new compile-once execution model
+/// ## Language Flags
+/// The language's `flags` in languages.json turn on container pooling and fill in the
+/// job's unset comparison mode and stderr policy.
+/// Its `time_multiplier` scales the job's time limit; the result reports the scaled
+/// limit as `effective_timeout_ms`.
 pub async fn execute_docker(
     job: &JobRequest,
+    engine: &DockerEngine,
     config_manager: &LanguageConfigManager,
     redis_conn: &mut redis::aio::ConnectionManager,
+    cancel: &CancellationToken,
 ) -> Result<ExecutionResult> {
     println!("→ Starting job execution: {}", job.id);
-    
-    // Check feature flag for compile-once execution
-    let use_compile_once = std::env::var("USE_COMPILE_ONCE")
-        .unwrap_or_else(|_| "false".to_string())
-        .to_lowercase() == "true";
-    
-    let execution_mode = if use_compile_once { "compile_once" } else { "legacy" };
-    
-    if use_compile_once {
-        println!("  Using: Compile-Once Execution (NEW)");
-    } else {
-        println!("  Using: Per-Test Compilation (LEGACY)");
-    }
-    println!();
-    
+
+    let scaled = with_time_multiplier(job, config_manager.get_time_multiplier(&job.language));
+    let job = scaled.as_ref();
+    let options = EvaluationOptions::for_language(job, &config_manager.get_flags(&job.language));
+
     tracing::info!(
         job_id = %job.id,
         language = %job.language,
         test_count = job.test_cases.len(),
-        execution_mode = execution_mode,
         "Starting job execution"
     );
 
-    // Step 1: Create Docker engine with config manager
-    let engine = DockerEngine::new_with_config(config_manager)?;
+    // Step 1: Docker engine is built once per worker and passed in (see engine::SharedEngine)
+    chaos::docker_fault("engine_init")?;
 
-    // Step 2: Execute with Docker engine (with cancellation support)
-    let outputs = if use_compile_once {
-        // NEW PATH: Compile once, run all tests
-        engine.execute_job_in_single_container(job, redis_conn).await
-    } else {
-        // LEGACY PATH: Compile per test (current behavior)
-        execute_job_async(job, &engine, redis_conn).await
-    };
+    // Step 2: Execute with Docker engine (with cancellation support and per-test progress)
+    let progress = ProgressReporter::new(job).with_options(options.clone());
+    progress.start(redis_conn).await;
+    let mut run = run_outputs(job, engine, Some(redis_conn), cancel, &progress).await;
+    let resource_series: Vec<_> = run
+        .outputs
+        .iter_mut()
+        .filter_map(|o| Some((o.test_id, o.resource_samples.take()?)))
+        .collect();
 
     // Cross-layer guard: Log failed executions before evaluation
-    for output in &outputs {
+    for output in &run.outputs {
         if output.compilation_failed {
             tracing::warn!(
                 test_id = output.test_id,
@@ -94,7 +146,219 @@ pub async fn execute_docker(
     }
 
     // Step 3: Evaluate outputs
-    let result = evaluator::evaluate(job, outputs);
+    let mut result = evaluate(job, run, &options);
+    result.judge_env_version = config_manager.get_env_version(&job.language);
+    result.effective_timeout_ms = Some(job.timeout_ms);
+
+    // Step 4: Keep the resource series for explaining borderline verdicts
+    monitor::store(redis_conn, config_manager, job, &result, resource_series).await;
+
+    Ok(result)
+}
+
+/// Execute a job on a specific image (canary shadow runs, verification re-runs)
+///
+/// Same execution and evaluator as the primary run, but no progress or
+/// checkpoints are published for it.
+pub async fn execute_on_image(
+    job: &JobRequest,
+    engine: &DockerEngine,
+    config_manager: &LanguageConfigManager,
+    redis_conn: &mut redis::aio::ConnectionManager,
+    image: &str,
+    cancel: &CancellationToken,
+) -> Result<ExecutionResult> {
+    let scaled = with_time_multiplier(job, config_manager.get_time_multiplier(&job.language));
+    let job = scaled.as_ref();
+    let options = EvaluationOptions::for_language(job, &config_manager.get_flags(&job.language));
+
+    let engine = engine.clone().with_image(image);
+    let progress = ProgressReporter::disabled();
+    let run = run_outputs(job, &engine, Some(redis_conn), cancel, &progress).await;
+
+    let mut result = evaluate(job, run, &options);
+    result.effective_timeout_ms = Some(job.timeout_ms);
+    Ok(result)
+}
+
+/// Execute a job without Redis (standalone mode, see standalone.rs)
+///
+/// Same execution and evaluator as `execute_docker`; there is nothing to
+/// cancel, report progress to or store debug artifacts in.
+pub async fn execute_standalone(
+    job: &JobRequest,
+    engine: &DockerEngine,
+    config_manager: &LanguageConfigManager,
+) -> Result<ExecutionResult> {
+    let scaled = with_time_multiplier(job, config_manager.get_time_multiplier(&job.language));
+    let job = scaled.as_ref();
+    let options = EvaluationOptions::for_language(job, &config_manager.get_flags(&job.language));
+
+    let progress = ProgressReporter::disabled();
+    let run = run_outputs(job, engine, None, &CancellationToken::new(), &progress).await;
 
+    let mut result = evaluate(job, run, &options);
+    result.judge_env_version = config_manager.get_env_version(&job.language);
+    result.effective_timeout_ms = Some(job.timeout_ms);
     Ok(result)
 }
+
+/// Raw outputs of one run of a job, with how long it took
+struct Run {
+    outputs: Vec<TestExecutionOutput>,
+    /// The time budget ran out before every test ran
+    over_budget: bool,
+    compile_time: Option<Duration>,
+    execution_time: Duration,
+}
+
+/// Run the job's tests within its time budget
+///
+/// Running out of budget stops the engine like a cancellation does (through a child
+/// of the job's token), so the tests that finished keep their outputs and the
+/// containers are cleaned up as usual.
+async fn run_outputs(
+    job: &JobRequest,
+    engine: &DockerEngine,
+    redis_conn: Option<&mut redis::aio::ConnectionManager>,
+    cancel: &CancellationToken,
+    progress: &ProgressReporter<'_>,
+) -> Run {
+    let started = Instant::now();
+    let budget = job_budget(job, max_job_budget());
+    let stop = cancel.child_token();
+    let expire = stop.clone();
+    let timer = tokio::spawn(async move {
+        tokio::time::sleep(budget).await;
+        expire.cancel();
+    });
+
+    let mut outputs = engine.execute_job_in_single_container(job, redis_conn, &stop, progress).await;
+    timer.abort();
+
+    let over_budget = stop.is_cancelled() && !cancel.is_cancelled();
+    if over_budget {
+        tracing::warn!(
+            job_id = %job.id,
+            budget_secs = budget.as_secs(),
+            completed_tests = outputs.len(),
+            "Job exceeded its time budget; remaining tests timed out"
+        );
+        fill_over_budget(job, &mut outputs);
+    }
+    Run {
+        outputs,
+        over_budget,
+        compile_time: progress.compile_time(),
+        execution_time: started.elapsed(),
+    }
+}
+
+/// Mark the tests that never ran because the job ran out of time as timed out
+fn fill_over_budget(job: &JobRequest, outputs: &mut Vec<TestExecutionOutput>) {
+    for test_case in &job.test_cases {
+        if outputs.iter().any(|o| o.test_id == test_case.id) {
+            continue;
+        }
+        outputs.push(TestExecutionOutput {
+            test_id: test_case.id,
+            stdout: String::new(),
+            stderr: "[job time budget exceeded]".to_string(),
+            execution_time_ms: 0,
+            timed_out: true,
+            runtime_error: false,
+            compilation_failed: false,
+            stdout_bytes: None,
+            stderr_bytes: None,
+            stdout_total_bytes: None,
+            stderr_total_bytes: None,
+            overhead_ms: None,
+            cpu_time_ms: None,
+            memory_kb: None,
+            resource_samples: None,
+            oom_killed: false,
+            failure: None,
+            checker: None,
+        });
+    }
+}
+
+fn evaluate(job: &JobRequest, run: Run, options: &EvaluationOptions) -> ExecutionResult {
+    let started = Instant::now();
+    let mut result = evaluator::evaluate(job, run.outputs, options);
+    if run.over_budget {
+        result.overall_status = JobStatus::TimedOut;
+    }
+    result.timings = Some(JobTimings {
+        compile_ms: run.compile_time.map(|t| t.as_millis() as u64),
+        total_execution_ms: run.execution_time.as_millis() as u64,
+        evaluation_ms: started.elapsed().as_millis() as u64,
+        ..Default::default()
+    });
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(tests: usize, timeout_ms: u64) -> JobRequest {
+        let test_cases: Vec<_> = (1..=tests)
+            .map(|id| serde_json::json!({"id": id, "input": "", "expected_output": "1", "weight": 1}))
+            .collect();
+        serde_json::from_value(serde_json::json!({
+            "id": uuid::Uuid::new_v4(),
+            "language": "python",
+            "source_code": "print(1)",
+            "test_cases": test_cases,
+            "timeout_ms": timeout_ms
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_job_budget_scales_with_tests_and_is_capped() {
+        let small = job(2, 1000);
+        let budget = job_budget(&small, Duration::from_secs(600));
+        assert!(budget > engine::COMPILE_BUDGET + Duration::from_secs(2));
+        assert!(budget < Duration::from_secs(600));
+
+        // 100 tests at 60s each would be 100 minutes
+        let large = job(100, 60_000);
+        assert_eq!(job_budget(&large, Duration::from_secs(600)), Duration::from_secs(600));
+    }
+
+    #[test]
+    fn test_time_multiplier_scales_the_limit() {
+        let original = job(2, 1000);
+        assert!(matches!(with_time_multiplier(&original, 1.0), Cow::Borrowed(_)));
+
+        let scaled = with_time_multiplier(&original, 3.0);
+        assert_eq!(scaled.timeout_ms, 3000);
+        assert!(job_budget(&scaled, Duration::from_secs(600)) > job_budget(&original, Duration::from_secs(600)));
+    }
+
+    #[test]
+    fn test_over_budget_tests_time_out() {
+        let job = job(3, 1000);
+        let mut outputs = Vec::new();
+        fill_over_budget(&job, &mut outputs);
+        assert_eq!(outputs.len(), 3);
+        assert!(outputs.iter().all(|o| o.timed_out));
+
+        let run = Run {
+            outputs,
+            over_budget: true,
+            compile_time: Some(Duration::from_millis(1500)),
+            execution_time: Duration::from_secs(600),
+        };
+        let result = evaluate(&job, run, &EvaluationOptions::for_language(&job, &Default::default()));
+        assert_eq!(result.overall_status, JobStatus::TimedOut);
+        assert_eq!(result.score, 0);
+
+        let timings = result.timings.unwrap();
+        assert_eq!(timings.compile_ms, Some(1500));
+        assert_eq!(timings.total_execution_ms, 600_000);
+        assert_eq!(timings.queue_wait_ms, None);
+    }
+}
@@ -0,0 +1,263 @@
+//! Interactors - Interactive Problem Support
+//!
+//! **Core Responsibility:**
+//! Connect a submission to the job's interactor for each test and relay what they say to
+//! each other.
+//!
+//! In an interactive problem the program never sees the whole input: it asks questions and
+//! gets answers (guess the number, query a hidden graph). A job may carry `interactor`
+//! source code in the job's language. Like a checker, it is compiled once in a container of
+//! its own, so the submission can't read the hidden input; per test it runs as
+//!
+//! ```text
+//! <interactor> /tmp/input /tmp/expected
+//! ```
+//!
+//! while the program runs in the job's container with no input file. The worker relays:
+//!
+//! - program stdout → interactor stdin (and the test's stdout, as usual)
+//! - interactor stdout → program stdin
+//! - interactor stderr → the judge's message, returned as `checker_output`
+//!
+//! When either side's output ends, the other side's input is closed, so a program waiting
+//! for an answer that will never come sees end of file rather than hanging. The interactor's
+//! exit code is the verdict, as a checker's: 0 accepted, 1 wrong, anything else a judge
+//! error. The program's CPU time is held to the test's time limit as usual, and the whole
+//! conversation, waiting on the interactor included, to the wall-clock ceiling.
+
+use crate::capture::CappedBuffer;
+use crate::checker::{self, CheckerOutcome};
+use crate::progress::LiveOutput;
+use bollard::container::LogOutput;
+use futures_util::{Stream, StreamExt};
+use optimus_common::types::{JobRequest, OutputStream};
+use std::time::Duration;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// Interactor stderr kept per test (the outcome truncates it further)
+const MAX_JUDGE_BYTES: usize = 16 * 1024;
+
+/// Extra time a job needs for its interactor: compiling it (each run shares the test's limit)
+pub fn allowance(job: &JobRequest) -> Duration {
+    if job.interactor.is_none() {
+        return Duration::ZERO;
+    }
+    crate::engine::COMPILE_BUDGET
+}
+
+/// Shell command running the compiled interactor on the test's files
+pub fn command(program: &str) -> String {
+    format!("cd /code && {} {} {}", program, checker::INPUT_PATH, checker::EXPECTED_PATH)
+}
+
+/// One side of the conversation: an attached exec's output and input
+pub struct Side<O, I> {
+    pub output: O,
+    pub input: I,
+}
+
+/// What the relay captured
+#[derive(Debug)]
+pub struct Transcript {
+    pub stdout: CappedBuffer,
+    pub stderr: CappedBuffer,
+    /// What the interactor printed to stderr
+    pub judge: CappedBuffer,
+    /// A stream that failed mid-test (a sandbox failure, not the program's)
+    pub error: Option<String>,
+}
+
+/// Relay between the program and the interactor until both have stopped printing
+///
+/// The two directions run on their own, so a side that stops reading its input only
+/// stalls what is sent to it, never what it sends.
+pub async fn relay<PO, PI, JO, JI, E>(
+    program: Side<PO, PI>,
+    interactor: Side<JO, JI>,
+    live: &mut LiveOutput,
+) -> Transcript
+where
+    PO: Stream<Item = Result<LogOutput, E>> + Unpin,
+    JO: Stream<Item = Result<LogOutput, E>> + Unpin,
+    PI: AsyncWrite + Unpin,
+    JI: AsyncWrite + Unpin,
+    E: std::fmt::Display,
+{
+    let Side { output: mut program_out, input: program_in } = program;
+    let Side { output: mut judge_out, input: judge_in } = interactor;
+
+    let program_to_judge = async {
+        let (mut stdout, mut stderr) = CappedBuffer::for_test();
+        let mut judge_in = Some(judge_in);
+        let mut error = None;
+        while let Some(message) = program_out.next().await {
+            match message {
+                Ok(LogOutput::StdOut { message }) => {
+                    live.push(OutputStream::Stdout, &message);
+                    stdout.push(&message);
+                    forward(&mut judge_in, &message).await;
+                }
+                Ok(LogOutput::StdErr { message }) => {
+                    live.push(OutputStream::Stderr, &message);
+                    stderr.push(&message);
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    error = Some(format!("Program stream failed: {}", e));
+                    break;
+                }
+            }
+        }
+        close(&mut judge_in).await;
+        (stdout, stderr, error)
+    };
+
+    let judge_to_program = async {
+        let mut judge = CappedBuffer::head(MAX_JUDGE_BYTES);
+        let mut program_in = Some(program_in);
+        let mut error = None;
+        while let Some(message) = judge_out.next().await {
+            match message {
+                Ok(LogOutput::StdOut { message }) => forward(&mut program_in, &message).await,
+                Ok(LogOutput::StdErr { message }) => judge.push(&message),
+                Ok(_) => {}
+                Err(e) => {
+                    error = Some(format!("Interactor stream failed: {}", e));
+                    break;
+                }
+            }
+        }
+        close(&mut program_in).await;
+        (judge, error)
+    };
+
+    let ((stdout, stderr, program_error), (judge, judge_error)) = tokio::join!(program_to_judge, judge_to_program);
+    Transcript { stdout, stderr, judge, error: program_error.or(judge_error) }
+}
+
+/// The interactor's verdict on a test, from its exit code and what it printed to stderr
+pub fn outcome(exit_code: Option<i64>, judge: CappedBuffer) -> CheckerOutcome {
+    let (printed, _) = judge.finish();
+    CheckerOutcome::finished(exit_code, &String::from_utf8_lossy(&printed))
+}
+
+/// Pass bytes on; a side that stopped reading has its input dropped, and the rest discarded
+async fn forward<W: AsyncWrite + Unpin>(input: &mut Option<W>, bytes: &[u8]) {
+    if let Some(writer) = input {
+        if writer.write_all(bytes).await.and(writer.flush().await).is_err() {
+            *input = None;
+        }
+    }
+}
+
+/// Close a side's input, so it reads end of file
+async fn close<W: AsyncWrite + Unpin>(input: &mut Option<W>) {
+    if let Some(mut writer) = input.take() {
+        let _ = writer.shutdown().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::stream;
+    use tokio::io::{AsyncBufReadExt, BufReader};
+    use tokio_util::io::ReaderStream;
+
+    fn out(text: &str) -> Result<LogOutput, std::io::Error> {
+        Ok(LogOutput::StdOut { message: text.as_bytes().to_vec().into() })
+    }
+
+    fn err(text: &str) -> Result<LogOutput, std::io::Error> {
+        Ok(LogOutput::StdErr { message: text.as_bytes().to_vec().into() })
+    }
+
+    #[test]
+    fn test_command_and_allowance() {
+        assert_eq!(command("/code/main"), "cd /code && /code/main /tmp/input /tmp/expected");
+        let mut job: JobRequest = serde_json::from_value(serde_json::json!({
+            "id": "00000000-0000-0000-0000-000000000000",
+            "language": "python",
+            "source_code": "print(1)",
+            "test_cases": [],
+            "timeout_ms": 1000
+        }))
+        .unwrap();
+        assert_eq!(allowance(&job), Duration::ZERO);
+        job.interactor = Some("print(1)".to_string());
+        assert_eq!(allowance(&job), crate::engine::COMPILE_BUDGET);
+    }
+
+    #[tokio::test]
+    async fn test_relay_connects_both_sides() {
+        // The program's answer reaches the interactor, whose reply reaches the program
+        let (program_stdin, program_reads) = tokio::io::duplex(1024);
+        let (judge_stdin, judge_reads) = tokio::io::duplex(1024);
+        let program = stream::iter(vec![out("guess 5\n"), err("thinking\n")]);
+        let judge_out = ReaderStream::new(judge_reads).map(|chunk| chunk.map(|b| LogOutput::StdOut { message: b }));
+        let judge = judge_out.chain(stream::iter(vec![err("found in 1 guess\n")]));
+
+        let transcript = relay(
+            Side { output: program, input: program_stdin },
+            Side { output: judge, input: judge_stdin },
+            &mut LiveOutput::off(),
+        )
+        .await;
+
+        // The interactor "echoed" the guess back, and both inputs were closed at the end
+        let mut replies = Vec::new();
+        let mut lines = BufReader::new(program_reads).lines();
+        while let Some(line) = lines.next_line().await.unwrap() {
+            replies.push(line);
+        }
+        assert_eq!(replies, ["guess 5"]);
+        assert_eq!(transcript.stdout.finish().0, b"guess 5\n");
+        assert_eq!(transcript.stderr.finish().0, b"thinking\n");
+        assert!(transcript.error.is_none());
+        let outcome = outcome(Some(0), transcript.judge);
+        assert_eq!(outcome.message, "found in 1 guess");
+        assert_eq!(outcome.verdict(), checker::CheckerVerdict::Accepted);
+    }
+
+    #[tokio::test]
+    async fn test_relay_ends_when_the_interactor_gives_up() {
+        // The interactor stops at once; the program's stdin is closed rather than left open
+        let (program_stdin, mut program_reads) = tokio::io::duplex(64);
+        let judge = stream::iter(vec![err("wrong answer\n")]);
+        let program = stream::iter(vec![out("1\n")]);
+        let transcript = relay(
+            Side { output: program, input: program_stdin },
+            Side { output: judge, input: tokio::io::sink() },
+            &mut LiveOutput::off(),
+        )
+        .await;
+        let mut rest = Vec::new();
+        tokio::io::AsyncReadExt::read_to_end(&mut program_reads, &mut rest).await.unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(outcome(Some(1), transcript.judge).verdict(), checker::CheckerVerdict::Rejected);
+    }
+
+    #[tokio::test]
+    async fn test_program_that_stops_reading_still_reaches_the_interactor() {
+        // The program never reads its stdin, so the interactor's reply can't be delivered;
+        // what the program prints must still get through
+        let (program_stdin, _unread) = tokio::io::duplex(8);
+        let (judge_stdin, judge_reads) = tokio::io::duplex(1024);
+        let judge = stream::iter(vec![out("a reply longer than the pipe holds\n")]).chain(stream::pending());
+        let program = stream::iter(vec![out("42\n")]).chain(stream::pending());
+        let relaying = tokio::spawn(async move {
+            relay(
+                Side { output: program, input: program_stdin },
+                Side { output: judge, input: judge_stdin },
+                &mut LiveOutput::off(),
+            )
+            .await
+        });
+
+        let mut line = String::new();
+        let mut judge_reads = BufReader::new(judge_reads);
+        tokio::time::timeout(Duration::from_secs(5), judge_reads.read_line(&mut line)).await.unwrap().unwrap();
+        assert_eq!(line, "42\n");
+        relaying.abort();
+    }
+}
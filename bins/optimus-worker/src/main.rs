@@ -1,18 +1,49 @@
 mod engine;
 mod evaluator;
+mod misuse;
+mod checker;
+mod interactor;
+mod sanitizer;
+mod notebook;
 mod executor;
 mod config;
+mod chaos;
+mod capture;
+mod progress;
+mod runtime_info;
+mod selftest;
+mod canary;
+mod cancellation;
+mod verification;
+mod determinism;
+mod protocol;
+mod monitor;
+mod pool;
+mod pipeline;
+mod streaming;
+mod diff;
+mod standalone;
+mod store;
+mod supervisor;
 
 #[cfg(test)]
 mod engine_tests;
+#[cfg(all(test, feature = "chaos"))]
+mod chaos_tests;
 
 use optimus_common::redis;
-use optimus_common::types::Language;
+use optimus_common::validate;
+use optimus_common::types::{EventKind, Language, SystemEvent};
 use optimus_common::config::WorkerConfig;
-use tokio::signal;
-use tokio::sync::{Semaphore, RwLock};
+use optimus_common::retry::Backoff;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::task::JoinSet;
+use std::collections::HashMap;
 use std::sync::Arc;
 use config::LanguageConfigManager;
+use supervisor::{TaskInfo, TaskPhase, Tasks};
+use cancellation::Cancellations;
+use tokio_util::sync::CancellationToken;
 use tracing::{info, error, warn, debug, instrument};
 use bollard::{Docker, image::CreateImageOptions};
 use futures_util::stream::StreamExt;
@@ -24,35 +55,54 @@ use axum::{
     http::StatusCode,
 };
 use tokio::net::TcpListener;
+use clap::Parser;
 use serde::Serialize;
 
 /// Shared worker state for health checks
 #[derive(Clone)]
 struct WorkerState {
     redis_url: String,
-    is_executing: Arc<RwLock<bool>>,
+    tasks: Tasks,
+    /// Cancelled once the worker stops taking jobs (SIGTERM or the preStop hook)
+    draining: CancellationToken,
 }
 
 #[derive(Serialize)]
 struct HealthResponse {
     status: String,
     executing_job: bool,
+    /// Job tasks running on this worker and their phase
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tasks: Vec<TaskInfo>,
 }
 
 /// Liveness probe - simple process alive check
 async fn health_handler(State(state): State<WorkerState>) -> impl IntoResponse {
-    let executing = *state.is_executing.read().await;
+    let tasks = state.tasks.snapshot();
     (
         StatusCode::OK,
         Json(HealthResponse {
             status: "healthy".to_string(),
-            executing_job: executing,
+            executing_job: !tasks.is_empty(),
+            tasks,
         })
     )
 }
 
 /// Readiness probe - checks Redis connectivity and execution state
 async fn ready_handler(State(state): State<WorkerState>) -> impl IntoResponse {
+    if state.draining.is_cancelled() {
+        let tasks = state.tasks.snapshot();
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(HealthResponse {
+                status: "draining".to_string(),
+                executing_job: !tasks.is_empty(),
+                tasks,
+            }),
+        );
+    }
+
     // Check Redis connectivity
     let redis_ok = match ::redis::Client::open(state.redis_url.as_str()) {
         Ok(client) => {
@@ -69,7 +119,8 @@ async fn ready_handler(State(state): State<WorkerState>) -> impl IntoResponse {
         Err(_) => false,
     };
 
-    let executing = *state.is_executing.read().await;
+    let tasks = state.tasks.snapshot();
+    let executing = !tasks.is_empty();
     
     // Worker is ready if Redis is reachable AND not currently executing
     // This allows KEDA to scale down idle workers safely
@@ -86,15 +137,28 @@ async fn ready_handler(State(state): State<WorkerState>) -> impl IntoResponse {
         Json(HealthResponse {
             status: if is_ready { "ready".to_string() } else { "not_ready".to_string() },
             executing_job: executing,
+            tasks,
         })
     )
 }
 
+/// Kubernetes preStop hook - stop taking jobs before SIGTERM arrives
+///
+/// Running jobs keep going; the worker finishes them once SIGTERM follows.
+async fn drain_handler(State(state): State<WorkerState>) -> impl IntoResponse {
+    if !state.draining.is_cancelled() {
+        warn!("⚠️  preStop hook called - no longer taking jobs");
+        state.draining.cancel();
+    }
+    StatusCode::OK
+}
+
 /// Start health check HTTP server
 async fn start_health_server(state: WorkerState) -> anyhow::Result<()> {
     let app = Router::new()
         .route("/health", get(health_handler))
         .route("/ready", get(ready_handler))
+        .route("/drain", get(drain_handler).post(drain_handler))
         .with_state(state);
 
     let port = std::env::var("HEALTH_PORT")
@@ -108,6 +172,38 @@ async fn start_health_server(state: WorkerState) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Heartbeat publish interval; the key expires after three missed beats
+const HEARTBEAT_INTERVAL_SECS: u64 = 10;
+
+/// Publish a heartbeat so the API can report worker health, and start draining when an
+/// admin asks for it (POST /admin/workers/{id}/drain, `optimus-cli worker drain`)
+async fn heartbeat_loop(
+    mut redis_conn: ::redis::aio::ConnectionManager,
+    mut heartbeat: optimus_common::types::WorkerHeartbeat,
+    tasks: Tasks,
+    draining: CancellationToken,
+) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(HEARTBEAT_INTERVAL_SECS));
+    loop {
+        interval.tick().await;
+        if !draining.is_cancelled() && matches!(redis::is_drain_requested(&mut redis_conn, &heartbeat.worker_id).await, Ok(true)) {
+            warn!("⚠️  Drain requested - no longer taking jobs, exiting once running jobs finish");
+            draining.cancel();
+            publish_event(
+                &mut redis_conn,
+                SystemEvent::worker(EventKind::WorkerDraining, &heartbeat.worker_id, heartbeat.language),
+            )
+            .await;
+        }
+        heartbeat.last_seen = chrono::Utc::now();
+        heartbeat.executing = tasks.is_executing();
+        heartbeat.draining = draining.is_cancelled();
+        if let Err(e) = redis::publish_heartbeat(&mut redis_conn, &heartbeat, HEARTBEAT_INTERVAL_SECS * 3).await {
+            warn!("Failed to publish heartbeat: {}", e);
+        }
+    }
+}
+
 /// Pre-pull a Docker image (best-effort)
 /// Returns Ok(true) if image was pulled, Ok(false) if already present
 async fn prepull_image(image: &str) -> anyhow::Result<bool> {
@@ -132,11 +228,28 @@ async fn prepull_image(image: &str) -> anyhow::Result<bool> {
     Ok(true) // Successfully pulled
 }
 
+#[derive(Parser)]
+#[command(name = "optimus-worker", about = "Optimus judge worker")]
+struct Args {
+    /// Config profile: overlays config/languages.{env}.json on config/languages.json
+    #[arg(long, env = optimus_common::language_config::PROFILE_ENV_VAR, value_parser = optimus_common::language_config::parse_profile)]
+    env: Option<String>,
+
+    /// Language this worker judges
+    #[arg(long, env = "OPTIMUS_LANGUAGE")]
+    language: Option<String>,
+
+    /// Serve the API and judge jobs in this process, without Redis
+    #[arg(long)]
+    standalone: bool,
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     // Initialize tracing subscriber
     // Load environment variables from .env file
     dotenvy::dotenv().ok();
+    let args = Args::parse();
     
     tracing_subscriber::fmt()
         .with_env_filter(
@@ -149,6 +262,46 @@ async fn main() -> anyhow::Result<()> {
         .init();
 
     info!("Optimus Worker booting...");
+    chaos::announce();
+
+    // Resolve secrets kept in Vault / AWS Secrets Manager before any config reads them
+    if let Err(e) = optimus_common::secrets::init().await {
+        error!("❌ FATAL: Failed to resolve secrets: {}", e);
+        std::process::exit(1);
+    }
+
+    // Result signing (optional) - results are signed inside redis::store_result
+    match optimus_common::signing::init_from_env() {
+        Ok(Some(signer)) => info!("Result signing enabled (active key: {})", signer.active_key_id()),
+        Ok(None) => info!("Result signing disabled (RESULT_SIGNING_KEYS not set)"),
+        Err(e) => {
+            error!("❌ FATAL: Invalid result signing configuration: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    // Encryption at rest (optional) - queue entries and results are sealed inside optimus_common::redis
+    match optimus_common::crypto::init_from_env() {
+        Ok(Some(cipher)) => info!("Encryption at rest enabled (active key: {})", cipher.active_key_id()),
+        Ok(None) => info!("Encryption at rest disabled (OPTIMUS_ENCRYPTION_KEYS not set)"),
+        Err(e) => {
+            error!("❌ FATAL: Invalid encryption configuration: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    // Safety limits (file and env overrides) - reloaded on SIGHUP and periodically
+    match optimus_common::limits::init() {
+        Ok(limits) => info!(
+            "Safety limits: engine_max_source_code_bytes={}, engine_max_test_input_bytes={}, engine_max_output_bytes={}",
+            limits.engine_max_source_code_bytes, limits.engine_max_test_input_bytes, limits.engine_max_output_bytes
+        ),
+        Err(e) => {
+            error!("❌ FATAL: Invalid safety limits: {}", e);
+            std::process::exit(1);
+        }
+    }
+    tokio::spawn(optimus_common::limits::watch());
 
     // Load worker concurrency configuration
     let worker_config = WorkerConfig::from_env();
@@ -159,13 +312,16 @@ async fn main() -> anyhow::Result<()> {
     );
 
     // Load language configurations
-    let config_manager = LanguageConfigManager::load_default()
+    let config_manager = LanguageConfigManager::load_default(args.env.as_deref())
         .map_err(|e| {
             error!("Failed to load language configurations: {}", e);
             error!("Make sure config/languages.json exists");
             e
         })?;
     
+    if let Some(profile) = &args.env {
+        info!("Config profile: {}", profile);
+    }
     info!("Loaded language configurations for: {:?}", config_manager.list_languages());
 
     // Pre-pull all language images (best-effort, async, non-blocking)
@@ -187,15 +343,23 @@ async fn main() -> anyhow::Result<()> {
         info!("✓ Image pre-pull complete");
     });
 
+    // One Docker engine for the whole worker; jobs get it after a health check
+    let engine = engine::SharedEngine::new(&config_manager, worker_config.gpu)?;
+
+    // Standalone mode: API + worker for every language in this process, no Redis
+    if standalone::enabled(args.standalone) {
+        return standalone::run(config_manager, engine, worker_config).await;
+    }
+
     // ===== LANGUAGE BINDING ENFORCEMENT =====
     // Worker MUST be bound to exactly one language via environment variables
     // This is non-negotiable for proper scaling and isolation
     
     // 1. Validate OPTIMUS_LANGUAGE is set (REQUIRED)
-    let language_str = std::env::var("OPTIMUS_LANGUAGE")
-        .unwrap_or_else(|_| {
+    let language_str = args.language.clone()
+        .unwrap_or_else(|| {
             error!("❌ FATAL: OPTIMUS_LANGUAGE environment variable not set");
-            error!("Worker must be bound to a specific language (python, java, rust)");
+            error!("Worker must be bound to a specific language (python, java, rust, kotlin, scala, c, php, ruby, asm)");
             error!("This worker cannot start without language specification");
             std::process::exit(1);
         });
@@ -220,8 +384,26 @@ async fn main() -> anyhow::Result<()> {
         std::process::exit(1);
     }
 
-    // 3. Validate OPTIMUS_QUEUE matches language (REQUIRED)
+    // 3. Validate OPTIMUS_QUEUE matches language (REQUIRED); workers with capability labels
+    // also read the queues of jobs requiring them, and may name one of those instead
+    let lang_config = config_manager.get_config(&language)?;
+    if worker_config.gpu && lang_config.gpu.is_none() {
+        error!("❌ FATAL: WORKER_GPU=true but {} has no gpu block in languages.json", language);
+        std::process::exit(1);
+    }
+    // Labels no job of the language may require have no queues to read
+    let requirable = lang_config.requirable_labels();
+    let capabilities: Vec<String> = worker_config
+        .capabilities
+        .iter()
+        .filter(|label| requirable.contains(label))
+        .cloned()
+        .collect();
     let expected_queue = config_manager.get_queue_name(&language)?;
+    let readable_queues: Vec<String> = redis::worker_queues(&language, &capabilities)
+        .into_iter()
+        .map(|(queue, _)| queue)
+        .collect();
     let queue_name = std::env::var("OPTIMUS_QUEUE")
         .unwrap_or_else(|_| {
             error!("❌ FATAL: OPTIMUS_QUEUE environment variable not set");
@@ -230,7 +412,7 @@ async fn main() -> anyhow::Result<()> {
             std::process::exit(1);
         });
     
-    if queue_name != expected_queue {
+    if queue_name != expected_queue && !readable_queues.contains(&queue_name) {
         error!("❌ FATAL: Queue mismatch detected");
         error!("  Configured language: {}", language);
         error!("  Expected queue: {}", expected_queue);
@@ -265,25 +447,40 @@ async fn main() -> anyhow::Result<()> {
     info!("Queue: {}", queue_name);
 
     // Connect to Redis
-    let redis_url = std::env::var("REDIS_URL")
-        .unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+    let redis_url = optimus_common::secrets::var("REDIS_URL")
+        .map_err(|e| anyhow::anyhow!(e))?
+        .unwrap_or_else(|| "redis://127.0.0.1:6379".to_string());
     
     let client = ::redis::Client::open(redis_url.as_str())?;
-    let mut redis_conn = ::redis::aio::ConnectionManager::new(client).await?;
+    let mut redis_conn = optimus_common::retry::connect(&client).await?;
+    // Blocking dequeues (BLMOVE) get a connection of their own
+    let dequeue_conn = optimus_common::retry::connect(&client).await?;
     
-    info!("Connected to Redis: {}", redis_url);
-    info!("Worker is READY - waiting for jobs from queue: {}", queue_name);
+    info!("Connected to Redis: {}", optimus_common::secrets::redact_url(&redis_url));
+
+    // Jobs under a legacy queue name (jobs:{language}) would wait there forever
+    match redis::legacy_queue_depths(&mut redis_conn, &[language]).await {
+        Ok(stuck) => {
+            for (queue, depth) in stuck {
+                warn!(queue = %queue, depth, "Jobs stuck under a legacy queue name - run `optimus-cli migrate-queues`");
+            }
+        }
+        Err(e) => warn!(error = %e, "Failed to check legacy queues"),
+    }
+    info!("Worker is READY - waiting for jobs from queues: {}", readable_queues.join(", "));
 
     // Create semaphore for concurrency control
     // This guarantees at most max_parallel_jobs jobs execute simultaneously
     let semaphore = Arc::new(Semaphore::new(worker_config.max_parallel_jobs));
     info!("Concurrency semaphore initialized with {} permits", worker_config.max_parallel_jobs);
 
-    // Create shared state for health checks
-    let is_executing = Arc::new(RwLock::new(false));
+    // Job tasks, shared with the health endpoint and heartbeats
+    let tasks = Tasks::new();
+    let draining = CancellationToken::new();
     let health_state = WorkerState {
         redis_url: redis_url.clone(),
-        is_executing: is_executing.clone(),
+        tasks: tasks.clone(),
+        draining: draining.clone(),
     };
 
     // Start health check server in background
@@ -293,60 +490,207 @@ async fn main() -> anyhow::Result<()> {
         }
     });
 
-    // Setup graceful shutdown - handles both SIGTERM (Kubernetes) and SIGINT (CTRL+C)
-    let shutdown = async {
-        #[cfg(unix)]
-        {
-            use tokio::signal::unix::{signal, SignalKind};
-            let mut sigterm = signal(SignalKind::terminate())
-                .expect("failed to install SIGTERM signal handler");
-            let mut sigint = signal(SignalKind::interrupt())
-                .expect("failed to install SIGINT signal handler");
-            
-            tokio::select! {
-                _ = sigterm.recv() => {
-                    warn!("⚠️  Received SIGTERM - initiating graceful shutdown");
-                }
-                _ = sigint.recv() => {
-                    warn!("⚠️  Received SIGINT (CTRL+C) - initiating graceful shutdown");
-                }
-            }
-        }
-        #[cfg(not(unix))]
-        {
-            signal::ctrl_c().await.expect("failed to install CTRL+C signal handler");
-            warn!("⚠️  Received CTRL+C - initiating graceful shutdown");
+    // Publish heartbeats for the admin dashboard (pod name when running in Kubernetes)
+    let worker_id = std::env::var("HOSTNAME")
+        .unwrap_or_else(|_| format!("worker-{}", &uuid::Uuid::new_v4().to_string()[..8]));
+    let now = chrono::Utc::now();
+    tokio::spawn(heartbeat_loop(
+        redis_conn.clone(),
+        optimus_common::types::WorkerHeartbeat {
+            worker_id: worker_id.clone(),
+            language,
+            started_at: now,
+            last_seen: now,
+            executing: false,
+            max_parallel_jobs: worker_config.max_parallel_jobs,
+            draining: false,
+            capabilities: worker_config.capabilities.clone(),
+            queues: readable_queues.clone(),
+        },
+        tasks.clone(),
+        draining.clone(),
+    ));
+    info!("Publishing heartbeats as worker '{}'", worker_id);
+    publish_event(
+        &mut redis_conn,
+        SystemEvent::worker(EventKind::WorkerUp, &worker_id, language)
+            .with_details(serde_json::json!({ "image": image })),
+    )
+    .await;
+
+    // Report the judge image's toolchain versions for GET /languages/{name}/runtime-info
+    tokio::spawn(runtime_info::publish(
+        redis_conn.clone(),
+        engine.clone(),
+        language,
+        worker_id.clone(),
+    ));
+
+    // Check the sandbox still runs code and has no network (alerts via the API notifier)
+    tokio::spawn(selftest::self_test_loop(
+        redis_conn.clone(),
+        engine.clone(),
+        language,
+        worker_id.clone(),
+    ));
+
+    // Warm shutdown: on SIGTERM (Kubernetes) or SIGINT (CTRL+C), stop taking jobs and let
+    // the running ones finish within SHUTDOWN_GRACE_SECS
+    tokio::spawn({
+        let draining = draining.clone();
+        async move {
+            shutdown_signal().await;
+            warn!("Worker will finish its running jobs and exit cleanly");
+            draining.cancel();
         }
-        warn!("Worker will finish current job and exit cleanly");
+    });
+
+    // Cancel running jobs as soon as the API asks (pub/sub, with a polling fallback)
+    let cancellations = Cancellations::new();
+    tokio::spawn(cancellation::watch_loop(client.clone(), redis_conn.clone(), cancellations.clone()));
+
+    let context = JobContext {
+        redis_conn: redis_conn.clone(),
+        config_manager: config_manager.clone(),
+        engine,
+        semaphore,
+        tasks,
+        cancellations,
+        worker_id: worker_id.clone(),
+        lease_secs: worker_config.inflight_lease_secs,
+        capabilities,
+        max_wall: std::time::Duration::from_secs(worker_config.job_max_wall_secs),
+        shutdown_grace: std::time::Duration::from_secs(worker_config.shutdown_grace_secs),
+        draining,
     };
 
-    tokio::select! {
-        _ = worker_loop(&mut redis_conn, &language, &config_manager, semaphore, is_executing) => {},
-        _ = shutdown => {},
+    worker_loop(&mut redis_conn, dequeue_conn, &language, context).await;
+    pool::drain().await;
+    if let Err(e) = redis::mark_worker_stopped(&mut redis_conn, &worker_id).await {
+        warn!(error = %e, "Failed to record graceful shutdown");
     }
+    // The drain (if any) is done; a restart under the same id must take jobs again
+    if let Err(e) = redis::cancel_worker_drain(&mut redis_conn, &worker_id).await {
+        warn!(error = %e, "Failed to clear the drain request");
+    }
+    publish_event(&mut redis_conn, SystemEvent::worker(EventKind::WorkerDown, &worker_id, language)).await;
 
     info!("✓ Worker shutdown complete - all jobs processed");
     Ok(())
 }
 
-#[instrument(skip(redis_conn, config_manager, semaphore, is_executing), fields(language = %language))]
+/// Shared by every job task of the worker
+#[derive(Clone)]
+struct JobContext {
+    redis_conn: ::redis::aio::ConnectionManager,
+    config_manager: LanguageConfigManager,
+    engine: engine::SharedEngine,
+    semaphore: Arc<Semaphore>,
+    tasks: Tasks,
+    cancellations: Cancellations,
+    worker_id: String,
+    lease_secs: u64,
+    /// Capability labels whose jobs this worker takes (see capabilities.rs)
+    capabilities: Vec<String>,
+    max_wall: std::time::Duration,
+    shutdown_grace: std::time::Duration,
+    draining: CancellationToken,
+}
+
+/// Wait for SIGTERM (Kubernetes) or SIGINT (CTRL+C)
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut sigterm = signal(SignalKind::terminate())
+            .expect("failed to install SIGTERM signal handler");
+        let mut sigint = signal(SignalKind::interrupt())
+            .expect("failed to install SIGINT signal handler");
+
+        tokio::select! {
+            _ = sigterm.recv() => {
+                warn!("⚠️  Received SIGTERM - initiating graceful shutdown");
+            }
+            _ = sigint.recv() => {
+                warn!("⚠️  Received SIGINT (CTRL+C) - initiating graceful shutdown");
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        tokio::signal::ctrl_c().await.expect("failed to install CTRL+C signal handler");
+        warn!("⚠️  Received CTRL+C - initiating graceful shutdown");
+    }
+}
+
+/// Supervisor: dequeues jobs and runs each in its own task, at most max_parallel_jobs at once
+///
+/// A panic while handling a job fails that job through the retry path instead of
+/// tearing down the worker. Once draining, the loop stops dequeuing and returns when
+/// the running jobs are done (see finish_running_jobs).
+#[instrument(skip(redis_conn, dequeue_conn, context), fields(language = %language))]
 async fn worker_loop(
     redis_conn: &mut ::redis::aio::ConnectionManager,
+    mut dequeue_conn: ::redis::aio::ConnectionManager,
     language: &Language,
-    config_manager: &LanguageConfigManager,
-    semaphore: Arc<Semaphore>,
-    is_executing: Arc<RwLock<bool>>,
-) -> anyhow::Result<()> {
-    loop {
+    context: JobContext,
+) {
+    let (semaphore, worker_id, lease_secs) = (context.semaphore.clone(), context.worker_id.clone(), context.lease_secs);
+    let draining = context.draining.clone();
+    let mut job_tasks = JoinSet::new();
+    let mut running: HashMap<tokio::task::Id, optimus_common::types::JobRequest> = HashMap::new();
+
+    // Jobs left in flight by a worker that died before this one started
+    reclaim_abandoned_jobs(redis_conn, language).await;
+
+    let mut redis_backoff = Backoff::new();
+    while !draining.is_cancelled() {
+        // Collect finished job tasks; a panicked one is failed like any infrastructure error
+        while let Some(joined) = job_tasks.try_join_next_with_id() {
+            reap_job_task(redis_conn, &mut running, joined).await;
+        }
+
+        // Paused queues are left untouched until resumed (POST /admin/queues/{language}/resume)
+        if matches!(redis::is_queue_paused(redis_conn, language).await, Ok(true)) {
+            debug!("Queue paused - not dequeuing");
+            tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+            continue;
+        }
+
+        // CRITICAL: Acquire a semaphore permit before taking a job off the queue
+        // This enforces max_parallel_jobs and leaves jobs for other workers while we are full
+        debug!("Acquiring concurrency permit");
+        let permit = tokio::select! {
+            permit = semaphore.clone().acquire_owned() => permit.expect("Semaphore should never be closed"),
+            _ = draining.cancelled() => break,
+        };
+
         // Log idle state (waiting for jobs)
         debug!("Worker IDLE - waiting for job from queue");
-        
-        // BLPOP with 5 second timeout for graceful shutdown
-        // Consumes from both main queue and retry queue (main has priority)
-        match redis::pop_job_with_retry(redis_conn, language, 5.0).await {
+
+        // Waits up to 5 seconds so shutdown is noticed; consumes from both main queue and
+        // retry queue (main has priority) and records the job as in flight
+        chaos::redis_delay("pop_job").await;
+        let popped = redis::pop_job_with_retry(
+            redis_conn,
+            &mut dequeue_conn,
+            language,
+            &context.capabilities,
+            &worker_id,
+            5.0,
+            lease_secs,
+        )
+        .await;
+        if popped.is_ok() {
+            let failures = redis_backoff.success();
+            if failures > 0 {
+                info!(failures, "Redis connection restored");
+            }
+        }
+        match popped {
             Ok(Some(mut job)) => {
                 let job_id = job.id;
-                
+
                 // ===== CRITICAL: Language Mismatch Check =====
                 // Workers MUST only process jobs for their configured language
                 // This prevents cross-language execution bugs
@@ -363,244 +707,574 @@ async fn worker_loop(
                         "Worker bound to '{}' received '{}' job - this should never happen",
                         language, job.language
                     );
-                    
+
                     // This is a routing bug - send directly to DLQ
                     job.metadata.last_failure_reason = Some(format!(
                         "Language routing error: worker bound to '{}' cannot execute '{}' job",
                         language, job.language
                     ));
-                    
+
                     if let Err(dlq_err) = redis::push_to_dlq(redis_conn, &job).await {
                         error!(
                             job_id = %job_id,
                             error = %dlq_err,
                             "Failed to push misrouted job to DLQ"
                         );
+                        // Keep the in-flight record: the lease expires and the job is reclaimed
                     } else {
+                        // Claimed under this worker's queue, not the job's language
+                        let _ = redis::release_inflight(redis_conn, language, &job_id).await;
                         warn!(job_id = %job_id, "Misrouted job sent to DLQ");
+                        let event = SystemEvent::job(EventKind::JobDlq, &job)
+                            .with_details(serde_json::json!({ "reason": job.metadata.last_failure_reason }));
+                        publish_event(redis_conn, event).await;
                     }
-                    
+
                     continue;
                 }
                 // ===== End Language Validation =====
-                
-                // CRITICAL: Acquire semaphore permit before starting execution
-                // This enforces max_parallel_jobs limit
-                debug!(job_id = %job_id, "Acquiring concurrency permit");
-                let permit = semaphore.clone().acquire_owned().await
-                    .expect("Semaphore should never be closed");
-                
-                info!(
-                    job_id = %job_id,
-                    language = %job.language,
-                    timeout_ms = job.timeout_ms,
-                    test_cases = job.test_cases.len(),
-                    source_size = job.source_code.len(),
-                    phase = "dequeued",
-                    available_permits = semaphore.available_permits(),
-                    "Worker BUSY - processing job"
-                );
-                
-                // Display language-specific configuration
-                if let Ok(config) = config_manager.get_config(&job.language) {
-                    debug!(
-                        job_id = %job_id,
-                        image = %config.image,
-                        memory_mb = config.memory_limit_mb,
-                        cpu_limit = config.cpu_limit,
-                        "Job configuration"
-                    );
-                }
-                
-                // Check for cancellation before starting execution
-                match redis::is_job_cancelled(redis_conn, &job_id).await {
-                    Ok(true) => {
-                        warn!(
-                            job_id = %job_id,
-                            phase = "cancelled_before_execution",
-                            "Job was cancelled before execution started"
-                        );
-                        
-                        // Store cancelled result
-                        let cancelled_result = optimus_common::types::ExecutionResult {
-                            job_id: job.id,
-                            overall_status: optimus_common::types::JobStatus::Cancelled,
-                            score: 0,
-                            max_score: job.test_cases.iter().map(|tc| tc.weight).sum(),
-                            results: vec![],
-                        };
-                        
-                        if let Err(store_err) = redis::store_result_with_metrics(redis_conn, &cancelled_result, &job.language).await {
-                            error!(
-                                job_id = %job_id,
-                                error = %store_err,
-                                "Failed to store cancelled result"
-                            );
-                        } else {
-                            info!(job_id = %job_id, "Cancelled result stored");
-                        }
-                        
-                        // MARK: Worker as idle (job was cancelled)
-                        *is_executing.write().await = false;
-                        drop(permit);
-                        
-                        continue;
-                    }
-                    Ok(false) => {
-                        // Not cancelled, proceed with execution
-                    }
-                    Err(e) => {
-                        error!(
-                            job_id = %job_id,
-                            error = %e,
-                            "Failed to check cancellation status, proceeding with execution"
-                        );
-                    }
-                }
-                
-                // MARK: Worker as executing (for readiness probe)
-                *is_executing.write().await = true;
-                
-                // Execute job with Docker executor
-                info!(
-                    job_id = %job_id, 
-                    phase = "executing",
-                    attempt = job.metadata.attempts + 1,
-                    max_attempts = job.metadata.max_attempts,
-                    "Starting execution"
-                );
-                let start = std::time::Instant::now();
-                let result = match executor::execute_docker(&job, config_manager, redis_conn).await {
-                    Ok(result) => result,
-                    Err(e) => {
-                        error!(
-                            job_id = %job_id, 
-                            phase = "execution_failed", 
-                            error = %e,
-                            attempts = job.metadata.attempts,
-                            "Docker execution failed"
-                        );
-                        
-                        // Increment attempts
-                        job.metadata.attempts += 1;
-                        job.metadata.last_failure_reason = Some(format!("Execution error: {}", e));
-                        
-                        // Retry logic
-                        if job.metadata.attempts < job.metadata.max_attempts {
-                            warn!(
-                                job_id = %job_id,
-                                attempt = job.metadata.attempts,
-                                max_attempts = job.metadata.max_attempts,
-                                "Job failed, sending to retry queue"
-                            );
-                            
-                            if let Err(retry_err) = redis::push_to_retry_queue(redis_conn, &job).await {
-                                error!(
-                                    job_id = %job_id,
-                                    error = %retry_err,
-                                    "Failed to push job to retry queue"
-                                );
-                            } else {
-                                info!(job_id = %job_id, "Job pushed to retry queue");
-                            }
-                        } else {
-                            error!(
-                                job_id = %job_id,
-                                attempts = job.metadata.attempts,
-                                "Job exceeded max attempts, sending to DLQ"
-                            );
-                            
-                            if let Err(dlq_err) = redis::push_to_dlq(redis_conn, &job).await {
-                                error!(
-                                    job_id = %job_id,
-                                    error = %dlq_err,
-                                    "Failed to push job to DLQ"
-                                );
-                            } else {
-                                info!(job_id = %job_id, "Job pushed to DLQ");
-                            }
-                            
-                            // Store final failed result
-                            let failed_result = optimus_common::types::ExecutionResult {
-                                job_id: job.id,
-                                overall_status: optimus_common::types::JobStatus::Failed,
-                                score: 0,
-                                max_score: job.test_cases.iter().map(|tc| tc.weight).sum(),
-                                results: vec![],
-                            };
-                            
-                            if let Err(store_err) = redis::store_result_with_metrics(redis_conn, &failed_result, &job.language).await {
-                                error!(
-                                    job_id = %job_id,
-                                    error = %store_err,
-                                    "Failed to store failed result"
-                                );
-                            }
-                        }
-                        
-                        // MARK: Worker as idle (execution failed)
-                        *is_executing.write().await = false;
-                        drop(permit);
-                        
-                        continue;
-                    }
-                };
-                let execution_time = start.elapsed();
-                
-                info!(
-                    job_id = %job_id,
-                    phase = "evaluated",
-                    status = ?result.overall_status,
-                    score = result.score,
-                    max_score = result.max_score,
-                    execution_ms = execution_time.as_millis(),
-                    "Execution completed"
-                );
-                
-                for (idx, test_result) in result.results.iter().enumerate() {
-                    debug!(
-                        job_id = %job_id,
-                        test_num = idx + 1,
-                        test_id = test_result.test_id,
-                        status = ?test_result.status,
-                        execution_ms = test_result.execution_time_ms,
-                        "Test result"
-                    );
+
+                // Jobs from producers other than the API (CLI replay, admin requeue) skip its
+                // validation: refuse them here rather than somewhere inside the engine
+                if let Err(violations) = validate::validate_job(&job, &optimus_common::limits::current()) {
+                    reject_invalid_job(redis_conn, &mut job, &violations).await;
+                    continue;
                 }
-                
-                // Persist result to Redis with metrics
-                info!(job_id = %job_id, phase = "persisting", "Storing result to Redis");
-                match redis::store_result_with_metrics(redis_conn, &result, &job.language).await {
-                    Ok(_) => {
-                        info!(job_id = %job_id, phase = "completed", "Result persisted to Redis");
-                    }
-                    Err(e) => {
-                        error!(job_id = %job_id, phase = "persist_failed", error = %e, "Failed to persist result");
-                        // Non-fatal - worker continues
+
+                // Verification re-runs should land on a different worker than the original
+                if should_hand_off(redis_conn, &job, &worker_id).await {
+                    debug!(job_id = %job_id, "Handing verification re-run to another worker");
+                    match redis::push_job(redis_conn, &job).await {
+                        Ok(()) => release_job(redis_conn, &job).await,
+                        Err(e) => error!(job_id = %job_id, error = %e, "Failed to requeue verification re-run"),
                     }
+                    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+                    continue;
                 }
-                
-                info!(
-                    job_id = %job_id, 
-                    phase = "done", 
-                    available_permits = semaphore.available_permits() + 1,
-                    "Worker IDLE - job completed, permit released"
-                );
-                
-                // MARK: Worker as idle (for readiness probe)
-                *is_executing.write().await = false;
-                
-                // Permit is automatically released when dropped here
-                drop(permit);
+
+                let handle = job_tasks.spawn(run_job(context.clone(), job.clone(), permit));
+                running.insert(handle.id(), job);
             }
             Ok(None) => {
-                // Timeout - check for shutdown (idle continues)
+                // Timeout - use the idle moment to pick up jobs from dead workers
+                reclaim_abandoned_jobs(redis_conn, language).await;
                 continue;
             }
+            Err(e) if optimus_common::retry::is_transient(&e) => {
+                // Redis unavailable: back off, logging the outage at growing intervals
+                let (delay, log) = redis_backoff.failure();
+                if log {
+                    warn!(error = %e, retry_in_ms = delay.as_millis() as u64, "Redis unavailable");
+                }
+                tokio::time::sleep(delay).await;
+            }
             Err(e) => {
                 error!(error = %e, "Redis error");
                 tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
             }
         }
     }
+
+    finish_running_jobs(redis_conn, job_tasks, running, context.shutdown_grace).await;
+}
+
+/// Let running jobs finish within the shutdown grace period, then put the rest back
+/// on the queue so they run elsewhere at once instead of after their leases expire
+async fn finish_running_jobs(
+    redis_conn: &mut ::redis::aio::ConnectionManager,
+    mut job_tasks: JoinSet<()>,
+    mut running: HashMap<tokio::task::Id, optimus_common::types::JobRequest>,
+    grace: std::time::Duration,
+) {
+    if !running.is_empty() {
+        info!(running = running.len(), grace_secs = grace.as_secs(), "Waiting for running jobs to finish");
+    }
+    let deadline = tokio::time::Instant::now() + grace;
+    while let Ok(Some(joined)) = tokio::time::timeout_at(deadline, job_tasks.join_next_with_id()).await {
+        reap_job_task(redis_conn, &mut running, joined).await;
+    }
+    if job_tasks.is_empty() {
+        return;
+    }
+
+    job_tasks.abort_all();
+    while let Some(joined) = job_tasks.join_next_with_id().await {
+        let Some(mut job) = reap_job_task(redis_conn, &mut running, joined).await else { continue };
+        warn!(job_id = %job.id, phase = "requeued_on_shutdown", "Job still running at the end of the grace period - requeueing");
+        job.metadata.enqueued_at = Some(chrono::Utc::now());
+        match redis::push_job(redis_conn, &job).await {
+            Ok(()) => {
+                let _ = redis::release_inflight(redis_conn, &job.language, &job.id).await;
+            }
+            // Keep the in-flight record: the lease expires and the job is reclaimed
+            Err(e) => error!(job_id = %job.id, error = %e, "Failed to requeue job on shutdown"),
+        }
+    }
+}
+
+/// Account for a finished job task; a panic fails its job through the retry path
+///
+/// Returns the job of an aborted task (only aborted on shutdown) for the caller to requeue.
+async fn reap_job_task(
+    redis_conn: &mut ::redis::aio::ConnectionManager,
+    running: &mut HashMap<tokio::task::Id, optimus_common::types::JobRequest>,
+    joined: Result<(tokio::task::Id, ()), tokio::task::JoinError>,
+) -> Option<optimus_common::types::JobRequest> {
+    let error = match joined {
+        Ok((id, ())) => {
+            running.remove(&id);
+            return None;
+        }
+        Err(error) => error,
+    };
+    let mut job = running.remove(&error.id())?;
+    if !error.is_panic() {
+        return Some(job);
+    }
+    let message = supervisor::panic_message(error.into_panic().as_ref());
+    error!(job_id = %job.id, panic = %message, phase = "panicked", "Job task panicked");
+    let failure = anyhow::anyhow!("Worker panicked while handling the job: {}", message);
+    handle_execution_failure(redis_conn, &mut job, &failure).await;
+    let _ = redis::release_inflight(redis_conn, &job.language, &job.id).await;
+    None
+}
+
+/// Handle one dequeued job: execute it, store its result and release it
+async fn run_job(
+    context: JobContext,
+    mut job: optimus_common::types::JobRequest,
+    permit: OwnedSemaphorePermit,
+) {
+    let JobContext { mut redis_conn, config_manager, engine, semaphore, tasks, cancellations, worker_id, lease_secs, max_wall, .. } = context;
+    let redis_conn = &mut redis_conn;
+    let config_manager = &config_manager;
+    let job_id = job.id;
+    let queue_wait_ms = job.metadata.queue_wait_ms(chrono::Utc::now());
+
+    // Listed on the health endpoint until this task ends (panics included)
+    let task = tasks.start(&job);
+
+    // The dequeue recorded the job as in flight; keep its lease alive while it runs
+    let _lease = LeaseRenewal::spawn(redis_conn.clone(), job.language, job_id, lease_secs);
+
+    info!(
+        job_id = %job_id,
+        language = %job.language,
+        timeout_ms = job.timeout_ms,
+        test_cases = job.test_cases.len(),
+        source_size = job.source_code.len(),
+        phase = "dequeued",
+        available_permits = semaphore.available_permits(),
+        "Worker BUSY - processing job"
+    );
+
+    // Display language-specific configuration
+    if let Ok(config) = config_manager.get_config(&job.language) {
+        debug!(
+            job_id = %job_id,
+            image = %config.image,
+            memory_mb = config.memory_limit_mb,
+            cpu_limit = config.cpu_limit,
+            "Job configuration"
+        );
+    }
+
+    // Cancelled before execution started (the token is already cancelled), or from now on
+    let registration = cancellations.register(redis_conn, job_id).await;
+    let cancel = registration.token();
+    if cancel.is_cancelled() {
+        warn!(
+            job_id = %job_id,
+            phase = "cancelled_before_execution",
+            "Job was cancelled before execution started"
+        );
+
+        // Store cancelled result
+        let cancelled_result = optimus_common::types::ExecutionResult {
+            job_id: job.id,
+            overall_status: optimus_common::types::JobStatus::Cancelled,
+            score: 0,
+            max_score: job.max_score(),
+            results: vec![],
+            judge_env_version: None,
+            worker_id: None,
+            signature: None,
+            points: None,
+            timings: None,
+            misuse: Vec::new(),
+            effective_timeout_ms: None,
+            subtasks: Vec::new(),
+        };
+
+        if let Err(store_err) = redis::store_result_with_metrics(redis_conn, &cancelled_result, &job.language).await {
+            error!(
+                job_id = %job_id,
+                error = %store_err,
+                "Failed to store cancelled result"
+            );
+        } else {
+            info!(job_id = %job_id, "Cancelled result stored");
+        }
+        release_job(redis_conn, &job).await;
+        return;
+    }
+
+    // MARK: Worker as executing (for readiness probe)
+    task.set_phase(TaskPhase::Executing);
+
+    // Execute job with Docker executor
+    info!(
+        job_id = %job_id,
+        phase = "executing",
+        attempt = job.metadata.attempts + 1,
+        max_attempts = job.metadata.max_attempts,
+        "Starting execution"
+    );
+    let started = SystemEvent {
+        worker_id: Some(worker_id.clone()),
+        ..SystemEvent::job(EventKind::JobStarted, &job)
+    };
+    let started = started.with_details(serde_json::json!({ "attempt": job.metadata.attempts + 1 }));
+    publish_event(redis_conn, started).await;
+    let start = std::time::Instant::now();
+    let verify_image = job.metadata.verification.as_ref().and_then(|v| v.image.clone());
+    let execution = async {
+        let engine = engine.healthy().await?;
+        match &verify_image {
+            Some(image) => executor::execute_on_image(&job, &engine, config_manager, redis_conn, image, cancel).await,
+            None => executor::execute_docker(&job, &engine, config_manager, redis_conn, cancel).await,
+        }
+    };
+    // Orchestration-level limit, on top of the engine's per-test timeouts
+    let outcome = match tokio::time::timeout(max_wall, execution).await {
+        Ok(outcome) => outcome,
+        Err(_) => {
+            error!(job_id = %job_id, max_wall_secs = max_wall.as_secs(), phase = "wall_time_exceeded", "Job exceeded max wall time");
+            Err(anyhow::anyhow!("Job exceeded the worker's max wall time ({}s)", max_wall.as_secs()))
+        }
+    };
+    let mut result = match outcome {
+        Ok(result) => result,
+        Err(e) => {
+            handle_execution_failure(redis_conn, &mut job, &e).await;
+            let _ = redis::release_inflight(redis_conn, &job.language, &job_id).await;
+            return;
+        }
+    };
+    let execution_time = start.elapsed();
+    result.worker_id = Some(worker_id.clone());
+    if let Some(timings) = result.timings.as_mut() {
+        timings.queue_wait_ms = queue_wait_ms;
+    }
+    if cancel.is_cancelled() {
+        // Keeps the tests that finished before the cancellation
+        warn!(job_id = %job_id, phase = "cancelled", completed_tests = result.results.len(), "Job cancelled during execution");
+        result.overall_status = optimus_common::types::JobStatus::Cancelled;
+    }
+
+    info!(
+        job_id = %job_id,
+        phase = "evaluated",
+        status = ?result.overall_status,
+        score = result.score,
+        max_score = result.max_score,
+        execution_ms = execution_time.as_millis(),
+        "Execution completed"
+    );
+
+    for (idx, test_result) in result.results.iter().enumerate() {
+        debug!(
+            job_id = %job_id,
+            test_num = idx + 1,
+            test_id = test_result.test_id,
+            status = ?test_result.status,
+            execution_ms = test_result.execution_time_ms,
+            "Test result"
+        );
+    }
+
+    // Persist result to Redis with metrics
+    task.set_phase(TaskPhase::Persisting);
+    info!(job_id = %job_id, phase = "persisting", "Storing result to Redis");
+    chaos::redis_delay("store_result").await;
+    let persist_started = std::time::Instant::now();
+    match redis::store_result_with_metrics(redis_conn, &result, &job.language).await {
+        Ok(_) => {
+            // Logged, not stored: storing the write's own duration would take a second write
+            let persist_ms = persist_started.elapsed().as_millis() as u64;
+            info!(job_id = %job_id, phase = "completed", persist_ms, "Result persisted to Redis");
+            // Charged here, once per stored result (a no-op for jobs without a CPU quota)
+            let day = redis::quota_day(chrono::Utc::now());
+            if let Err(e) = redis::charge_cpu_seconds(redis_conn, &job_id, redis::charged_cpu_seconds(&result), &day).await {
+                warn!(job_id = %job_id, error = %e, "Failed to charge CPU time to quota");
+            }
+            // Cancelled jobs never ran to a verdict, so they aren't ranked or counted
+            if let Some(tag) = job.leaderboard.as_ref().filter(|_| result.overall_status != optimus_common::types::JobStatus::Cancelled) {
+                let fingerprint = job.test_set_fingerprint();
+                match redis::record_leaderboard(redis_conn, tag, &fingerprint, &result).await {
+                    Ok(Some(_)) => {}
+                    Ok(None) => {
+                        info!(job_id = %job_id, problem_id = %tag.problem_id, fingerprint = %fingerprint, "Test set not registered for the problem; result not ranked");
+                    }
+                    Err(e) => {
+                        warn!(job_id = %job_id, problem_id = %tag.problem_id, error = %e, "Failed to update leaderboard");
+                    }
+                }
+                if let Err(e) = redis::record_problem_stats(redis_conn, tag, &fingerprint, &result, chrono::Utc::now()).await {
+                    warn!(job_id = %job_id, problem_id = %tag.problem_id, error = %e, "Failed to update problem timeline");
+                }
+            }
+        }
+        Err(e) => {
+            error!(job_id = %job_id, phase = "persist_failed", error = %e, "Failed to persist result");
+            // Non-fatal - worker continues
+        }
+    }
+    release_job(redis_conn, &job).await;
+
+    if let Some(target) = &job.metadata.verification {
+        let image = verify_image
+            .unwrap_or_else(|| config_manager.get_image(&job.language).unwrap_or_default());
+        verification::record(redis_conn, &job, target, &result, &image).await;
+    } else if !cancel.is_cancelled() {
+        // Canary: re-run a sample of jobs on the candidate image (after the user's result is stored)
+        canary::maybe_shadow(&job, &result, &engine.get(), config_manager, redis_conn).await;
+    }
+
+    info!(
+        job_id = %job_id,
+        phase = "done",
+        available_permits = semaphore.available_permits() + 1,
+        "Worker IDLE - job completed, permit released"
+    );
+
+    // Permit is automatically released when dropped here
+    drop(permit);
+}
+
+/// Whether this worker should leave a verification re-run to another live worker
+/// (it produced the original result and is not the only worker for the language)
+async fn should_hand_off(
+    redis_conn: &mut ::redis::aio::ConnectionManager,
+    job: &optimus_common::types::JobRequest,
+    worker_id: &str,
+) -> bool {
+    let Some(avoid) = job.metadata.verification.as_ref().and_then(|v| v.avoid_worker.as_deref()) else {
+        return false;
+    };
+    if avoid != worker_id {
+        return false;
+    }
+    match redis::list_heartbeats(redis_conn).await {
+        Ok(workers) => workers
+            .iter()
+            .any(|w| w.language == job.language && w.worker_id != worker_id),
+        Err(_) => false,
+    }
+}
+
+/// Keeps a job's in-flight lease alive while it executes; stops when dropped
+struct LeaseRenewal(tokio::task::JoinHandle<()>);
+
+impl LeaseRenewal {
+    fn spawn(
+        mut redis_conn: ::redis::aio::ConnectionManager,
+        language: Language,
+        job_id: uuid::Uuid,
+        lease_secs: u64,
+    ) -> Self {
+        Self(tokio::spawn(async move {
+            let every = std::time::Duration::from_secs((lease_secs / 3).max(1));
+            loop {
+                tokio::time::sleep(every).await;
+                if let Err(e) = redis::renew_inflight(&mut redis_conn, &language, &job_id, lease_secs).await {
+                    warn!(job_id = %job_id, error = %e, "Failed to renew in-flight lease");
+                }
+            }
+        }))
+    }
+}
+
+impl Drop for LeaseRenewal {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Publish an operational event (best-effort, never blocks job processing on failure)
+async fn publish_event(redis_conn: &mut ::redis::aio::ConnectionManager, event: SystemEvent) {
+    if let Err(e) = redis::publish_event(redis_conn, &event).await {
+        warn!(kind = ?event.kind, error = %e, "Failed to publish event");
+    }
+}
+
+/// Forget a finished job's lease and checkpoints
+async fn release_job(
+    redis_conn: &mut ::redis::aio::ConnectionManager,
+    job: &optimus_common::types::JobRequest,
+) {
+    if let Err(e) = redis::release_inflight(redis_conn, &job.language, &job.id).await {
+        warn!(job_id = %job.id, error = %e, "Failed to release in-flight lease");
+    }
+    let _ = redis::delete_checkpoints(redis_conn, &job.id, job.metadata.attempts).await;
+}
+
+/// Send a job that fails validation straight to the DLQ, with a terminal Failed result so
+/// clients stop polling; retrying it could never succeed
+async fn reject_invalid_job(
+    redis_conn: &mut ::redis::aio::ConnectionManager,
+    job: &mut optimus_common::types::JobRequest,
+    violations: &validate::Violations,
+) {
+    let job_id = job.id;
+    let code = violations.first().code;
+    warn!(job_id = %job_id, code = code, reason = %violations, "Invalid job - sending to DLQ");
+    job.metadata.last_failure_reason = Some(format!("Invalid job: {}", violations));
+
+    if let Err(e) = redis::push_to_dlq(redis_conn, job).await {
+        // Keep the in-flight record: the lease expires and the job is reclaimed
+        error!(job_id = %job_id, error = %e, "Failed to push invalid job to DLQ");
+        return;
+    }
+    let event = SystemEvent::job(EventKind::JobDlq, job).with_details(serde_json::json!({
+        "code": code,
+        "reason": job.metadata.last_failure_reason,
+    }));
+    publish_event(redis_conn, event).await;
+    store_failed_result(redis_conn, job).await;
+    release_job(redis_conn, job).await;
+}
+
+/// Store a terminal Failed result for a job that won't run again
+async fn store_failed_result(
+    redis_conn: &mut ::redis::aio::ConnectionManager,
+    job: &optimus_common::types::JobRequest,
+) {
+    let failed_result = optimus_common::types::ExecutionResult {
+        job_id: job.id,
+        overall_status: optimus_common::types::JobStatus::Failed,
+        score: 0,
+        max_score: job.max_score(),
+        results: vec![],
+        judge_env_version: None,
+        worker_id: None,
+        signature: None,
+        points: None,
+        timings: None,
+        misuse: Vec::new(),
+        effective_timeout_ms: None,
+        subtasks: Vec::new(),
+    };
+
+    if let Err(store_err) = redis::store_result_with_metrics(redis_conn, &failed_result, &job.language).await {
+        error!(
+            job_id = %job.id,
+            error = %store_err,
+            "Failed to store failed result"
+        );
+    }
+}
+
+/// Route jobs whose worker died mid-execution through the normal failure path,
+/// so they are retried (resuming from their checkpoint) or dead-lettered
+async fn reclaim_abandoned_jobs(
+    redis_conn: &mut ::redis::aio::ConnectionManager,
+    language: &Language,
+) {
+    match redis::recover_processing_lists(redis_conn, language).await {
+        Ok(0) => {}
+        Ok(recovered) => warn!(recovered, "Returned jobs a lost worker had dequeued but not claimed"),
+        Err(e) => warn!(error = %e, "Failed to check for jobs dequeued by lost workers"),
+    }
+    let reclaimed = match redis::reclaim_expired_inflight(redis_conn, language).await {
+        Ok(reclaimed) => reclaimed,
+        Err(e) => {
+            warn!(error = %e, "Failed to check for abandoned in-flight jobs");
+            return;
+        }
+    };
+    for (job_id, e) in &reclaimed.unreadable {
+        error!(job_id = %job_id, error = %e, "Unreadable in-flight job from a lost worker - moved to the DLQ");
+    }
+    for mut job in reclaimed.jobs {
+        warn!(job_id = %job.id, attempt = job.metadata.attempts + 1, "Reclaiming job from a lost worker");
+        let error = anyhow::anyhow!("Worker lost during execution (lease expired)");
+        handle_execution_failure(redis_conn, &mut job, &error).await;
+    }
+}
+
+/// Handle a job whose execution failed at the infrastructure level
+///
+/// Increments the attempt counter and routes the job:
+/// - attempts < max_attempts → retry queue
+/// - otherwise → DLQ, plus a terminal Failed result so clients stop polling
+async fn handle_execution_failure(
+    redis_conn: &mut ::redis::aio::ConnectionManager,
+    job: &mut optimus_common::types::JobRequest,
+    e: &anyhow::Error,
+) {
+    let job_id = job.id;
+    error!(
+        job_id = %job_id, 
+        phase = "execution_failed", 
+        error = %e,
+        attempts = job.metadata.attempts,
+        "Docker execution failed"
+    );
+    
+    // Increment attempts
+    job.metadata.attempts += 1;
+    job.metadata.last_failure_reason = Some(format!("Execution error: {}", e));
+    job.metadata.enqueued_at = Some(chrono::Utc::now());
+    
+    // Retry logic
+    if job.metadata.attempts < job.metadata.max_attempts {
+        warn!(
+            job_id = %job_id,
+            attempt = job.metadata.attempts,
+            max_attempts = job.metadata.max_attempts,
+            "Job failed, sending to retry queue"
+        );
+        
+        if let Err(retry_err) = redis::push_to_retry_queue(redis_conn, job).await {
+            error!(
+                job_id = %job_id,
+                error = %retry_err,
+                "Failed to push job to retry queue"
+            );
+        } else {
+            info!(job_id = %job_id, "Job pushed to retry queue");
+            let event = SystemEvent::job(EventKind::JobRetried, job).with_details(serde_json::json!({
+                "attempt": job.metadata.attempts,
+                "max_attempts": job.metadata.max_attempts,
+                "reason": job.metadata.last_failure_reason,
+            }));
+            publish_event(redis_conn, event).await;
+        }
+    } else {
+        error!(
+            job_id = %job_id,
+            attempts = job.metadata.attempts,
+            "Job exceeded max attempts, sending to DLQ"
+        );
+        
+        if let Err(dlq_err) = redis::push_to_dlq(redis_conn, job).await {
+            error!(
+                job_id = %job_id,
+                error = %dlq_err,
+                "Failed to push job to DLQ"
+            );
+        } else {
+            info!(job_id = %job_id, "Job pushed to DLQ");
+            let event = SystemEvent::job(EventKind::JobDlq, job).with_details(serde_json::json!({
+                "attempts": job.metadata.attempts,
+                "reason": job.metadata.last_failure_reason,
+            }));
+            publish_event(redis_conn, event).await;
+        }
+        
+        // Store final failed result
+        store_failed_result(redis_conn, job).await;
+        let _ = redis::delete_checkpoints(redis_conn, &job_id, job.metadata.attempts).await;
+    }
 }
@@ -0,0 +1,315 @@
+// Misuse detection - known abuse patterns in a submission, reported next to its verdict
+//
+// The sandbox already blocks the network and confines the filesystem, so misuse rarely
+// changes a verdict; it is still worth telling an instructor that a submission tried to
+// run `curl` or read /etc/passwd. Two sources are scanned:
+//
+// - the source code, for the language's process, network and file APIs
+// - each test's stderr, for the errors the sandbox produces when such attempts fail
+//   (a missing curl, a refused connection, no more threads)
+//
+// Matches are plain substrings, so expect false positives (a comment mentioning
+// `subprocess`); warnings are a signal to look, never a verdict. Nor are they complete: a
+// determined submission can build a name or syscall number at run time. Assembly is
+// matched by the syscall number loaded into the accumulator (`asm_syscalls`), in any
+// spelling of the constant, which only catches numbers written as constants.
+
+use crate::evaluator::TestExecutionOutput;
+use optimus_common::types::{JobRequest, Language, MisuseKind, MisuseWarning};
+
+/// Files a solution has no reason to read
+const SENSITIVE_PATHS: &[&str] = &["/etc/passwd", "/etc/shadow", "/proc/self/environ", "/var/run/docker.sock"];
+
+/// Programs a solution has no reason to start
+const SPAWNED_PROGRAMS: &[&str] = &["curl", "wget", "nc", "bash", "sh"];
+
+/// Source patterns per language (ProcessSpawn, NetworkAccess)
+fn source_patterns(language: Language) -> &'static [(MisuseKind, &'static str)] {
+    use MisuseKind::*;
+    match language {
+        Language::Python => &[
+            (ProcessSpawn, "subprocess"),
+            (ProcessSpawn, "os.system"),
+            (ProcessSpawn, "os.popen"),
+            (ProcessSpawn, "os.exec"),
+            (NetworkAccess, "import socket"),
+            (NetworkAccess, "urllib"),
+            (NetworkAccess, "requests."),
+        ],
+        // Kotlin calls the same JDK classes
+        Language::Java | Language::Kotlin => &[
+            (ProcessSpawn, "Runtime.getRuntime().exec"),
+            (ProcessSpawn, "ProcessBuilder"),
+            (NetworkAccess, "java.net.Socket"),
+            (NetworkAccess, "HttpURLConnection"),
+            (NetworkAccess, "HttpClient"),
+        ],
+        Language::Scala => &[
+            (ProcessSpawn, "Runtime.getRuntime().exec"),
+            (ProcessSpawn, "ProcessBuilder"),
+            (ProcessSpawn, "sys.process"),
+            (NetworkAccess, "java.net.Socket"),
+            (NetworkAccess, "HttpURLConnection"),
+            (NetworkAccess, "HttpClient"),
+        ],
+        Language::C => &[
+            (ProcessSpawn, "system("),
+            (ProcessSpawn, "popen("),
+            (ProcessSpawn, "fork("),
+            (ProcessSpawn, "execv"),
+            (ProcessSpawn, "execl"),
+            (NetworkAccess, "<sys/socket.h>"),
+            (NetworkAccess, "<netdb.h>"),
+        ],
+        Language::Php => &[
+            (ProcessSpawn, "shell_exec"),
+            (ProcessSpawn, "proc_open"),
+            (ProcessSpawn, "passthru"),
+            (ProcessSpawn, "exec("),
+            (ProcessSpawn, "system("),
+            (NetworkAccess, "fsockopen"),
+            (NetworkAccess, "curl_init"),
+            (NetworkAccess, "socket_create"),
+        ],
+        Language::Ruby => &[
+            (ProcessSpawn, "system("),
+            (ProcessSpawn, "spawn("),
+            (ProcessSpawn, "Open3"),
+            (ProcessSpawn, "IO.popen"),
+            (ProcessSpawn, "%x("),
+            (NetworkAccess, "require 'socket'"),
+            (NetworkAccess, "Net::HTTP"),
+        ],
+        // Matched by syscall number instead (see asm_syscalls)
+        Language::Asm => &[],
+        Language::Rust => &[
+            (ProcessSpawn, "std::process::Command"),
+            (ProcessSpawn, "Command::new"),
+            (NetworkAccess, "TcpStream"),
+            (NetworkAccess, "UdpSocket"),
+        ],
+    }
+}
+
+/// x86-64 syscalls a solution has no reason to make: execve, execveat, fork, vfork,
+/// clone, socket, connect
+const ASM_SYSCALLS: &[(MisuseKind, u64)] = &[
+    (MisuseKind::ProcessSpawn, 59),
+    (MisuseKind::ProcessSpawn, 322),
+    (MisuseKind::ProcessSpawn, 57),
+    (MisuseKind::ProcessSpawn, 58),
+    (MisuseKind::ProcessSpawn, 56),
+    (MisuseKind::NetworkAccess, 41),
+    (MisuseKind::NetworkAccess, 42),
+];
+
+/// The same through the 32-bit `int 0x80` interface: execve, fork, vfork, clone, socketcall
+const ASM_SYSCALLS_32: &[(MisuseKind, u64)] = &[
+    (MisuseKind::ProcessSpawn, 11),
+    (MisuseKind::ProcessSpawn, 2),
+    (MisuseKind::ProcessSpawn, 190),
+    (MisuseKind::ProcessSpawn, 120),
+    (MisuseKind::NetworkAccess, 102),
+];
+
+/// A NASM integer constant: decimal, 0x/0h hex, trailing-h hex, 0b/trailing-b binary, 0o/trailing-q octal
+fn asm_number(token: &str) -> Option<u64> {
+    let token = token.replace('_', "");
+    let (digits, radix) = if let Some(hex) = token.strip_prefix("0x").or_else(|| token.strip_prefix("0h")) {
+        (hex, 16)
+    } else if let Some(bin) = token.strip_prefix("0b") {
+        (bin, 2)
+    } else if let Some(oct) = token.strip_prefix("0o").or_else(|| token.strip_prefix("0q")) {
+        (oct, 8)
+    } else if let Some(hex) = token.strip_suffix('h') {
+        (hex, 16)
+    } else if let Some(bin) = token.strip_suffix('b') {
+        (bin, 2)
+    } else if let Some(oct) = token.strip_suffix('q').or_else(|| token.strip_suffix('o')) {
+        (oct, 8)
+    } else {
+        (token.as_str(), 10)
+    };
+    u64::from_str_radix(digits, radix).ok()
+}
+
+/// Syscall numbers loaded into the accumulator (`mov rax, 59`, `mov al, 0x3b`, ...) that
+/// match a blocked syscall, with the source line as evidence
+fn asm_syscalls(source: &str) -> Vec<(MisuseKind, String)> {
+    let lines: Vec<(String, &str)> = source
+        .lines()
+        .map(|line| {
+            let code = line.split(';').next().unwrap_or_default();
+            let compact: String = code.chars().filter(|c| !c.is_whitespace()).collect::<String>().to_ascii_lowercase();
+            (compact, line.trim())
+        })
+        .collect();
+    let legacy = lines.iter().any(|(code, _)| code == "int0x80" || code == "int80h");
+    let mut found = Vec::new();
+    for (code, line) in &lines {
+        let Some(operands) = code.strip_prefix("mov") else { continue };
+        let Some((register, value)) = operands.split_once(',') else { continue };
+        if !matches!(register, "rax" | "eax" | "ax" | "al") {
+            continue;
+        }
+        let Some(number) = asm_number(value) else { continue };
+        let blocked = ASM_SYSCALLS.iter().chain(if legacy { ASM_SYSCALLS_32 } else { &[] });
+        if let Some(&(kind, _)) = blocked.into_iter().find(|&&(_, n)| n == number) {
+            found.push((kind, line.to_string()));
+        }
+    }
+    found
+}
+
+/// stderr lines the sandbox's refusals produce
+fn stderr_patterns() -> Vec<(MisuseKind, String)> {
+    let mut patterns: Vec<(MisuseKind, String)> = SPAWNED_PROGRAMS
+        .iter()
+        .flat_map(|program| {
+            [
+                (MisuseKind::ProcessSpawn, format!("{}: not found", program)),
+                (MisuseKind::ProcessSpawn, format!("{}: command not found", program)),
+            ]
+        })
+        .collect();
+    patterns.extend(
+        [
+            (MisuseKind::NetworkAccess, "Network is unreachable"),
+            (MisuseKind::NetworkAccess, "Temporary failure in name resolution"),
+            (MisuseKind::NetworkAccess, "Could not resolve host"),
+            (MisuseKind::ThreadExhaustion, "can't start new thread"),
+            (MisuseKind::ThreadExhaustion, "unable to create native thread"),
+            (MisuseKind::ThreadExhaustion, "Resource temporarily unavailable"),
+            (MisuseKind::ThreadExhaustion, "failed to spawn thread"),
+        ]
+        .into_iter()
+        .map(|(kind, pattern)| (kind, pattern.to_string())),
+    );
+    patterns
+}
+
+/// Misuse warnings for a job's source and test outputs, each kind and evidence reported once
+pub fn scan(job: &JobRequest, outputs: &[TestExecutionOutput]) -> Vec<MisuseWarning> {
+    let mut warnings: Vec<MisuseWarning> = Vec::new();
+    let mut push = |kind: MisuseKind, test_id: Option<u32>, evidence: &str| {
+        if !warnings.iter().any(|w| w.kind == kind && w.evidence == evidence) {
+            warnings.push(MisuseWarning { kind, test_id, evidence: evidence.to_string() });
+        }
+    };
+
+    for &(kind, pattern) in source_patterns(job.language) {
+        if job.source_code.contains(pattern) {
+            push(kind, None, pattern);
+        }
+    }
+    if job.language == Language::Asm {
+        for (kind, line) in asm_syscalls(&job.source_code) {
+            push(kind, None, &line);
+        }
+    }
+    for path in SENSITIVE_PATHS {
+        if job.source_code.contains(path) {
+            push(MisuseKind::SensitiveFileAccess, None, path);
+        }
+    }
+
+    let patterns = stderr_patterns();
+    for output in outputs {
+        for (kind, pattern) in &patterns {
+            if output.stderr.contains(pattern.as_str()) {
+                push(*kind, Some(output.test_id), pattern);
+            }
+        }
+        for path in SENSITIVE_PATHS {
+            if output.stderr.contains(path) || output.stdout.contains(path) {
+                push(MisuseKind::SensitiveFileAccess, Some(output.test_id), path);
+            }
+        }
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(language: &str, source: &str) -> JobRequest {
+        serde_json::from_value(serde_json::json!({
+            "id": uuid::Uuid::new_v4(),
+            "language": language,
+            "source_code": source,
+            "test_cases": [{"id": 1, "input": "", "expected_output": "1", "weight": 1}],
+            "timeout_ms": 1000
+        }))
+        .unwrap()
+    }
+
+    fn output(test_id: u32, stderr: &str) -> TestExecutionOutput {
+        TestExecutionOutput {
+            test_id,
+            stdout: String::new(),
+            stderr: stderr.to_string(),
+            execution_time_ms: 1,
+            timed_out: false,
+            runtime_error: true,
+            compilation_failed: false,
+            stdout_bytes: None,
+            stderr_bytes: None,
+            stdout_total_bytes: None,
+            stderr_total_bytes: None,
+            overhead_ms: None,
+            cpu_time_ms: None,
+            memory_kb: None,
+            resource_samples: None,
+            oom_killed: false,
+            failure: None,
+            checker: None,
+        }
+    }
+
+    #[test]
+    fn test_scan_source_and_stderr() {
+        let submission = job("python", "import subprocess\nsubprocess.run(['curl', 'x'])\nopen('/etc/passwd').read()");
+        let outputs = [
+            output(1, "/bin/sh: 1: curl: not found"),
+            output(2, "/bin/sh: 1: curl: not found\nRuntimeError: can't start new thread"),
+        ];
+        let warnings = scan(&submission, &outputs);
+        let found: Vec<(MisuseKind, Option<u32>, &str)> =
+            warnings.iter().map(|w| (w.kind, w.test_id, w.evidence.as_str())).collect();
+        assert_eq!(
+            found,
+            vec![
+                (MisuseKind::ProcessSpawn, None, "subprocess"),
+                (MisuseKind::SensitiveFileAccess, None, "/etc/passwd"),
+                (MisuseKind::ProcessSpawn, Some(1), "curl: not found"),
+                (MisuseKind::ThreadExhaustion, Some(2), "can't start new thread"),
+            ]
+        );
+
+        // Patterns are per language, and clean runs report nothing
+        assert!(scan(&job("java", "import subprocess"), &[output(1, "")]).is_empty());
+        assert_eq!(scan(&job("java", "new ProcessBuilder(\"sh\")"), &[]).len(), 1);
+        assert_eq!(scan(&job("scala", "import scala.sys.process._"), &[]).len(), 1);
+        assert_eq!(scan(&job("scala", "import scala.sys.process._"), &[]).len(), 1);
+        assert_eq!(scan(&job("php", "<?php passthru('ls');"), &[]).len(), 1);
+        assert_eq!(scan(&job("ruby", "require 'net/http'\nNet::HTTP.get(uri)"), &[]).len(), 1);
+        assert_eq!(scan(&job("asm", "mov rax, 59\nsyscall"), &[]).len(), 1);
+    }
+
+    #[test]
+    fn test_asm_syscalls_in_any_spelling() {
+        let kinds = |source: &str| asm_syscalls(source).into_iter().map(|(kind, _)| kind).collect::<Vec<_>>();
+        for source in ["mov rax,59", "MOV EAX, 0x3B", "mov al, 3bh ; execve", "mov\trax, 0b111011", "mov eax, 322"] {
+            assert_eq!(kinds(source), [MisuseKind::ProcessSpawn], "{}", source);
+        }
+        assert_eq!(kinds("mov rax, 41"), [MisuseKind::NetworkAccess]);
+        assert_eq!(asm_syscalls("mov rax, 59 ; execve\nsyscall")[0].1, "mov rax, 59 ; execve");
+
+        // Ordinary syscalls, other registers, commented-out code
+        assert!(kinds("mov rax, 60\nmov rdi, 59\n; mov rax, 59\nmov rax, 1").is_empty());
+        // 32-bit numbers only count with int 0x80
+        assert!(kinds("mov eax, 11").is_empty());
+        assert_eq!(kinds("mov eax, 11\nint 0x80"), [MisuseKind::ProcessSpawn]);
+    }
+}
@@ -0,0 +1,302 @@
+//! Resource Monitor - Per-Test Stats Sidecar
+//!
+//! **Core Responsibility:**
+//! Sample the execution container's CPU and memory while each test runs, so a
+//! borderline verdict can be explained after the fact ("CPU-bound the whole time"
+//! vs "idle, probably blocked on input").
+//!
+//! A background task polls one-shot Docker stats every RESOURCE_SAMPLE_INTERVAL_MS
+//! (default 100, 0 disables) for the duration of the test. After evaluation the series
+//! is summarized, downsampled and stored as the job's debug artifacts at
+//! `optimus:debug:{job_id}` (GET /admin/jobs/{id}/debug).
+//!
+//! Monitoring is best-effort: failed samples are skipped and never affect the verdict.
+
+use crate::config::LanguageConfigManager;
+use bollard::container::StatsOptions;
+use bollard::Docker;
+use futures_util::stream::StreamExt;
+use optimus_common::redis;
+use optimus_common::types::{
+    DebugArtifacts, ExecutionResult, JobRequest, ResourceSample, TestResourceUsage, TestResult,
+    TestStatus,
+};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// Samples kept per test in the stored series (peak and average use all of them)
+const MAX_STORED_SAMPLES: usize = 120;
+
+/// Time between samples, or None if monitoring is disabled
+fn sample_interval() -> Option<Duration> {
+    let ms = std::env::var("RESOURCE_SAMPLE_INTERVAL_MS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(100);
+    (ms > 0).then(|| Duration::from_millis(ms))
+}
+
+pub struct ResourceMonitor {
+    stop: oneshot::Sender<()>,
+    handle: JoinHandle<Vec<ResourceSample>>,
+}
+
+impl ResourceMonitor {
+    /// Start sampling a container (None if monitoring is disabled)
+    pub fn start(docker: &Docker, container_id: &str) -> Option<Self> {
+        let interval = sample_interval()?;
+        let docker = docker.clone();
+        let container_id = container_id.to_string();
+        let (stop, mut stopped) = oneshot::channel();
+
+        let handle = tokio::spawn(async move {
+            let started = Instant::now();
+            let mut samples = Vec::new();
+            // The first poll is the baseline CPU counter; usage is measured between polls
+            let mut previous: Option<(u64, Instant)> = None;
+            loop {
+                let options = StatsOptions { stream: false, one_shot: true };
+                if let Some(Ok(stats)) = docker.stats(&container_id, Some(options)).next().await {
+                    let now = Instant::now();
+                    let cpu_ns = stats.cpu_stats.cpu_usage.total_usage;
+                    if let Some((previous_ns, previous_at)) = previous {
+                        samples.push(ResourceSample {
+                            t_ms: started.elapsed().as_millis() as u64,
+                            cpu_percent: cpu_percent(previous_ns, cpu_ns, now - previous_at),
+                            memory_kb: stats.memory_stats.usage.unwrap_or(0) / 1024,
+                        });
+                    }
+                    previous = Some((cpu_ns, now));
+                }
+                tokio::select! {
+                    _ = &mut stopped => break,
+                    _ = tokio::time::sleep(interval) => {}
+                }
+            }
+            samples
+        });
+
+        Some(Self { stop, handle })
+    }
+
+    /// Stop sampling and return the series
+    pub async fn finish(self) -> Vec<ResourceSample> {
+        let _ = self.stop.send(());
+        self.handle.await.unwrap_or_default()
+    }
+}
+
+/// A test's peak memory: what the runner measured (`RunnerReport::memory_kb`), else the
+/// highest container usage sampled while it ran
+pub fn peak_memory_kb(measured_kb: Option<u64>, samples: Option<&[ResourceSample]>) -> Option<u64> {
+    measured_kb.or_else(|| samples?.iter().map(|s| s.memory_kb).max())
+}
+
+/// CPU usage between two cumulative counters, in percent of one CPU
+fn cpu_percent(previous_ns: u64, current_ns: u64, elapsed: Duration) -> f64 {
+    let elapsed_ns = elapsed.as_nanos() as f64;
+    if elapsed_ns == 0.0 {
+        return 0.0;
+    }
+    current_ns.saturating_sub(previous_ns) as f64 / elapsed_ns * 100.0
+}
+
+/// Limits a test ran under, for judging how close it came to them
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    pub cpu_percent: f64,
+    pub memory_kb: u64,
+    pub timeout_ms: u64,
+}
+
+/// Summarize one test's series and explain its verdict if it is borderline
+pub fn summarize(result: &TestResult, samples: &[ResourceSample], limits: &Limits) -> TestResourceUsage {
+    let peak_memory_kb = samples.iter().map(|s| s.memory_kb).max().unwrap_or(0);
+    let avg_cpu_percent = if samples.is_empty() {
+        0.0
+    } else {
+        samples.iter().map(|s| s.cpu_percent).sum::<f64>() / samples.len() as f64
+    };
+
+    TestResourceUsage {
+        test_id: result.test_id,
+        status: result.status,
+        execution_time_ms: result.execution_time_ms,
+        peak_memory_kb,
+        avg_cpu_percent,
+        explanation: explain(result, !samples.is_empty(), avg_cpu_percent, peak_memory_kb, limits),
+        samples: downsample(samples),
+    }
+}
+
+fn explain(
+    result: &TestResult,
+    sampled: bool,
+    avg_cpu_percent: f64,
+    peak_memory_kb: u64,
+    limits: &Limits,
+) -> Option<String> {
+    let memory_share = peak_memory_kb as f64 / limits.memory_kb.max(1) as f64;
+    let memory = format!(
+        "memory peaked at {} MiB of the {} MiB limit",
+        peak_memory_kb / 1024,
+        limits.memory_kb / 1024
+    );
+
+    match result.status {
+        TestStatus::TimeLimitExceeded if sampled => {
+            let cpu_share = avg_cpu_percent / limits.cpu_percent.max(1.0);
+            let quota = format!("{:.0}% CPU of a {:.0}% quota", avg_cpu_percent, limits.cpu_percent);
+            Some(if cpu_share >= 0.8 {
+                format!("CPU-bound: used {} until the limit, so it needed more time rather than waiting", quota)
+            } else if cpu_share < 0.2 {
+                format!("Mostly idle: used {}, so it was likely blocked (waiting for input, sleeping or deadlocked)", quota)
+            } else {
+                format!("Partly idle: used {}, so part of the time was spent waiting", quota)
+            })
+        }
+        TestStatus::MemoryLimitExceeded if sampled => Some(format!("Out of memory: {}", memory)),
+        TestStatus::RuntimeError if memory_share >= 0.9 => {
+            Some(format!("Likely out of memory: {}", memory))
+        }
+        // The limit applies to CPU time when it was measured
+        _ if limited_time(result) * 10 >= limits.timeout_ms * 8 => Some(format!(
+            "Borderline time: {} of {} ms, so it may exceed the limit on a busier worker",
+            limited_time(result), limits.timeout_ms
+        )),
+        _ if memory_share >= 0.8 => Some(format!("Borderline memory: {}", memory)),
+        _ => None,
+    }
+}
+
+/// Keep at most MAX_STORED_SAMPLES evenly spaced samples
+fn downsample(samples: &[ResourceSample]) -> Vec<ResourceSample> {
+    let stride = samples.len().div_ceil(MAX_STORED_SAMPLES).max(1);
+    samples.iter().step_by(stride).copied().collect()
+}
+
+/// Store the job's per-test resource usage as debug artifacts
+pub async fn store(
+    redis_conn: &mut ::redis::aio::ConnectionManager,
+    config_manager: &LanguageConfigManager,
+    job: &JobRequest,
+    result: &ExecutionResult,
+    series: Vec<(u32, Vec<ResourceSample>)>,
+) {
+    if series.is_empty() {
+        return;
+    }
+    let limits = Limits {
+        cpu_percent: config_manager.get_cpu_limit(&job.language).map(|c| c * 100.0).unwrap_or(50.0),
+        memory_kb: config_manager.get_memory_limit_mb(&job.language).map(|m| m as u64 * 1024).unwrap_or(256 * 1024),
+        timeout_ms: job.timeout_ms,
+    };
+    let tests = series
+        .iter()
+        .filter_map(|(test_id, samples)| {
+            let test = result.results.iter().find(|r| r.test_id == *test_id)?;
+            Some(summarize(test, samples, &limits))
+        })
+        .collect();
+
+    let artifacts = DebugArtifacts {
+        job_id: job.id,
+        cpu_limit_percent: limits.cpu_percent,
+        memory_limit_kb: limits.memory_kb,
+        tests,
+        created_at: chrono::Utc::now(),
+    };
+    if let Err(e) = redis::store_debug_artifacts(redis_conn, &artifacts).await {
+        warn!(job_id = %job.id, error = %e, "Failed to store debug artifacts");
+    }
+}
+
+/// The time held to the limit: CPU time when measured, otherwise wall time
+fn limited_time(result: &TestResult) -> u64 {
+    result.cpu_time_ms.unwrap_or(result.execution_time_ms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMITS: Limits = Limits { cpu_percent: 100.0, memory_kb: 256 * 1024, timeout_ms: 1000 };
+
+    fn test_result(status: TestStatus, execution_time_ms: u64) -> TestResult {
+        TestResult {
+            test_id: 1,
+            status,
+            stdout: String::new(),
+            stderr: String::new(),
+            execution_time_ms,
+            stdout_base64: None,
+            stderr_base64: None,
+            overhead_ms: None,
+            cpu_time_ms: None,
+            wall_time_ms: None,
+            memory_kb: None,
+            failure: None,
+            checker_output: None,
+            sanitizer_report: None,
+            diff: None,
+        }
+    }
+
+    fn series(cpu_percent: f64, memory_kb: u64, n: usize) -> Vec<ResourceSample> {
+        (0..n)
+            .map(|i| ResourceSample { t_ms: i as u64 * 100, cpu_percent, memory_kb })
+            .collect()
+    }
+
+    #[test]
+    fn test_cpu_percent() {
+        assert_eq!(cpu_percent(0, 50_000_000, Duration::from_millis(100)), 50.0);
+        assert_eq!(cpu_percent(10, 5, Duration::from_millis(100)), 0.0);
+        assert_eq!(cpu_percent(0, 5, Duration::ZERO), 0.0);
+    }
+
+    #[test]
+    fn test_tle_explanations() {
+        let tle = test_result(TestStatus::TimeLimitExceeded, 1000);
+        let busy = summarize(&tle, &series(97.0, 1024, 10), &LIMITS);
+        assert!(busy.explanation.unwrap().starts_with("CPU-bound"));
+        assert_eq!(busy.avg_cpu_percent, 97.0);
+
+        let idle = summarize(&tle, &series(2.0, 1024, 10), &LIMITS);
+        assert!(idle.explanation.unwrap().starts_with("Mostly idle"));
+    }
+
+    #[test]
+    fn test_memory_and_borderline_explanations() {
+        let crashed = summarize(&test_result(TestStatus::RuntimeError, 50), &series(90.0, 250 * 1024, 3), &LIMITS);
+        assert_eq!(crashed.peak_memory_kb, 250 * 1024);
+        assert!(crashed.explanation.unwrap().starts_with("Likely out of memory"));
+        let killed = summarize(&test_result(TestStatus::MemoryLimitExceeded, 50), &series(90.0, 256 * 1024, 3), &LIMITS);
+        assert!(killed.explanation.unwrap().starts_with("Out of memory"));
+
+        let close = summarize(&test_result(TestStatus::Passed, 850), &series(90.0, 1024, 3), &LIMITS);
+        assert!(close.explanation.unwrap().starts_with("Borderline time"));
+
+        let fine = summarize(&test_result(TestStatus::Passed, 100), &series(90.0, 1024, 3), &LIMITS);
+        assert!(fine.explanation.is_none());
+    }
+
+    #[test]
+    fn test_peak_memory_prefers_the_runner() {
+        let samples = series(50.0, 4096, 3);
+        assert_eq!(peak_memory_kb(Some(1200), Some(&samples)), Some(1200));
+        assert_eq!(peak_memory_kb(None, Some(&samples)), Some(4096));
+        assert_eq!(peak_memory_kb(None, Some(&[])), None);
+        assert_eq!(peak_memory_kb(None, None), None);
+    }
+
+    #[test]
+    fn test_downsample_bounds_series() {
+        assert_eq!(downsample(&series(1.0, 1, 50)).len(), 50);
+        let long = downsample(&series(1.0, 1, 1000));
+        assert!(long.len() <= MAX_STORED_SAMPLES);
+        assert_eq!(long[1].t_ms, 900);
+    }
+}
@@ -0,0 +1,102 @@
+//! Notebook Submissions - Jupyter .ipynb for Python
+//!
+//! **Core Responsibility:**
+//! Turn a notebook submission into the script that is judged.
+//!
+//! Students often work in notebooks. A Python job with `"source_format": "notebook"`
+//! carries the .ipynb document as its source. The worker writes it to
+//! `/code/main.ipynb`, and before anything else the container extracts its code cells, in
+//! order, into `/code/main.py`, the way `jupyter nbconvert --to script` would. The
+//! judging that follows is that of any Python job.
+//!
+//! - Markdown and raw cells are dropped
+//! - IPython magics and shell escapes (`%timeit`, `!pip install`) are commented out: they
+//!   are not Python, and the sandbox has nothing to install from anyway
+//! - A document that can't be converted fails every test as a compilation error
+//!
+//! The conversion is the first stage of the job's compile step, so notebook jobs run in
+//! compile-once containers.
+
+use optimus_common::language_config::CompileSpec;
+use optimus_common::types::{JobRequest, Language, SourceFormat};
+
+/// Where the notebook is written
+const NOTEBOOK_FILE: &str = "main.ipynb";
+
+/// Extracts the code cells of /code/main.ipynb into /code/main.py (nbformat 4)
+///
+/// Kept free of double quotes, `$` and backticks: it runs inside `python3 -c "..."`.
+const CONVERT_COMMAND: &str = r#"python3 -c "
+import json, sys
+try:
+    notebook = json.load(open('/code/main.ipynb', encoding='utf-8'))
+    cells = [cell for cell in notebook['cells'] if cell.get('cell_type') == 'code']
+except (ValueError, KeyError, TypeError, AttributeError) as e:
+    sys.exit('Invalid notebook: %s' % e)
+def source(cell):
+    text = cell.get('source', '')
+    text = ''.join(text) if isinstance(text, list) else text
+    return '\n'.join('# ' + line if line.lstrip().startswith(('%', '!')) else line for line in text.splitlines())
+open('/code/main.py', 'w', encoding='utf-8').write('\n\n'.join(source(cell) for cell in cells) + '\n')
+""#;
+
+/// Whether the job's source is a notebook to convert
+pub fn is_notebook(job: &JobRequest) -> bool {
+    job.source_format == SourceFormat::Notebook && job.language == Language::Python
+}
+
+/// File in /code the job's source is written to
+pub fn source_file(job: &JobRequest) -> &'static str {
+    if is_notebook(job) {
+        NOTEBOOK_FILE
+    } else {
+        job.language.source_file()
+    }
+}
+
+/// The language's compile step, after converting the notebook (a step of its own for
+/// interpreted languages)
+pub fn compile(compile: Option<CompileSpec>, job: &JobRequest) -> Option<CompileSpec> {
+    if !is_notebook(job) {
+        return compile;
+    }
+    let mut then = Vec::new();
+    if let Some(compile) = compile {
+        then.push(compile.command);
+        then.extend(compile.then);
+    }
+    Some(CompileSpec { command: CONVERT_COMMAND.to_string(), then })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(language: &str, source_format: &str) -> JobRequest {
+        serde_json::from_value(serde_json::json!({
+            "id": uuid::Uuid::new_v4(),
+            "language": language,
+            "source_code": "{}",
+            "test_cases": [{"id": 1, "input": "", "expected_output": "", "weight": 1}],
+            "timeout_ms": 1000,
+            "source_format": source_format,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_notebooks_convert_before_compiling() {
+        let notebook = job("python", "notebook");
+        assert_eq!(source_file(&notebook), "main.ipynb");
+        let spec = compile(None, &notebook).unwrap();
+        assert!(spec.command.starts_with("python3 -c \"") && spec.then.is_empty());
+        assert!(!CONVERT_COMMAND[13..CONVERT_COMMAND.len() - 1].contains(['"', '$', '`']));
+
+        let script = job("python", "script");
+        assert_eq!(source_file(&script), "main.py");
+        assert_eq!(compile(None, &script), None);
+        // Only Python reads notebooks
+        let java = job("java", "notebook");
+        assert_eq!(compile(Some(CompileSpec::new("javac /code/Main.java")), &java).unwrap().command, "javac /code/Main.java");
+    }
+}
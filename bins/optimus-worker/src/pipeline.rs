@@ -0,0 +1,323 @@
+//! Evaluator Pipeline - Composable Normalizers and Comparators
+//!
+//! **Core Responsibility:**
+//! Decide whether two outputs match: normalizers rewrite both outputs in order, then one
+//! comparator judges the results.
+//!
+//! A job describes its pipeline declaratively (`JobRequest.evaluation`):
+//! `{ "normalizers": [{"type": "trim"}, {"type": "float_round", "decimals": 3}],
+//!    "comparator": {"type": "numeric", "abs_tolerance": 1e-6} }`
+//!
+//! Jobs without one get `Pipeline::legacy`, which reproduces the comparison modes and
+//! encoding options exactly, so existing jobs keep their verdicts.
+//!
+//! Pipelines can also be built in code:
+//! `Pipeline::builder().normalize(Normalizer::Trim).compare(Comparator::Tokens).build()`
+
+use optimus_common::types::{
+    ComparisonMode, Comparator, EvaluationPipeline, NormalizationOptions, Normalizer,
+};
+use std::borrow::Cow;
+use unicode_normalization::UnicodeNormalization;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Pipeline {
+    normalizers: Vec<Normalizer>,
+    comparator: Comparator,
+}
+
+/// Builder for a `Pipeline` (exact comparison unless told otherwise)
+#[derive(Debug, Default)]
+pub struct PipelineBuilder {
+    pipeline: Pipeline,
+}
+
+impl PipelineBuilder {
+    /// Append a normalizer (applied after the ones added before it)
+    pub fn normalize(mut self, normalizer: Normalizer) -> Self {
+        self.pipeline.normalizers.push(normalizer);
+        self
+    }
+
+    pub fn compare(mut self, comparator: Comparator) -> Self {
+        self.pipeline.comparator = comparator;
+        self
+    }
+
+    pub fn build(self) -> Pipeline {
+        self.pipeline
+    }
+}
+
+impl Pipeline {
+    pub fn builder() -> PipelineBuilder {
+        PipelineBuilder::default()
+    }
+
+    /// Pipeline described in a job
+    pub fn from_spec(spec: &EvaluationPipeline) -> Self {
+        Self {
+            normalizers: spec.normalizers.clone(),
+            comparator: spec.comparator,
+        }
+    }
+
+    /// Pipeline equivalent to a comparison mode plus the job's normalization options
+    ///
+    /// Encoding normalizers run first (BOM, replacement chars, NFC), then the text ones the
+    /// job asked for (lowercase, collapse whitespace, trim every line), then:
+    /// - Strict: nothing else, exact comparison
+    /// - Ordered: trim, exact comparison
+    /// - Unordered: trim, trim every line, lines compared as a multiset
+    ///
+    /// `tokens` swaps the exact comparison of ordered and strict for a token comparison.
+    pub fn legacy(mode: ComparisonMode, options: &NormalizationOptions) -> Self {
+        let mut builder = Self::builder();
+        if options.strip_bom {
+            builder = builder.normalize(Normalizer::StripBom);
+        }
+        if options.lenient_utf8 {
+            builder = builder.normalize(Normalizer::DropReplacementChars);
+        }
+        if options.unicode_nfc {
+            builder = builder.normalize(Normalizer::UnicodeNfc);
+        }
+        if options.ignore_case {
+            builder = builder.normalize(Normalizer::Lowercase);
+        }
+        if options.collapse_whitespace {
+            builder = builder.normalize(Normalizer::CollapseWhitespace);
+        }
+        if options.ignore_trailing_whitespace {
+            builder = builder.normalize(Normalizer::TrimLines);
+        }
+
+        match mode {
+            ComparisonMode::Strict => builder,
+            ComparisonMode::Ordered => builder.normalize(Normalizer::Trim),
+            ComparisonMode::Unordered => {
+                return builder
+                    .normalize(Normalizer::Trim)
+                    .normalize(Normalizer::TrimLines)
+                    .compare(Comparator::UnorderedLines)
+                    .build();
+            }
+        }
+        .compare(if options.tokens { Comparator::Tokens } else { Comparator::Exact })
+        .build()
+    }
+
+    pub fn normalizers(&self) -> &[Normalizer] {
+        &self.normalizers
+    }
+
+    pub fn comparator(&self) -> Comparator {
+        self.comparator
+    }
+
+    /// Apply every normalizer in order (borrows the input if none changes it)
+    pub fn normalize<'a>(&self, output: &'a str) -> Cow<'a, str> {
+        self.normalizers
+            .iter()
+            .fold(Cow::Borrowed(output), |text, normalizer| apply(*normalizer, text))
+    }
+
+    /// Normalize both outputs and compare them
+    pub fn matches(&self, actual: &str, expected: &str) -> bool {
+        let actual = self.normalize(actual);
+        let expected = self.normalize(expected);
+        compare(self.comparator, &actual, &expected)
+    }
+}
+
+/// Apply one normalizer
+pub fn apply(normalizer: Normalizer, text: Cow<'_, str>) -> Cow<'_, str> {
+    match normalizer {
+        Normalizer::Trim => match text {
+            Cow::Borrowed(s) => Cow::Borrowed(s.trim()),
+            Cow::Owned(s) => Cow::Owned(s.trim().to_string()),
+        },
+        Normalizer::TrimLines => Cow::Owned(
+            text.split('\n').map(str::trim_end).collect::<Vec<_>>().join("\n"),
+        ),
+        Normalizer::CollapseWhitespace => Cow::Owned(
+            text.split('\n')
+                .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ),
+        Normalizer::Lowercase => Cow::Owned(text.to_lowercase()),
+        Normalizer::FloatRound { decimals } => Cow::Owned(round_floats(&text, decimals)),
+        Normalizer::StripBom => match text.strip_prefix('\u{FEFF}') {
+            Some(stripped) => Cow::Owned(stripped.to_string()),
+            None => text,
+        },
+        Normalizer::DropReplacementChars if text.contains(char::REPLACEMENT_CHARACTER) => {
+            Cow::Owned(text.replace(char::REPLACEMENT_CHARACTER, ""))
+        }
+        Normalizer::UnicodeNfc if !unicode_normalization::is_nfc(&text) => {
+            Cow::Owned(text.nfc().collect())
+        }
+        Normalizer::DropReplacementChars | Normalizer::UnicodeNfc => text,
+    }
+}
+
+/// Round every decimal number token, keeping the whitespace between tokens
+///
+/// Integers are left alone so ids and counts never gain a fractional part.
+fn round_floats(text: &str, decimals: u32) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while !rest.is_empty() {
+        let space = rest.find(|c: char| !c.is_whitespace()).unwrap_or(rest.len());
+        out.push_str(&rest[..space]);
+        rest = &rest[space..];
+
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let token = &rest[..end];
+        match parse_decimal(token) {
+            Some(value) => {
+                let rounded = format!("{:.*}", decimals as usize, value);
+                // -0.0001 rounds to "-0.000", which should equal "0.000"
+                let negative_zero = rounded.starts_with('-') && rounded[1..].bytes().all(|b| b == b'0' || b == b'.');
+                out.push_str(if negative_zero { &rounded[1..] } else { &rounded });
+            }
+            None => out.push_str(token),
+        }
+        rest = &rest[end..];
+    }
+    out
+}
+
+fn parse_decimal(token: &str) -> Option<f64> {
+    if !token.contains(['.', 'e', 'E']) {
+        return None;
+    }
+    token.parse::<f64>().ok().filter(|v| v.is_finite())
+}
+
+fn compare(comparator: Comparator, actual: &str, expected: &str) -> bool {
+    match comparator {
+        Comparator::Exact => actual == expected,
+        Comparator::UnorderedLines => {
+            let mut actual_lines: Vec<&str> = actual.lines().collect();
+            let mut expected_lines: Vec<&str> = expected.lines().collect();
+            if actual_lines.len() != expected_lines.len() {
+                return false;
+            }
+            actual_lines.sort_unstable();
+            expected_lines.sort_unstable();
+            actual_lines == expected_lines
+        }
+        Comparator::Tokens => actual.split_whitespace().eq(expected.split_whitespace()),
+        Comparator::Numeric { abs_tolerance, rel_tolerance } => {
+            let actual: Vec<&str> = actual.split_whitespace().collect();
+            let expected: Vec<&str> = expected.split_whitespace().collect();
+            actual.len() == expected.len()
+                && actual.iter().zip(&expected).all(|(a, e)| {
+                    numbers_close(a, e, abs_tolerance, rel_tolerance).unwrap_or(a == e)
+                })
+        }
+    }
+}
+
+/// Whether two number tokens are within either tolerance (None if either isn't a number)
+pub fn numbers_close(actual: &str, expected: &str, abs_tolerance: f64, rel_tolerance: f64) -> Option<bool> {
+    let actual = actual.parse::<f64>().ok().filter(|v| v.is_finite())?;
+    let expected = expected.parse::<f64>().ok().filter(|v| v.is_finite())?;
+    let diff = (actual - expected).abs();
+    Some(diff <= abs_tolerance || diff <= rel_tolerance * actual.abs().max(expected.abs()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_and_spec_agree() {
+        let built = Pipeline::builder()
+            .normalize(Normalizer::Trim)
+            .normalize(Normalizer::Lowercase)
+            .compare(Comparator::Tokens)
+            .build();
+        let spec: EvaluationPipeline = serde_json::from_str(
+            r#"{"normalizers":[{"type":"trim"},{"type":"lowercase"}],"comparator":{"type":"tokens"}}"#,
+        )
+        .unwrap();
+        assert_eq!(Pipeline::from_spec(&spec), built);
+        assert!(built.matches("  Hello   WORLD\n", "hello world"));
+        assert!(!built.matches("hello", "hello world"));
+    }
+
+    #[test]
+    fn test_normalizers() {
+        let collapse = Pipeline::builder().normalize(Normalizer::CollapseWhitespace).build();
+        assert_eq!(collapse.normalize("a \t b\n  c  d "), "a b\nc d");
+
+        let trim_lines = Pipeline::builder().normalize(Normalizer::TrimLines).build();
+        assert_eq!(trim_lines.normalize("a  \r\nb\t\n"), "a\nb\n");
+
+        let untouched = Pipeline::builder().normalize(Normalizer::UnicodeNfc).build();
+        assert!(matches!(untouched.normalize("plain"), Cow::Borrowed("plain")));
+    }
+
+    #[test]
+    fn test_float_round() {
+        let round = Pipeline::builder().normalize(Normalizer::FloatRound { decimals: 2 }).build();
+        assert_eq!(round.normalize("3.14159 2 1e-1\n-0.001  x.y"), "3.14 2 0.10\n0.00  x.y");
+        assert!(round.matches("0.333333", "0.33"));
+        assert!(!round.matches("0.336", "0.33"));
+    }
+
+    #[test]
+    fn test_numeric_comparator() {
+        let numeric = |abs_tolerance, rel_tolerance| {
+            Pipeline::builder().compare(Comparator::Numeric { abs_tolerance, rel_tolerance }).build()
+        };
+        assert!(numeric(1e-6, 0.0).matches("1.0000001 ok", "1.0 ok"));
+        assert!(!numeric(1e-6, 0.0).matches("1.0000001 ok", "1.0 OK"));
+        assert!(!numeric(1e-9, 0.0).matches("1000001", "1000000"));
+        assert!(numeric(1e-9, 1e-6).matches("1000001", "1000000"));
+        assert!(!numeric(1.0, 0.0).matches("1 2", "1"));
+        assert!(!numeric(1.0, 1.0).matches("nan", "1"));
+    }
+
+    #[test]
+    fn test_legacy_modes() {
+        let options = NormalizationOptions::default();
+        let ordered = Pipeline::legacy(ComparisonMode::Ordered, &options);
+        assert!(ordered.matches("  1 2\n", "1 2"));
+        assert!(!ordered.matches("2\n1", "1\n2"));
+
+        let unordered = Pipeline::legacy(ComparisonMode::Unordered, &options);
+        assert!(unordered.matches("2 \r\n1\n", "1\n2"));
+
+        let strict = Pipeline::legacy(ComparisonMode::Strict, &options);
+        assert!(!strict.matches("1\n", "1"));
+
+        let bom = NormalizationOptions { strip_bom: true, ..Default::default() };
+        assert!(Pipeline::legacy(ComparisonMode::Strict, &bom).matches("\u{FEFF}1", "1"));
+    }
+
+    #[test]
+    fn test_legacy_text_options() {
+        let ordered = |options: NormalizationOptions| Pipeline::legacy(ComparisonMode::Ordered, &options);
+        let trailing = ordered(NormalizationOptions { ignore_trailing_whitespace: true, ..Default::default() });
+        assert!(trailing.matches("1 2  \n3\t\n", "1 2\n3"));
+        assert!(!trailing.matches("1  2", "1 2"));
+
+        let case = ordered(NormalizationOptions { ignore_case: true, ..Default::default() });
+        assert!(case.matches("YES", "yes"));
+
+        let collapse = ordered(NormalizationOptions { collapse_whitespace: true, ..Default::default() });
+        assert!(collapse.matches("1 \t  2", "1 2"));
+        assert!(!collapse.matches("1\n2", "1 2"));
+
+        let tokens = NormalizationOptions { tokens: true, ..Default::default() };
+        assert!(ordered(tokens).matches("1\n2   3", "1 2 3"));
+        assert_eq!(Pipeline::legacy(ComparisonMode::Strict, &tokens).comparator(), Comparator::Tokens);
+        // Unordered comparison keeps comparing lines
+        let unordered = Pipeline::legacy(ComparisonMode::Unordered, &tokens);
+        assert_eq!(unordered.comparator(), Comparator::UnorderedLines);
+    }
+}
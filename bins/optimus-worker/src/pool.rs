@@ -0,0 +1,388 @@
+//! Container Pool
+//!
+//! **Core Responsibility:**
+//! Keep warm compile-once containers for languages with `container_pooling` set, so a job
+//! skips container creation and startup.
+//!
+//! Containers are pooled per language and image (limits and environment follow from both).
+//! Pooled containers are created without job-specific environment, and each test gets its
+//! environment per exec, so only processes and files can carry over between jobs. Before a
+//! finished job's container is returned, the reset:
+//! 1. Kills every process but init and the keep-alive, whose PID is recorded when the
+//!    container starts (before any job code runs)
+//! 2. Empties `/code`, `/tmp` and `/dev/shm`, and checks that they stay empty
+//! 3. Checks the container's filesystem diff: nothing may differ from the image outside
+//!    `/code` and `/tmp`
+//!
+//! Any failure removes the container instead. A job only gets a container whose keep-alive
+//! covers its whole budget; idle containers are removed when the worker shuts down.
+
+use bollard::container::RemoveContainerOptions;
+use bollard::exec::{CreateExecOptions, StartExecOptions, StartExecResults};
+use bollard::models::FilesystemChange;
+use bollard::Docker;
+use futures_util::stream::StreamExt;
+use optimus_common::types::Language;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// Idle containers kept per language and image
+const MAX_IDLE: usize = 2;
+
+/// Minimum keep-alive of pooled containers (longer jobs create theirs with their own budget)
+pub const KEEP_ALIVE: Duration = Duration::from_secs(900);
+
+/// Prints the children of init (tini, PID 1); right after the container starts, that is
+/// only the keep-alive
+const KEEP_ALIVE_SCRIPT: &str = r#"
+for stat in /proc/[0-9]*/stat; do read -r pid _ _ ppid _ < "$stat" && [ "$ppid" = 1 ] && echo "$pid"; done 2>/dev/null
+"#;
+
+/// Kills every process a job left running in a container but init and the keep-alive
+/// (`keep`, the PID recorded at start); exits non-zero (printing what is left) if any
+/// survives or the keep-alive is gone
+const KILL_SCRIPT: &str = r#"
+[ -d "/proc/$keep" ] || { echo "keep-alive $keep is gone"; exit 1; }
+others() {
+    for dir in /proc/[0-9]*; do
+        pid=${dir#/proc/}
+        case "$pid" in 1|"$keep"|"$$"|"$BASHPID") ;; *) echo "$pid" ;; esac
+    done
+}
+for _ in 1 2 3 4 5 6 7 8 9 10; do
+    pids=$(others)
+    [ -z "$pids" ] && break
+    kill -9 $pids 2>/dev/null
+    sleep 0.1
+done
+pids=$(others)
+[ -z "$pids" ] || { echo "processes left:" $pids; exit 1; }
+"#;
+
+/// Empties the scratch directories after KILL_SCRIPT; exits non-zero if files survive
+const EMPTY_SCRIPT: &str = r#"
+shopt -s nullglob dotglob
+rm -rf -- /code/* /tmp/* /dev/shm/* 2>/dev/null
+left=(/code/* /tmp/* /dev/shm/*)
+[ ${#left[@]} -eq 0 ] || { echo "files left:" "${left[@]}"; exit 1; }
+"#;
+
+/// Paths that may differ from the image in a reset container (both emptied by the reset)
+const SCRATCH_DIRS: [&str; 2] = ["/code", "/tmp"];
+
+/// Keep-alive PID of every running sandbox container, by container id
+fn keep_alive_pids() -> &'static Mutex<HashMap<String, u32>> {
+    static PIDS: OnceLock<Mutex<HashMap<String, u32>>> = OnceLock::new();
+    PIDS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Record the keep-alive of a container that just started, before any job code runs in it
+pub async fn record_keep_alive(docker: &Docker, container_id: &str) -> anyhow::Result<()> {
+    let printed = run_script(docker, container_id, KEEP_ALIVE_SCRIPT).await?;
+    let pid = parse_keep_alive(&printed)?;
+    keep_alive_pids().lock().unwrap_or_else(|e| e.into_inner()).insert(container_id.to_string(), pid);
+    Ok(())
+}
+
+/// The single child PID of init
+fn parse_keep_alive(printed: &str) -> anyhow::Result<u32> {
+    let pids: Vec<u32> = printed.split_whitespace().filter_map(|pid| pid.parse().ok()).collect();
+    match pids.as_slice() {
+        [pid] => Ok(*pid),
+        _ => anyhow::bail!("expected one keep-alive process, found {:?}", pids),
+    }
+}
+
+/// Drop the recorded keep-alive of a removed container
+pub fn forget(container_id: &str) {
+    keep_alive_pids().lock().unwrap_or_else(|e| e.into_inner()).remove(container_id);
+}
+
+/// KILL_SCRIPT for a container, sparing its recorded keep-alive
+fn kill_script(container_id: &str) -> anyhow::Result<String> {
+    let pids = keep_alive_pids().lock().unwrap_or_else(|e| e.into_inner());
+    let Some(keep) = pids.get(container_id) else {
+        anyhow::bail!("no keep-alive recorded for the container");
+    };
+    Ok(format!("keep={}\n{}", keep, KILL_SCRIPT))
+}
+
+struct Idle {
+    container_id: String,
+    /// When the container's init process exits
+    expires: Instant,
+}
+
+fn idle() -> &'static Mutex<HashMap<String, Vec<Idle>>> {
+    static IDLE: OnceLock<Mutex<HashMap<String, Vec<Idle>>>> = OnceLock::new();
+    IDLE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn key(language: &Language, image: &str) -> String {
+    format!("{}|{}", language, image)
+}
+
+/// A pooled container checked out for one job
+pub struct Lease {
+    pub container_id: String,
+    expires: Instant,
+}
+
+/// Take an idle container that stays up for at least `needed`
+///
+/// Returns the containers too close to expiring, for the caller to remove.
+pub fn checkout(language: &Language, image: &str, needed: Duration) -> (Option<Lease>, Vec<String>) {
+    let mut idle = idle().lock().unwrap_or_else(|e| e.into_inner());
+    let Some(containers) = idle.get_mut(&key(language, image)) else { return (None, Vec::new()) };
+
+    let mut expired = Vec::new();
+    while let Some(container) = containers.pop() {
+        if container.expires.saturating_duration_since(Instant::now()) >= needed {
+            return (Some(Lease { container_id: container.container_id, expires: container.expires }), expired);
+        }
+        expired.push(container.container_id);
+    }
+    (None, expired)
+}
+
+/// Lease for a container the caller just started with this keep-alive
+pub fn lease_new(container_id: String, keep_alive: Duration) -> Lease {
+    Lease { container_id, expires: Instant::now() + keep_alive }
+}
+
+/// Reset a job's container and return it to the pool
+///
+/// Returns false if the container was not kept (reset failed, expired, pool full);
+/// the caller still owns it and must remove it.
+pub async fn checkin(docker: &Docker, language: &Language, image: &str, lease: Lease) -> bool {
+    if lease.expires <= Instant::now() {
+        return false;
+    }
+    if let Err(e) = reset(docker, &lease.container_id).await {
+        warn!(container_id = %lease.container_id, error = %e, "Failed to reset pooled container");
+        return false;
+    }
+
+    let mut idle = idle().lock().unwrap_or_else(|e| e.into_inner());
+    let containers = idle.entry(key(language, image)).or_default();
+    if containers.len() >= MAX_IDLE {
+        return false;
+    }
+    debug!(container_id = %lease.container_id, "Returned container to pool");
+    containers.push(Idle { container_id: lease.container_id, expires: lease.expires });
+    true
+}
+
+async fn reset(docker: &Docker, container_id: &str) -> anyhow::Result<()> {
+    run_script(docker, container_id, &format!("{}{}", kill_script(container_id)?, EMPTY_SCRIPT)).await?;
+    let changes = docker.container_changes(container_id).await?.unwrap_or_default();
+    let leaked = unexpected_changes(&changes);
+    if !leaked.is_empty() {
+        anyhow::bail!("filesystem differs from the image: {}", leaked.join(", "));
+    }
+    Ok(())
+}
+
+/// Changed paths outside the scratch directories
+fn unexpected_changes(changes: &[FilesystemChange]) -> Vec<&str> {
+    changes
+        .iter()
+        .map(|change| change.path.as_str())
+        .filter(|path| !SCRATCH_DIRS.contains(path))
+        .collect()
+}
+
+/// Kill the processes left running in a container mid-job (e.g. after a timed-out
+/// warmup run), keeping its files
+pub async fn kill_strays(docker: &Docker, container_id: &str) -> anyhow::Result<()> {
+    run_script(docker, container_id, &kill_script(container_id)?).await.map(|_| ())
+}
+
+/// Run a script as root; returns what it printed, or an error if it exits non-zero
+async fn run_script(docker: &Docker, container_id: &str, script: &str) -> anyhow::Result<String> {
+    let exec = docker
+        .create_exec(
+            container_id,
+            CreateExecOptions {
+                cmd: Some(vec!["bash", "-c", script]),
+                attach_stdout: Some(true),
+                attach_stderr: Some(true),
+                user: Some("root"),
+                ..Default::default()
+            },
+        )
+        .await?;
+    let mut printed = String::new();
+    if let StartExecResults::Attached { mut output, .. } =
+        docker.start_exec(&exec.id, Some(StartExecOptions { detach: false, ..Default::default() })).await?
+    {
+        while let Some(chunk) = output.next().await {
+            if let Ok(chunk) = chunk {
+                printed.push_str(&chunk.to_string());
+            }
+        }
+    }
+    match docker.inspect_exec(&exec.id).await?.exit_code {
+        Some(0) => Ok(printed),
+        code => anyhow::bail!("script exited with {:?}: {}", code, printed.trim()),
+    }
+}
+
+/// Remove containers, ignoring failures (they may already be gone)
+pub async fn remove(docker: &Docker, container_ids: Vec<String>) {
+    for container_id in container_ids {
+        forget(&container_id);
+        let options = RemoveContainerOptions { force: true, ..Default::default() };
+        if let Err(e) = docker.remove_container(&container_id, Some(options)).await {
+            debug!(container_id = %container_id, error = %e, "Failed to remove pooled container");
+        }
+    }
+}
+
+/// Remove every idle container (worker shutdown)
+pub async fn drain() {
+    let container_ids: Vec<String> = {
+        let mut idle = idle().lock().unwrap_or_else(|e| e.into_inner());
+        idle.drain().flat_map(|(_, containers)| containers).map(|c| c.container_id).collect()
+    };
+    if container_ids.is_empty() {
+        return;
+    }
+    match Docker::connect_with_local_defaults() {
+        Ok(docker) => remove(&docker, container_ids).await,
+        Err(e) => warn!(error = %e, "Failed to connect to Docker to drain the container pool"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bollard::models::ChangeType;
+
+    #[test]
+    fn test_checkout_skips_expiring_containers() {
+        let image = "optimus-pool-test:latest";
+        let minute = Duration::from_secs(60);
+        idle().lock().unwrap().insert(
+            key(&Language::Rust, image),
+            vec![
+                Idle { container_id: "fresh".into(), expires: Instant::now() + KEEP_ALIVE },
+                Idle { container_id: "expiring".into(), expires: Instant::now() + minute },
+            ],
+        );
+
+        let (lease, expired) = checkout(&Language::Rust, image, 2 * minute);
+        assert_eq!(lease.unwrap().container_id, "fresh");
+        assert_eq!(expired, vec!["expiring".to_string()]);
+        assert!(checkout(&Language::Rust, image, minute).0.is_none());
+        assert!(checkout(&Language::Java, image, minute).0.is_none());
+    }
+
+    #[test]
+    fn test_keep_alive_must_be_the_only_child_of_init() {
+        assert_eq!(parse_keep_alive("7\n").unwrap(), 7);
+        assert!(parse_keep_alive("").is_err());
+        assert!(parse_keep_alive("7\n12\n").is_err());
+        assert!(kill_script("optimus-pool-test-unknown").is_err());
+    }
+
+    #[test]
+    fn test_changes_outside_scratch_dirs_are_leaks() {
+        let change = |path: &str| FilesystemChange { path: path.to_string(), kind: ChangeType::_1 };
+        let changes = vec![change("/code"), change("/tmp"), change("/etc"), change("/etc/leak"), change("/code2")];
+        assert_eq!(unexpected_changes(&changes), vec!["/etc", "/etc/leak", "/code2"]);
+        assert!(unexpected_changes(&changes[..2]).is_empty());
+    }
+
+    async fn exec(docker: &Docker, container_id: &str, script: &str) -> String {
+        let exec = docker
+            .create_exec(
+                container_id,
+                CreateExecOptions {
+                    cmd: Some(vec!["bash", "-c", script]),
+                    attach_stdout: Some(true),
+                    attach_stderr: Some(true),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        let mut printed = String::new();
+        if let StartExecResults::Attached { mut output, .. } = docker.start_exec(&exec.id, None).await.unwrap() {
+            while let Some(Ok(chunk)) = output.next().await {
+                printed.push_str(&chunk.to_string());
+            }
+        }
+        printed
+    }
+
+    async fn start_container(docker: &Docker, image: &str) -> String {
+        use bollard::container::{Config, CreateContainerOptions, StartContainerOptions};
+        let name = format!("optimus-pool-test-{}", uuid::Uuid::new_v4());
+        let config = Config {
+            image: Some(image.to_string()),
+            cmd: Some(vec!["sleep".to_string(), "300".to_string()]),
+            entrypoint: Some(vec![]),
+            network_disabled: Some(true),
+            host_config: Some(bollard::models::HostConfig { init: Some(true), ..Default::default() }),
+            working_dir: Some("/code".to_string()),
+            ..Default::default()
+        };
+        let options = CreateContainerOptions { name: name.as_str(), platform: None };
+        let container = docker.create_container(Some(options), config).await.unwrap();
+        docker.start_container(&container.id, None::<StartContainerOptions<String>>).await.unwrap();
+        record_keep_alive(docker, &container.id).await.unwrap();
+        container.id
+    }
+
+    /// A job's files and processes never reach the next job in the container
+    #[tokio::test]
+    #[ignore] // Requires Docker and the optimus-python image
+    async fn test_reset_leaves_nothing_for_the_next_job() {
+        let docker = Docker::connect_with_local_defaults().unwrap();
+        let image = "optimus-python:latest";
+        let container_id = start_container(&docker, image).await;
+
+        // Job 1: files in every writable scratch place, a background process and a daemon
+        exec(
+            &docker,
+            &container_id,
+            "echo secret > /code/solution.py; echo secret > /code/.hidden; mkdir -p /tmp/cache/deep; \
+             echo secret > /tmp/cache/deep/file; echo secret > /dev/shm/segment; \
+             nohup sleep 600 >/dev/null 2>&1 & setsid bash -c 'while true; do sleep 1; done' >/dev/null 2>&1 &",
+        )
+        .await;
+        let lease = lease_new(container_id.clone(), KEEP_ALIVE);
+        assert!(checkin(&docker, &Language::Python, image, lease).await, "reset should succeed");
+
+        // Job 2: sees empty scratch directories and only init plus the keep-alive
+        let (lease, _) = checkout(&Language::Python, image, Duration::from_secs(60));
+        assert_eq!(lease.unwrap().container_id, container_id);
+        let files = exec(&docker, &container_id, "shopt -s nullglob dotglob; echo /code/* /tmp/* /dev/shm/*").await;
+        assert_eq!(files.trim(), "");
+        let processes = exec(&docker, &container_id, r#"for s in /proc/[0-9]*/cmdline; do tr '\0' ' ' < "$s"; echo; done"#).await;
+        let processes: Vec<&str> = processes.lines().map(str::trim).filter(|p| !p.is_empty()).collect();
+        assert!(!processes.iter().any(|p| p.contains("600") || p.contains("while")), "leftover processes: {:?}", processes);
+        assert_eq!(processes.iter().filter(|p| p.starts_with("sleep 300")).count(), 1, "keep-alive: {:?}", processes);
+
+        remove(&docker, vec![container_id]).await;
+    }
+
+    /// Writes outside the scratch directories make the container unusable for other jobs
+    #[tokio::test]
+    #[ignore] // Requires Docker and the optimus-python image
+    async fn test_reset_rejects_writes_outside_scratch_dirs() {
+        let docker = Docker::connect_with_local_defaults().unwrap();
+        let image = "optimus-python:latest";
+        let container_id = start_container(&docker, image).await;
+
+        exec(&docker, &container_id, "echo secret > /etc/leak").await;
+        let lease = lease_new(container_id.clone(), KEEP_ALIVE);
+        assert!(!checkin(&docker, &Language::Python, image, lease).await);
+        assert!(checkout(&Language::Python, image, Duration::from_secs(60)).0.is_none());
+
+        remove(&docker, vec![container_id]).await;
+    }
+}
@@ -0,0 +1,494 @@
+//! Per-Test Progress Reporting
+//!
+//! **Responsibility:**
+//! Publish each test's verdict to `optimus:progress:{job_id}` as soon as it finishes,
+//! so clients polling `GET /job/{id}/progress` see "7/20 tests done" on long jobs.
+//!
+//! The engine hands every raw output to the reporter; the reporter asks the evaluator
+//! for the verdict, so the engine still never judges correctness.
+//! Progress is best-effort: Redis failures are logged and never fail the job.
+//!
+//! **Live events:**
+//! The job's start (`running`), its compile step and each finished test are also published
+//! on the progress channel, for clients streaming GET /job/{id}/stream; storing the
+//! result publishes `done`.
+//!
+//! **Live output:**
+//! With LIVE_OUTPUT_KB set, the first that many KB of each running test's stdout and stderr
+//! are published as `output` events as the program writes them, so a stream shows the
+//! program's output before its verdict. Chunks are never stored: a client connecting late
+//! only sees what is still to come.
+//!
+//! **Checkpoints:**
+//! Jobs with at least CHECKPOINT_MIN_TESTS tests also persist raw outputs to
+//! `optimus:checkpoint:{job_id}:{attempt}` every CHECKPOINT_EVERY_TESTS tests.
+//! When a retry starts, the latest earlier checkpoint is carried forward and the
+//! engine skips the tests it covers, so a worker dying halfway only costs the
+//! tests since the last checkpoint.
+
+use crate::evaluator::{self, EvaluationOptions, TestExecutionOutput};
+use optimus_common::redis;
+use optimus_common::types::{JobRequest, OutputChunk, OutputStream, ProgressEvent, TestCase, TestProgress};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// Output of each test published live (LIVE_OUTPUT_KB, default 0 = off)
+fn live_output_bytes() -> usize {
+    std::env::var("LIVE_OUTPUT_KB")
+        .ok()
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .unwrap_or(0)
+        .saturating_mul(1024)
+}
+
+/// When and how often to checkpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CheckpointPolicy {
+    /// Jobs with fewer tests are cheap to rerun and never checkpoint (0 disables)
+    pub min_tests: usize,
+    /// Flush a checkpoint after this many newly completed tests
+    pub every_tests: usize,
+}
+
+impl CheckpointPolicy {
+    pub fn from_env() -> Self {
+        let read = |name: &str, default: usize| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        };
+        Self {
+            min_tests: read("CHECKPOINT_MIN_TESTS", 50),
+            every_tests: read("CHECKPOINT_EVERY_TESTS", 10).max(1),
+        }
+    }
+
+    fn applies_to(&self, job: &JobRequest) -> bool {
+        self.min_tests > 0 && job.test_cases.len() >= self.min_tests
+    }
+}
+
+#[derive(Default)]
+struct CheckpointState {
+    /// Outputs completed since the last flush
+    pending: Vec<TestExecutionOutput>,
+    /// Outputs carried over from an earlier attempt, by test id
+    restored: HashMap<u32, TestExecutionOutput>,
+}
+
+pub struct ProgressReporter<'a> {
+    job: Option<&'a JobRequest>,
+    options: EvaluationOptions,
+    checkpoint: Option<CheckpointPolicy>,
+    state: Mutex<CheckpointState>,
+    /// How long the compile step took (compile-once execution only)
+    compile_time: Mutex<Option<Duration>>,
+    /// Bytes of each test's output published live (0 = off)
+    live_output_bytes: usize,
+}
+
+impl<'a> ProgressReporter<'a> {
+    pub fn new(job: &'a JobRequest) -> Self {
+        Self {
+            live_output_bytes: live_output_bytes(),
+            ..Self::with_policy(job, CheckpointPolicy::from_env())
+        }
+    }
+
+    pub fn with_policy(job: &'a JobRequest, policy: CheckpointPolicy) -> Self {
+        Self {
+            job: Some(job),
+            options: EvaluationOptions::for_job(job),
+            checkpoint: policy.applies_to(job).then_some(policy),
+            state: Mutex::new(CheckpointState::default()),
+            compile_time: Mutex::new(None),
+            live_output_bytes: 0,
+        }
+    }
+
+    /// Judge live verdicts with these settings instead of the job's own (language flags applied)
+    pub fn with_options(mut self, options: EvaluationOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Reporter that publishes nothing (shadow runs, engine tests)
+    pub fn disabled() -> Self {
+        Self {
+            job: None,
+            options: EvaluationOptions::default(),
+            checkpoint: None,
+            state: Mutex::new(CheckpointState::default()),
+            compile_time: Mutex::new(None),
+            live_output_bytes: 0,
+        }
+    }
+
+    /// Tee for one test's output while it runs; publishes nothing unless live output is on
+    pub fn live_output(&self, conn: Option<&::redis::aio::ConnectionManager>, test_id: u32) -> LiveOutput {
+        let (Some(conn), Some(job)) = (conn, self.job) else { return LiveOutput::off() };
+        if self.live_output_bytes == 0 {
+            return LiveOutput::off();
+        }
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut conn = conn.clone();
+        let job_id = job.id;
+        // Published in order by one task, so reading the container's output never waits on Redis
+        let publisher = tokio::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                let _ = redis::publish_progress(&mut conn, &job_id, event).await;
+            }
+        });
+        LiveOutput::new(tx, test_id, self.live_output_bytes, Some(publisher))
+    }
+
+    /// Note how long compiling the job took
+    pub fn record_compile(&self, elapsed: Duration) {
+        *self.compile_time.lock().unwrap() = Some(elapsed);
+    }
+
+    pub fn compile_time(&self) -> Option<Duration> {
+        *self.compile_time.lock().unwrap()
+    }
+
+    /// Clear progress from a previous attempt and restore its checkpoint, if any
+    pub async fn start(&self, conn: &mut ::redis::aio::ConnectionManager) {
+        let Some(job) = self.job else { return };
+        if let Err(e) = redis::reset_progress(conn, &job.id).await {
+            warn!(job_id = %job.id, error = %e, "Failed to reset job progress");
+        }
+        let _ = redis::publish_progress(conn, &job.id, ProgressEvent::Running).await;
+        if self.checkpoint.is_none() {
+            return;
+        }
+
+        let restored = self.restore_checkpoint(conn, job).await;
+        if restored.is_empty() {
+            return;
+        }
+        info!(
+            job_id = %job.id,
+            attempt = job.metadata.attempts,
+            restored_tests = restored.len(),
+            "Resuming from checkpoint"
+        );
+        for output in &restored {
+            if let Some(entry) = self.entry_for(output) {
+                let _ = redis::push_progress(conn, &job.id, &entry).await;
+            }
+        }
+        self.state.lock().unwrap().restored = restored
+            .into_iter()
+            .map(|o| (o.test_id, o))
+            .collect();
+    }
+
+    /// Find the most recent earlier attempt's checkpoint and carry it forward
+    /// into this attempt's key, so a chain of failed attempts never loses work
+    async fn restore_checkpoint(
+        &self,
+        conn: &mut ::redis::aio::ConnectionManager,
+        job: &JobRequest,
+    ) -> Vec<TestExecutionOutput> {
+        let attempt = job.metadata.attempts;
+        for previous in (0..attempt).rev() {
+            let entries = match redis::load_checkpoint(conn, &job.id, previous).await {
+                Ok(entries) if !entries.is_empty() => entries,
+                Ok(_) => continue,
+                Err(e) => {
+                    warn!(job_id = %job.id, attempt = previous, error = %e, "Failed to load checkpoint");
+                    continue;
+                }
+            };
+
+            if let Err(e) = redis::append_checkpoint(conn, &job.id, attempt, &entries).await {
+                warn!(job_id = %job.id, error = %e, "Failed to carry checkpoint forward");
+            }
+            let _ = redis::delete_checkpoints(conn, &job.id, previous).await;
+
+            return entries
+                .iter()
+                .filter_map(|entry| serde_json::from_str(entry).ok())
+                .collect();
+        }
+        Vec::new()
+    }
+
+    /// Output restored from a checkpoint for this test, if it already ran
+    pub fn restored(&self, test_id: u32) -> Option<TestExecutionOutput> {
+        self.state.lock().unwrap().restored.get(&test_id).cloned()
+    }
+
+    /// All outputs in test order, if the checkpoint covers every test
+    pub fn restored_all(&self, test_cases: &[TestCase]) -> Option<Vec<TestExecutionOutput>> {
+        test_cases.iter().map(|tc| self.restored(tc.id)).collect()
+    }
+
+    /// Announce that the job's compile step started
+    pub async fn compiling(&self, conn: Option<&mut ::redis::aio::ConnectionManager>) {
+        let (Some(conn), Some(job)) = (conn, self.job) else { return };
+        let _ = redis::publish_progress(conn, &job.id, ProgressEvent::Compiling).await;
+    }
+
+    /// Publish the verdict for one finished test and checkpoint when due
+    pub async fn record(&self, conn: Option<&mut ::redis::aio::ConnectionManager>, output: &TestExecutionOutput) {
+        let (Some(conn), Some(job), Some(entry)) = (conn, self.job, self.entry_for(output)) else { return };
+        if let Err(e) = redis::push_progress(conn, &job.id, &entry).await {
+            warn!(job_id = %job.id, test_id = output.test_id, error = %e, "Failed to publish test progress");
+        }
+
+        let Some(policy) = self.checkpoint else { return };
+        let due = {
+            let mut state = self.state.lock().unwrap();
+            state.pending.push(compact(output));
+            if state.pending.len() >= policy.every_tests {
+                std::mem::take(&mut state.pending)
+            } else {
+                Vec::new()
+            }
+        };
+        if !due.is_empty() {
+            self.flush(conn, job, due).await;
+        }
+    }
+
+    async fn flush(
+        &self,
+        conn: &mut ::redis::aio::ConnectionManager,
+        job: &JobRequest,
+        outputs: Vec<TestExecutionOutput>,
+    ) {
+        let entries: Vec<String> = outputs
+            .iter()
+            .filter_map(|o| serde_json::to_string(o).ok())
+            .collect();
+        if let Err(e) = redis::append_checkpoint(conn, &job.id, job.metadata.attempts, &entries).await {
+            // A missed checkpoint only means a retry reruns more tests
+            warn!(job_id = %job.id, error = %e, "Failed to write checkpoint");
+        }
+    }
+
+    fn entry_for(&self, output: &TestExecutionOutput) -> Option<TestProgress> {
+        let job = self.job?;
+        let test_case = job.test_cases.iter().find(|tc| tc.id == output.test_id)?;
+        let result = evaluator::evaluate_test(output, test_case, &self.options);
+        Some(TestProgress {
+            test_id: result.test_id,
+            status: result.status,
+            execution_time_ms: result.execution_time_ms,
+            total_tests: job.test_cases.len(),
+        })
+    }
+}
+
+/// Publishes the start of a running test's output as `output` events
+pub struct LiveOutput {
+    tx: Option<mpsc::UnboundedSender<ProgressEvent>>,
+    publisher: Option<JoinHandle<()>>,
+    test_id: u32,
+    /// Bytes still to publish, over both streams
+    remaining: usize,
+    /// The start of a character split across chunks, per stream
+    partial: [Vec<u8>; 2],
+}
+
+impl LiveOutput {
+    fn new(tx: mpsc::UnboundedSender<ProgressEvent>, test_id: u32, budget: usize, publisher: Option<JoinHandle<()>>) -> Self {
+        Self { tx: Some(tx), publisher, test_id, remaining: budget, partial: Default::default() }
+    }
+
+    /// A tee that publishes nothing
+    pub fn off() -> Self {
+        Self { tx: None, publisher: None, test_id: 0, remaining: 0, partial: Default::default() }
+    }
+
+    /// Publish what the program just wrote, while the budget lasts
+    pub fn push(&mut self, stream: OutputStream, bytes: &[u8]) {
+        let Some(tx) = &self.tx else { return };
+        let take = bytes.len().min(self.remaining);
+        if take == 0 {
+            return;
+        }
+        self.remaining -= take;
+
+        let partial = &mut self.partial[stream as usize];
+        partial.extend_from_slice(&bytes[..take]);
+        // Hold back a character cut off at the end of the chunk until the rest arrives
+        let complete = match std::str::from_utf8(partial) {
+            Ok(_) => partial.len(),
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(_) => partial.len(),
+        };
+        if complete == 0 {
+            return;
+        }
+        let data = String::from_utf8_lossy(&partial[..complete]).into_owned();
+        partial.drain(..complete);
+        let _ = tx.send(ProgressEvent::Output(OutputChunk { test_id: self.test_id, stream, data }));
+    }
+
+    /// Wait until everything pushed is published, so the test's verdict comes after it
+    pub async fn finish(mut self) {
+        self.tx = None;
+        if let Some(publisher) = self.publisher.take() {
+            let _ = publisher.await;
+        }
+    }
+}
+
+/// Drop raw bytes that are identical to the decoded text (the evaluator falls back
+/// to the text), so text-mode checkpoints don't store every output twice
+fn compact(output: &TestExecutionOutput) -> TestExecutionOutput {
+    let mut output = output.clone();
+    if output.stdout_bytes.as_deref() == Some(output.stdout.as_bytes()) {
+        output.stdout_bytes = None;
+    }
+    if output.stderr_bytes.as_deref() == Some(output.stderr.as_bytes()) {
+        output.stderr_bytes = None;
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use optimus_common::types::{Language, TestCase, TestStatus};
+    use uuid::Uuid;
+
+    fn output(test_id: u32, stdout: &str) -> TestExecutionOutput {
+        TestExecutionOutput {
+            test_id,
+            stdout: stdout.to_string(),
+            stderr: String::new(),
+            execution_time_ms: 7,
+            timed_out: false,
+            runtime_error: false,
+            compilation_failed: false,
+            stdout_bytes: None,
+            stderr_bytes: None,
+            stdout_total_bytes: None,
+            stderr_total_bytes: None,
+            overhead_ms: None,
+            cpu_time_ms: None,
+            memory_kb: None,
+            resource_samples: None,
+            oom_killed: false,
+            failure: None,
+            checker: None,
+        }
+    }
+
+    fn job() -> JobRequest {
+        JobRequest {
+            id: Uuid::new_v4(),
+            language: Language::Python,
+            source_code: "print(input())".to_string(),
+            test_cases: vec![
+                TestCase { id: 1, input: "a".to_string(), expected_output: "a".to_string(), weight: 1, ..Default::default() },
+                TestCase { id: 2, input: "b".to_string(), expected_output: "b".to_string(), weight: 1, ..Default::default() },
+            ],
+            timeout_ms: 1000,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_entry_carries_verdict_and_total() {
+        let job = job();
+        let reporter = ProgressReporter::new(&job);
+
+        let passed = reporter.entry_for(&output(1, "a")).unwrap();
+        assert_eq!(passed.status, TestStatus::Passed);
+        assert_eq!(passed.total_tests, 2);
+        assert_eq!(passed.execution_time_ms, 7);
+
+        let failed = reporter.entry_for(&output(2, "wrong")).unwrap();
+        assert_eq!(failed.status, TestStatus::Failed);
+    }
+
+    #[test]
+    fn test_checkpoint_policy_threshold() {
+        let job = job();
+        let small = CheckpointPolicy { min_tests: 3, every_tests: 1 };
+        assert!(ProgressReporter::with_policy(&job, small).checkpoint.is_none());
+
+        let enabled = CheckpointPolicy { min_tests: 2, every_tests: 1 };
+        assert_eq!(ProgressReporter::with_policy(&job, enabled).checkpoint, Some(enabled));
+
+        let disabled = CheckpointPolicy { min_tests: 0, every_tests: 1 };
+        assert!(ProgressReporter::with_policy(&job, disabled).checkpoint.is_none());
+    }
+
+    #[test]
+    fn test_restored_all_requires_every_test() {
+        let job = job();
+        let reporter = ProgressReporter::new(&job);
+        assert!(reporter.restored_all(&job.test_cases).is_none());
+
+        reporter.state.lock().unwrap().restored.insert(1, output(1, "a"));
+        assert!(reporter.restored(1).is_some());
+        assert!(reporter.restored_all(&job.test_cases).is_none());
+
+        reporter.state.lock().unwrap().restored.insert(2, output(2, "b"));
+        let all = reporter.restored_all(&job.test_cases).unwrap();
+        assert_eq!(all.iter().map(|o| o.test_id).collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_compact_drops_redundant_bytes() {
+        let mut text = output(1, "héllo");
+        text.stdout_bytes = Some("héllo".as_bytes().to_vec());
+        assert!(compact(&text).stdout_bytes.is_none());
+
+        let mut binary = output(1, "\u{FFFD}");
+        binary.stdout_bytes = Some(vec![0xff]);
+        assert_eq!(compact(&binary).stdout_bytes, Some(vec![0xff]));
+
+        // Roundtrips through the checkpoint encoding
+        let json = serde_json::to_string(&compact(&binary)).unwrap();
+        let restored: TestExecutionOutput = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.stdout_bytes, Some(vec![0xff]));
+    }
+
+    #[test]
+    fn test_live_output_budget_and_split_characters() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut live = LiveOutput::new(tx, 4, 8, None);
+        let euro = "€".as_bytes();
+        live.push(OutputStream::Stdout, &[b'a', euro[0]]);
+        live.push(OutputStream::Stderr, b"err");
+        live.push(OutputStream::Stdout, &euro[1..]);
+        // Over budget: cut at 8 bytes in all
+        live.push(OutputStream::Stdout, b"xyz");
+
+        let mut sent = Vec::new();
+        while let Ok(ProgressEvent::Output(chunk)) = rx.try_recv() {
+            assert_eq!(chunk.test_id, 4);
+            sent.push((chunk.stream, chunk.data));
+        }
+        assert_eq!(
+            sent,
+            [
+                (OutputStream::Stdout, "a".to_string()),
+                (OutputStream::Stderr, "err".to_string()),
+                (OutputStream::Stdout, "€".to_string()),
+                (OutputStream::Stdout, "x".to_string()),
+            ]
+        );
+
+        let mut off = LiveOutput::off();
+        off.push(OutputStream::Stdout, b"nothing");
+    }
+
+    #[test]
+    fn test_unknown_test_and_disabled_reporter_publish_nothing() {
+        let job = job();
+        assert!(ProgressReporter::new(&job).entry_for(&output(99, "a")).is_none());
+        assert!(ProgressReporter::disabled().entry_for(&output(1, "a")).is_none());
+    }
+}
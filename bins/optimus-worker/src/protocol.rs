@@ -0,0 +1,286 @@
+//! Runner Protocol v2 - Structured Test Reports
+//!
+//! **Core Responsibility:**
+//! Define what the in-container runner reports about each test run, and parse it.
+//!
+//! Every test command runs as root, feeds the input from a root-owned file, and runs the
+//! program as the image's unprivileged user (uid 1000, through `setpriv`) under bash
+//! `time` (and GNU time for peak memory, when the image has it), with a soft CPU limit
+//! (`cpu_limit_secs`). It then writes a JSON report to a file under `/run/optimus`, a
+//! directory only root can read:
+//!
+//! ```text
+//! {"exit_code":0,"wall_us":10423,"user_ms":8,"sys_ms":1,"max_rss_kb":9212,"peak_kb":10240,"oom_kills":0}
+//! ```
+//!
+//! The engine reads the report with a second exec and takes verdict inputs from it instead
+//! of inferring them from the exec's exit status and wall-clock time. The program never
+//! sees the file, so it cannot report on itself; the engine still drops a report whose exit
+//! code disagrees with the exec's and caps its times at what it measured itself (see
+//! `RunnerReport::checked`). `wall_us` is timed around the program only, so exec setup is
+//! reported as `overhead_ms` rather than charged to the user. `user_ms` and `sys_ms` are how
+//! far the cgroup's `cpu.stat` counters moved while the program ran, so they count every
+//! process and thread it started; bash `time` stands in where cgroup v2 isn't mounted.
+//! `oom_kills` is how far the
+//! cgroup's OOM-kill counter moved while the program ran, so a memory kill is told apart
+//! from any other SIGKILL. `peak_kb` is the cgroup's `memory.peak` after the run; the tests
+//! of a job share a container, so it is only reported when this program set a new peak.
+//!
+//! A missing or rejected report (image without bash 5, or a run killed on timeout) falls
+//! back to the exec exit code and wall-clock time.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Root-only directory the runner keeps its files in
+pub const REPORT_DIR: &str = "/run/optimus";
+
+/// Test command template; `__INPUT_B64__`, `__PROGRAM__`, `__CPU_SECS__` and `__REPORT__`
+/// are filled in per test
+const SCRIPT: &str = r#"__d=/run/optimus; mkdir -p -m 700 $__d
+echo '__INPUT_B64__' | base64 -d > $__d/input
+command -v setpriv >/dev/null || { echo 'runner: setpriv is missing from the image' >&2; exit 125; }
+__T=; /usr/bin/time --version 2>&1 | grep -q GNU && __T="/usr/bin/time -f %M -o $__d/rss"
+rm -f $__d/rss
+__oom() { sed -n 's/^oom_kill //p' /sys/fs/cgroup/memory.events /sys/fs/cgroup/memory/memory.oom_control 2>/dev/null | head -n 1; }
+__peak() { cat /sys/fs/cgroup/memory.peak /sys/fs/cgroup/memory/memory.max_usage_in_bytes 2>/dev/null | head -n 1; }
+__cpu() { local k v u= s=; while read -r k v; do case $k in user_usec) u=$v ;; system_usec) s=$v ;; esac; done 2>/dev/null < /sys/fs/cgroup/cpu.stat; echo "$u:$s"; }
+__k0=$(__oom); __p0=$(__peak)
+TIMEFORMAT='%3U %3S'
+ulimit -St __CPU_SECS__
+__c0=$(__cpu)
+__s=${EPOCHREALTIME/[.,]/}
+{ time $__T setpriv --reuid=1000 --regid=1000 --clear-groups __PROGRAM__ < $__d/input 2>&3 ; } 3>&2 2>$__d/cpu
+__rc=$?
+__e=${EPOCHREALTIME/[.,]/}
+__c1=$(__cpu)
+__w=null; [ -n "$__s" ] && __w=$((__e - __s))
+__k1=$(__oom); __k=null
+case "$__k0:$__k1" in :*|*:|*[!0-9:]*) ;; *) __k=$((__k1 - __k0)) ;; esac
+__p1=$(__peak); __p=null
+case "$__p0:$__p1" in :*|*:|*[!0-9:]*) ;; *) [ "$__p1" -gt "$__p0" ] && __p=$((__p1 / 1024)) ;; esac
+read -r __u __y <<< "$(tail -n 1 $__d/cpu)"
+__u=${__u/[.,]/}; __y=${__y/[.,]/}
+case "$__u$__y" in ''|*[!0-9]*) __u=null; __y=null ;; *) __u=$((10#$__u)); __y=$((10#$__y)) ;; esac
+case "$__c0:$__c1" in :*|*::*|*:|*[!0-9:]*) ;; *) __u=$(((${__c1%:*} - ${__c0%:*}) / 1000)); __y=$(((${__c1#*:} - ${__c0#*:}) / 1000)) ;; esac
+__m=$(tail -n 1 $__d/rss 2>/dev/null)
+case "$__m" in ''|*[!0-9]*) __m=null ;; esac
+printf '{"exit_code":%d,"wall_us":%s,"user_ms":%s,"sys_ms":%s,"max_rss_kb":%s,"peak_kb":%s,"oom_kills":%s}\n' "$__rc" "$__w" "$__u" "$__y" "$__m" "$__p" "$__k" > $__d/__REPORT__
+exit $__rc"#;
+
+/// What the runner reports about one test run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunnerReport {
+    pub exit_code: i32,
+    /// Program wall-clock time in microseconds (None without bash 5 `$EPOCHREALTIME`)
+    pub wall_us: Option<u64>,
+    pub user_ms: Option<u64>,
+    pub sys_ms: Option<u64>,
+    /// Peak resident memory (None when the image has no GNU time)
+    pub max_rss_kb: Option<u64>,
+    /// The cgroup's memory peak, when the program raised it (None when an earlier test in
+    /// the container peaked higher, or the cgroup can't be read)
+    #[serde(default)]
+    pub peak_kb: Option<u64>,
+    /// Processes the kernel killed for exceeding the container's memory limit while the
+    /// program ran, from the cgroup's counters (None where they can't be read)
+    #[serde(default)]
+    pub oom_kills: Option<u64>,
+}
+
+impl RunnerReport {
+    /// CPU time (user + sys) in milliseconds
+    pub fn cpu_time_ms(&self) -> Option<u64> {
+        Some(self.user_ms? + self.sys_ms?)
+    }
+
+    /// Peak memory in KiB: the cgroup's peak when the program set it, else its resident peak
+    pub fn memory_kb(&self) -> Option<u64> {
+        self.peak_kb.or(self.max_rss_kb)
+    }
+
+    /// The report, checked against what the engine saw of the same run
+    ///
+    /// A report whose exit code differs from the exec's is dropped. Wall time is capped at
+    /// the engine's own `elapsed_ms`, and CPU time at what `cpus` CPUs could use in it.
+    pub fn checked(self, exit_code: Option<i64>, elapsed_ms: u64, cpus: f64) -> Option<Self> {
+        if exit_code != Some(self.exit_code as i64) {
+            return None;
+        }
+        let cpu_cap = (elapsed_ms as f64 * cpus.max(1.0)).ceil() as u64;
+        let user_ms = self.user_ms.map(|ms| ms.min(cpu_cap));
+        let sys_ms = self.sys_ms.map(|ms| ms.min(cpu_cap.saturating_sub(user_ms.unwrap_or(0))));
+        Some(Self {
+            wall_us: self.wall_us.map(|us| us.min(elapsed_ms.saturating_mul(1000))),
+            user_ms,
+            sys_ms,
+            ..self
+        })
+    }
+}
+
+/// Whether the memory limit killed the program
+///
+/// The cgroup's OOM-kill counter says so for certain; without it (cgroup v1 kernels before
+/// 4.13, or no report) a SIGKILL exit (137) is taken to be one.
+pub fn oom_killed(report: Option<RunnerReport>, exit_code: Option<i64>) -> bool {
+    match report.and_then(|r| r.oom_kills) {
+        Some(kills) => kills > 0,
+        None => exit_code == Some(137),
+    }
+}
+
+/// Extra wall-clock time allowed beyond the time limit for exec overhead
+///
+/// The time limit itself is enforced against program time; this only bounds how long
+/// the engine waits before killing the test (`EXEC_OVERHEAD_GRACE_MS`, default 500).
+pub fn overhead_grace() -> Duration {
+    let ms = std::env::var("EXEC_OVERHEAD_GRACE_MS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(500);
+    Duration::from_millis(ms)
+}
+
+/// Wall-clock ceiling of a test, in milliseconds
+///
+/// The time limit applies to CPU time, so a program that waits (on I/O, a sleep, a
+/// deadlock) would never reach it; it is stopped after `WALL_TIME_FACTOR` (default 2,
+/// at least 1) times the limit instead.
+pub fn wall_limit_ms(timeout_ms: u64) -> u64 {
+    let factor = std::env::var("WALL_TIME_FACTOR")
+        .ok()
+        .and_then(|v| v.parse::<f64>().ok())
+        .filter(|f| f.is_finite())
+        .unwrap_or(2.0)
+        .max(1.0);
+    (timeout_ms as f64 * factor).min(u64::MAX as f64) as u64
+}
+
+/// Whether a run went over its time limit
+///
+/// CPU time is held to the limit and wall time to the ceiling, so scheduling noise on a
+/// busy worker doesn't turn into TLE; without a CPU measurement, wall time is held to the
+/// limit as before. A program with helper threads (a JVM's GC and JIT) can use more CPU
+/// than wall time, so it is held to the smaller of the two and never judged more strictly
+/// than on wall time.
+pub fn over_time_limit(timeout_ms: u64, wall_limit_ms: u64, cpu_time_ms: Option<u64>, wall_time_ms: u64) -> bool {
+    match cpu_time_ms {
+        Some(cpu_time_ms) => cpu_time_ms.min(wall_time_ms) > timeout_ms || wall_time_ms > wall_limit_ms,
+        None => wall_time_ms > timeout_ms,
+    }
+}
+
+/// CPU seconds the runner lets a test's processes use (`ulimit -St`)
+///
+/// A CPU-bound program is stopped (SIGXCPU, exit 152) soon after its time limit instead of
+/// running on to the wall-clock ceiling. The limit is per process and whole seconds, so it
+/// is what `cpus` CPUs could use in the time limit, plus a second. Only the soft limit is
+/// set, so the kill is never a SIGKILL mistaken for a memory kill.
+pub fn cpu_limit_secs(timeout_ms: u64, cpus: f64) -> u64 {
+    (timeout_ms as f64 * cpus.max(1.0) / 1000.0).ceil().min(u64::MAX as f64) as u64 + 1
+}
+
+/// Shell command that feeds the test input to `program` and writes its report as
+/// `report` in `REPORT_DIR`
+///
+/// `program` must be a simple command (no subshells), so GNU time can wrap it. The exec
+/// must run as root.
+pub fn test_command(encoded_input: &str, program: &str, cpu_secs: u64, report: &str) -> String {
+    SCRIPT
+        .replace("__INPUT_B64__", encoded_input)
+        .replace("__PROGRAM__", program)
+        .replace("__CPU_SECS__", &cpu_secs.to_string())
+        .replace("__REPORT__", report)
+}
+
+/// Shell command that runs `program` on the exec's own stdin and writes its report
+///
+/// For interactive tests, whose input is the interactor's replies rather than a file.
+pub fn interactive_command(program: &str, cpu_secs: u64, report: &str) -> String {
+    SCRIPT
+        .replace("echo '__INPUT_B64__' | base64 -d > $__d/input\n", "")
+        .replace(" < $__d/input", "")
+        .replace("__PROGRAM__", program)
+        .replace("__CPU_SECS__", &cpu_secs.to_string())
+        .replace("__REPORT__", report)
+}
+
+/// Shell command that prints a report and removes it
+pub fn take_report_command(report: &str) -> String {
+    format!("cat {dir}/{report} 2>/dev/null; rm -f {dir}/{report}", dir = REPORT_DIR, report = report)
+}
+
+/// Parse what `take_report_command` printed
+pub fn parse_report(printed: &str) -> Option<RunnerReport> {
+    serde_json::from_str(printed.trim()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_shape() {
+        let cmd = test_command("aGk=", "/code/main", 3, "r1");
+        assert!(cmd.contains("echo 'aGk=' | base64 -d > $__d/input\n"));
+        assert!(cmd.contains("ulimit -St 3\n"));
+        assert!(cmd.contains("{ time $__T setpriv --reuid=1000 --regid=1000 --clear-groups /code/main < $__d/input 2>&3 ; }"));
+        assert!(cmd.contains("> $__d/r1\nexit $__rc"));
+        assert_eq!(take_report_command("r1"), "cat /run/optimus/r1 2>/dev/null; rm -f /run/optimus/r1");
+    }
+
+    #[test]
+    fn test_cpu_time_against_the_limit() {
+        // Waiting on I/O: little CPU, within the wall ceiling
+        assert!(!over_time_limit(1000, 2000, Some(120), 1500));
+        // Busy: over on CPU time
+        assert!(over_time_limit(1000, 2000, Some(1010), 1020));
+        // Idle past the ceiling
+        assert!(over_time_limit(1000, 2000, Some(5), 2001));
+        // Helper threads: more CPU than wall time, judged on wall time
+        assert!(!over_time_limit(1000, 2000, Some(1800), 900));
+        // No CPU measurement: wall time against the limit
+        assert!(over_time_limit(1000, 2000, None, 1001));
+        assert!(wall_limit_ms(1000) >= 1000);
+        assert_eq!(cpu_limit_secs(1000, 1.0), 2);
+        assert_eq!(cpu_limit_secs(1500, 2.0), 4);
+        assert_eq!(cpu_limit_secs(200, 0.5), 2);
+    }
+
+    #[test]
+    fn test_interactive_command_reads_stdin() {
+        let cmd = interactive_command("/code/main", 3, "r1");
+        assert!(!cmd.contains("$__d/input"));
+        assert!(cmd.contains("{ time $__T setpriv --reuid=1000 --regid=1000 --clear-groups /code/main 2>&3 ; }"));
+        assert!(cmd.ends_with("exit $__rc"));
+    }
+
+    #[test]
+    fn test_parse_and_check_report() {
+        let printed = "{\"exit_code\":1,\"wall_us\":12345,\"user_ms\":9,\"sys_ms\":2,\"max_rss_kb\":null}\n";
+        let report = parse_report(printed).unwrap();
+        assert_eq!(report.exit_code, 1);
+        assert_eq!(report.wall_us, Some(12345));
+        assert_eq!(report.cpu_time_ms(), Some(11));
+        assert_eq!(report.max_rss_kb, None);
+        assert_eq!(report.peak_kb, None);
+        assert_eq!(RunnerReport { max_rss_kb: Some(900), ..report }.memory_kb(), Some(900));
+        assert_eq!(RunnerReport { max_rss_kb: Some(900), peak_kb: Some(1200), ..report }.memory_kb(), Some(1200));
+        assert_eq!(parse_report(""), None);
+
+        // The cgroup counter decides over the exit code
+        assert!(!oom_killed(Some(report), Some(1)));
+        assert!(oom_killed(Some(RunnerReport { oom_kills: Some(1), ..report }), Some(137)));
+        assert!(!oom_killed(Some(RunnerReport { oom_kills: Some(0), ..report }), Some(137)));
+        assert!(oom_killed(None, Some(137)));
+
+        // Checked against the exec: same exit code, times within what the engine measured
+        assert_eq!(report.checked(Some(1), 50, 1.0), Some(report));
+        assert_eq!(report.checked(Some(0), 50, 1.0), None);
+        assert_eq!(report.checked(None, 50, 1.0), None);
+        let capped = report.checked(Some(1), 10, 1.0).unwrap();
+        assert_eq!(capped.wall_us, Some(10_000));
+        assert_eq!((capped.user_ms, capped.sys_ms), (Some(9), Some(1)));
+    }
+}
@@ -0,0 +1,130 @@
+//! Runtime Info - Toolchain Version Probe
+//!
+//! **Core Responsibility:**
+//! Report exactly which compiler/interpreter versions judge submissions, by running
+//! `--version` probes inside the worker's own judge image.
+//!
+//! **Caching:**
+//! The result is stored at `optimus:runtime:{language}` together with the Docker image id.
+//! A worker starting on an image that was already probed reuses the cached report,
+//! so the probe runs once per image rather than once per worker.
+//!
+//! Best-effort: a failed probe is logged and never stops the worker.
+
+use crate::engine::SharedEngine;
+use optimus_common::redis;
+use optimus_common::types::{Language, RuntimeInfo};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Upper bound for the whole probe script
+const PROBE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Tools reported for each language, with the command that prints their version
+fn probe_commands(language: &Language) -> &'static [(&'static str, &'static str)] {
+    match language {
+        Language::Python => &[("python", "python3 --version")],
+        Language::Java => &[("java", "java -version"), ("javac", "javac -version")],
+        Language::Rust => &[("rustc", "rustc --version")],
+        Language::Kotlin => &[("java", "java -version"), ("kotlinc", "kotlinc -version")],
+        Language::C => &[("gcc", "gcc --version")],
+        Language::Php => &[("php", "php --version")],
+        Language::Ruby => &[("ruby", "ruby --version")],
+        Language::Asm => &[("nasm", "nasm -v"), ("ld", "ld --version")],
+        Language::Scala => &[("java", "java -version"), ("scalac", "scalac -version")],
+    }
+}
+
+/// Shell script printing one `tool<TAB>first line of version output` line per tool
+fn probe_script(language: &Language) -> String {
+    probe_commands(language)
+        .iter()
+        .map(|(tool, cmd)| format!("printf '%s\\t' {}; {} 2>&1 | head -n 1", tool, cmd))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Parse probe output; tools that printed nothing (not installed) are left out
+fn parse_probe_output(output: &str) -> BTreeMap<String, String> {
+    output
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(tool, version)| (tool.trim().to_string(), version.trim().to_string()))
+        .filter(|(tool, version)| !tool.is_empty() && !version.is_empty())
+        .collect()
+}
+
+/// Probe the judge image (unless its report is cached) and publish the result
+pub async fn publish(
+    mut redis_conn: ::redis::aio::ConnectionManager,
+    engine: SharedEngine,
+    language: Language,
+    worker_id: String,
+) {
+    let engine = match engine.healthy().await {
+        Ok(engine) => engine,
+        Err(e) => {
+            warn!(error = %e, "Skipping runtime probe: Docker unavailable");
+            return;
+        }
+    };
+
+    let (image, image_id) = match engine.image_identity(&language).await {
+        Ok(identity) => identity,
+        Err(e) => {
+            warn!(error = %e, "Skipping runtime probe: judge image unavailable");
+            return;
+        }
+    };
+
+    if let Ok(Some(cached)) = redis::get_runtime_info(&mut redis_conn, &language).await {
+        if cached.image == image && cached.image_id.is_some() && cached.image_id == image_id {
+            info!("Runtime info for {} already probed for this image", language);
+            return;
+        }
+    }
+
+    let output = match engine.run_image_script(&language, &probe_script(&language), PROBE_TIMEOUT).await {
+        Ok(output) => output,
+        Err(e) => {
+            warn!(error = %e, "Runtime probe failed");
+            return;
+        }
+    };
+
+    let info = RuntimeInfo {
+        language,
+        image,
+        image_id,
+        toolchain: parse_probe_output(&output),
+        probed_at: chrono::Utc::now(),
+        probed_by: worker_id,
+    };
+    match redis::store_runtime_info(&mut redis_conn, &info).await {
+        Ok(()) => info!("Published runtime info for {}: {:?}", language, info.toolchain),
+        Err(e) => warn!(error = %e, "Failed to publish runtime info"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_probe_script_covers_every_tool() {
+        let script = probe_script(&Language::Java);
+        assert!(script.contains("printf '%s\\t' java; java -version 2>&1 | head -n 1"));
+        assert!(script.contains("printf '%s\\t' javac; javac -version 2>&1 | head -n 1"));
+    }
+
+    #[test]
+    fn test_parse_probe_output() {
+        let output = "java\topenjdk version \"17.0.2\" 2022-01-18\njavac\tjavac 17.0.2\r\nmissing\t\nnoise\n";
+        let toolchain = parse_probe_output(output);
+
+        assert_eq!(toolchain.len(), 2);
+        assert_eq!(toolchain["java"], "openjdk version \"17.0.2\" 2022-01-18");
+        assert_eq!(toolchain["javac"], "javac 17.0.2");
+    }
+}
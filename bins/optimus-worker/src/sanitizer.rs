@@ -0,0 +1,132 @@
+//! Sanitizers - Undefined Behaviour Detection for C
+//!
+//! **Core Responsibility:**
+//! Build a C job's program with the gcc sanitizers it asks for, and pick the sanitizer's
+//! report out of a failed test's stderr.
+//!
+//! A C program that reads past an array or overflows a signed int often exits 0 with a
+//! plausible answer. A job with `"sanitizers": ["address", "undefined"]` is compiled with
+//! `-fsanitize=address,undefined`, so the first such error stops the program with a
+//! report on stderr: the test is a runtime error and the report is returned with it as
+//! `sanitizer_report`.
+//!
+//! - UBSan is built with `-fno-sanitize-recover`: by default it reports and carries on,
+//!   and the exit code would never show the error
+//! - Leak detection is off: LeakSanitizer needs ptrace, which the sandbox forbids, and a
+//!   leak isn't a wrong answer
+//!
+//! Sanitized jobs run in compile-once containers, like jobs with a checker; the checker
+//! itself is built without sanitizers.
+
+use optimus_common::language_config::CompileSpec;
+use optimus_common::types::{JobRequest, Language, Sanitizer};
+use std::borrow::Cow;
+
+/// Added to the compile command with the `-fsanitize` list
+const COMPILE_FLAGS: &str = "-fno-sanitize-recover=all -fno-omit-frame-pointer -g";
+
+/// Runtime options, set on the program's command line (pooled containers carry no job env)
+const RUNTIME_ENV: &str = "env ASAN_OPTIONS=detect_leaks=0 UBSAN_OPTIONS=print_stacktrace=1";
+
+/// Lines that start a sanitizer's report
+const REPORT_MARKERS: &[&str] = &[
+    "ERROR: AddressSanitizer",
+    "ERROR: UndefinedBehaviorSanitizer",
+    ": runtime error: ",
+];
+
+/// Report kept per test
+const MAX_REPORT_BYTES: usize = 4096;
+
+/// The job's sanitizers; none unless the job is C
+pub fn active(job: &JobRequest) -> &[Sanitizer] {
+    match job.language {
+        Language::C => &job.sanitizers,
+        _ => &[],
+    }
+}
+
+/// The language's compile step, instrumented with the job's sanitizers
+pub fn compile(compile: CompileSpec, job: &JobRequest) -> CompileSpec {
+    let sanitizers = active(job);
+    if sanitizers.is_empty() {
+        return compile;
+    }
+    let names: Vec<&str> = sanitizers.iter().map(Sanitizer::as_str).collect();
+    CompileSpec {
+        command: format!("{} -fsanitize={} {}", compile.command, names.join(","), COMPILE_FLAGS),
+        ..compile
+    }
+}
+
+/// Command running the job's program, with the sanitizers' runtime options
+pub fn program(program: &'static str, job: &JobRequest) -> Cow<'static, str> {
+    if active(job).is_empty() {
+        Cow::Borrowed(program)
+    } else {
+        Cow::Owned(format!("{} {}", RUNTIME_ENV, program))
+    }
+}
+
+/// The sanitizer's report in a test's stderr: from the line that starts it to the end
+pub fn report(stderr: &str) -> Option<String> {
+    let found = REPORT_MARKERS.iter().filter_map(|marker| stderr.find(marker)).min()?;
+    let start = stderr[..found].rfind('\n').map_or(0, |newline| newline + 1);
+    let mut report = stderr[start..].trim_end().to_string();
+    if report.len() > MAX_REPORT_BYTES {
+        let mut end = MAX_REPORT_BYTES;
+        while !report.is_char_boundary(end) {
+            end -= 1;
+        }
+        report.truncate(end);
+        report.push('…');
+    }
+    Some(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(language: &str, sanitizers: &[&str]) -> JobRequest {
+        serde_json::from_value(serde_json::json!({
+            "id": uuid::Uuid::new_v4(),
+            "language": language,
+            "source_code": "int main(void) { return 0; }",
+            "test_cases": [{"id": 1, "input": "", "expected_output": "", "weight": 1}],
+            "timeout_ms": 1000,
+            "sanitizers": sanitizers,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_only_c_jobs_are_instrumented() {
+        let gcc = || CompileSpec::new("gcc /code/main.c -o /code/main");
+
+        let sanitized = job("c", &["address", "undefined"]);
+        assert_eq!(
+            compile(gcc(), &sanitized).command,
+            "gcc /code/main.c -o /code/main -fsanitize=address,undefined -fno-sanitize-recover=all -fno-omit-frame-pointer -g"
+        );
+        assert!(program("/code/main", &sanitized).ends_with(" /code/main"));
+        assert!(program("/code/main", &sanitized).starts_with("env ASAN_OPTIONS=detect_leaks=0"));
+
+        assert_eq!(compile(gcc(), &job("c", &[])).command, gcc().command);
+        assert_eq!(program("/code/main", &job("c", &[])), "/code/main");
+        assert!(active(&job("python", &["address"])).is_empty());
+    }
+
+    #[test]
+    fn test_report_starts_at_the_error() {
+        let asan = "partial output\n=================================================================\n==12==ERROR: AddressSanitizer: heap-buffer-overflow on address 0x602000000014\n    #0 0x401 in main /code/main.c:5\n";
+        let asan_report = report(asan).unwrap();
+        assert!(asan_report.starts_with("==12==ERROR: AddressSanitizer: heap-buffer-overflow"));
+        assert!(asan_report.ends_with("main.c:5"));
+
+        let ubsan = "/code/main.c:3:14: runtime error: signed integer overflow: 2147483647 + 1 cannot be represented in type 'int'\n";
+        assert!(report(ubsan).unwrap().starts_with("/code/main.c:3:14: runtime error: signed integer overflow"));
+
+        assert_eq!(report("Segmentation fault\n"), None);
+    }
+}
@@ -0,0 +1,99 @@
+//! Sandbox Self-Test
+//!
+//! **Core Responsibility:**
+//! Check that the judge sandbox works and is still isolated: a throwaway container of the
+//! worker's image must run a script, return its output and see no network interface
+//! besides loopback.
+//!
+//! Runs at startup, then every SANDBOX_SELF_TEST_INTERVAL_SECS (default 0 = startup only).
+//! A failure is logged and published as a `sandbox_self_test_failed` event, which the API's
+//! notifier turns into an alert. The worker keeps running, since the fault may be transient.
+
+use crate::engine::SharedEngine;
+use optimus_common::redis;
+use optimus_common::types::{EventKind, Language, SystemEvent};
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+const SELF_TEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+const MARKER: &str = "optimus-selftest-ok";
+
+/// Print the marker, then the container's network interfaces on one line
+fn self_test_script() -> String {
+    format!("echo {}; ls /sys/class/net | tr '\\n' ' '", MARKER)
+}
+
+/// Check the script output; the error describes what is broken
+fn check_output(output: &str) -> Result<(), String> {
+    let mut lines = output.lines();
+    if lines.next().map(str::trim) != Some(MARKER) {
+        return Err(format!("sandbox did not return the expected output (got {:?})", output));
+    }
+    let interfaces: Vec<&str> = lines
+        .flat_map(str::split_whitespace)
+        .filter(|iface| *iface != "lo")
+        .collect();
+    if !interfaces.is_empty() {
+        return Err(format!("network isolation broken: sandbox sees interfaces {}", interfaces.join(", ")));
+    }
+    Ok(())
+}
+
+/// Run the self-test once
+pub async fn run(engine: &SharedEngine, language: &Language) -> Result<(), String> {
+    let engine = engine.healthy().await.map_err(|e| format!("Docker unavailable: {:#}", e))?;
+    let output = engine
+        .run_image_script(language, &self_test_script(), SELF_TEST_TIMEOUT)
+        .await
+        .map_err(|e| format!("sandbox failed to run: {:#}", e))?;
+    check_output(&output)
+}
+
+/// Run the self-test at startup and on the configured interval, publishing failures
+pub async fn self_test_loop(
+    mut redis_conn: ::redis::aio::ConnectionManager,
+    engine: SharedEngine,
+    language: Language,
+    worker_id: String,
+) {
+    let interval = std::env::var("SANDBOX_SELF_TEST_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|secs| *secs > 0)
+        .map(Duration::from_secs);
+
+    loop {
+        match run(&engine, &language).await {
+            Ok(()) => info!("Sandbox self-test passed"),
+            Err(reason) => {
+                error!(reason = %reason, "Sandbox self-test failed");
+                let event = SystemEvent::worker(EventKind::SandboxSelfTestFailed, &worker_id, language)
+                    .with_details(serde_json::json!({ "reason": reason }));
+                if let Err(e) = redis::publish_event(&mut redis_conn, &event).await {
+                    warn!(error = %e, "Failed to publish self-test failure");
+                }
+            }
+        }
+        match interval {
+            Some(interval) => tokio::time::sleep(interval).await,
+            None => return,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_output() {
+        assert!(check_output("optimus-selftest-ok\nlo ").is_ok());
+        assert!(check_output("optimus-selftest-ok\n").is_ok());
+
+        let leaked = check_output("optimus-selftest-ok\neth0 lo ").unwrap_err();
+        assert!(leaked.contains("eth0"));
+        assert!(check_output("bash: echo: not found").is_err());
+        assert!(check_output("").is_err());
+    }
+}
@@ -0,0 +1,315 @@
+//! Standalone Mode - API + Worker in One Process, No Redis
+//!
+//! **Core Responsibility:**
+//! Accept submissions over HTTP and judge them in the same process, for demos, local
+//! development and small self-hosted installs that don't want to run Redis.
+//!
+//! Jobs go through an in-memory channel queue; results are kept in memory, or in SQLite
+//! with OPTIMUS_STANDALONE_DB (see store.rs). One process serves every configured language,
+//! running up to MAX_PARALLEL_JOBS jobs at a time. Only Docker is required.
+//!
+//! Endpoints mirror the API: POST /execute, GET /job/{id}, GET /health. Everything that
+//! needs Redis - cancellation, progress, retries and the DLQ, result signing, admin and
+//! debug endpoints - is not available.
+//!
+//! Started with `optimus-worker --standalone` (or OPTIMUS_STANDALONE=true); listens on PORT
+//! (default 4001, the API's port).
+
+use crate::config::LanguageConfigManager;
+use crate::engine::SharedEngine;
+use crate::executor;
+use crate::store::{self, JobState, Store};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+    routing::{get, post},
+    Router,
+};
+use optimus_common::config::WorkerConfig;
+use optimus_common::types::{
+    ComparisonMode, EvaluationPipeline, GroupScoring, JobMetadata, JobRequest, Language, NormalizationOptions, OutputMode, ScoreScaling,
+    StatusPolicy, TestCase,
+};
+use optimus_common::validate;
+use serde::Deserialize;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::sync::{mpsc, Semaphore};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+/// Jobs waiting for a free slot before submissions are refused
+const QUEUE_CAPACITY: usize = 1000;
+
+/// Whether standalone mode was requested (`--standalone`, passed in as `flag`, or
+/// OPTIMUS_STANDALONE=true)
+pub fn enabled(flag: bool) -> bool {
+    flag || std::env::var("OPTIMUS_STANDALONE").is_ok_and(|v| v.eq_ignore_ascii_case("true") || v == "1")
+}
+
+/// Subset of the API's submit request (no idempotency keys or tenants)
+#[derive(Debug, Deserialize)]
+struct SubmitRequest {
+    language: Language,
+    source_code: String,
+    test_cases: Vec<TestCaseInput>,
+    #[serde(default = "default_timeout")]
+    timeout_ms: u64,
+    #[serde(default)]
+    output_mode: OutputMode,
+    #[serde(default)]
+    comparison: Option<ComparisonMode>,
+    #[serde(default)]
+    normalization: NormalizationOptions,
+    #[serde(default)]
+    evaluation: Option<EvaluationPipeline>,
+    #[serde(default)]
+    status_policy: Option<StatusPolicy>,
+    #[serde(default)]
+    scoring: Option<ScoreScaling>,
+    #[serde(default)]
+    group_scoring: GroupScoring,
+    #[serde(default)]
+    subtask_scoring: std::collections::BTreeMap<u32, GroupScoring>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TestCaseInput {
+    input: String,
+    expected_output: String,
+    #[serde(default = "default_weight")]
+    weight: u32,
+    #[serde(default)]
+    comparison: Option<ComparisonMode>,
+    #[serde(default)]
+    group_id: Option<u32>,
+}
+
+fn default_timeout() -> u64 {
+    5000
+}
+
+fn default_weight() -> u32 {
+    10
+}
+
+impl SubmitRequest {
+    fn into_job(self) -> JobRequest {
+        let test_cases = self
+            .test_cases
+            .into_iter()
+            .enumerate()
+            .map(|(idx, tc)| TestCase {
+                id: (idx + 1) as u32,
+                input: tc.input,
+                expected_output: tc.expected_output,
+                weight: tc.weight,
+                comparison: tc.comparison,
+                group_id: tc.group_id,
+            })
+            .collect();
+        JobRequest {
+            id: Uuid::new_v4(),
+            language: self.language,
+            source_code: self.source_code,
+            test_cases,
+            timeout_ms: self.timeout_ms,
+            metadata: JobMetadata { enqueued_at: Some(chrono::Utc::now()), ..Default::default() },
+            normalization: self.normalization,
+            output_mode: self.output_mode,
+            comparison: self.comparison,
+            evaluation: self.evaluation,
+            status_policy: self.status_policy,
+            scoring: self.scoring,
+            leaderboard: None,
+            checker: None,
+            warmup: None,
+            sanitizers: Vec::new(),
+            source_format: Default::default(),
+            interactor: None,
+            group_scoring: self.group_scoring,
+            subtask_scoring: self.subtask_scoring,
+        }
+    }
+}
+
+#[derive(Clone)]
+struct StandaloneState {
+    config_manager: LanguageConfigManager,
+    queue: mpsc::Sender<JobRequest>,
+    store: Arc<dyn Store>,
+}
+
+fn error_response(status: StatusCode, code: &str, message: String) -> Response {
+    (status, Json(serde_json::json!({ "error": { "code": code, "message": message } }))).into_response()
+}
+
+/// POST /execute - Validate and queue a job
+async fn submit_job(State(state): State<StandaloneState>, Json(payload): Json<SubmitRequest>) -> Response {
+    if state.config_manager.get_config(&payload.language).is_err() {
+        let message = format!("Language '{}' is not configured", payload.language);
+        return error_response(StatusCode::BAD_REQUEST, "LANGUAGE_NOT_SUPPORTED", message);
+    }
+    // The API's checks, so a submission valid here is valid there
+    let job = payload.into_job();
+    if let Err(violations) = validate::validate_submission(&validate::Payload::from(&job), &optimus_common::limits::report()) {
+        let status = if violations.too_large() { StatusCode::PAYLOAD_TOO_LARGE } else { StatusCode::BAD_REQUEST };
+        let first = violations.first();
+        let body = serde_json::json!({
+            "error": { "code": first.code, "message": first.message, "violations": violations.as_slice() }
+        });
+        return (status, Json(body)).into_response();
+    }
+    let job_id = job.id;
+    let stored = job.clone();
+    if let Err(e) = store::run_blocking(&state.store, move |store| store.insert(&stored)).await {
+        error!(job_id = %job_id, error = %e, "Failed to store job");
+        return error_response(StatusCode::INTERNAL_SERVER_ERROR, "INTERNAL_ERROR", format!("Failed to store job: {}", e));
+    }
+    if state.queue.try_send(job).is_err() {
+        if let Err(e) = store::run_blocking(&state.store, move |store| store.remove(job_id)).await {
+            warn!(job_id = %job_id, error = %e, "Failed to remove unqueued job");
+        }
+        return error_response(StatusCode::SERVICE_UNAVAILABLE, "QUEUE_FULL", "Too many jobs waiting; retry later".to_string());
+    }
+
+    info!(job_id = %job_id, "Job queued");
+    (StatusCode::CREATED, Json(serde_json::json!({ "job_id": job_id.to_string() }))).into_response()
+}
+
+/// GET /job/{job_id} - Result once finished, 202 while queued or running
+async fn get_job_result(State(state): State<StandaloneState>, Path(job_id): Path<String>) -> Response {
+    let Ok(job_uuid) = Uuid::parse_str(&job_id) else {
+        return error_response(StatusCode::BAD_REQUEST, "INVALID_JOB_ID", "Invalid job ID format".to_string());
+    };
+
+    let job_state = match store::run_blocking(&state.store, move |store| store.get(&job_uuid)).await {
+        Ok(job_state) => job_state,
+        Err(e) => {
+            error!(job_id = %job_id, error = %e, "Failed to read job state");
+            return error_response(StatusCode::INTERNAL_SERVER_ERROR, "INTERNAL_ERROR", format!("Failed to read job state: {}", e));
+        }
+    };
+    match job_state {
+        Some(JobState::Finished(result)) => (StatusCode::OK, Json(result)).into_response(),
+        Some(JobState::Failed(message)) => error_response(StatusCode::INTERNAL_SERVER_ERROR, "EXECUTION_FAILED", message),
+        Some(pending) => {
+            let status = if matches!(pending, JobState::Queued) { "queued" } else { "running" };
+            (
+                StatusCode::ACCEPTED,
+                Json(serde_json::json!({
+                    "job_id": job_id,
+                    "status": status,
+                    "message": "Job is queued or still executing"
+                })),
+            )
+                .into_response()
+        }
+        None => error_response(StatusCode::NOT_FOUND, "JOB_NOT_FOUND", "Unknown job".to_string()),
+    }
+}
+
+async fn health_check() -> impl IntoResponse {
+    Json(serde_json::json!({ "status": "healthy", "mode": "standalone" }))
+}
+
+async fn save(store: &Arc<dyn Store>, job_id: Uuid, state: JobState) {
+    if let Err(e) = store::run_blocking(store, move |store| store.set(job_id, state)).await {
+        warn!(job_id = %job_id, error = %e, "Failed to store job state");
+    }
+}
+
+/// Take jobs off the queue and judge up to `max_parallel_jobs` at a time
+async fn dispatch_loop(
+    mut queue: mpsc::Receiver<JobRequest>,
+    engine: SharedEngine,
+    config_manager: LanguageConfigManager,
+    store: Arc<dyn Store>,
+    max_parallel_jobs: usize,
+) {
+    let semaphore = Arc::new(Semaphore::new(max_parallel_jobs));
+    while let Some(job) = queue.recv().await {
+        let permit = semaphore.clone().acquire_owned().await.expect("Semaphore should never be closed");
+        let engine = engine.clone();
+        let config_manager = config_manager.clone();
+        let store = store.clone();
+        tokio::spawn(async move {
+            let _permit = permit;
+            let queue_wait_ms = job.metadata.queue_wait_ms(chrono::Utc::now());
+            save(&store, job.id, JobState::Running).await;
+            let outcome = match engine.healthy().await {
+                Ok(engine) => executor::execute_standalone(&job, &engine, &config_manager).await,
+                Err(e) => Err(e),
+            };
+            let state = match outcome {
+                Ok(mut result) => {
+                    if let Some(timings) = result.timings.as_mut() {
+                        timings.queue_wait_ms = queue_wait_ms;
+                    }
+                    info!(job_id = %job.id, status = ?result.overall_status, score = result.score, "Job finished");
+                    JobState::Finished(Box::new(result))
+                }
+                Err(e) => {
+                    error!(job_id = %job.id, error = %e, "Job execution failed");
+                    JobState::Failed(e.to_string())
+                }
+            };
+            save(&store, job.id, state).await;
+        });
+    }
+}
+
+/// Serve the API and judge jobs in this process until the server stops
+pub async fn run(config_manager: LanguageConfigManager, engine: SharedEngine, worker_config: WorkerConfig) -> anyhow::Result<()> {
+    let (sender, receiver) = mpsc::channel(QUEUE_CAPACITY);
+    let store = store::from_env()?;
+    info!("Result store: {}", store.describe());
+
+    // Jobs interrupted by the last shutdown run again (SQLite store only)
+    let unfinished = store::run_blocking(&store, |store| store.unfinished()).await?;
+    if !unfinished.is_empty() {
+        info!("Re-queueing {} unfinished job(s)", unfinished.len());
+        let requeue = sender.clone();
+        tokio::spawn(async move {
+            for job in unfinished {
+                let _ = requeue.send(job).await;
+            }
+        });
+    }
+
+    tokio::spawn(dispatch_loop(receiver, engine, config_manager.clone(), store.clone(), worker_config.max_parallel_jobs));
+
+    let app = Router::new()
+        .route("/execute", post(submit_job))
+        .route("/job/:job_id", get(get_job_result))
+        .route("/health", get(health_check))
+        .with_state(StandaloneState { config_manager, queue: sender, store });
+
+    let port = std::env::var("PORT").unwrap_or_else(|_| "4001".to_string());
+    let addr = format!("0.0.0.0:{}", port);
+    let listener = TcpListener::bind(&addr).await?;
+    info!("Standalone mode: API and worker listening on {} (in-memory queue, no Redis)", addr);
+    info!("Max parallel jobs: {}", worker_config.max_parallel_jobs);
+
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_submit_request_defaults_and_ids() {
+        let request: SubmitRequest = serde_json::from_str(
+            r#"{"language": "python", "source_code": "print(input())",
+                "test_cases": [{"input": "1", "expected_output": "1"}, {"input": "2", "expected_output": "2"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(request.timeout_ms, 5000);
+        let job = request.into_job();
+        assert_eq!(job.test_cases.iter().map(|tc| tc.id).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(job.test_cases[0].weight, 10);
+    }
+}
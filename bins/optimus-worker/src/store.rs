@@ -0,0 +1,377 @@
+//! Standalone Result Store - In Memory or SQLite
+//!
+//! **Core Responsibility:**
+//! Keep job state and results for standalone mode (standalone.rs), where there is no Redis.
+//!
+//! By default everything lives in memory and is lost on restart. With OPTIMUS_STANDALONE_DB
+//! set to a file path, jobs and results go to a SQLite database instead: results survive
+//! restarts, and jobs that were still queued or running when the process stopped are queued
+//! again on the next start. Both keep the newest MAX_STORED_RESULTS finished jobs.
+//!
+//! Only standalone mode uses `Store`. With Redis, results are stored and read through
+//! `optimus_common::redis` (`store_result` / `get_result`), as before.
+
+use optimus_common::types::{ExecutionResult, JobRequest};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use tracing::warn;
+use uuid::Uuid;
+
+/// Finished results kept (in memory or in SQLite); the oldest are dropped first
+const MAX_STORED_RESULTS: usize = 10_000;
+
+#[derive(Debug, Clone)]
+pub enum JobState {
+    Queued,
+    Running,
+    Finished(Box<ExecutionResult>),
+    /// The sandbox could not run the job (Docker unavailable, image missing...)
+    Failed(String),
+}
+
+impl JobState {
+    fn is_final(&self) -> bool {
+        matches!(self, JobState::Finished(_) | JobState::Failed(_))
+    }
+}
+
+/// Where standalone mode keeps jobs and results (not the Redis result path)
+///
+/// Calls block (SQLite does file I/O), so async code goes through `run_blocking`.
+pub trait Store: Send + Sync {
+    fn describe(&self) -> String;
+
+    /// Record a newly submitted job
+    fn insert(&self, job: &JobRequest) -> anyhow::Result<()>;
+
+    fn set(&self, job_id: Uuid, state: JobState) -> anyhow::Result<()>;
+
+    /// Forget a job that could not be queued
+    fn remove(&self, job_id: Uuid) -> anyhow::Result<()>;
+
+    fn get(&self, job_id: &Uuid) -> anyhow::Result<Option<JobState>>;
+
+    /// Jobs left queued or running by a previous process, oldest first
+    fn unfinished(&self) -> anyhow::Result<Vec<JobRequest>>;
+}
+
+/// SQLite when OPTIMUS_STANDALONE_DB is set, else in memory
+pub fn from_env() -> anyhow::Result<Arc<dyn Store>> {
+    match std::env::var("OPTIMUS_STANDALONE_DB") {
+        Ok(path) if !path.trim().is_empty() => Ok(Arc::new(SqliteStore::open(path.trim())?)),
+        _ => Ok(Arc::new(MemoryStore::default())),
+    }
+}
+
+/// Run a store call on the blocking thread pool
+pub async fn run_blocking<T, F>(store: &Arc<dyn Store>, call: F) -> anyhow::Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&dyn Store) -> anyhow::Result<T> + Send + 'static,
+{
+    let store = store.clone();
+    tokio::task::spawn_blocking(move || call(store.as_ref())).await?
+}
+
+#[derive(Default)]
+pub struct MemoryStore {
+    inner: Mutex<MemoryJobs>,
+}
+
+#[derive(Default)]
+struct MemoryJobs {
+    jobs: HashMap<Uuid, JobState>,
+    /// Finished and failed jobs, oldest first
+    finished: VecDeque<Uuid>,
+}
+
+impl MemoryStore {
+    fn jobs(&self) -> std::sync::MutexGuard<'_, MemoryJobs> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Store for MemoryStore {
+    fn describe(&self) -> String {
+        "in memory (lost on restart)".to_string()
+    }
+
+    fn insert(&self, job: &JobRequest) -> anyhow::Result<()> {
+        self.set(job.id, JobState::Queued)
+    }
+
+    fn set(&self, job_id: Uuid, state: JobState) -> anyhow::Result<()> {
+        let mut memory = self.jobs();
+        // A job stored again keeps its place in the eviction order
+        let stored_final = memory.jobs.get(&job_id).is_some_and(JobState::is_final);
+        if state.is_final() && !stored_final {
+            memory.finished.push_back(job_id);
+            while memory.finished.len() > MAX_STORED_RESULTS {
+                if let Some(oldest) = memory.finished.pop_front() {
+                    memory.jobs.remove(&oldest);
+                }
+            }
+        }
+        memory.jobs.insert(job_id, state);
+        Ok(())
+    }
+
+    fn remove(&self, job_id: Uuid) -> anyhow::Result<()> {
+        self.jobs().jobs.remove(&job_id);
+        Ok(())
+    }
+
+    fn get(&self, job_id: &Uuid) -> anyhow::Result<Option<JobState>> {
+        Ok(self.jobs().jobs.get(job_id).cloned())
+    }
+
+    fn unfinished(&self) -> anyhow::Result<Vec<JobRequest>> {
+        Ok(Vec::new())
+    }
+}
+
+pub struct SqliteStore {
+    path: String,
+    conn: Mutex<Connection>,
+}
+
+/// One row per job: the request is kept until it finishes (to re-queue it after a
+/// restart), then replaced by the result or the failure message
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS jobs (
+    job_id      TEXT PRIMARY KEY,
+    state       TEXT NOT NULL,
+    job         TEXT,
+    result      TEXT,
+    error       TEXT,
+    created_at  TEXT NOT NULL,
+    updated_at  TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS jobs_state ON jobs (state, created_at);
+CREATE INDEX IF NOT EXISTS jobs_done ON jobs (state, updated_at);
+";
+
+/// Drops the oldest finished and failed jobs past the newest ?1, as the memory store does
+const PRUNE: &str = "
+DELETE FROM jobs WHERE job_id IN (
+    SELECT job_id FROM jobs WHERE state IN ('finished', 'failed')
+    ORDER BY updated_at DESC LIMIT -1 OFFSET ?1
+)";
+
+impl SqliteStore {
+    pub fn open(path: &str) -> anyhow::Result<Self> {
+        let conn = Connection::open(path)?;
+        Self::init(path, conn)
+    }
+
+    fn init(path: &str, conn: Connection) -> anyhow::Result<Self> {
+        // WAL keeps reads from waiting on the writes of finishing jobs
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { path: path.to_string(), conn: Mutex::new(conn) })
+    }
+
+    fn conn(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Store for SqliteStore {
+    fn describe(&self) -> String {
+        format!("SQLite ({})", self.path)
+    }
+
+    fn insert(&self, job: &JobRequest) -> anyhow::Result<()> {
+        let now = chrono::Utc::now().to_rfc3339();
+        self.conn().execute(
+            "INSERT INTO jobs (job_id, state, job, created_at, updated_at) VALUES (?1, 'queued', ?2, ?3, ?3)",
+            params![job.id.to_string(), serde_json::to_string(job)?, now],
+        )?;
+        Ok(())
+    }
+
+    fn set(&self, job_id: Uuid, state: JobState) -> anyhow::Result<()> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let id = job_id.to_string();
+        let conn = self.conn();
+        match &state {
+            JobState::Queued | JobState::Running => conn.execute(
+                "UPDATE jobs SET state = ?2, updated_at = ?3 WHERE job_id = ?1",
+                params![id, if matches!(state, JobState::Queued) { "queued" } else { "running" }, now],
+            )?,
+            JobState::Finished(result) => conn.execute(
+                "UPDATE jobs SET state = 'finished', job = NULL, result = ?2, updated_at = ?3 WHERE job_id = ?1",
+                params![id, serde_json::to_string(result)?, now],
+            )?,
+            JobState::Failed(message) => conn.execute(
+                "UPDATE jobs SET state = 'failed', job = NULL, error = ?2, updated_at = ?3 WHERE job_id = ?1",
+                params![id, message, now],
+            )?,
+        };
+        if state.is_final() {
+            conn.execute(PRUNE, params![MAX_STORED_RESULTS as i64])?;
+        }
+        Ok(())
+    }
+
+    fn remove(&self, job_id: Uuid) -> anyhow::Result<()> {
+        self.conn().execute("DELETE FROM jobs WHERE job_id = ?1", params![job_id.to_string()])?;
+        Ok(())
+    }
+
+    fn get(&self, job_id: &Uuid) -> anyhow::Result<Option<JobState>> {
+        let row = self
+            .conn()
+            .query_row(
+                "SELECT state, result, error FROM jobs WHERE job_id = ?1",
+                params![job_id.to_string()],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?, row.get::<_, Option<String>>(2)?)),
+            )
+            .optional()?;
+        let Some((state, result, error)) = row else { return Ok(None) };
+        Ok(Some(match state.as_str() {
+            "queued" => JobState::Queued,
+            "running" => JobState::Running,
+            "finished" => JobState::Finished(serde_json::from_str(result.as_deref().unwrap_or_default())?),
+            _ => JobState::Failed(error.unwrap_or_default()),
+        }))
+    }
+
+    /// Rows that no longer decode (written by an incompatible version) are marked failed,
+    /// with a warning, instead of staying queued forever
+    fn unfinished(&self) -> anyhow::Result<Vec<JobRequest>> {
+        let rows = {
+            let conn = self.conn();
+            let mut statement = conn
+                .prepare("SELECT job_id, job FROM jobs WHERE state IN ('queued', 'running') AND job IS NOT NULL ORDER BY created_at")?;
+            let rows = statement
+                .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+                .collect::<Result<Vec<_>, _>>()?;
+            rows
+        };
+        let mut jobs = Vec::new();
+        for (job_id, job) in rows {
+            match serde_json::from_str::<JobRequest>(&job) {
+                Ok(job) => jobs.push(job),
+                Err(e) => {
+                    warn!(job_id = %job_id, error = %e, "Stored job can't be decoded; marking it failed");
+                    self.conn().execute(
+                        "UPDATE jobs SET state = 'failed', job = NULL, error = ?2, updated_at = ?3 WHERE job_id = ?1",
+                        params![job_id, format!("Stored job could not be decoded after a restart: {}", e), chrono::Utc::now().to_rfc3339()],
+                    )?;
+                }
+            }
+        }
+        Ok(jobs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use optimus_common::types::{JobStatus, Language};
+
+    fn job() -> JobRequest {
+        serde_json::from_value(serde_json::json!({
+            "id": Uuid::new_v4(),
+            "language": "python",
+            "source_code": "print(1)",
+            "test_cases": [{"id": 1, "input": "", "expected_output": "1", "weight": 10}],
+            "timeout_ms": 5000
+        }))
+        .unwrap()
+    }
+
+    fn result(job: &JobRequest) -> ExecutionResult {
+        serde_json::from_value(serde_json::json!({
+            "job_id": job.id,
+            "overall_status": "completed",
+            "score": 10,
+            "max_score": 10,
+            "results": []
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_memory_store_evicts_oldest_finished_jobs() {
+        let store = MemoryStore::default();
+        let queued = Uuid::new_v4();
+        store.set(queued, JobState::Queued).unwrap();
+        let ids: Vec<Uuid> = (0..MAX_STORED_RESULTS + 2).map(|_| Uuid::new_v4()).collect();
+        for id in &ids {
+            store.set(*id, JobState::Failed("boom".to_string())).unwrap();
+        }
+        assert!(store.get(&ids[0]).unwrap().is_none());
+        assert!(store.get(&ids[1]).unwrap().is_none());
+        assert!(matches!(store.get(&ids[2]).unwrap(), Some(JobState::Failed(_))));
+        // Pending jobs are never evicted
+        assert!(matches!(store.get(&queued).unwrap(), Some(JobState::Queued)));
+    }
+
+    #[test]
+    fn test_memory_store_counts_a_restored_result_once() {
+        let store = MemoryStore::default();
+        let job = job();
+        store.set(job.id, JobState::Failed("boom".to_string())).unwrap();
+        store.set(job.id, JobState::Finished(Box::new(result(&job)))).unwrap();
+        assert_eq!(store.jobs().finished.len(), 1);
+        assert!(matches!(store.get(&job.id).unwrap(), Some(JobState::Finished(_))));
+    }
+
+    #[test]
+    fn test_sqlite_store_round_trip_and_requeue() {
+        let store = SqliteStore::init(":memory:", Connection::open_in_memory().unwrap()).unwrap();
+        let (done, pending) = (job(), job());
+        store.insert(&done).unwrap();
+        store.insert(&pending).unwrap();
+        store.set(pending.id, JobState::Running).unwrap();
+        store.set(done.id, JobState::Finished(Box::new(result(&done)))).unwrap();
+
+        match store.get(&done.id).unwrap() {
+            Some(JobState::Finished(result)) => {
+                assert_eq!(result.overall_status, JobStatus::Completed);
+                assert_eq!(result.score, 10);
+            }
+            other => panic!("unexpected state {:?}", other),
+        }
+        assert!(matches!(store.get(&pending.id).unwrap(), Some(JobState::Running)));
+
+        // Only the unfinished job is queued again after a restart
+        let unfinished = store.unfinished().unwrap();
+        assert_eq!(unfinished.len(), 1);
+        assert_eq!(unfinished[0].id, pending.id);
+        assert_eq!(unfinished[0].language, Language::Python);
+
+        store.remove(pending.id).unwrap();
+        assert!(store.get(&pending.id).unwrap().is_none());
+
+        // A row that no longer decodes is failed, not silently left queued
+        store.conn().execute(
+            "INSERT INTO jobs (job_id, state, job, created_at, updated_at) VALUES ('broken', 'queued', '{}', '0', '0')",
+            [],
+        ).unwrap();
+        assert!(store.unfinished().unwrap().is_empty());
+        let error: String = store.conn().query_row("SELECT error FROM jobs WHERE job_id = 'broken'", [], |row| row.get(0)).unwrap();
+        assert!(error.contains("could not be decoded"), "{}", error);
+    }
+
+    #[test]
+    fn test_sqlite_store_keeps_the_newest_finished_jobs() {
+        let store = SqliteStore::init(":memory:", Connection::open_in_memory().unwrap()).unwrap();
+        let pending = job();
+        store.insert(&pending).unwrap();
+        for _ in 0..3 {
+            let done = job();
+            store.insert(&done).unwrap();
+            store.set(done.id, JobState::Failed("boom".to_string())).unwrap();
+        }
+        store.conn().execute(PRUNE, params![2]).unwrap();
+        let count = |state: &str| -> i64 {
+            store.conn().query_row("SELECT COUNT(*) FROM jobs WHERE state = ?1", params![state], |row| row.get(0)).unwrap()
+        };
+        assert_eq!(count("failed"), 2);
+        assert_eq!(count("queued"), 1);
+    }
+}
@@ -0,0 +1,337 @@
+//! Streaming Comparison - Large Outputs Line by Line
+//!
+//! **Core Responsibility:**
+//! Decide whether two outputs match, as `Pipeline::matches` would, reading both a line
+//! at a time and stopping at the first difference.
+//!
+//! `Pipeline::matches` normalizes each output into a new string, once per normalizer, so
+//! a job with 64KB outputs over 100 tests holds many copies of every one of them. Here
+//! memory is bounded by the longest line, whatever the size of the outputs:
+//!
+//! - Both sides are any `BufRead`: outputs in memory, or files copied out of the container
+//!   or fetched from object storage, which never have to be read whole
+//! - Normalizers run on each line; `trim` applies to the stream as a whole, skipping the
+//!   leading blank lines and holding back blank ones until a line follows them
+//! - `tokens` and `numeric` compare token by token, whatever the line breaks
+//!
+//! Not every pipeline streams: `unordered_lines` needs every line before it can judge,
+//! and `strip_bom` or `drop_replacement_chars` after a `trim` would change what the trim
+//! removes. `StreamingComparison::new` returns None for those, and the caller compares in
+//! memory.
+
+use crate::pipeline::{self, Pipeline};
+use optimus_common::types::{Comparator, Normalizer};
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::io::{self, BufRead};
+
+/// A pipeline that can compare outputs line by line
+#[derive(Debug)]
+pub struct StreamingComparison<'p> {
+    normalizers: &'p [Normalizer],
+    comparator: Comparator,
+    trim: bool,
+}
+
+impl<'p> StreamingComparison<'p> {
+    /// None if the pipeline can't be streamed
+    pub fn new(pipeline: &'p Pipeline) -> Option<Self> {
+        let normalizers = pipeline.normalizers();
+        let first_trim = normalizers.iter().position(|n| *n == Normalizer::Trim);
+        // Trim commutes with every normalizer but these two, which may expose whitespace
+        // (or hide the BOM behind it)
+        let reordered = first_trim.is_some_and(|trim| {
+            normalizers[trim..]
+                .iter()
+                .any(|n| matches!(n, Normalizer::StripBom | Normalizer::DropReplacementChars))
+        });
+        if reordered || pipeline.comparator() == Comparator::UnorderedLines {
+            return None;
+        }
+        Some(Self { normalizers, comparator: pipeline.comparator(), trim: first_trim.is_some() })
+    }
+
+    /// Whether the outputs match; stops reading at the first difference
+    pub fn matches(&self, actual: impl BufRead, expected: impl BufRead) -> io::Result<bool> {
+        let mut actual = self.lines(actual);
+        let mut expected = self.lines(expected);
+        match self.comparator {
+            Comparator::Exact => Ok(first_different_line(&mut actual, &mut expected)?.is_none()),
+            Comparator::Tokens | Comparator::Numeric { .. } => {
+                let mut actual = Tokens::new(actual);
+                let mut expected = Tokens::new(expected);
+                loop {
+                    let same = match (actual.next_token()?, expected.next_token()?) {
+                        (None, None) => return Ok(true),
+                        (Some(a), Some(e)) => match self.comparator {
+                            Comparator::Numeric { abs_tolerance, rel_tolerance } => {
+                                pipeline::numbers_close(&a, &e, abs_tolerance, rel_tolerance).unwrap_or(a == e)
+                            }
+                            _ => a == e,
+                        },
+                        _ => false,
+                    };
+                    if !same {
+                        return Ok(false);
+                    }
+                }
+            }
+            // Refused by `new`
+            Comparator::UnorderedLines => unreachable!("unordered lines can't be streamed"),
+        }
+    }
+
+    /// The first lines that differ, numbered from 1, with each side's line (None where that
+    /// output ended); None when the outputs match. Lines only differ under `exact`.
+    pub fn first_difference(&self, actual: impl BufRead, expected: impl BufRead) -> io::Result<Option<LineDifference>> {
+        if self.comparator != Comparator::Exact {
+            return Ok(None);
+        }
+        first_different_line(&mut self.lines(actual), &mut self.lines(expected))
+    }
+
+    fn lines<R: BufRead>(&self, reader: R) -> NormalizedLines<'p, R> {
+        NormalizedLines {
+            lines: Lines { reader, buf: Vec::new(), done: false },
+            normalizers: self.normalizers,
+            trim: self.trim,
+            first: true,
+            held: None,
+            blanks: Vec::new(),
+            ready: VecDeque::new(),
+        }
+    }
+}
+
+/// Where two outputs first differ: line number, actual line, expected line
+pub type LineDifference = (usize, Option<String>, Option<String>);
+
+fn first_different_line(
+    actual: &mut NormalizedLines<'_, impl BufRead>,
+    expected: &mut NormalizedLines<'_, impl BufRead>,
+) -> io::Result<Option<LineDifference>> {
+    let mut line = 0;
+    loop {
+        line += 1;
+        match (actual.next_line()?, expected.next_line()?) {
+            (None, None) => return Ok(None),
+            (Some(a), Some(e)) if a == e => {}
+            (a, e) => return Ok(Some((line, a, e))),
+        }
+    }
+}
+
+/// The text's lines as `split('\n')` cuts them, read one at a time
+struct Lines<R> {
+    reader: R,
+    buf: Vec<u8>,
+    done: bool,
+}
+
+impl<R: BufRead> Lines<R> {
+    fn next_line(&mut self) -> io::Result<Option<String>> {
+        if self.done {
+            return Ok(None);
+        }
+        self.buf.clear();
+        self.reader.read_until(b'\n', &mut self.buf)?;
+        if self.buf.last() == Some(&b'\n') {
+            self.buf.pop();
+        } else {
+            self.done = true;
+        }
+        // A '\n' never splits a UTF-8 sequence, so this is what the whole text would give
+        Ok(Some(String::from_utf8_lossy(&self.buf).into_owned()))
+    }
+}
+
+/// Lines after the pipeline's normalizers
+struct NormalizedLines<'p, R> {
+    lines: Lines<R>,
+    normalizers: &'p [Normalizer],
+    trim: bool,
+    /// No line read yet (the BOM can only be on the first)
+    first: bool,
+    /// Last line with content, which is trimmed if nothing but blank lines follow it
+    held: Option<String>,
+    /// Blank lines after `held`, dropped if nothing follows them
+    blanks: Vec<String>,
+    ready: VecDeque<String>,
+}
+
+impl<R: BufRead> NormalizedLines<'_, R> {
+    fn next_line(&mut self) -> io::Result<Option<String>> {
+        loop {
+            if let Some(line) = self.ready.pop_front() {
+                return Ok(Some(line));
+            }
+            let Some(line) = self.lines.next_line()? else {
+                return Ok(self.held.take().map(|last| last.trim_end().to_string()));
+            };
+            let line = self.normalize(line);
+            if !self.trim {
+                return Ok(Some(line));
+            }
+            if line.trim().is_empty() {
+                // Leading blank lines are trimmed away
+                if self.held.is_some() {
+                    self.blanks.push(line);
+                }
+                continue;
+            }
+            match self.held.replace(line) {
+                Some(previous) => {
+                    self.ready.push_back(previous);
+                    self.ready.extend(self.blanks.drain(..));
+                }
+                None => {
+                    let first = self.held.as_mut().expect("just set");
+                    *first = first.trim_start().to_string();
+                }
+            }
+        }
+    }
+
+    fn normalize(&mut self, line: String) -> String {
+        let first = std::mem::replace(&mut self.first, false);
+        self.normalizers
+            .iter()
+            .filter(|n| **n != Normalizer::Trim && (first || **n != Normalizer::StripBom))
+            .fold(Cow::Owned(line), |text, normalizer| pipeline::apply(*normalizer, text))
+            .into_owned()
+    }
+}
+
+/// Whitespace-separated tokens, across line breaks
+struct Tokens<'p, R> {
+    lines: NormalizedLines<'p, R>,
+    pending: VecDeque<String>,
+}
+
+impl<'p, R: BufRead> Tokens<'p, R> {
+    fn new(lines: NormalizedLines<'p, R>) -> Self {
+        Self { lines, pending: VecDeque::new() }
+    }
+
+    fn next_token(&mut self) -> io::Result<Option<String>> {
+        loop {
+            if let Some(token) = self.pending.pop_front() {
+                return Ok(Some(token));
+            }
+            match self.lines.next_line()? {
+                Some(line) => self.pending.extend(line.split_whitespace().map(str::to_string)),
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use optimus_common::types::{ComparisonMode, NormalizationOptions};
+    use std::io::Read;
+
+    const OUTPUTS: &[&str] = &[
+        "",
+        "\n",
+        "1 2\n",
+        "1 2",
+        "  1 2 \n\n",
+        "\n\n 1  2\n3\n \n\t\n",
+        "1 2\n\n\n3",
+        "1 2\n \n3\n",
+        "1\r\n2\r\n",
+        "1\n2",
+        "\u{FEFF}Hello  World\n",
+        "hello world",
+        "HELLO\n\nWORLD \n",
+        "0.3333333 x\n1e-1\n",
+        "0.333 x 0.1",
+        "caf\u{0065}\u{0301}\n",
+        "caf\u{00E9}",
+        "a\u{FFFD}b\n",
+    ];
+
+    fn pipelines() -> Vec<Pipeline> {
+        let all_options = NormalizationOptions {
+            strip_bom: true,
+            lenient_utf8: true,
+            unicode_nfc: true,
+            ignore_trailing_whitespace: true,
+            ignore_case: true,
+            collapse_whitespace: true,
+            tokens: true,
+        };
+        let mut pipelines: Vec<Pipeline> = [ComparisonMode::Ordered, ComparisonMode::Strict]
+            .into_iter()
+            .flat_map(|mode| [Pipeline::legacy(mode, &Default::default()), Pipeline::legacy(mode, &all_options)])
+            .collect();
+        pipelines.push(
+            Pipeline::builder()
+                .normalize(Normalizer::Trim)
+                .normalize(Normalizer::CollapseWhitespace)
+                .normalize(Normalizer::Lowercase)
+                .build(),
+        );
+        pipelines.push(Pipeline::builder().normalize(Normalizer::TrimLines).normalize(Normalizer::Trim).build());
+        pipelines.push(
+            Pipeline::builder()
+                .normalize(Normalizer::FloatRound { decimals: 2 })
+                .compare(Comparator::Tokens)
+                .build(),
+        );
+        pipelines.push(Pipeline::builder().compare(Comparator::Numeric { abs_tolerance: 1e-3, rel_tolerance: 0.0 }).build());
+        pipelines
+    }
+
+    #[test]
+    fn test_streaming_agrees_with_in_memory() {
+        for pipeline in pipelines() {
+            let streaming = StreamingComparison::new(&pipeline).unwrap();
+            for actual in OUTPUTS {
+                for expected in OUTPUTS {
+                    assert_eq!(
+                        streaming.matches(actual.as_bytes(), expected.as_bytes()).unwrap(),
+                        pipeline.matches(actual, expected),
+                        "{:?} vs {:?} with {:?}",
+                        actual,
+                        expected,
+                        pipeline
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_unstreamable_pipelines() {
+        let unordered = Pipeline::legacy(ComparisonMode::Unordered, &Default::default());
+        assert!(StreamingComparison::new(&unordered).is_none());
+        let bom_after_trim = Pipeline::builder().normalize(Normalizer::Trim).normalize(Normalizer::StripBom).build();
+        assert!(StreamingComparison::new(&bom_after_trim).is_none());
+    }
+
+    /// Fails if read: everything after the first difference
+    struct Unread;
+
+    impl Read for Unread {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::other("read past the first difference"))
+        }
+    }
+
+    #[test]
+    fn test_stops_at_first_difference() {
+        let pipeline = Pipeline::legacy(ComparisonMode::Ordered, &Default::default());
+        let streaming = StreamingComparison::new(&pipeline).unwrap();
+        let actual = io::BufReader::new(b"1\n2\n3\n".chain(Unread));
+        let expected = io::BufReader::new(b"1\n5\n3\n".chain(Unread));
+        assert!(!streaming.matches(actual, expected).unwrap());
+
+        let actual = io::BufReader::new(b"1\n2\n3\n".chain(Unread));
+        let expected = io::BufReader::new(b"1\n5\n3\n".chain(Unread));
+        let difference = streaming.first_difference(actual, expected).unwrap();
+        assert_eq!(difference, Some((2, Some("2".to_string()), Some("5".to_string()))));
+    }
+}
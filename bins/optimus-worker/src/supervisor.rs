@@ -0,0 +1,146 @@
+//! Job Task Supervision
+//!
+//! **Core Responsibility:**
+//! Track the job tasks worker_loop spawns, so the health endpoint and heartbeats can
+//! report what each one is doing, and turn a task's panic into an ordinary job failure.
+//!
+//! Each job runs in its own task in the loop's JoinSet. A `TaskGuard` registers the job
+//! while it runs and unregisters it when dropped - also while unwinding from a panic -
+//! so the registry never shows a job that is no longer running.
+
+use chrono::{DateTime, Utc};
+use optimus_common::types::{JobRequest, Language};
+use serde::Serialize;
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use uuid::Uuid;
+
+/// What a job task is doing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskPhase {
+    /// Dequeued, checking for cancellation
+    Starting,
+    /// Running in the sandbox
+    Executing,
+    /// Storing the result and follow-up work (verification, canary)
+    Persisting,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskInfo {
+    pub job_id: Uuid,
+    pub language: Language,
+    pub phase: TaskPhase,
+    pub started_at: DateTime<Utc>,
+}
+
+/// Job tasks currently running on this worker
+#[derive(Debug, Clone, Default)]
+pub struct Tasks(Arc<RwLock<HashMap<Uuid, TaskInfo>>>);
+
+impl Tasks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a job; it stays listed until the returned guard is dropped
+    pub fn start(&self, job: &JobRequest) -> TaskGuard {
+        let info = TaskInfo {
+            job_id: job.id,
+            language: job.language,
+            phase: TaskPhase::Starting,
+            started_at: Utc::now(),
+        };
+        self.0.write().unwrap().insert(job.id, info);
+        TaskGuard { tasks: self.clone(), job_id: job.id }
+    }
+
+    /// Running tasks, oldest first
+    pub fn snapshot(&self) -> Vec<TaskInfo> {
+        let mut tasks: Vec<TaskInfo> = self.0.read().unwrap().values().cloned().collect();
+        tasks.sort_by_key(|t| t.started_at);
+        tasks
+    }
+
+    pub fn is_executing(&self) -> bool {
+        !self.0.read().unwrap().is_empty()
+    }
+}
+
+/// Keeps a job listed in `Tasks` while it is alive
+pub struct TaskGuard {
+    tasks: Tasks,
+    job_id: Uuid,
+}
+
+impl TaskGuard {
+    pub fn set_phase(&self, phase: TaskPhase) {
+        if let Some(info) = self.tasks.0.write().unwrap().get_mut(&self.job_id) {
+            info.phase = phase;
+        }
+    }
+}
+
+impl Drop for TaskGuard {
+    fn drop(&mut self) {
+        // Never panic in drop: a poisoned lock still holds usable data
+        let mut tasks = self.tasks.0.write().unwrap_or_else(|e| e.into_inner());
+        tasks.remove(&self.job_id);
+    }
+}
+
+/// Readable message from a task's panic payload
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job() -> JobRequest {
+        serde_json::from_value(serde_json::json!({
+            "id": Uuid::new_v4(),
+            "language": "python",
+            "source_code": "print(1)",
+            "test_cases": [],
+            "timeout_ms": 5000
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_guard_tracks_phase_and_unregisters() {
+        let tasks = Tasks::new();
+        let job = job();
+        let guard = tasks.start(&job);
+        guard.set_phase(TaskPhase::Executing);
+        assert!(tasks.is_executing());
+        assert_eq!(tasks.snapshot()[0].phase, TaskPhase::Executing);
+        drop(guard);
+        assert!(!tasks.is_executing());
+    }
+
+    #[tokio::test]
+    async fn test_guard_unregisters_on_panic() {
+        let tasks = Tasks::new();
+        let job = job();
+        let registry = tasks.clone();
+        let handle = tokio::spawn(async move {
+            let _guard = registry.start(&job);
+            panic!("boom");
+        });
+        let error = handle.await.unwrap_err();
+        assert!(error.is_panic());
+        assert_eq!(panic_message(error.into_panic().as_ref()), "boom");
+        assert!(!tasks.is_executing());
+    }
+}
@@ -0,0 +1,201 @@
+//! Dual-Run Verification - Flaky Verdict Investigation
+//!
+//! **Core Responsibility:**
+//! When an admin asks to verify a job (POST /admin/jobs/{id}/verify), the API queues
+//! a copy of it marked with a `VerificationTarget`. The worker that executes the copy
+//! compares its result with the original field by field and stores the report at
+//! `optimus:verification:{original_job_id}`.
+//!
+//! Execution times always differ between runs, so they are reported per test but never
+//! count as a difference; a test that timed out in only one run is flagged as
+//! timing-sensitive, which is the usual cause of "flaky TLE" complaints.
+
+use optimus_common::redis;
+use optimus_common::types::{
+    ExecutionResult, FieldDifference, JobRequest, TestStatus, TestTiming, Verification,
+    VerificationReport, VerificationTarget,
+};
+use std::collections::HashMap;
+use tracing::{info, warn};
+
+/// Compare two results of the same job field by field
+pub fn compare_results(
+    original: &ExecutionResult,
+    rerun: &ExecutionResult,
+    image: &str,
+) -> VerificationReport {
+    let mut differences = Vec::new();
+    let mut diff = |field: String, a: String, b: String| {
+        if a != b {
+            differences.push(FieldDifference { field, original: a, verification: b });
+        }
+    };
+
+    diff("overall_status".into(), format!("{:?}", original.overall_status), format!("{:?}", rerun.overall_status));
+    diff("score".into(), original.score.to_string(), rerun.score.to_string());
+    diff("max_score".into(), original.max_score.to_string(), rerun.max_score.to_string());
+
+    let rerun_tests: HashMap<u32, _> = rerun.results.iter().map(|r| (r.test_id, r)).collect();
+    let mut timings = Vec::new();
+    let mut timing_sensitive_tests = Vec::new();
+
+    for test in &original.results {
+        let field = |name: &str| format!("results[test_id={}].{}", test.test_id, name);
+        let Some(other) = rerun_tests.get(&test.test_id) else {
+            diff(field("status"), format!("{:?}", test.status), "missing".into());
+            continue;
+        };
+
+        diff(field("status"), format!("{:?}", test.status), format!("{:?}", other.status));
+        diff(field("stdout"), test.stdout.clone(), other.stdout.clone());
+        diff(field("stderr"), test.stderr.clone(), other.stderr.clone());
+        diff(
+            field("stdout_base64"),
+            test.stdout_base64.clone().unwrap_or_default(),
+            other.stdout_base64.clone().unwrap_or_default(),
+        );
+
+        let timed_out = |status: TestStatus| status == TestStatus::TimeLimitExceeded;
+        if timed_out(test.status) != timed_out(other.status) {
+            timing_sensitive_tests.push(test.test_id);
+        }
+        timings.push(TestTiming {
+            test_id: test.test_id,
+            original_ms: test.execution_time_ms,
+            verification_ms: other.execution_time_ms,
+        });
+    }
+
+    VerificationReport {
+        original_worker: original.worker_id.clone(),
+        verification_worker: rerun.worker_id.clone(),
+        image: image.to_string(),
+        verdict_changed: original.overall_status != rerun.overall_status || original.score != rerun.score,
+        nondeterministic: !differences.is_empty(),
+        timing_sensitive_tests,
+        differences,
+        timings,
+        verified_at: chrono::Utc::now(),
+    }
+}
+
+/// Compare a finished verification re-run with the original result and store the report
+pub async fn record(
+    redis_conn: &mut ::redis::aio::ConnectionManager,
+    job: &JobRequest,
+    target: &VerificationTarget,
+    result: &ExecutionResult,
+    image: &str,
+) {
+    let original = match redis::get_result(redis_conn, &target.original_job_id).await {
+        Ok(Some(original)) => original,
+        Ok(None) => {
+            warn!(job_id = %job.id, original_job_id = %target.original_job_id, "Original result expired; verification skipped");
+            return;
+        }
+        Err(e) => {
+            warn!(job_id = %job.id, error = %e, "Failed to read original result for verification");
+            return;
+        }
+    };
+
+    let report = compare_results(&original, result, image);
+    info!(
+        original_job_id = %target.original_job_id,
+        verification_job_id = %job.id,
+        verdict_changed = report.verdict_changed,
+        nondeterministic = report.nondeterministic,
+        "Dual-run verification complete"
+    );
+
+    let requested_at = match redis::get_verification(redis_conn, &target.original_job_id).await {
+        Ok(Some(existing)) => existing.requested_at,
+        _ => chrono::Utc::now(),
+    };
+    let verification = Verification {
+        original_job_id: target.original_job_id,
+        verification_job_id: job.id,
+        requested_at,
+        report: Some(report),
+    };
+    if let Err(e) = redis::store_verification(redis_conn, &verification).await {
+        warn!(job_id = %job.id, error = %e, "Failed to store verification report");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use optimus_common::types::{JobStatus, TestResult};
+    use uuid::Uuid;
+
+    fn result(worker: &str, tests: &[(TestStatus, &str, u64)]) -> ExecutionResult {
+        let results: Vec<TestResult> = tests
+            .iter()
+            .enumerate()
+            .map(|(i, (status, stdout, time))| TestResult {
+                test_id: i as u32 + 1,
+                status: *status,
+                stdout: stdout.to_string(),
+                stderr: String::new(),
+                execution_time_ms: *time,
+                stdout_base64: None,
+                stderr_base64: None,
+                overhead_ms: None,
+                cpu_time_ms: None,
+                wall_time_ms: None,
+                memory_kb: None,
+                failure: None,
+                checker_output: None,
+                sanitizer_report: None,
+                diff: None,
+            })
+            .collect();
+        let score = results.iter().filter(|r| r.status == TestStatus::Passed).count() as u32;
+        ExecutionResult {
+            job_id: Uuid::new_v4(),
+            overall_status: if score as usize == results.len() { JobStatus::Completed } else { JobStatus::Failed },
+            score,
+            max_score: results.len() as u32,
+            results,
+            judge_env_version: None,
+            worker_id: Some(worker.to_string()),
+            signature: None,
+            points: None,
+            timings: None,
+            misuse: Vec::new(),
+            effective_timeout_ms: None,
+            subtasks: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_identical_runs_only_differ_in_timing() {
+        let a = result("w1", &[(TestStatus::Passed, "1", 10), (TestStatus::Passed, "2", 20)]);
+        let b = result("w2", &[(TestStatus::Passed, "1", 14), (TestStatus::Passed, "2", 18)]);
+        let report = compare_results(&a, &b, "img");
+
+        assert!(!report.nondeterministic);
+        assert!(!report.verdict_changed);
+        assert!(report.differences.is_empty());
+        assert_eq!(report.timings[0], TestTiming { test_id: 1, original_ms: 10, verification_ms: 14 });
+        assert_eq!(report.original_worker.as_deref(), Some("w1"));
+        assert_eq!(report.verification_worker.as_deref(), Some("w2"));
+    }
+
+    #[test]
+    fn test_flaky_tle_flagged() {
+        let a = result("w1", &[(TestStatus::Passed, "1", 10), (TestStatus::TimeLimitExceeded, "", 1000)]);
+        let b = result("w2", &[(TestStatus::Passed, "1", 10), (TestStatus::Passed, "2", 950)]);
+        let report = compare_results(&a, &b, "img");
+
+        assert!(report.verdict_changed);
+        assert!(report.nondeterministic);
+        assert_eq!(report.timing_sensitive_tests, vec![2]);
+        let fields: Vec<&str> = report.differences.iter().map(|d| d.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            vec!["overall_status", "score", "results[test_id=2].status", "results[test_id=2].stdout"]
+        );
+    }
+}
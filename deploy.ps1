@@ -9,6 +9,7 @@ param(
     [switch]$SkipBuild,
     [switch]$SkipKeda,
     [switch]$SkipImages,
+    [switch]$Dispatcher,
     [switch]$Uninstall,
     [string]$Context = "docker-desktop"
 )
@@ -42,6 +43,9 @@ if ($Uninstall) {
     Write-Host "Deleting API deployment..."
     kubectl delete -f k8s/api-deployment.yaml --ignore-not-found=true 2>$null
     
+    Write-Host "Deleting dispatcher deployment..."
+    kubectl delete -f k8s/dispatcher-deployment.yaml --ignore-not-found=true 2>$null
+    
     Write-Host "Deleting Redis..."
     kubectl delete -f k8s/redis.yaml --ignore-not-found=true 2>$null
     
@@ -138,7 +142,15 @@ if (-not $SkipImages) {
         exit 1
     }
     
-    Write-Success "Docker images built (API + Worker)"
+    # Build Dispatcher image (only deployed with -Dispatcher)
+    Write-Info "Building optimus-dispatcher image..."
+    docker build --no-cache -t optimus-dispatcher:latest -f bins/optimus-dispatcher/Dockerfile .
+    if ($LASTEXITCODE -ne 0) {
+        Write-Error "Failed to build optimus-dispatcher image"
+        exit 1
+    }
+    
+    Write-Success "Docker images built (API + Worker + Dispatcher)"
     Write-Info "Note: Language runtime images (python/java/rust) are used by workers to spawn execution containers"
     
     # For kind clusters, load images
@@ -146,6 +158,7 @@ if (-not $SkipImages) {
         Write-Info "Detected kind cluster - loading images..."
         kind load docker-image optimus-api:latest
         kind load docker-image optimus-worker:latest
+        kind load docker-image optimus-dispatcher:latest
         Write-Success "Images loaded into kind cluster"
     }
 }
@@ -213,6 +226,11 @@ Write-Step "Creating Optimus namespace..."
 kubectl apply -f k8s/namespace.yaml
 Write-Success "Namespace created"
 
+# Step 7b: Safety limits (mounted by the API and workers, reloaded without a restart)
+Write-Step "Publishing config/limits.json as the optimus-limits ConfigMap..."
+kubectl create configmap optimus-limits -n optimus --from-file=limits.json=config/limits.json --dry-run=client -o yaml | kubectl apply -f -
+Write-Success "Limits ConfigMap applied"
+
 # Step 8: Deploy Redis
 Write-Step "Deploying Redis..."
 kubectl apply -f k8s/redis.yaml
@@ -238,6 +256,14 @@ if (-not $apiReady) {
 }
 Write-Success "API deployed"
 
+# Step 9b: Deploy dispatcher (optional fair scheduling)
+if ($Dispatcher) {
+    Write-Step "Deploying Optimus dispatcher..."
+    kubectl apply -f k8s/dispatcher-deployment.yaml
+    kubectl set env deployment/optimus-api -n optimus DISPATCHER_ENABLED=true
+    Write-Success "Dispatcher deployed (API now queues jobs on the intake)"
+}
+
 # Step 10: Deploy workers
 Write-Step "Deploying language-specific worker deployments..."
 Write-Info "Each deployment uses the same optimus-worker image with different env vars"
@@ -268,6 +294,7 @@ kubectl get deployments -n optimus
 Write-Step "Getting API endpoint..."
 $apiService = kubectl get svc optimus-api -n optimus -o json | ConvertFrom-Json
 $apiPort = $apiService.spec.ports[0].port
+$keyPrefix = if ($env:OPTIMUS_KEY_PREFIX) { $env:OPTIMUS_KEY_PREFIX } else { "optimus" }
 
 if ($Context -eq "docker-desktop") {
     $apiUrl = "http://localhost:$apiPort"
@@ -300,8 +327,8 @@ Monitor autoscaling:
    # Watch pods scale up/down:
    kubectl get pods -n optimus -w
 
-   # Check queue lengths:
-   kubectl exec -n optimus deployment/redis -- redis-cli LLEN optimus:queue:python
+   # Check queue lengths (keys are under OPTIMUS_KEY_PREFIX, default optimus):
+   kubectl exec -n optimus deployment/redis -- redis-cli LLEN ${keyPrefix}:queue:python
 
    # View KEDA scaler events:
    kubectl describe scaledobject -n optimus
@@ -8,6 +8,7 @@ set -e
 SKIP_BUILD=false
 SKIP_KEDA=false
 SKIP_IMAGES=false
+DISPATCHER=false
 UNINSTALL=false
 CONTEXT="docker-desktop"
 
@@ -26,6 +27,10 @@ while [[ $# -gt 0 ]]; do
             SKIP_IMAGES=true
             shift
             ;;
+        --dispatcher)
+            DISPATCHER=true
+            shift
+            ;;
         --uninstall)
             UNINSTALL=true
             shift
@@ -36,7 +41,7 @@ while [[ $# -gt 0 ]]; do
             ;;
         *)
             echo "Unknown option: $1"
-            echo "Usage: $0 [--skip-build] [--skip-keda] [--skip-images] [--uninstall] [--context CONTEXT]"
+            echo "Usage: $0 [--skip-build] [--skip-keda] [--skip-images] [--dispatcher] [--uninstall] [--context CONTEXT]"
             exit 1
             ;;
     esac
@@ -78,6 +83,9 @@ if [ "$UNINSTALL" = true ]; then
     
     echo "Deleting API deployment..."
     kubectl delete -f k8s/api-deployment.yaml --ignore-not-found=true 2>/dev/null || true
+
+    echo "Deleting dispatcher deployment..."
+    kubectl delete -f k8s/dispatcher-deployment.yaml --ignore-not-found=true 2>/dev/null || true
     
     echo "Deleting Redis..."
     kubectl delete -f k8s/redis.yaml --ignore-not-found=true 2>/dev/null || true
@@ -158,7 +166,11 @@ if [ "$SKIP_IMAGES" = false ]; then
     write_info "Building optimus-worker image..."
     docker build --no-cache -t optimus-worker:latest -f bins/optimus-worker/Dockerfile .
     
-    write_success "Docker images built (API + Worker)"
+    # Build Dispatcher image (only deployed with --dispatcher)
+    write_info "Building optimus-dispatcher image..."
+    docker build --no-cache -t optimus-dispatcher:latest -f bins/optimus-dispatcher/Dockerfile .
+    
+    write_success "Docker images built (API + Worker + Dispatcher)"
     write_info "Note: Language runtime images (python/java/rust) are used by workers to spawn execution containers"
     
     # For kind clusters, load images
@@ -166,6 +178,7 @@ if [ "$SKIP_IMAGES" = false ]; then
         write_info "Detected kind cluster - loading images..."
         kind load docker-image optimus-api:latest
         kind load docker-image optimus-worker:latest
+        kind load docker-image optimus-dispatcher:latest
         write_success "Images loaded into kind cluster"
     # For k3s clusters, import images into containerd
     elif [[ "$CONTEXT" == *"k3s"* ]] || [[ "$CURRENT_CONTEXT" == *"k3s"* ]]; then
@@ -179,6 +192,9 @@ if [ "$SKIP_IMAGES" = false ]; then
         write_info "Saving optimus-worker image..."
         docker save optimus-worker:latest -o /tmp/optimus-worker.tar
         
+        write_info "Saving optimus-dispatcher image..."
+        docker save optimus-dispatcher:latest -o /tmp/optimus-dispatcher.tar
+        
         # Import into k3s containerd
         write_info "Importing optimus-api into k3s..."
         sudo k3s ctr images import /tmp/optimus-api.tar
@@ -186,8 +202,11 @@ if [ "$SKIP_IMAGES" = false ]; then
         write_info "Importing optimus-worker into k3s..."
         sudo k3s ctr images import /tmp/optimus-worker.tar
         
+        write_info "Importing optimus-dispatcher into k3s..."
+        sudo k3s ctr images import /tmp/optimus-dispatcher.tar
+        
         # Cleanup tar files
-        rm -f /tmp/optimus-api.tar /tmp/optimus-worker.tar
+        rm -f /tmp/optimus-api.tar /tmp/optimus-worker.tar /tmp/optimus-dispatcher.tar
         
         write_success "Images imported into k3s containerd"
         write_info "Verifying images in k3s..."
@@ -249,6 +268,11 @@ write_step "Creating Optimus namespace..."
 kubectl apply -f k8s/namespace.yaml
 write_success "Namespace created"
 
+# Step 7b: Safety limits (mounted by the API and workers, reloaded without a restart)
+write_step "Publishing config/limits.json as the optimus-limits ConfigMap..."
+kubectl create configmap optimus-limits -n optimus --from-file=limits.json=config/limits.json --dry-run=client -o yaml | kubectl apply -f -
+write_success "Limits ConfigMap applied"
+
 # Step 8: Deploy Redis
 write_step "Deploying Redis..."
 kubectl apply -f k8s/redis.yaml
@@ -272,6 +296,14 @@ if ! kubectl wait --for=condition=ready pod -l app=optimus-api -n optimus --time
 fi
 write_success "API deployed"
 
+# Step 9b: Deploy dispatcher (optional fair scheduling)
+if [ "$DISPATCHER" = true ]; then
+    write_step "Deploying Optimus dispatcher..."
+    kubectl apply -f k8s/dispatcher-deployment.yaml
+    kubectl set env deployment/optimus-api -n optimus DISPATCHER_ENABLED=true
+    write_success "Dispatcher deployed (API now queues jobs on the intake)"
+fi
+
 # Step 10: Deploy workers
 write_step "Deploying language-specific worker deployments..."
 write_info "Each deployment uses the same optimus-worker image with different env vars"
@@ -336,8 +368,8 @@ Monitor autoscaling:
    # Watch pods scale up/down:
    kubectl get pods -n optimus -w
 
-   # Check queue lengths:
-   kubectl exec -n optimus deployment/redis -- redis-cli LLEN optimus:queue:python
+   # Check queue lengths (keys are under OPTIMUS_KEY_PREFIX, default optimus):
+   kubectl exec -n optimus deployment/redis -- redis-cli LLEN ${OPTIMUS_KEY_PREFIX:-optimus}:queue:python
 
    # View KEDA scaler events:
    kubectl describe scaledobject -n optimus
@@ -1,7 +1,7 @@
 #!/bin/bash
 # Universal Optimus Code Runner
 # This script detects the language and executes code appropriately
-# Supports: Python, Java, Rust, C++, Go, Node.js, and more
+# Supports: Python, Java, Rust, C++, Go, Node.js, x86-64 assembly, and more
 #
 # CRITICAL: All execution commands MUST explicitly propagate exit codes
 # to ensure runtime errors are detected by the Docker engine
@@ -93,6 +93,14 @@ case "$EXECUTION_MODE" in
                 exit $?
                 ;;
             
+            asm)
+                # Write assembly code
+                echo "$SOURCE_CODE" > /code/main.asm
+                # Assemble, then link (no libc)
+                nasm -f elf64 /code/main.asm -o /code/main.o 2>&1 && ld /code/main.o -o /code/main 2>&1
+                exit $?
+                ;;
+            
             go)
                 # Write Go code
                 echo "$SOURCE_CODE" > /code/main.go
@@ -117,6 +125,14 @@ case "$EXECUTION_MODE" in
                 exit $?
                 ;;
             
+            scala)
+                # Write Scala code
+                echo "$SOURCE_CODE" > /code/Main.scala
+                # Compile Scala code
+                scalac -d /code /code/Main.scala 2>&1
+                exit $?
+                ;;
+            
             swift)
                 # Write Swift code
                 echo "$SOURCE_CODE" > /code/main.swift
@@ -134,7 +150,7 @@ case "$EXECUTION_MODE" in
                 ;;
             
             # Interpreted languages don't need compilation
-            javascript|node|nodejs|ruby|php|scala)
+            javascript|node|nodejs|ruby|php)
                 echo "$SOURCE_CODE" > /code/main.$LANGUAGE
                 echo "Interpreted language - no compilation needed"
                 exit 0
@@ -181,6 +197,12 @@ case "$EXECUTION_MODE" in
                 exit $?
                 ;;
             
+            asm)
+                # Execute linked assembly binary
+                echo "$TEST_INPUT" | /code/main
+                exit $?
+                ;;
+            
             go)
                 # Execute compiled Go binary
                 echo "$TEST_INPUT" | /code/main
@@ -230,8 +252,9 @@ case "$EXECUTION_MODE" in
                 ;;
             
             scala)
-                # Execute Scala
-                echo "$TEST_INPUT" | scala /code/Main.scala
+                # Execute compiled Scala classes
+                unset JAVA_TOOL_OPTIONS
+                echo "$TEST_INPUT" | java -cp /code:/opt/scala/lib/scala-library.jar Main
                 exit $?
                 ;;
             
@@ -330,6 +353,24 @@ case "$EXECUTION_MODE" in
         exit $?
         ;;
         
+    asm)
+        # Write assembly code
+        echo "$SOURCE_CODE" > /code/main.asm
+        
+        # Assemble, then link (no libc)
+        nasm -f elf64 /code/main.asm -o /code/main.o 2>&1 && ld /code/main.o -o /code/main 2>&1
+        
+        if [ $? -ne 0 ]; then
+            echo "Compilation failed" >&2
+            exit 1
+        fi
+        
+        # Execute assembly binary with test input
+        echo "$TEST_INPUT" | /code/main
+        # CRITICAL: Propagate exit code to Docker
+        exit $?
+        ;;
+        
     go)
         # Write Go code
         echo "$SOURCE_CODE" > /code/main.go
@@ -410,8 +451,17 @@ case "$EXECUTION_MODE" in
         # Write Scala code
         echo "$SOURCE_CODE" > /code/Main.scala
         
-        # Compile and execute Scala code with test input
-        echo "$TEST_INPUT" | scala /code/Main.scala
+        # Compile Scala code
+        scalac -d /code /code/Main.scala 2>&1
+        
+        if [ $? -ne 0 ]; then
+            echo "Compilation failed" >&2
+            exit 1
+        fi
+        
+        # Execute compiled Scala classes with test input
+        unset JAVA_TOOL_OPTIONS
+        echo "$TEST_INPUT" | java -cp /code:/opt/scala/lib/scala-library.jar Main
         # CRITICAL: Propagate exit code to Docker
         exit $?
         ;;
@@ -454,7 +504,7 @@ case "$EXECUTION_MODE" in
         
     *)
         echo "Error: Unsupported language '$LANGUAGE'" >&2
-        echo "Supported languages: python, java, rust, cpp, c, go, javascript, typescript, ruby, php, kotlin, scala, csharp, swift" >&2
+        echo "Supported languages: python, java, rust, cpp, c, asm, go, javascript, typescript, ruby, php, kotlin, scala, csharp, swift" >&2
         exit 1
         ;;
 esac
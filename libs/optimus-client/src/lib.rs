@@ -0,0 +1,229 @@
+// Optimus client - submit jobs to the API from Rust
+//
+// Before sending a submission the client checks it against the limits the API reports
+// on GET /limits, with the API's own checks (optimus_common::validate), so a too-large
+// source or a bad timeout fails at once with the same error code the API would return,
+// without a round-trip. The limits are cached for LIMITS_TTL and dropped whenever the API
+// refuses a submission (they may have been reloaded since). When they can't be fetched
+// (an older API) submissions are sent unchecked and the API validates them as usual.
+
+use optimus_common::limits::LimitsReport;
+use optimus_common::types::Language;
+use optimus_common::validate::{self, Payload, TestCasePayload, Violations};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long fetched limits are trusted
+const LIMITS_TTL: Duration = Duration::from_secs(60);
+
+/// A job to submit (POST /execute); other request fields take the API's defaults
+#[derive(Debug, Clone, Serialize)]
+pub struct Submission {
+    pub language: Language,
+    pub source_code: String,
+    pub test_cases: Vec<TestCase>,
+    pub timeout_ms: u64,
+    /// 0-9, higher first (with the dispatcher)
+    #[serde(skip_serializing_if = "is_zero")]
+    pub priority: u8,
+}
+
+fn is_zero(value: &u8) -> bool {
+    *value == 0
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TestCase {
+    pub input: String,
+    pub expected_output: String,
+    pub weight: u32,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    /// Refused locally: the submission breaks limits from GET /limits (the API's codes)
+    Limit(Violations),
+    /// The API refused the request
+    Api { status: u16, code: String, message: String },
+    /// The request failed or its response couldn't be read
+    Http(reqwest::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Limit(violations) => write!(f, "{}", violations),
+            Error::Api { status, code, message } => write!(f, "{} {}: {}", status, code, message),
+            Error::Http(e) => write!(f, "request failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        Error::Http(e)
+    }
+}
+
+#[derive(Deserialize)]
+struct ErrorBody {
+    error: ErrorDetail,
+}
+
+#[derive(Deserialize)]
+struct ErrorDetail {
+    code: String,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct SubmitResponse {
+    job_id: String,
+}
+
+/// Check a submission against the API's limits with the API's own checks
+pub fn check(report: &LimitsReport, submission: &Submission) -> Result<(), Error> {
+    let test_cases = submission
+        .test_cases
+        .iter()
+        .map(|tc| TestCasePayload {
+            id: None,
+            input: &tc.input,
+            expected_output: &tc.expected_output,
+            weight: tc.weight,
+        })
+        .collect();
+    let payload = Payload {
+        priority: submission.priority,
+        ..Payload::new(submission.language, &submission.source_code, test_cases, submission.timeout_ms)
+    };
+    validate::validate_submission(&payload, report).map_err(Error::Limit)
+}
+
+pub struct Client {
+    http: reqwest::Client,
+    base_url: String,
+    limits: Mutex<Option<(Instant, LimitsReport)>>,
+}
+
+impl Client {
+    /// Client for the API at `base_url` (e.g. http://localhost:8080)
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            limits: Mutex::new(None),
+        }
+    }
+
+    /// The API's limits, cached for LIMITS_TTL
+    pub async fn limits(&self) -> Result<LimitsReport, Error> {
+        if let Some((fetched, report)) = self.limits.lock().unwrap_or_else(|e| e.into_inner()).as_ref() {
+            if fetched.elapsed() < LIMITS_TTL {
+                return Ok(report.clone());
+            }
+        }
+        let response = self.http.get(format!("{}/limits", self.base_url)).send().await?;
+        let report: LimitsReport = Self::read(response).await?;
+        *self.limits.lock().unwrap_or_else(|e| e.into_inner()) = Some((Instant::now(), report.clone()));
+        Ok(report)
+    }
+
+    /// Check the submission locally, then submit it; returns the job id
+    pub async fn submit(&self, submission: &Submission) -> Result<String, Error> {
+        if let Ok(report) = self.limits().await {
+            check(&report, submission)?;
+        }
+        let response = self.http.post(format!("{}/execute", self.base_url)).json(submission).send().await?;
+        match Self::read::<SubmitResponse>(response).await {
+            Ok(accepted) => Ok(accepted.job_id),
+            Err(e) => {
+                if matches!(e, Error::Api { .. }) {
+                    *self.limits.lock().unwrap_or_else(|e| e.into_inner()) = None;
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Body of a successful response, or the API's error
+    async fn read<T: for<'de> Deserialize<'de>>(response: reqwest::Response) -> Result<T, Error> {
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response.json().await?);
+        }
+        let text = response.text().await?;
+        let (code, message) = match serde_json::from_str::<ErrorBody>(&text) {
+            Ok(body) => (body.error.code, body.error.message),
+            Err(_) => ("HTTP_ERROR".to_string(), text),
+        };
+        Err(Error::Api { status: status.as_u16(), code, message })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use optimus_common::limits::Limits;
+
+    fn report() -> LimitsReport {
+        LimitsReport {
+            limits: Limits { max_test_cases: 2, max_source_code_bytes: 20, ..Limits::default() },
+            max_test_weight: 100,
+            max_priority: 9,
+        }
+    }
+
+    fn submission() -> Submission {
+        let test = TestCase { input: "1".to_string(), expected_output: "1".to_string(), weight: 10 };
+        Submission {
+            language: Language::Python,
+            source_code: "print(input())".to_string(),
+            test_cases: vec![test],
+            timeout_ms: 1000,
+            priority: 0,
+        }
+    }
+
+    fn refused(submission: &Submission) -> Option<&'static str> {
+        match check(&report(), submission) {
+            Err(Error::Limit(violations)) => Some(violations.first().code),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_check_uses_reported_limits() {
+        assert!(check(&report(), &submission()).is_ok());
+
+        let mut s = submission();
+        s.source_code = "x".repeat(21);
+        assert_eq!(refused(&s), Some("SOURCE_CODE_TOO_LARGE"));
+
+        let mut s = submission();
+        s.test_cases = vec![s.test_cases[0].clone(); 3];
+        assert_eq!(refused(&s), Some("TOO_MANY_TEST_CASES"));
+
+        let mut s = submission();
+        s.test_cases[0].weight = 101;
+        assert_eq!(refused(&s), Some("INVALID_WEIGHT"));
+
+        let mut s = submission();
+        s.timeout_ms = 0;
+        assert_eq!(refused(&s), Some("INVALID_TIMEOUT"));
+    }
+
+    #[test]
+    fn test_report_round_trips() {
+        // The client reads what the API serves
+        let json = serde_json::to_value(report()).unwrap();
+        assert_eq!(json["max_test_cases"], 2);
+        assert_eq!(json["max_priority"], 9);
+        let parsed: LimitsReport = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, report());
+    }
+}
@@ -0,0 +1,109 @@
+// Worker capability labels and job requirements - jobs only reach workers that can run them
+//
+// Workers advertise labels such as `gpu`, `large-memory` or their architecture
+// (`amd64`, `arm64`) in their heartbeats; a job lists the labels it needs in
+// `metadata.requirements`. Jobs with requirements wait in a queue named after them
+// (`optimus:queue:python:gpu`, `optimus:queue:python:arm64+gpu`), and a worker reads
+// the queues of every requirement set its labels cover, so a job only reaches a
+// worker that can run it.
+
+/// Label of workers with GPUs (languages.json `gpu` block, WORKER_GPU=true)
+pub const GPU: &str = "gpu";
+
+/// Most labels a language may declare; a worker reads 2^n queue pairs for n labels
+pub const MAX_LABELS: usize = 4;
+
+/// Longest label
+const MAX_LABEL_LEN: usize = 32;
+
+/// Words already used as queue key suffixes
+const RESERVED: &[&str] = &["retry", "dlq", "inflight", "paused"];
+
+/// A label is lowercase letters, digits and dashes, starting with a letter
+pub fn check_label(label: &str) -> Result<(), String> {
+    if label.is_empty() || label.len() > MAX_LABEL_LEN {
+        return Err(format!("capability '{}' must be 1-{} characters", label, MAX_LABEL_LEN));
+    }
+    if !label.starts_with(|c: char| c.is_ascii_lowercase())
+        || !label.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    {
+        return Err(format!(
+            "capability '{}' must be lowercase letters, digits and dashes, starting with a letter",
+            label
+        ));
+    }
+    if RESERVED.contains(&label) {
+        return Err(format!("capability '{}' is reserved", label));
+    }
+    Ok(())
+}
+
+/// Labels sorted and without duplicates, so equal sets name the same queue
+pub fn normalize<S: AsRef<str>>(labels: &[S]) -> Vec<String> {
+    let mut labels: Vec<String> = labels.iter().map(|l| l.as_ref().trim().to_ascii_lowercase()).collect();
+    labels.sort();
+    labels.dedup();
+    labels
+}
+
+/// Queue key suffix of a (normalized) requirement set: "" for none, ":arm64+gpu"
+pub fn queue_suffix(requirements: &[String]) -> String {
+    if requirements.is_empty() {
+        String::new()
+    } else {
+        format!(":{}", requirements.join("+"))
+    }
+}
+
+/// Architecture label of this build (`amd64`, `arm64`, or Rust's name for others)
+pub fn arch_label() -> &'static str {
+    match std::env::consts::ARCH {
+        "x86_64" => "amd64",
+        "aarch64" => "arm64",
+        other => other,
+    }
+}
+
+/// Every requirement set a worker with these (normalized) labels can run, most labels
+/// first so specialised workers prefer the jobs only they can take; the empty set is last
+pub fn satisfiable_sets(labels: &[String]) -> Vec<Vec<String>> {
+    let mut sets: Vec<Vec<String>> = (0..1usize << labels.len())
+        .map(|mask| {
+            labels
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| mask & (1 << i) != 0)
+                .map(|(_, label)| label.clone())
+                .collect()
+        })
+        .collect();
+    sets.sort_by(|a: &Vec<String>, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+    sets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_labels() {
+        assert!(check_label("gpu").is_ok());
+        assert!(check_label("large-memory").is_ok());
+        assert!(check_label("arm64").is_ok());
+        for bad in ["", "GPU", "2x", "gpu+arm64", "a:b", "retry", &"x".repeat(33)] {
+            assert!(check_label(bad).is_err(), "{}", bad);
+        }
+        assert_eq!(normalize(&["gpu", "arm64", "GPU "]), vec!["arm64", "gpu"]);
+    }
+
+    #[test]
+    fn test_queue_sets() {
+        assert_eq!(queue_suffix(&[]), "");
+        assert_eq!(queue_suffix(&normalize(&["gpu", "arm64"])), ":arm64+gpu");
+
+        let sets = satisfiable_sets(&normalize(&["gpu", "arm64"]));
+        let suffixes: Vec<String> = sets.iter().map(|set| queue_suffix(set)).collect();
+        assert_eq!(suffixes, [":arm64+gpu", ":arm64", ":gpu", ""]);
+        assert_eq!(satisfiable_sets(&[]), vec![Vec::<String>::new()]);
+    }
+}
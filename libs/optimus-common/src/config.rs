@@ -6,7 +6,6 @@ use std::env;
 pub struct Config {
     pub redis_url: String,
     pub default_timeout_ms: u64,
-    pub max_timeout_ms: u64,
 }
 
 /// Worker concurrency configuration
@@ -20,21 +19,43 @@ pub struct WorkerConfig {
     /// Maximum test cases executing in parallel within a single job
     /// Default: 1 (strict isolation - sequential execution within job)
     pub max_parallel_tests: usize,
+
+    /// Seconds an in-flight job may go without a lease renewal before it is
+    /// considered lost and reclaimed by another worker
+    /// Default: 60
+    pub inflight_lease_secs: u64,
+
+    /// Wall-clock limit for handling one job (execution included); a job still
+    /// running after this is abandoned and fails through the retry path
+    /// Default: 900
+    pub job_max_wall_secs: u64,
+
+    /// After SIGTERM, how long running jobs get to finish before they are put back
+    /// on the queue; keep it below the pod's terminationGracePeriodSeconds
+    /// Default: 270
+    pub shutdown_grace_secs: u64,
+
+    /// GPU worker: offers the `gpu` capability and gives containers GPUs
+    /// Default: false
+    pub gpu: bool,
+
+    /// Capability labels the worker offers (WORKER_CAPABILITIES, comma-separated), plus
+    /// `gpu` on GPU workers and the architecture label; sorted, invalid labels dropped
+    /// Default: the architecture label only
+    pub capabilities: Vec<String>,
 }
 
 impl Config {
     pub fn from_env() -> Self {
         Self {
-            redis_url: env::var("REDIS_URL")
-                .unwrap_or_else(|_| "redis://localhost:6379".to_string()),
+            redis_url: crate::secrets::var("REDIS_URL")
+                .ok()
+                .flatten()
+                .unwrap_or_else(|| "redis://localhost:6379".to_string()),
             default_timeout_ms: env::var("DEFAULT_TIMEOUT_MS")
                 .ok()
                 .and_then(|v| v.parse().ok())
                 .unwrap_or(5000),
-            max_timeout_ms: env::var("MAX_TIMEOUT_MS")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(30000),
         }
     }
 
@@ -49,8 +70,33 @@ impl Default for Config {
     }
 }
 
+/// Labels a worker offers: the listed ones that are valid, `gpu` and the architecture
+fn worker_capabilities(listed: Option<&str>, gpu: bool) -> Vec<String> {
+    let mut labels: Vec<String> = listed
+        .unwrap_or_default()
+        .split(',')
+        .map(|label| label.trim().to_ascii_lowercase())
+        .filter(|label| !label.is_empty())
+        .filter(|label| match crate::capabilities::check_label(label) {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::warn!("Ignoring WORKER_CAPABILITIES entry: {}", e);
+                false
+            }
+        })
+        .collect();
+    if gpu {
+        labels.push(crate::capabilities::GPU.to_string());
+    }
+    labels.push(crate::capabilities::arch_label().to_string());
+    crate::capabilities::normalize(&labels)
+}
+
 impl WorkerConfig {
     pub fn from_env() -> Self {
+        let gpu = env::var("WORKER_GPU")
+            .map(|v| v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
         Self {
             max_parallel_jobs: env::var("MAX_PARALLEL_JOBS")
                 .ok()
@@ -60,6 +106,22 @@ impl WorkerConfig {
                 .ok()
                 .and_then(|v| v.parse().ok())
                 .unwrap_or(1),
+            inflight_lease_secs: env::var("INFLIGHT_LEASE_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|secs: &u64| *secs > 0)
+                .unwrap_or(60),
+            job_max_wall_secs: env::var("JOB_MAX_WALL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|secs: &u64| *secs > 0)
+                .unwrap_or(900),
+            shutdown_grace_secs: env::var("SHUTDOWN_GRACE_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(270),
+            gpu,
+            capabilities: worker_capabilities(env::var("WORKER_CAPABILITIES").ok().as_deref(), gpu),
         }
     }
     
@@ -82,7 +144,6 @@ mod tests {
     fn test_config_defaults() {
         let config = Config::default();
         assert_eq!(config.default_timeout_ms, 5000);
-        assert_eq!(config.max_timeout_ms, 30000);
     }
     
     #[test]
@@ -90,5 +151,17 @@ mod tests {
         let config = WorkerConfig::default();
         assert_eq!(config.max_parallel_jobs, 1);
         assert_eq!(config.max_parallel_tests, 1);
+        assert_eq!(config.inflight_lease_secs, 60);
+        assert_eq!(config.job_max_wall_secs, 900);
+        assert_eq!(config.shutdown_grace_secs, 270);
+        assert!(!config.gpu);
+        assert_eq!(config.capabilities, [crate::capabilities::arch_label()]);
+    }
+
+    #[test]
+    fn test_worker_capabilities() {
+        let mut expected = vec!["gpu", "large-memory", crate::capabilities::arch_label()];
+        expected.sort();
+        assert_eq!(worker_capabilities(Some(" Large-Memory, BAD:LABEL,,gpu"), true), expected);
     }
 }
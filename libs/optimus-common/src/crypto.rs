@@ -0,0 +1,273 @@
+// Encryption at rest - AES-256-GCM envelope encryption for sensitive Redis payloads
+// Source code, test data and captured output are sealed before they are written to
+// queues/results so a shared Redis never holds student code in plaintext.
+//
+// Envelope scheme:
+// - Every payload gets a fresh random data key (DEK)
+// - The payload is encrypted with the DEK, the DEK is wrapped with the master key (KEK)
+// - Only the KEK id, wrapped DEK and ciphertext are stored
+//
+// Keys: OPTIMUS_ENCRYPTION_KEYS (or OPTIMUS_ENCRYPTION_KEYS_FILE, or a secret reference -
+// see secrets.rs) is an ordered list of `key_id:base64(32 bytes)` pairs. The first key seals new data;
+// every listed key can open existing data, so rotation is prepend-then-retire.
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::{Engine as _, engine::general_purpose};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::sync::OnceLock;
+
+/// Prefix identifying sealed values (versioned for future format changes)
+pub const SEALED_PREFIX: &str = "enc:v1";
+
+const KEY_BYTES: usize = 32;
+const NONCE_BYTES: usize = 12;
+
+static GLOBAL_CIPHER: OnceLock<Option<PayloadCipher>> = OnceLock::new();
+
+#[derive(Clone)]
+struct MasterKey {
+    id: String,
+    cipher: Aes256Gcm,
+}
+
+impl fmt::Debug for MasterKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print key material
+        f.debug_struct("MasterKey").field("id", &self.id).finish_non_exhaustive()
+    }
+}
+
+/// Seals and opens payloads with a rotating set of master keys
+#[derive(Debug, Clone)]
+pub struct PayloadCipher {
+    /// keys[0] is the active sealing key
+    keys: Vec<MasterKey>,
+}
+
+impl PayloadCipher {
+    /// Parse a key list of the form `id1:base64key1,id2:base64key2`
+    pub fn from_key_list(spec: &str) -> Result<Self, String> {
+        let mut keys: Vec<MasterKey> = Vec::new();
+
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (id, encoded) = entry
+                .split_once(':')
+                .ok_or_else(|| "Encryption key entries must be 'key_id:base64_key'".to_string())?;
+            let id = id.trim();
+            if id.is_empty() || id.contains(':') {
+                return Err("Encryption key id must be non-empty and contain no ':'".to_string());
+            }
+            let bytes = general_purpose::STANDARD
+                .decode(encoded.trim())
+                .map_err(|_| format!("Encryption key '{}' is not valid base64", id))?;
+            if bytes.len() != KEY_BYTES {
+                return Err(format!(
+                    "Encryption key '{}' must be {} bytes, got {}",
+                    id, KEY_BYTES, bytes.len()
+                ));
+            }
+            if keys.iter().any(|k| k.id == id) {
+                return Err(format!("Duplicate encryption key id '{}'", id));
+            }
+            keys.push(MasterKey {
+                id: id.to_string(),
+                cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&bytes)),
+            });
+        }
+
+        if keys.is_empty() {
+            return Err("No encryption keys configured".to_string());
+        }
+
+        Ok(Self { keys })
+    }
+
+    /// Load from OPTIMUS_ENCRYPTION_KEYS or OPTIMUS_ENCRYPTION_KEYS_FILE
+    /// Returns Ok(None) when encryption is not configured
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let spec = match crate::secrets::var("OPTIMUS_ENCRYPTION_KEYS")? {
+            Some(spec) if !spec.trim().is_empty() => spec,
+            _ => return Ok(None),
+        };
+        Self::from_key_list(&spec).map(Some)
+    }
+
+    /// Id of the key used to seal new payloads
+    pub fn active_key_id(&self) -> &str {
+        &self.keys[0].id
+    }
+
+    /// Encrypt a payload: `enc:v1:{key_id}:{b64(nonce||wrapped_dek)}:{b64(nonce||ciphertext)}`
+    pub fn seal(&self, plaintext: &[u8]) -> Result<String, String> {
+        let master = &self.keys[0];
+
+        // Fresh data key per payload
+        let dek = Aes256Gcm::generate_key(OsRng);
+        let data_cipher = Aes256Gcm::new(&dek);
+        let data_nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = data_cipher
+            .encrypt(&data_nonce, plaintext)
+            .map_err(|_| "Payload encryption failed".to_string())?;
+
+        // Wrap the data key with the master key
+        let wrap_nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let wrapped_dek = master
+            .cipher
+            .encrypt(&wrap_nonce, dek.as_slice())
+            .map_err(|_| "Data key wrapping failed".to_string())?;
+
+        Ok(format!(
+            "{}:{}:{}:{}",
+            SEALED_PREFIX,
+            master.id,
+            general_purpose::STANDARD.encode([wrap_nonce.as_slice(), &wrapped_dek].concat()),
+            general_purpose::STANDARD.encode([data_nonce.as_slice(), &ciphertext].concat()),
+        ))
+    }
+
+    /// Decrypt a value produced by `seal`
+    pub fn open(&self, sealed: &str) -> Result<Vec<u8>, String> {
+        let rest = sealed
+            .strip_prefix(SEALED_PREFIX)
+            .and_then(|r| r.strip_prefix(':'))
+            .ok_or_else(|| "Value is not a sealed payload".to_string())?;
+        let mut parts = rest.splitn(3, ':');
+        let (key_id, wrapped, body) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(k), Some(w), Some(b)) => (k, w, b),
+            _ => return Err("Malformed sealed payload".to_string()),
+        };
+
+        let master = self
+            .keys
+            .iter()
+            .find(|k| k.id == key_id)
+            .ok_or_else(|| format!("Sealed with unknown encryption key '{}'", key_id))?;
+
+        let wrapped = decode_nonce_prefixed(wrapped)?;
+        let (wrap_nonce, wrapped_dek) = wrapped.split_at(NONCE_BYTES);
+        let dek = master
+            .cipher
+            .decrypt(Nonce::from_slice(wrap_nonce), wrapped_dek)
+            .map_err(|_| "Failed to unwrap data key".to_string())?;
+        if dek.len() != KEY_BYTES {
+            return Err("Unwrapped data key has wrong length".to_string());
+        }
+
+        let body = decode_nonce_prefixed(body)?;
+        let (data_nonce, ciphertext) = body.split_at(NONCE_BYTES);
+        Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&dek))
+            .decrypt(Nonce::from_slice(data_nonce), ciphertext)
+            .map_err(|_| "Failed to decrypt payload".to_string())
+    }
+
+    /// Seal a UTF-8 string
+    pub fn seal_str(&self, plaintext: &str) -> Result<String, String> {
+        self.seal(plaintext.as_bytes())
+    }
+
+    /// Open a sealed UTF-8 string
+    pub fn open_str(&self, sealed: &str) -> Result<String, String> {
+        String::from_utf8(self.open(sealed)?)
+            .map_err(|_| "Decrypted payload is not valid UTF-8".to_string())
+    }
+}
+
+/// Whether a stored string is a sealed payload
+pub fn is_sealed(value: &str) -> bool {
+    value.starts_with(SEALED_PREFIX)
+}
+
+/// Hex SHA-256 digest, for comparing sensitive payloads without storing them
+pub fn digest(value: &str) -> String {
+    hex::encode(Sha256::digest(value.as_bytes()))
+}
+
+/// Decode base64(nonce || data), checking there is data after the nonce
+fn decode_nonce_prefixed(encoded: &str) -> Result<Vec<u8>, String> {
+    let bytes = general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(|_| "Sealed payload is not valid base64".to_string())?;
+    if bytes.len() <= NONCE_BYTES {
+        return Err("Sealed payload is truncated".to_string());
+    }
+    Ok(bytes)
+}
+
+/// Load the process-wide cipher from the environment
+/// Call once at startup; a malformed key list is a fatal configuration error
+pub fn init_from_env() -> Result<Option<&'static PayloadCipher>, String> {
+    let cipher = PayloadCipher::from_env()?;
+    Ok(GLOBAL_CIPHER.get_or_init(|| cipher).as_ref())
+}
+
+/// The process-wide cipher, if encryption at rest is enabled
+pub fn global_cipher() -> Option<&'static PayloadCipher> {
+    GLOBAL_CIPHER.get().and_then(|c| c.as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key_b64(byte: u8) -> String {
+        general_purpose::STANDARD.encode([byte; KEY_BYTES])
+    }
+
+    #[test]
+    fn test_seal_open_roundtrip() {
+        let cipher = PayloadCipher::from_key_list(&format!("k1:{}", key_b64(7))).unwrap();
+        let sealed = cipher.seal_str("print(input())").unwrap();
+
+        assert!(is_sealed(&sealed));
+        assert!(sealed.starts_with("enc:v1:k1:"));
+        assert!(!sealed.contains("print"));
+        assert_eq!(cipher.open_str(&sealed).unwrap(), "print(input())");
+    }
+
+    #[test]
+    fn test_each_seal_uses_fresh_keys() {
+        let cipher = PayloadCipher::from_key_list(&format!("k1:{}", key_b64(7))).unwrap();
+        let a = cipher.seal_str("same").unwrap();
+        let b = cipher.seal_str("same").unwrap();
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_tampered_payload_rejected() {
+        let cipher = PayloadCipher::from_key_list(&format!("k1:{}", key_b64(7))).unwrap();
+        let sealed = cipher.seal_str("secret").unwrap();
+
+        let (head, body) = sealed.rsplit_once(':').unwrap();
+        let mut bytes = general_purpose::STANDARD.decode(body).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0x01;
+        let tampered = format!("{}:{}", head, general_purpose::STANDARD.encode(bytes));
+
+        assert!(cipher.open(&tampered).is_err());
+    }
+
+    #[test]
+    fn test_key_rotation() {
+        let old = PayloadCipher::from_key_list(&format!("k1:{}", key_b64(1))).unwrap();
+        let sealed = old.seal_str("data").unwrap();
+
+        let rotated = PayloadCipher::from_key_list(&format!("k2:{},k1:{}", key_b64(2), key_b64(1))).unwrap();
+        assert_eq!(rotated.active_key_id(), "k2");
+        assert_eq!(rotated.open_str(&sealed).unwrap(), "data");
+        assert!(rotated.seal_str("new").unwrap().starts_with("enc:v1:k2:"));
+
+        let retired = PayloadCipher::from_key_list(&format!("k2:{}", key_b64(2))).unwrap();
+        assert!(retired.open(&sealed).unwrap_err().contains("unknown encryption key"));
+    }
+
+    #[test]
+    fn test_invalid_key_lists() {
+        assert!(PayloadCipher::from_key_list("").is_err());
+        assert!(PayloadCipher::from_key_list("k1").is_err());
+        assert!(PayloadCipher::from_key_list("k1:not-base64!").is_err());
+        assert!(PayloadCipher::from_key_list(&format!("k1:{}", general_purpose::STANDARD.encode([0u8; 16]))).is_err());
+        assert!(PayloadCipher::from_key_list(&format!("k1:{},k1:{}", key_b64(1), key_b64(2))).is_err());
+    }
+}
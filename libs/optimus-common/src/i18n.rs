@@ -0,0 +1,173 @@
+// User-facing text per locale - verdict labels and explanations
+//
+// Frontends used to invent their own wording for statuses; the API now sends a label and
+// an explanation with every verdict, in the language picked from Accept-Language. Codes
+// (AC, WA, ...) never change with the locale, so clients can still branch on them.
+//
+// Adding a locale: a Locale variant, its tag in `Locale::tag`/`Locale::from_tag`, and a
+// match arm in each catalog. English is the fallback for anything unmatched.
+
+use crate::types::Verdict;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Locale {
+    #[default]
+    En,
+    Es,
+    Hi,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 3] = [Locale::En, Locale::Es, Locale::Hi];
+
+    /// BCP 47 language tag, as sent in Content-Language
+    pub fn tag(self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Es => "es",
+            Locale::Hi => "hi",
+        }
+    }
+
+    /// Locale for a language tag, ignoring region and case ("es-MX" is Spanish)
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let language = tag.split(['-', '_']).next()?.trim();
+        Self::ALL.into_iter().find(|locale| locale.tag().eq_ignore_ascii_case(language))
+    }
+
+    /// Best supported locale for an Accept-Language header (English when nothing matches)
+    ///
+    /// Ranges are tried by descending q-value, then in header order; `q=0` excludes one.
+    pub fn negotiate(accept_language: Option<&str>) -> Self {
+        let Some(header) = accept_language else { return Self::default() };
+        let mut ranges: Vec<(f32, usize, Locale)> = header
+            .split(',')
+            .enumerate()
+            .filter_map(|(idx, range)| {
+                let mut parts = range.split(';');
+                let locale = Self::from_tag(parts.next()?)?;
+                let q = parts
+                    .find_map(|p| p.trim().strip_prefix("q="))
+                    .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+                (q > 0.0).then_some((q, idx, locale))
+            })
+            .collect();
+        ranges.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+        ranges.first().map(|(_, _, locale)| *locale).unwrap_or_default()
+    }
+}
+
+/// A verdict with its label and explanation in one locale, as the API returns it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VerdictText {
+    pub code: Verdict,
+    pub label: &'static str,
+    pub explanation: &'static str,
+}
+
+impl VerdictText {
+    pub fn new(verdict: Verdict, locale: Locale) -> Self {
+        Self {
+            code: verdict,
+            label: verdict_label(verdict, locale),
+            explanation: verdict_explanation(verdict, locale),
+        }
+    }
+}
+
+/// Short name of a verdict
+pub fn verdict_label(verdict: Verdict, locale: Locale) -> &'static str {
+    use Verdict::*;
+    match locale {
+        Locale::En => match verdict {
+            AC => "Accepted",
+            WA => "Wrong Answer",
+            TLE => "Time Limit Exceeded",
+            MLE => "Memory Limit Exceeded",
+            RE => "Runtime Error",
+            CE => "Compilation Error",
+            IE => "Internal Error",
+        },
+        Locale::Es => match verdict {
+            AC => "Aceptado",
+            WA => "Respuesta incorrecta",
+            TLE => "Tiempo límite excedido",
+            MLE => "Memoria límite excedida",
+            RE => "Error de ejecución",
+            CE => "Error de compilación",
+            IE => "Error interno",
+        },
+        Locale::Hi => match verdict {
+            AC => "स्वीकृत",
+            WA => "गलत उत्तर",
+            TLE => "समय सीमा पार",
+            MLE => "मेमोरी सीमा पार",
+            RE => "रनटाइम त्रुटि",
+            CE => "कंपाइलेशन त्रुटि",
+            IE => "आंतरिक त्रुटि",
+        },
+    }
+}
+
+/// One-sentence explanation of a verdict, for the person who submitted
+pub fn verdict_explanation(verdict: Verdict, locale: Locale) -> &'static str {
+    use Verdict::*;
+    match locale {
+        Locale::En => match verdict {
+            AC => "Your program produced the expected output.",
+            WA => "Your program ran successfully but its output did not match the expected output.",
+            TLE => "Your program did not finish within the time limit.",
+            MLE => "Your program used more memory than allowed and was stopped.",
+            RE => "Your program crashed or exited with a non-zero status.",
+            CE => "Your code could not be compiled; see the compiler output for details.",
+            IE => "The judge could not run your program. This is not your fault; please resubmit.",
+        },
+        Locale::Es => match verdict {
+            AC => "Tu programa produjo la salida esperada.",
+            WA => "Tu programa se ejecutó correctamente, pero su salida no coincide con la esperada.",
+            TLE => "Tu programa no terminó dentro del tiempo límite.",
+            MLE => "Tu programa usó más memoria de la permitida y fue detenido.",
+            RE => "Tu programa falló o terminó con un código distinto de cero.",
+            CE => "Tu código no se pudo compilar; revisa la salida del compilador.",
+            IE => "El juez no pudo ejecutar tu programa. No es tu culpa; vuelve a enviarlo.",
+        },
+        Locale::Hi => match verdict {
+            AC => "आपके प्रोग्राम ने अपेक्षित आउटपुट दिया।",
+            WA => "आपका प्रोग्राम सफलतापूर्वक चला, लेकिन उसका आउटपुट अपेक्षित आउटपुट से मेल नहीं खाया।",
+            TLE => "आपका प्रोग्राम समय सीमा के भीतर पूरा नहीं हुआ।",
+            MLE => "आपके प्रोग्राम ने अनुमत सीमा से अधिक मेमोरी का उपयोग किया और उसे रोक दिया गया।",
+            RE => "आपका प्रोग्राम क्रैश हो गया या शून्य के अलावा किसी स्थिति के साथ समाप्त हुआ।",
+            CE => "आपका कोड कंपाइल नहीं हो सका; विवरण के लिए कंपाइलर आउटपुट देखें।",
+            IE => "जज आपका प्रोग्राम नहीं चला सका। यह आपकी गलती नहीं है; कृपया फिर से सबमिट करें।",
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate_accept_language() {
+        assert_eq!(Locale::negotiate(None), Locale::En);
+        assert_eq!(Locale::negotiate(Some("es-MX,es;q=0.9,en;q=0.8")), Locale::Es);
+        assert_eq!(Locale::negotiate(Some("fr-FR, hi;q=0.5, en;q=0.4")), Locale::Hi);
+        assert_eq!(Locale::negotiate(Some("en;q=0.2, ES;q=0.7")), Locale::Es);
+        // Equal q-values keep header order; q=0 rules a locale out; junk falls back to English
+        assert_eq!(Locale::negotiate(Some("hi, es")), Locale::Hi);
+        assert_eq!(Locale::negotiate(Some("es;q=0, fr")), Locale::En);
+        assert_eq!(Locale::negotiate(Some("es;q=abc, *")), Locale::En);
+    }
+
+    #[test]
+    fn test_every_verdict_has_text_in_every_locale() {
+        use Verdict::*;
+        for locale in Locale::ALL {
+            for verdict in [AC, WA, TLE, MLE, RE, CE, IE] {
+                assert!(!verdict_label(verdict, locale).is_empty());
+                assert!(!verdict_explanation(verdict, locale).is_empty());
+            }
+        }
+    }
+}
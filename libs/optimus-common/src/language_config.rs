@@ -0,0 +1,644 @@
+// Language configuration - the one model of config/languages.json
+//
+// Shared by the CLI (which writes the file), the API and the workers. Files written by
+// older binaries still load:
+// - `resources` and `concurrency` are optional (only the CLI used to write them)
+// - `execution.args` and `queue_name` may be missing (defaults: none, optimus:queue:{name})
+// - `compile` may be missing: java and rust get the compile commands workers used to hard-code
+// - `cpu_limit` is rounded to millicores, dropping the noise of values the CLI stored as f32
+//   (0.1 -> 0.10000000149011612)
+//
+// A profile (dev/staging/prod) overlays `languages.{profile}.json` on the base file:
+// languages are matched by name and merged field by field, the overlay winning.
+
+use crate::language_schema;
+use crate::redis;
+use crate::types::{ComparisonMode, JudgeEnvRelease, Language};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_CONFIG_PATH: &str = "config/languages.json";
+
+/// Environment variable selecting the config profile (each binary's `--env` takes precedence)
+pub const PROFILE_ENV_VAR: &str = "OPTIMUS_ENV";
+
+/// Check a profile name (clap value parser for `--env`)
+///
+/// Names become part of a file name, so only letters, digits, '-' and '_' are allowed:
+/// `../x` or `a/b` would read an overlay from outside the config directory.
+pub fn parse_profile(profile: &str) -> Result<String, String> {
+    let valid = !profile.is_empty() && profile.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'));
+    if !valid {
+        return Err(format!(
+            "invalid config profile {:?}: use letters, digits, '-' and '_'",
+            profile
+        ));
+    }
+    Ok(profile.to_string())
+}
+
+/// Overlay of a profile next to the base file: config/languages.json -> config/languages.prod.json
+pub fn overlay_path(base: &Path, profile: &str) -> PathBuf {
+    let stem = base.file_stem().and_then(|s| s.to_str()).unwrap_or("languages");
+    base.with_file_name(format!("{}.{}.json", stem, profile))
+}
+
+/// Merge a profile overlay into the base file's JSON
+///
+/// Entries of `languages` are matched by `name`: objects merge recursively, any other
+/// value in the overlay replaces the base one. Languages only the overlay has are added.
+pub fn merge_overlay(base: &mut Value, overlay: Value) {
+    let (Some(languages), Value::Object(mut overlay)) = (base.get_mut("languages").and_then(Value::as_array_mut), overlay) else {
+        return;
+    };
+    if let Some(Value::Array(overrides)) = overlay.remove("languages") {
+        for entry in overrides {
+            let existing = languages.iter_mut().find(|l| l.get("name").is_some() && l.get("name") == entry.get("name"));
+            match existing {
+                Some(language) => merge_values(language, entry),
+                None => languages.push(entry),
+            }
+        }
+    }
+    for (key, value) in overlay {
+        match base.get_mut(&key) {
+            Some(existing) => merge_values(existing, value),
+            None => {
+                base[key] = value;
+            }
+        }
+    }
+}
+
+fn merge_values(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_values(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LanguagesFile {
+    pub languages: Vec<LanguageConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanguageConfig {
+    pub name: String,
+    pub version: String,
+    pub image: String,
+    pub dockerfile_path: String,
+    /// Target platforms for `optimus-cli build-image`, e.g. ["linux/amd64", "linux/arm64"]
+    /// (empty: the build machine's own platform)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub platforms: Vec<String>,
+    pub execution: ExecutionConfig,
+    /// Compile step of compile-once execution (absent: interpreted, no compile phase)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compile: Option<CompileSpec>,
+    /// Always optimus:queue:{name}; filled in on load when missing, left out on save
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub queue_name: String,
+    pub memory_limit_mb: u32,
+    /// CPUs per execution container (fractional)
+    #[serde(deserialize_with = "deserialize_cpu_limit")]
+    pub cpu_limit: f64,
+    /// Factor applied to every job's time limit (e.g. 3 for an interpreter), default 1
+    #[serde(default = "default_time_multiplier", skip_serializing_if = "is_default_time_multiplier")]
+    pub time_multiplier: f64,
+    /// Kubernetes requests/limits for the worker pod (rendered by `optimus-cli render-k8s`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resources: Option<Resources>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrency: Option<Concurrency>,
+    /// Judge environment version, bumped by `optimus-cli build-image` (0 = untracked)
+    #[serde(default, skip_serializing_if = "is_untracked")]
+    pub env_version: u32,
+    /// Judge environment changelog, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env_history: Vec<JudgeEnvRelease>,
+    /// Candidate image validated against live traffic in shadow mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub canary: Option<CanaryConfig>,
+    /// Timing-variance reduction for tight time limits
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub determinism: Option<DeterminismConfig>,
+    /// Per-language execution behavior (unset fields keep the worker-wide defaults)
+    #[serde(default, skip_serializing_if = "LanguageFlags::is_default")]
+    pub flags: LanguageFlags,
+    /// GPU execution (absent: jobs of this language can't ask for a GPU)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gpu: Option<GpuConfig>,
+    /// Capability labels beyond `gpu` that jobs of this language may require (e.g.
+    /// `large-memory`, `arm64`); workers started with them in WORKER_CAPABILITIES run those jobs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub capabilities: Vec<String>,
+}
+
+impl LanguageConfig {
+    /// Labels a job of this language may require: `capabilities`, plus `gpu` with a gpu block
+    pub fn requirable_labels(&self) -> Vec<String> {
+        let mut labels = self.capabilities.clone();
+        if self.gpu.is_some() {
+            labels.push(crate::capabilities::GPU.to_string());
+        }
+        crate::capabilities::normalize(&labels)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionConfig {
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    pub file_extension: String,
+}
+
+/// How a compiled language builds the job's source
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompileSpec {
+    /// Shell command run in the job's container once the source is in /code
+    /// (e.g. "javac /code/Main.java"); a non-zero exit fails every test
+    pub command: String,
+    /// Further stages run after `command`, in order (e.g. linking the object file an
+    /// assembler wrote); the first non-zero exit stops the build
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub then: Vec<String>,
+}
+
+impl CompileSpec {
+    /// A single-stage build
+    pub fn new(command: impl Into<String>) -> Self {
+        Self { command: command.into(), then: Vec::new() }
+    }
+
+    /// Every stage as one shell command, with all stages' output on stdout
+    pub fn script(&self) -> String {
+        let stages: Vec<&str> = std::iter::once(self.command.as_str()).chain(self.then.iter().map(String::as_str)).collect();
+        format!("{{ {}; }} 2>&1", stages.join(" && "))
+    }
+}
+
+/// Compile commands of the built-in languages, for configs that don't set `compile`
+/// (written before it was configurable) and workers running without a config
+pub fn legacy_compile(language: &str) -> Option<CompileSpec> {
+    // Assembled, then linked without libc: the program makes its own syscalls
+    if language == "asm" {
+        return Some(CompileSpec {
+            command: "nasm -f elf64 /code/main.asm -o /code/main.o".to_string(),
+            then: vec!["ld /code/main.o -o /code/main".to_string()],
+        });
+    }
+    let command = match language {
+        "java" => "javac /code/Main.java",
+        "rust" => "rustc /code/main.rs -o /code/main",
+        // Bundles the Kotlin runtime, so the jar runs on a plain JVM
+        "kotlin" => "kotlinc /code/Main.kt -include-runtime -d /code/main.jar",
+        "scala" => "scalac -d /code /code/Main.scala",
+        "c" => "gcc -std=c11 -O2 /code/main.c -o /code/main -lm",
+        _ => return None,
+    };
+    Some(CompileSpec::new(command))
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Resources {
+    pub requests: ResourceQuantities,
+    pub limits: ResourceQuantities,
+}
+
+/// Kubernetes quantities, e.g. memory "512Mi", cpu "500m"
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResourceQuantities {
+    pub memory: String,
+    pub cpu: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Concurrency {
+    pub max_parallel_jobs: u32,
+    pub max_parallel_tests: u32,
+}
+
+/// Shadow-run a percentage of jobs on a candidate image before promoting it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CanaryConfig {
+    pub image: String,
+    /// Share of jobs (0-100) also executed on the candidate image
+    pub percent: f64,
+}
+
+/// Execution settings that reduce timing variance (see the worker's determinism module)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeterminismConfig {
+    /// Host CPUs execution containers are pinned to, in Docker cpuset syntax (e.g. "2,3")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpuset: Option<String>,
+    /// Run user programs with address space layout randomization disabled
+    #[serde(default)]
+    pub disable_aslr: bool,
+    /// Value for LANG and LC_ALL (default: C.UTF-8)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    /// Extra fixed environment variables (e.g. PYTHONHASHSEED)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// Share of jobs (0-100) whose first test is repeated to measure timing variance
+    #[serde(default)]
+    pub variance_sample_percent: f64,
+}
+
+/// GPU jobs require the `gpu` capability, which GPU workers (WORKER_GPU=true) offer; those
+/// mount `devices` NVIDIA GPUs into every execution container
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GpuConfig {
+    /// Every job of the language needs a GPU, not only those submitted with `gpu: true`
+    #[serde(default)]
+    pub required: bool,
+    /// GPUs per execution container (1-8)
+    #[serde(default = "default_gpu_devices")]
+    pub devices: u32,
+}
+
+fn default_gpu_devices() -> u32 {
+    1
+}
+
+/// Execution flags tuned per runtime
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LanguageFlags {
+    /// Reuse warm compile-once containers across jobs instead of creating one per job
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub container_pooling: bool,
+    /// Comparison mode for jobs that don't choose one (unset: ordered)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_comparison: Option<ComparisonMode>,
+    #[serde(default, skip_serializing_if = "StderrPolicy::is_default")]
+    pub stderr_policy: StderrPolicy,
+    /// Report known abuse patterns (process spawning, network, system files) with results
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub misuse_detection: bool,
+    /// Run each program once on empty input before the timed tests (warms the file cache);
+    /// jobs can turn it on or off with `warmup`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub warmup: bool,
+}
+
+impl LanguageFlags {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Whether a job gets a warmup run: its own `warmup` setting, else the flag
+    pub fn warmup_for(&self, job_warmup: Option<bool>) -> bool {
+        job_warmup.unwrap_or(self.warmup)
+    }
+}
+
+/// How stderr output of a clean run (exit code 0, in time) affects its verdict
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StderrPolicy {
+    /// Any stderr output fails the test (JVM startup notices excepted)
+    #[default]
+    Fail,
+    /// Judge on stdout alone; stderr is still returned with the result
+    Ignore,
+}
+
+impl StderrPolicy {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+fn default_time_multiplier() -> f64 {
+    1.0
+}
+
+fn is_default_time_multiplier(multiplier: &f64) -> bool {
+    *multiplier == 1.0
+}
+
+/// Time limit a job runs under: the requested one times the language's multiplier, rounded up
+pub fn effective_timeout_ms(requested_ms: u64, multiplier: f64) -> u64 {
+    // Float-to-int casts saturate, so huge limits stay at u64::MAX
+    ((requested_ms as f64 * multiplier).ceil() as u64).max(1)
+}
+
+fn is_untracked(env_version: &u32) -> bool {
+    *env_version == 0
+}
+
+fn deserialize_cpu_limit<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    let cpu = f64::deserialize(deserializer)?;
+    Ok((cpu * 1000.0).round() / 1000.0)
+}
+
+impl LanguageConfig {
+    /// Pod resources, derived from the container limits when the file has none
+    ///
+    /// Requests are 2x the container memory and 1x its CPU; limits are 4x both.
+    pub fn resources_or_default(&self) -> Resources {
+        self.resources.clone().unwrap_or_else(|| Resources {
+            requests: ResourceQuantities {
+                memory: format!("{}Mi", self.memory_limit_mb * 2),
+                cpu: format!("{}m", (self.cpu_limit * 1000.0).round() as u32),
+            },
+            limits: ResourceQuantities {
+                memory: format!("{}Gi", (self.memory_limit_mb as f64 * 4.0 / 1024.0).ceil() as u32),
+                cpu: format!("{}m", (self.cpu_limit * 4000.0).round() as u32),
+            },
+        })
+    }
+
+    /// Worker concurrency, sized by container memory when the file has none
+    pub fn concurrency_or_default(&self) -> Concurrency {
+        self.concurrency.unwrap_or(if self.memory_limit_mb >= 512 {
+            Concurrency { max_parallel_jobs: 2, max_parallel_tests: 3 }
+        } else {
+            Concurrency { max_parallel_jobs: 3, max_parallel_tests: 5 }
+        })
+    }
+}
+
+impl LanguagesFile {
+    /// Read, validate and parse a languages.json file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Self::parse(&content).map_err(|e| format!("Invalid {}: {}", path.display(), e))
+    }
+
+    /// Load the base file with a profile's overlay merged in (just the base file without a profile)
+    ///
+    /// The overlay of a selected profile must exist; only the merged result is validated,
+    /// so overlays list just the fields they change.
+    pub fn load_profile<P: AsRef<Path>>(path: P, profile: Option<&str>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let Some(profile) = profile else { return Self::load(path) };
+        parse_profile(profile)?;
+        let overlay_path = overlay_path(path, profile);
+
+        let read = |path: &Path| -> Result<Value, String> {
+            let content = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            serde_json::from_str(&content).map_err(|e| format!("Invalid {}: {}", path.display(), e))
+        };
+        let mut value = read(path)?;
+        merge_overlay(&mut value, read(&overlay_path)?);
+        Self::from_value(value)
+            .map_err(|e| format!("Invalid {} with {}: {}", path.display(), overlay_path.display(), e))
+    }
+
+    /// Validate and parse languages.json content
+    pub fn parse(content: &str) -> Result<Self, String> {
+        language_schema::validate_str(content)?;
+        let file: Self = serde_json::from_str(content).map_err(|e| e.to_string())?;
+        Ok(file.with_defaults())
+    }
+
+    fn from_value(value: Value) -> Result<Self, String> {
+        language_schema::validate(&value).map_err(|errors| language_schema::format_errors(&errors))?;
+        let file: Self = serde_json::from_value(value).map_err(|e| e.to_string())?;
+        Ok(file.with_defaults())
+    }
+
+    fn with_defaults(mut self) -> Self {
+        for language in &mut self.languages {
+            if language.queue_name.is_empty() {
+                language.queue_name = redis::canonical_queue_name(&language.name);
+            }
+            if language.compile.is_none() {
+                language.compile = legacy_compile(&language.name);
+            }
+        }
+        self
+    }
+
+    /// Validate and write the file (pretty-printed), creating its directory if needed
+    ///
+    /// Queue names the loader derives are not written, so the file keeps working under
+    /// any OPTIMUS_KEY_PREFIX.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        let path = path.as_ref();
+        let mut file = self.clone();
+        for language in &mut file.languages {
+            if language.queue_name == redis::canonical_queue_name(&language.name) {
+                language.queue_name.clear();
+            }
+        }
+        let value = serde_json::to_value(&file).map_err(|e| e.to_string())?;
+        language_schema::validate(&value).map_err(|errors| {
+            format!("Refusing to write invalid {}: {}", path.display(), language_schema::format_errors(&errors))
+        })?;
+
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        // Serialize the struct, not the Value, to keep the field order stable
+        let content = serde_json::to_string_pretty(&file).map_err(|e| e.to_string())?;
+        fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&LanguageConfig> {
+        self.languages.iter().find(|l| l.name == name)
+    }
+
+    /// Cross-check every `queue_name` against the queue the API enqueues the language on
+    /// (`redis::queue_name`); one line per mismatch. The API and workers refuse to start
+    /// on any, since those jobs would never be picked up.
+    pub fn check_queue_names(&self) -> Result<(), String> {
+        let mismatches: Vec<String> = self
+            .languages
+            .iter()
+            .filter_map(|config| {
+                let language = Language::from_str(&config.name)?;
+                let expected = redis::queue_name(&language);
+                (config.queue_name != expected).then(|| {
+                    format!(
+                        "{}: queue_name is {:?}, but jobs for {} are queued on {:?}",
+                        config.name, config.queue_name, language, expected
+                    )
+                })
+            })
+            .collect();
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(mismatches.join("\n"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loads_shipped_config() {
+        let file = LanguagesFile::load("../../config/languages.json").unwrap();
+        let python = file.get("python").unwrap();
+        assert_eq!(python.queue_name, "optimus:queue:python");
+        assert_eq!(python.cpu_limit, 0.5);
+        assert_eq!(python.concurrency_or_default().max_parallel_jobs, 34);
+        assert_eq!(python.compile, None);
+        assert_eq!(file.get("java").unwrap().compile.as_ref().unwrap().command, "javac /code/Main.java");
+        assert_eq!(file.get("asm").unwrap().compile, legacy_compile("asm"));
+    }
+
+    #[test]
+    fn test_compile_stages_run_in_order() {
+        assert_eq!(CompileSpec::new("javac /code/Main.java").script(), "{ javac /code/Main.java; } 2>&1");
+        assert_eq!(
+            legacy_compile("asm").unwrap().script(),
+            "{ nasm -f elf64 /code/main.asm -o /code/main.o && ld /code/main.o -o /code/main; } 2>&1"
+        );
+        // Single-stage specs are written as before
+        let json = serde_json::to_value(CompileSpec::new("javac /code/Main.java")).unwrap();
+        assert_eq!(json, serde_json::json!({ "command": "javac /code/Main.java" }));
+    }
+
+    #[test]
+    fn test_loads_minimal_and_f32_files() {
+        let file = LanguagesFile::parse(
+            r#"{"languages": [{"name": "rust", "version": "1.80", "image": "optimus-rust:1.80",
+                "dockerfile_path": "dockerfiles/rust/Dockerfile",
+                "execution": {"command": "rustc", "file_extension": ".rs"},
+                "memory_limit_mb": 512, "cpu_limit": 0.10000000149011612}]}"#,
+        )
+        .unwrap();
+        let rust = &file.languages[0];
+        assert_eq!(rust.queue_name, "optimus:queue:rust");
+        assert!(rust.execution.args.is_empty());
+        assert_eq!(rust.cpu_limit, 0.1);
+        // Written before `compile` existed
+        assert_eq!(rust.compile, legacy_compile("rust"));
+        assert_eq!(rust.resources_or_default().limits, ResourceQuantities { memory: "2Gi".into(), cpu: "400m".into() });
+        assert_eq!(rust.concurrency_or_default(), Concurrency { max_parallel_jobs: 2, max_parallel_tests: 3 });
+        assert_eq!(rust.time_multiplier, 1.0);
+        assert!(serde_json::to_value(rust).unwrap().get("time_multiplier").is_none());
+    }
+
+    #[test]
+    fn test_effective_timeout() {
+        assert_eq!(effective_timeout_ms(1000, 1.0), 1000);
+        assert_eq!(effective_timeout_ms(1000, 2.5), 2500);
+        assert_eq!(effective_timeout_ms(333, 1.5), 500);
+        assert_eq!(effective_timeout_ms(1, 0.1), 1);
+        assert_eq!(effective_timeout_ms(u64::MAX, 3.0), u64::MAX);
+    }
+
+    #[test]
+    fn test_save_round_trips_and_validates() {
+        let dir = std::env::temp_dir().join(format!("optimus-langs-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("languages.json");
+        let mut file = LanguagesFile::load("../../config/languages.json").unwrap();
+        file.save(&path).unwrap();
+        assert_eq!(LanguagesFile::load(&path).unwrap().languages.len(), file.languages.len());
+        // Derived queue names stay derived, so the file follows OPTIMUS_KEY_PREFIX
+        assert!(!fs::read_to_string(&path).unwrap().contains("queue_name"));
+
+        file.languages[0].cpu_limit = 0.0;
+        let error = file.save(&path).unwrap_err();
+        assert!(error.contains("languages[0].cpu_limit"), "{}", error);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_queue_names_cross_checked() {
+        // The default queue is the one the enum's language is queued on
+        let mut file = LanguagesFile::parse(
+            r#"{"languages": [{"name": "python", "version": "3", "image": "optimus-python:3",
+                "dockerfile_path": "dockerfiles/python/Dockerfile",
+                "execution": {"command": "python", "file_extension": ".py"},
+                "memory_limit_mb": 256, "cpu_limit": 0.5}]}"#,
+        )
+        .unwrap();
+        assert_eq!(file.languages[0].queue_name, "optimus:queue:python");
+        assert!(file.check_queue_names().is_ok());
+        assert!(LanguagesFile::load("../../config/languages.json").unwrap().check_queue_names().is_ok());
+        // The shipped file leaves every queue name to the loader
+        assert!(!fs::read_to_string("../../config/languages.json").unwrap().contains("queue_name"));
+
+        file.languages[0].queue_name = "optimus:queue:Python".to_string();
+        let error = file.check_queue_names().unwrap_err();
+        assert!(error.contains("\"optimus:queue:python\""), "{}", error);
+    }
+
+    #[test]
+    fn test_flags() {
+        let file = LanguagesFile::parse(
+            r#"{"languages": [{"name": "python", "version": "3.12", "image": "optimus-python:latest",
+                "dockerfile_path": "dockerfiles/python/Dockerfile",
+                "execution": {"command": "python", "file_extension": ".py"},
+                "memory_limit_mb": 256, "cpu_limit": 0.5,
+                "flags": {"container_pooling": true, "default_comparison": "unordered", "stderr_policy": "ignore"}}]}"#,
+        )
+        .unwrap();
+        let flags = file.languages[0].flags;
+        assert!(flags.container_pooling);
+        assert_eq!(flags.default_comparison, Some(ComparisonMode::Unordered));
+        assert_eq!(flags.stderr_policy, StderrPolicy::Ignore);
+
+        let unset = LanguageFlags::default();
+        assert!(!unset.warmup_for(None));
+        assert!(unset.warmup_for(Some(true)));
+        assert!(!LanguageFlags { warmup: true, ..unset }.warmup_for(Some(false)));
+        assert!(serde_json::to_value(&file.languages[0]).unwrap().get("flags").is_some());
+        assert!(LanguagesFile::load("../../config/languages.json").unwrap().languages.iter().all(|l| l.flags.is_default()));
+    }
+
+    #[test]
+    fn test_profile_overlay() {
+        let dir = std::env::temp_dir().join(format!("optimus-langs-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("languages.json");
+        fs::copy("../../config/languages.json", &path).unwrap();
+        fs::write(
+            overlay_path(&path, "dev"),
+            r#"{"languages": [{"name": "python", "memory_limit_mb": 128, "resources": {"limits": {"cpu": "250m"}}}]}"#,
+        )
+        .unwrap();
+        fs::write(overlay_path(&path, "broken"), r#"{"languages": [{"name": "java", "cpu_limit": 0}]}"#).unwrap();
+
+        let base = LanguagesFile::load(&path).unwrap();
+        let dev = LanguagesFile::load_profile(&path, Some("dev")).unwrap();
+        let (python, base_python) = (dev.get("python").unwrap(), base.get("python").unwrap());
+        assert_eq!(python.memory_limit_mb, 128);
+        assert_eq!(python.resources.as_ref().unwrap().limits.cpu, "250m");
+        assert_eq!(python.resources.as_ref().unwrap().limits.memory, base_python.resources.as_ref().unwrap().limits.memory);
+        assert_eq!(python.image, base_python.image);
+        assert_eq!(dev.languages.len(), base.languages.len());
+
+        let error = LanguagesFile::load_profile(&path, Some("broken")).unwrap_err();
+        assert!(error.contains("languages[1].cpu_limit"), "{}", error);
+        assert!(LanguagesFile::load_profile(&path, Some("prod")).is_err());
+        assert_eq!(LanguagesFile::load_profile(&path, None).unwrap().languages.len(), base.languages.len());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_profile_names() {
+        assert_eq!(parse_profile("prod"), Ok("prod".to_string()));
+        assert!(parse_profile("eu_west-1").is_ok());
+        for invalid in ["", "../prod", "a/b", "..", "prod.json", "c:\\x"] {
+            assert!(parse_profile(invalid).is_err(), "{:?}", invalid);
+        }
+        assert!(LanguagesFile::load_profile(DEFAULT_CONFIG_PATH, Some("../../etc/x")).unwrap_err().contains("invalid config profile"));
+        assert_eq!(overlay_path(Path::new(DEFAULT_CONFIG_PATH), "prod"), Path::new("config/languages.prod.json"));
+    }
+}
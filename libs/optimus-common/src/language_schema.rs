@@ -0,0 +1,564 @@
+// languages.json schema - structural validation with field-level errors
+//
+// Checked against the raw JSON before deserialization, so a bad file is reported as
+// `languages[1].cpu_limit: must be between 0 (exclusive) and 64, got 0` instead of a
+// generic serde error. Every problem in the file is reported at once.
+//
+// Applied by the CLI before writing the file and by the API and workers on load.
+// Unknown fields are ignored, so newer files still load in older binaries.
+
+use crate::capabilities;
+use crate::redis::canonical_queue_name;
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::fmt;
+
+pub const MIN_MEMORY_LIMIT_MB: u64 = 16;
+pub const MAX_MEMORY_LIMIT_MB: u64 = 65_536;
+pub const MAX_CPU_LIMIT: f64 = 64.0;
+pub const MAX_GPU_DEVICES: u64 = 8;
+pub const MAX_TIME_MULTIPLIER: f64 = 10.0;
+
+/// One problem, located by its path in the file (e.g. `languages[0].execution.command`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaError {
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Parse and validate languages.json content
+/// The error lists every problem, one per line
+pub fn validate_str(content: &str) -> Result<(), String> {
+    let value: Value = serde_json::from_str(content).map_err(|e| {
+        format!("not valid JSON (line {}, column {}): {}", e.line(), e.column(), e)
+    })?;
+    validate(&value).map_err(|errors| format_errors(&errors))
+}
+
+/// Render errors as "N problem(s):" followed by one indented line each
+pub fn format_errors(errors: &[SchemaError]) -> String {
+    let mut out = format!("{} problem(s):", errors.len());
+    for error in errors {
+        out.push_str("\n  - ");
+        out.push_str(&error.to_string());
+    }
+    out
+}
+
+/// Validate a parsed languages.json document
+pub fn validate(value: &Value) -> Result<(), Vec<SchemaError>> {
+    let mut checker = Checker::default();
+    match value.as_object() {
+        Some(root) => {
+            if let Some(languages) = checker.array(root, "", "languages", true) {
+                let mut names = HashSet::new();
+                for (i, language) in languages.iter().enumerate() {
+                    let path = format!("languages[{}]", i);
+                    match language.as_object() {
+                        Some(language) => checker.language(language, &path, &mut names),
+                        None => checker.error(&path, format!("expected an object, got {}", kind(language))),
+                    }
+                }
+            }
+        }
+        None => checker.error("(root)", format!("expected an object with a \"languages\" list, got {}", kind(value))),
+    }
+
+    if checker.errors.is_empty() {
+        Ok(())
+    } else {
+        Err(checker.errors)
+    }
+}
+
+#[derive(Default)]
+struct Checker {
+    errors: Vec<SchemaError>,
+}
+
+impl Checker {
+    fn error(&mut self, path: &str, message: impl Into<String>) {
+        self.errors.push(SchemaError { path: path.to_string(), message: message.into() });
+    }
+
+    fn language(&mut self, obj: &Map<String, Value>, path: &str, names: &mut HashSet<String>) {
+        let name = self.string(obj, path, "name", true);
+        if let Some(name) = name {
+            if !is_language_name(name) {
+                self.error(&join(path, "name"), format!("must be lowercase letters, digits, '_', '+' or '-' starting with a letter, got {:?}", name));
+            } else if !names.insert(name.to_string()) {
+                self.error(&join(path, "name"), format!("language {:?} is configured more than once", name));
+            }
+        }
+
+        self.string(obj, path, "version", true);
+        self.string(obj, path, "dockerfile_path", true);
+        if let Some(image) = self.string(obj, path, "image", true) {
+            if let Err(e) = check_image_ref(image) {
+                self.error(&join(path, "image"), e);
+            }
+        }
+
+        if let Some(execution) = self.object(obj, path, "execution", true) {
+            let exec_path = join(path, "execution");
+            self.string(execution, &exec_path, "command", true);
+            if let Some(args) = self.array(execution, &exec_path, "args", false) {
+                for (i, arg) in args.iter().enumerate() {
+                    if !arg.is_string() {
+                        self.error(&format!("{}.args[{}]", exec_path, i), format!("expected a string, got {}", kind(arg)));
+                    }
+                }
+            }
+            if let Some(ext) = self.string(execution, &exec_path, "file_extension", true) {
+                if !ext.starts_with('.') || ext.len() < 2 {
+                    self.error(&join(&exec_path, "file_extension"), format!("must start with '.' (e.g. \".py\"), got {:?}", ext));
+                }
+            }
+        }
+
+        // Optional: interpreted languages have no compile step
+        if let Some(compile) = self.object(obj, path, "compile", false) {
+            let compile_path = join(path, "compile");
+            self.string(compile, &compile_path, "command", true);
+            if let Some(stages) = self.array(compile, &compile_path, "then", false) {
+                for (i, stage) in stages.iter().enumerate() {
+                    match stage.as_str() {
+                        Some(stage) if !stage.trim().is_empty() => {}
+                        Some(_) => self.error(&format!("{}.then[{}]", compile_path, i), "must not be empty"),
+                        None => self.error(&format!("{}.then[{}]", compile_path, i), format!("expected a string, got {}", kind(stage))),
+                    }
+                }
+            }
+        }
+
+        // Optional: missing means the default queue (see language_config)
+        if let Some(queue) = self.string(obj, path, "queue_name", false) {
+            if let Some(name) = name {
+                let expected = canonical_queue_name(name);
+                if queue != expected {
+                    self.error(
+                        &join(path, "queue_name"),
+                        format!("must be {:?} - the API always enqueues there, got {:?}", expected, queue),
+                    );
+                }
+            }
+        }
+
+        self.integer(obj, path, "memory_limit_mb", true, MIN_MEMORY_LIMIT_MB, MAX_MEMORY_LIMIT_MB);
+        if let Some(cpu) = self.number(obj, path, "cpu_limit", true) {
+            if !(cpu > 0.0 && cpu <= MAX_CPU_LIMIT) {
+                self.error(&join(path, "cpu_limit"), format!("must be between 0 (exclusive) and {}, got {}", MAX_CPU_LIMIT, cpu));
+            }
+        }
+        if let Some(multiplier) = self.number(obj, path, "time_multiplier", false) {
+            if !(multiplier > 0.0 && multiplier <= MAX_TIME_MULTIPLIER) {
+                self.error(&join(path, "time_multiplier"), format!("must be between 0 (exclusive) and {}, got {}", MAX_TIME_MULTIPLIER, multiplier));
+            }
+        }
+        self.integer(obj, path, "env_version", false, 0, u32::MAX as u64);
+        self.array(obj, path, "env_history", false);
+
+        if let Some(canary) = self.object(obj, path, "canary", false) {
+            let canary_path = join(path, "canary");
+            if let Some(image) = self.string(canary, &canary_path, "image", true) {
+                if let Err(e) = check_image_ref(image) {
+                    self.error(&join(&canary_path, "image"), e);
+                }
+            }
+            self.percent(canary, &canary_path, "percent", true);
+        }
+
+        if let Some(determinism) = self.object(obj, path, "determinism", false) {
+            let det_path = join(path, "determinism");
+            if let Some(cpuset) = self.string(determinism, &det_path, "cpuset", false) {
+                if cpuset.is_empty() || !cpuset.chars().all(|c| c.is_ascii_digit() || c == ',' || c == '-') {
+                    self.error(&join(&det_path, "cpuset"), format!("must be a Docker cpuset like \"2,3\" or \"0-3\", got {:?}", cpuset));
+                }
+            }
+            self.string(determinism, &det_path, "locale", false);
+            if let Some(env) = self.object(determinism, &det_path, "env", false) {
+                for (key, value) in env {
+                    if !value.is_string() {
+                        self.error(&format!("{}.env.{}", det_path, key), format!("expected a string, got {}", kind(value)));
+                    }
+                }
+            }
+            self.percent(determinism, &det_path, "variance_sample_percent", false);
+        }
+
+        if let Some(gpu) = self.object(obj, path, "gpu", false) {
+            let gpu_path = join(path, "gpu");
+            self.boolean(gpu, &gpu_path, "required");
+            self.integer(gpu, &gpu_path, "devices", false, 1, MAX_GPU_DEVICES);
+        }
+
+        if let Some(labels) = self.array(obj, path, "capabilities", false) {
+            if labels.len() > capabilities::MAX_LABELS {
+                self.error(&join(path, "capabilities"), format!("at most {} labels, got {}", capabilities::MAX_LABELS, labels.len()));
+            }
+            for (i, label) in labels.iter().enumerate() {
+                let label_path = format!("{}.capabilities[{}]", path, i);
+                match label.as_str() {
+                    Some(capabilities::GPU) => self.error(&label_path, "`gpu` comes from the gpu block; remove it here"),
+                    Some(label) => {
+                        if let Err(e) = capabilities::check_label(label) {
+                            self.error(&label_path, e);
+                        }
+                    }
+                    None => self.error(&label_path, format!("expected a string, got {}", kind(label))),
+                }
+            }
+        }
+
+        if let Some(resources) = self.object(obj, path, "resources", false) {
+            let res_path = join(path, "resources");
+            for section in ["requests", "limits"] {
+                if let Some(quantities) = self.object(resources, &res_path, section, true) {
+                    let section_path = join(&res_path, section);
+                    for field in ["memory", "cpu"] {
+                        if let Some(quantity) = self.string(quantities, &section_path, field, true) {
+                            if !is_quantity(quantity) {
+                                self.error(&join(&section_path, field), format!("must be a Kubernetes quantity like \"512Mi\" or \"500m\", got {:?}", quantity));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(concurrency) = self.object(obj, path, "concurrency", false) {
+            let conc_path = join(path, "concurrency");
+            self.integer(concurrency, &conc_path, "max_parallel_jobs", true, 1, u32::MAX as u64);
+            self.integer(concurrency, &conc_path, "max_parallel_tests", true, 1, u32::MAX as u64);
+        }
+
+        if let Some(platforms) = self.array(obj, path, "platforms", false) {
+            for (i, platform) in platforms.iter().enumerate() {
+                if !platform.as_str().is_some_and(is_platform) {
+                    self.error(&format!("{}.platforms[{}]", path, i), format!("must be a platform like \"linux/amd64\" or \"linux/arm64/v8\", got {}", platform));
+                }
+            }
+        }
+
+        if let Some(flags) = self.object(obj, path, "flags", false) {
+            let flags_path = join(path, "flags");
+            self.boolean(flags, &flags_path, "container_pooling");
+            self.one_of(flags, &flags_path, "default_comparison", &["ordered", "unordered", "strict"]);
+            self.one_of(flags, &flags_path, "stderr_policy", &["fail", "ignore"]);
+            self.boolean(flags, &flags_path, "misuse_detection");
+            self.boolean(flags, &flags_path, "warmup");
+        }
+    }
+
+    /// Look up a field, reporting it if required and missing (null counts as missing)
+    fn field<'a>(&mut self, obj: &'a Map<String, Value>, path: &str, key: &str, required: bool) -> Option<&'a Value> {
+        match obj.get(key).filter(|v| !v.is_null()) {
+            Some(value) => Some(value),
+            None => {
+                if required {
+                    self.error(&join(path, key), "missing required field");
+                }
+                None
+            }
+        }
+    }
+
+    fn string<'a>(&mut self, obj: &'a Map<String, Value>, path: &str, key: &str, required: bool) -> Option<&'a str> {
+        let value = self.field(obj, path, key, required)?;
+        match value.as_str() {
+            Some(s) if s.trim().is_empty() && required => {
+                self.error(&join(path, key), "must not be empty");
+                None
+            }
+            Some(s) => Some(s),
+            None => {
+                self.error(&join(path, key), format!("expected a string, got {}", kind(value)));
+                None
+            }
+        }
+    }
+
+    fn number(&mut self, obj: &Map<String, Value>, path: &str, key: &str, required: bool) -> Option<f64> {
+        let value = self.field(obj, path, key, required)?;
+        let number = value.as_f64();
+        if number.is_none() {
+            self.error(&join(path, key), format!("expected a number, got {}", kind(value)));
+        }
+        number
+    }
+
+    fn integer(&mut self, obj: &Map<String, Value>, path: &str, key: &str, required: bool, min: u64, max: u64) {
+        let Some(value) = self.field(obj, path, key, required) else { return };
+        match value.as_u64() {
+            Some(n) if (min..=max).contains(&n) => {}
+            _ if value.is_number() => {
+                self.error(&join(path, key), format!("must be a whole number between {} and {}, got {}", min, max, value));
+            }
+            _ => self.error(&join(path, key), format!("expected a whole number, got {}", kind(value))),
+        }
+    }
+
+    fn percent(&mut self, obj: &Map<String, Value>, path: &str, key: &str, required: bool) {
+        if let Some(percent) = self.number(obj, path, key, required) {
+            if !(0.0..=100.0).contains(&percent) {
+                self.error(&join(path, key), format!("must be between 0 and 100, got {}", percent));
+            }
+        }
+    }
+
+    fn boolean(&mut self, obj: &Map<String, Value>, path: &str, key: &str) -> Option<bool> {
+        let value = self.field(obj, path, key, false)?;
+        let boolean = value.as_bool();
+        if boolean.is_none() {
+            self.error(&join(path, key), format!("expected true or false, got {}", kind(value)));
+        }
+        boolean
+    }
+
+    fn one_of(&mut self, obj: &Map<String, Value>, path: &str, key: &str, allowed: &[&str]) {
+        if let Some(value) = self.string(obj, path, key, false) {
+            if !allowed.contains(&value) {
+                self.error(&join(path, key), format!("must be one of {}, got {:?}", allowed.join(", "), value));
+            }
+        }
+    }
+
+    fn object<'a>(&mut self, obj: &'a Map<String, Value>, path: &str, key: &str, required: bool) -> Option<&'a Map<String, Value>> {
+        let value = self.field(obj, path, key, required)?;
+        let object = value.as_object();
+        if object.is_none() {
+            self.error(&join(path, key), format!("expected an object, got {}", kind(value)));
+        }
+        object
+    }
+
+    fn array<'a>(&mut self, obj: &'a Map<String, Value>, path: &str, key: &str, required: bool) -> Option<&'a Vec<Value>> {
+        let value = self.field(obj, path, key, required)?;
+        let array = value.as_array();
+        if array.is_none() {
+            self.error(&join(path, key), format!("expected a list, got {}", kind(value)));
+        }
+        array
+    }
+}
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+/// JSON type name with the value for scalars, for "expected X, got Y" messages
+fn kind(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => format!("boolean {}", b),
+        Value::Number(n) => format!("number {}", n),
+        Value::String(s) => format!("string {:?}", s),
+        Value::Array(_) => "a list".to_string(),
+        Value::Object(_) => "an object".to_string(),
+    }
+}
+
+fn is_language_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '+' | '-'))
+}
+
+/// Check a Docker image reference: `[registry[:port]/]path[:tag][@sha256:digest]`
+pub fn check_image_ref(image: &str) -> Result<(), String> {
+    let (reference, digest) = match image.split_once('@') {
+        Some((reference, digest)) => (reference, Some(digest)),
+        None => (image, None),
+    };
+    if let Some(digest) = digest {
+        let valid = digest
+            .strip_prefix("sha256:")
+            .is_some_and(|hex| hex.len() == 64 && hex.chars().all(|c| c.is_ascii_hexdigit()));
+        if !valid {
+            return Err(format!("invalid image digest in {:?} (expected @sha256:<64 hex chars>)", image));
+        }
+    }
+
+    // A ':' after the last '/' starts the tag; earlier ones belong to a registry port
+    let last_slash = reference.rfind('/').map_or(0, |i| i + 1);
+    let (name, tag) = match reference[last_slash..].rfind(':') {
+        Some(i) => (&reference[..last_slash + i], Some(&reference[last_slash + i + 1..])),
+        None => (reference, None),
+    };
+    if let Some(tag) = tag {
+        let valid = !tag.is_empty()
+            && tag.len() <= 128
+            && !tag.starts_with(['.', '-'])
+            && tag.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'));
+        if !valid {
+            return Err(format!("invalid image tag {:?} in {:?}", tag, image));
+        }
+    }
+
+    let mut components: Vec<&str> = name.split('/').collect();
+    let has_registry = components.len() > 1
+        && (components[0].contains(['.', ':']) || components[0] == "localhost");
+    if has_registry {
+        let registry = components.remove(0);
+        let (host, port) = match registry.split_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (registry, None),
+        };
+        let host_ok = !host.is_empty() && host.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-'));
+        let port_ok = port.is_none_or(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()));
+        if !host_ok || !port_ok {
+            return Err(format!("invalid registry {:?} in image {:?}", registry, image));
+        }
+    }
+    for component in components {
+        let valid = component.starts_with(|c: char| c.is_ascii_lowercase() || c.is_ascii_digit())
+            && component.ends_with(|c: char| c.is_ascii_lowercase() || c.is_ascii_digit())
+            && component.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '.' | '_' | '-'));
+        if !valid {
+            return Err(format!(
+                "invalid image name {:?}: name components must be lowercase letters, digits, '.', '_' or '-'",
+                image
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// os/arch[/variant], as accepted by `docker buildx build --platform`
+fn is_platform(platform: &str) -> bool {
+    let parts: Vec<&str> = platform.split('/').collect();
+    (2..=3).contains(&parts.len())
+        && parts
+            .iter()
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_'))
+}
+
+/// Kubernetes resource quantity, e.g. "500m", "2", "1.5", "512Mi", "1G"
+fn is_quantity(quantity: &str) -> bool {
+    const SUFFIXES: [&str; 13] = ["Ki", "Mi", "Gi", "Ti", "Pi", "Ei", "m", "k", "M", "G", "T", "P", "E"];
+    let number = SUFFIXES
+        .iter()
+        .find_map(|suffix| quantity.strip_suffix(suffix))
+        .unwrap_or(quantity);
+    let (whole, fraction) = match number.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (number, None),
+    };
+    !whole.is_empty()
+        && whole.chars().all(|c| c.is_ascii_digit())
+        && fraction.is_none_or(|f| !f.is_empty() && f.chars().all(|c| c.is_ascii_digit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn python() -> Value {
+        json!({
+            "name": "python",
+            "version": "3.12",
+            "image": "optimus-python:latest",
+            "dockerfile_path": "dockerfiles/python/Dockerfile",
+            "execution": { "command": "python", "args": [], "file_extension": ".py" },
+            "queue_name": "optimus:queue:python",
+            "memory_limit_mb": 256,
+            "cpu_limit": 0.5
+        })
+    }
+
+    fn paths(errors: Vec<SchemaError>) -> Vec<String> {
+        errors.into_iter().map(|e| e.path).collect()
+    }
+
+    #[test]
+    fn test_shipped_config_is_valid() {
+        let content = std::fs::read_to_string("../../config/languages.json").unwrap();
+        assert_eq!(validate_str(&content), Ok(()));
+    }
+
+    #[test]
+    fn test_errors_name_the_field() {
+        let mut bad = python();
+        bad["cpu_limit"] = json!(0);
+        bad["memory_limit_mb"] = json!("512");
+        bad["execution"].as_object_mut().unwrap().remove("command");
+        bad["queue_name"] = json!("jobs:python");
+        let errors = validate(&json!({ "languages": [python(), bad] })).unwrap_err();
+
+        assert_eq!(
+            paths(errors.clone()),
+            vec![
+                "languages[1].name",
+                "languages[1].execution.command",
+                "languages[1].queue_name",
+                "languages[1].memory_limit_mb",
+                "languages[1].cpu_limit",
+            ]
+        );
+        assert!(errors[0].message.contains("more than once"));
+        assert_eq!(errors[3].message, "expected a whole number, got string \"512\"");
+        assert!(format_errors(&errors).starts_with("5 problem(s):\n  - languages[1].name:"));
+    }
+
+    #[test]
+    fn test_optional_sections() {
+        let mut language = python();
+        language["canary"] = json!({ "image": "optimus-python:candidate", "percent": 150 });
+        language["compile"] = json!({ "command": "", "then": ["ld /code/main.o -o /code/main", " "] });
+        language["resources"] = json!({ "requests": { "memory": "512Mi", "cpu": "500m" }, "limits": { "memory": "1 GB", "cpu": "2" } });
+        language["concurrency"] = json!({ "max_parallel_jobs": 0, "max_parallel_tests": 4 });
+        language["flags"] = json!({ "container_pooling": "yes", "stderr_policy": "warn" });
+        language["platforms"] = json!(["linux/amd64", "linux/arm64/v8", "arm64", "Linux/AMD64"]);
+        language["gpu"] = json!({ "required": true, "devices": 9 });
+        language["capabilities"] = json!(["large-memory", "gpu", "Arm64"]);
+        language["time_multiplier"] = json!(0);
+        let errors = validate(&json!({ "languages": [language] })).unwrap_err();
+        assert_eq!(
+            paths(errors),
+            vec![
+                "languages[0].compile.command",
+                "languages[0].compile.then[1]",
+                "languages[0].time_multiplier",
+                "languages[0].canary.percent",
+                "languages[0].gpu.devices",
+                "languages[0].capabilities[1]",
+                "languages[0].capabilities[2]",
+                "languages[0].resources.limits.memory",
+                "languages[0].concurrency.max_parallel_jobs",
+                "languages[0].platforms[2]",
+                "languages[0].platforms[3]",
+                "languages[0].flags.container_pooling",
+                "languages[0].flags.stderr_policy",
+            ]
+        );
+
+        assert!(validate_str("{\"languages\": [").unwrap_err().contains("line 1"));
+        assert_eq!(paths(validate(&json!([])).unwrap_err()), vec!["(root)"]);
+    }
+
+    #[test]
+    fn test_image_refs() {
+        for image in [
+            "optimus-python:latest",
+            "python",
+            "ghcr.io/org/optimus-python:3.12-slim",
+            "localhost:5000/optimus-java",
+            "registry.example.com:443/a/b@sha256:0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef",
+        ] {
+            assert_eq!(check_image_ref(image), Ok(()), "{}", image);
+        }
+        for image in ["Optimus-Python:latest", "optimus-python:", "optimus python", "a/-b", "img@sha256:abc", ""] {
+            assert!(check_image_ref(image).is_err(), "{}", image);
+        }
+    }
+}
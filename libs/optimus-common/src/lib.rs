@@ -1,6 +1,16 @@
 pub mod types;
 pub mod redis;
 pub mod config;
+pub mod signing;
+pub mod crypto;
+pub mod secrets;
+pub mod retry;
+pub mod language_schema;
+pub mod language_config;
+pub mod i18n;
+pub mod capabilities;
+pub mod limits;
+pub mod validate;
 
 // Re-export commonly used types for convenience
 pub use types::{ExecutionResult, JobRequest, JobStatus, Language};
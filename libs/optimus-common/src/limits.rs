@@ -0,0 +1,265 @@
+// Safety limits - submission sizes, test counts and timeouts, tunable without a rebuild
+//
+// Built-in defaults, overridden by a JSON file (LIMITS_FILE, default config/limits.json;
+// missing is fine), then by LIMIT_<FIELD> environment variables (LIMIT_MAX_TEST_CASES=200).
+// `reload()` re-reads both: the API and workers call it on SIGHUP and every
+// LIMITS_RELOAD_SECS (default 30), so an edited ConfigMap takes effect without a restart.
+// Limits that fail to parse or validate are refused and the previous ones stay.
+//
+// The API checks submissions against these and reports them on GET /limits (clients such
+// as optimus-client check submissions locally against the report); the worker applies the
+// same submission limits in standalone mode and the engine_* guardrails before anything
+// reaches Docker.
+
+use crate::types::{MAX_PRIORITY, MAX_TEST_WEIGHT};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Limits file used when LIMITS_FILE is unset
+const DEFAULT_LIMITS_FILE: &str = "config/limits.json";
+
+/// Prefix of the per-field environment overrides
+const ENV_PREFIX: &str = "LIMIT_";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Limits {
+    /// Test cases per submission
+    pub max_test_cases: usize,
+    /// Source code size in bytes
+    pub max_source_code_bytes: usize,
+    /// Input size of one test case in bytes
+    pub max_stdin_bytes: usize,
+    /// Expected output size of one test case in bytes
+    pub max_expected_output_bytes: usize,
+    pub min_timeout_ms: u64,
+    pub max_timeout_ms: u64,
+    /// Worker guardrail: largest source code sent to a container
+    pub engine_max_source_code_bytes: usize,
+    /// Worker guardrail: largest test input sent to a container
+    pub engine_max_test_input_bytes: usize,
+    /// Worker guardrail: container output kept per stream (stdout, stderr); the rest is counted
+    pub engine_max_output_bytes: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_test_cases: 100,
+            max_source_code_bytes: 256_000,
+            max_stdin_bytes: 64_000,
+            max_expected_output_bytes: 64_000,
+            min_timeout_ms: 1,
+            max_timeout_ms: 60_000,
+            engine_max_source_code_bytes: 1024 * 1024,
+            engine_max_test_input_bytes: 10 * 1024 * 1024,
+            engine_max_output_bytes: 4 * 1024 * 1024,
+        }
+    }
+}
+
+impl Limits {
+    /// Defaults, then the file's fields, then LIMIT_* overrides; `file` is the file's contents
+    pub fn resolve(file: Option<&str>, env: impl Fn(&str) -> Option<String>) -> Result<Self, String> {
+        let mut fields = match serde_json::to_value(Limits::default()) {
+            Ok(serde_json::Value::Object(fields)) => fields,
+            _ => unreachable!("Limits serializes to an object"),
+        };
+        if let Some(contents) = file {
+            let overrides: serde_json::Map<String, serde_json::Value> =
+                serde_json::from_str(contents).map_err(|e| format!("limits file: {}", e))?;
+            for (name, value) in overrides {
+                if !fields.contains_key(&name) {
+                    return Err(format!("limits file: unknown limit '{}'", name));
+                }
+                fields.insert(name, value);
+            }
+        }
+        for (name, value) in fields.iter_mut() {
+            let var = format!("{}{}", ENV_PREFIX, name.to_ascii_uppercase());
+            if let Some(raw) = env(&var) {
+                let parsed: u64 = raw.trim().parse().map_err(|_| format!("{} must be a whole number, got '{}'", var, raw))?;
+                *value = parsed.into();
+            }
+        }
+        let limits: Limits = serde_json::from_value(serde_json::Value::Object(fields))
+            .map_err(|e| format!("limits: {}", e))?;
+        limits.validate()?;
+        Ok(limits)
+    }
+
+    /// Reject limits that can't work: zero sizes, inverted timeouts, guardrails below
+    /// the submission limits (accepted jobs would then fail on the worker)
+    pub fn validate(&self) -> Result<(), String> {
+        let sizes = [
+            ("max_test_cases", self.max_test_cases),
+            ("max_source_code_bytes", self.max_source_code_bytes),
+            ("max_stdin_bytes", self.max_stdin_bytes),
+            ("max_expected_output_bytes", self.max_expected_output_bytes),
+        ];
+        if let Some((name, _)) = sizes.iter().find(|(_, value)| *value == 0) {
+            return Err(format!("{} must be at least 1", name));
+        }
+        // Summed weights must fit the u32 max_score
+        let most_tests = (u32::MAX / MAX_TEST_WEIGHT) as usize;
+        if self.max_test_cases > most_tests {
+            return Err(format!("max_test_cases must be at most {}", most_tests));
+        }
+        if self.min_timeout_ms == 0 || self.min_timeout_ms > self.max_timeout_ms {
+            return Err("timeouts must satisfy 1 <= min_timeout_ms <= max_timeout_ms".to_string());
+        }
+        if self.engine_max_source_code_bytes < self.max_source_code_bytes {
+            return Err("engine_max_source_code_bytes must be at least max_source_code_bytes".to_string());
+        }
+        if self.engine_max_test_input_bytes < self.max_stdin_bytes {
+            return Err("engine_max_test_input_bytes must be at least max_stdin_bytes".to_string());
+        }
+        if self.engine_max_output_bytes < self.max_expected_output_bytes {
+            return Err("engine_max_output_bytes must be at least max_expected_output_bytes".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// GET /limits: the reloadable limits plus the fixed bounds
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LimitsReport {
+    #[serde(flatten)]
+    pub limits: Limits,
+    pub max_test_weight: u32,
+    pub max_priority: u8,
+}
+
+/// Report of the limits in effect
+pub fn report() -> LimitsReport {
+    LimitsReport {
+        limits: (*current()).clone(),
+        max_test_weight: MAX_TEST_WEIGHT,
+        max_priority: MAX_PRIORITY,
+    }
+}
+
+fn cell() -> &'static RwLock<Arc<Limits>> {
+    static CURRENT: OnceLock<RwLock<Arc<Limits>>> = OnceLock::new();
+    CURRENT.get_or_init(|| RwLock::new(Arc::new(Limits::default())))
+}
+
+/// The limits in effect (defaults until `init`)
+pub fn current() -> Arc<Limits> {
+    cell().read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+fn limits_file() -> String {
+    std::env::var("LIMITS_FILE").unwrap_or_else(|_| DEFAULT_LIMITS_FILE.to_string())
+}
+
+/// Limits from the limits file and the environment
+fn load() -> Result<Limits, String> {
+    let path = limits_file();
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => Some(contents),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(format!("{}: {}", path, e)),
+    };
+    Limits::resolve(contents.as_deref(), |var| std::env::var(var).ok())
+}
+
+/// Load the process-wide limits; call once at startup, an invalid file is fatal
+pub fn init() -> Result<Arc<Limits>, String> {
+    let limits = Arc::new(load()?);
+    *cell().write().unwrap_or_else(|e| e.into_inner()) = limits.clone();
+    Ok(limits)
+}
+
+/// Re-read the file and environment; returns whether the limits changed
+/// On error the previous limits stay in effect
+pub fn reload() -> Result<bool, String> {
+    let limits = load()?;
+    let mut current = cell().write().unwrap_or_else(|e| e.into_inner());
+    if **current == limits {
+        return Ok(false);
+    }
+    *current = Arc::new(limits);
+    Ok(true)
+}
+
+fn reload_interval() -> Duration {
+    let secs = std::env::var("LIMITS_RELOAD_SECS")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .filter(|secs: &u64| *secs > 0)
+        .unwrap_or(30);
+    Duration::from_secs(secs)
+}
+
+/// Reload the limits on SIGHUP and every LIMITS_RELOAD_SECS; runs until the task is dropped
+pub async fn watch() {
+    let mut tick = tokio::time::interval(reload_interval());
+    tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    tick.tick().await;
+    #[cfg(unix)]
+    let mut hangup = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()) {
+        Ok(hangup) => Some(hangup),
+        Err(e) => {
+            warn!(error = %e, "SIGHUP handler unavailable; limits reload on the timer only");
+            None
+        }
+    };
+    loop {
+        #[cfg(unix)]
+        {
+            let signalled = async {
+                match hangup.as_mut() {
+                    Some(hangup) => hangup.recv().await,
+                    None => std::future::pending().await,
+                }
+            };
+            tokio::select! {
+                _ = tick.tick() => {}
+                _ = signalled => info!("Received SIGHUP - reloading limits"),
+            }
+        }
+        #[cfg(not(unix))]
+        tick.tick().await;
+
+        match reload() {
+            Ok(true) => info!(limits = ?*current(), "Safety limits reloaded"),
+            Ok(false) => {}
+            Err(e) => warn!(error = %e, "Invalid safety limits; keeping the previous ones"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |name| vars.iter().find(|(var, _)| *var == name).map(|(_, value)| value.to_string())
+    }
+
+    #[test]
+    fn test_resolve_layers() {
+        assert_eq!(Limits::resolve(None, env(&[])).unwrap(), Limits::default());
+
+        let file = r#"{"max_test_cases": 200, "max_timeout_ms": 120000}"#;
+        let limits = Limits::resolve(Some(file), env(&[("LIMIT_MAX_TEST_CASES", "300")])).unwrap();
+        assert_eq!(limits.max_test_cases, 300);
+        assert_eq!(limits.max_timeout_ms, 120_000);
+        assert_eq!(limits.max_stdin_bytes, Limits::default().max_stdin_bytes);
+    }
+
+    #[test]
+    fn test_resolve_rejects_bad_limits() {
+        assert!(Limits::resolve(Some(r#"{"max_tests": 5}"#), env(&[])).is_err());
+        assert!(Limits::resolve(Some("{"), env(&[])).is_err());
+        assert!(Limits::resolve(None, env(&[("LIMIT_MAX_TIMEOUT_MS", "soon")])).is_err());
+        assert!(Limits::resolve(None, env(&[("LIMIT_MAX_TEST_CASES", "0")])).is_err());
+        assert!(Limits::resolve(None, env(&[("LIMIT_MAX_TEST_CASES", "5000")])).is_err());
+        assert!(Limits::resolve(None, env(&[("LIMIT_MIN_TIMEOUT_MS", "70000")])).is_err());
+        assert!(Limits::resolve(None, env(&[("LIMIT_MAX_SOURCE_CODE_BYTES", "2000000")])).is_err());
+        assert!(Limits::resolve(None, env(&[("LIMIT_ENGINE_MAX_OUTPUT_BYTES", "1000")])).is_err());
+    }
+}
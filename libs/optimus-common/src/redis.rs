@@ -1,87 +1,846 @@
-use crate::types::{Language, JobRequest};
-use redis::{AsyncCommands, RedisResult};
+use crate::capabilities;
+use crate::crypto::{self, PayloadCipher};
+use crate::retry::with_retry;
+use crate::types::{Language, JobLocation, JobPlace, JobRequest};
+use redis::{AsyncCommands, RedisResult, Script};
+use std::sync::OnceLock;
 
-/// Redis queue semantics - defines only semantics, not runtime logic
-/// Ensures API and worker never drift, Redis keys are deterministic,
-/// and KEDA scaling remains predictable
+// Redis queue semantics - defines only semantics, not runtime logic
+// Ensures API and worker never drift, Redis keys are deterministic,
+// and KEDA scaling remains predictable
+//
+// Every key and channel lives under the deployment's prefix (`key_prefix`, `optimus` unless
+// OPTIMUS_KEY_PREFIX says otherwise), so several deployments - staging and production, one
+// per course - can share one Redis. The names below are relative to it; always build keys
+// through the functions in this module.
 
-pub const QUEUE_PREFIX: &str = "optimus:queue";
-pub const RESULT_PREFIX: &str = "optimus:result";
-pub const STATUS_PREFIX: &str = "optimus:status";
-pub const METRICS_PREFIX: &str = "optimus:metrics";
-pub const CONTROL_PREFIX: &str = "optimus:control";
+pub const QUEUE_PREFIX: &str = "queue";
+pub const RESULT_PREFIX: &str = "result";
+pub const STATUS_PREFIX: &str = "status";
+pub const METRICS_PREFIX: &str = "metrics";
+pub const CONTROL_PREFIX: &str = "control";
+pub const INDEX_PREFIX: &str = "index";
+pub const IDEMPOTENCY_PREFIX: &str = "idempotency";
+pub const WORKER_PREFIX: &str = "worker";
+pub const PROGRESS_PREFIX: &str = "progress";
+pub const CHECKPOINT_PREFIX: &str = "checkpoint";
+pub const RUNTIME_PREFIX: &str = "runtime";
+pub const SHADOW_PREFIX: &str = "shadow";
+pub const JOB_PREFIX: &str = "job";
+pub const VERIFICATION_PREFIX: &str = "verification";
+pub const DEBUG_PREFIX: &str = "debug";
+pub const ANNOTATION_PREFIX: &str = "annotations";
+pub const OVERRIDE_PREFIX: &str = "override";
+pub const EVENTS_STREAM: &str = "events";
+pub const ALERT_PREFIX: &str = "alert";
+pub const LEADERBOARD_PREFIX: &str = "leaderboard";
+pub const STATS_PREFIX: &str = "stats";
+pub const TOMBSTONE_PREFIX: &str = "tombstone";
+pub const COOLDOWN_PREFIX: &str = "cooldown";
+pub const QUOTA_PREFIX: &str = "quota";
+
+/// Name of the single queue the API fills when the dispatcher schedules jobs (see
+/// `intake_queue_name`)
+pub const INTAKE_QUEUE: &str = "intake";
+
+/// Name of the queue of intake entries the dispatcher could not read
+pub const INTAKE_DLQ: &str = "intake:dlq";
+
+/// Prefix of the queue names older configs and docs used (`jobs:{language}`, never under
+/// a deployment prefix); nothing consumes these queues - `optimus-cli migrate-queues` moves their jobs
+pub const LEGACY_QUEUE_PREFIX: &str = "jobs:";
+
+/// Suffixes of a language's queues: pending, retry and dead-letter
+pub const QUEUE_SUFFIXES: [&str; 3] = ["", ":retry", ":dlq"];
+
+/// How long results (and their status) are kept
+pub const RESULT_TTL_SECS: u64 = 86400;
+
+/// How long a job's location outlives its last move; dead-lettered jobs keep theirs until
+/// they leave the DLQ
+pub const LOCATION_TTL_SECS: u64 = 86400;
+
+/// Timeline events kept per problem (oldest dropped first)
+pub const PROBLEM_TIMELINE_LIMIT: isize = 500;
+
+/// How far back a problem's submissions per minute go, and how long a job counted in
+/// its statistics is remembered so a re-run isn't counted again (as long as a job copy)
+pub const PROBLEM_STATS_WINDOW_SECS: u64 = 86400;
+
+/// Number of shadow comparisons kept per language for the canary report
+pub const SHADOW_COMPARISONS_LIMIT: isize = 1000;
+
+/// Approximate number of events kept in the event stream
+pub const EVENTS_STREAM_MAXLEN: usize = 10_000;
+
+/// Number of submissions kept in the recent jobs list
+pub const RECENT_JOBS_LIMIT: isize = 100;
+
+/// Prefix of every key and channel when OPTIMUS_KEY_PREFIX is unset
+pub const DEFAULT_KEY_PREFIX: &str = "optimus";
+
+/// Prefix of this deployment's keys and channels: OPTIMUS_KEY_PREFIX (e.g.
+/// `optimus:staging`; a trailing ':' is optional), read once per process
+pub fn key_prefix() -> &'static str {
+    static PREFIX: OnceLock<String> = OnceLock::new();
+    PREFIX.get_or_init(|| parse_key_prefix(std::env::var("OPTIMUS_KEY_PREFIX").ok().as_deref()))
+}
+
+fn parse_key_prefix(value: Option<&str>) -> String {
+    match value.map(|v| v.trim().trim_end_matches(':')) {
+        Some(prefix) if !prefix.is_empty() => prefix.to_string(),
+        _ => DEFAULT_KEY_PREFIX.to_string(),
+    }
+}
+
+/// What the functions below send their commands on: a ConnectionManager, or a wrapper
+/// around one (the API's pool times every command that passes through it)
+pub trait Connection: redis::aio::ConnectionLike + Clone + Send + Sync {}
+
+impl<C: redis::aio::ConnectionLike + Clone + Send + Sync> Connection for C {}
+
+/// A key or channel of this deployment: `{key_prefix}:{name}`
+pub fn namespaced(name: &str) -> String {
+    format!("{}:{}", key_prefix(), name)
+}
 
 /// Generate deterministic queue name for a language
 pub fn queue_name(language: &Language) -> String {
-    format!("{}:{}", QUEUE_PREFIX, language)
+    canonical_queue_name(&language.to_string())
+}
+
+/// Queue of a language named as in languages.json (trimmed and lowercased, as
+/// `Language::from_str` reads names)
+///
+/// The one place queue names are derived: `queue_name`, config defaults and validation,
+/// and the CLI all go through it, so the API, workers and KEDA never disagree.
+pub fn canonical_queue_name(language_name: &str) -> String {
+    namespaced(&format!("{}:{}", QUEUE_PREFIX, language_name.trim().to_ascii_lowercase()))
 }
 
 /// Generate retry queue name for a language
 pub fn retry_queue_name(language: &Language) -> String {
-    format!("{}:{}:retry", QUEUE_PREFIX, language)
+    namespaced(&format!("{}:{}:retry", QUEUE_PREFIX, language))
+}
+
+/// Pending and retry queue of a language's jobs with these (normalized) requirements;
+/// jobs without requirements use the language's own queues
+pub fn requirement_queues(language: &Language, requirements: &[String]) -> (String, String) {
+    let queue = format!("{}{}", queue_name(language), capabilities::queue_suffix(requirements));
+    let retry_queue = format!("{}:retry", queue);
+    (queue, retry_queue)
+}
+
+/// Pending and retry queue a job waits in
+pub fn job_queues(job: &JobRequest) -> (String, String) {
+    requirement_queues(&job.language, &job.metadata.requirements)
+}
+
+/// Pending and retry queues a worker with these (normalized) capabilities reads, the
+/// queues of jobs needing the most of them first
+pub fn worker_queues(language: &Language, capabilities: &[String]) -> Vec<(String, String)> {
+    capabilities::satisfiable_sets(capabilities)
+        .iter()
+        .map(|requirements| requirement_queues(language, requirements))
+        .collect()
 }
 
 /// Generate dead letter queue name for a language
 pub fn dlq_name(language: &Language) -> String {
-    format!("{}:{}:dlq", QUEUE_PREFIX, language)
+    namespaced(&format!("{}:{}:dlq", QUEUE_PREFIX, language))
+}
+
+/// Jobs a worker moved off `queue` with BLMOVE but has yet to record as in flight
+/// (see pop_job_with_retry)
+pub fn processing_list_name(queue: &str, worker_id: &str) -> String {
+    format!("{}:processing:{}", queue, worker_id)
+}
+
+/// Sorted set of job ids currently executing, scored by lease deadline (unix seconds)
+pub fn inflight_queue_name(language: &Language) -> String {
+    namespaced(&format!("{}:{}:inflight", QUEUE_PREFIX, language))
+}
+
+/// Hash of job id -> queue payload for in-flight jobs
+pub fn inflight_jobs_name(language: &Language) -> String {
+    namespaced(&format!("{}:{}:inflight:jobs", QUEUE_PREFIX, language))
 }
 
 /// Generate result key for a job
 pub fn result_key(job_id: &uuid::Uuid) -> String {
-    format!("{}:{}", RESULT_PREFIX, job_id)
+    namespaced(&format!("{}:{}", RESULT_PREFIX, job_id))
 }
 
 /// Generate status key for a job
 pub fn status_key(job_id: &uuid::Uuid) -> String {
-    format!("{}:{}", STATUS_PREFIX, job_id)
+    namespaced(&format!("{}:{}", STATUS_PREFIX, job_id))
 }
 
 /// Generate control key for a job (cancellation flag)
 pub fn control_key(job_id: &uuid::Uuid) -> String {
-    format!("{}:{}", CONTROL_PREFIX, job_id)
+    namespaced(&format!("{}:{}", CONTROL_PREFIX, job_id))
+}
+
+/// Pub/sub channel announcing cancelled job ids (workers cancel the running job at once)
+pub fn cancel_channel() -> String {
+    namespaced(&format!("{}:cancellations", CONTROL_PREFIX))
+}
+
+/// Generate idempotency key for a client-supplied Idempotency-Key header
+pub fn idempotency_key(key: &str) -> String {
+    namespaced(&format!("{}:{}", IDEMPOTENCY_PREFIX, key))
+}
+
+/// Escape Redis glob metacharacters, for SCAN MATCH on a literal prefix
+fn glob_escape(literal: &str) -> String {
+    let mut escaped = String::with_capacity(literal.len());
+    for c in literal.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Set of tenants that have indexed submissions
+pub fn tenant_index_key() -> String {
+    namespaced(&format!("{}:tenants", INDEX_PREFIX))
+}
+
+/// Per-tenant sorted set of job ids scored by submission time (unix seconds)
+pub fn submission_index_key(tenant: &str) -> String {
+    namespaced(&format!("{}:submissions:{}", INDEX_PREFIX, tenant))
+}
+
+/// Hash of job id -> idempotency key, so retention can remove the stored payload
+pub fn idempotency_index_key() -> String {
+    namespaced(&format!("{}:idempotency", INDEX_PREFIX))
+}
+
+/// Generate progress key for a job (list of per-test progress entries)
+pub fn progress_key(job_id: &uuid::Uuid) -> String {
+    namespaced(&format!("{}:{}", PROGRESS_PREFIX, job_id))
+}
+
+/// Pub/sub channel carrying every job's progress events (JobProgressEvent)
+pub fn progress_channel() -> String {
+    namespaced(&format!("{}:events", PROGRESS_PREFIX))
+}
+
+/// Pub/sub channel of job completions (worker -> API metrics)
+pub fn completions_channel() -> String {
+    namespaced(&format!("{}:completions", METRICS_PREFIX))
+}
+
+/// Pub/sub channel of timing-variance samples (worker -> API metrics)
+pub fn variance_channel() -> String {
+    namespaced(&format!("{}:variance", METRICS_PREFIX))
+}
+
+/// Single queue the API fills when the dispatcher schedules jobs (DISPATCHER_ENABLED)
+pub fn intake_queue_name() -> String {
+    namespaced(INTAKE_QUEUE)
+}
+
+/// Intake entries the dispatcher could not read
+pub fn intake_dlq_name() -> String {
+    namespaced(INTAKE_DLQ)
+}
+
+/// Generate checkpoint key for one attempt of a job
+pub fn checkpoint_key(job_id: &uuid::Uuid, attempt: u8) -> String {
+    namespaced(&format!("{}:{}:{}", CHECKPOINT_PREFIX, job_id, attempt))
+}
+
+/// Generate heartbeat key for a worker
+pub fn worker_key(worker_id: &str) -> String {
+    namespaced(&format!("{}:{}", WORKER_PREFIX, worker_id))
+}
+
+/// Marker left by a worker that shut down gracefully (see mark_worker_stopped)
+pub fn worker_stopped_key(worker_id: &str) -> String {
+    namespaced(&format!("{}:stopped:{}", CONTROL_PREFIX, worker_id))
+}
+
+/// Drain request for one worker (see request_worker_drain)
+pub fn worker_drain_key(worker_id: &str) -> String {
+    namespaced(&format!("{}:drain:{}", CONTROL_PREFIX, worker_id))
+}
+
+/// Generate runtime info key for a language
+pub fn runtime_info_key(language: &Language) -> String {
+    namespaced(&format!("{}:{}", RUNTIME_PREFIX, language))
+}
+
+/// Generate key for a job's shadow (candidate image) result
+pub fn shadow_result_key(job_id: &uuid::Uuid) -> String {
+    namespaced(&format!("{}:result:{}", SHADOW_PREFIX, job_id))
+}
+
+/// Capped list of shadow comparisons for a language, newest first
+pub fn shadow_comparisons_key(language: &Language) -> String {
+    namespaced(&format!("{}:{}:comparisons", SHADOW_PREFIX, language))
+}
+
+/// Generate key for the stored copy of a submitted job
+pub fn job_key(job_id: &uuid::Uuid) -> String {
+    namespaced(&format!("{}:{}", JOB_PREFIX, job_id))
+}
+
+/// Generate key for where a job waits or runs (see JobLocation)
+pub fn job_location_key(job_id: &uuid::Uuid) -> String {
+    namespaced(&format!("{}:{}:location", JOB_PREFIX, job_id))
+}
+
+/// Generate key for the dual-run verification of a job
+pub fn verification_key(original_job_id: &uuid::Uuid) -> String {
+    namespaced(&format!("{}:{}", VERIFICATION_PREFIX, original_job_id))
+}
+
+/// Generate key for a job's debug artifacts (per-test resource usage)
+pub fn debug_key(job_id: &uuid::Uuid) -> String {
+    namespaced(&format!("{}:{}", DEBUG_PREFIX, job_id))
+}
+
+/// Generate key for the reviewer annotations of a job (list, oldest first)
+pub fn annotations_key(job_id: &uuid::Uuid) -> String {
+    namespaced(&format!("{}:{}", ANNOTATION_PREFIX, job_id))
+}
+
+/// Generate key for the manual score overrides of a job (audit trail, oldest first)
+pub fn overrides_key(job_id: &uuid::Uuid) -> String {
+    namespaced(&format!("{}:{}", OVERRIDE_PREFIX, job_id))
+}
+
+/// Stream of operational events (see SystemEvent)
+pub fn events_stream_key() -> String {
+    namespaced(EVENTS_STREAM)
+}
+
+/// Flag set while a language's queue is paused (workers stop dequeuing)
+pub fn queue_paused_key(language: &Language) -> String {
+    namespaced(&format!("{}:paused:{}", CONTROL_PREFIX, language))
+}
+
+/// Lock held by the API replica trimming the dead letter queues
+pub fn dlq_trim_lock_key() -> String {
+    namespaced(&format!("{}:dlq-trim", CONTROL_PREFIX))
+}
+
+/// Cooldown marker for an alert (set while the alert must not be re-sent)
+pub fn alert_cooldown_key(alert_key: &str) -> String {
+    namespaced(&format!("{}:{}", ALERT_PREFIX, alert_key))
+}
+
+/// Capped list of recent submissions, newest first
+pub fn recent_jobs_key() -> String {
+    namespaced(&format!("{}:recent", INDEX_PREFIX))
+}
+
+/// Sorted set ranking a problem's participants (details in `leaderboard_entries_key`)
+pub fn problem_leaderboard_key(problem_id: &str) -> String {
+    namespaced(&format!("{}:problem:{}", LEADERBOARD_PREFIX, problem_id))
+}
+
+/// Sorted set ranking a contest's participants (details in `leaderboard_entries_key`)
+pub fn contest_leaderboard_key(contest_id: &str) -> String {
+    namespaced(&format!("{}:contest:{}", LEADERBOARD_PREFIX, contest_id))
+}
+
+/// Hash of participant -> best score and time behind a leaderboard's sorted set
+pub fn leaderboard_entries_key(board_key: &str) -> String {
+    format!("{}:entries", board_key)
+}
+
+/// Hash of job id -> score and time of every ranked result of a participant on a
+/// problem, from which the best is recomputed when one is overridden or deleted
+pub fn leaderboard_results_key(board_key: &str, participant: &str, problem_id: &str) -> String {
+    format!("{}:results:{}:{}", board_key, participant, problem_id)
+}
+
+/// Set of the test-set fingerprints a problem's leaderboard ranks results of
+pub fn problem_test_sets_key(problem_id: &str) -> String {
+    format!("{}:test_sets", problem_leaderboard_key(problem_id))
+}
+
+/// Leaderboard tag of a ranked job, so overrides and deletions find its boards
+pub fn ranked_job_key(job_id: &uuid::Uuid) -> String {
+    namespaced(&format!("{}:job:{}", LEADERBOARD_PREFIX, job_id))
+}
+
+/// Hash of a problem's counters, first solve and fastest solution
+pub fn problem_stats_key(problem_id: &str) -> String {
+    namespaced(&format!("{}:problem:{}", STATS_PREFIX, problem_id))
+}
+
+/// Capped list of a problem's timeline events, oldest first
+pub fn problem_timeline_key(problem_id: &str) -> String {
+    format!("{}:timeline", problem_stats_key(problem_id))
+}
+
+/// Hash of minute (unix seconds) -> submissions, and "{minute}:accepted" -> accepted ones
+pub fn problem_submissions_key(problem_id: &str) -> String {
+    format!("{}:per_minute", problem_stats_key(problem_id))
+}
+
+/// Set of participants who solved a problem
+pub fn problem_solvers_key(problem_id: &str) -> String {
+    format!("{}:solvers", problem_stats_key(problem_id))
+}
+
+/// Marks a job as counted in its problem's statistics (see PROBLEM_STATS_WINDOW_SECS)
+pub fn problem_counted_key(job_id: &uuid::Uuid) -> String {
+    namespaced(&format!("{}:counted:{}", STATS_PREFIX, job_id))
+}
+
+/// Tombstone of a deleted job
+pub fn tombstone_key(job_id: &uuid::Uuid) -> String {
+    namespaced(&format!("{}:{}", TOMBSTONE_PREFIX, job_id))
+}
+
+/// Sorted set of tombstoned job ids awaiting purge, scored by purge time (unix seconds)
+pub fn tombstone_index_key() -> String {
+    namespaced(&format!("{}:tombstones", INDEX_PREFIX))
+}
+
+/// Marker of a submitter's last submission to a problem, expiring with the cool-down
+pub fn submission_cooldown_key(problem_id: &str, submitter: &str) -> String {
+    namespaced(&format!("{}:{}:{}", COOLDOWN_PREFIX, problem_id, submitter))
+}
+
+/// Token bucket limiting an API key's submissions (hash of tokens, refilled_ms)
+pub fn rate_limit_key(api_key_id: &str) -> String {
+    namespaced(&format!("{}:{}:rate", QUOTA_PREFIX, api_key_id))
+}
+
+/// The UTC day CPU time is charged to (YYYYMMDD)
+pub fn quota_day(now: chrono::DateTime<chrono::Utc>) -> String {
+    now.format("%Y%m%d").to_string()
+}
+
+/// CPU-seconds an API key's jobs used on one UTC day (YYYYMMDD)
+pub fn cpu_quota_key(api_key_id: &str, day: &str) -> String {
+    namespaced(&format!("{}:{}:cpu:{}", QUOTA_PREFIX, api_key_id, day))
+}
+
+/// API key a running job's CPU time is charged to, until it completes
+pub fn quota_owner_key(job_id: &uuid::Uuid) -> String {
+    namespaced(&format!("{}:job:{}", QUOTA_PREFIX, job_id))
+}
+
+/// Field holding the sealed copy of sensitive fields when encryption at rest is enabled
+pub const SEALED_FIELD: &str = "sealed";
+
+/// JobRequest fields that carry student code, test data and the problem's checker
+const SENSITIVE_JOB_FIELDS: &[&str] = &["source_code", "test_cases", "checker"];
+
+/// ExecutionResult fields that carry program output
+const SENSITIVE_RESULT_FIELDS: &[&str] = &["results"];
+
+/// Serialize a job for a queue entry, sealing sensitive fields if encryption is enabled
+pub fn encode_job(job: &JobRequest) -> Result<String, String> {
+    encode_job_with(job, crypto::global_cipher())
+}
+
+/// Parse a queue entry, opening sealed fields if present
+pub fn decode_job(payload: &str) -> Result<JobRequest, String> {
+    decode_job_with(payload, crypto::global_cipher())
+}
+
+pub fn encode_job_with(job: &JobRequest, cipher: Option<&PayloadCipher>) -> Result<String, String> {
+    match cipher {
+        Some(cipher) => seal_fields(job, SENSITIVE_JOB_FIELDS, cipher),
+        None => serde_json::to_string(job).map_err(|e| e.to_string()),
+    }
+}
+
+pub fn decode_job_with(payload: &str, cipher: Option<&PayloadCipher>) -> Result<JobRequest, String> {
+    let value = open_fields(payload, cipher)?;
+    serde_json::from_value(value).map_err(|e| e.to_string())
+}
+
+fn encode_result_with(
+    result: &crate::types::ExecutionResult,
+    cipher: Option<&PayloadCipher>,
+) -> Result<String, String> {
+    match cipher {
+        Some(cipher) => seal_fields(result, SENSITIVE_RESULT_FIELDS, cipher),
+        None => serde_json::to_string(result).map_err(|e| e.to_string()),
+    }
+}
+
+fn decode_result_with(
+    payload: &str,
+    cipher: Option<&PayloadCipher>,
+) -> Result<crate::types::ExecutionResult, String> {
+    let value = open_fields(payload, cipher)?;
+    serde_json::from_value(value).map_err(|e| e.to_string())
+}
+
+/// Move `fields` into a single sealed blob; ids, language and metadata stay readable
+/// so queue inspection and routing keep working without the key
+fn seal_fields<T: serde::Serialize>(
+    item: &T,
+    fields: &[&str],
+    cipher: &PayloadCipher,
+) -> Result<String, String> {
+    let mut value = serde_json::to_value(item).map_err(|e| e.to_string())?;
+    if let Some(object) = value.as_object_mut() {
+        let mut sensitive = serde_json::Map::new();
+        for field in fields {
+            if let Some(v) = object.remove(*field) {
+                sensitive.insert(field.to_string(), v);
+            }
+        }
+        let plaintext = serde_json::to_string(&sensitive).map_err(|e| e.to_string())?;
+        object.insert(SEALED_FIELD.to_string(), serde_json::Value::String(cipher.seal_str(&plaintext)?));
+    }
+    serde_json::to_string(&value).map_err(|e| e.to_string())
+}
+
+/// Inverse of `seal_fields`; plaintext payloads pass through untouched
+fn open_fields(payload: &str, cipher: Option<&PayloadCipher>) -> Result<serde_json::Value, String> {
+    let mut value: serde_json::Value = serde_json::from_str(payload).map_err(|e| e.to_string())?;
+    let sealed = match value.as_object_mut().and_then(|o| o.remove(SEALED_FIELD)) {
+        Some(sealed) => sealed,
+        None => return Ok(value),
+    };
+    let cipher = cipher.ok_or_else(|| "Payload is encrypted but no encryption keys are configured".to_string())?;
+    let sealed = sealed.as_str().ok_or_else(|| "Sealed field must be a string".to_string())?;
+    let sensitive: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_str(&cipher.open_str(sealed)?).map_err(|e| e.to_string())?;
+    if let Some(object) = value.as_object_mut() {
+        object.extend(sensitive);
+    }
+    Ok(value)
+}
+
+fn payload_error(desc: &'static str, detail: String) -> redis::RedisError {
+    redis::RedisError::from((redis::ErrorKind::TypeError, desc, detail))
+}
+
+/// RPUSH a job's payload and record its new location in one transaction, retrying
+/// transient errors
+///
+/// A push whose reply was lost may land twice; delivery is at-least-once anyway
+/// (reclaimed in-flight jobs run again), and a re-run stores the same result.
+async fn push_with_location(
+    conn: &impl Connection,
+    queue: &str,
+    payload: &str,
+    job: &JobRequest,
+    place: JobPlace,
+) -> RedisResult<()> {
+    let location = location_payload(job, place, Some(queue))?;
+    let location_key = job_location_key(&job.id);
+    with_retry("rpush", || {
+        let mut conn = conn.clone();
+        let mut pipe = redis::pipe();
+        pipe.atomic().rpush(queue, payload).ignore();
+        match location_ttl(place) {
+            Some(ttl) => pipe.set_ex(&location_key, &location, ttl).ignore(),
+            None => pipe.set(&location_key, &location).ignore(),
+        };
+        async move { pipe.query_async(&mut conn).await }
+    })
+    .await
+}
+
+fn location_payload(job: &JobRequest, place: JobPlace, queue: Option<&str>) -> RedisResult<String> {
+    let location = JobLocation {
+        place,
+        queue: queue.map(str::to_string),
+        metadata: job.metadata.clone(),
+        updated_at: chrono::Utc::now(),
+    };
+    serde_json::to_string(&location).map_err(|e| payload_error("serialization error", e.to_string()))
+}
+
+/// None: kept until the job leaves the DLQ
+fn location_ttl(place: JobPlace) -> Option<u64> {
+    (place != JobPlace::DeadLettered).then_some(LOCATION_TTL_SECS)
+}
+
+/// Record where a job is now (see JobLocation)
+pub async fn set_job_location(
+    conn: &mut impl Connection,
+    job: &JobRequest,
+    place: JobPlace,
+    queue: Option<&str>,
+) -> RedisResult<()> {
+    let location = location_payload(job, place, queue)?;
+    match location_ttl(place) {
+        Some(ttl) => conn.set_ex(job_location_key(&job.id), location, ttl).await,
+        None => conn.set(job_location_key(&job.id), location).await,
+    }
+}
+
+/// Where a job is, if it is waiting or running (one GET, whatever the queue sizes)
+pub async fn get_job_location(
+    conn: &mut impl Connection,
+    job_id: &uuid::Uuid,
+) -> RedisResult<Option<JobLocation>> {
+    let payload: Option<String> = conn.get(job_location_key(job_id)).await?;
+    payload
+        .map(|p| serde_json::from_str(&p).map_err(|e| payload_error("deserialization error", e.to_string())))
+        .transpose()
+}
+
+/// Forget a job's location once it left the queues for good (trimmed or purged from a DLQ)
+pub async fn clear_job_location(conn: &mut impl Connection, job_id: &uuid::Uuid) -> RedisResult<()> {
+    conn.del(job_location_key(job_id)).await
 }
 
 /// Push a job to the language-specific queue
 /// Uses RPUSH for FIFO semantics
 pub async fn push_job(
-    conn: &mut redis::aio::ConnectionManager,
+    conn: &mut impl Connection,
     job: &JobRequest,
 ) -> RedisResult<()> {
-    let queue = queue_name(&job.language);
-    let payload = serde_json::to_string(job)
-        .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "serialization error", e.to_string())))?;
+    let (queue, _) = job_queues(job);
+    let payload = encode_job(job)
+        .map_err(|e| payload_error("serialization error", e))?;
     
-    conn.rpush(&queue, payload).await
+    push_with_location(conn, &queue, &payload, job, JobPlace::Queued).await
+}
+
+/// Queues under the legacy names that still hold jobs, with their lengths
+pub async fn legacy_queue_depths(
+    conn: &mut impl Connection,
+    languages: &[Language],
+) -> RedisResult<Vec<(String, i64)>> {
+    let keys: Vec<String> = languages
+        .iter()
+        .flat_map(|language| QUEUE_SUFFIXES.iter().map(move |suffix| format!("{}{}{}", LEGACY_QUEUE_PREFIX, language, suffix)))
+        .collect();
+    let mut pipe = redis::pipe();
+    for key in &keys {
+        pipe.llen(key);
+    }
+    let depths: Vec<i64> = pipe.query_async(conn).await?;
+    Ok(keys.into_iter().zip(depths).filter(|(_, depth)| *depth > 0).collect())
+}
+
+/// Move a list's entries to the head of another, oldest first, and delete the source
+const MOVE_QUEUE_SCRIPT: &str = r#"
+local items = redis.call('LRANGE', KEYS[1], 0, -1)
+for i = #items, 1, -1 do
+  redis.call('LPUSH', KEYS[2], items[i])
+end
+redis.call('DEL', KEYS[1])
+return #items
+"#;
+
+/// Move every job queued under `from` ahead of the jobs queued under `to`, keeping their
+/// order; returns how many moved
+///
+/// One script, so workers never see a job in both queues or in neither.
+pub async fn move_queue(conn: &mut impl Connection, from: &str, to: &str) -> RedisResult<i64> {
+    Script::new(MOVE_QUEUE_SCRIPT).key(from).key(to).invoke_async(conn).await
+}
+
+/// What a submission writes besides the queue entry
+pub struct Submission<'a> {
+    pub job: &'a JobRequest,
+    /// Client Idempotency-Key and the data to store under it
+    pub idempotency: Option<(&'a str, String)>,
+    /// How long the idempotency key is kept
+    pub idempotency_ttl_secs: u64,
+    /// Tenant whose retention index records the submission (None: retention disabled)
+    pub retention_tenant: Option<&'a str>,
+    pub recent: &'a crate::types::RecentJob,
+    /// Queue on the dispatcher's intake instead of the job's worker queue
+    pub via_intake: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Enqueued {
+    Queued,
+    /// The idempotency key was already taken; nothing was written. Holds the stored data.
+    IdempotencyKeyTaken(String),
+}
+
+/// Status stored for a job between enqueue and its result
+pub const QUEUED_STATUS: &str = "\"queued\"";
+
+/// Queue a submission with its status, job copy, location, recent-jobs entry, idempotency
+/// key and retention index in one script, so a failure never leaves a queued job without them
+/// (or an idempotency key pointing at a job that was never queued)
+const ENQUEUE_SCRIPT: &str = r#"
+-- KEYS: queue, status, job copy, recent jobs, idempotency, tenant set, tenant submissions, idempotency index,
+--       location
+-- ARGV: payload, job id, ttl, recent entry, recent limit, idempotency data, tenant, submitted at, status,
+--       idempotency ttl, location
+if ARGV[6] ~= '' then
+  local existing = redis.call('GET', KEYS[5])
+  if existing then
+    return {0, existing}
+  end
+  redis.call('SET', KEYS[5], ARGV[6], 'EX', ARGV[10])
+end
+redis.call('RPUSH', KEYS[1], ARGV[1])
+redis.call('SET', KEYS[2], ARGV[9], 'EX', ARGV[3])
+redis.call('SET', KEYS[3], ARGV[1], 'EX', ARGV[3])
+redis.call('SET', KEYS[9], ARGV[11], 'EX', ARGV[3])
+redis.call('LPUSH', KEYS[4], ARGV[4])
+redis.call('LTRIM', KEYS[4], 0, tonumber(ARGV[5]) - 1)
+if ARGV[7] ~= '' then
+  redis.call('SADD', KEYS[6], ARGV[7])
+  redis.call('ZADD', KEYS[7], ARGV[8], ARGV[2])
+  if ARGV[6] ~= '' then
+    redis.call('HSET', KEYS[8], ARGV[2], KEYS[5])
+  end
+end
+return {1, ''}
+"#;
+
+fn enqueue_script() -> &'static Script {
+    static SCRIPT: OnceLock<Script> = OnceLock::new();
+    SCRIPT.get_or_init(|| Script::new(ENQUEUE_SCRIPT))
+}
+
+/// Queue a new submission atomically (see ENQUEUE_SCRIPT)
+pub async fn enqueue_submission(
+    conn: &mut impl Connection,
+    submission: &Submission<'_>,
+) -> RedisResult<Enqueued> {
+    let job = submission.job;
+    let payload = encode_job(job)
+        .map_err(|e| payload_error("serialization error", e))?;
+    let recent = serde_json::to_string(submission.recent)
+        .map_err(|e| payload_error("serialization error", e.to_string()))?;
+    let (idempotency_redis_key, idempotency_data) = match &submission.idempotency {
+        Some((key, data)) => (idempotency_key(key), data.as_str()),
+        None => (String::new(), ""),
+    };
+    let tenant = submission.retention_tenant.unwrap_or("");
+
+    let (queue, place) = if submission.via_intake {
+        (intake_queue_name(), JobPlace::Intake)
+    } else {
+        (job_queues(job).0, JobPlace::Queued)
+    };
+    let location = location_payload(job, place, Some(&queue))?;
+    let mut invocation = enqueue_script().prepare_invoke();
+    invocation
+        .key(&queue)
+        .key(status_key(&job.id))
+        .key(job_key(&job.id))
+        .key(recent_jobs_key())
+        .key(&idempotency_redis_key)
+        .key(tenant_index_key())
+        .key(submission_index_key(tenant))
+        .key(idempotency_index_key())
+        .key(job_location_key(&job.id))
+        .arg(&payload)
+        .arg(job.id.to_string())
+        .arg(RESULT_TTL_SECS)
+        .arg(&recent)
+        .arg(RECENT_JOBS_LIMIT)
+        .arg(idempotency_data)
+        .arg(tenant)
+        .arg(chrono::Utc::now().timestamp())
+        .arg(QUEUED_STATUS)
+        .arg(submission.idempotency_ttl_secs.max(1))
+        .arg(&location);
+    let (queued, existing): (i64, String) = with_retry("enqueue", || {
+        let mut conn = conn.clone();
+        let invocation = &invocation;
+        async move { invocation.invoke_async(&mut conn).await }
+    })
+    .await?;
+
+    Ok(if queued == 1 { Enqueued::Queued } else { Enqueued::IdempotencyKeyTaken(existing) })
+}
+
+/// Move an intake entry onto a worker queue, if it is still on the intake (another
+/// dispatcher may have moved it already)
+const DISPATCH_SCRIPT: &str = r#"
+-- KEYS: intake, target queue, job location
+-- ARGV: payload, location ('' = unknown job), location ttl (0 = keep)
+if redis.call('LREM', KEYS[1], 1, ARGV[1]) == 1 then
+  redis.call('RPUSH', KEYS[2], ARGV[1])
+  if ARGV[2] ~= '' then
+    if ARGV[3] == '0' then
+      redis.call('SET', KEYS[3], ARGV[2])
+    else
+      redis.call('SET', KEYS[3], ARGV[2], 'EX', ARGV[3])
+    end
+  end
+  return 1
+end
+return 0
+"#;
+
+fn dispatch_script() -> &'static Script {
+    static SCRIPT: OnceLock<Script> = OnceLock::new();
+    SCRIPT.get_or_init(|| Script::new(DISPATCH_SCRIPT))
+}
+
+/// The oldest `limit` intake entries, as stored (see decode_job)
+pub async fn peek_intake(
+    conn: &mut impl Connection,
+    limit: usize,
+) -> RedisResult<Vec<String>> {
+    conn.lrange(intake_queue_name(), 0, limit.max(1) as isize - 1).await
+}
+
+/// Move one intake entry to `queue`, recording the location of its `job` (None for
+/// unreadable entries); false when it was no longer on the intake
+pub async fn dispatch_entry(
+    conn: &mut impl Connection,
+    payload: &str,
+    job: Option<&JobRequest>,
+    queue: &str,
+) -> RedisResult<bool> {
+    let place = if queue == intake_dlq_name() { JobPlace::DeadLettered } else { JobPlace::Queued };
+    let (location_key, location) = match job {
+        Some(job) => (job_location_key(&job.id), location_payload(job, place, Some(queue))?),
+        None => (String::new(), String::new()),
+    };
+    let moved: i64 = dispatch_script()
+        .key(intake_queue_name())
+        .key(queue)
+        .key(location_key)
+        .arg(payload)
+        .arg(location)
+        .arg(location_ttl(place).unwrap_or(0))
+        .invoke_async(conn)
+        .await?;
+    Ok(moved == 1)
 }
 
 /// Push a job to the retry queue
 pub async fn push_to_retry_queue(
-    conn: &mut redis::aio::ConnectionManager,
+    conn: &mut impl Connection,
     job: &JobRequest,
 ) -> RedisResult<()> {
-    let queue = retry_queue_name(&job.language);
-    let payload = serde_json::to_string(job)
-        .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "serialization error", e.to_string())))?;
+    let (_, queue) = job_queues(job);
+    let payload = encode_job(job)
+        .map_err(|e| payload_error("serialization error", e))?;
     
-    conn.rpush(&queue, payload).await
+    push_with_location(conn, &queue, &payload, job, JobPlace::Retrying).await
 }
 
-/// Push a job to the dead letter queue
+/// Push a job to the dead letter queue, stamped with the time it got there
 pub async fn push_to_dlq(
-    conn: &mut redis::aio::ConnectionManager,
+    conn: &mut impl Connection,
     job: &JobRequest,
 ) -> RedisResult<()> {
     let queue = dlq_name(&job.language);
-    let payload = serde_json::to_string(job)
-        .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "serialization error", e.to_string())))?;
+    let mut job = job.clone();
+    job.metadata.dead_lettered_at = Some(chrono::Utc::now());
+    let payload = encode_job(&job)
+        .map_err(|e| payload_error("serialization error", e))?;
     
-    conn.rpush(&queue, payload).await
+    push_with_location(conn, &queue, &payload, &job, JobPlace::DeadLettered).await
 }
 
 /// Pop a job from the language-specific queue
 /// Uses BLPOP with timeout for graceful shutdown
 pub async fn pop_job(
-    conn: &mut redis::aio::ConnectionManager,
+    conn: &mut impl Connection,
     language: &Language,
     timeout_seconds: f64,
 ) -> RedisResult<Option<JobRequest>> {
@@ -90,80 +849,274 @@ pub async fn pop_job(
     
     match result {
         Some((_key, payload)) => {
-            let job: JobRequest = serde_json::from_str(&payload)
-                .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "deserialization error", e.to_string())))?;
+            let job = decode_job(&payload)
+                .map_err(|e| payload_error("deserialization error", e))?;
             Ok(Some(job))
         }
         None => Ok(None),
     }
 }
 
-/// Pop a job from either the main queue or retry queue (priority: main first)
-/// Uses BLPOP with multiple keys - Redis pops from first non-empty queue
+/// Pop the next job (main queue first, then the retry queue, for each requirement set in
+/// turn) and record it as in flight in the same script, so a worker dying between the two
+/// can't lose the job
+const DEQUEUE_SCRIPT: &str = r#"
+-- KEYS: in-flight jobs hash, in-flight lease set, then the lists to pop from in order
+-- ARGV: lease deadline (unix seconds)
+local payload
+for i = 3, #KEYS do
+  payload = redis.call('LPOP', KEYS[i])
+  if payload then
+    break
+  end
+end
+if not payload then
+  return false
+end
+local ok, job = pcall(cjson.decode, payload)
+if ok and type(job) == 'table' and type(job.id) == 'string' then
+  redis.call('HSET', KEYS[1], job.id, payload)
+  redis.call('ZADD', KEYS[2], ARGV[1], job.id)
+end
+return payload
+"#;
+
+fn dequeue_script() -> &'static Script {
+    static SCRIPT: OnceLock<Script> = OnceLock::new();
+    SCRIPT.get_or_init(|| Script::new(DEQUEUE_SCRIPT))
+}
+
+/// Pop a job from either the main queue or retry queue (priority: main first), claimed as
+/// in flight with a lease of `lease_secs` (see claim_inflight); workers with capability
+/// labels also pop jobs that require them, those needing the most labels first
+///
+/// When every queue is empty, waits up to `timeout_seconds` with BLMOVE on the worker's
+/// most specific main queue (the one no other kind of worker reads) into the worker's
+/// processing list, then claims from there. A job on another queue is seen by the next
+/// call. BLMOVE holds its connection while it waits, so `blocking_conn` must be one no
+/// other task uses. A worker that dies between the move and the claim leaves the job in
+/// its processing list: the same worker claims it first on its next call, any other
+/// worker returns it to the queue (see recover_processing_lists).
 pub async fn pop_job_with_retry(
-    conn: &mut redis::aio::ConnectionManager,
+    conn: &mut impl Connection,
+    blocking_conn: &mut impl Connection,
     language: &Language,
+    capabilities: &[String],
+    worker_id: &str,
     timeout_seconds: f64,
+    lease_secs: u64,
 ) -> RedisResult<Option<JobRequest>> {
-    let main_queue = queue_name(language);
-    let retry_queue = retry_queue_name(language);
-    
-    // BLPOP checks keys in order - main queue has priority
-    let result: Option<(String, String)> = conn.blpop(&[main_queue, retry_queue], timeout_seconds).await?;
-    
-    match result {
-        Some((_key, payload)) => {
-            let job: JobRequest = serde_json::from_str(&payload)
-                .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "deserialization error", e.to_string())))?;
-            Ok(Some(job))
+    let queues = worker_queues(language, capabilities);
+    let blocking_queue = &queues[0].0;
+    let processing = processing_list_name(blocking_queue, worker_id);
+
+    let mut lists = vec![processing.clone()];
+    lists.extend(queues.iter().flat_map(|(queue, retry_queue)| [queue.clone(), retry_queue.clone()]));
+    if let Some(job) = claim_next(conn, language, &lists, lease_secs).await? {
+        return Ok(Some(job));
+    }
+
+    let moved: Option<String> = redis::cmd("BLMOVE")
+        .arg(blocking_queue)
+        .arg(&processing)
+        .arg("LEFT")
+        .arg("RIGHT")
+        .arg(timeout_seconds.max(0.01))
+        .query_async(blocking_conn)
+        .await?;
+    if moved.is_none() {
+        return Ok(None);
+    }
+    claim_next(conn, language, std::slice::from_ref(&processing), lease_secs).await
+}
+
+/// Pop the first job of `lists` and claim it as in flight (DEQUEUE_SCRIPT)
+async fn claim_next(
+    conn: &mut impl Connection,
+    language: &Language,
+    lists: &[String],
+    lease_secs: u64,
+) -> RedisResult<Option<JobRequest>> {
+    let lease_deadline = chrono::Utc::now().timestamp() + lease_secs as i64;
+    let mut invocation = dequeue_script().prepare_invoke();
+    invocation
+        .key(inflight_jobs_name(language))
+        .key(inflight_queue_name(language));
+    for list in lists {
+        invocation.key(list);
+    }
+    let payload: Option<String> = invocation.arg(lease_deadline).invoke_async(conn).await?;
+    let Some(payload) = payload else { return Ok(None) };
+    let job = decode_job(&payload)
+        .map_err(|e| payload_error("deserialization error", e))?;
+    // The job is already claimed; a missed update only leaves its location stale
+    let _ = set_job_location(conn, &job, JobPlace::Running, None).await;
+    Ok(Some(job))
+}
+
+/// Return jobs left in the processing lists of dead workers (no heartbeat) to the front of
+/// the queues they were moved from; returns how many were returned
+pub async fn recover_processing_lists(
+    conn: &mut impl Connection,
+    language: &Language,
+) -> RedisResult<usize> {
+    let pattern = format!("{}*:processing:*", glob_escape(&queue_name(language)));
+    let mut lists: Vec<String> = Vec::new();
+    let mut cursor: u64 = 0;
+    loop {
+        let (next, batch): (u64, Vec<String>) = redis::cmd("SCAN")
+            .arg(cursor)
+            .arg("MATCH")
+            .arg(&pattern)
+            .arg("COUNT")
+            .arg(100)
+            .query_async(conn)
+            .await?;
+        lists.extend(batch);
+        cursor = next;
+        if cursor == 0 {
+            break;
+        }
+    }
+
+    let mut recovered = 0;
+    for list in lists {
+        let Some((queue, worker_id)) = list.rsplit_once(":processing:") else { continue };
+        if conn.exists(worker_key(worker_id)).await? {
+            continue;
+        }
+        // Last first onto the head, so the jobs keep their order ahead of the queue
+        loop {
+            let moved: Option<String> = redis::cmd("LMOVE")
+                .arg(&list)
+                .arg(queue)
+                .arg("RIGHT")
+                .arg("LEFT")
+                .query_async(conn)
+                .await?;
+            if moved.is_none() {
+                break;
+            }
+            recovered += 1;
         }
-        None => Ok(None),
     }
+    Ok(recovered)
 }
 
 /// Store execution result in Redis
 /// TTL is optional - set to 24 hours for now (can be configured later)
 /// 
-/// Also publishes metrics event for distributed tracking
+/// Results are signed with the process-wide signer when RESULT_SIGNING_KEYS is set,
+/// then test outputs are sealed when OPTIMUS_ENCRYPTION_KEYS is set
 pub async fn store_result(
-    conn: &mut redis::aio::ConnectionManager,
+    conn: &mut impl Connection,
     result: &crate::types::ExecutionResult,
 ) -> RedisResult<()> {
     let key = result_key(&result.job_id);
-    let payload = serde_json::to_string(result)
-        .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "serialization error", e.to_string())))?;
-    
-    // Store result with 24-hour TTL
-    let _: () = conn.set_ex(&key, payload, 86400).await?;
-    
-    // Also store status separately for quick lookup
+    let payload = encode_stored_result(result)?;
     let status_key_str = status_key(&result.job_id);
     let status_str = serde_json::to_string(&result.overall_status)
         .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "serialization error", e.to_string())))?;
-    let _: () = conn.set_ex(&status_key_str, status_str, 86400).await?;
     
-    Ok(())
+    // Result and status (kept separately for quick lookup) with 24-hour TTL;
+    // both writes are idempotent, so a Redis blip is retried rather than losing the result
+    with_retry("store_result", || {
+        let mut conn = conn.clone();
+        let (key, payload, status_key_str, status_str) = (&key, &payload, &status_key_str, &status_str);
+        async move {
+            redis::pipe()
+                .set_ex(key, payload, RESULT_TTL_SECS).ignore()
+                .set_ex(status_key_str, status_str, RESULT_TTL_SECS).ignore()
+                .query_async(&mut conn)
+                .await
+        }
+    })
+    .await
 }
 
-/// Store execution result and publish completion metrics
-/// This is a convenience function that combines store_result with metrics publishing
-pub async fn store_result_with_metrics(
-    conn: &mut redis::aio::ConnectionManager,
+/// Overwrite a stored result in place, keeping its remaining TTL
+/// Used by the retention janitor to anonymize results
+pub async fn replace_result(
+    conn: &mut impl Connection,
     result: &crate::types::ExecutionResult,
-    language: &crate::types::Language,
 ) -> RedisResult<()> {
-    // Store the result first
-    store_result(conn, result).await?;
-    
-    // Publish metrics event
-    publish_job_completion(conn, result, language).await?;
-    
-    Ok(())
+    let payload = encode_stored_result(result)?;
+    redis::cmd("SET")
+        .arg(result_key(&result.job_id))
+        .arg(payload)
+        .arg("KEEPTTL")
+        .query_async(conn)
+        .await
 }
 
-/// Publish job completion metrics (for distributed metrics tracking)
-async fn publish_job_completion(
-    conn: &mut redis::aio::ConnectionManager,
+/// Store a shadow run's result and its comparison with the primary result
+/// Kept apart from the result keys so users never see candidate verdicts
+pub async fn store_shadow_run(
+    conn: &mut impl Connection,
+    result: &crate::types::ExecutionResult,
+    comparison: &crate::types::ShadowComparison,
+) -> RedisResult<()> {
+    let payload = encode_stored_result(result)?;
+    let entry = serde_json::to_string(comparison)
+        .map_err(|e| payload_error("serialization error", e.to_string()))?;
+    let list = shadow_comparisons_key(&comparison.language);
+    redis::pipe()
+        .set_ex(shadow_result_key(&result.job_id), payload, 86400).ignore()
+        .lpush(&list, entry).ignore()
+        .ltrim(&list, 0, SHADOW_COMPARISONS_LIMIT - 1).ignore()
+        .query_async(conn)
+        .await
+}
+
+/// Most recent shadow comparisons for a language, newest first
+pub async fn list_shadow_comparisons(
+    conn: &mut impl Connection,
+    language: &Language,
+) -> RedisResult<Vec<crate::types::ShadowComparison>> {
+    let items: Vec<String> = conn.lrange(shadow_comparisons_key(language), 0, -1).await?;
+    Ok(items
+        .iter()
+        .filter_map(|item| serde_json::from_str(item).ok())
+        .collect())
+}
+
+/// Sign and seal a result exactly as it will be written to Redis
+fn encode_stored_result(result: &crate::types::ExecutionResult) -> RedisResult<String> {
+    let signed;
+    let result = match crate::signing::global_signer() {
+        Some(signer) => {
+            let mut copy = result.clone();
+            signer.sign(&mut copy);
+            signed = copy;
+            &signed
+        }
+        None => result,
+    };
+    encode_result_with(result, crypto::global_cipher())
+        .map_err(|e| payload_error("serialization error", e))
+}
+
+/// Store execution result and publish completion metrics
+/// This is a convenience function that combines store_result with metrics publishing
+pub async fn store_result_with_metrics(
+    conn: &mut impl Connection,
+    result: &crate::types::ExecutionResult,
+    language: &crate::types::Language,
+) -> RedisResult<()> {
+    // Store the result first
+    store_result(conn, result).await?;
+    
+    // Publish metrics event
+    publish_job_completion(conn, result, language).await?;
+
+    // Close live progress streams
+    let done = crate::types::ProgressEvent::Done { status: result.overall_status };
+    publish_progress(conn, &result.job_id, done).await
+}
+
+/// Publish job completion metrics (for distributed metrics tracking)
+async fn publish_job_completion(
+    conn: &mut impl Connection,
     result: &crate::types::ExecutionResult,
     language: &crate::types::Language,
 ) -> RedisResult<()> {
@@ -172,7 +1125,7 @@ async fn publish_job_completion(
         .map(|r| r.execution_time_ms)
         .sum();
     
-    let channel = format!("{}:completions", METRICS_PREFIX);
+    let channel = completions_channel();
     let event = serde_json::json!({
         "job_id": result.job_id.to_string(),
         "language": language.to_string(),
@@ -189,9 +1142,28 @@ async fn publish_job_completion(
     Ok(())
 }
 
+/// Publish one timing-variance sample (coefficient of variation of repeated runs)
+pub async fn publish_timing_variance(
+    conn: &mut impl Connection,
+    language: &crate::types::Language,
+    pinned: bool,
+    cv: f64,
+) -> RedisResult<()> {
+    let channel = variance_channel();
+    let event = serde_json::json!({
+        "language": language.to_string(),
+        "pinned": pinned,
+        "cv": cv,
+    });
+
+    // Fire-and-forget, like completion events
+    let _: i64 = conn.publish(&channel, event.to_string()).await.unwrap_or(0);
+    Ok(())
+}
+
 /// Retrieve execution result from Redis
 pub async fn get_result(
-    conn: &mut redis::aio::ConnectionManager,
+    conn: &mut impl Connection,
     job_id: &uuid::Uuid,
 ) -> RedisResult<Option<crate::types::ExecutionResult>> {
     let key = result_key(job_id);
@@ -199,18 +1171,853 @@ pub async fn get_result(
     
     match payload {
         Some(data) => {
-            let result: crate::types::ExecutionResult = serde_json::from_str(&data)
-                .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "deserialization error", e.to_string())))?;
+            let result = decode_result_with(&data, crypto::global_cipher())
+                .map_err(|e| payload_error("deserialization error", e))?;
             Ok(Some(result))
         }
         None => Ok(None),
     }
 }
 
+/// Keep a copy of a submitted job (24h, like results) so admins can re-run it
+pub async fn store_job_copy(
+    conn: &mut impl Connection,
+    job: &JobRequest,
+) -> RedisResult<()> {
+    let payload = encode_job(job)
+        .map_err(|e| payload_error("serialization error", e))?;
+    conn.set_ex(job_key(&job.id), payload, 86400).await
+}
+
+/// One SCAN page of stored results, each with its remaining TTL in seconds
+///
+/// Returns the cursor of the next page (0 after the last). Results that can't be decoded
+/// (e.g. encrypted with a key this process doesn't have) are skipped.
+pub async fn scan_results(
+    conn: &mut impl Connection,
+    cursor: u64,
+) -> RedisResult<(u64, Vec<(crate::types::ExecutionResult, i64)>)> {
+    let (next, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+        .arg(cursor)
+        .arg("MATCH")
+        .arg(format!("{}:*", glob_escape(&namespaced(RESULT_PREFIX))))
+        .arg("COUNT")
+        .arg(200)
+        .query_async(conn)
+        .await?;
+    if keys.is_empty() {
+        return Ok((next, Vec::new()));
+    }
+    let mut pipe = redis::pipe();
+    for key in &keys {
+        pipe.get(key).ttl(key);
+    }
+    let values: Vec<(Option<String>, i64)> = pipe.query_async(conn).await?;
+    let results = values
+        .into_iter()
+        .filter_map(|(data, ttl)| Some((decode_result_with(&data?, crypto::global_cipher()).ok()?, ttl)))
+        .collect();
+    Ok((next, results))
+}
+
+/// Stored copy of a submitted job, if it has not expired
+pub async fn get_job_copy(
+    conn: &mut impl Connection,
+    job_id: &uuid::Uuid,
+) -> RedisResult<Option<JobRequest>> {
+    let payload: Option<String> = conn.get(job_key(job_id)).await?;
+    payload
+        .map(|p| decode_job(&p))
+        .transpose()
+        .map_err(|e| payload_error("deserialization error", e))
+}
+
+/// Store a job's debug artifacts (24h, like results)
+pub async fn store_debug_artifacts(
+    conn: &mut impl Connection,
+    artifacts: &crate::types::DebugArtifacts,
+) -> RedisResult<()> {
+    let payload = serde_json::to_string(artifacts)
+        .map_err(|e| payload_error("serialization error", e.to_string()))?;
+    conn.set_ex(debug_key(&artifacts.job_id), payload, 86400).await
+}
+
+/// Debug artifacts of a job, if recorded and not expired
+pub async fn get_debug_artifacts(
+    conn: &mut impl Connection,
+    job_id: &uuid::Uuid,
+) -> RedisResult<Option<crate::types::DebugArtifacts>> {
+    let payload: Option<String> = conn.get(debug_key(job_id)).await?;
+    payload
+        .map(|p| serde_json::from_str(&p))
+        .transpose()
+        .map_err(|e| payload_error("deserialization error", e.to_string()))
+}
+
+/// Store a dual-run verification request or its completed report
+/// Reports quote program output, so the whole record is sealed when encryption is enabled
+pub async fn store_verification(
+    conn: &mut impl Connection,
+    verification: &crate::types::Verification,
+) -> RedisResult<()> {
+    let mut payload = serde_json::to_string(verification)
+        .map_err(|e| payload_error("serialization error", e.to_string()))?;
+    if let Some(cipher) = crypto::global_cipher() {
+        payload = cipher.seal_str(&payload)
+            .map_err(|e| payload_error("serialization error", e))?;
+    }
+    conn.set_ex(verification_key(&verification.original_job_id), payload, 86400).await
+}
+
+/// Latest dual-run verification of a job
+pub async fn get_verification(
+    conn: &mut impl Connection,
+    original_job_id: &uuid::Uuid,
+) -> RedisResult<Option<crate::types::Verification>> {
+    let Some(mut payload): Option<String> = conn.get(verification_key(original_job_id)).await? else {
+        return Ok(None);
+    };
+    if crypto::is_sealed(&payload) {
+        payload = crypto::global_cipher()
+            .ok_or_else(|| "Verification is encrypted but no encryption keys are configured".to_string())
+            .and_then(|cipher| cipher.open_str(&payload))
+            .map_err(|e| payload_error("deserialization error", e))?;
+    }
+    serde_json::from_str(&payload)
+        .map(Some)
+        .map_err(|e| payload_error("deserialization error", e.to_string()))
+}
+
+/// Append a reviewer annotation to a job
+/// The list expires together with the job's result
+pub async fn add_annotation(
+    conn: &mut impl Connection,
+    job_id: &uuid::Uuid,
+    annotation: &crate::types::Annotation,
+) -> RedisResult<()> {
+    let payload = serde_json::to_string(annotation)
+        .map_err(|e| payload_error("serialization error", e.to_string()))?;
+    append_with_result_ttl(conn, job_id, &annotations_key(job_id), payload).await
+}
+
+/// Push onto a per-job list that should expire together with the job's result
+async fn append_with_result_ttl(
+    conn: &mut impl Connection,
+    job_id: &uuid::Uuid,
+    key: &str,
+    payload: String,
+) -> RedisResult<()> {
+    let ttl: i64 = conn.ttl(result_key(job_id)).await?;
+    redis::pipe()
+        .rpush(key, payload).ignore()
+        .expire(key, if ttl > 0 { ttl } else { 86400 }).ignore()
+        .query_async(conn)
+        .await
+}
+
+/// Reviewer annotations of a job, oldest first
+pub async fn get_annotations(
+    conn: &mut impl Connection,
+    job_id: &uuid::Uuid,
+) -> RedisResult<Vec<crate::types::Annotation>> {
+    let payloads: Vec<String> = conn.lrange(annotations_key(job_id), 0, -1).await?;
+    payloads
+        .iter()
+        .map(|p| serde_json::from_str(p))
+        .collect::<Result<_, _>>()
+        .map_err(|e| payload_error("deserialization error", e.to_string()))
+}
+
+/// Record a manual score override (the machine result is left untouched)
+pub async fn add_score_override(
+    conn: &mut impl Connection,
+    score_override: &crate::types::ScoreOverride,
+) -> RedisResult<()> {
+    let payload = serde_json::to_string(score_override)
+        .map_err(|e| payload_error("serialization error", e.to_string()))?;
+    let job_id = &score_override.job_id;
+    append_with_result_ttl(conn, job_id, &overrides_key(job_id), payload).await
+}
+
+/// Every override of a job, oldest first; the last one is in effect
+pub async fn get_score_overrides(
+    conn: &mut impl Connection,
+    job_id: &uuid::Uuid,
+) -> RedisResult<Vec<crate::types::ScoreOverride>> {
+    let payloads: Vec<String> = conn.lrange(overrides_key(job_id), 0, -1).await?;
+    payloads
+        .iter()
+        .map(|p| serde_json::from_str(p))
+        .collect::<Result<_, _>>()
+        .map_err(|e| payload_error("deserialization error", e.to_string()))
+}
+
+/// Append an operational event to the event stream (capped at ~EVENTS_STREAM_MAXLEN)
+pub async fn publish_event(
+    conn: &mut impl Connection,
+    event: &crate::types::SystemEvent,
+) -> RedisResult<()> {
+    let payload = serde_json::to_string(event)
+        .map_err(|e| payload_error("serialization error", e.to_string()))?;
+    conn.xadd_maxlen(
+        events_stream_key(),
+        redis::streams::StreamMaxlen::Approx(EVENTS_STREAM_MAXLEN),
+        "*",
+        &[("event", payload)],
+    )
+    .await
+}
+
+/// Create a consumer group on the event stream if it doesn't exist
+/// New groups start at the end of the stream (no backlog replay)
+pub async fn ensure_event_group(
+    conn: &mut impl Connection,
+    group: &str,
+) -> RedisResult<()> {
+    match conn
+        .xgroup_create_mkstream::<_, _, _, ()>(events_stream_key(), group, "$")
+        .await
+    {
+        Err(e) if e.code() == Some("BUSYGROUP") => Ok(()),
+        other => other,
+    }
+}
+
+/// Read new events for a consumer group member, blocking up to `block_ms`
+/// Returns (stream entry id, event) pairs; malformed entries are returned as None
+pub async fn read_events(
+    conn: &mut impl Connection,
+    group: &str,
+    consumer: &str,
+    count: usize,
+    block_ms: usize,
+) -> RedisResult<Vec<(String, Option<crate::types::SystemEvent>)>> {
+    let options = redis::streams::StreamReadOptions::default()
+        .group(group, consumer)
+        .count(count)
+        .block(block_ms);
+    let reply: Option<redis::streams::StreamReadReply> = conn
+        .xread_options(&[events_stream_key()], &[">"], &options)
+        .await?;
+    Ok(parse_event_entries(reply))
+}
+
+/// Read up to `count` events after a stream entry id, without a consumer group
+/// Every reader sees every event; returns immediately when there are none
+pub async fn read_events_after(
+    conn: &mut impl Connection,
+    after_id: &str,
+    count: usize,
+) -> RedisResult<Vec<(String, Option<crate::types::SystemEvent>)>> {
+    let options = redis::streams::StreamReadOptions::default().count(count);
+    let reply: Option<redis::streams::StreamReadReply> = conn
+        .xread_options(&[events_stream_key()], &[after_id], &options)
+        .await?;
+    Ok(parse_event_entries(reply))
+}
+
+/// Take over events another consumer of the group read but never acknowledged for
+/// `min_idle_ms` (its replica died mid-delivery), up to `count` of them
+pub async fn claim_stale_events(
+    conn: &mut impl Connection,
+    group: &str,
+    consumer: &str,
+    min_idle_ms: u64,
+    count: usize,
+) -> RedisResult<Vec<(String, Option<crate::types::SystemEvent>)>> {
+    // Reply: [next cursor, entries, deleted ids (Redis 7+)]
+    let reply: Vec<redis::Value> = redis::cmd("XAUTOCLAIM")
+        .arg(events_stream_key())
+        .arg(group)
+        .arg(consumer)
+        .arg(min_idle_ms)
+        .arg("0-0")
+        .arg("COUNT")
+        .arg(count)
+        .query_async(conn)
+        .await?;
+    let Some(entries) = reply.get(1) else { return Ok(Vec::new()) };
+    let claimed: redis::streams::StreamClaimReply = redis::from_redis_value(entries)?;
+    Ok(claimed.ids.into_iter().map(parse_event_entry).collect())
+}
+
+fn parse_event_entries(
+    reply: Option<redis::streams::StreamReadReply>,
+) -> Vec<(String, Option<crate::types::SystemEvent>)> {
+    reply
+        .into_iter()
+        .flat_map(|reply| reply.keys)
+        .flat_map(|key| key.ids)
+        .map(parse_event_entry)
+        .collect()
+}
+
+fn parse_event_entry(entry: redis::streams::StreamId) -> (String, Option<crate::types::SystemEvent>) {
+    let event = entry
+        .get::<String>("event")
+        .and_then(|payload| serde_json::from_str(&payload).ok());
+    (entry.id, event)
+}
+
+/// Acknowledge a delivered event for a consumer group
+pub async fn ack_event(
+    conn: &mut impl Connection,
+    group: &str,
+    entry_id: &str,
+) -> RedisResult<()> {
+    conn.xack(events_stream_key(), group, &[entry_id]).await
+}
+
+/// Claim the right to send an alert, starting its cooldown
+/// Returns false if the alert was already sent (by any API replica) within `cooldown_secs`
+pub async fn claim_alert(
+    conn: &mut impl Connection,
+    alert_key: &str,
+    cooldown_secs: u64,
+) -> RedisResult<bool> {
+    let claimed: Option<String> = redis::cmd("SET")
+        .arg(alert_cooldown_key(alert_key))
+        .arg(chrono::Utc::now().to_rfc3339())
+        .arg("NX")
+        .arg("EX")
+        .arg(cooldown_secs.max(1))
+        .query_async(conn)
+        .await?;
+    Ok(claimed.is_some())
+}
+
+/// Take the DLQ trimming lock for `ttl_secs`; false while another replica holds it
+pub async fn claim_dlq_trim(
+    conn: &mut impl Connection,
+    holder: &str,
+    ttl_secs: u64,
+) -> RedisResult<bool> {
+    let claimed: Option<String> = redis::cmd("SET")
+        .arg(dlq_trim_lock_key())
+        .arg(holder)
+        .arg("NX")
+        .arg("EX")
+        .arg(ttl_secs.max(1))
+        .query_async(conn)
+        .await?;
+    Ok(claimed.is_some())
+}
+
+/// Delete a key only while it still holds `ARGV[1]`
+const RELEASE_LOCK_SCRIPT: &str = r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+  return redis.call('DEL', KEYS[1])
+end
+return 0
+"#;
+
+/// Release the DLQ trimming lock, unless it expired and another replica took it since
+pub async fn release_dlq_trim(
+    conn: &mut impl Connection,
+    holder: &str,
+) -> RedisResult<()> {
+    let _: i64 = Script::new(RELEASE_LOCK_SCRIPT)
+        .key(dlq_trim_lock_key())
+        .arg(holder)
+        .invoke_async(conn)
+        .await?;
+    Ok(())
+}
+
+/// Pause or resume dequeuing for a language
+pub async fn set_queue_paused(
+    conn: &mut impl Connection,
+    language: &Language,
+    paused: bool,
+) -> RedisResult<()> {
+    if paused {
+        conn.set(queue_paused_key(language), chrono::Utc::now().to_rfc3339()).await
+    } else {
+        conn.del(queue_paused_key(language)).await
+    }
+}
+
+/// How long a drain request is kept; the worker clears it when it exits, so this only
+/// bounds a request left behind by a worker that died while draining
+pub const DRAIN_REQUEST_TTL_SECS: u64 = 3600;
+
+/// Set a drain request only while the worker's heartbeat is live
+const REQUEST_DRAIN_SCRIPT: &str = r#"
+-- KEYS: heartbeat, drain request
+-- ARGV: requested at, ttl
+if redis.call('EXISTS', KEYS[1]) == 0 then
+  return 0
+end
+redis.call('SET', KEYS[2], ARGV[1], 'EX', ARGV[2])
+return 1
+"#;
+
+/// Flag a live worker to stop taking jobs and exit once its running jobs are done;
+/// false when no worker with that id has a live heartbeat
+pub async fn request_worker_drain(
+    conn: &mut impl Connection,
+    worker_id: &str,
+) -> RedisResult<bool> {
+    let requested: i64 = Script::new(REQUEST_DRAIN_SCRIPT)
+        .key(worker_key(worker_id))
+        .key(worker_drain_key(worker_id))
+        .arg(chrono::Utc::now().to_rfc3339())
+        .arg(DRAIN_REQUEST_TTL_SECS)
+        .invoke_async(conn)
+        .await?;
+    Ok(requested == 1)
+}
+
+/// Withdraw a drain request; false when there was none. A worker already draining
+/// keeps draining.
+pub async fn cancel_worker_drain(
+    conn: &mut impl Connection,
+    worker_id: &str,
+) -> RedisResult<bool> {
+    let removed: i64 = conn.del(worker_drain_key(worker_id)).await?;
+    Ok(removed > 0)
+}
+
+pub async fn is_drain_requested(
+    conn: &mut impl Connection,
+    worker_id: &str,
+) -> RedisResult<bool> {
+    conn.exists(worker_drain_key(worker_id)).await
+}
+
+pub async fn is_queue_paused(
+    conn: &mut impl Connection,
+    language: &Language,
+) -> RedisResult<bool> {
+    conn.exists(queue_paused_key(language)).await
+}
+
+/// Record a dequeued job as in flight until `lease_secs` from now
+/// If the worker dies, the lease expires and another worker reclaims the job
+pub async fn claim_inflight(
+    conn: &mut impl Connection,
+    job: &JobRequest,
+    lease_secs: u64,
+) -> RedisResult<()> {
+    let payload = encode_job(job)
+        .map_err(|e| payload_error("serialization error", e))?;
+    let deadline = chrono::Utc::now().timestamp() + lease_secs as i64;
+    redis::pipe()
+        .hset(inflight_jobs_name(&job.language), job.id.to_string(), payload).ignore()
+        .zadd(inflight_queue_name(&job.language), job.id.to_string(), deadline).ignore()
+        .query_async(conn)
+        .await
+}
+
+/// Extend the lease of a job that is still executing
+pub async fn renew_inflight(
+    conn: &mut impl Connection,
+    language: &Language,
+    job_id: &uuid::Uuid,
+    lease_secs: u64,
+) -> RedisResult<()> {
+    let deadline = chrono::Utc::now().timestamp() + lease_secs as i64;
+    redis::cmd("ZADD")
+        .arg(inflight_queue_name(language))
+        .arg("XX")
+        .arg(deadline)
+        .arg(job_id.to_string())
+        .query_async(conn)
+        .await
+}
+
+/// Drop the in-flight record once the job reached a result, retry queue or DLQ
+pub async fn release_inflight(
+    conn: &mut impl Connection,
+    language: &Language,
+    job_id: &uuid::Uuid,
+) -> RedisResult<()> {
+    redis::pipe()
+        .zrem(inflight_queue_name(language), job_id.to_string()).ignore()
+        .hdel(inflight_jobs_name(language), job_id.to_string()).ignore()
+        .query_async(conn)
+        .await
+}
+
+/// In-flight jobs taken back from lost workers
+#[derive(Debug, Default)]
+pub struct Reclaimed {
+    pub jobs: Vec<JobRequest>,
+    /// Ids (and why) of payloads that couldn't be read; they went to the DLQ as stored
+    pub unreadable: Vec<(String, String)>,
+}
+
+/// Take ownership of in-flight jobs whose lease expired (their worker died)
+/// ZREM decides ownership, so concurrent reapers never reclaim the same job twice
+///
+/// A payload that can't be decoded goes to the language's DLQ as stored, so one bad entry
+/// neither loses nor holds back the others.
+pub async fn reclaim_expired_inflight(
+    conn: &mut impl Connection,
+    language: &Language,
+) -> RedisResult<Reclaimed> {
+    let now = chrono::Utc::now().timestamp();
+    let expired: Vec<String> = conn
+        .zrangebyscore(inflight_queue_name(language), "-inf", now)
+        .await?;
+
+    let mut reclaimed = Reclaimed::default();
+    for job_id in expired {
+        let removed: i64 = conn.zrem(inflight_queue_name(language), &job_id).await?;
+        if removed == 0 {
+            continue; // Another worker got it first
+        }
+        let payload: Option<String> = conn.hget(inflight_jobs_name(language), &job_id).await?;
+        let Some(payload) = payload else {
+            continue;
+        };
+        match decode_job(&payload) {
+            Ok(job) => reclaimed.jobs.push(job),
+            Err(e) => {
+                let _: () = conn.rpush(dlq_name(language), &payload).await?;
+                reclaimed.unreadable.push((job_id.clone(), e));
+            }
+        }
+        let _: () = conn.hdel(inflight_jobs_name(language), &job_id).await?;
+    }
+    Ok(reclaimed)
+}
+
+/// Append completed test entries to an attempt's checkpoint
+/// Entries are opaque to this module; they are sealed when encryption at rest is enabled
+pub async fn append_checkpoint(
+    conn: &mut impl Connection,
+    job_id: &uuid::Uuid,
+    attempt: u8,
+    entries: &[String],
+) -> RedisResult<()> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+    let key = checkpoint_key(job_id, attempt);
+    let mut pipe = redis::pipe();
+    for entry in entries {
+        let stored = match crypto::global_cipher() {
+            Some(cipher) => cipher.seal_str(entry)
+                .map_err(|e| payload_error("serialization error", e))?,
+            None => entry.clone(),
+        };
+        pipe.rpush(&key, stored).ignore();
+    }
+    pipe.expire(&key, 86400).ignore();
+    pipe.query_async(conn).await
+}
+
+/// Read back an attempt's checkpoint entries in the order they were written
+pub async fn load_checkpoint(
+    conn: &mut impl Connection,
+    job_id: &uuid::Uuid,
+    attempt: u8,
+) -> RedisResult<Vec<String>> {
+    let items: Vec<String> = conn.lrange(checkpoint_key(job_id, attempt), 0, -1).await?;
+    items
+        .into_iter()
+        .map(|item| {
+            if !crypto::is_sealed(&item) {
+                return Ok(item);
+            }
+            crypto::global_cipher()
+                .ok_or_else(|| "Checkpoint is encrypted but no encryption keys are configured".to_string())
+                .and_then(|cipher| cipher.open_str(&item))
+                .map_err(|e| payload_error("deserialization error", e))
+        })
+        .collect()
+}
+
+/// Remove checkpoints for every attempt up to and including `last_attempt`
+pub async fn delete_checkpoints(
+    conn: &mut impl Connection,
+    job_id: &uuid::Uuid,
+    last_attempt: u8,
+) -> RedisResult<()> {
+    let keys: Vec<String> = (0..=last_attempt).map(|a| checkpoint_key(job_id, a)).collect();
+    conn.del(keys).await
+}
+
+/// Clear progress left by a previous attempt before a job starts executing
+pub async fn reset_progress(
+    conn: &mut impl Connection,
+    job_id: &uuid::Uuid,
+) -> RedisResult<()> {
+    conn.del(progress_key(job_id)).await
+}
+
+/// A job's status: "queued" from submission until its result is stored, then the final one
+/// (None once the job is unknown or expired)
+pub async fn get_job_status(
+    conn: &mut impl Connection,
+    job_id: &uuid::Uuid,
+) -> RedisResult<Option<crate::types::JobStatus>> {
+    let status: Option<String> = conn.get(status_key(job_id)).await?;
+    Ok(status.and_then(|s| serde_json::from_str(&s).ok()))
+}
+
+/// Append a finished test to the job's progress list and publish it as a `test` event
+/// TTL of 24 hours to match result expiry
+pub async fn push_progress(
+    conn: &mut impl Connection,
+    job_id: &uuid::Uuid,
+    progress: &crate::types::TestProgress,
+) -> RedisResult<()> {
+    let key = progress_key(job_id);
+    let payload = serde_json::to_string(progress)
+        .map_err(|e| payload_error("serialization error", e.to_string()))?;
+    let event = progress_event_payload(job_id, crate::types::ProgressEvent::Test(progress.clone()))?;
+    redis::pipe()
+        .rpush(&key, payload).ignore()
+        .expire(&key, 86400).ignore()
+        .publish(progress_channel(), event).ignore()
+        .query_async(conn)
+        .await
+}
+
+fn progress_event_payload(job_id: &uuid::Uuid, event: crate::types::ProgressEvent) -> RedisResult<String> {
+    serde_json::to_string(&crate::types::JobProgressEvent { job_id: *job_id, event })
+        .map_err(|e| payload_error("serialization error", e.to_string()))
+}
+
+/// Publish a progress event for live streams (fire-and-forget, no subscribers required)
+pub async fn publish_progress(
+    conn: &mut impl Connection,
+    job_id: &uuid::Uuid,
+    event: crate::types::ProgressEvent,
+) -> RedisResult<()> {
+    let payload = progress_event_payload(job_id, event)?;
+    let _: i64 = conn.publish(progress_channel(), payload).await.unwrap_or(0);
+    Ok(())
+}
+
+/// Per-test progress published so far, in completion order
+pub async fn get_progress(
+    conn: &mut impl Connection,
+    job_id: &uuid::Uuid,
+) -> RedisResult<Vec<crate::types::TestProgress>> {
+    let items: Vec<String> = conn.lrange(progress_key(job_id), 0, -1).await?;
+    Ok(items.iter().filter_map(|item| serde_json::from_str(item).ok()).collect())
+}
+
+/// Record a submission in the tenant's retention index
+pub async fn index_submission(
+    conn: &mut impl Connection,
+    tenant: &str,
+    job_id: &uuid::Uuid,
+    submitted_at: i64,
+    idempotency_redis_key: Option<&str>,
+) -> RedisResult<()> {
+    let mut pipe = redis::pipe();
+    pipe.sadd(tenant_index_key(), tenant).ignore()
+        .zadd(submission_index_key(tenant), job_id.to_string(), submitted_at).ignore();
+    if let Some(key) = idempotency_redis_key {
+        pipe.hset(idempotency_index_key(), job_id.to_string(), key).ignore();
+    }
+    pipe.query_async(conn).await
+}
+
+/// Prepend a submission to the capped recent jobs list
+pub async fn record_recent_job(
+    conn: &mut impl Connection,
+    job: &crate::types::RecentJob,
+) -> RedisResult<()> {
+    let payload = serde_json::to_string(job)
+        .map_err(|e| payload_error("serialization error", e.to_string()))?;
+    redis::pipe()
+        .lpush(recent_jobs_key(), payload).ignore()
+        .ltrim(recent_jobs_key(), 0, RECENT_JOBS_LIMIT - 1).ignore()
+        .query_async(conn)
+        .await
+}
+
+/// Most recent submissions, newest first
+pub async fn get_recent_jobs(
+    conn: &mut impl Connection,
+    limit: isize,
+) -> RedisResult<Vec<crate::types::RecentJob>> {
+    let items: Vec<String> = conn.lrange(recent_jobs_key(), 0, limit.max(1) - 1).await?;
+    Ok(items.iter().filter_map(|item| serde_json::from_str(item).ok()).collect())
+}
+
+/// Publish a worker heartbeat that expires after `ttl_secs`
+pub async fn publish_heartbeat(
+    conn: &mut impl Connection,
+    heartbeat: &crate::types::WorkerHeartbeat,
+    ttl_secs: u64,
+) -> RedisResult<()> {
+    let payload = serde_json::to_string(heartbeat)
+        .map_err(|e| payload_error("serialization error", e.to_string()))?;
+    conn.set_ex(worker_key(&heartbeat.worker_id), payload, ttl_secs).await
+}
+
+/// How long a graceful shutdown is remembered; outlives any heartbeat TTL
+pub const WORKER_STOPPED_TTL_SECS: u64 = 3600;
+
+/// Record that a worker shut down gracefully, so its expiring heartbeat is not taken
+/// for a crash. Unlike the worker_down event, the marker survives stream trimming.
+pub async fn mark_worker_stopped(conn: &mut impl Connection, worker_id: &str) -> RedisResult<()> {
+    conn.set_ex(worker_stopped_key(worker_id), 1, WORKER_STOPPED_TTL_SECS).await
+}
+
+/// Which of `worker_ids` shut down gracefully
+pub async fn stopped_workers(
+    conn: &mut impl Connection,
+    worker_ids: &[String],
+) -> RedisResult<std::collections::HashSet<String>> {
+    if worker_ids.is_empty() {
+        return Ok(Default::default());
+    }
+    let keys: Vec<String> = worker_ids.iter().map(|id| worker_stopped_key(id)).collect();
+    let markers: Vec<Option<String>> = redis::cmd("MGET").arg(&keys).query_async(conn).await?;
+    Ok(worker_ids
+        .iter()
+        .zip(markers)
+        .filter(|(_, marker)| marker.is_some())
+        .map(|(id, _)| id.clone())
+        .collect())
+}
+
+/// Cache the toolchain versions probed from a language's judge image
+pub async fn store_runtime_info(
+    conn: &mut impl Connection,
+    info: &crate::types::RuntimeInfo,
+) -> RedisResult<()> {
+    let payload = serde_json::to_string(info)
+        .map_err(|e| payload_error("serialization error", e.to_string()))?;
+    conn.set(runtime_info_key(&info.language), payload).await
+}
+
+/// Cached toolchain versions for a language, if a worker has probed its image
+pub async fn get_runtime_info(
+    conn: &mut impl Connection,
+    language: &Language,
+) -> RedisResult<Option<crate::types::RuntimeInfo>> {
+    let payload: Option<String> = conn.get(runtime_info_key(language)).await?;
+    payload
+        .map(|p| serde_json::from_str(&p))
+        .transpose()
+        .map_err(|e| payload_error("deserialization error", e.to_string()))
+}
+
+/// All live worker heartbeats
+pub async fn list_heartbeats(
+    conn: &mut impl Connection,
+) -> RedisResult<Vec<crate::types::WorkerHeartbeat>> {
+    let mut keys: Vec<String> = Vec::new();
+    let mut cursor: u64 = 0;
+    loop {
+        let (next, batch): (u64, Vec<String>) = redis::cmd("SCAN")
+            .arg(cursor)
+            .arg("MATCH")
+            .arg(format!("{}:*", glob_escape(&namespaced(WORKER_PREFIX))))
+            .arg("COUNT")
+            .arg(100)
+            .query_async(conn)
+            .await?;
+        keys.extend(batch);
+        cursor = next;
+        if cursor == 0 {
+            break;
+        }
+    }
+    if keys.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let items: Vec<Option<String>> = redis::cmd("MGET").arg(&keys).query_async(conn).await?;
+    let mut heartbeats: Vec<crate::types::WorkerHeartbeat> = items
+        .into_iter()
+        .flatten()
+        .filter_map(|item| serde_json::from_str(&item).ok())
+        .collect();
+    heartbeats.sort_by(|a, b| a.worker_id.cmp(&b.worker_id));
+    Ok(heartbeats)
+}
+
+/// One page of stored idempotency keys whose client key starts with `prefix`
+///
+/// Pages follow a SCAN cursor (0 starts over); returns the entries, sorted by key, and the
+/// cursor of the next page (None after the last). A page holds about `limit` entries.
+pub async fn list_idempotency_keys(
+    conn: &mut impl Connection,
+    prefix: &str,
+    cursor: u64,
+    limit: usize,
+) -> RedisResult<(Vec<crate::types::IdempotencyEntry>, Option<u64>)> {
+    let pattern = format!("{}*", glob_escape(&idempotency_key(prefix)));
+    let mut keys: Vec<String> = Vec::new();
+    let mut cursor = cursor;
+    loop {
+        let (next, batch): (u64, Vec<String>) = redis::cmd("SCAN")
+            .arg(cursor)
+            .arg("MATCH")
+            .arg(&pattern)
+            .arg("COUNT")
+            .arg(100)
+            .query_async(conn)
+            .await?;
+        keys.extend(batch);
+        cursor = next;
+        if cursor == 0 || keys.len() >= limit {
+            break;
+        }
+    }
+    let next = (cursor != 0).then_some(cursor);
+    if keys.is_empty() {
+        return Ok((Vec::new(), next));
+    }
+
+    let mut pipe = redis::pipe();
+    for key in &keys {
+        pipe.get(key).ttl(key);
+    }
+    let values: Vec<(Option<String>, i64)> = pipe.query_async(conn).await?;
+    let mut entries: Vec<crate::types::IdempotencyEntry> = keys
+        .iter()
+        .zip(values)
+        // Keys that expired since the scan are skipped
+        .filter_map(|(key, (data, ttl_secs))| Some(idempotency_entry(key, &data?, ttl_secs)))
+        .collect();
+    entries.sort_by(|a, b| a.key.cmp(&b.key));
+    Ok((entries, next))
+}
+
+/// Delete a client's idempotency key so it can be reused; returns what was stored
+pub async fn delete_idempotency_key(
+    conn: &mut impl Connection,
+    key: &str,
+) -> RedisResult<Option<crate::types::IdempotencyEntry>> {
+    let redis_key = idempotency_key(key);
+    let (data, ttl_secs): (Option<String>, i64) = redis::pipe().get(&redis_key).ttl(&redis_key).query_async(conn).await?;
+    let Some(data) = data else { return Ok(None) };
+    let entry = idempotency_entry(&redis_key, &data, ttl_secs);
+
+    let mut pipe = redis::pipe();
+    pipe.del(&redis_key).ignore();
+    if let Some(job_id) = &entry.job_id {
+        pipe.hdel(idempotency_index_key(), job_id).ignore();
+    }
+    let _: () = pipe.query_async(conn).await?;
+    Ok(Some(entry))
+}
+
+fn idempotency_entry(redis_key: &str, data: &str, ttl_secs: i64) -> crate::types::IdempotencyEntry {
+    let stored: serde_json::Value = serde_json::from_str(data).unwrap_or_default();
+    let field = |name: &str| stored.get(name).and_then(|v| v.as_str()).map(str::to_string);
+    crate::types::IdempotencyEntry {
+        key: redis_key.strip_prefix(&idempotency_key("")).unwrap_or(redis_key).to_string(),
+        job_id: field("job_id"),
+        created_at: field("created_at"),
+        ttl_secs,
+    }
+}
+
 /// Set cancellation flag for a job
 /// TTL of 24 hours to match result expiry
 pub async fn set_job_cancelled(
-    conn: &mut redis::aio::ConnectionManager,
+    conn: &mut impl Connection,
     job_id: &uuid::Uuid,
 ) -> RedisResult<()> {
     let key = control_key(job_id);
@@ -219,12 +2026,39 @@ pub async fn set_job_cancelled(
         .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "serialization error", e.to_string())))?;
     
     // Store with 24-hour TTL
-    conn.set_ex(&key, payload, 86400).await
+    let _: () = conn.set_ex(&key, payload, 86400).await?;
+
+    // Wake the worker running the job; the flag above covers workers that miss this
+    let _: i64 = conn.publish(cancel_channel(), job_id.to_string()).await.unwrap_or(0);
+    Ok(())
+}
+
+/// The subset of `job_ids` whose cancellation flag is set (one round trip)
+pub async fn cancelled_jobs(
+    conn: &mut impl Connection,
+    job_ids: &[uuid::Uuid],
+) -> RedisResult<Vec<uuid::Uuid>> {
+    if job_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let keys: Vec<String> = job_ids.iter().map(control_key).collect();
+    let payloads: Vec<Option<String>> = redis::cmd("MGET").arg(&keys).query_async(conn).await?;
+    Ok(job_ids
+        .iter()
+        .zip(payloads)
+        .filter(|(_, payload)| {
+            payload
+                .as_deref()
+                .and_then(|data| serde_json::from_str::<crate::types::JobControl>(data).ok())
+                .is_some_and(|control| control.cancelled)
+        })
+        .map(|(job_id, _)| *job_id)
+        .collect())
 }
 
 /// Check if a job has been cancelled
 pub async fn is_job_cancelled(
-    conn: &mut redis::aio::ConnectionManager,
+    conn: &mut impl Connection,
     job_id: &uuid::Uuid,
 ) -> RedisResult<bool> {
     let key = control_key(job_id);
@@ -240,6 +2074,846 @@ pub async fn is_job_cancelled(
     }
 }
 
+/// Start a submitter's cool-down on a problem, unless one is already running
+///
+/// Returns None when the submission may go ahead (the cool-down now runs for `secs`),
+/// or the seconds left of the running one.
+pub async fn claim_submission_cooldown(
+    conn: &mut impl Connection,
+    problem_id: &str,
+    submitter: &str,
+    secs: u64,
+) -> RedisResult<Option<u64>> {
+    let key = submission_cooldown_key(problem_id, submitter);
+    let claimed: Option<String> = redis::cmd("SET")
+        .arg(&key)
+        .arg(chrono::Utc::now().timestamp())
+        .arg("NX")
+        .arg("EX")
+        .arg(secs)
+        .query_async(conn)
+        .await?;
+    if claimed.is_some() {
+        return Ok(None);
+    }
+    // Expired between the two commands: still report a wait, the client just retries
+    let ttl: i64 = conn.ttl(&key).await?;
+    Ok(Some(ttl.max(1) as u64))
+}
+
+/// Token bucket: KEYS[1] bucket, ARGV[1] capacity, ARGV[2] refill window in ms (a full
+/// bucket's worth of tokens per window). Takes one token and returns 0, or returns the
+/// ms until one is available. Uses the server's clock, so API replicas agree.
+const TAKE_TOKEN_SCRIPT: &str = r#"
+local capacity = tonumber(ARGV[1])
+local window = tonumber(ARGV[2])
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+local bucket = redis.call('HMGET', KEYS[1], 'tokens', 'refilled_ms')
+local tokens = tonumber(bucket[1]) or capacity
+local refilled = tonumber(bucket[2]) or now
+tokens = math.min(capacity, tokens + (now - refilled) * capacity / window)
+local wait = 0
+if tokens >= 1 then
+  tokens = tokens - 1
+else
+  wait = math.ceil((1 - tokens) * window / capacity)
+end
+redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'refilled_ms', now)
+redis.call('PEXPIRE', KEYS[1], window * 2)
+return wait
+"#;
+
+fn take_token_script() -> &'static Script {
+    static SCRIPT: OnceLock<Script> = OnceLock::new();
+    SCRIPT.get_or_init(|| Script::new(TAKE_TOKEN_SCRIPT))
+}
+
+/// Take one submission token from an API key's bucket (`per_minute` tokens a minute)
+///
+/// Returns None when the submission may go ahead, or the seconds until it may.
+pub async fn take_rate_token(
+    conn: &mut impl Connection,
+    api_key_id: &str,
+    per_minute: u32,
+) -> RedisResult<Option<u64>> {
+    let wait_ms: u64 = take_token_script()
+        .key(rate_limit_key(api_key_id))
+        .arg(per_minute.max(1))
+        .arg(60_000)
+        .invoke_async(conn)
+        .await?;
+    Ok((wait_ms > 0).then(|| wait_ms.div_ceil(1000)))
+}
+
+/// Refund: KEYS[1] bucket, ARGV[1] capacity. Puts one token back, never past capacity; a
+/// bucket that has expired is full already.
+const REFUND_TOKEN_SCRIPT: &str = r#"
+local tokens = tonumber(redis.call('HGET', KEYS[1], 'tokens'))
+if not tokens then
+  return 0
+end
+redis.call('HSET', KEYS[1], 'tokens', tostring(math.min(tonumber(ARGV[1]), tokens + 1)))
+return 1
+"#;
+
+fn refund_token_script() -> &'static Script {
+    static SCRIPT: OnceLock<Script> = OnceLock::new();
+    SCRIPT.get_or_init(|| Script::new(REFUND_TOKEN_SCRIPT))
+}
+
+/// Give a token taken by `take_rate_token` back to an API key's bucket, for a submission
+/// that was not queued
+pub async fn refund_rate_token(
+    conn: &mut impl Connection,
+    api_key_id: &str,
+    per_minute: u32,
+) -> RedisResult<()> {
+    let _: i64 = refund_token_script()
+        .key(rate_limit_key(api_key_id))
+        .arg(per_minute.max(1))
+        .invoke_async(conn)
+        .await?;
+    Ok(())
+}
+
+/// CPU-seconds charged to an API key on a UTC day
+pub async fn cpu_seconds_used(
+    conn: &mut impl Connection,
+    api_key_id: &str,
+    day: &str,
+) -> RedisResult<f64> {
+    let used: Option<f64> = conn.get(cpu_quota_key(api_key_id, day)).await?;
+    Ok(used.unwrap_or(0.0))
+}
+
+/// Remember which API key a queued job's CPU time is charged to (kept like results)
+pub async fn set_quota_owner(
+    conn: &mut impl Connection,
+    job_id: &uuid::Uuid,
+    api_key_id: &str,
+) -> RedisResult<()> {
+    conn.set_ex(quota_owner_key(job_id), api_key_id, RESULT_TTL_SECS).await
+}
+
+/// Charge: KEYS[1] owner key; ARGV[1] CPU-seconds, ARGV[2] day, ARGV[3] quota key prefix.
+/// The owner key is taken with the charge, so a job is charged once however many API
+/// replicas see its completion.
+const CHARGE_CPU_SCRIPT: &str = r#"
+local owner = redis.call('GET', KEYS[1])
+if not owner then
+  return false
+end
+redis.call('DEL', KEYS[1])
+local key = ARGV[3] .. ':' .. owner .. ':cpu:' .. ARGV[2]
+redis.call('INCRBYFLOAT', key, ARGV[1])
+redis.call('EXPIRE', key, 172800)
+return owner
+"#;
+
+fn charge_cpu_script() -> &'static Script {
+    static SCRIPT: OnceLock<Script> = OnceLock::new();
+    SCRIPT.get_or_init(|| Script::new(CHARGE_CPU_SCRIPT))
+}
+
+/// CPU-seconds a result is charged: per test, the runner's CPU time, but never less than
+/// the wall time the engine measured around the run (a forged report can't lower it)
+pub fn charged_cpu_seconds(result: &crate::types::ExecutionResult) -> f64 {
+    let ms: u64 = result
+        .results
+        .iter()
+        .map(|r| {
+            let wall_ms = r.execution_time_ms + r.overhead_ms.unwrap_or(0);
+            r.cpu_time_ms.unwrap_or(0).max(wall_ms)
+        })
+        .sum();
+    ms as f64 / 1000.0
+}
+
+/// Charge a completed job's CPU time to the API key that submitted it, on `day`
+///
+/// Returns the key charged, or None when the job has no owner (submitted without a CPU
+/// quota, already charged, or expired).
+pub async fn charge_cpu_seconds(
+    conn: &mut impl Connection,
+    job_id: &uuid::Uuid,
+    cpu_seconds: f64,
+    day: &str,
+) -> RedisResult<Option<String>> {
+    // The owner's key is built in the script; a Redis Cluster would need a hash tag
+    charge_cpu_script()
+        .key(quota_owner_key(job_id))
+        .arg(cpu_seconds)
+        .arg(day)
+        .arg(namespaced(QUOTA_PREFIX))
+        .invoke_async(conn)
+        .await
+}
+
+/// End a cool-down early, for a submission that was claimed but never queued
+pub async fn release_submission_cooldown(
+    conn: &mut impl Connection,
+    problem_id: &str,
+    submitter: &str,
+) -> RedisResult<()> {
+    conn.del(submission_cooldown_key(problem_id, submitter)).await
+}
+
+/// Tombstone a job: hide it, drop it from its tenant's retention index and schedule its purge
+///
+/// Returns the tombstone in place - the earlier one if the job was already deleted. The
+/// tombstone outlives the purge by a day, so the job keeps reading as deleted, not pending.
+pub async fn tombstone_job(
+    conn: &mut impl Connection,
+    tombstone: &crate::types::Tombstone,
+) -> RedisResult<crate::types::Tombstone> {
+    let key = tombstone_key(&tombstone.job_id);
+    let payload = serde_json::to_string(tombstone)
+        .map_err(|e| payload_error("serialization error", e.to_string()))?;
+    let window_secs = (tombstone.purge_at - tombstone.deleted_at).num_seconds().max(0) as u64;
+    let created: Option<String> = redis::cmd("SET")
+        .arg(&key)
+        .arg(payload)
+        .arg("NX")
+        .arg("EX")
+        .arg(window_secs + RESULT_TTL_SECS)
+        .query_async(conn)
+        .await?;
+    if created.is_none() {
+        return get_tombstone(conn, &tombstone.job_id)
+            .await?
+            .ok_or_else(|| payload_error("deserialization error", format!("tombstone of {} vanished", tombstone.job_id)));
+    }
+
+    let member = tombstone.job_id.to_string();
+    redis::pipe()
+        .zadd(tombstone_index_key(), &member, tombstone.purge_at.timestamp()).ignore()
+        .zrem(submission_index_key(&tombstone.tenant), &member).ignore()
+        .query_async::<_, ()>(conn)
+        .await?;
+    Ok(tombstone.clone())
+}
+
+/// Tombstone of a job, if it was deleted
+pub async fn get_tombstone(
+    conn: &mut impl Connection,
+    job_id: &uuid::Uuid,
+) -> RedisResult<Option<crate::types::Tombstone>> {
+    let payload: Option<String> = conn.get(tombstone_key(job_id)).await?;
+    payload
+        .map(|p| serde_json::from_str(&p))
+        .transpose()
+        .map_err(|e| payload_error("deserialization error", e.to_string()))
+}
+
+/// Which of `job_ids` were deleted (have a tombstone)
+pub async fn deleted_jobs(
+    conn: &mut impl Connection,
+    job_ids: &[uuid::Uuid],
+) -> RedisResult<std::collections::HashSet<uuid::Uuid>> {
+    if job_ids.is_empty() {
+        return Ok(std::collections::HashSet::new());
+    }
+    let keys: Vec<String> = job_ids.iter().map(tombstone_key).collect();
+    let tombstones: Vec<Option<String>> = redis::cmd("MGET").arg(&keys).query_async(conn).await?;
+    Ok(job_ids.iter().zip(tombstones).filter(|(_, t)| t.is_some()).map(|(id, _)| *id).collect())
+}
+
+/// Claim a tombstoned job for purging or restoring
+///
+/// Removes it from the purge index; false if it was not there (restored, already
+/// purged or claimed by someone else), so only one of purge and restore ever proceeds.
+pub async fn claim_tombstone(
+    conn: &mut impl Connection,
+    job_id: &uuid::Uuid,
+) -> RedisResult<bool> {
+    let removed: i64 = conn.zrem(tombstone_index_key(), job_id.to_string()).await?;
+    Ok(removed > 0)
+}
+
+/// Undo a deletion that `claim_tombstone` won: drop the tombstone and re-index the job
+pub async fn restore_tombstone(
+    conn: &mut impl Connection,
+    tombstone: &crate::types::Tombstone,
+) -> RedisResult<()> {
+    let mut pipe = redis::pipe();
+    pipe.del(tombstone_key(&tombstone.job_id)).ignore();
+    if let Some(submitted_at) = tombstone.submitted_at {
+        pipe.zadd(submission_index_key(&tombstone.tenant), tombstone.job_id.to_string(), submitted_at).ignore();
+    }
+    pipe.query_async(conn).await
+}
+
+/// Tombstoned jobs whose purge time has passed, oldest first
+pub async fn due_tombstones(
+    conn: &mut impl Connection,
+    now: i64,
+    limit: usize,
+) -> RedisResult<Vec<uuid::Uuid>> {
+    let members: Vec<String> = redis::cmd("ZRANGEBYSCORE")
+        .arg(tombstone_index_key())
+        .arg("-inf")
+        .arg(now)
+        .arg("LIMIT")
+        .arg(0)
+        .arg(limit)
+        .query_async(conn)
+        .await?;
+    Ok(members.iter().filter_map(|m| uuid::Uuid::parse_str(m).ok()).collect())
+}
+
+/// Time range folded into a leaderboard's sort value: score * span - time, so a higher
+/// score always ranks first and a faster time breaks ties (times are capped at the span)
+///
+/// Exact while score * span stays below 2^53, i.e. scores under 2^29.
+pub const LEADERBOARD_TIME_SPAN_MS: u64 = 1 << 24;
+
+/// Keep a participant's best result on the problem leaderboard and, for contest
+/// submissions, their best per problem in the contest, with totals summed over problems
+///
+/// Every ranked result is kept per participant and problem (see `leaderboard_results_key`),
+/// so an override or deletion can fall back to the next best.
+const RECORD_LEADERBOARD_SCRIPT: &str = r#"
+-- KEYS: registered test sets, ranked job, problem board, problem entries, problem results,
+--       [contest board, contest entries, contest results]
+-- ARGV: test-set fingerprint, participant, problem id, score, time ms, job id, time span, tag
+if redis.call('SISMEMBER', KEYS[1], ARGV[1]) == 0 then
+  return -1
+end
+local span = tonumber(ARGV[7])
+local score, time = tonumber(ARGV[4]), tonumber(ARGV[5])
+local best = {score = score, time_ms = time, job_id = ARGV[6]}
+local function better(old)
+  return old == nil or score > old.score or (score == old.score and time < old.time_ms)
+end
+local function sort_value(s, t)
+  return s * span - math.min(t, span - 1)
+end
+
+redis.call('SET', KEYS[2], ARGV[8])
+local ranked = cjson.encode({score = score, time_ms = time})
+local changed = 0
+redis.call('HSET', KEYS[5], ARGV[6], ranked)
+local raw = redis.call('HGET', KEYS[4], ARGV[2])
+if better(raw and cjson.decode(raw) or nil) then
+  redis.call('HSET', KEYS[4], ARGV[2], cjson.encode(best))
+  redis.call('ZADD', KEYS[3], sort_value(score, time), ARGV[2])
+  changed = 1
+end
+
+if #KEYS == 8 then
+  redis.call('HSET', KEYS[8], ARGV[6], ranked)
+  raw = redis.call('HGET', KEYS[7], ARGV[2])
+  local entry = raw and cjson.decode(raw) or {problems = {}}
+  if better(entry.problems[ARGV[3]]) then
+    entry.problems[ARGV[3]] = best
+    entry.score, entry.time_ms = 0, 0
+    for _, problem in pairs(entry.problems) do
+      entry.score = entry.score + problem.score
+      entry.time_ms = entry.time_ms + problem.time_ms
+    end
+    redis.call('HSET', KEYS[7], ARGV[2], cjson.encode(entry))
+    redis.call('ZADD', KEYS[6], sort_value(entry.score, entry.time_ms), ARGV[2])
+    changed = 1
+  end
+end
+return changed
+"#;
+
+fn record_leaderboard_script() -> &'static Script {
+    static SCRIPT: OnceLock<Script> = OnceLock::new();
+    SCRIPT.get_or_init(|| Script::new(RECORD_LEADERBOARD_SCRIPT))
+}
+
+/// Change one ranked result and re-derive the participant's best from the rest: a new
+/// score (override), hidden or shown again (deletion and restore), or purged for good
+const RESCORE_LEADERBOARD_SCRIPT: &str = r#"
+-- KEYS: problem board, problem entries, problem results, [contest board, contest entries, contest results]
+-- ARGV: participant, problem id, job id, action (score/hide/show/purge), score, time span
+local span = tonumber(ARGV[6])
+local function sort_value(s, t)
+  return s * span - math.min(t, span - 1)
+end
+local function update(results)
+  local raw = redis.call('HGET', results, ARGV[3])
+  if not raw then
+    return false
+  end
+  local ranked = cjson.decode(raw)
+  if ARGV[4] == 'purge' then
+    redis.call('HDEL', results, ARGV[3])
+    return true
+  elseif ARGV[4] == 'score' then
+    ranked.score = tonumber(ARGV[5])
+  else
+    ranked.hidden = ARGV[4] == 'hide' or nil
+  end
+  redis.call('HSET', results, ARGV[3], cjson.encode(ranked))
+  return true
+end
+local function best_of(results)
+  local best
+  local all = redis.call('HGETALL', results)
+  for i = 1, #all, 2 do
+    local r = cjson.decode(all[i + 1])
+    if not r.hidden and (best == nil or r.score > best.score or (r.score == best.score and r.time_ms < best.time_ms)) then
+      best = {score = r.score, time_ms = r.time_ms, job_id = all[i]}
+    end
+  end
+  return best
+end
+local function rank(board, entries, entry)
+  if entry then
+    redis.call('HSET', entries, ARGV[1], cjson.encode(entry))
+    redis.call('ZADD', board, sort_value(entry.score, entry.time_ms), ARGV[1])
+  else
+    redis.call('HDEL', entries, ARGV[1])
+    redis.call('ZREM', board, ARGV[1])
+  end
+end
+
+local changed = 0
+if update(KEYS[3]) then
+  rank(KEYS[1], KEYS[2], best_of(KEYS[3]))
+  changed = 1
+end
+if #KEYS == 6 and update(KEYS[6]) then
+  local raw = redis.call('HGET', KEYS[5], ARGV[1])
+  local entry = raw and cjson.decode(raw) or {problems = {}}
+  entry.problems[ARGV[2]] = best_of(KEYS[6])
+  entry.score, entry.time_ms = 0, 0
+  local problems = 0
+  for _, problem in pairs(entry.problems) do
+    entry.score = entry.score + problem.score
+    entry.time_ms = entry.time_ms + problem.time_ms
+    problems = problems + 1
+  end
+  rank(KEYS[4], KEYS[5], problems > 0 and entry or nil)
+  changed = 1
+end
+return changed
+"#;
+
+fn rescore_leaderboard_script() -> &'static Script {
+    static SCRIPT: OnceLock<Script> = OnceLock::new();
+    SCRIPT.get_or_init(|| Script::new(RESCORE_LEADERBOARD_SCRIPT))
+}
+
+/// Summed test execution time a leaderboard ranks a result by
+pub fn leaderboard_time_ms(result: &crate::types::ExecutionResult) -> u64 {
+    result.results.iter().map(|t| t.execution_time_ms).sum()
+}
+
+/// Rank a persisted result on its problem's (and contest's) leaderboard if it beats the
+/// participant's best; returns whether a leaderboard changed, or None when the result
+/// was judged against a test set not registered for the problem (it is not ranked)
+///
+/// The boards are updated incrementally here, so reading them never rescans results.
+pub async fn record_leaderboard(
+    conn: &mut impl Connection,
+    tag: &crate::types::LeaderboardTag,
+    fingerprint: &str,
+    result: &crate::types::ExecutionResult,
+) -> RedisResult<Option<bool>> {
+    let encoded_tag = serde_json::to_string(tag)
+        .map_err(|e| payload_error("serialization error", e.to_string()))?;
+    let problem_board = problem_leaderboard_key(&tag.problem_id);
+    let mut invocation = record_leaderboard_script().prepare_invoke();
+    invocation
+        .key(problem_test_sets_key(&tag.problem_id))
+        .key(ranked_job_key(&result.job_id))
+        .key(&problem_board)
+        .key(leaderboard_entries_key(&problem_board))
+        .key(leaderboard_results_key(&problem_board, &tag.participant, &tag.problem_id));
+    if let Some(contest_id) = &tag.contest_id {
+        let contest_board = contest_leaderboard_key(contest_id);
+        invocation
+            .key(&contest_board)
+            .key(leaderboard_entries_key(&contest_board))
+            .key(leaderboard_results_key(&contest_board, &tag.participant, &tag.problem_id));
+    }
+    let changed: i64 = invocation
+        .arg(fingerprint)
+        .arg(&tag.participant)
+        .arg(&tag.problem_id)
+        .arg(result.score)
+        .arg(leaderboard_time_ms(result))
+        .arg(result.job_id.to_string())
+        .arg(LEADERBOARD_TIME_SPAN_MS)
+        .arg(encoded_tag)
+        .invoke_async(conn)
+        .await?;
+    Ok((changed >= 0).then_some(changed == 1))
+}
+
+/// What happens to a ranked result (see `rescore_leaderboard`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeaderboardChange {
+    /// Rank it by this score instead (a manual override)
+    Score(u32),
+    /// Stop ranking it, but keep it for a restore (a deleted job)
+    Hide,
+    /// Rank it again (a restored job)
+    Show,
+    /// Forget it (a deleted job being purged)
+    Purge,
+}
+
+/// Leaderboard tag of a job whose result was ranked
+async fn ranked_job_tag(
+    conn: &mut impl Connection,
+    job_id: &uuid::Uuid,
+) -> RedisResult<Option<crate::types::LeaderboardTag>> {
+    let encoded: Option<String> = conn.get(ranked_job_key(job_id)).await?;
+    Ok(encoded.and_then(|t| serde_json::from_str(&t).ok()))
+}
+
+/// Apply a change to a ranked job's result on every board it is on, re-deriving the
+/// participant's best; returns whether the job was ranked at all
+pub async fn rescore_leaderboard(
+    conn: &mut impl Connection,
+    job_id: &uuid::Uuid,
+    change: LeaderboardChange,
+) -> RedisResult<bool> {
+    let Some(tag) = ranked_job_tag(conn, job_id).await? else {
+        return Ok(false);
+    };
+    let problem_board = problem_leaderboard_key(&tag.problem_id);
+    let mut invocation = rescore_leaderboard_script().prepare_invoke();
+    invocation
+        .key(&problem_board)
+        .key(leaderboard_entries_key(&problem_board))
+        .key(leaderboard_results_key(&problem_board, &tag.participant, &tag.problem_id));
+    if let Some(contest_id) = &tag.contest_id {
+        let contest_board = contest_leaderboard_key(contest_id);
+        invocation
+            .key(&contest_board)
+            .key(leaderboard_entries_key(&contest_board))
+            .key(leaderboard_results_key(&contest_board, &tag.participant, &tag.problem_id));
+    }
+    let (action, score) = match change {
+        LeaderboardChange::Score(score) => ("score", score),
+        LeaderboardChange::Hide => ("hide", 0),
+        LeaderboardChange::Show => ("show", 0),
+        LeaderboardChange::Purge => ("purge", 0),
+    };
+    let changed: i64 = invocation
+        .arg(&tag.participant)
+        .arg(&tag.problem_id)
+        .arg(job_id.to_string())
+        .arg(action)
+        .arg(score)
+        .arg(LEADERBOARD_TIME_SPAN_MS)
+        .invoke_async(conn)
+        .await?;
+    if change == LeaderboardChange::Purge {
+        conn.del::<_, ()>(ranked_job_key(job_id)).await?;
+    }
+    Ok(changed == 1)
+}
+
+/// Register a test set whose results a problem's leaderboard ranks; false if it already was
+pub async fn register_test_set(
+    conn: &mut impl Connection,
+    problem_id: &str,
+    fingerprint: &str,
+) -> RedisResult<bool> {
+    let added: i64 = conn.sadd(problem_test_sets_key(problem_id), fingerprint).await?;
+    Ok(added == 1)
+}
+
+/// Stop ranking new results judged against a test set; false if it was not registered
+pub async fn unregister_test_set(
+    conn: &mut impl Connection,
+    problem_id: &str,
+    fingerprint: &str,
+) -> RedisResult<bool> {
+    let removed: i64 = conn.srem(problem_test_sets_key(problem_id), fingerprint).await?;
+    Ok(removed == 1)
+}
+
+/// Test-set fingerprints registered for a problem, sorted
+pub async fn registered_test_sets(
+    conn: &mut impl Connection,
+    problem_id: &str,
+) -> RedisResult<Vec<String>> {
+    let mut fingerprints: Vec<String> = conn.smembers(problem_test_sets_key(problem_id)).await?;
+    fingerprints.sort();
+    Ok(fingerprints)
+}
+
+/// A page of a leaderboard, best first, and the number of ranked participants
+pub async fn read_leaderboard(
+    conn: &mut impl Connection,
+    board_key: &str,
+    offset: usize,
+    limit: usize,
+) -> RedisResult<(Vec<crate::types::LeaderboardEntry>, u64)> {
+    let stop = offset.saturating_add(limit).saturating_sub(1);
+    let (participants, total): (Vec<String>, u64) = redis::pipe()
+        .zrevrange(board_key, offset as isize, stop.min(isize::MAX as usize) as isize)
+        .zcard(board_key)
+        .query_async(conn)
+        .await?;
+    if participants.is_empty() || limit == 0 {
+        return Ok((Vec::new(), total));
+    }
+    let details: Vec<Option<String>> = redis::cmd("HMGET")
+        .arg(leaderboard_entries_key(board_key))
+        .arg(&participants)
+        .query_async(conn)
+        .await?;
+    Ok((leaderboard_entries(offset, participants, details), total))
+}
+
+/// Number the participants from `offset`, skipping any whose details are missing
+fn leaderboard_entries(
+    offset: usize,
+    participants: Vec<String>,
+    details: Vec<Option<String>>,
+) -> Vec<crate::types::LeaderboardEntry> {
+    participants
+        .into_iter()
+        .zip(details)
+        .enumerate()
+        .filter_map(|(idx, (participant, detail))| {
+            let mut entry: serde_json::Value = serde_json::from_str(&detail?).ok()?;
+            entry["rank"] = (offset + idx + 1).into();
+            entry["participant"] = participant.into();
+            serde_json::from_value(entry).ok()
+        })
+        .collect()
+}
+
+/// Count a submission and record the milestones it reaches (first solve, a participant's
+/// first solve, fastest solution); returns how many timeline events it added
+///
+/// Only results judged against a registered test set count (as for the leaderboards), each
+/// job once however often it runs, and per-minute counts older than the window are dropped.
+const RECORD_PROBLEM_STATS_SCRIPT: &str = r#"
+-- KEYS: stats, timeline, per-minute counts, solvers, registered test sets, counted marker
+-- ARGV: minute, accepted (1/0), participant, time ms, timeline limit,
+--       first-solve event, solved event, fastest-solution event,
+--       test-set fingerprint, per-minute window (secs)
+if redis.call('SISMEMBER', KEYS[5], ARGV[9]) == 0 then
+  return 0
+end
+-- The marker says where the job was counted, so a deletion can take it back out
+local counted = ARGV[2] == '1' and ARGV[1] .. ':accepted' or ARGV[1]
+if not redis.call('SET', KEYS[6], counted, 'NX', 'EX', ARGV[10]) then
+  return 0
+end
+redis.call('HINCRBY', KEYS[1], 'submissions', 1)
+if redis.call('HINCRBY', KEYS[3], ARGV[1], 1) == 1 then
+  -- First submission of a minute: drop the minutes that left the window
+  local cutoff = tonumber(ARGV[1]) - tonumber(ARGV[10])
+  for _, field in ipairs(redis.call('HKEYS', KEYS[3])) do
+    local minute = tonumber(string.match(field, '^%d+'))
+    if minute == nil or minute <= cutoff then
+      redis.call('HDEL', KEYS[3], field)
+    end
+  end
+end
+if ARGV[2] ~= '1' then
+  return 0
+end
+redis.call('HINCRBY', KEYS[1], 'accepted', 1)
+redis.call('HINCRBY', KEYS[3], ARGV[1] .. ':accepted', 1)
+
+local events = {}
+if redis.call('SADD', KEYS[4], ARGV[3]) == 1 then
+  if redis.call('HSETNX', KEYS[1], 'first_solve', ARGV[6]) == 1 then
+    table.insert(events, ARGV[6])
+  else
+    table.insert(events, ARGV[7])
+  end
+end
+local fastest = tonumber(redis.call('HGET', KEYS[1], 'fastest_time_ms'))
+if fastest == nil or tonumber(ARGV[4]) < fastest then
+  redis.call('HSET', KEYS[1], 'fastest_time_ms', ARGV[4], 'fastest_solution', ARGV[8])
+  table.insert(events, ARGV[8])
+end
+for _, event in ipairs(events) do
+  redis.call('RPUSH', KEYS[2], event)
+end
+redis.call('LTRIM', KEYS[2], -tonumber(ARGV[5]), -1)
+return #events
+"#;
+
+fn record_problem_stats_script() -> &'static Script {
+    static SCRIPT: OnceLock<Script> = OnceLock::new();
+    SCRIPT.get_or_init(|| Script::new(RECORD_PROBLEM_STATS_SCRIPT))
+}
+
+/// Count a persisted result in its problem's statistics and timeline, if it was judged
+/// against a test set registered for the problem and has not been counted before
+pub async fn record_problem_stats(
+    conn: &mut impl Connection,
+    tag: &crate::types::LeaderboardTag,
+    fingerprint: &str,
+    result: &crate::types::ExecutionResult,
+    at: chrono::DateTime<chrono::Utc>,
+) -> RedisResult<i64> {
+    use crate::types::{TimelineEvent, TimelineEventKind};
+
+    let event = |kind| {
+        serde_json::to_string(&TimelineEvent {
+            kind,
+            participant: tag.participant.clone(),
+            job_id: result.job_id,
+            contest_id: tag.contest_id.clone(),
+            time_ms: leaderboard_time_ms(result),
+            at,
+        })
+        .map_err(|e| payload_error("serialization error", e.to_string()))
+    };
+    let minute = at.timestamp() - at.timestamp().rem_euclid(60);
+    let problem_id = &tag.problem_id;
+    record_problem_stats_script()
+        .key(problem_stats_key(problem_id))
+        .key(problem_timeline_key(problem_id))
+        .key(problem_submissions_key(problem_id))
+        .key(problem_solvers_key(problem_id))
+        .key(problem_test_sets_key(problem_id))
+        .key(problem_counted_key(&result.job_id))
+        .arg(minute)
+        .arg(if result.accepted() { "1" } else { "0" })
+        .arg(&tag.participant)
+        .arg(leaderboard_time_ms(result))
+        .arg(PROBLEM_TIMELINE_LIMIT)
+        .arg(event(TimelineEventKind::FirstSolve)?)
+        .arg(event(TimelineEventKind::Solved)?)
+        .arg(event(TimelineEventKind::FastestSolution)?)
+        .arg(fingerprint)
+        .arg(PROBLEM_STATS_WINDOW_SECS)
+        .invoke_async(conn)
+        .await
+}
+
+/// Take a deleted job out of its problem's statistics: its counts (while the job is still
+/// remembered as counted) and every timeline event and milestone naming it
+const FORGET_PROBLEM_STATS_SCRIPT: &str = r#"
+-- KEYS: stats, timeline, per-minute counts, counted marker
+-- ARGV: job id
+local counted = redis.call('GET', KEYS[4])
+if counted then
+  redis.call('DEL', KEYS[4])
+  redis.call('HINCRBY', KEYS[1], 'submissions', -1)
+  local minute = string.match(counted, '^%d+')
+  if redis.call('HEXISTS', KEYS[3], minute) == 1 then
+    redis.call('HINCRBY', KEYS[3], minute, -1)
+  end
+  if minute ~= counted then
+    redis.call('HINCRBY', KEYS[1], 'accepted', -1)
+    if redis.call('HEXISTS', KEYS[3], counted) == 1 then
+      redis.call('HINCRBY', KEYS[3], counted, -1)
+    end
+  end
+end
+local function names_job(raw)
+  local ok, event = pcall(cjson.decode, raw)
+  return ok and type(event) == 'table' and event.job_id == ARGV[1]
+end
+local removed = 0
+for _, event in ipairs(redis.call('LRANGE', KEYS[2], 0, -1)) do
+  if names_job(event) then
+    removed = removed + redis.call('LREM', KEYS[2], 0, event)
+  end
+end
+local first = redis.call('HGET', KEYS[1], 'first_solve')
+if first and names_job(first) then
+  redis.call('HDEL', KEYS[1], 'first_solve')
+end
+local fastest = redis.call('HGET', KEYS[1], 'fastest_solution')
+if fastest and names_job(fastest) then
+  redis.call('HDEL', KEYS[1], 'fastest_solution', 'fastest_time_ms')
+end
+return removed
+"#;
+
+fn forget_problem_stats_script() -> &'static Script {
+    static SCRIPT: OnceLock<Script> = OnceLock::new();
+    SCRIPT.get_or_init(|| Script::new(FORGET_PROBLEM_STATS_SCRIPT))
+}
+
+/// Take a deleted ranked job out of its problem's statistics and timeline; returns
+/// whether it was a ranked job
+pub async fn forget_problem_stats(
+    conn: &mut impl Connection,
+    job_id: &uuid::Uuid,
+) -> RedisResult<bool> {
+    let Some(tag) = ranked_job_tag(conn, job_id).await? else {
+        return Ok(false);
+    };
+    let _: i64 = forget_problem_stats_script()
+        .key(problem_stats_key(&tag.problem_id))
+        .key(problem_timeline_key(&tag.problem_id))
+        .key(problem_submissions_key(&tag.problem_id))
+        .key(problem_counted_key(job_id))
+        .arg(job_id.to_string())
+        .invoke_async(conn)
+        .await?;
+    Ok(true)
+}
+
+/// A problem's counters, milestones and submissions per minute
+pub async fn read_problem_stats(
+    conn: &mut impl Connection,
+    problem_id: &str,
+) -> RedisResult<crate::types::ProblemStats> {
+    let (stats, timeline, per_minute, solvers): (
+        std::collections::HashMap<String, String>,
+        Vec<String>,
+        std::collections::HashMap<String, u64>,
+        u64,
+    ) = redis::pipe()
+        .hgetall(problem_stats_key(problem_id))
+        .lrange(problem_timeline_key(problem_id), 0, -1)
+        .hgetall(problem_submissions_key(problem_id))
+        .scard(problem_solvers_key(problem_id))
+        .query_async(conn)
+        .await?;
+    Ok(problem_stats(problem_id, stats, &timeline, per_minute, solvers))
+}
+
+/// Assemble the stored pieces of a problem's statistics, skipping unreadable entries
+fn problem_stats(
+    problem_id: &str,
+    stats: std::collections::HashMap<String, String>,
+    timeline: &[String],
+    per_minute: std::collections::HashMap<String, u64>,
+    solvers: u64,
+) -> crate::types::ProblemStats {
+    let counter = |field: &str| stats.get(field).and_then(|v| v.parse().ok()).unwrap_or(0);
+    let event = |field: &str| stats.get(field).and_then(|v| serde_json::from_str(v).ok());
+
+    let mut buckets: std::collections::BTreeMap<i64, crate::types::SubmissionBucket> = std::collections::BTreeMap::new();
+    for (field, count) in per_minute {
+        let (minute, accepted) = match field.strip_suffix(":accepted") {
+            Some(minute) => (minute, true),
+            None => (field.as_str(), false),
+        };
+        let Some(at) = minute.parse().ok().and_then(|secs| chrono::DateTime::from_timestamp(secs, 0)) else { continue };
+        let bucket = buckets.entry(at.timestamp()).or_insert(crate::types::SubmissionBucket { minute: at, submissions: 0, accepted: 0 });
+        if accepted {
+            bucket.accepted = count;
+        } else {
+            bucket.submissions = count;
+        }
+    }
+
+    crate::types::ProblemStats {
+        problem_id: problem_id.to_string(),
+        submissions: counter("submissions"),
+        accepted: counter("accepted"),
+        solvers,
+        first_solve: event("first_solve"),
+        fastest_solution: event("fastest_solution"),
+        timeline: timeline.iter().filter_map(|e| serde_json::from_str(e).ok()).collect(),
+        submissions_per_minute: buckets.into_values().collect(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -257,6 +2931,119 @@ mod tests {
         
         assert_eq!(dlq_name(&Language::Python), "optimus:queue:python:dlq");
         assert_eq!(dlq_name(&Language::Rust), "optimus:queue:rust:dlq");
+        assert_eq!(
+            processing_list_name(&queue_name(&Language::Python), "worker-1"),
+            "optimus:queue:python:processing:worker-1"
+        );
+
+        // Jobs with requirements have their own pending and retry queues
+        let gpu = vec!["gpu".to_string()];
+        assert_eq!(
+            requirement_queues(&Language::Python, &gpu),
+            ("optimus:queue:python:gpu".to_string(), "optimus:queue:python:gpu:retry".to_string())
+        );
+        assert_eq!(
+            worker_queues(&Language::Rust, &[]),
+            [(queue_name(&Language::Rust), retry_queue_name(&Language::Rust))]
+        );
+        let queues: Vec<String> = worker_queues(&Language::Rust, &gpu).into_iter().map(|(queue, _)| queue).collect();
+        assert_eq!(queues, ["optimus:queue:rust:gpu", "optimus:queue:rust"]);
+
+        // migrate-queues moves each suffixed queue onto the same suffix under the new prefix
+        let names: Vec<String> = QUEUE_SUFFIXES.iter().map(|suffix| format!("optimus:queue:java{}", suffix)).collect();
+        assert_eq!(names, [queue_name(&Language::Java), retry_queue_name(&Language::Java), dlq_name(&Language::Java)]);
+
+        // Config names map onto the enum's queues whatever their case
+        for language in Language::all_variants() {
+            let name = language.to_string().to_uppercase();
+            assert_eq!(Language::from_str(&name), Some(*language));
+            assert_eq!(canonical_queue_name(&format!(" {} ", name)), queue_name(language));
+        }
+    }
+
+    #[test]
+    fn test_key_prefix_namespaces_deployments() {
+        assert_eq!(parse_key_prefix(None), "optimus");
+        assert_eq!(parse_key_prefix(Some("  ")), "optimus");
+        assert_eq!(parse_key_prefix(Some("optimus:staging:")), "optimus:staging");
+        assert_eq!(parse_key_prefix(Some("cs101")), "cs101");
+        assert_eq!(intake_queue_name(), "optimus:intake");
+        assert_eq!(completions_channel(), "optimus:metrics:completions");
+    }
+
+    #[test]
+    fn test_idempotency_scan_pattern_is_literal() {
+        assert_eq!(glob_escape("client-1"), "client-1");
+        assert_eq!(glob_escape(r"a*b?[c]\d"), r"a\*b\?\[c\]\\d");
+        assert_eq!(format!("{}*", glob_escape(&idempotency_key("tenant:*"))), r"optimus:idempotency:tenant:\**");
+    }
+
+    #[test]
+    fn test_idempotency_entry_hides_payload() {
+        let data = r#"{"job_id":"j1","payload":"{\"source_code\":\"secret\"}","created_at":"2026-10-16T12:00:00Z"}"#;
+        let entry = idempotency_entry("optimus:idempotency:client:1", data, 60);
+        assert_eq!(entry.key, "client:1");
+        assert_eq!(entry.job_id.as_deref(), Some("j1"));
+        assert_eq!(entry.created_at.as_deref(), Some("2026-10-16T12:00:00Z"));
+        assert_eq!(entry.ttl_secs, 60);
+        assert!(!serde_json::to_string(&entry).unwrap().contains("secret"));
+    }
+
+    #[test]
+    fn test_leaderboard_entries_numbered_from_offset() {
+        let board = problem_leaderboard_key("two-sum");
+        assert_eq!(board, "optimus:leaderboard:problem:two-sum");
+        assert_eq!(leaderboard_entries_key(&board), "optimus:leaderboard:problem:two-sum:entries");
+
+        let participants = vec!["alice".to_string(), "gone".to_string(), "bob".to_string()];
+        let details = vec![
+            Some(r#"{"score":30,"time_ms":12,"job_id":"j1"}"#.to_string()),
+            None,
+            Some(r#"{"score":40,"time_ms":9,"problems":{"a":{"score":30,"time_ms":5,"job_id":"j2"},"b":{"score":10,"time_ms":4,"job_id":"j3"}}}"#.to_string()),
+        ];
+        let entries = leaderboard_entries(10, participants, details);
+        assert_eq!(entries.len(), 2);
+        assert_eq!((entries[0].rank, entries[0].participant.as_str(), entries[0].best.score), (11, "alice", 30));
+        assert_eq!(entries[0].best.job_id.as_deref(), Some("j1"));
+        assert_eq!((entries[1].rank, entries[1].best.time_ms), (13, 9));
+        assert_eq!(entries[1].problems["a"].score, 30);
+    }
+
+    #[test]
+    fn test_problem_stats_assembles_buckets_and_events() {
+        let first = serde_json::json!({
+            "kind": "first_solve", "participant": "alice", "job_id": uuid::Uuid::nil(),
+            "time_ms": 40, "at": "2024-05-01T10:02:30Z"
+        })
+        .to_string();
+        let stats = [("submissions", "5"), ("accepted", "2"), ("first_solve", first.as_str())]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let per_minute = [("1714557720", 3), ("1714557720:accepted", 1), ("1714557660", 2), ("garbage", 9)]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect();
+        let stats = problem_stats("two-sum", stats, &[first.clone(), "not json".to_string()], per_minute, 1);
+
+        assert_eq!((stats.submissions, stats.accepted, stats.solvers), (5, 2, 1));
+        assert_eq!(stats.first_solve.as_ref().map(|e| e.participant.as_str()), Some("alice"));
+        assert!(stats.fastest_solution.is_none());
+        assert_eq!(stats.timeline.len(), 1);
+        let buckets: Vec<(i64, u64, u64)> =
+            stats.submissions_per_minute.iter().map(|b| (b.minute.timestamp(), b.submissions, b.accepted)).collect();
+        assert_eq!(buckets, vec![(1714557660, 2, 0), (1714557720, 3, 1)]);
+    }
+
+    #[test]
+    fn test_problem_stats_keys() {
+        let job_id = Uuid::nil();
+        assert_eq!(problem_test_sets_key("two-sum"), "optimus:leaderboard:problem:two-sum:test_sets");
+        assert_eq!(problem_counted_key(&job_id), format!("optimus:stats:counted:{}", job_id));
+        // Unregistered test sets and re-runs are turned away before anything is counted
+        let counted = RECORD_PROBLEM_STATS_SCRIPT.find("'submissions'").unwrap();
+        assert!(RECORD_PROBLEM_STATS_SCRIPT.find("SISMEMBER").unwrap() < counted);
+        assert!(RECORD_PROBLEM_STATS_SCRIPT.find("'NX'").unwrap() < counted);
     }
 
     #[test]
@@ -268,6 +3055,37 @@ mod tests {
         assert!(key1.starts_with("optimus:result:"));
     }
 
+    #[test]
+    fn test_quota_keys() {
+        assert_eq!(cpu_quota_key("3f2a", "20261016"), "optimus:quota:3f2a:cpu:20261016");
+        assert_eq!(rate_limit_key("3f2a"), "optimus:quota:3f2a:rate");
+        // CHARGE_CPU_SCRIPT builds the day's key itself
+        assert!(CHARGE_CPU_SCRIPT.contains("ARGV[3] .. ':' .. owner .. ':cpu:' .. ARGV[2]"));
+        use chrono::TimeZone;
+        assert_eq!(quota_day(chrono::Utc.with_ymd_and_hms(2026, 10, 16, 23, 59, 30).unwrap()), "20261016");
+    }
+
+    #[test]
+    fn test_charge_never_below_measured_wall_time() {
+        let test = |execution_time_ms: u64, overhead_ms: Option<u64>, cpu_time_ms: Option<u64>| serde_json::json!({
+            "test_id": 1, "status": "passed", "stdout": "", "stderr": "",
+            "execution_time_ms": execution_time_ms, "overhead_ms": overhead_ms, "cpu_time_ms": cpu_time_ms,
+        });
+        let result: crate::types::ExecutionResult = serde_json::from_value(serde_json::json!({
+            "job_id": Uuid::new_v4(), "overall_status": "completed", "score": 0, "max_score": 0,
+            "results": [
+                // Multi-threaded: more CPU than wall time
+                test(100, Some(20), Some(300)),
+                // A report claiming next to no time is held to the engine's clock
+                test(1, Some(499), Some(1)),
+                // No report
+                test(200, None, None),
+            ],
+        }))
+        .unwrap();
+        assert_eq!(charged_cpu_seconds(&result), 1.0);
+    }
+
     #[test]
     fn test_status_key_format() {
         let id = Uuid::new_v4();
@@ -275,4 +3093,244 @@ mod tests {
         assert!(key.starts_with("optimus:status:"));
         assert!(key.contains(&id.to_string()));
     }
+
+    #[test]
+    fn test_job_location_record() {
+        let mut job = sample_job();
+        job.metadata.attempts = 2;
+        assert_eq!(job_location_key(&job.id), format!("optimus:job:{}:location", job.id));
+
+        let payload = location_payload(&job, JobPlace::Retrying, Some("optimus:queue:python:retry")).unwrap();
+        let location: JobLocation = serde_json::from_str(&payload).unwrap();
+        assert_eq!(location.place, JobPlace::Retrying);
+        assert_eq!(location.queue.as_deref(), Some("optimus:queue:python:retry"));
+        assert_eq!(location.metadata.attempts, 2);
+        // Never the job's payload
+        assert!(!payload.contains("secret-input"));
+
+        assert_eq!(location_ttl(JobPlace::Running), Some(LOCATION_TTL_SECS));
+        assert_eq!(location_ttl(JobPlace::DeadLettered), None);
+    }
+
+    fn sample_job() -> JobRequest {
+        JobRequest {
+            id: Uuid::new_v4(),
+            language: Language::Python,
+            source_code: "print('hello')".to_string(),
+            test_cases: vec![crate::types::TestCase {
+                id: 1,
+                input: "secret-input".to_string(),
+                expected_output: "hello".to_string(),
+                weight: 10,
+                ..Default::default()
+            }],
+            timeout_ms: 5000,
+            ..Default::default()
+        }
+    }
+
+    fn test_cipher() -> PayloadCipher {
+        PayloadCipher::from_key_list("k1:AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=").unwrap()
+    }
+
+    #[test]
+    fn test_job_encoding_plaintext_without_cipher() {
+        let job = sample_job();
+        let payload = encode_job_with(&job, None).unwrap();
+        assert_eq!(payload, serde_json::to_string(&job).unwrap());
+        assert_eq!(decode_job_with(&payload, None).unwrap().id, job.id);
+    }
+
+    #[test]
+    fn test_job_encoding_seals_sensitive_fields() {
+        let cipher = test_cipher();
+        let job = sample_job();
+        let payload = encode_job_with(&job, Some(&cipher)).unwrap();
+
+        assert!(!payload.contains("print('hello')"));
+        assert!(!payload.contains("secret-input"));
+        // Routing fields stay readable
+        assert!(payload.contains(&job.id.to_string()));
+        assert!(payload.contains(SEALED_FIELD));
+        // The dequeue script claims jobs by their top-level string id
+        let value: serde_json::Value = serde_json::from_str(&payload).unwrap();
+        assert_eq!(value["id"].as_str(), Some(job.id.to_string().as_str()));
+
+        let decoded = decode_job_with(&payload, Some(&cipher)).unwrap();
+        assert_eq!(decoded.source_code, job.source_code);
+        assert_eq!(decoded.test_cases[0].input, "secret-input");
+    }
+
+    #[test]
+    fn test_sealed_job_requires_key() {
+        let payload = encode_job_with(&sample_job(), Some(&test_cipher())).unwrap();
+        assert!(decode_job_with(&payload, None).is_err());
+    }
+
+    #[test]
+    fn test_plaintext_job_readable_after_enabling_encryption() {
+        let job = sample_job();
+        let payload = encode_job_with(&job, None).unwrap();
+        let decoded = decode_job_with(&payload, Some(&test_cipher())).unwrap();
+        assert_eq!(decoded.source_code, job.source_code);
+    }
+
+    /// Connection to the Redis the ignored tests run against (REDIS_URL, else localhost).
+    /// They use the deployment's real keys, so point them at a scratch instance.
+    async fn test_conn() -> redis::aio::ConnectionManager {
+        let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+        redis::Client::open(url)
+            .expect("Failed to create Redis client")
+            .get_connection_manager()
+            .await
+            .expect("Failed to connect to Redis")
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires Redis
+    async fn test_reclaim_dead_letters_unreadable_payloads() {
+        let mut conn = test_conn().await;
+        let language = Language::Python;
+        let (first, second) = (JobRequest::default(), JobRequest::default());
+        let corrupt_id = Uuid::new_v4().to_string();
+        let corrupt = format!("{{\"id\": \"{}\", \"truncated", corrupt_id);
+        // Expired leases, the corrupt entry between the valid ones
+        let expired = chrono::Utc::now().timestamp() - 60;
+        let entries = [
+            (first.id.to_string(), encode_job(&first).unwrap()),
+            (corrupt_id.clone(), corrupt.clone()),
+            (second.id.to_string(), encode_job(&second).unwrap()),
+        ];
+        for (i, (id, payload)) in entries.iter().enumerate() {
+            let _: () = conn.hset(inflight_jobs_name(&language), id, payload).await.unwrap();
+            let _: () = conn.zadd(inflight_queue_name(&language), id, expired + i as i64).await.unwrap();
+        }
+
+        let reclaimed = reclaim_expired_inflight(&mut conn, &language).await.unwrap();
+        let ids: Vec<Uuid> = reclaimed.jobs.iter().map(|job| job.id).collect();
+        assert!(ids.contains(&first.id) && ids.contains(&second.id), "{:?}", ids);
+        assert!(reclaimed.unreadable.iter().any(|(id, _)| *id == corrupt_id), "{:?}", reclaimed.unreadable);
+        let dead_lettered: i64 = conn.lrem(dlq_name(&language), 1, &corrupt).await.unwrap();
+        assert_eq!(dead_lettered, 1, "the corrupt payload is dead-lettered as stored");
+        for (id, _) in &entries {
+            let left: Option<String> = conn.hget(inflight_jobs_name(&language), id).await.unwrap();
+            assert_eq!(left, None, "{} left in flight", id);
+        }
+    }
+
+    fn recent(job: &JobRequest) -> crate::types::RecentJob {
+        crate::types::RecentJob { job_id: job.id, language: job.language, test_cases: 0, submitted_at: chrono::Utc::now() }
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires Redis
+    async fn test_enqueue_with_a_taken_idempotency_key_writes_nothing() {
+        let mut conn = test_conn().await;
+        let key = format!("test-{}", Uuid::new_v4());
+        let (first, second) = (JobRequest::default(), JobRequest::default());
+        let (first_recent, second_recent) = (recent(&first), recent(&second));
+        let submit = |job, data: &str, recent| Submission {
+            job,
+            idempotency: Some((key.as_str(), data.to_string())),
+            idempotency_ttl_secs: 60,
+            retention_tenant: None,
+            recent,
+            via_intake: false,
+        };
+
+        let queued = enqueue_submission(&mut conn, &submit(&first, "first", &first_recent)).await.unwrap();
+        assert_eq!(queued, Enqueued::Queued);
+        let ttl: i64 = conn.ttl(status_key(&first.id)).await.unwrap();
+        assert!(ttl > 0 && ttl as u64 <= RESULT_TTL_SECS, "status ttl {}", ttl);
+
+        let replay = enqueue_submission(&mut conn, &submit(&second, "second", &second_recent)).await.unwrap();
+        assert_eq!(replay, Enqueued::IdempotencyKeyTaken("first".to_string()));
+        for written in [status_key(&second.id), job_key(&second.id), job_location_key(&second.id)] {
+            let exists: bool = conn.exists(&written).await.unwrap();
+            assert!(!exists, "{} written for a replay", written);
+        }
+        let queued: Vec<String> = conn.lrange(queue_name(&Language::Python), 0, -1).await.unwrap();
+        assert!(!queued.iter().any(|payload| payload.contains(&second.id.to_string())));
+
+        let first_id = first.id.to_string();
+        for payload in queued.iter().filter(|payload| payload.contains(&first_id)) {
+            let _: i64 = conn.lrem(queue_name(&Language::Python), 1, payload).await.unwrap();
+        }
+        let _: i64 = conn
+            .del(&[idempotency_key(&key), status_key(&first.id), job_key(&first.id), job_location_key(&first.id)])
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires Redis
+    async fn test_dequeue_claims_the_job_as_in_flight() {
+        let mut conn = test_conn().await;
+        let language = Language::Python;
+        let list = namespaced(&format!("test:{}", Uuid::new_v4()));
+        let job = JobRequest::default();
+        let stored = encode_job(&job).unwrap();
+        let _: () = conn.rpush(&list, &stored).await.unwrap();
+
+        let before = chrono::Utc::now().timestamp();
+        let claimed = claim_next(&mut conn, &language, std::slice::from_ref(&list), 30).await.unwrap();
+        assert_eq!(claimed.map(|claimed| claimed.id), Some(job.id));
+        let left: i64 = conn.llen(&list).await.unwrap();
+        assert_eq!(left, 0);
+
+        let id = job.id.to_string();
+        let payload: Option<String> = conn.hget(inflight_jobs_name(&language), &id).await.unwrap();
+        assert_eq!(payload, Some(stored));
+        let lease: Option<i64> = conn.zscore(inflight_queue_name(&language), &id).await.unwrap();
+        assert!(lease.is_some_and(|lease| lease >= before + 30), "lease {:?}", lease);
+        // Nothing left to claim
+        assert!(claim_next(&mut conn, &language, std::slice::from_ref(&list), 30).await.unwrap().is_none());
+
+        let _: () = conn.hdel(inflight_jobs_name(&language), &id).await.unwrap();
+        let _: () = conn.zrem(inflight_queue_name(&language), &id).await.unwrap();
+        let _: () = conn.del(job_location_key(&job.id)).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires Redis
+    async fn test_refunded_rate_token_can_be_taken_again() {
+        let mut conn = test_conn().await;
+        let api_key_id = format!("test-{}", Uuid::new_v4());
+
+        assert_eq!(take_rate_token(&mut conn, &api_key_id, 2).await.unwrap(), None);
+        assert_eq!(take_rate_token(&mut conn, &api_key_id, 2).await.unwrap(), None);
+        assert!(take_rate_token(&mut conn, &api_key_id, 2).await.unwrap().is_some());
+        refund_rate_token(&mut conn, &api_key_id, 2).await.unwrap();
+        assert_eq!(take_rate_token(&mut conn, &api_key_id, 2).await.unwrap(), None);
+
+        // Refunds never fill the bucket past its capacity
+        for _ in 0..3 {
+            refund_rate_token(&mut conn, &api_key_id, 2).await.unwrap();
+        }
+        assert_eq!(take_rate_token(&mut conn, &api_key_id, 2).await.unwrap(), None);
+        assert_eq!(take_rate_token(&mut conn, &api_key_id, 2).await.unwrap(), None);
+        assert!(take_rate_token(&mut conn, &api_key_id, 2).await.unwrap().is_some());
+
+        let _: () = conn.del(rate_limit_key(&api_key_id)).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires Redis
+    async fn test_submission_cooldown_claim_and_release() {
+        let mut conn = test_conn().await;
+        let problem = format!("test-{}", Uuid::new_v4());
+
+        assert_eq!(claim_submission_cooldown(&mut conn, &problem, "key:course", 60).await.unwrap(), None);
+        let wait = claim_submission_cooldown(&mut conn, &problem, "key:course", 60).await.unwrap();
+        assert!(wait.is_some_and(|wait| (1..=60).contains(&wait)), "wait {:?}", wait);
+        // Other submitters have their own cool-down
+        assert_eq!(claim_submission_cooldown(&mut conn, &problem, "participant:alice", 60).await.unwrap(), None);
+
+        release_submission_cooldown(&mut conn, &problem, "key:course").await.unwrap();
+        assert_eq!(claim_submission_cooldown(&mut conn, &problem, "key:course", 60).await.unwrap(), None);
+
+        for submitter in ["key:course", "participant:alice"] {
+            release_submission_cooldown(&mut conn, &problem, submitter).await.unwrap();
+        }
+    }
 }
@@ -0,0 +1,209 @@
+// Redis retry policy - ride out brief Redis outages instead of failing the operation
+//
+// Transient errors (connection refused/dropped, timeouts, a replica still loading, a
+// failover in progress) are retried with exponential backoff and full jitter; anything
+// else (wrong type, bad payload, authentication) is fatal and returned at once.
+// ConnectionManager reconnects on the next command after a dropped connection, so a
+// retry after a backoff is also how the connection comes back.
+//
+// Tuning (API and workers):
+//   REDIS_RETRY_MAX_ATTEMPTS (default 5), REDIS_RETRY_BASE_MS (100), REDIS_RETRY_MAX_MS (5000)
+
+use redis::{ErrorKind, RedisError, RedisResult};
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tracing::warn;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// Attempts including the first (1 disables retries)
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Load from REDIS_RETRY_* (defaults for unset or invalid values)
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let number = |name: &str| std::env::var(name).ok().and_then(|v| v.trim().parse::<u64>().ok());
+        Self {
+            max_attempts: number("REDIS_RETRY_MAX_ATTEMPTS").map_or(defaults.max_attempts, |n| n.clamp(1, 100) as u32),
+            base_delay: number("REDIS_RETRY_BASE_MS").map_or(defaults.base_delay, Duration::from_millis),
+            max_delay: number("REDIS_RETRY_MAX_MS").map_or(defaults.max_delay, Duration::from_millis),
+        }
+    }
+
+    /// The process-wide policy, read from the environment on first use
+    pub fn global() -> &'static Self {
+        static POLICY: OnceLock<RetryPolicy> = OnceLock::new();
+        POLICY.get_or_init(Self::from_env)
+    }
+
+    /// Upper bound of the delay before retry number `retry` (0-based): base * 2^retry, capped
+    pub fn ceiling(&self, retry: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(retry.min(16)))
+            .min(self.max_delay)
+    }
+
+    /// Delay before retry number `retry`, uniformly jittered below the ceiling
+    pub fn delay(&self, retry: u32) -> Duration {
+        let ceiling = self.ceiling(retry).as_millis() as u64;
+        if ceiling == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_millis(random() % (ceiling + 1))
+    }
+}
+
+fn random() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_nanos());
+    hasher.finish()
+}
+
+/// Whether retrying the command may succeed
+pub fn is_transient(error: &RedisError) -> bool {
+    error.is_io_error()
+        || error.is_connection_dropped()
+        || error.is_connection_refusal()
+        || error.is_timeout()
+        || matches!(
+            error.kind(),
+            ErrorKind::BusyLoadingError | ErrorKind::TryAgain | ErrorKind::ClusterDown | ErrorKind::MasterDown | ErrorKind::ReadOnly
+        )
+}
+
+/// Run a Redis operation, retrying transient errors under the global policy
+///
+/// `operation` names the call in logs. The closure runs once per attempt, so it must
+/// not consume its inputs (clone the ConnectionManager inside it - clones share one
+/// connection).
+pub async fn with_retry<T, F, Fut>(operation: &str, mut attempt: F) -> RedisResult<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = RedisResult<T>>,
+{
+    let policy = RetryPolicy::global();
+    let mut retry = 0;
+    loop {
+        match attempt().await {
+            Err(e) if is_transient(&e) && retry + 1 < policy.max_attempts => {
+                let delay = policy.delay(retry);
+                warn!(operation, attempt = retry + 1, delay_ms = delay.as_millis() as u64, error = %e, "Transient Redis error, retrying");
+                tokio::time::sleep(delay).await;
+                retry += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Open a ConnectionManager, retrying while Redis is still starting or unreachable
+pub async fn connect(client: &redis::Client) -> RedisResult<redis::aio::ConnectionManager> {
+    with_retry("connect", || redis::aio::ConnectionManager::new(client.clone())).await
+}
+
+/// Backoff for long-running loops (e.g. a worker's dequeue loop) while Redis is down
+///
+/// Unlike `with_retry` it never gives up; it grows the pause between iterations and
+/// tells the caller when to log, so an outage logs once when it starts, then at growing
+/// intervals, and once when Redis is back.
+#[derive(Debug, Default)]
+pub struct Backoff {
+    failures: u32,
+}
+
+impl Backoff {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a failure; returns the pause before the next try and whether to log it
+    /// (the 1st, 2nd, 4th, 8th... consecutive failure)
+    pub fn failure(&mut self) -> (Duration, bool) {
+        let policy = RetryPolicy::global();
+        self.failures = self.failures.saturating_add(1);
+        let delay = policy.ceiling(self.failures - 1).max(policy.base_delay);
+        (delay, self.failures.is_power_of_two())
+    }
+
+    /// Record a success; returns how many failures preceded it (0 if there was no outage)
+    pub fn success(&mut self) -> u32 {
+        std::mem::take(&mut self.failures)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn io_error() -> RedisError {
+        RedisError::from(std::io::Error::new(std::io::ErrorKind::ConnectionReset, "reset"))
+    }
+
+    #[test]
+    fn test_classifies_errors() {
+        assert!(is_transient(&io_error()));
+        assert!(is_transient(&RedisError::from((ErrorKind::BusyLoadingError, "loading"))));
+        assert!(!is_transient(&RedisError::from((ErrorKind::TypeError, "wrong type"))));
+        assert!(!is_transient(&RedisError::from((ErrorKind::AuthenticationFailed, "denied"))));
+    }
+
+    #[test]
+    fn test_delay_grows_and_is_capped() {
+        let policy = RetryPolicy { max_attempts: 5, base_delay: Duration::from_millis(100), max_delay: Duration::from_secs(1) };
+        assert_eq!(policy.ceiling(0), Duration::from_millis(100));
+        assert_eq!(policy.ceiling(2), Duration::from_millis(400));
+        assert_eq!(policy.ceiling(10), Duration::from_secs(1));
+        assert_eq!(policy.ceiling(u32::MAX), Duration::from_secs(1));
+        assert!((0..50).all(|_| policy.delay(3) <= Duration::from_millis(800)));
+    }
+
+    #[tokio::test]
+    async fn test_with_retry_retries_transient_errors_only() {
+        let calls = AtomicU32::new(0);
+        let result = with_retry("test", || async {
+            match calls.fetch_add(1, Ordering::SeqCst) {
+                0 | 1 => Err(io_error()),
+                _ => Ok(7),
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), 7);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        let calls = AtomicU32::new(0);
+        let result: RedisResult<()> = with_retry("test", || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(RedisError::from((ErrorKind::TypeError, "wrong type")))
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_backoff_logs_sparsely() {
+        let mut backoff = Backoff::new();
+        let logged: Vec<bool> = (0..8).map(|_| backoff.failure().1).collect();
+        assert_eq!(logged, vec![true, true, false, true, false, false, false, true]);
+        assert_eq!(backoff.success(), 8);
+        assert_eq!(backoff.success(), 0);
+    }
+}
@@ -0,0 +1,286 @@
+// Result signing - HMAC-SHA256 over stored ExecutionResults
+// Workers sign results before they reach Redis, the API verifies before returning them,
+// so a compromised Redis or rogue writer cannot forge verdicts.
+//
+// Key rotation: RESULT_SIGNING_KEYS is an ordered list of `key_id:secret` pairs.
+// The first key signs new results; every listed key is accepted for verification.
+// Rotate by prepending a new key, then drop the old one once its results expire (24h).
+
+use crate::types::{ExecutionResult, ResultSignature};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::fmt;
+use std::sync::OnceLock;
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub const SIGNING_ALGORITHM: &str = "hmac-sha256";
+
+/// Minimum secret length - shorter secrets are rejected at startup
+const MIN_SECRET_BYTES: usize = 16;
+
+static GLOBAL_SIGNER: OnceLock<Option<ResultSigner>> = OnceLock::new();
+
+#[derive(Clone)]
+struct SigningKey {
+    id: String,
+    secret: Vec<u8>,
+}
+
+impl fmt::Debug for SigningKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print secrets
+        f.debug_struct("SigningKey").field("id", &self.id).finish_non_exhaustive()
+    }
+}
+
+/// Why a stored result failed verification
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureError {
+    /// Result carries no signature and unsigned results are not allowed
+    Missing,
+    /// Signed with a key this process does not know (rotated out or forged)
+    UnknownKey(String),
+    /// Signature does not match the result contents
+    Invalid,
+}
+
+impl fmt::Display for SignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignatureError::Missing => write!(f, "result is not signed"),
+            SignatureError::UnknownKey(id) => write!(f, "result signed with unknown key '{}'", id),
+            SignatureError::Invalid => write!(f, "result signature does not match contents"),
+        }
+    }
+}
+
+impl std::error::Error for SignatureError {}
+
+/// Signs and verifies ExecutionResults with a rotating set of HMAC keys
+#[derive(Debug, Clone)]
+pub struct ResultSigner {
+    /// keys[0] is the active signing key
+    keys: Vec<SigningKey>,
+    /// Accept results without a signature (migration window after enabling signing)
+    allow_unsigned: bool,
+}
+
+impl ResultSigner {
+    /// Parse a key list of the form `id1:secret1,id2:secret2`
+    pub fn from_key_list(spec: &str, allow_unsigned: bool) -> Result<Self, String> {
+        let mut keys: Vec<SigningKey> = Vec::new();
+
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (id, secret) = entry
+                .split_once(':')
+                .ok_or_else(|| "Signing key entries must be 'key_id:secret'".to_string())?;
+            let id = id.trim();
+            if id.is_empty() {
+                return Err("Signing key id cannot be empty".to_string());
+            }
+            if secret.len() < MIN_SECRET_BYTES {
+                return Err(format!(
+                    "Signing key '{}' is too short (minimum {} bytes)",
+                    id, MIN_SECRET_BYTES
+                ));
+            }
+            if keys.iter().any(|k| k.id == id) {
+                return Err(format!("Duplicate signing key id '{}'", id));
+            }
+            keys.push(SigningKey {
+                id: id.to_string(),
+                secret: secret.as_bytes().to_vec(),
+            });
+        }
+
+        if keys.is_empty() {
+            return Err("No signing keys configured".to_string());
+        }
+
+        Ok(Self { keys, allow_unsigned })
+    }
+
+    /// Load from RESULT_SIGNING_KEYS / RESULT_SIGNING_ALLOW_UNSIGNED
+    /// Returns Ok(None) when signing is not configured
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let spec = match crate::secrets::var("RESULT_SIGNING_KEYS")? {
+            Some(spec) if !spec.trim().is_empty() => spec,
+            _ => return Ok(None),
+        };
+        let allow_unsigned = std::env::var("RESULT_SIGNING_ALLOW_UNSIGNED")
+            .map(|v| v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+        Self::from_key_list(&spec, allow_unsigned).map(Some)
+    }
+
+    /// Id of the key used for new signatures
+    pub fn active_key_id(&self) -> &str {
+        &self.keys[0].id
+    }
+
+    /// Sign a result in place with the active key (replaces any existing signature)
+    pub fn sign(&self, result: &mut ExecutionResult) {
+        let key = &self.keys[0];
+        let mac = compute_mac(&key.secret, result);
+        result.signature = Some(ResultSignature {
+            algorithm: SIGNING_ALGORITHM.to_string(),
+            key_id: key.id.clone(),
+            value: hex::encode(mac),
+        });
+    }
+
+    /// Verify a result's signature against the configured keys
+    pub fn verify(&self, result: &ExecutionResult) -> Result<(), SignatureError> {
+        let signature = match &result.signature {
+            Some(sig) => sig,
+            None if self.allow_unsigned => return Ok(()),
+            None => return Err(SignatureError::Missing),
+        };
+
+        if signature.algorithm != SIGNING_ALGORITHM {
+            return Err(SignatureError::Invalid);
+        }
+
+        let key = self
+            .keys
+            .iter()
+            .find(|k| k.id == signature.key_id)
+            .ok_or_else(|| SignatureError::UnknownKey(signature.key_id.clone()))?;
+
+        let expected = hex::decode(&signature.value).map_err(|_| SignatureError::Invalid)?;
+        let mut mac = HmacSha256::new_from_slice(&key.secret)
+            .expect("HMAC accepts keys of any length");
+        mac.update(&signing_payload(result));
+        // verify_slice is constant-time
+        mac.verify_slice(&expected).map_err(|_| SignatureError::Invalid)
+    }
+}
+
+/// Canonical bytes covered by the signature: the result serialized without its signature
+fn signing_payload(result: &ExecutionResult) -> Vec<u8> {
+    let mut unsigned = result.clone();
+    unsigned.signature = None;
+    serde_json::to_vec(&unsigned).expect("ExecutionResult is always serializable")
+}
+
+fn compute_mac(secret: &[u8], result: &ExecutionResult) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(secret)
+        .expect("HMAC accepts keys of any length");
+    mac.update(&signing_payload(result));
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Load the process-wide signer from the environment
+/// Call once at startup; a malformed key list is a fatal configuration error
+pub fn init_from_env() -> Result<Option<&'static ResultSigner>, String> {
+    let signer = ResultSigner::from_env()?;
+    Ok(GLOBAL_SIGNER.get_or_init(|| signer).as_ref())
+}
+
+/// The process-wide signer, if signing is enabled
+pub fn global_signer() -> Option<&'static ResultSigner> {
+    GLOBAL_SIGNER.get().and_then(|s| s.as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{JobStatus, TestResult, TestStatus};
+    use uuid::Uuid;
+
+    fn sample_result() -> ExecutionResult {
+        ExecutionResult {
+            job_id: Uuid::new_v4(),
+            overall_status: JobStatus::Completed,
+            score: 10,
+            max_score: 20,
+            results: vec![TestResult {
+                test_id: 1,
+                status: TestStatus::Passed,
+                stdout: "42".to_string(),
+                stderr: String::new(),
+                execution_time_ms: 12,
+                stdout_base64: None,
+                stderr_base64: None,
+                overhead_ms: None,
+                cpu_time_ms: None,
+                wall_time_ms: None,
+                memory_kb: None,
+                failure: None,
+                checker_output: None,
+                sanitizer_report: None,
+                diff: None,
+            }],
+            judge_env_version: None,
+            worker_id: None,
+            signature: None,
+            points: None,
+            timings: None,
+            misuse: Vec::new(),
+            effective_timeout_ms: None,
+            subtasks: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_sign_and_verify_roundtrip() {
+        let signer = ResultSigner::from_key_list("k1:0123456789abcdef0123", false).unwrap();
+        let mut result = sample_result();
+        signer.sign(&mut result);
+
+        let sig = result.signature.as_ref().unwrap();
+        assert_eq!(sig.key_id, "k1");
+        assert_eq!(sig.algorithm, SIGNING_ALGORITHM);
+        assert_eq!(signer.verify(&result), Ok(()));
+
+        // Survives a JSON roundtrip (what Redis storage does)
+        let json = serde_json::to_string(&result).unwrap();
+        let restored: ExecutionResult = serde_json::from_str(&json).unwrap();
+        assert_eq!(signer.verify(&restored), Ok(()));
+    }
+
+    #[test]
+    fn test_tampered_result_rejected() {
+        let signer = ResultSigner::from_key_list("k1:0123456789abcdef0123", false).unwrap();
+        let mut result = sample_result();
+        signer.sign(&mut result);
+
+        result.score = 20;
+        assert_eq!(signer.verify(&result), Err(SignatureError::Invalid));
+    }
+
+    #[test]
+    fn test_unsigned_result_policy() {
+        let strict = ResultSigner::from_key_list("k1:0123456789abcdef0123", false).unwrap();
+        assert_eq!(strict.verify(&sample_result()), Err(SignatureError::Missing));
+
+        let lenient = ResultSigner::from_key_list("k1:0123456789abcdef0123", true).unwrap();
+        assert_eq!(lenient.verify(&sample_result()), Ok(()));
+    }
+
+    #[test]
+    fn test_key_rotation() {
+        let old = ResultSigner::from_key_list("k1:0123456789abcdef0123", false).unwrap();
+        let mut result = sample_result();
+        old.sign(&mut result);
+
+        // New key first, old key still accepted for verification
+        let rotated = ResultSigner::from_key_list("k2:fedcba9876543210fedc,k1:0123456789abcdef0123", false).unwrap();
+        assert_eq!(rotated.active_key_id(), "k2");
+        assert_eq!(rotated.verify(&result), Ok(()));
+
+        // Once k1 is dropped, its signatures are rejected
+        let retired = ResultSigner::from_key_list("k2:fedcba9876543210fedc", false).unwrap();
+        assert_eq!(retired.verify(&result), Err(SignatureError::UnknownKey("k1".to_string())));
+    }
+
+    #[test]
+    fn test_invalid_key_lists() {
+        assert!(ResultSigner::from_key_list("", false).is_err());
+        assert!(ResultSigner::from_key_list("no-colon-here", false).is_err());
+        assert!(ResultSigner::from_key_list("k1:short", false).is_err());
+        assert!(ResultSigner::from_key_list(":0123456789abcdef0123", false).is_err());
+        assert!(ResultSigner::from_key_list("k1:0123456789abcdef0123,k1:0123456789abcdef0123", false).is_err());
+    }
+}
@@ -10,6 +10,13 @@ pub enum Language {
     Python,
     Java,
     Rust,
+    Kotlin,
+    Scala,
+    C,
+    Php,
+    Ruby,
+    /// x86-64 assembly (NASM syntax), linked without libc
+    Asm,
 }
 
 /// Job Metadata for Retry and Failure Handling
@@ -20,40 +27,142 @@ pub struct JobMetadata {
     pub max_attempts: u8,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_failure_reason: Option<String>,
+    /// Set on admin-triggered re-runs of an earlier job (dual-run verification)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verification: Option<VerificationTarget>,
+    /// When the job (or its latest retry) was queued; absent on jobs queued before it was recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enqueued_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Capability labels a worker needs to run the job (sorted; see capabilities.rs),
+    /// which also pick the queue it waits in
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub requirements: Vec<String>,
+    /// Dispatch priority, 0-9 (higher first); only the dispatcher orders by it
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub priority: u8,
+    /// Tenant that submitted the job (its API key's name); the dispatcher shares turns between tenants
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+    /// When the job was pushed to the dead letter queue (DLQ retention ages entries by it)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dead_lettered_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Name of the API key that submitted the job (None = anonymous); that key may delete it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
 }
 
+fn is_zero(value: &u8) -> bool {
+    *value == 0
+}
+
+/// Highest job priority
+pub const MAX_PRIORITY: u8 = 9;
+
 impl Default for JobMetadata {
     fn default() -> Self {
         Self {
             attempts: 0,
             max_attempts: 3,
             last_failure_reason: None,
+            verification: None,
+            enqueued_at: None,
+            requirements: Vec::new(),
+            priority: 0,
+            tenant: None,
+            dead_lettered_at: None,
+            owner: None,
         }
     }
 }
 
+impl JobMetadata {
+    /// How long the job has waited since it was queued (None when that time is unknown)
+    pub fn queue_wait_ms(&self, now: chrono::DateTime<chrono::Utc>) -> Option<u64> {
+        self.enqueued_at.map(|at| (now - at).num_milliseconds().max(0) as u64)
+    }
+}
+
+/// What a verification re-run is checking and where it must run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VerificationTarget {
+    pub original_job_id: Uuid,
+    /// Image to re-run on (None = the language's configured image)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    /// Worker that produced the original result; avoided while other workers are live
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avoid_worker: Option<String>,
+}
+
 /// Job Cancellation Control
 /// Tracks cancellation state for cooperative shutdown
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct JobControl {
     pub cancelled: bool,
 }
 
-impl Default for JobControl {
-    fn default() -> Self {
-        Self { cancelled: false }
-    }
-}
-
 /// Test Case Definition (Immutable Input)
 /// Test cases are immutable - workers must not mutate them
 /// Ordering matters - execution is sequential
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TestCase {
     pub id: u32,
     pub input: String,
     pub expected_output: String,
     pub weight: u32, // for scoring
+    /// How stdout is compared against expected_output (None = job default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comparison: Option<ComparisonMode>,
+    /// Subtask the test belongs to (None = scored on its own); tests sharing a group
+    /// are scored together per the job's `subtask_scoring` or `group_scoring`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_id: Option<u32>,
+}
+
+/// Group Scoring
+/// How a subtask (the tests sharing a `group_id`) earns the sum of its tests' weights
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupScoring {
+    /// All of it if every test in the group passes, nothing otherwise (IOI-style subtasks);
+    /// "min" is read as this, since tests only pass or fail
+    #[default]
+    #[serde(alias = "min")]
+    AllOrNothing,
+    /// The weights of the tests that passed, as for ungrouped tests
+    Sum,
+}
+
+impl GroupScoring {
+    /// Score of a group worth `max_score` whose tests passed as listed
+    pub fn score(self, max_score: u32, weights: &[(u32, bool)]) -> u32 {
+        match self {
+            GroupScoring::AllOrNothing if weights.iter().all(|(_, passed)| *passed) => max_score,
+            GroupScoring::AllOrNothing => 0,
+            GroupScoring::Sum => weights
+                .iter()
+                .filter(|(_, passed)| *passed)
+                .fold(0u32, |sum, (weight, _)| sum.saturating_add(*weight)),
+        }
+    }
+}
+
+/// Largest accepted test case weight; 100 tests at this weight still fit a u32 score
+pub const MAX_TEST_WEIGHT: u32 = 1_000_000;
+
+/// Output Comparison Mode
+/// Selects how the evaluator matches program output against expected output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ComparisonMode {
+    /// Lines must match in order (after normalization)
+    #[default]
+    Ordered,
+    /// Lines may appear in any order - compared as a multiset
+    /// Used for graph/query problems where result ordering is arbitrary
+    Unordered,
+    /// Exact match with no trimming - trailing whitespace and newlines matter
+    Strict,
 }
 
 impl Language {
@@ -61,18 +170,44 @@ impl Language {
     /// This is the single source of truth for available languages
     /// Add new languages here and they'll automatically propagate everywhere
     pub fn all_variants() -> &'static [Language] {
-        &[Language::Python, Language::Java, Language::Rust]
+        &[Language::Python, Language::Java, Language::Rust, Language::Kotlin, Language::Scala, Language::C, Language::Php, Language::Ruby, Language::Asm]
     }
     
     /// Parse a language from string (case-insensitive)
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Language> {
         match s.to_lowercase().as_str() {
             "python" => Some(Language::Python),
             "java" => Some(Language::Java),
             "rust" => Some(Language::Rust),
+            "kotlin" => Some(Language::Kotlin),
+            "scala" => Some(Language::Scala),
+            "c" => Some(Language::C),
+            "php" => Some(Language::Php),
+            "ruby" => Some(Language::Ruby),
+            "asm" => Some(Language::Asm),
             _ => None,
         }
     }
+
+    /// File the job's source is written to in /code
+    ///
+    /// The JVM languages name it after the main class, like Java's `public class Main`:
+    /// Kotlin compiles a top-level `fun main` in Main.kt to class MainKt, and Scala runs
+    /// `object Main`.
+    pub fn source_file(&self) -> &'static str {
+        match self {
+            Language::Python => "main.py",
+            Language::Java => "Main.java",
+            Language::Rust => "main.rs",
+            Language::Kotlin => "Main.kt",
+            Language::Scala => "Main.scala",
+            Language::C => "main.c",
+            Language::Php => "main.php",
+            Language::Ruby => "main.rb",
+            Language::Asm => "main.asm",
+        }
+    }
 }
 
 impl fmt::Display for Language {
@@ -81,6 +216,12 @@ impl fmt::Display for Language {
             Language::Python => write!(f, "python"),
             Language::Java => write!(f, "java"),
             Language::Rust => write!(f, "rust"),
+            Language::Kotlin => write!(f, "kotlin"),
+            Language::Scala => write!(f, "scala"),
+            Language::C => write!(f, "c"),
+            Language::Php => write!(f, "php"),
+            Language::Ruby => write!(f, "ruby"),
+            Language::Asm => write!(f, "asm"),
         }
     }
 }
@@ -102,6 +243,371 @@ pub struct JobRequest {
     pub timeout_ms: u64,
     #[serde(default)]
     pub metadata: JobMetadata,
+    #[serde(default)]
+    pub normalization: NormalizationOptions,
+    #[serde(default)]
+    pub output_mode: OutputMode,
+    /// Default comparison mode for test cases that don't set their own
+    /// (None = the language's default_comparison flag, else ordered)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comparison: Option<ComparisonMode>,
+    /// Declarative evaluator pipeline; replaces normalization/comparison when set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evaluation: Option<EvaluationPipeline>,
+    /// How overall_status is derived from the score (None = Completed if any test passed)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_policy: Option<StatusPolicy>,
+    /// Scale the weighted score to a fixed number of points (e.g. 100)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scoring: Option<ScoreScaling>,
+    /// Rank the result on the problem's (and contest's) leaderboard
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leaderboard: Option<LeaderboardTag>,
+    /// Checker source code (special judge), in the job's language; its exit code
+    /// decides each test instead of output comparison
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checker: Option<String>,
+    /// Run the program once on empty input before the timed tests, discarding the run
+    /// (None = the language's `warmup` flag)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warmup: Option<bool>,
+    /// Build the program with these sanitizers (C only); an error they detect fails
+    /// the test as a runtime error with the sanitizer's report
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sanitizers: Vec<Sanitizer>,
+    /// What `source_code` holds: the program itself, or a Jupyter notebook (Python)
+    /// whose code cells the worker extracts into the program before judging
+    #[serde(default)]
+    pub source_format: SourceFormat,
+    /// Interactor source code (interactive problems), in the job's language; it converses
+    /// with the program over stdin/stdout and its exit code decides each test
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interactor: Option<String>,
+    /// How tests with a `group_id` are scored as subtasks
+    #[serde(default)]
+    pub group_scoring: GroupScoring,
+    /// How particular subtasks are scored, by group id, where they differ from
+    /// `group_scoring`
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub subtask_scoring: std::collections::BTreeMap<u32, GroupScoring>,
+}
+
+/// An empty Python job with a fresh id, to fill in with struct update syntax
+impl Default for JobRequest {
+    fn default() -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            language: Language::Python,
+            source_code: String::new(),
+            test_cases: Vec::new(),
+            timeout_ms: 0,
+            metadata: JobMetadata::default(),
+            normalization: NormalizationOptions::default(),
+            output_mode: OutputMode::default(),
+            comparison: None,
+            evaluation: None,
+            status_policy: None,
+            scoring: None,
+            leaderboard: None,
+            checker: None,
+            warmup: None,
+            sanitizers: Vec::new(),
+            source_format: SourceFormat::default(),
+            interactor: None,
+            group_scoring: GroupScoring::default(),
+            subtask_scoring: Default::default(),
+        }
+    }
+}
+
+/// Submission Source Format
+/// Script (default): source_code is the program
+/// Notebook: source_code is an .ipynb document; its code cells, in order, are the program
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceFormat {
+    #[default]
+    Script,
+    Notebook,
+}
+
+/// gcc runtime checks a C program can be built with (`-fsanitize=<name>`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Sanitizer {
+    /// Out-of-bounds and use-after-free accesses (AddressSanitizer)
+    Address,
+    /// Signed overflow, bad shifts, null dereferences... (UndefinedBehaviorSanitizer)
+    Undefined,
+}
+
+impl Sanitizer {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Sanitizer::Address => "address",
+            Sanitizer::Undefined => "undefined",
+        }
+    }
+}
+
+impl JobRequest {
+    /// Sum of all test weights; saturates instead of overflowing on weights the API
+    /// would have refused (jobs from older or other producers)
+    pub fn max_score(&self) -> u32 {
+        self.test_cases.iter().fold(0u32, |sum, tc| sum.saturating_add(tc.weight))
+    }
+
+    /// Hex SHA-256 of everything besides the program that decides the job's score: its
+    /// tests, time limit, and how outputs are compared, checked and scored
+    ///
+    /// The submitter picks all of these, so a leaderboard only ranks results whose
+    /// fingerprint an admin registered for the problem (see redis::record_leaderboard).
+    pub fn test_set_fingerprint(&self) -> String {
+        use sha2::{Digest, Sha256};
+
+        let scored = serde_json::json!({
+            "test_cases": self.test_cases,
+            "timeout_ms": self.timeout_ms,
+            "normalization": self.normalization,
+            "output_mode": self.output_mode,
+            "comparison": self.comparison,
+            "evaluation": self.evaluation,
+            "scoring": self.scoring,
+            "checker": self.checker,
+            "interactor": self.interactor,
+            "group_scoring": self.group_scoring,
+            "subtask_scoring": self.subtask_scoring,
+        });
+        hex::encode(Sha256::digest(scored.to_string().as_bytes()))
+    }
+}
+
+/// Score Scaling
+/// points = score / max_score * max_points, rounded to `decimals` places by `rounding`
+///
+/// Computed in exact integer arithmetic, so every consumer sees the same points
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScoreScaling {
+    pub max_points: u32,
+    #[serde(default)]
+    pub rounding: Rounding,
+    #[serde(default)]
+    pub decimals: u32,
+}
+
+/// Largest `decimals` accepted for scaled points
+pub const MAX_POINTS_DECIMALS: u32 = 6;
+
+/// Rounding rule for scaled points
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rounding {
+    /// Round half away from zero (2.5 -> 3)
+    #[default]
+    HalfUp,
+    /// Round half to even (2.5 -> 2, 3.5 -> 4)
+    HalfEven,
+    Floor,
+    Ceil,
+}
+
+impl ScoreScaling {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_points == 0 {
+            return Err("max_points must be greater than 0".to_string());
+        }
+        if self.decimals > MAX_POINTS_DECIMALS {
+            return Err(format!("decimals must be at most {}", MAX_POINTS_DECIMALS));
+        }
+        Ok(())
+    }
+
+    /// Scale a raw weighted score (a job with no weight scores 0 points)
+    pub fn scale(&self, score: u32, max_score: u32) -> ScaledScore {
+        let unit = 10u128.pow(self.decimals);
+        let points = if max_score == 0 {
+            0.0
+        } else {
+            let numerator = score as u128 * self.max_points as u128 * unit;
+            let denominator = max_score as u128;
+            let (quotient, remainder) = (numerator / denominator, numerator % denominator);
+            let round_up = match self.rounding {
+                Rounding::Floor => false,
+                Rounding::Ceil => remainder > 0,
+                Rounding::HalfUp => remainder * 2 >= denominator,
+                Rounding::HalfEven => {
+                    remainder * 2 > denominator || (remainder * 2 == denominator && quotient % 2 == 1)
+                }
+            };
+            (quotient + round_up as u128) as f64 / unit as f64
+        };
+        ScaledScore {
+            points,
+            max_points: self.max_points,
+            rounding: self.rounding,
+            decimals: self.decimals,
+        }
+    }
+}
+
+/// Scaled score reported with a result, along with the rule that produced it
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ScaledScore {
+    pub points: f64,
+    pub max_points: u32,
+    pub rounding: Rounding,
+    pub decimals: u32,
+}
+
+/// Overall Status Policy
+/// Decides whether a score counts as a pass; with a policy set, a passing job is
+/// Passed (every test passed) or PartiallyPassed, and anything else is Failed
+///
+/// JSON: `"any_pass"`, `"all_pass"` or `{"threshold": 60}`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatusPolicy {
+    /// At least one weighted test passed (score > 0)
+    AnyPass,
+    /// Every test passed
+    AllPass,
+    /// Score is at least this percentage of max_score
+    Threshold(f64),
+}
+
+impl StatusPolicy {
+    /// Reject thresholds outside 0-100
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            StatusPolicy::Threshold(percent) if !(0.0..=100.0).contains(percent) => {
+                Err("threshold must be a percentage between 0 and 100".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether a job with this outcome passes
+    pub fn accepts(&self, score: u32, max_score: u32, all_passed: bool) -> bool {
+        match self {
+            StatusPolicy::AnyPass => score > 0,
+            StatusPolicy::AllPass => all_passed,
+            StatusPolicy::Threshold(percent) => {
+                score as f64 * 100.0 >= percent * max_score as f64
+            }
+        }
+    }
+}
+
+/// Evaluator Pipeline
+/// Normalizers applied in order to both outputs, then one comparator
+///
+/// ```json
+/// { "normalizers": [{"type": "trim"}, {"type": "float_round", "decimals": 3}],
+///   "comparator": {"type": "tokens"} }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EvaluationPipeline {
+    #[serde(default)]
+    pub normalizers: Vec<Normalizer>,
+    #[serde(default)]
+    pub comparator: Comparator,
+}
+
+/// Largest `float_round` precision accepted
+pub const MAX_ROUND_DECIMALS: u32 = 15;
+
+impl EvaluationPipeline {
+    /// Reject settings the evaluator cannot apply meaningfully
+    pub fn validate(&self) -> Result<(), String> {
+        for normalizer in &self.normalizers {
+            if let Normalizer::FloatRound { decimals } = normalizer {
+                if *decimals > MAX_ROUND_DECIMALS {
+                    return Err(format!("float_round decimals must be at most {}", MAX_ROUND_DECIMALS));
+                }
+            }
+        }
+        if let Comparator::Numeric { abs_tolerance, rel_tolerance } = self.comparator {
+            if !(abs_tolerance >= 0.0 && rel_tolerance >= 0.0) {
+                return Err("numeric tolerances must be non-negative numbers".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// One normalization step of an evaluator pipeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Normalizer {
+    /// Trim leading and trailing whitespace of the whole output
+    Trim,
+    /// Trim trailing whitespace of every line (also drops \r)
+    TrimLines,
+    /// Collapse runs of spaces/tabs within a line to one space
+    CollapseWhitespace,
+    Lowercase,
+    /// Round every decimal number to this many places
+    FloatRound { decimals: u32 },
+    /// Drop a leading byte-order mark (U+FEFF)
+    StripBom,
+    /// Normalize Unicode to NFC
+    UnicodeNfc,
+    /// Drop U+FFFD replacement characters left by lossy UTF-8 decoding
+    DropReplacementChars,
+}
+
+/// How normalized outputs are compared
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Comparator {
+    /// Identical strings
+    #[default]
+    Exact,
+    /// Same lines in any order (multiset)
+    UnorderedLines,
+    /// Same whitespace-separated tokens in the same order
+    Tokens,
+    /// Tokens in order; numbers match within either tolerance, other tokens exactly
+    Numeric {
+        #[serde(default)]
+        abs_tolerance: f64,
+        #[serde(default)]
+        rel_tolerance: f64,
+    },
+}
+
+/// Output Capture Mode
+/// Text (default): stdout is decoded as UTF-8 and compared after normalization
+/// Binary: stdout is compared byte-for-byte; expected_output holds base64 bytes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputMode {
+    #[default]
+    Text,
+    Binary,
+}
+
+/// Output Normalization Options
+/// Applied to both actual and expected output before comparison
+/// All options default to off - existing jobs compare exactly as before
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NormalizationOptions {
+    /// Normalize Unicode to NFC (composed "é" == "e" + combining accent)
+    pub unicode_nfc: bool,
+    /// Strip a leading byte-order mark (U+FEFF)
+    pub strip_bom: bool,
+    /// Ignore U+FFFD replacement characters produced by lossy UTF-8 decoding
+    pub lenient_utf8: bool,
+    /// Ignore whitespace at the end of every line ("1 2 \n" == "1 2\n")
+    pub ignore_trailing_whitespace: bool,
+    /// Compare case-insensitively ("YES" == "yes")
+    pub ignore_case: bool,
+    /// Treat runs of spaces and tabs within a line as one space ("1   2" == "1 2")
+    pub collapse_whitespace: bool,
+    /// Compare whitespace-separated tokens, wherever the line breaks fall (ordered and
+    /// strict comparison; unordered comparison still compares lines)
+    pub tokens: bool,
 }
 
 /// Job State Machine
@@ -114,6 +620,11 @@ pub enum JobStatus {
     Running,
     Completed,
     Failed,
+    /// Every test passed (jobs with a status policy)
+    Passed,
+    /// The status policy accepted the score, but not every test passed
+    #[serde(rename = "partially_passed")]
+    PartiallyPassed,
     TimedOut,
     Cancelled,
 }
@@ -127,6 +638,8 @@ pub enum TestStatus {
     Failed,
     RuntimeError,
     TimeLimitExceeded,
+    /// Killed for exceeding the memory limit
+    MemoryLimitExceeded,
 }
 
 /// Per-Test Result
@@ -139,6 +652,105 @@ pub struct TestResult {
     pub stdout: String,
     pub stderr: String,
     pub execution_time_ms: u64,
+    /// Raw stdout bytes (base64) - only present for binary output mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stdout_base64: Option<String>,
+    /// Raw stderr bytes (base64) - only present for binary output mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stderr_base64: Option<String>,
+    /// Exec setup time measured separately and not charged to execution_time_ms
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overhead_ms: Option<u64>,
+    /// User + system CPU time, when the judge image reports it; the time limit applies to it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_time_ms: Option<u64>,
+    /// Program wall-clock time (the same as execution_time_ms), held to a ceiling above the
+    /// time limit when CPU time is measured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wall_time_ms: Option<u64>,
+    /// Peak memory of the test in KiB: the container cgroup's `memory.peak` when the test
+    /// set it, else the program's resident peak, else the highest container usage sampled
+    /// while it ran
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_kb: Option<u64>,
+    /// What went wrong when the status alone doesn't say (compile errors and sandbox
+    /// failures, both reported as runtime_error; checker errors)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure: Option<TestFailure>,
+    /// What the job's checker printed about this test (truncated)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checker_output: Option<String>,
+    /// The sanitizer's error report, for a runtime error a sanitizer caught (truncated)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sanitizer_report: Option<String>,
+    /// Where stdout first differs from the expected output, for a test failed on its output
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff: Option<OutputDiff>,
+}
+
+/// Output Diff
+/// The first difference between a failed test's output and the expected output, as the
+/// comparison saw them (after normalization); positions are 1-based
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutputDiff {
+    pub line: usize,
+    /// Character within the line
+    pub column: usize,
+    /// The expected line around the difference; None if the expected output ended first
+    pub expected: Option<String>,
+    /// The program's line around the difference; None if its output ended first
+    pub actual: Option<String>,
+    /// Column both snippets start at (1 unless the lines were cut)
+    pub snippet_start: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TestFailure {
+    CompilationError,
+    /// Results stored before `TestStatus::MemoryLimitExceeded`, which reported memory kills
+    /// as runtime errors
+    MemoryLimitExceeded,
+    /// The sandbox failed to run the test; not the submission's fault
+    SandboxError,
+    /// The job's checker failed to compile, crashed or timed out; not the submission's fault
+    CheckerError,
+}
+
+/// User-Facing Verdict
+/// The short codes judges show (AC, WA, ...), derived from statuses and failures;
+/// labels and explanations per locale are in i18n.rs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Verdict {
+    /// Accepted
+    AC,
+    /// Wrong Answer
+    WA,
+    /// Time Limit Exceeded
+    TLE,
+    /// Memory Limit Exceeded
+    MLE,
+    /// Runtime Error
+    RE,
+    /// Compilation Error
+    CE,
+    /// Internal Error
+    IE,
+}
+
+impl TestResult {
+    pub fn verdict(&self) -> Verdict {
+        match (self.status, self.failure) {
+            (TestStatus::Passed, _) => Verdict::AC,
+            (TestStatus::MemoryLimitExceeded, _) => Verdict::MLE,
+            (_, Some(TestFailure::CompilationError)) => Verdict::CE,
+            (_, Some(TestFailure::MemoryLimitExceeded)) => Verdict::MLE,
+            (_, Some(TestFailure::SandboxError | TestFailure::CheckerError)) => Verdict::IE,
+            (TestStatus::Failed, None) => Verdict::WA,
+            (TestStatus::TimeLimitExceeded, None) => Verdict::TLE,
+            (TestStatus::RuntimeError, None) => Verdict::RE,
+        }
+    }
 }
 
 /// Execution Output
@@ -147,7 +759,8 @@ pub struct TestResult {
 /// ## Scoring Semantics:
 /// - score: sum of weights for passed tests
 /// - max_score: sum of all test case weights
-/// - overall_status: Completed if all tests passed, Failed otherwise
+/// - overall_status: Completed if any test passed, Failed otherwise;
+///   with a StatusPolicy, Passed / PartiallyPassed / Failed
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionResult {
     pub job_id: Uuid,
@@ -155,6 +768,619 @@ pub struct ExecutionResult {
     pub score: u32,
     pub max_score: u32,
     pub results: Vec<TestResult>,
+    /// Judge environment version of the image that produced this result
+    /// (see JudgeEnvRelease); absent for results from untracked images
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub judge_env_version: Option<u32>,
+    /// Worker that executed the job
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worker_id: Option<String>,
+    /// Integrity signature added by the worker (see signing.rs)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<ResultSignature>,
+    /// Score scaled per the job's ScoreScaling (absent when the job has none)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub points: Option<ScaledScore>,
+    /// Where the job spent its time (absent on results that never reached execution)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timings: Option<JobTimings>,
+    /// Suspected misuse found after execution, for languages with misuse detection on;
+    /// a signal for instructors that never changes the verdict
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub misuse: Vec<MisuseWarning>,
+    /// Time limit the tests ran under: the job's `timeout_ms` times the language's
+    /// `time_multiplier` (absent on results that never reached execution)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effective_timeout_ms: Option<u64>,
+    /// Score of each test group (subtask), by group id; empty when no test has a group
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub subtasks: Vec<SubtaskScore>,
+}
+
+/// Subtask Score
+/// What one test group earned under its GroupScoring
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubtaskScore {
+    pub group_id: u32,
+    pub score: u32,
+    /// Sum of the group's test weights
+    pub max_score: u32,
+    /// Whether every test in the group passed
+    pub passed: bool,
+}
+
+impl ExecutionResult {
+    /// Whether every test passed (an AC verdict)
+    pub fn accepted(&self) -> bool {
+        !self.results.is_empty() && self.results.iter().all(|t| t.status == TestStatus::Passed)
+    }
+
+    /// Verdict of the whole submission: AC when every test passed, else the first failing
+    /// test's verdict; a compile or sandbox failure anywhere wins. None for jobs that never
+    /// produced a verdict (pending or cancelled).
+    pub fn verdict(&self) -> Option<Verdict> {
+        if self.overall_status == JobStatus::Cancelled {
+            return None;
+        }
+        if self.results.is_empty() {
+            // Jobs that exhausted their retries are stored as failed without results
+            return (self.overall_status == JobStatus::Failed).then_some(Verdict::IE);
+        }
+        let verdicts: Vec<Verdict> = self.results.iter().map(TestResult::verdict).collect();
+        [Verdict::CE, Verdict::IE]
+            .into_iter()
+            .find(|v| verdicts.contains(v))
+            .or_else(|| verdicts.iter().copied().find(|v| *v != Verdict::AC))
+            .or(Some(Verdict::AC))
+    }
+}
+
+/// Misuse Warning
+/// A known abuse pattern seen in a submission's source or its test output
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MisuseWarning {
+    pub kind: MisuseKind,
+    /// Test whose output showed it (absent for findings in the source)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test_id: Option<u32>,
+    /// What matched, e.g. `subprocess` or `/etc/passwd`
+    pub evidence: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MisuseKind {
+    /// Starting other programs (a shell, curl, ...)
+    ProcessSpawn,
+    /// Opening network connections
+    NetworkAccess,
+    /// Reading system files such as /etc/passwd
+    SensitiveFileAccess,
+    /// Creating threads until the sandbox refuses more
+    ThreadExhaustion,
+}
+
+/// Job Phase Timings
+/// Filled in by the worker so users and operators can see where a slow job spent its time
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobTimings {
+    /// From queueing (or the latest retry) to the worker picking the job up
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub queue_wait_ms: Option<u64>,
+    /// Compile step of compile-once execution (absent when compiling per test)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compile_ms: Option<u64>,
+    /// Running the job in the sandbox, compilation included
+    pub total_execution_ms: u64,
+    /// Scoring the outputs
+    pub evaluation_ms: u64,
+    /// Writing the result to storage; only in results of older workers (workers now log
+    /// it, since recording it in the result took a second write)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub persist_ms: Option<u64>,
+}
+
+/// Result Signature
+/// HMAC over the result contents, tagged with the key that produced it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResultSignature {
+    pub algorithm: String,
+    pub key_id: String,
+    /// Hex-encoded MAC
+    pub value: String,
+}
+
+/// Per-Test Progress
+/// Published by the worker as each test finishes (verdict and timing only, no output)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestProgress {
+    pub test_id: u32,
+    pub status: TestStatus,
+    pub execution_time_ms: u64,
+    pub total_tests: usize,
+}
+
+/// Output Chunk
+/// Part of a running test's output, as the program writes it (workers with live output on)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputChunk {
+    pub test_id: u32,
+    pub stream: OutputStream,
+    pub data: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// Progress Event
+/// One step of a job's life, pushed live to clients (GET /job/{id}/stream):
+/// queued -> running -> compiling -> one `test` per finished test -> done,
+/// with `output` chunks while a test runs when the worker tees its output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProgressEvent {
+    Queued,
+    /// A worker picked the job up
+    Running,
+    Compiling,
+    /// The start of a running test's output (never stored, live streams only)
+    Output(OutputChunk),
+    Test(TestProgress),
+    /// The result is stored; nothing follows
+    Done { status: JobStatus },
+}
+
+/// A progress event as workers publish it on the progress channel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobProgressEvent {
+    pub job_id: Uuid,
+    #[serde(flatten)]
+    pub event: ProgressEvent,
+}
+
+/// Worker Heartbeat
+/// Published periodically by each worker; expires when the worker stops
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkerHeartbeat {
+    pub worker_id: String,
+    pub language: Language,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub last_seen: chrono::DateTime<chrono::Utc>,
+    pub executing: bool,
+    pub max_parallel_jobs: usize,
+    /// No longer taking jobs; exits once the running ones finish (drain request, preStop, SIGTERM)
+    #[serde(default)]
+    pub draining: bool,
+    /// Capability labels the worker offers (`gpu`, `arm64`, WORKER_CAPABILITIES)
+    #[serde(default)]
+    pub capabilities: Vec<String>,
+    /// Pending queues the worker reads (redis::worker_queues), requirement queues included
+    #[serde(default)]
+    pub queues: Vec<String>,
+}
+
+/// Recent Submission
+/// Lightweight record of an accepted job for operator dashboards (no source or test data)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentJob {
+    pub job_id: Uuid,
+    pub language: Language,
+    pub test_cases: usize,
+    pub submitted_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Where a job waits (or runs) between submission and its result
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobPlace {
+    /// On the dispatcher's intake
+    Intake,
+    Queued,
+    Retrying,
+    /// Claimed by a worker
+    Running,
+    DeadLettered,
+}
+
+/// Job Location
+/// Where a job was last put, so lookups don't scan every queue (see redis::job_location_key).
+/// Kept as long as a result after its last move; a dead-lettered job's until it leaves the DLQ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobLocation {
+    pub place: JobPlace,
+    /// The list holding the job (None while it runs)
+    pub queue: Option<String>,
+    /// The job's metadata as of its last move
+    pub metadata: JobMetadata,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Tombstone
+/// A deleted submission: hidden as soon as it is deleted, purged once `purge_at` passes
+/// (an admin can restore it until then)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Tombstone {
+    pub job_id: Uuid,
+    pub deleted_at: chrono::DateTime<chrono::Utc>,
+    pub purge_at: chrono::DateTime<chrono::Utc>,
+    /// Tenant whose retention index held the job; restoring puts it back
+    pub tenant: String,
+    /// Score of the job in that index (unix seconds), if it was indexed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub submitted_at: Option<i64>,
+}
+
+/// Stored Idempotency-Key
+/// What the admin API shows of a client's key (never the payload it fingerprints)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IdempotencyEntry {
+    /// The client's Idempotency-Key header
+    pub key: String,
+    pub job_id: Option<String>,
+    pub created_at: Option<String>,
+    /// Seconds until the key expires (-1: never)
+    pub ttl_secs: i64,
+}
+
+/// Longest problem, contest or participant id on a leaderboard
+pub const MAX_LEADERBOARD_ID_LEN: usize = 64;
+
+/// Leaderboard Tag
+/// Who submitted a job and for which problem (and contest), so its result is ranked
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeaderboardTag {
+    pub problem_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contest_id: Option<String>,
+    pub participant: String,
+}
+
+impl LeaderboardTag {
+    /// Ids end up in Redis keys: 1-64 letters, digits, '-', '_' or '.'
+    pub fn validate(&self) -> Result<(), String> {
+        let ids = [("problem_id", Some(&self.problem_id)), ("contest_id", self.contest_id.as_ref()), ("participant", Some(&self.participant))];
+        for (field, id) in ids {
+            let Some(id) = id else { continue };
+            if !valid_leaderboard_id(id) {
+                return Err(format!(
+                    "leaderboard.{} must be 1-{} letters, digits, '-', '_' or '.'",
+                    field, MAX_LEADERBOARD_ID_LEN
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether an id can name a problem, contest or participant
+pub fn valid_leaderboard_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_LEADERBOARD_ID_LEN
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Best score and time of a participant (on a problem, or summed over a contest)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeaderboardScore {
+    pub score: u64,
+    /// Summed test execution time
+    pub time_ms: u64,
+    /// Job of the best submission (problem scores only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<String>,
+}
+
+/// Leaderboard Entry
+/// Ranked by score (highest first), then time (fastest first)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub rank: u64,
+    pub participant: String,
+    #[serde(flatten)]
+    pub best: LeaderboardScore,
+    /// Best score per problem (contest leaderboards only)
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub problems: std::collections::BTreeMap<String, LeaderboardScore>,
+}
+
+/// Problem Timeline Event
+/// Milestones of a problem as results arrive, for contest commentary views
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimelineEvent {
+    pub kind: TimelineEventKind,
+    pub participant: String,
+    pub job_id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contest_id: Option<String>,
+    /// Summed test execution time of the submission
+    pub time_ms: u64,
+    pub at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelineEventKind {
+    /// The problem's first accepted submission
+    FirstSolve,
+    /// A participant's first accepted submission (after the first solve)
+    Solved,
+    /// An accepted submission faster than every earlier one
+    FastestSolution,
+}
+
+/// Submissions to a problem within one minute
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubmissionBucket {
+    pub minute: chrono::DateTime<chrono::Utc>,
+    pub submissions: u64,
+    pub accepted: u64,
+}
+
+/// Problem Statistics
+/// Counters and timeline of a problem, kept up to date as results are persisted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProblemStats {
+    pub problem_id: String,
+    pub submissions: u64,
+    /// Submissions that passed every test
+    pub accepted: u64,
+    /// Participants with at least one accepted submission
+    pub solvers: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_solve: Option<TimelineEvent>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fastest_solution: Option<TimelineEvent>,
+    /// Most recent milestones, oldest first
+    pub timeline: Vec<TimelineEvent>,
+    /// Submission counts per minute, oldest first
+    pub submissions_per_minute: Vec<SubmissionBucket>,
+}
+
+/// Judge Environment Release
+/// One entry in a language's judge environment changelog, recorded in languages.json
+/// by `optimus-cli build-image` whenever a rebuild produces a different image
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JudgeEnvRelease {
+    pub version: u32,
+    pub image: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_id: Option<String>,
+    pub built_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Shadow Comparison
+/// Outcome of re-running a job on a canary (candidate) judge image
+/// The candidate's result is never returned to users; only this comparison is reported
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShadowComparison {
+    pub job_id: Uuid,
+    pub language: Language,
+    pub primary_image: String,
+    pub candidate_image: String,
+    pub primary_status: JobStatus,
+    pub candidate_status: JobStatus,
+    pub primary_score: u32,
+    pub candidate_score: u32,
+    pub max_score: u32,
+    /// Tests whose verdict differs between the two images
+    pub mismatched_tests: Vec<u32>,
+    pub primary_time_ms: u64,
+    pub candidate_time_ms: u64,
+    pub compared_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl ShadowComparison {
+    /// Same overall status, score and per-test verdicts
+    pub fn agrees(&self) -> bool {
+        self.primary_status == self.candidate_status
+            && self.primary_score == self.candidate_score
+            && self.mismatched_tests.is_empty()
+    }
+}
+
+/// Dual-Run Verification
+/// Admin-requested re-run of a finished job; `report` is filled in by the worker
+/// that executed the re-run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Verification {
+    pub original_job_id: Uuid,
+    pub verification_job_id: Uuid,
+    pub requested_at: chrono::DateTime<chrono::Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub report: Option<VerificationReport>,
+}
+
+/// Field-by-field comparison of the original and re-run results
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerificationReport {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_worker: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verification_worker: Option<String>,
+    pub image: String,
+    /// Overall status or score changed between runs
+    pub verdict_changed: bool,
+    /// Any verdict or output field differs (execution times excluded)
+    pub nondeterministic: bool,
+    /// Tests that timed out in exactly one of the two runs
+    pub timing_sensitive_tests: Vec<u32>,
+    pub differences: Vec<FieldDifference>,
+    pub timings: Vec<TestTiming>,
+    pub verified_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldDifference {
+    /// Path of the differing field, e.g. `results[test_id=3].stdout`
+    pub field: String,
+    pub original: String,
+    pub verification: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestTiming {
+    pub test_id: u32,
+    pub original_ms: u64,
+    pub verification_ms: u64,
+}
+
+/// Debug Artifacts
+/// Resource usage sampled while each test of a job ran (no program output)
+/// Kept for 24h to explain borderline TLE/MLE verdicts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DebugArtifacts {
+    pub job_id: Uuid,
+    /// CPU quota of the execution container, in percent of one CPU
+    pub cpu_limit_percent: f64,
+    pub memory_limit_kb: u64,
+    pub tests: Vec<TestResourceUsage>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestResourceUsage {
+    pub test_id: u32,
+    pub status: TestStatus,
+    pub execution_time_ms: u64,
+    pub peak_memory_kb: u64,
+    pub avg_cpu_percent: f64,
+    /// Why the verdict is (or nearly was) a limit verdict, when it is borderline
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<String>,
+    pub samples: Vec<ResourceSample>,
+}
+
+/// One container stats sample taken while a test ran
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ResourceSample {
+    /// Milliseconds since the test started
+    pub t_ms: u64,
+    /// CPU usage since the previous sample, in percent of one CPU
+    pub cpu_percent: f64,
+    pub memory_kb: u64,
+}
+
+/// Review Annotation
+/// A reviewer's note on a stored result (manual grading adjustments, feedback),
+/// returned alongside the automated verdict
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Annotation {
+    pub id: Uuid,
+    pub author: String,
+    pub text: String,
+    /// Tests the note refers to (empty = the whole submission)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub test_ids: Vec<u32>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Score Override
+/// A manual correction of a job's final score and/or status, kept next to the
+/// machine result (which is never modified); every override is retained as an audit trail
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoreOverride {
+    pub job_id: Uuid,
+    pub actor: String,
+    pub reason: String,
+    pub score: u32,
+    pub status: JobStatus,
+    /// Machine verdict at the time of the override
+    pub machine_score: u32,
+    pub machine_status: JobStatus,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// System Event
+/// Operational event published to the `optimus:events` stream (and outbound webhooks)
+/// Never carries source code or test data - only ids, language and short reasons
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SystemEvent {
+    pub id: Uuid,
+    pub kind: EventKind,
+    pub at: chrono::DateTime<chrono::Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<Uuid>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<Language>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worker_id: Option<String>,
+    /// Event-specific fields (attempt numbers, failure reasons, ...)
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    pub details: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    JobSubmitted,
+    JobStarted,
+    JobRetried,
+    JobDlq,
+    WorkerUp,
+    WorkerDown,
+    /// A worker stopped taking jobs on an admin drain request
+    WorkerDraining,
+    QueuePaused,
+    QueueResumed,
+    /// A worker's sandbox self-test failed (details.reason)
+    SandboxSelfTestFailed,
+}
+
+impl SystemEvent {
+    pub fn new(kind: EventKind) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            kind,
+            at: chrono::Utc::now(),
+            job_id: None,
+            language: None,
+            worker_id: None,
+            details: serde_json::Value::Null,
+        }
+    }
+
+    /// Event about a job (id and language only)
+    pub fn job(kind: EventKind, job: &JobRequest) -> Self {
+        Self {
+            job_id: Some(job.id),
+            language: Some(job.language),
+            ..Self::new(kind)
+        }
+    }
+
+    /// Event about a worker
+    pub fn worker(kind: EventKind, worker_id: &str, language: Language) -> Self {
+        Self {
+            worker_id: Some(worker_id.to_string()),
+            language: Some(language),
+            ..Self::new(kind)
+        }
+    }
+
+    pub fn with_details(mut self, details: serde_json::Value) -> Self {
+        self.details = details;
+        self
+    }
+}
+
+/// Runtime Info
+/// Exact toolchain versions found inside a language's judge image
+/// Probed by a worker and cached per image id, so it always matches what judges code
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuntimeInfo {
+    pub language: Language,
+    pub image: String,
+    /// Docker image id the probe ran against
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_id: Option<String>,
+    /// Tool name -> version string as printed by the tool (e.g. "javac" -> "javac 17.0.2")
+    pub toolchain: std::collections::BTreeMap<String, String>,
+    pub probed_at: chrono::DateTime<chrono::Utc>,
+    pub probed_by: String,
 }
 
 #[cfg(test)]
@@ -179,12 +1405,14 @@ mod tests {
                 input: "5\n".to_string(),
                 expected_output: "120\n".to_string(),
                 weight: 10,
+                ..Default::default()
             },
             TestCase {
                 id: 2,
                 input: "3\n".to_string(),
                 expected_output: "6\n".to_string(),
                 weight: 10,
+                ..Default::default()
             },
         ];
         
@@ -194,7 +1422,7 @@ mod tests {
             source_code: "public class Main {}".to_string(),
             test_cases,
             timeout_ms: 5000,
-            metadata: JobMetadata::default(),
+            ..Default::default()
         };
         
         let json = serde_json::to_string(&job).unwrap();
@@ -204,6 +1432,60 @@ mod tests {
         assert_eq!(deserialized.timeout_ms, 5000);
         assert_eq!(deserialized.test_cases.len(), 2);
         assert_eq!(deserialized.test_cases[0].weight, 10);
+        assert_eq!(deserialized.max_score(), 20);
+    }
+
+    #[test]
+    fn test_max_score_saturates() {
+        let job: JobRequest = serde_json::from_value(serde_json::json!({
+            "id": Uuid::new_v4(),
+            "language": "python",
+            "source_code": "",
+            "test_cases": (1..=100)
+                .map(|id| serde_json::json!({"id": id, "input": "", "expected_output": "", "weight": u32::MAX}))
+                .collect::<Vec<_>>(),
+            "timeout_ms": 1000
+        }))
+        .unwrap();
+        assert_eq!(job.max_score(), u32::MAX);
+        // The API's bound keeps every valid job far from saturating
+        assert!(MAX_TEST_WEIGHT.checked_mul(100).is_some());
+    }
+
+    #[test]
+    fn test_test_set_fingerprint() {
+        let job: JobRequest = serde_json::from_value(serde_json::json!({
+            "id": Uuid::new_v4(),
+            "language": "python",
+            "source_code": "print(2)",
+            "test_cases": [{"id": 1, "input": "1\n", "expected_output": "2\n", "weight": 10}],
+            "timeout_ms": 1000
+        }))
+        .unwrap();
+        // Another program (or language) against the same tests shares the fingerprint
+        let mut other = job.clone();
+        other.id = Uuid::new_v4();
+        other.source_code = "print(3)".into();
+        other.language = Language::Ruby;
+        assert_eq!(job.test_set_fingerprint(), other.test_set_fingerprint());
+        // A reweighted test or a looser time limit does not
+        other.test_cases[0].weight = 1000;
+        assert_ne!(job.test_set_fingerprint(), other.test_set_fingerprint());
+        other = job.clone();
+        other.timeout_ms = 60_000;
+        assert_ne!(job.test_set_fingerprint(), other.test_set_fingerprint());
+    }
+
+    #[test]
+    fn test_leaderboard_tag_ids() {
+        let mut tag = LeaderboardTag { problem_id: "two-sum".into(), contest_id: Some("weekly_12".into()), participant: "alice.b".into() };
+        assert!(tag.validate().is_ok());
+        // Ids name Redis keys, so ':' (and anything else odd) is refused
+        tag.contest_id = Some("weekly:12".into());
+        assert!(tag.validate().unwrap_err().contains("contest_id"));
+        tag.contest_id = None;
+        tag.participant = "a".repeat(MAX_LEADERBOARD_ID_LEN + 1);
+        assert!(tag.validate().unwrap_err().contains("participant"));
     }
 
     #[test]
@@ -211,6 +1493,52 @@ mod tests {
         let status = JobStatus::Completed;
         let json = serde_json::to_string(&status).unwrap();
         assert_eq!(json, "\"completed\"");
+        assert_eq!(serde_json::to_string(&JobStatus::PartiallyPassed).unwrap(), "\"partially_passed\"");
+    }
+
+    #[test]
+    fn test_group_scoring_per_subtask() {
+        // "min" over pass/fail tests is all_or_nothing
+        assert_eq!(serde_json::from_str::<GroupScoring>("\"min\"").unwrap(), GroupScoring::AllOrNothing);
+        let job: JobRequest = serde_json::from_value(serde_json::json!({
+            "id": Uuid::nil(), "language": "python", "source_code": "", "test_cases": [], "timeout_ms": 1,
+            "group_scoring": "sum", "subtask_scoring": {"2": "all_or_nothing"}
+        }))
+        .unwrap();
+        assert_eq!(job.subtask_scoring.get(&2), Some(&GroupScoring::AllOrNothing));
+        assert_eq!(serde_json::to_value(&job).unwrap()["subtask_scoring"], serde_json::json!({"2": "all_or_nothing"}));
+    }
+
+    #[test]
+    fn test_score_scaling() {
+        let scaling = |rounding, decimals| ScoreScaling { max_points: 100, rounding, decimals };
+        // 1 of 3 weighted points = 33.333...
+        assert_eq!(scaling(Rounding::HalfUp, 0).scale(1, 3).points, 33.0);
+        assert_eq!(scaling(Rounding::Ceil, 0).scale(1, 3).points, 34.0);
+        assert_eq!(scaling(Rounding::HalfUp, 2).scale(2, 3).points, 66.67);
+        assert_eq!(scaling(Rounding::Floor, 2).scale(2, 3).points, 66.66);
+        // 5 of 8 = 62.5
+        assert_eq!(scaling(Rounding::HalfUp, 0).scale(5, 8).points, 63.0);
+        assert_eq!(scaling(Rounding::HalfEven, 0).scale(5, 8).points, 62.0);
+        assert_eq!(scaling(Rounding::HalfEven, 0).scale(7, 8).points, 88.0);
+        assert_eq!(scaling(Rounding::HalfUp, 0).scale(0, 0).points, 0.0);
+
+        assert!(ScoreScaling { max_points: 0, rounding: Rounding::Floor, decimals: 0 }.validate().is_err());
+        assert!(scaling(Rounding::Floor, 7).validate().is_err());
+    }
+
+    #[test]
+    fn test_status_policy() {
+        let threshold: StatusPolicy = serde_json::from_str(r#"{"threshold": 60}"#).unwrap();
+        assert_eq!(threshold, StatusPolicy::Threshold(60.0));
+        assert_eq!(serde_json::from_str::<StatusPolicy>("\"all_pass\"").unwrap(), StatusPolicy::AllPass);
+
+        assert!(threshold.accepts(60, 100, false));
+        assert!(!threshold.accepts(59, 100, false));
+        assert!(StatusPolicy::AnyPass.accepts(1, 100, false));
+        assert!(!StatusPolicy::AllPass.accepts(99, 100, false));
+        assert!(StatusPolicy::Threshold(101.0).validate().is_err());
+        assert!(StatusPolicy::Threshold(f64::NAN).validate().is_err());
     }
 
     #[test]
@@ -222,6 +1550,16 @@ mod tests {
                 stdout: "120\n".to_string(),
                 stderr: String::new(),
                 execution_time_ms: 45,
+                stdout_base64: None,
+                stderr_base64: None,
+                overhead_ms: None,
+                cpu_time_ms: None,
+                wall_time_ms: None,
+                memory_kb: None,
+                failure: None,
+                checker_output: None,
+                sanitizer_report: None,
+                diff: None,
             },
             TestResult {
                 test_id: 2,
@@ -229,6 +1567,16 @@ mod tests {
                 stdout: "5\n".to_string(),
                 stderr: String::new(),
                 execution_time_ms: 42,
+                stdout_base64: None,
+                stderr_base64: None,
+                overhead_ms: None,
+                cpu_time_ms: None,
+                wall_time_ms: None,
+                memory_kb: None,
+                failure: None,
+                checker_output: None,
+                sanitizer_report: None,
+                diff: None,
             },
         ];
         
@@ -238,6 +1586,14 @@ mod tests {
             score: 10,
             max_score: 20,
             results: test_results,
+            judge_env_version: None,
+            worker_id: None,
+            signature: None,
+            points: None,
+            timings: None,
+            misuse: Vec::new(),
+            effective_timeout_ms: None,
+            subtasks: Vec::new(),
         };
         
         assert_eq!(result.overall_status, JobStatus::Completed);
@@ -247,6 +1603,22 @@ mod tests {
         assert_eq!(result.results[0].status, TestStatus::Passed);
         assert_eq!(result.results[1].status, TestStatus::Failed);
     }
+
+    #[test]
+    fn test_queue_wait_and_timings_serialization() {
+        let now = chrono::Utc::now();
+        let metadata = JobMetadata {
+            enqueued_at: Some(now - chrono::Duration::milliseconds(1500)),
+            ..Default::default()
+        };
+        assert_eq!(metadata.queue_wait_ms(now), Some(1500));
+        assert_eq!(JobMetadata::default().queue_wait_ms(now), None);
+
+        // Phases that did not happen are left out
+        let timings = JobTimings { total_execution_ms: 900, evaluation_ms: 2, ..Default::default() };
+        let json = serde_json::to_value(&timings).unwrap();
+        assert_eq!(json, serde_json::json!({"total_execution_ms": 900, "evaluation_ms": 2}));
+    }
     
     #[test]
     fn test_test_case_immutability() {
@@ -255,6 +1627,7 @@ mod tests {
             input: "input".to_string(),
             expected_output: "output".to_string(),
             weight: 5,
+            ..Default::default()
         };
         
         // Test case can be cloned but original is immutable
@@ -274,13 +1647,54 @@ mod tests {
         assert_eq!(json, "\"failed\"");
     }
     
+    #[test]
+    fn test_verdicts() {
+        let result = |status: &str, tests: serde_json::Value| -> ExecutionResult {
+            serde_json::from_value(serde_json::json!({
+                "job_id": Uuid::nil(), "overall_status": status, "score": 0, "max_score": 0, "results": tests
+            }))
+            .unwrap()
+        };
+        let test = |status: &str, failure: Option<&str>| {
+            serde_json::json!({"test_id": 1, "status": status, "stdout": "", "stderr": "", "execution_time_ms": 1, "failure": failure})
+        };
+
+        let passed = result("completed", serde_json::json!([test("passed", None), test("passed", None)]));
+        assert_eq!(passed.verdict(), Some(Verdict::AC));
+        // The first failing test decides, unless compilation or the sandbox failed
+        let mixed = result("completed", serde_json::json!([test("passed", None), test("timelimitexceeded", None), test("failed", None)]));
+        assert_eq!(mixed.verdict(), Some(Verdict::TLE));
+        let sandbox = result("failed", serde_json::json!([test("failed", None), test("runtimeerror", Some("sandbox_error"))]));
+        assert_eq!(sandbox.verdict(), Some(Verdict::IE));
+        let compile = result("failed", serde_json::json!([test("runtimeerror", Some("compilation_error"))]));
+        assert_eq!(compile.verdict(), Some(Verdict::CE));
+        let oom = result("failed", serde_json::json!([test("memorylimitexceeded", None)]));
+        assert_eq!(oom.verdict(), Some(Verdict::MLE));
+        // Stored before memory kills had a status of their own
+        let stored_oom = result("failed", serde_json::json!([test("runtimeerror", Some("memory_limit_exceeded"))]));
+        assert_eq!(stored_oom.verdict(), Some(Verdict::MLE));
+
+        assert_eq!(result("failed", serde_json::json!([])).verdict(), Some(Verdict::IE));
+        assert_eq!(result("cancelled", serde_json::json!([test("passed", None)])).verdict(), None);
+        assert_eq!(serde_json::to_string(&Verdict::TLE).unwrap(), "\"TLE\"");
+    }
+
     #[test]
     fn test_language_all_variants() {
         let variants = Language::all_variants();
-        assert_eq!(variants.len(), 3);
+        assert_eq!(variants.len(), 9);
         assert!(variants.contains(&Language::Python));
         assert!(variants.contains(&Language::Java));
         assert!(variants.contains(&Language::Rust));
+        assert!(variants.contains(&Language::Kotlin));
+        assert!(variants.contains(&Language::Scala));
+        assert!(variants.contains(&Language::C));
+        assert!(variants.contains(&Language::Php));
+        assert!(variants.contains(&Language::Ruby));
+        assert!(variants.contains(&Language::Asm));
+        for language in variants {
+            assert_eq!(Language::from_str(&language.to_string()), Some(*language));
+        }
     }
     
     #[test]
@@ -291,8 +1705,55 @@ mod tests {
         
         assert_eq!(Language::from_str("java"), Some(Language::Java));
         assert_eq!(Language::from_str("rust"), Some(Language::Rust));
+        assert_eq!(Language::from_str("Kotlin"), Some(Language::Kotlin));
+        assert_eq!(Language::from_str("scala"), Some(Language::Scala));
+        assert_eq!(Language::from_str("PHP"), Some(Language::Php));
+        assert_eq!(Language::from_str("ruby"), Some(Language::Ruby));
         
         assert_eq!(Language::from_str("javascript"), None);
         assert_eq!(Language::from_str(""), None);
     }
+
+    #[test]
+    fn test_comparison_mode_serialization() {
+        let json = serde_json::to_string(&ComparisonMode::Unordered).unwrap();
+        assert_eq!(json, "\"unordered\"");
+
+        // Omitted comparison deserializes as None (ordered by default)
+        let tc: TestCase = serde_json::from_str(
+            r#"{"id":1,"input":"","expected_output":"x","weight":1}"#
+        ).unwrap();
+        assert_eq!(tc.comparison, None);
+        assert_eq!(tc.comparison.unwrap_or_default(), ComparisonMode::Ordered);
+    }
+
+    #[test]
+    fn test_output_mode_defaults_to_text() {
+        let json = r#"{"id":"550e8400-e29b-41d4-a716-446655440000","language":"python","source_code":"","test_cases":[],"timeout_ms":1000}"#;
+        let job: JobRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(job.output_mode, OutputMode::Text);
+        assert_eq!(serde_json::to_string(&OutputMode::Binary).unwrap(), "\"binary\"");
+    }
+
+    #[test]
+    fn test_progress_event_wire_format() {
+        let job_id = Uuid::nil();
+        let test = TestProgress { test_id: 3, status: TestStatus::Passed, execution_time_ms: 12, total_tests: 5 };
+        let event = JobProgressEvent { job_id, event: ProgressEvent::Test(test) };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["event"], "test");
+        assert_eq!(json["test_id"], 3);
+        assert_eq!(json["job_id"], job_id.to_string());
+
+        let done: JobProgressEvent =
+            serde_json::from_str(r#"{"job_id": "00000000-0000-0000-0000-000000000000", "event": "done", "status": "passed"}"#).unwrap();
+        assert!(matches!(done.event, ProgressEvent::Done { status: JobStatus::Passed }));
+        assert_eq!(serde_json::to_string(&ProgressEvent::Compiling).unwrap(), r#"{"event":"compiling"}"#);
+
+        let output = ProgressEvent::Output(OutputChunk { test_id: 2, stream: OutputStream::Stderr, data: "oops\n".to_string() });
+        assert_eq!(
+            serde_json::to_string(&output).unwrap(),
+            r#"{"event":"output","test_id":2,"stream":"stderr","data":"oops\n"}"#
+        );
+    }
 }
@@ -0,0 +1,479 @@
+// Submission validation - the submission limits, in one place
+//
+// The API checks a submission before queueing it, the client SDK before sending it, and
+// workers check every job they pop: jobs also reach the queues from producers that skip the
+// API (CLI replay, admin requeue, scripts writing to Redis). All of them call
+// `validate_submission` on a `Payload` borrowed from their own request type, so the limits,
+// the order they are checked in and the error codes can't drift apart. Every violation is
+// reported, each with the API's error code for the problem (one of `CODES`).
+//
+// Workers check popped jobs with `validate_job`, which holds them to the job's structure and
+// the engine guardrails only: a job accepted under looser limits stays valid after the limits
+// are tightened.
+
+use crate::limits::{Limits, LimitsReport};
+use crate::types::{
+    EvaluationPipeline, JobRequest, Language, LeaderboardTag, OutputMode, Sanitizer, ScoreScaling, SourceFormat,
+    StatusPolicy, MAX_PRIORITY, MAX_TEST_WEIGHT,
+};
+use base64::{engine::general_purpose, Engine as _};
+use serde::Serialize;
+use std::fmt;
+
+/// Every code a violation can carry
+pub const CODES: &[&str] = &[
+    "NO_TEST_CASES", "TOO_MANY_TEST_CASES", "SOURCE_CODE_TOO_LARGE", "EMPTY_SOURCE_CODE",
+    "INVALID_ENCODING", "TEST_CASE_INPUT_TOO_LARGE", "TEST_CASE_OUTPUT_TOO_LARGE", "INVALID_WEIGHT",
+    "INVALID_EXPECTED_OUTPUT", "INVALID_TEST_CASES", "INVALID_TIMEOUT", "INVALID_EVALUATION",
+    "INVALID_STATUS_POLICY", "INVALID_SCORING", "INVALID_LEADERBOARD", "INVALID_PRIORITY",
+    "INVALID_CHECKER", "INVALID_INTERACTOR", "SANITIZERS_UNSUPPORTED", "NOTEBOOK_UNSUPPORTED",
+    "INVALID_NOTEBOOK",
+];
+
+/// What is wrong with a submission
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Violation {
+    /// The API's error code for it (TOO_MANY_TEST_CASES, INVALID_TIMEOUT, ...)
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl Violation {
+    fn new(code: &'static str, message: impl Into<String>) -> Self {
+        debug_assert!(CODES.contains(&code), "{} is missing from CODES", code);
+        Self { code, message: message.into() }
+    }
+
+    /// Whether the submission is over a size limit (413 rather than 400 at the API)
+    pub fn too_large(&self) -> bool {
+        matches!(self.code, "SOURCE_CODE_TOO_LARGE" | "TEST_CASE_INPUT_TOO_LARGE" | "TEST_CASE_OUTPUT_TOO_LARGE")
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)
+    }
+}
+
+/// Everything wrong with a submission, in the order checked (never empty)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violations(Vec<Violation>);
+
+impl Violations {
+    /// The first violation, which names the submission's error at the API
+    pub fn first(&self) -> &Violation {
+        &self.0[0]
+    }
+
+    pub fn as_slice(&self) -> &[Violation] {
+        &self.0
+    }
+
+    /// Whether the submission is refused for its size (see `Violation::too_large`)
+    pub fn too_large(&self) -> bool {
+        self.first().too_large()
+    }
+}
+
+impl fmt::Display for Violations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, violation) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{}", violation)?;
+        }
+        Ok(())
+    }
+}
+
+/// The parts of a submission the limits apply to
+///
+/// Fields a request type doesn't have keep the defaults of `Payload::new`.
+#[derive(Debug, Clone)]
+pub struct Payload<'a> {
+    pub language: Language,
+    pub source_code: &'a str,
+    pub test_cases: Vec<TestCasePayload<'a>>,
+    pub timeout_ms: u64,
+    pub priority: u8,
+    pub output_mode: OutputMode,
+    pub evaluation: Option<&'a EvaluationPipeline>,
+    pub status_policy: Option<&'a StatusPolicy>,
+    pub scoring: Option<&'a ScoreScaling>,
+    pub leaderboard: Option<&'a LeaderboardTag>,
+    pub checker: Option<&'a str>,
+    pub interactor: Option<&'a str>,
+    pub sanitizers: &'a [Sanitizer],
+    pub source_format: SourceFormat,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TestCasePayload<'a> {
+    /// Set once ids are assigned (queued jobs); requests number their tests in order
+    pub id: Option<u32>,
+    pub input: &'a str,
+    pub expected_output: &'a str,
+    pub weight: u32,
+}
+
+impl<'a> Payload<'a> {
+    pub fn new(language: Language, source_code: &'a str, test_cases: Vec<TestCasePayload<'a>>, timeout_ms: u64) -> Self {
+        Self {
+            language,
+            source_code,
+            test_cases,
+            timeout_ms,
+            priority: 0,
+            output_mode: OutputMode::default(),
+            evaluation: None,
+            status_policy: None,
+            scoring: None,
+            leaderboard: None,
+            checker: None,
+            interactor: None,
+            sanitizers: &[],
+            source_format: SourceFormat::default(),
+        }
+    }
+}
+
+impl<'a> From<&'a JobRequest> for Payload<'a> {
+    fn from(job: &'a JobRequest) -> Self {
+        let test_cases = job
+            .test_cases
+            .iter()
+            .map(|tc| TestCasePayload {
+                id: Some(tc.id),
+                input: &tc.input,
+                expected_output: &tc.expected_output,
+                weight: tc.weight,
+            })
+            .collect();
+        Self {
+            priority: job.metadata.priority,
+            output_mode: job.output_mode,
+            evaluation: job.evaluation.as_ref(),
+            status_policy: job.status_policy.as_ref(),
+            scoring: job.scoring.as_ref(),
+            leaderboard: job.leaderboard.as_ref(),
+            checker: job.checker.as_deref(),
+            interactor: job.interactor.as_deref(),
+            sanitizers: &job.sanitizers,
+            source_format: job.source_format,
+            ..Self::new(job.language, &job.source_code, test_cases, job.timeout_ms)
+        }
+    }
+}
+
+/// Check a submission against the limits; every violation found, in the order checked
+pub fn validate_submission(payload: &Payload, report: &LimitsReport) -> Result<(), Violations> {
+    let limits = &report.limits;
+    let mut found = Vec::new();
+
+    // 1. Test case count
+    if payload.test_cases.is_empty() {
+        found.push(Violation::new("NO_TEST_CASES", "At least one test case is required"));
+    }
+    if payload.test_cases.len() > limits.max_test_cases {
+        found.push(Violation::new(
+            "TOO_MANY_TEST_CASES",
+            format!("Maximum {} test cases allowed, got {}", limits.max_test_cases, payload.test_cases.len()),
+        ));
+    }
+
+    // 2. Source code size, something to run, and text rather than a binary or UTF-16 file
+    if payload.source_code.len() > limits.max_source_code_bytes {
+        found.push(Violation::new(
+            "SOURCE_CODE_TOO_LARGE",
+            format!("Maximum {} bytes allowed, got {} bytes", limits.max_source_code_bytes, payload.source_code.len()),
+        ));
+    } else if payload.source_code.trim().is_empty() {
+        found.push(Violation::new("EMPTY_SOURCE_CODE", "Source code cannot be empty"));
+    }
+    let sources = [("Source code", Some(payload.source_code)), ("Checker", payload.checker), ("Interactor", payload.interactor)];
+    for (name, source) in sources {
+        if source.is_some_and(|source| source.contains('\0')) {
+            found.push(Violation::new(
+                "INVALID_ENCODING",
+                format!("{} must be UTF-8 text; it has NUL characters (a binary or UTF-16 file?)", name),
+            ));
+        }
+    }
+
+    // 3. Test case input/output sizes (the first test over each)
+    if let Some(idx) = payload.test_cases.iter().position(|tc| tc.input.len() > limits.max_stdin_bytes) {
+        found.push(Violation::new(
+            "TEST_CASE_INPUT_TOO_LARGE",
+            format!("Test case {} input exceeds {} bytes", idx + 1, limits.max_stdin_bytes),
+        ));
+    }
+    if let Some(idx) = payload.test_cases.iter().position(|tc| tc.expected_output.len() > limits.max_expected_output_bytes) {
+        found.push(Violation::new(
+            "TEST_CASE_OUTPUT_TOO_LARGE",
+            format!("Test case {} expected output exceeds {} bytes", idx + 1, limits.max_expected_output_bytes),
+        ));
+    }
+
+    // 4. Weights, bounded so the summed max_score can't overflow
+    if let Some(idx) = payload.test_cases.iter().position(|tc| tc.weight > report.max_test_weight) {
+        found.push(Violation::new(
+            "INVALID_WEIGHT",
+            format!("Test case {} weight exceeds {}", idx + 1, report.max_test_weight),
+        ));
+    }
+
+    // 5. Binary output mode compares base64-encoded expected outputs
+    if payload.output_mode == OutputMode::Binary {
+        if let Some(idx) =
+            payload.test_cases.iter().position(|tc| general_purpose::STANDARD.decode(tc.expected_output.trim()).is_err())
+        {
+            found.push(Violation::new(
+                "INVALID_EXPECTED_OUTPUT",
+                format!("Test case {} expected_output must be base64 in binary output mode", idx + 1),
+            ));
+        }
+    }
+
+    // 6. Test ids key results, checkpoints and progress
+    let mut ids: Vec<u32> = payload.test_cases.iter().filter_map(|tc| tc.id).collect();
+    ids.sort_unstable();
+    if let Some(pair) = ids.windows(2).find(|pair| pair[0] == pair[1]) {
+        found.push(Violation::new("INVALID_TEST_CASES", format!("Test case id {} is used twice", pair[0])));
+    }
+
+    // 7. Timeout
+    if payload.timeout_ms < limits.min_timeout_ms || payload.timeout_ms > limits.max_timeout_ms {
+        found.push(Violation::new(
+            "INVALID_TIMEOUT",
+            format!("Timeout must be between {}ms and {}ms", limits.min_timeout_ms, limits.max_timeout_ms),
+        ));
+    }
+
+    // 8. Evaluator pipeline, status policy, score scaling, leaderboard ids (they name Redis keys)
+    if let Some(Err(message)) = payload.evaluation.map(EvaluationPipeline::validate) {
+        found.push(Violation::new("INVALID_EVALUATION", message));
+    }
+    if let Some(Err(message)) = payload.status_policy.map(StatusPolicy::validate) {
+        found.push(Violation::new("INVALID_STATUS_POLICY", message));
+    }
+    if let Some(Err(message)) = payload.scoring.map(ScoreScaling::validate) {
+        found.push(Violation::new("INVALID_SCORING", message));
+    }
+    if let Some(Err(message)) = payload.leaderboard.map(LeaderboardTag::validate) {
+        found.push(Violation::new("INVALID_LEADERBOARD", message));
+    }
+
+    // 9. Priority
+    if payload.priority > report.max_priority {
+        found.push(Violation::new("INVALID_PRIORITY", format!("Priority must be between 0 and {}", report.max_priority)));
+    }
+
+    // 10. Checker and interactor sources: same size limit as the submission, and not both
+    if let Some(checker) = payload.checker {
+        if checker.trim().is_empty() || checker.len() > limits.max_source_code_bytes {
+            found.push(Violation::new(
+                "INVALID_CHECKER",
+                format!("Checker must be non-empty and at most {} bytes", limits.max_source_code_bytes),
+            ));
+        }
+    }
+    if let Some(interactor) = payload.interactor {
+        if interactor.trim().is_empty() || interactor.len() > limits.max_source_code_bytes {
+            found.push(Violation::new(
+                "INVALID_INTERACTOR",
+                format!("Interactor must be non-empty and at most {} bytes", limits.max_source_code_bytes),
+            ));
+        }
+        if payload.checker.is_some() {
+            found.push(Violation::new("INVALID_INTERACTOR", "A job can have a checker or an interactor, not both"));
+        }
+    }
+
+    // 11. Sanitizers are gcc instrumentation
+    if !payload.sanitizers.is_empty() && payload.language != Language::C {
+        found.push(Violation::new(
+            "SANITIZERS_UNSUPPORTED",
+            format!("Sanitizers are only available for C, not {}", payload.language),
+        ));
+    }
+
+    // 12. Notebooks: Python only, and something the worker can extract code cells from
+    if payload.source_format == SourceFormat::Notebook {
+        if payload.language != Language::Python {
+            found.push(Violation::new(
+                "NOTEBOOK_UNSUPPORTED",
+                format!("Notebook submissions are only available for Python, not {}", payload.language),
+            ));
+        } else if !has_code_cells(payload.source_code) {
+            found.push(Violation::new(
+                "INVALID_NOTEBOOK",
+                "source_code must be a Jupyter notebook (.ipynb JSON) with at least one code cell",
+            ));
+        }
+    }
+
+    if found.is_empty() {
+        Ok(())
+    } else {
+        Err(Violations(found))
+    }
+}
+
+/// Check a queued job: its structure, and sizes against the engine guardrails
+///
+/// The submission limits (test count, sizes, timeout range) were checked when the job was
+/// accepted and may have changed since, so they don't apply here.
+pub fn validate_job(job: &JobRequest, limits: &Limits) -> Result<(), Violations> {
+    let structural = LimitsReport {
+        limits: Limits {
+            max_test_cases: usize::MAX,
+            max_source_code_bytes: limits.engine_max_source_code_bytes,
+            max_stdin_bytes: limits.engine_max_test_input_bytes,
+            max_expected_output_bytes: usize::MAX,
+            min_timeout_ms: 1,
+            max_timeout_ms: u64::MAX,
+            ..limits.clone()
+        },
+        max_test_weight: MAX_TEST_WEIGHT,
+        max_priority: MAX_PRIORITY,
+    };
+    validate_submission(&Payload::from(job), &structural)
+}
+
+/// Whether a notebook document has a code cell (the nbformat 4 layout)
+fn has_code_cells(source_code: &str) -> bool {
+    let Ok(notebook) = serde_json::from_str::<serde_json::Value>(source_code) else {
+        return false;
+    };
+    notebook["cells"]
+        .as_array()
+        .is_some_and(|cells| cells.iter().any(|cell| cell["cell_type"] == "code"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::limits;
+    use crate::types::TestCase;
+    use uuid::Uuid;
+
+    fn job() -> JobRequest {
+        JobRequest {
+            id: Uuid::new_v4(),
+            language: Language::Python,
+            source_code: "print(input())".to_string(),
+            test_cases: (1..=3)
+                .map(|id| TestCase { id, input: "1".to_string(), expected_output: "1".to_string(), weight: 10, ..Default::default() })
+                .collect(),
+            timeout_ms: 5000,
+            ..Default::default()
+        }
+    }
+
+    /// A way to break a job, and the code it should be refused with
+    type Case = (fn(&mut JobRequest), &'static str);
+
+    fn code(job: &JobRequest) -> Option<&'static str> {
+        validate_submission(&Payload::from(job), &limits::report()).err().map(|violations| violations.first().code)
+    }
+
+    #[test]
+    fn test_valid_job_passes() {
+        assert_eq!(validate_submission(&Payload::from(&job()), &limits::report()), Ok(()));
+        assert_eq!(validate_job(&job(), &Limits::default()), Ok(()));
+    }
+
+    #[test]
+    fn test_violations_carry_api_codes() {
+        let cases: Vec<Case> = vec![
+            (|j| j.test_cases.clear(), "NO_TEST_CASES"),
+            (|j| j.source_code = " \n".to_string(), "EMPTY_SOURCE_CODE"),
+            (|j| j.source_code = "p\0r\0i\0n\0t\0".to_string(), "INVALID_ENCODING"),
+            (|j| j.source_code = "x".repeat(Limits::default().max_source_code_bytes + 1), "SOURCE_CODE_TOO_LARGE"),
+            (|j| j.test_cases[1].input = "x".repeat(Limits::default().max_stdin_bytes + 1), "TEST_CASE_INPUT_TOO_LARGE"),
+            (|j| j.test_cases[2].weight = MAX_TEST_WEIGHT + 1, "INVALID_WEIGHT"),
+            (|j| j.test_cases[2].id = 1, "INVALID_TEST_CASES"),
+            (|j| j.timeout_ms = 0, "INVALID_TIMEOUT"),
+            (|j| j.metadata.priority = MAX_PRIORITY + 1, "INVALID_PRIORITY"),
+            (|j| j.sanitizers = vec![crate::types::Sanitizer::Address], "SANITIZERS_UNSUPPORTED"),
+            (|j| j.output_mode = OutputMode::Binary, "INVALID_EXPECTED_OUTPUT"),
+            (|j| j.source_format = SourceFormat::Notebook, "INVALID_NOTEBOOK"),
+            (
+                |j| {
+                    j.checker = Some("x".to_string());
+                    j.interactor = Some("y".to_string());
+                },
+                "INVALID_INTERACTOR",
+            ),
+        ];
+        for (break_job, expected) in cases {
+            let mut job = job();
+            break_job(&mut job);
+            assert_eq!(code(&job), Some(expected));
+        }
+
+        let tight = LimitsReport { limits: Limits { max_test_cases: 2, ..Limits::default() }, ..limits::report() };
+        let violations = validate_submission(&Payload::from(&job()), &tight).unwrap_err();
+        assert_eq!(violations.to_string(), "TOO_MANY_TEST_CASES: Maximum 2 test cases allowed, got 3");
+        assert!(!violations.too_large());
+    }
+
+    #[test]
+    fn test_every_violation_is_reported() {
+        let mut job = job();
+        job.timeout_ms = 0;
+        job.test_cases[0].weight = MAX_TEST_WEIGHT + 1;
+        job.checker = Some("\0".to_string());
+        let violations = validate_submission(&Payload::from(&job), &limits::report()).unwrap_err();
+        let codes: Vec<_> = violations.as_slice().iter().map(|v| v.code).collect();
+        assert_eq!(codes, ["INVALID_ENCODING", "INVALID_WEIGHT", "INVALID_TIMEOUT"]);
+        assert!(violations.to_string().starts_with("INVALID_ENCODING: Checker must be UTF-8 text"));
+    }
+
+    #[test]
+    fn test_queued_jobs_keep_the_limits_they_were_accepted_under() {
+        // Limits tightened after a job was queued don't apply to it
+        let tight = Limits { max_test_cases: 2, max_source_code_bytes: 4, max_timeout_ms: 1000, ..Limits::default() };
+        assert_eq!(validate_job(&job(), &tight), Ok(()));
+
+        // The engine guardrails and the job's structure still do
+        let mut oversized = job();
+        oversized.source_code = "x".repeat(tight.engine_max_source_code_bytes + 1);
+        assert_eq!(validate_job(&oversized, &tight).unwrap_err().first().code, "SOURCE_CODE_TOO_LARGE");
+        let mut duplicated = job();
+        duplicated.test_cases[2].id = 1;
+        assert_eq!(validate_job(&duplicated, &tight).unwrap_err().first().code, "INVALID_TEST_CASES");
+    }
+
+    #[test]
+    fn test_requests_without_ids_are_numbered_in_order() {
+        // A request's tests have no ids yet, so none can repeat; sizes are still checked
+        let payload = Payload::new(
+            Language::Python,
+            "print(1)",
+            vec![TestCasePayload { id: None, input: "", expected_output: "1", weight: 10 }; 2],
+            5000,
+        );
+        assert_eq!(validate_submission(&payload, &limits::report()), Ok(()));
+        let oversized = "x".repeat(Limits::default().max_stdin_bytes + 1);
+        let payload = Payload {
+            test_cases: vec![TestCasePayload { id: None, input: &oversized, expected_output: "", weight: 10 }],
+            ..payload
+        };
+        let violations = validate_submission(&payload, &limits::report()).unwrap_err();
+        assert_eq!(violations.first().code, "TEST_CASE_INPUT_TOO_LARGE");
+        assert!(violations.too_large());
+    }
+
+    #[test]
+    fn test_notebook_needs_code_cells() {
+        let notebook = r##"{"cells": [{"cell_type": "markdown", "source": ["# Task"]},
+            {"cell_type": "code", "source": ["n = int(input())\n", "print(n * 2)"]}], "nbformat": 4}"##;
+        assert!(has_code_cells(notebook));
+        assert!(!has_code_cells(r#"{"cells": [{"cell_type": "markdown", "source": "x"}]}"#));
+        assert!(!has_code_cells(r#"{"cells": {}}"#));
+        assert!(!has_code_cells("print(1)"));
+    }
+}
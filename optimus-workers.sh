@@ -44,9 +44,39 @@ get_config() {
             LANG_IMAGE="optimus-rust:1.75-slim"
             PORT_BASE=8100
             ;;
+        kotlin)
+            LANG_QUEUE="optimus:queue:kotlin"
+            LANG_IMAGE="optimus-kotlin:17"
+            PORT_BASE=8110
+            ;;
+        scala)
+            LANG_QUEUE="optimus:queue:scala"
+            LANG_IMAGE="optimus-scala:17"
+            PORT_BASE=8120
+            ;;
+        c)
+            LANG_QUEUE="optimus:queue:c"
+            LANG_IMAGE="optimus-c:13"
+            PORT_BASE=8130
+            ;;
+        php)
+            LANG_QUEUE="optimus:queue:php"
+            LANG_IMAGE="optimus-php:8.3"
+            PORT_BASE=8140
+            ;;
+        ruby)
+            LANG_QUEUE="optimus:queue:ruby"
+            LANG_IMAGE="optimus-ruby:3.3"
+            PORT_BASE=8150
+            ;;
+        asm)
+            LANG_QUEUE="optimus:queue:asm"
+            LANG_IMAGE="optimus-asm:bookworm"
+            PORT_BASE=8160
+            ;;
         *)
             echo -e "${RED}Error: Unknown language '$lang'${NC}"
-            echo "Supported: python, java, rust"
+            echo "Supported: python, java, rust, kotlin, scala, c, php, ruby, asm"
             return 1
             ;;
     esac
@@ -205,7 +235,7 @@ cmd_stop() {
     
     if [ "$lang" = "--all" ] || [ -z "$lang" ]; then
         echo -e "${CYAN}Stopping all workers...${NC}"
-        for l in python java rust; do
+        for l in python java rust kotlin scala c php ruby asm; do
             cmd_stop "$l"
         done
         return
@@ -293,7 +323,7 @@ cmd_status() {
     
     local total=0
     
-    for lang in python java rust; do
+    for lang in python java rust kotlin scala c php ruby asm; do
         get_config "$lang"
         local found=0
         
@@ -364,7 +394,7 @@ usage() {
     echo "  status                         Show status of all workers"
     echo "  logs <language> [instance]     Tail logs for a worker"
     echo ""
-    echo -e "${BOLD}LANGUAGES:${NC}  python (8081+), java (8091+), rust (8101+)"
+    echo -e "${BOLD}LANGUAGES:${NC}  python (8081+), java (8091+), rust (8101+), kotlin (8111+), scala (8121+), c (8131+), php (8141+), ruby (8151+), asm (8161+)"
     echo ""
     echo -e "${BOLD}EXAMPLES:${NC}"
     echo "  $0 start python 3              # Start 3 Python workers"
@@ -26,6 +26,12 @@ usage() {
     echo "  python    Start Python worker"
     echo "  java      Start Java worker"
     echo "  rust      Start Rust worker"
+    echo "  kotlin    Start Kotlin worker"
+    echo "  scala     Start Scala worker"
+    echo "  c         Start C worker"
+    echo "  php       Start PHP worker"
+    echo "  ruby      Start Ruby worker"
+    echo "  asm       Start x86-64 assembly worker"
     echo ""
     echo "Options:"
     echo "  --all     Start workers for ALL configured languages (in background)"
@@ -56,9 +62,39 @@ get_language_config() {
             export OPTIMUS_QUEUE="optimus:queue:rust"
             export OPTIMUS_IMAGE="optimus-rust:1.75-slim"
             ;;
+        kotlin)
+            export OPTIMUS_LANGUAGE="kotlin"
+            export OPTIMUS_QUEUE="optimus:queue:kotlin"
+            export OPTIMUS_IMAGE="optimus-kotlin:17"
+            ;;
+        scala)
+            export OPTIMUS_LANGUAGE="scala"
+            export OPTIMUS_QUEUE="optimus:queue:scala"
+            export OPTIMUS_IMAGE="optimus-scala:17"
+            ;;
+        c)
+            export OPTIMUS_LANGUAGE="c"
+            export OPTIMUS_QUEUE="optimus:queue:c"
+            export OPTIMUS_IMAGE="optimus-c:13"
+            ;;
+        php)
+            export OPTIMUS_LANGUAGE="php"
+            export OPTIMUS_QUEUE="optimus:queue:php"
+            export OPTIMUS_IMAGE="optimus-php:8.3"
+            ;;
+        ruby)
+            export OPTIMUS_LANGUAGE="ruby"
+            export OPTIMUS_QUEUE="optimus:queue:ruby"
+            export OPTIMUS_IMAGE="optimus-ruby:3.3"
+            ;;
+        asm)
+            export OPTIMUS_LANGUAGE="asm"
+            export OPTIMUS_QUEUE="optimus:queue:asm"
+            export OPTIMUS_IMAGE="optimus-asm:bookworm"
+            ;;
         *)
             echo -e "${RED}Error: Unknown language '$lang'${NC}"
-            echo "Supported languages: python, java, rust"
+            echo "Supported languages: python, java, rust, kotlin, scala, c, php, ruby, asm"
             exit 1
             ;;
     esac
@@ -92,7 +128,7 @@ start_all_workers() {
     echo -e "${CYAN}╚═══════════════════════════════════════════════════════════════╝${NC}"
     echo ""
     
-    for lang in python java rust; do
+    for lang in python java rust kotlin scala c php ruby asm; do
         start_worker "$lang" "true"
         sleep 1  # Brief delay between worker starts
     done
@@ -125,7 +161,7 @@ case $1 in
     --help|-h)
         usage
         ;;
-    python|java|rust)
+    python|java|rust|kotlin|scala|c|php|ruby|asm)
         start_worker "$1" "false"
         ;;
     *)
@@ -116,6 +116,17 @@ run_test "Python" "❌ Timeout (Infinite Loop)" '{
   "timeout_ms": 2000
 }'
 
+run_test "Python" "✅ Success (Jupyter notebook)" '{
+  "language": "python",
+  "source_format": "notebook",
+  "source_code": "{\"cells\": [{\"cell_type\": \"markdown\", \"metadata\": {}, \"source\": [\"# Double it\"]}, {\"cell_type\": \"code\", \"metadata\": {}, \"execution_count\": 1, \"outputs\": [], \"source\": [\"%matplotlib inline\\n\", \"def double(n):\\n\", \"    return n * 2\"]}, {\"cell_type\": \"code\", \"metadata\": {}, \"execution_count\": 2, \"outputs\": [], \"source\": [\"print(double(int(input())))\"]}], \"metadata\": {\"kernelspec\": {\"name\": \"python3\", \"language\": \"python\", \"display_name\": \"Python 3\"}}, \"nbformat\": 4, \"nbformat_minor\": 5}",
+  "test_cases": [
+    {"id": 1, "input": "21", "expected_output": "42", "weight": 10},
+    {"id": 2, "input": "5", "expected_output": "10", "weight": 10}
+  ],
+  "timeout_ms": 5000
+}'
+
 # ==================== JAVA TESTS ====================
 echo -e "${GREEN}========== JAVA TESTS ==========${NC}"
 echo ""
@@ -194,6 +205,157 @@ run_test "Rust" "❌ Timeout (Infinite Loop)" '{
   "timeout_ms": 3000
 }'
 
+# ==================== KOTLIN TESTS ====================
+echo -e "${GREEN}========== KOTLIN TESTS ==========${NC}"
+echo ""
+
+run_test "Kotlin" "✅ Success" '{
+  "language": "kotlin",
+  "source_code": "fun main() {\n  println(readLine()!!)\n}",
+  "test_cases": [{"id": 1, "input": "hello", "expected_output": "hello", "weight": 10}],
+  "timeout_ms": 10000
+}'
+
+run_test "Kotlin" "❌ Compilation Error" '{
+  "language": "kotlin",
+  "source_code": "fun main() {\n  println(\"hello\"\n}",
+  "test_cases": [{"id": 1, "input": "", "expected_output": "hello", "weight": 10}],
+  "timeout_ms": 10000
+}'
+
+run_test "Kotlin" "❌ Runtime Error (NullPointerException)" '{
+  "language": "kotlin",
+  "source_code": "fun main() {\n  val s: String? = null\n  println(s!!.length)\n}",
+  "test_cases": [{"id": 1, "input": "", "expected_output": "", "weight": 10}],
+  "timeout_ms": 10000
+}'
+
+run_test "Kotlin" "❌ Timeout (Infinite Loop)" '{
+  "language": "kotlin",
+  "source_code": "fun main() {\n  while (true) {}\n}",
+  "test_cases": [{"id": 1, "input": "", "expected_output": "", "weight": 10}],
+  "timeout_ms": 3000
+}'
+
+# ==================== SCALA TESTS ====================
+echo -e "${GREEN}========== SCALA TESTS ==========${NC}"
+echo ""
+
+run_test "Scala" "✅ Success" '{
+  "language": "scala",
+  "source_code": "object Main {\n  def main(args: Array[String]): Unit = println(scala.io.StdIn.readLine())\n}",
+  "test_cases": [{"id": 1, "input": "hello", "expected_output": "hello", "weight": 10}],
+  "timeout_ms": 10000
+}'
+
+run_test "Scala" "❌ Compilation Error" '{
+  "language": "scala",
+  "source_code": "object Main {\n  def main(args: Array[String]): Unit = println(\"hello\"\n}",
+  "test_cases": [{"id": 1, "input": "", "expected_output": "hello", "weight": 10}],
+  "timeout_ms": 10000
+}'
+
+run_test "Scala" "❌ Wrong Answer" '{
+  "language": "scala",
+  "source_code": "object Main {\n  def main(args: Array[String]): Unit = println(\"wrong\")\n}",
+  "test_cases": [{"id": 1, "input": "hello", "expected_output": "hello", "weight": 10}],
+  "timeout_ms": 10000
+}'
+
+run_test "Scala" "❌ Timeout (Infinite Loop)" '{
+  "language": "scala",
+  "source_code": "object Main {\n  def main(args: Array[String]): Unit = while (true) {}\n}",
+  "test_cases": [{"id": 1, "input": "", "expected_output": "", "weight": 10}],
+  "timeout_ms": 3000
+}'
+
+# ==================== C TESTS ====================
+echo -e "${GREEN}========== C TESTS ==========${NC}"
+echo ""
+
+run_test "C" "✅ Success" '{
+  "language": "c",
+  "source_code": "#include <stdio.h>\nint main(void) {\n  char s[64];\n  scanf(\"%63s\", s);\n  printf(\"%s\\n\", s);\n  return 0;\n}",
+  "test_cases": [{"id": 1, "input": "hello", "expected_output": "hello", "weight": 10}],
+  "timeout_ms": 5000
+}'
+
+run_test "C" "❌ Runtime Error (AddressSanitizer)" '{
+  "language": "c",
+  "source_code": "#include <stdio.h>\n#include <stdlib.h>\nint main(void) {\n  int *a = malloc(4 * sizeof(int));\n  a[4] = 1;\n  printf(\"%d\\n\", a[4]);\n  return 0;\n}",
+  "test_cases": [{"id": 1, "input": "", "expected_output": "1", "weight": 10}],
+  "sanitizers": ["address", "undefined"],
+  "timeout_ms": 5000
+}'
+
+run_test "C" "❌ Runtime Error (UndefinedBehaviorSanitizer)" '{
+  "language": "c",
+  "source_code": "#include <stdio.h>\n#include <limits.h>\nint main(void) {\n  int x;\n  scanf(\"%d\", &x);\n  printf(\"%d\\n\", x + INT_MAX);\n  return 0;\n}",
+  "test_cases": [{"id": 1, "input": "1", "expected_output": "-2147483648", "weight": 10}],
+  "sanitizers": ["undefined"],
+  "timeout_ms": 5000
+}'
+
+# ==================== PHP TESTS ====================
+echo -e "${GREEN}========== PHP TESTS ==========${NC}"
+echo ""
+
+run_test "PHP" "✅ Success" '{
+  "language": "php",
+  "source_code": "<?php\n$n = (int) trim(fgets(STDIN));\necho $n * 2, \"\\n\";\n",
+  "test_cases": [
+    {"id": 1, "input": "21", "expected_output": "42", "weight": 10},
+    {"id": 2, "input": "5", "expected_output": "10", "weight": 10}
+  ],
+  "timeout_ms": 5000
+}'
+
+run_test "PHP" "❌ Runtime Error" '{
+  "language": "php",
+  "source_code": "<?php\nthrow new Exception(\"boom\");\n",
+  "test_cases": [{"id": 1, "input": "", "expected_output": "", "weight": 10}],
+  "timeout_ms": 5000
+}'
+
+# ==================== RUBY TESTS ====================
+echo -e "${GREEN}========== RUBY TESTS ==========${NC}"
+echo ""
+
+run_test "Ruby" "✅ Success" '{
+  "language": "ruby",
+  "source_code": "n = gets.to_i\nputs n * 2\n",
+  "test_cases": [
+    {"id": 1, "input": "21", "expected_output": "42", "weight": 10},
+    {"id": 2, "input": "5", "expected_output": "10", "weight": 10}
+  ],
+  "timeout_ms": 5000
+}'
+
+run_test "Ruby" "❌ Runtime Error" '{
+  "language": "ruby",
+  "source_code": "raise \"boom\"\n",
+  "test_cases": [{"id": 1, "input": "", "expected_output": "", "weight": 10}],
+  "timeout_ms": 5000
+}'
+
+# ==================== ASSEMBLY TESTS ====================
+echo -e "${GREEN}========== ASSEMBLY TESTS ==========${NC}"
+echo ""
+
+run_test "Assembly" "✅ Success (echo stdin)" '{
+  "language": "asm",
+  "source_code": "section .bss\nbuf resb 4096\nsection .text\nglobal _start\n_start:\n    mov rax, 0\n    mov rdi, 0\n    mov rsi, buf\n    mov rdx, 4096\n    syscall\n    mov rdx, rax\n    mov rax, 1\n    mov rdi, 1\n    mov rsi, buf\n    syscall\n    mov rax, 60\n    xor rdi, rdi\n    syscall\n",
+  "test_cases": [{"id": 1, "input": "hello", "expected_output": "hello", "weight": 10}],
+  "timeout_ms": 2000
+}'
+
+run_test "Assembly" "❌ Compilation Error" '{
+  "language": "asm",
+  "source_code": "section .text\nglobal _start\n_start:\n    mov rax,\n",
+  "test_cases": [{"id": 1, "input": "", "expected_output": "", "weight": 10}],
+  "timeout_ms": 2000
+}'
+
 echo "=========================================="
 echo "    TEST SUITE COMPLETE"
 echo "=========================================="